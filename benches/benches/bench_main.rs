@@ -7,4 +7,6 @@ criterion_main! {
     benchmarks::secp_2in2out::process_block,
     benchmarks::next_epoch_ext::next_epoch_ext,
     benchmarks::overall::overall,
+    benchmarks::tx_pool::tx_pool,
+    benchmarks::compact_block::compact_block,
 }
@@ -0,0 +1,67 @@
+use crate::benchmarks::overall::{gen_txs_from_block, setup_chain};
+use ckb_store::ChainStore;
+use ckb_sync::{ReconstructionResult, Relayer, SyncShared};
+use ckb_types::{packed, prelude::*};
+use criterion::{criterion_group, Criterion};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+#[cfg(not(feature = "ci"))]
+const SIZES: &[usize] = &[100usize, 500];
+
+#[cfg(feature = "ci")]
+const SIZES: &[usize] = &[2usize];
+
+fn bench(c: &mut Criterion) {
+    // reconstructs a compact block whose transactions are all in the tx-pool
+    c.bench_function_over_inputs(
+        "compact_block reconstruct",
+        |b, txs_size| {
+            let (shared, chain) = setup_chain(**txs_size);
+            let txs = {
+                let snapshot = shared.snapshot();
+                let genesis = snapshot
+                    .get_block(&shared.genesis_hash())
+                    .expect("genesis exist");
+                gen_txs_from_block(&genesis)
+            };
+            let tx_pool = shared.tx_pool_controller();
+            tx_pool
+                .submit_txs(txs.clone())
+                .unwrap()
+                .expect("submit_txs");
+            let block_template = tx_pool
+                .get_block_template(None, None, None)
+                .unwrap()
+                .expect("get_block_template");
+            let block = packed::Block::from(block_template)
+                .into_view()
+                .as_advanced_builder()
+                .transactions(txs)
+                .build();
+            let compact_block = packed::CompactBlock::build_from_block(&block, &HashSet::new());
+
+            let sync_shared = Arc::new(SyncShared::new(shared));
+            let relayer = Relayer::new(chain, Arc::clone(&sync_shared));
+            b.iter(|| {
+                match relayer.reconstruct_block(
+                    &sync_shared.active_chain(),
+                    &compact_block,
+                    Vec::new(),
+                    &[],
+                    &[],
+                ) {
+                    ReconstructionResult::Block(_) => {}
+                    result => panic!("reconstruct block failed: {:?}", result),
+                }
+            })
+        },
+        SIZES,
+    );
+}
+
+criterion_group!(
+    name = compact_block;
+    config = Criterion::default().sample_size(10);
+    targets = bench
+);
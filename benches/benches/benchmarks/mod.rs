@@ -1,5 +1,7 @@
 pub mod always_success;
+pub mod compact_block;
 pub mod next_epoch_ext;
 pub mod overall;
 pub mod secp_2in2out;
+pub mod tx_pool;
 pub mod util;
@@ -0,0 +1,46 @@
+use crate::benchmarks::overall::{gen_txs_from_block, setup_chain};
+use ckb_store::ChainStore;
+use criterion::{criterion_group, Criterion};
+
+#[cfg(not(feature = "ci"))]
+const SIZES: &[usize] = &[100usize, 500];
+
+#[cfg(feature = "ci")]
+const SIZES: &[usize] = &[2usize];
+
+fn bench(c: &mut Criterion) {
+    // submits the transactions spending the genesis cells, each of them is verified by the
+    // secp lock script
+    c.bench_function_over_inputs(
+        "tx_pool submit_txs",
+        |b, txs_size| {
+            b.iter_with_setup(
+                || {
+                    let (shared, chain) = setup_chain(**txs_size);
+                    let txs = {
+                        let snapshot = shared.snapshot();
+                        let genesis = snapshot
+                            .get_block(&shared.genesis_hash())
+                            .expect("genesis exist");
+                        gen_txs_from_block(&genesis)
+                    };
+                    (shared, chain, txs)
+                },
+                |(shared, _chain, txs)| {
+                    shared
+                        .tx_pool_controller()
+                        .submit_txs(txs)
+                        .unwrap()
+                        .expect("submit_txs");
+                },
+            )
+        },
+        SIZES,
+    );
+}
+
+criterion_group!(
+    name = tx_pool;
+    config = Criterion::default().sample_size(10);
+    targets = bench
+);
@@ -0,0 +1,205 @@
+//! Verifies the scripts of the blocks which the assume-valid sync skips.
+use crate::chain::ChainController;
+use ckb_chain_spec::consensus::Consensus;
+use ckb_error::{Error, InternalErrorKind};
+use ckb_logger::{debug, error, info};
+use ckb_shared::shared::Shared;
+use ckb_stop_handler::{SignalSender, StopHandler};
+use ckb_store::ChainStore;
+use ckb_types::{
+    core::{
+        cell::{resolve_transaction, CellProvider, CellStatus, HeaderChecker, ResolvedTransaction},
+        BlockNumber, BlockView, Cycle,
+    },
+    packed::OutPoint,
+    prelude::*,
+};
+use ckb_verification::{BlockErrorKind, ScriptVerifier};
+use crossbeam_channel::{bounded, RecvTimeoutError};
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+use std::{cmp, thread};
+
+// The progress is saved after each batch
+const BACKFILL_BATCH_SIZE: BlockNumber = 100;
+// Check for the blocks to verify at this interval when there is none
+const BACKFILL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Verifies the scripts of the main chain blocks in `get_unverified_scripts_range` once the main
+/// chain grows past them, that is the assume-valid sync has reached its target. A block whose
+/// scripts fail is invalidated, so the main chain is rewound to its parent. A block which can't
+/// be verified, e.g. its body is pruned, stops the backfill there, it is never trusted silently.
+pub struct ScriptsBackfill {
+    shared: Shared,
+    chain: ChainController,
+}
+
+impl ScriptsBackfill {
+    pub fn new(shared: Shared, chain: ChainController) -> Self {
+        ScriptsBackfill { shared, chain }
+    }
+
+    pub fn start(self) -> StopHandler<()> {
+        let (signal_sender, signal_receiver) = bounded::<()>(1);
+        let thread = thread::Builder::new()
+            .name("ScriptsBackfill".to_string())
+            .spawn(move || loop {
+                let timeout = match self.backfill_batch() {
+                    Ok(true) => Duration::from_secs(0),
+                    Ok(false) => BACKFILL_INTERVAL,
+                    Err(err) => {
+                        error!("scripts backfill error {}", err);
+                        BACKFILL_INTERVAL
+                    }
+                };
+                match signal_receiver.recv_timeout(timeout) {
+                    Err(RecvTimeoutError::Timeout) => {}
+                    _ => break,
+                }
+            })
+            .expect("Start ScriptsBackfill failed");
+        StopHandler::new(SignalSender::Crossbeam(signal_sender), thread)
+    }
+
+    /// Verifies a batch of the blocks, returns whether there are more to verify. The range stays
+    /// from the first block which can't be verified, and an error is returned for it.
+    pub fn backfill_batch(&self) -> Result<bool, Error> {
+        let snapshot = Arc::clone(&self.shared.snapshot());
+        let (start, end) = match snapshot.get_unverified_scripts_range() {
+            Some(range) => range,
+            None => return Ok(false),
+        };
+        // the sync may be still skipping the scripts
+        if snapshot.tip_number() <= end {
+            return Ok(false);
+        }
+
+        let batch_end = cmp::min(end, start.saturating_add(BACKFILL_BATCH_SIZE - 1));
+        for number in start..=batch_end {
+            let block = match snapshot
+                .get_block_hash(number)
+                .and_then(|hash| snapshot.get_block(&hash))
+            {
+                Some(block) => block,
+                None => {
+                    self.save_progress(Some((number, end)))?;
+                    return Err(InternalErrorKind::Database
+                        .reason(format!(
+                            "the body of the block {} assumed valid is missing or pruned",
+                            number
+                        ))
+                        .into());
+                }
+            };
+            // the spent cells are read from the block bodies, which stay until the range is
+            // verified, see `ChainDB::prune_blocks`
+            let resolved = match resolve_block(&*snapshot, &block) {
+                Ok(resolved) => resolved,
+                Err(err) => {
+                    self.save_progress(Some((number, end)))?;
+                    return Err(InternalErrorKind::Database
+                        .reason(format!(
+                            "resolve the block {} {} assumed valid: {}",
+                            number,
+                            block.hash(),
+                            err
+                        ))
+                        .into());
+                }
+            };
+            if let Err(err) =
+                verify_block_scripts(&*snapshot, snapshot.consensus(), &block, &resolved)
+            {
+                error!(
+                    "the scripts of the block {} {} assumed valid fail: {}, invalidate it",
+                    number,
+                    block.hash(),
+                    err
+                );
+                self.chain.invalidate_block(block.hash())?;
+                // the blocks after it are detached from the main chain
+                self.save_progress(None)?;
+                return Ok(false);
+            }
+        }
+
+        if batch_end < end {
+            self.save_progress(Some((batch_end + 1, end)))?;
+            debug!("verified the scripts of the blocks up to {}", batch_end);
+            Ok(true)
+        } else {
+            self.save_progress(None)?;
+            info!(
+                "verified the scripts of the blocks assumed valid up to {}",
+                end
+            );
+            Ok(false)
+        }
+    }
+
+    fn save_progress(&self, range: Option<(BlockNumber, BlockNumber)>) -> Result<(), Error> {
+        let txn = self.shared.store().begin_transaction();
+        match range {
+            Some((start, end)) => txn.insert_unverified_scripts_range(start, end)?,
+            None => txn.delete_unverified_scripts_range()?,
+        }
+        txn.commit()
+    }
+}
+// Resolves the cells whether they are spent or not, the block was verified against the live cells
+// when it was attached
+struct HistoricalCellProvider<'a, S>(&'a S);
+
+impl<'a, S: ChainStore<'a>> CellProvider for HistoricalCellProvider<'a, S> {
+    fn cell(&self, out_point: &OutPoint, with_data: bool) -> CellStatus {
+        let tx_hash = out_point.tx_hash();
+        let index = out_point.index().unpack();
+        match self.0.get_cell_meta(&tx_hash, index) {
+            Some(mut cell_meta) => {
+                if with_data {
+                    cell_meta.mem_cell_data = self.0.get_cell_data(&tx_hash, index);
+                }
+                CellStatus::live_cell(cell_meta)
+            }
+            None => CellStatus::Unknown,
+        }
+    }
+}
+
+fn resolve_block<'a, S: ChainStore<'a> + HeaderChecker>(
+    store: &'a S,
+    block: &BlockView,
+) -> Result<Vec<ResolvedTransaction>, Error> {
+    let cell_provider = HistoricalCellProvider(store);
+    let mut seen_inputs = HashSet::new();
+    block
+        .transactions()
+        .into_iter()
+        .skip(1)
+        .map(|tx| resolve_transaction(tx, &mut seen_inputs, &cell_provider, store))
+        .collect()
+}
+
+fn verify_block_scripts<'a, S: ChainStore<'a>>(
+    store: &'a S,
+    consensus: &Consensus,
+    block: &BlockView,
+    resolved: &[ResolvedTransaction],
+) -> Result<Cycle, Error> {
+    let vm_version = consensus.vm_version(block.epoch().number());
+    let max_block_cycles = consensus.max_block_cycles();
+    let mut cycles: Cycle = 0;
+    for rtx in resolved {
+        let tx_cycles = ScriptVerifier::new(rtx, store)
+            .vm_version(vm_version)
+            .verify(max_block_cycles)?;
+        cycles = cycles
+            .checked_add(tx_cycles)
+            .ok_or(BlockErrorKind::ExceededMaximumCycles)?;
+    }
+    if cycles > max_block_cycles {
+        return Err(BlockErrorKind::ExceededMaximumCycles.into());
+    }
+    Ok(cycles)
+}
@@ -1,8 +1,12 @@
 use crate::cell::{attach_block_cell, detach_block_cell};
+use crate::metrics::{
+    PROCESSED_BLOCKS, PROCESS_BLOCK_DURATION, PROCESS_BLOCK_PHASE_DURATION, REORG_DEPTH, TIP_NUMBER,
+};
 use crate::switch::Switch;
 use ckb_error::{Error, InternalErrorKind};
 use ckb_logger::{self, debug, error, info, log_enabled, metric, trace, warn};
-use ckb_proposal_table::ProposalTable;
+use ckb_notify::ReorgEvent;
+use ckb_proposal_table::{ProposalTable, ProposalView};
 use ckb_shared::shared::Shared;
 use ckb_stop_handler::{SignalSender, StopHandler};
 use ckb_store::{ChainStore, StoreTransaction};
@@ -10,24 +14,32 @@ use ckb_types::{
     core::{
         cell::{resolve_transaction, BlockCellProvider, OverlayCellProvider, ResolvedTransaction},
         service::{Request, DEFAULT_CHANNEL_SIZE, SIGNAL_CHANNEL_SIZE},
-        BlockExt, BlockNumber, BlockView,
+        BlockExt, BlockNumber, BlockView, EpochExt, HeaderView,
     },
     packed::{Byte32, ProposalShortId},
     U256,
 };
 use ckb_verification::InvalidParentError;
-use ckb_verification::{BlockVerifier, ContextualBlockVerifier, Verifier, VerifyContext};
+use ckb_verification::{
+    measure_verify_phase, BlockVerifier, ContextualBlockVerifier, Verifier, VerifyContext,
+    VerifyOrigin, VerifyPhase,
+};
 use crossbeam_channel::{self, select, Receiver, Sender};
 use faketime::unix_time_as_millis;
 use std::collections::{HashSet, VecDeque};
 use std::sync::Arc;
+use std::time::Instant;
 use std::{cmp, thread};
 
 type ProcessBlockRequest = Request<(Arc<BlockView>, Switch), Result<bool, Error>>;
+type InvalidateBlockRequest = Request<Byte32, Result<(), Error>>;
+type ReconsiderBlockRequest = Request<Byte32, Result<(), Error>>;
 
 #[derive(Clone)]
 pub struct ChainController {
     process_block_sender: Sender<ProcessBlockRequest>,
+    invalidate_block_sender: Sender<InvalidateBlockRequest>,
+    reconsider_block_sender: Sender<ReconsiderBlockRequest>,
     stop: StopHandler<()>,
 }
 
@@ -38,6 +50,11 @@ impl Drop for ChainController {
 }
 
 impl ChainController {
+    /// Returns the handler which stops the chain service on shutdown.
+    pub fn stop_handler(&self) -> StopHandler<()> {
+        self.stop.clone()
+    }
+
     pub fn process_block(&self, block: Arc<BlockView>) -> Result<bool, Error> {
         self.internal_process_block(block, Switch::NONE)
     }
@@ -53,10 +70,31 @@ impl ChainController {
                 .into())
         })
     }
+
+    /// Marks the block invalid, the main chain is rewound to its parent if it is on the main
+    /// chain, and its descendants are refused until it is reconsidered.
+    pub fn invalidate_block(&self, hash: Byte32) -> Result<(), Error> {
+        Request::call(&self.invalidate_block_sender, hash).unwrap_or_else(|| {
+            Err(InternalErrorKind::System
+                .reason("Chain service has gone")
+                .into())
+        })
+    }
+
+    /// Undoes `invalidate_block`, the main chain switches back to the block if it is better.
+    pub fn reconsider_block(&self, hash: Byte32) -> Result<(), Error> {
+        Request::call(&self.reconsider_block_sender, hash).unwrap_or_else(|| {
+            Err(InternalErrorKind::System
+                .reason("Chain service has gone")
+                .into())
+        })
+    }
 }
 
 struct ChainReceivers {
     process_block_receiver: Receiver<ProcessBlockRequest>,
+    invalidate_block_receiver: Receiver<InvalidateBlockRequest>,
+    reconsider_block_receiver: Receiver<ReconsiderBlockRequest>,
 }
 
 #[derive(Debug, Default)]
@@ -117,13 +155,21 @@ impl GlobalIndex {
 pub struct ChainService {
     shared: Shared,
     proposal_table: ProposalTable,
+    // the blocks invalidated manually, they and their descendants never become the main chain
+    invalidated_blocks: HashSet<Byte32>,
 }
 
 impl ChainService {
     pub fn new(shared: Shared, proposal_table: ProposalTable) -> ChainService {
+        let invalidated_blocks = shared
+            .store()
+            .get_invalidated_blocks()
+            .into_iter()
+            .collect();
         ChainService {
             shared,
             proposal_table,
+            invalidated_blocks,
         }
     }
 
@@ -134,6 +180,10 @@ impl ChainService {
             crossbeam_channel::bounded::<()>(SIGNAL_CHANNEL_SIZE);
         let (process_block_sender, process_block_receiver) =
             crossbeam_channel::bounded(DEFAULT_CHANNEL_SIZE);
+        let (invalidate_block_sender, invalidate_block_receiver) =
+            crossbeam_channel::bounded(DEFAULT_CHANNEL_SIZE);
+        let (reconsider_block_sender, reconsider_block_receiver) =
+            crossbeam_channel::bounded(DEFAULT_CHANNEL_SIZE);
 
         // Mainly for test: give an empty thread_name
         let mut thread_builder = thread::Builder::new();
@@ -143,6 +193,8 @@ impl ChainService {
 
         let receivers = ChainReceivers {
             process_block_receiver,
+            invalidate_block_receiver,
+            reconsider_block_receiver,
         };
         let thread = thread_builder
             .spawn(move || loop {
@@ -158,6 +210,24 @@ impl ChainService {
                             error!("process_block_receiver closed");
                             break;
                         },
+                    },
+                    recv(receivers.invalidate_block_receiver) -> msg => match msg {
+                        Ok(Request { responder, arguments: hash }) => {
+                            let _ = responder.send(self.invalidate_block(hash));
+                        },
+                        _ => {
+                            error!("invalidate_block_receiver closed");
+                            break;
+                        },
+                    },
+                    recv(receivers.reconsider_block_receiver) -> msg => match msg {
+                        Ok(Request { responder, arguments: hash }) => {
+                            let _ = responder.send(self.reconsider_block(hash));
+                        },
+                        _ => {
+                            error!("reconsider_block_receiver closed");
+                            break;
+                        },
                     }
                 }
             })
@@ -166,6 +236,8 @@ impl ChainService {
 
         ChainController {
             process_block_sender,
+            invalidate_block_sender,
+            reconsider_block_sender,
             stop,
         }
     }
@@ -189,7 +261,16 @@ impl ChainService {
                 block.header().hash()
             );
         }
-        self.insert_block(block, switch).map(|ret| {
+        let started_at = Instant::now();
+        let result = self.insert_block(block, switch);
+        PROCESS_BLOCK_DURATION.observe_duration(started_at.elapsed());
+        let label = match result {
+            Ok(true) => "accepted",
+            Ok(false) => "duplicate",
+            Err(_) => "invalid",
+        };
+        PROCESSED_BLOCKS.with_label_values(&[label]).inc();
+        result.map(|ret| {
             debug!("finish processing block");
             ret
         })
@@ -214,7 +295,11 @@ impl ChainService {
         }
         // non-contextual verify
         if !switch.disable_non_contextual() {
+            let started_at = Instant::now();
             self.non_contextual_verify(&block)?;
+            PROCESS_BLOCK_PHASE_DURATION
+                .with_label_values(&["non_contextual_verify"])
+                .observe_duration(started_at.elapsed());
         }
 
         let mut total_difficulty = U256::zero();
@@ -289,7 +374,14 @@ impl ChainService {
                 &cannon_total_difficulty - &current_total_difficulty
             );
             self.find_fork(&mut fork, current_tip_header.number(), &block, ext);
+            if let Some(invalidated) = self.find_invalidated(&fork) {
+                return Err(InvalidParentError {
+                    parent_hash: invalidated,
+                }
+                .into());
+            }
             if !fork.detached_blocks.is_empty() {
+                REORG_DEPTH.observe(fork.detached_blocks.len() as f64);
                 metric!({
                     "topic": "reorg",
                     "fields": { "attached": fork.attached_blocks.len(), "detached": fork.detached_blocks.len(), },
@@ -299,7 +391,11 @@ impl ChainService {
             self.rollback(&fork, &db_txn)?;
             // update and verify chain root
             // MUST update index before reconcile_main_chain
+            let started_at = Instant::now();
             self.reconcile_main_chain(&db_txn, &mut fork, switch)?;
+            PROCESS_BLOCK_PHASE_DURATION
+                .with_label_values(&["contextual_verify"])
+                .observe_duration(started_at.elapsed());
 
             db_txn.insert_tip_header(&block.header())?;
             if new_epoch || fork.has_detached() {
@@ -309,10 +405,16 @@ impl ChainService {
         } else {
             db_txn.insert_block_ext(&block.header().hash(), &ext)?;
         }
+        let started_at = Instant::now();
         db_txn.commit()?;
+        PROCESS_BLOCK_PHASE_DURATION
+            .with_label_values(&["commit"])
+            .observe_duration(started_at.elapsed());
 
+        let started_at = Instant::now();
         if new_best_block {
             let tip_header = block.header();
+            TIP_NUMBER.set(tip_header.number() as i64);
             info!(
                 "block: {}, hash: {:#x}, epoch: {:#}, total_diff: {:#x}, txs: {}",
                 tip_header.number(),
@@ -322,35 +424,13 @@ impl ChainService {
                 block.transactions().len()
             );
 
-            self.update_proposal_table(&fork);
-            let (detached_proposal_id, new_proposals) = self
-                .proposal_table
-                .finalize(origin_proposals, tip_header.number());
-            fork.detached_proposal_id = detached_proposal_id;
-
-            let new_snapshot =
-                self.shared
-                    .new_snapshot(tip_header, total_difficulty, epoch, new_proposals);
-
-            self.shared.store_snapshot(Arc::clone(&new_snapshot));
-
-            if let Err(e) = self.shared.tx_pool_controller().update_tx_pool_for_reorg(
-                fork.detached_blocks().clone(),
-                fork.attached_blocks().clone(),
-                fork.detached_proposal_id().clone(),
-                new_snapshot,
-            ) {
-                error!("notify update_tx_pool_for_reorg error {}", e);
-            }
-            for detached_block in fork.detached_blocks() {
-                if let Err(e) = self
-                    .shared
-                    .tx_pool_controller()
-                    .notify_new_uncle(detached_block.as_uncle())
-                {
-                    error!("notify new_uncle error {}", e);
-                }
-            }
+            self.update_for_new_tip(
+                &mut fork,
+                tip_header,
+                total_difficulty,
+                epoch,
+                origin_proposals,
+            );
             let block_ref: &BlockView = &block;
             self.shared
                 .notify_controller()
@@ -376,7 +456,13 @@ impl ChainService {
             {
                 error!("notify new_uncle error {}", e);
             }
+            self.shared
+                .notify_controller()
+                .notify_new_uncle(block_ref.as_uncle());
         }
+        PROCESS_BLOCK_PHASE_DURATION
+            .with_label_values(&["notify"])
+            .observe_duration(started_at.elapsed());
 
         metric!({
             "topic": "chain",
@@ -388,6 +474,245 @@ impl ChainService {
         Ok(true)
     }
 
+    // Updates the proposal table, the snapshot and the tx-pool once the new tip is committed,
+    // and notifies the detached blocks and the reorg.
+    fn update_for_new_tip(
+        &mut self,
+        fork: &mut ForkChanges,
+        tip_header: HeaderView,
+        total_difficulty: U256,
+        epoch: EpochExt,
+        origin_proposals: &ProposalView,
+    ) {
+        self.update_proposal_table(fork);
+        let (detached_proposal_id, new_proposals) = self
+            .proposal_table
+            .finalize(origin_proposals, tip_header.number());
+        fork.detached_proposal_id = detached_proposal_id;
+
+        let new_snapshot =
+            self.shared
+                .new_snapshot(tip_header, total_difficulty, epoch, new_proposals);
+
+        self.shared.store_snapshot(Arc::clone(&new_snapshot));
+
+        if let Err(e) = self.shared.tx_pool_controller().update_tx_pool_for_reorg(
+            fork.detached_blocks().clone(),
+            fork.attached_blocks().clone(),
+            fork.detached_proposal_id().clone(),
+            new_snapshot,
+        ) {
+            error!("notify update_tx_pool_for_reorg error {}", e);
+        }
+        // the block assembler gets uncles from the tx pool channel directly, so they are
+        // ready before the next `get_block_template` call
+        for detached_block in fork.detached_blocks() {
+            if let Err(e) = self
+                .shared
+                .tx_pool_controller()
+                .notify_new_uncle(detached_block.as_uncle())
+            {
+                error!("notify new_uncle error {}", e);
+            }
+            self.shared
+                .notify_controller()
+                .notify_new_uncle(detached_block.as_uncle());
+        }
+        // the parent of the lowest detached block stays on the main chain
+        let common_ancestor = fork.detached_blocks().front().and_then(|b| {
+            self.shared
+                .store()
+                .get_block_header(&b.data().header().raw().parent_hash())
+        });
+        if let Some(common_ancestor) = common_ancestor {
+            self.shared.notify_controller().notify_reorg(ReorgEvent {
+                common_ancestor,
+                detached_blocks: fork.detached_blocks().iter().map(|b| b.header()).collect(),
+                attached_blocks: fork.attached_blocks().iter().map(|b| b.header()).collect(),
+                attached_tx_hashes: fork
+                    .attached_blocks()
+                    .iter()
+                    .map(|b| b.tx_hashes().to_vec())
+                    .collect(),
+            });
+        }
+    }
+
+    // The first attached block invalidated manually, the fork descending from it is refused.
+    fn find_invalidated(&self, fork: &ForkChanges) -> Option<Byte32> {
+        if self.invalidated_blocks.is_empty() {
+            return None;
+        }
+        fork.attached_blocks()
+            .iter()
+            .map(|b| b.hash())
+            .find(|hash| self.invalidated_blocks.contains(hash))
+    }
+
+    pub(crate) fn invalidate_block(&mut self, hash: Byte32) -> Result<(), Error> {
+        let db_txn = self.shared.store().begin_transaction();
+        let txn_snapshot = db_txn.get_snapshot();
+        let _snapshot_tip_hash = db_txn.get_update_for_tip_hash(&txn_snapshot);
+
+        let header = txn_snapshot
+            .get_block_header(&hash)
+            .ok_or_else(|| InternalErrorKind::System.reason(format!("unknown block {}", hash)))?;
+        if header.number() == 0 {
+            return Err(InternalErrorKind::System
+                .reason("the genesis block can't be invalidated")
+                .into());
+        }
+        let is_main_chain = txn_snapshot.is_main_chain(&hash);
+        if is_main_chain && txn_snapshot.is_pruned(&hash) {
+            return Err(InternalErrorKind::System
+                .reason(format!("the body of block {} is pruned", hash))
+                .into());
+        }
+
+        let mut ext = txn_snapshot
+            .get_block_ext(&hash)
+            .expect("block ext stored before invalidate_block");
+        ext.verified = Some(false);
+        db_txn.insert_block_ext(&hash, &ext)?;
+        let mut invalidated_blocks: Vec<Byte32> = self.invalidated_blocks.iter().cloned().collect();
+        if !self.invalidated_blocks.contains(&hash) {
+            invalidated_blocks.push(hash.clone());
+        }
+        db_txn.insert_invalidated_blocks(&invalidated_blocks)?;
+
+        if !is_main_chain {
+            db_txn.commit()?;
+            self.invalidated_blocks.insert(hash);
+            self.shared.refresh_snapshot();
+            return Ok(());
+        }
+
+        // rewind the main chain to the parent, the node switches to a better fork once it
+        // receives a new block of the fork
+        let shared_snapshot = Arc::clone(&self.shared.snapshot());
+        let origin_proposals = shared_snapshot.proposals();
+        let mut fork = ForkChanges::default();
+        for number in header.number()..=shared_snapshot.tip_number() {
+            let block = txn_snapshot
+                .get_block_hash(number)
+                .and_then(|hash| txn_snapshot.get_block(&hash))
+                .expect("main chain block stored before invalidate_block");
+            fork.detached_blocks.push_back(block);
+        }
+        self.rollback(&fork, &db_txn)?;
+
+        let parent_hash = header.data().raw().parent_hash();
+        let parent_header = txn_snapshot
+            .get_block_header(&parent_hash)
+            .expect("parent already store");
+        let parent_ext = txn_snapshot
+            .get_block_ext(&parent_hash)
+            .expect("parent already store");
+        let epoch = txn_snapshot
+            .get_block_epoch(&parent_hash)
+            .expect("parent epoch already store");
+        db_txn.insert_tip_header(&parent_header)?;
+        db_txn.insert_current_epoch_ext(&epoch)?;
+        db_txn.commit()?;
+        self.invalidated_blocks.insert(hash);
+
+        REORG_DEPTH.observe(fork.detached_blocks.len() as f64);
+        TIP_NUMBER.set(parent_header.number() as i64);
+        info!(
+            "invalidate block {}, rewind {} blocks to {}",
+            header.hash(),
+            fork.detached_blocks.len(),
+            parent_header.hash()
+        );
+        self.update_for_new_tip(
+            &mut fork,
+            parent_header,
+            parent_ext.total_difficulty,
+            epoch,
+            origin_proposals,
+        );
+        Ok(())
+    }
+
+    pub(crate) fn reconsider_block(&mut self, hash: Byte32) -> Result<(), Error> {
+        if !self.invalidated_blocks.contains(&hash) {
+            return Ok(());
+        }
+        let db_txn = self.shared.store().begin_transaction();
+        let txn_snapshot = db_txn.get_snapshot();
+        let _snapshot_tip_hash = db_txn.get_update_for_tip_hash(&txn_snapshot);
+
+        // the block is verified again once it is attached
+        let mut ext = txn_snapshot
+            .get_block_ext(&hash)
+            .expect("block ext stored before reconsider_block");
+        ext.verified = None;
+        db_txn.insert_block_ext(&hash, &ext)?;
+        let invalidated_blocks: Vec<Byte32> = self
+            .invalidated_blocks
+            .iter()
+            .filter(|invalidated| **invalidated != hash)
+            .cloned()
+            .collect();
+        db_txn.insert_invalidated_blocks(&invalidated_blocks)?;
+
+        // switch back to the block if it is better than the tip, its descendants are switched to
+        // once the node receives a new block on top of them
+        let shared_snapshot = Arc::clone(&self.shared.snapshot());
+        let origin_proposals = shared_snapshot.proposals();
+        let mut fork = ForkChanges::default();
+        let mut new_tip = None;
+        if ext.total_difficulty > *shared_snapshot.total_difficulty() {
+            let block = txn_snapshot
+                .get_block(&hash)
+                .expect("block stored before reconsider_block");
+            self.find_fork(&mut fork, shared_snapshot.tip_number(), &block, ext.clone());
+            let invalidated_ancestor = fork
+                .attached_blocks()
+                .iter()
+                .any(|b| b.hash() != hash && self.invalidated_blocks.contains(&b.hash()));
+            if invalidated_ancestor {
+                fork = ForkChanges::default();
+            } else {
+                self.rollback(&fork, &db_txn)?;
+                self.reconcile_main_chain(&db_txn, &mut fork, Switch::NONE)?;
+                let epoch = txn_snapshot
+                    .get_block_epoch(&hash)
+                    .expect("block epoch stored before reconsider_block");
+                db_txn.insert_tip_header(&block.header())?;
+                db_txn.insert_current_epoch_ext(&epoch)?;
+                new_tip = Some((block, epoch));
+            }
+        }
+        db_txn.commit()?;
+        self.invalidated_blocks.remove(&hash);
+
+        match new_tip {
+            Some((block, epoch)) => {
+                let tip_header = block.header();
+                TIP_NUMBER.set(tip_header.number() as i64);
+                info!(
+                    "reconsider block {}, switch to it with {} blocks detached",
+                    tip_header.hash(),
+                    fork.detached_blocks.len()
+                );
+                self.update_for_new_tip(
+                    &mut fork,
+                    tip_header,
+                    ext.total_difficulty,
+                    epoch,
+                    origin_proposals,
+                );
+                self.shared.notify_controller().notify_new_block(block);
+            }
+            None => {
+                info!("reconsider block {}", hash);
+                self.shared.refresh_snapshot();
+            }
+        }
+        Ok(())
+    }
+
     pub(crate) fn update_proposal_table(&mut self, fork: &ForkChanges) {
         for blk in fork.detached_blocks() {
             self.proposal_table.remove(blk.header().number());
@@ -401,6 +726,7 @@ impl ChainService {
     pub(crate) fn rollback(&self, fork: &ForkChanges, txn: &StoreTransaction) -> Result<(), Error> {
         for block in fork.detached_blocks().iter().rev() {
             txn.detach_block(block)?;
+            txn.delete_chain_root_leaf(block.number())?;
             detach_block_cell(txn, block)?;
         }
         Ok(())
@@ -533,14 +859,17 @@ impl ChainService {
         switch: Switch,
     ) -> Result<(), Error> {
         let txs_verify_cache = self.shared.txs_verify_cache();
+        let verify_pool = self.shared.verify_pool();
 
         let verified_len = fork.verified_len();
         for b in fork.attached_blocks().iter().take(verified_len) {
             txn.attach_block(b)?;
+            txn.insert_chain_root_leaf(b.number(), &b.hash())?;
             attach_block_cell(txn, b)?;
         }
 
-        let verify_context = VerifyContext::new(txn, self.shared.consensus());
+        let verify_context = VerifyContext::new(txn, self.shared.consensus())
+            .traced_transactions(self.shared.traced_transactions().clone());
         let async_handle = self.shared.tx_pool_controller().handle();
 
         let mut found_error = None;
@@ -565,18 +894,25 @@ impl ChainService {
                     let resolved = {
                         let txn_cell_provider = txn.cell_provider();
                         let cell_provider = OverlayCellProvider::new(&block_cp, &txn_cell_provider);
-                        transactions
-                            .iter()
-                            .cloned()
-                            .map(|x| {
-                                resolve_transaction(
-                                    x,
-                                    &mut seen_inputs,
-                                    &cell_provider,
-                                    &verify_context,
-                                )
-                            })
-                            .collect::<Result<Vec<ResolvedTransaction>, _>>()
+                        measure_verify_phase(
+                            VerifyOrigin::Block,
+                            VerifyPhase::Resolve,
+                            transactions.len(),
+                            || {
+                                transactions
+                                    .iter()
+                                    .cloned()
+                                    .map(|x| {
+                                        resolve_transaction(
+                                            x,
+                                            &mut seen_inputs,
+                                            &cell_provider,
+                                            &verify_context,
+                                        )
+                                    })
+                                    .collect::<Result<Vec<ResolvedTransaction>, _>>()
+                            },
+                        )
                     };
 
                     match resolved {
@@ -585,6 +921,7 @@ impl ChainService {
                                 &resolved,
                                 b,
                                 Arc::clone(&txs_verify_cache),
+                                &verify_pool,
                                 &async_handle,
                                 switch,
                             ) {
@@ -595,11 +932,15 @@ impl ChainService {
                                         .map(|entry| entry.fee)
                                         .collect();
                                     txn.attach_block(b)?;
+                                    txn.insert_chain_root_leaf(b.number(), &b.hash())?;
                                     attach_block_cell(txn, b)?;
                                     let mut mut_ext = ext.clone();
                                     mut_ext.verified = Some(true);
                                     mut_ext.txs_fees = txs_fees;
                                     txn.insert_block_ext(&b.header().hash(), &mut_ext)?;
+                                    if switch.contains(Switch::DISABLE_SCRIPT) {
+                                        mark_scripts_unverified(txn, b.number())?;
+                                    }
                                     if b.transactions().len() > 1 {
                                         info!(
                                             "[block_verifier] block number: {}, hash: {}, size:{}/{}, cycles: {}/{}",
@@ -639,6 +980,7 @@ impl ChainService {
                 }
             } else {
                 txn.attach_block(b)?;
+                txn.insert_chain_root_leaf(b.number(), &b.hash())?;
                 attach_block_cell(txn, b)?;
                 let mut mut_ext = ext.clone();
                 mut_ext.verified = Some(true);
@@ -676,3 +1018,12 @@ impl ChainService {
         debug!("}}");
     }
 }
+
+// The scripts of the block are verified later by `ScriptsBackfill`
+fn mark_scripts_unverified(txn: &StoreTransaction, number: BlockNumber) -> Result<(), Error> {
+    let (start, end) = match txn.get_unverified_scripts_range() {
+        Some((start, end)) => (cmp::min(start, number), cmp::max(end, number)),
+        None => (number, number),
+    };
+    txn.insert_unverified_scripts_range(start, end)
+}
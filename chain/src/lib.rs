@@ -4,8 +4,13 @@
 //!
 //! - [Chain](chain::chain::Chain) represent a struct which
 
+pub mod backfill;
 mod cell;
 pub mod chain;
+mod metrics;
+pub mod pruner;
 pub mod switch;
+
+pub use metrics::{process_block_phase_durations, PROCESS_BLOCK_PHASES};
 #[cfg(test)]
 mod tests;
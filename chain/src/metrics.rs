@@ -0,0 +1,60 @@
+use ckb_metrics::{
+    register_counter_vec, register_gauge, register_histogram, register_histogram_vec,
+    register_histogram_with_buckets, Counter, Gauge, Histogram, MetricVec, Subsystem,
+};
+use lazy_static::lazy_static;
+
+/// Upper bounds of the reorg depth buckets in blocks.
+const REORG_DEPTH_BUCKETS: &[f64] = &[1.0, 2.0, 3.0, 5.0, 10.0, 20.0, 50.0, 100.0];
+
+lazy_static! {
+    pub(crate) static ref PROCESS_BLOCK_DURATION: Histogram = register_histogram(
+        Subsystem::Chain,
+        "ckb_chain_process_block_duration_seconds",
+        "Time to verify and store a block",
+    );
+    pub(crate) static ref PROCESS_BLOCK_PHASE_DURATION: MetricVec<Histogram> =
+        register_histogram_vec(
+            Subsystem::Chain,
+            "ckb_chain_process_block_phase_duration_seconds",
+            "Time spent in each phase of processing a block",
+            &["phase"],
+        );
+    pub(crate) static ref PROCESSED_BLOCKS: MetricVec<Counter> = register_counter_vec(
+        Subsystem::Chain,
+        "ckb_chain_processed_blocks_total",
+        "Processed blocks by result, accepted, duplicate or invalid",
+        &["result"],
+    );
+    pub(crate) static ref TIP_NUMBER: Gauge = register_gauge(
+        Subsystem::Chain,
+        "ckb_chain_tip_number",
+        "Number of the main chain tip",
+    );
+    pub(crate) static ref REORG_DEPTH: Histogram = register_histogram_with_buckets(
+        Subsystem::Chain,
+        "ckb_chain_reorg_depth",
+        "Detached blocks of each reorg of the main chain",
+        REORG_DEPTH_BUCKETS,
+    );
+}
+
+/// The phases of processing a block, in the order they run.
+pub const PROCESS_BLOCK_PHASES: [&str; 4] = [
+    "non_contextual_verify",
+    "contextual_verify",
+    "commit",
+    "notify",
+];
+
+/// Returns the count and the total seconds of each phase in `PROCESS_BLOCK_PHASES`, they are
+/// only collected when the chain metrics are enabled.
+pub fn process_block_phase_durations() -> Vec<(&'static str, u64, f64)> {
+    PROCESS_BLOCK_PHASES
+        .iter()
+        .map(|phase| {
+            let histogram = PROCESS_BLOCK_PHASE_DURATION.with_label_values(&[phase]);
+            (*phase, histogram.count(), histogram.sum())
+        })
+        .collect()
+}
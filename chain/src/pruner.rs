@@ -0,0 +1,54 @@
+//! Prunes the old block bodies out of the chain service.
+use ckb_logger::{error, info};
+use ckb_shared::shared::Shared;
+use ckb_stop_handler::{SignalSender, StopHandler};
+use crossbeam_channel::{bounded, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Check for the blocks to prune at this interval when there is none
+const PRUNE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Prunes the blocks out of the kept depth in batches, see `ChainDB::prune_blocks`, on its own
+/// thread, so collecting the recent spends and deleting the bodies never hold the chain service.
+/// The depth is never less than `PRUNE_SAFETY_DEPTH`, so a reorg does not detach them.
+pub struct BlockPruner {
+    shared: Shared,
+}
+
+impl BlockPruner {
+    pub fn new(shared: Shared) -> Self {
+        BlockPruner { shared }
+    }
+
+    pub fn start(self) -> StopHandler<()> {
+        let (signal_sender, signal_receiver) = bounded::<()>(1);
+        let thread = thread::Builder::new()
+            .name("BlockPruner".to_string())
+            .spawn(move || loop {
+                let started_at = Instant::now();
+                // catch up without waiting when a batch is pruned
+                let timeout = match self.shared.store().prune_blocks() {
+                    Ok(Some(pruned)) => {
+                        info!(
+                            "pruned the block bodies up to {}, elapsed {:?}",
+                            pruned,
+                            started_at.elapsed()
+                        );
+                        Duration::from_secs(0)
+                    }
+                    Ok(None) => PRUNE_INTERVAL,
+                    Err(err) => {
+                        error!("prune blocks error {}", err);
+                        PRUNE_INTERVAL
+                    }
+                };
+                match signal_receiver.recv_timeout(timeout) {
+                    Err(RecvTimeoutError::Timeout) => {}
+                    _ => break,
+                }
+            })
+            .expect("Start BlockPruner failed");
+        StopHandler::new(SignalSender::Crossbeam(signal_sender), thread)
+    }
+}
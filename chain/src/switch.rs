@@ -12,6 +12,8 @@ bitflags! {
         const DISABLE_DAOHEADER         = 0b00001000;
         const DISABLE_REWARD            = 0b00010000;
         const DISABLE_NON_CONTEXTUAL    = 0b00100000;
+        // Not in `DISABLE_ALL`, the blocks assumed valid skip the scripts only
+        const DISABLE_SCRIPT            = 0b01000000;
         const DISABLE_ALL               = Self::DISABLE_EPOCH.bits | Self::DISABLE_UNCLES.bits |
                                     Self::DISABLE_TWO_PHASE_COMMIT.bits | Self::DISABLE_DAOHEADER.bits |
                                     Self::DISABLE_REWARD.bits |
@@ -45,4 +47,7 @@ impl VerificationSwitch for Switch {
     fn disable_reward(&self) -> bool {
         self.contains(Switch::DISABLE_REWARD)
     }
+    fn disable_script(&self) -> bool {
+        self.contains(Switch::DISABLE_SCRIPT)
+    }
 }
@@ -3,7 +3,7 @@ use crate::tests::util::{
     create_transaction, create_transaction_with_out_point, dao_data, start_chain, MockChain,
     MockStore,
 };
-use crate::{chain::ChainController, switch::Switch};
+use crate::{backfill::ScriptsBackfill, chain::ChainController, switch::Switch};
 use ckb_chain_spec::consensus::{Consensus, ConsensusBuilder};
 use ckb_dao_utils::genesis_dao_data;
 use ckb_error::assert_error_eq;
@@ -856,3 +856,232 @@ fn test_next_epoch_ext() {
         );
     }
 }
+
+// 0--1--2--3--4--5
+// \
+//  \
+//   1--2--3
+#[test]
+fn test_invalidate_and_reconsider_block() {
+    let (chain_controller, shared, parent) = start_chain(None);
+    let mock_store = MockStore::new(&parent, shared.store());
+    let mut fork1 = MockChain::new(parent.clone(), shared.consensus());
+    let mut fork2 = MockChain::new(parent.clone(), shared.consensus());
+    for _ in 0..4 {
+        fork1.gen_empty_block_with_diff(100u64, &mock_store);
+    }
+    for _ in 0..3 {
+        fork2.gen_empty_block_with_diff(90u64, &mock_store);
+    }
+    for blk in fork1.blocks() {
+        chain_controller
+            .internal_process_block(Arc::new(blk.clone()), Switch::DISABLE_ALL)
+            .expect("process block ok");
+    }
+
+    // rewinds to the genesis
+    let invalidated = fork1.blocks()[0].hash();
+    chain_controller
+        .invalidate_block(invalidated.clone())
+        .expect("invalidate block ok");
+    assert_eq!(shared.snapshot().tip_hash(), parent.hash());
+    assert_eq!(
+        shared.store().get_invalidated_blocks(),
+        vec![invalidated.clone()]
+    );
+
+    for blk in fork2.blocks() {
+        chain_controller
+            .internal_process_block(Arc::new(blk.clone()), Switch::DISABLE_ALL)
+            .expect("process block ok");
+    }
+    assert_eq!(shared.snapshot().tip_hash(), fork2.tip().hash());
+
+    // the descendants of the invalidated block are refused
+    fork1.gen_empty_block_with_diff(100u64, &mock_store);
+    let block = Arc::new(fork1.tip().clone());
+    assert!(chain_controller
+        .internal_process_block(Arc::clone(&block), Switch::DISABLE_ALL)
+        .is_err());
+
+    // the invalidated block is worse than the tip, the tip stays
+    chain_controller
+        .reconsider_block(invalidated)
+        .expect("reconsider block ok");
+    assert_eq!(shared.snapshot().tip_hash(), fork2.tip().hash());
+    assert!(shared.store().get_invalidated_blocks().is_empty());
+
+    chain_controller
+        .internal_process_block(block, Switch::DISABLE_ALL)
+        .expect("process block ok");
+    assert_eq!(shared.snapshot().tip_hash(), fork1.tip().hash());
+}
+
+#[test]
+fn test_backfill_assumed_valid_scripts() {
+    let (chain_controller, shared, parent) = start_chain(None);
+    let mock_store = MockStore::new(&parent, shared.store());
+    let mut chain = MockChain::new(parent, shared.consensus());
+    for _ in 0..4 {
+        chain.gen_empty_block_with_diff(100u64, &mock_store);
+    }
+    for blk in &chain.blocks()[..3] {
+        chain_controller
+            .internal_process_block(
+                Arc::new(blk.clone()),
+                Switch::DISABLE_ALL | Switch::DISABLE_SCRIPT,
+            )
+            .expect("process block ok");
+    }
+    assert_eq!(shared.store().get_unverified_scripts_range(), Some((1, 3)));
+
+    // waits for the sync to go past the blocks assumed valid
+    let backfill = ScriptsBackfill::new(shared.clone(), chain_controller.clone());
+    assert!(!backfill.backfill_batch().unwrap());
+    assert_eq!(shared.store().get_unverified_scripts_range(), Some((1, 3)));
+
+    chain_controller
+        .internal_process_block(Arc::new(chain.blocks()[3].clone()), Switch::DISABLE_ALL)
+        .expect("process block ok");
+    assert!(!backfill.backfill_batch().unwrap());
+    assert!(shared.store().get_unverified_scripts_range().is_none());
+    assert_eq!(shared.snapshot().tip_hash(), chain.tip().hash());
+}
+
+#[test]
+fn test_backfill_pruned_block_keeps_range() {
+    let (chain_controller, shared, parent) = start_chain(None);
+    let mock_store = MockStore::new(&parent, shared.store());
+    let mut chain = MockChain::new(parent, shared.consensus());
+    for _ in 0..4 {
+        chain.gen_empty_block_with_diff(100u64, &mock_store);
+    }
+    for (i, blk) in chain.blocks().iter().enumerate() {
+        let switch = if i < 3 {
+            Switch::DISABLE_ALL | Switch::DISABLE_SCRIPT
+        } else {
+            Switch::DISABLE_ALL
+        };
+        chain_controller
+            .internal_process_block(Arc::new(blk.clone()), switch)
+            .expect("process block ok");
+    }
+    // the body of the block 2 is gone
+    let txn = shared.store().begin_transaction();
+    txn.insert_pruned_block_number(2).unwrap();
+    txn.commit().unwrap();
+
+    let backfill = ScriptsBackfill::new(shared.clone(), chain_controller.clone());
+    assert!(backfill.backfill_batch().is_err());
+    // the blocks 1 and 2 are pruned, they are never trusted without the scripts run
+    assert_eq!(shared.store().get_unverified_scripts_range(), Some((1, 3)));
+    assert!(backfill.backfill_batch().is_err());
+    assert_eq!(shared.store().get_unverified_scripts_range(), Some((1, 3)));
+}
+
+#[test]
+fn test_rejected_transaction_notified() {
+    let (_chain_controller, shared, _parent) = start_chain(None);
+    let rejected_subscription = shared
+        .notify_controller()
+        .subscribe_rejected_transaction("test");
+    let genesis = shared.consensus().genesis_block().clone();
+    let accepted =
+        create_transaction_with_out_point(OutPoint::new(genesis.transactions()[1].hash(), 0), 1);
+    // a transaction without inputs never passes the non-contextual verification
+    let rejected = TransactionBuilder::default().build();
+
+    let tx_pool = shared.tx_pool_controller();
+    tx_pool
+        .submit_txs(vec![accepted])
+        .unwrap()
+        .expect("submit accepted tx");
+    assert!(tx_pool.submit_txs(vec![rejected.clone()]).unwrap().is_err());
+
+    // only the rejected transaction is pushed
+    let notified = rejected_subscription.recv().unwrap();
+    assert_eq!(notified.transaction.hash(), rejected.hash());
+    assert!(!notified.reason.is_empty());
+    assert!(rejected_subscription.try_recv().is_err());
+}
+
+#[test]
+fn test_dry_run_txs() {
+    let (_chain_controller, shared, _parent) = start_chain(None);
+    let genesis = shared.consensus().genesis_block().clone();
+    let parent =
+        create_transaction_with_out_point(OutPoint::new(genesis.transactions()[1].hash(), 0), 1);
+    // pays no fee
+    let child = create_transaction(&parent.hash(), 2);
+    let grandchild = create_transaction(&child.hash(), 3);
+
+    let tx_pool = shared.tx_pool_controller();
+    let results = tx_pool
+        .dry_run_txs(vec![parent.clone(), child, grandchild])
+        .unwrap();
+    assert_eq!(results.len(), 3);
+    let dry_run_entry = *results[0].as_ref().expect("dry run parent");
+    assert!(results[1].is_err());
+    // spends the output of a rejected tx
+    assert!(results[2].is_err());
+
+    // the pool is left untouched
+    assert_eq!(tx_pool.get_tx_pool_info().unwrap().pending_size, 0);
+    // and the dry run reports the cycles and fee of the real submission
+    let submitted = tx_pool
+        .submit_txs(vec![parent])
+        .unwrap()
+        .expect("submit the dry run tx");
+    assert_eq!(submitted, vec![dry_run_entry]);
+}
+
+// 0--1--2--3
+//     \
+//      2--3--4
+#[test]
+fn test_reorg_event_common_ancestor() {
+    let (chain_controller, shared, parent) = start_chain(None);
+    let reorg_subscription = shared.notify_controller().subscribe_reorg("test");
+    let mock_store = MockStore::new(&parent, shared.store());
+    let mut fork1 = MockChain::new(parent, shared.consensus());
+    for _ in 0..3 {
+        fork1.gen_empty_block_with_diff(100u64, &mock_store);
+    }
+    let common_ancestor = fork1.blocks()[0].header();
+    let mut fork2 = MockChain::new(common_ancestor.clone(), shared.consensus());
+    for _ in 0..3 {
+        fork2.gen_empty_block_with_diff(110u64, &mock_store);
+    }
+
+    // extending the main chain is not a reorg
+    for blk in fork1.blocks().iter().chain(fork2.blocks()) {
+        chain_controller
+            .internal_process_block(Arc::new(blk.clone()), Switch::DISABLE_ALL)
+            .expect("process block ok");
+    }
+    assert_eq!(shared.snapshot().tip_hash(), fork2.tip().hash());
+
+    // switches once the second block of fork2 outweighs fork1
+    let reorg = reorg_subscription.recv().unwrap();
+    assert_eq!(reorg.common_ancestor.hash(), common_ancestor.hash());
+    let hashes = |headers: &[HeaderView]| headers.iter().map(HeaderView::hash).collect::<Vec<_>>();
+    assert_eq!(
+        hashes(&reorg.detached_blocks),
+        fork1.blocks()[1..]
+            .iter()
+            .map(BlockView::hash)
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        hashes(&reorg.attached_blocks),
+        fork2.blocks()[..2]
+            .iter()
+            .map(BlockView::hash)
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        reorg.detached_blocks[0].parent_hash(),
+        reorg.common_ancestor.hash()
+    );
+    assert!(reorg_subscription.try_recv().is_err());
+}
@@ -606,3 +606,59 @@ fn test_package_txs_lower_than_min_fee_rate() {
         .unwrap();
     check_txs(&block_template, vec![&tx1]);
 }
+
+#[test]
+fn test_block_template_incremental_update() {
+    let mut consensus = Consensus::default();
+    consensus.genesis_epoch_ext.set_length(5);
+    let epoch = consensus.genesis_epoch_ext().clone();
+
+    let (chain_controller, shared) = start_chain(Some(consensus));
+
+    let genesis = shared
+        .store()
+        .get_block_header(&shared.store().get_block_hash(0).unwrap())
+        .unwrap();
+    let block0_0 = gen_block(&genesis, 11, &epoch);
+    let block0_1 = gen_block(&genesis, 10, &epoch);
+    chain_controller
+        .internal_process_block(Arc::new(block0_1.clone()), Switch::DISABLE_ALL)
+        .unwrap();
+
+    let tx_pool = shared.tx_pool_controller();
+    let block_template = tx_pool
+        .get_block_template(None, None, None)
+        .unwrap()
+        .unwrap();
+    check_txs(&block_template, vec![]);
+    assert!(block_template.uncles.is_empty());
+
+    // only the transactions changed, the cellbase and the uncles are kept
+    let tx1 = build_tx(&block0_1.transactions()[0], &[0], 2);
+    let entry = TxEntry::new(tx1.clone(), 0, Capacity::shannons(100), 100, vec![]);
+    tx_pool
+        .plug_entry(vec![entry], PlugTarget::Proposed)
+        .unwrap();
+    let updated = tx_pool
+        .get_block_template(None, None, None)
+        .unwrap()
+        .unwrap();
+    check_txs(&updated, vec![&tx1]);
+    assert_eq!(updated.cellbase, block_template.cellbase);
+    assert!(updated.uncles.is_empty());
+
+    // the new uncle is packaged as well
+    tx_pool.notify_new_uncle(block0_0.as_uncle()).unwrap();
+    let mut updated = tx_pool
+        .get_block_template(None, None, None)
+        .unwrap()
+        .unwrap();
+    while updated.uncles.is_empty() {
+        updated = tx_pool
+            .get_block_template(None, None, None)
+            .unwrap()
+            .unwrap()
+    }
+    assert_eq!(updated.uncles[0].hash, block0_0.hash().unpack());
+    check_txs(&updated, vec![&tx1]);
+}
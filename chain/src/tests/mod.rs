@@ -2,6 +2,7 @@ mod basic;
 mod block_assembler;
 mod delay_verify;
 mod find_fork;
+mod persisted_pool;
 mod reward;
 mod uncle;
 mod util;
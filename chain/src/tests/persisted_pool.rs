@@ -0,0 +1,102 @@
+use crate::switch::Switch;
+use crate::tests::util::{
+    create_always_success_out_point, create_transaction_with_out_point, start_chain, MockChain,
+    MockStore,
+};
+use ckb_test_chain_utils::always_success_cell;
+use ckb_types::{
+    bytes::Bytes,
+    core::{capacity_bytes, Capacity, TransactionBuilder, TransactionView},
+    packed::{CellDep, CellInput, CellOutputBuilder, OutPoint},
+    prelude::*,
+};
+use std::sync::Arc;
+
+fn create_child_transaction(parent: &TransactionView) -> TransactionView {
+    let (_, _, always_success_script) = always_success_cell();
+    TransactionBuilder::default()
+        .output(
+            CellOutputBuilder::default()
+                .capacity(capacity_bytes!(99).pack())
+                .lock(always_success_script.clone())
+                .build(),
+        )
+        .output_data(Bytes::new().pack())
+        .input(CellInput::new(OutPoint::new(parent.hash(), 0), 0))
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(create_always_success_out_point())
+                .build(),
+        )
+        .build()
+}
+
+#[test]
+fn test_save_and_load_pool() {
+    let tmp_dir = tempfile::Builder::new()
+        .prefix("test_save_and_load_pool")
+        .tempdir()
+        .unwrap();
+    let path = tmp_dir.path().join("persisted");
+
+    let (_chain_controller, shared, _parent) = start_chain(None);
+    let genesis = shared.consensus().genesis_block().clone();
+    let parent =
+        create_transaction_with_out_point(OutPoint::new(genesis.transactions()[1].hash(), 0), 1);
+    let child = create_child_transaction(&parent);
+    let stale =
+        create_transaction_with_out_point(OutPoint::new(genesis.transactions()[2].hash(), 0), 2);
+    let tx_pool = shared.tx_pool_controller();
+    tx_pool
+        .submit_txs(vec![parent.clone(), child.clone(), stale.clone()])
+        .unwrap()
+        .expect("submit txs");
+    assert_eq!(tx_pool.save_pool(path.clone()).unwrap().unwrap(), 3);
+
+    // another node, whose chain has spent the input of the stale tx
+    let (chain_controller, shared, genesis_header) = start_chain(None);
+    let mock_store = MockStore::new(&genesis_header, shared.store());
+    let mut chain = MockChain::new(genesis_header, shared.consensus());
+    let conflict =
+        create_transaction_with_out_point(OutPoint::new(genesis.transactions()[2].hash(), 0), 3);
+    chain.gen_block_with_commit_txs(vec![conflict], &mock_store, false);
+    chain_controller
+        .internal_process_block(Arc::new(chain.tip().clone()), Switch::DISABLE_ALL)
+        .expect("process block ok");
+
+    // the child is reloaded after its parent
+    let tx_pool = shared.tx_pool_controller();
+    assert_eq!(tx_pool.load_pool(path).unwrap().unwrap(), (2, 1));
+    assert_eq!(tx_pool.get_tx_pool_info().unwrap().pending_size, 2);
+}
+
+#[test]
+fn test_save_pool_on_stop() {
+    let tmp_dir = tempfile::Builder::new()
+        .prefix("test_save_pool_on_stop")
+        .tempdir()
+        .unwrap();
+    let path = tmp_dir.path().join("persisted");
+
+    let (_chain_controller, shared, _parent) = start_chain(None);
+    let genesis = shared.consensus().genesis_block().clone();
+    let parent =
+        create_transaction_with_out_point(OutPoint::new(genesis.transactions()[1].hash(), 0), 1);
+    let child = create_child_transaction(&parent);
+    let tx_pool = shared.tx_pool_controller();
+    tx_pool.persist_on_stop(path.clone());
+    tx_pool
+        .submit_txs(vec![parent])
+        .unwrap()
+        .expect("submit txs");
+    // queued or in flight when the service is stopped
+    tx_pool.notify_txs(vec![child], None).unwrap();
+    tx_pool.stop_handler().stop();
+    assert!(tx_pool.get_tx_pool_info().is_err());
+
+    // both are saved
+    let (_chain_controller, shared, _parent) = start_chain(None);
+    let tx_pool = shared.tx_pool_controller();
+    assert_eq!(tx_pool.load_pool(path).unwrap().unwrap(), (2, 0));
+    assert_eq!(tx_pool.get_tx_pool_info().unwrap().pending_size, 2);
+}
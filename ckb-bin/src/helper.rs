@@ -3,17 +3,140 @@ use std::io::{stdin, stdout, Write};
 use std::sync::Arc;
 
 pub fn wait_for_exit(exit: Arc<(Mutex<()>, Condvar)>) {
+    wait_for_exit_or_signals(exit, SignalHandlers::default());
+}
+
+/// The actions on the signals which do not exit.
+///
+/// On Windows, which has no such signals, the actions are triggered by setting the named events
+/// `Local\ckb-<pid>-reload` and `Local\ckb-<pid>-dump`, e.g. from a service controller.
+#[derive(Default)]
+pub struct SignalHandlers {
+    /// Called on SIGHUP instead of exiting, e.g. to reopen the log file and reload the config.
+    pub reload: Option<Box<dyn Fn() + Send>>,
+    /// Called on SIGUSR1 to dump the internal state into the log.
+    pub dump: Option<Box<dyn Fn() + Send>>,
+}
+
+/// Waits for the exit signals like `wait_for_exit`, and calls the handlers on the other signals.
+pub fn wait_for_exit_or_signals(exit: Arc<(Mutex<()>, Condvar)>, handlers: SignalHandlers) {
     // Handle possible exits
-    let e = Arc::<(Mutex<()>, Condvar)>::clone(&exit);
-    let _ = ctrlc::set_handler(move || {
-        e.1.notify_all();
-    });
+    handle_signals(Arc::<(Mutex<()>, Condvar)>::clone(&exit), handlers);
 
     // Wait for signal
     let mut l = exit.0.lock();
     exit.1.wait(&mut l);
 }
 
+#[cfg(unix)]
+fn handle_signals(exit: Arc<(Mutex<()>, Condvar)>, handlers: SignalHandlers) {
+    use signal_hook::{iterator::Signals, SIGHUP, SIGINT, SIGTERM, SIGUSR1};
+    use std::thread;
+
+    let mut registered = vec![SIGINT, SIGTERM, SIGHUP];
+    // keeps the default action, which terminates the process, if there is no dump handler
+    if handlers.dump.is_some() {
+        registered.push(SIGUSR1);
+    }
+    let signals = Signals::new(&registered).expect("Register signal handlers failed");
+    thread::Builder::new()
+        .name("SignalHandler".to_string())
+        .spawn(move || {
+            for signal in signals.forever() {
+                if !dispatch_signal(signal, &handlers) {
+                    exit.1.notify_all();
+                    break;
+                }
+            }
+        })
+        .expect("Start signal handler failed");
+}
+
+// Calls the handler of the signal, returns false if the signal exits instead.
+#[cfg(unix)]
+fn dispatch_signal(signal: i32, handlers: &SignalHandlers) -> bool {
+    use signal_hook::{SIGHUP, SIGUSR1};
+
+    match (signal, handlers.reload.as_ref(), handlers.dump.as_ref()) {
+        (SIGHUP, Some(reload), _) => reload(),
+        (SIGUSR1, _, Some(dump)) => dump(),
+        _ => return false,
+    }
+    true
+}
+
+#[cfg(not(unix))]
+fn handle_signals(exit: Arc<(Mutex<()>, Condvar)>, handlers: SignalHandlers) {
+    let _ = ctrlc::set_handler(move || {
+        exit.1.notify_all();
+    });
+    #[cfg(windows)]
+    handle_control_events(handlers);
+    #[cfg(not(windows))]
+    drop(handlers);
+}
+
+#[cfg(windows)]
+fn handle_control_events(handlers: SignalHandlers) {
+    use ckb_logger::{info, warn};
+    use std::ffi::OsStr;
+    use std::os::windows::ffi::OsStrExt;
+    use std::{process, ptr, thread};
+    use winapi::shared::minwindef::{DWORD, FALSE};
+    use winapi::um::synchapi::{CreateEventW, WaitForMultipleObjects};
+    use winapi::um::winbase::{INFINITE, WAIT_OBJECT_0};
+
+    let mut names = Vec::new();
+    let mut actions = Vec::new();
+    for (event, handler) in vec![("reload", handlers.reload), ("dump", handlers.dump)] {
+        if let Some(handler) = handler {
+            names.push(format!("Local\\ckb-{}-{}", process::id(), event));
+            actions.push(handler);
+        }
+    }
+    let mut events = Vec::with_capacity(names.len());
+    for name in &names {
+        let wide: Vec<u16> = OsStr::new(name).encode_wide().chain(Some(0)).collect();
+        // auto-reset event, which is reset after the waiting thread is released
+        let event = unsafe { CreateEventW(ptr::null_mut(), FALSE, FALSE, wide.as_ptr()) };
+        if event.is_null() {
+            warn!("create control event {} failed", name);
+            return;
+        }
+        info!("control event {} is created", name);
+        // the raw handle is not Send, and the events live as long as the process
+        events.push(event as usize);
+    }
+    if events.is_empty() {
+        return;
+    }
+
+    thread::Builder::new()
+        .name("ControlEventHandler".to_string())
+        .spawn(move || {
+            let handles: Vec<_> = events.into_iter().map(|event| event as _).collect();
+            loop {
+                let index = unsafe {
+                    WaitForMultipleObjects(
+                        handles.len() as DWORD,
+                        handles.as_ptr(),
+                        FALSE,
+                        INFINITE,
+                    )
+                }
+                .wrapping_sub(WAIT_OBJECT_0) as usize;
+                match actions.get(index) {
+                    Some(action) => action(),
+                    None => {
+                        warn!("wait for control events failed");
+                        break;
+                    }
+                }
+            }
+        })
+        .expect("Start control event handler failed");
+}
+
 #[cfg(not(feature = "deadlock_detection"))]
 pub fn deadlock_detection() {}
 
@@ -55,3 +178,36 @@ pub fn prompt(msg: &str) -> String {
 
     input
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use signal_hook::{SIGHUP, SIGINT, SIGTERM, SIGUSR1};
+    use std::sync::mpsc::channel;
+
+    #[test]
+    fn test_dispatch_signal() {
+        let (sender, receiver) = channel();
+        let reload_sender = sender.clone();
+        let handlers = SignalHandlers {
+            reload: Some(Box::new(move || reload_sender.send("reload").unwrap())),
+            dump: Some(Box::new(move || sender.send("dump").unwrap())),
+        };
+
+        // neither of them exits, so every signal reaches its handler
+        assert!(dispatch_signal(SIGHUP, &handlers));
+        assert!(dispatch_signal(SIGUSR1, &handlers));
+        assert!(dispatch_signal(SIGHUP, &handlers));
+        assert_eq!(
+            receiver.try_iter().collect::<Vec<_>>(),
+            vec!["reload", "dump", "reload"]
+        );
+
+        assert!(!dispatch_signal(SIGINT, &handlers));
+        assert!(!dispatch_signal(SIGTERM, &handlers));
+        assert!(receiver.try_recv().is_err());
+
+        // SIGHUP exits without the reload handler
+        assert!(!dispatch_signal(SIGHUP, &SignalHandlers::default()));
+    }
+}
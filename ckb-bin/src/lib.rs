@@ -13,10 +13,17 @@ pub fn run_app(version: Version) -> Result<(), ExitCode> {
     let app_matches = cli::get_matches(&version);
     match app_matches.subcommand() {
         (cli::CMD_INIT, Some(matches)) => {
-            return subcommand::init(Setup::init(&matches)?);
+            return subcommand::init(Setup::init(
+                &matches,
+                app_matches.value_of(cli::ARG_PROFILE),
+            )?);
         }
         (cli::CMD_LIST_HASHES, Some(matches)) => {
-            return subcommand::list_hashes(Setup::root_dir_from_matches(&matches)?, matches);
+            let root_dir = Setup::profile_dir(
+                Setup::root_dir_from_matches(&matches)?,
+                app_matches.value_of(cli::ARG_PROFILE),
+            )?;
+            return subcommand::list_hashes(root_dir, matches);
         }
         (cli::CMD_PEERID, Some(matches)) => match matches.subcommand() {
             (cli::CMD_GEN_SECRET, Some(matches)) => return Setup::gen(&matches),
@@ -37,10 +44,15 @@ pub fn run_app(version: Version) -> Result<(), ExitCode> {
         (cli::CMD_RUN, Some(matches)) => subcommand::run(setup.run(&matches)?, version),
         (cli::CMD_MINER, Some(matches)) => subcommand::miner(setup.miner(&matches)?),
         (cli::CMD_PROF, Some(matches)) => subcommand::profile(setup.prof(&matches)?),
+        (cli::CMD_REPLAY, Some(matches)) => subcommand::replay(setup.replay(&matches)?),
         (cli::CMD_EXPORT, Some(matches)) => subcommand::export(setup.export(&matches)?),
         (cli::CMD_IMPORT, Some(matches)) => subcommand::import(setup.import(&matches)?),
         (cli::CMD_STATS, Some(matches)) => subcommand::stats(setup.stats(&matches)?),
+        (cli::CMD_PEER, Some(matches)) => subcommand::peer(setup.peer(&matches)?),
+        (cli::CMD_INDEXER, Some(matches)) => subcommand::indexer(setup.indexer(&matches)?),
         (cli::CMD_RESET_DATA, Some(matches)) => subcommand::reset_data(setup.reset_data(&matches)?),
+        (cli::CMD_REPORT, Some(matches)) => subcommand::report(setup.report(&matches)?, version),
+        (cli::CMD_DNSSEED, Some(matches)) => subcommand::dnsseed(setup.dnsseed(&matches)?),
         _ => unreachable!(),
     }
 }
@@ -0,0 +1,20 @@
+use ckb_app_config::{DnsSeedArgs, ExitCode};
+use ckb_logger::info_target;
+use ckb_network::DnsSeedServer;
+
+pub fn dnsseed(args: DnsSeedArgs) -> Result<(), ExitCode> {
+    let server = DnsSeedServer::new(&args.config.network, &args.domain).map_err(|err| {
+        eprintln!("DNS seed server error: {}", err);
+        ExitCode::Config
+    })?;
+    info_target!(
+        crate::LOG_TARGET_MAIN,
+        "DNS seed server of {} signs the records by the public key {}",
+        args.domain,
+        server.pubkey_hex()
+    );
+    server.run(args.listen).map_err(|err| {
+        eprintln!("DNS seed server error: {}", err);
+        ExitCode::IO
+    })
+}
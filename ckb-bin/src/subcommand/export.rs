@@ -1,8 +1,12 @@
 use ckb_app_config::{ExitCode, ExportArgs};
-use ckb_instrument::Export;
+use ckb_instrument::{Export, Format};
 use ckb_shared::shared::SharedBuilder;
 
 pub fn export(args: ExportArgs) -> Result<(), ExitCode> {
+    let format: Format = args.format.parse().map_err(|err| {
+        eprintln!("Export error: {}", err);
+        ExitCode::Cli
+    })?;
     let (shared, _) = SharedBuilder::with_db_config(&args.config.db)
         .consensus(args.consensus)
         .build()
@@ -10,8 +14,12 @@ pub fn export(args: ExportArgs) -> Result<(), ExitCode> {
             eprintln!("Export error: {:?}", err);
             ExitCode::Failure
         })?;
-    Export::new(shared, args.target).execute().map_err(|err| {
-        eprintln!("Export error: {:?}", err);
-        ExitCode::Failure
-    })
+    Export::new(shared, args.target)
+        .format(format)
+        .with_cells(args.with_cells)
+        .execute()
+        .map_err(|err| {
+            eprintln!("Export error: {:?}", err);
+            ExitCode::Failure
+        })
 }
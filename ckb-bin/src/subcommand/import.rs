@@ -12,10 +12,11 @@ pub fn import(args: ImportArgs) -> Result<(), ExitCode> {
             ExitCode::Failure
         })?;
 
-    let chain_service = ChainService::new(shared, table);
+    let chain_service = ChainService::new(shared.clone(), table);
     let chain_controller = chain_service.start::<&str>(Some("ImportChainService"));
 
-    Import::new(chain_controller, args.source)
+    Import::new(chain_controller, shared, args.source)
+        .trusted(args.trusted)
         .execute()
         .map_err(|err| {
             eprintln!("Import error: {:?}", err);
@@ -0,0 +1,39 @@
+use crate::helper::wait_for_exit;
+use ckb_app_config::{ExitCode, IndexerArgs};
+use ckb_indexer::{ChainSource, RemoteChainSource, ScriptIndexer};
+use ckb_logger::info_target;
+use ckb_notify::NotifyService;
+use ckb_rpc::{RpcServer, ServiceBuilder};
+use ckb_util::{Condvar, Mutex};
+use std::sync::Arc;
+use std::time::Duration;
+
+pub fn indexer(args: IndexerArgs) -> Result<(), ExitCode> {
+    let exit_condvar = Arc::new((Mutex::new(()), Condvar::new()));
+
+    info_target!(
+        crate::LOG_TARGET_MAIN,
+        "indexer follows remote node: {}",
+        args.remote
+    );
+    let source: Arc<dyn ChainSource> = Arc::new(RemoteChainSource::new(args.remote));
+    let script_indexer = ScriptIndexer::open(&args.config.indexer, source);
+    // poll the remote tip, the node may not expose subscriptions to the indexer
+    let new_tip_receiver =
+        crossbeam_channel::tick(Duration::from_millis(args.config.indexer.batch_interval));
+    script_indexer
+        .clone()
+        .start(Some("ScriptIndexer"), new_tip_receiver);
+
+    let notify_controller = NotifyService::new(args.config.notify).start(Some("NotifyService"));
+    let io_handler = ServiceBuilder::new(&args.config.rpc)
+        .enable_standalone_indexer(script_indexer)
+        .build();
+    let _rpc_server = RpcServer::new(args.config.rpc, io_handler, &notify_controller);
+
+    wait_for_exit(exit_condvar);
+
+    info_target!(crate::LOG_TARGET_MAIN, "Finishing work, please wait...");
+
+    Ok(())
+}
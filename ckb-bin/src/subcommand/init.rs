@@ -119,6 +119,12 @@ pub fn init(args: InitArgs) -> Result<(), ExitCode> {
                     .unwrap_or_else(|| "0x".to_string()),
             )
         }
+        None if args.profile.as_deref() == Some("mining") => {
+            eprintln!(
+                "The profile mining requires the block assembler config options, e.g. --ba-arg"
+            );
+            return Err(ExitCode::Cli);
+        }
         None => {
             eprintln!("WARN: mining feature is disabled because of lacking the block assembler config options");
             format!(
@@ -143,9 +149,13 @@ pub fn init(args: InitArgs) -> Result<(), ExitCode> {
         },
         args.root_dir.display()
     );
+    if let Some(profile) = &args.profile {
+        println!("use profile {} for chain {}", profile, args.chain);
+    }
 
     let mut context = TemplateContext {
         spec: &args.chain,
+        profile: args.profile.as_deref(),
         rpc_port: &args.rpc_port,
         p2p_port: &args.p2p_port,
         log_to_file: args.log_to_file,
@@ -1,21 +1,31 @@
+mod dnsseed;
 mod export;
 mod import;
+mod indexer;
 mod init;
 mod list_hashes;
 mod miner;
+mod peer;
 mod peer_id;
 mod prof;
+mod replay;
+mod report;
 mod reset_data;
 mod run;
 mod stats;
 
+pub use self::dnsseed::dnsseed;
 pub use self::export::export;
 pub use self::import::import;
+pub use self::indexer::indexer;
 pub use self::init::init;
 pub use self::list_hashes::list_hashes;
 pub use self::miner::miner;
+pub use self::peer::peer;
 pub use self::peer_id::peer_id;
 pub use self::prof::profile;
+pub use self::replay::replay;
+pub use self::report::report;
 pub use self::reset_data::reset_data;
 pub use self::run::run;
 pub use self::stats::stats;
@@ -0,0 +1,89 @@
+use ckb_app_config::{ExitCode, PeerArgs, PeerCommand};
+use ckb_jsonrpc_types::{BannedAddr, Node};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+
+pub fn peer(args: PeerArgs) -> Result<(), ExitCode> {
+    let url = args.rpc_url;
+    match args.command {
+        PeerCommand::List { banned: false } => {
+            let peers: Vec<Node> = call(&url, "get_peers", json!([]))?;
+            for peer in peers {
+                let direction = match peer.is_outbound {
+                    Some(true) => "outbound",
+                    Some(false) => "inbound",
+                    None => "unknown",
+                };
+                let address = peer
+                    .addresses
+                    .first()
+                    .map(|address| address.address.as_str())
+                    .unwrap_or_default();
+                println!(
+                    "{} {} {} {}",
+                    peer.node_id, direction, peer.version, address
+                );
+            }
+        }
+        PeerCommand::List { banned: true } => {
+            let banned_addrs: Vec<BannedAddr> = call(&url, "get_banned_addresses", json!([]))?;
+            for banned in banned_addrs {
+                println!(
+                    "{} until {} {}",
+                    banned.address,
+                    banned.ban_until.value(),
+                    banned.ban_reason
+                );
+            }
+        }
+        PeerCommand::Ban {
+            address,
+            ban_time,
+            reason,
+        } => {
+            let ban_time = ban_time.map(|ban_time| format!("{:#x}", ban_time));
+            call::<Value>(
+                &url,
+                "set_ban",
+                json!([address, "insert", ban_time, false, reason]),
+            )?;
+        }
+        PeerCommand::Unban { address } => {
+            call::<Value>(&url, "set_ban", json!([address, "delete"]))?;
+        }
+        PeerCommand::Dial { address } => {
+            call::<Value>(&url, "dial_node", json!([address]))?;
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn call<T: DeserializeOwned>(
+    url: &str,
+    method: &str,
+    params: Value,
+) -> Result<T, ExitCode> {
+    let request = json!({
+        "id": 1,
+        "jsonrpc": "2.0",
+        "method": method,
+        "params": params,
+    });
+    let mut response: Value = reqwest::Client::new()
+        .post(url)
+        .json(&request)
+        .send()
+        .and_then(|mut response| response.json())
+        .map_err(|err| {
+            eprintln!("Request {} error: {}", url, err);
+            ExitCode::Failure
+        })?;
+    if let Some(error) = response.get("error") {
+        eprintln!("RPC {} error: {}", method, error);
+        return Err(ExitCode::Failure);
+    }
+    serde_json::from_value(response["result"].take()).map_err(|err| {
+        eprintln!("RPC {} unexpected result: {}", method, err);
+        ExitCode::Failure
+    })
+}
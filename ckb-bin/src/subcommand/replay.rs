@@ -0,0 +1,191 @@
+use ckb_app_config::{ExitCode, ReplayArgs};
+use ckb_chain::chain::{ChainController, ChainService};
+use ckb_chain::process_block_phase_durations;
+use ckb_chain::switch::Switch;
+use ckb_metrics::Subsystem;
+use ckb_shared::shared::{Shared, SharedBuilder};
+use ckb_store::ChainStore;
+use ckb_types::core::BlockNumber;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+pub fn replay(args: ReplayArgs) -> Result<(), ExitCode> {
+    let (shared, _table) = SharedBuilder::with_db_config(&args.config.db)
+        .consensus(args.consensus.clone())
+        .tx_pool_config(args.config.tx_pool)
+        .build()
+        .map_err(|err| {
+            eprintln!("Replay error: {:?}", err);
+            ExitCode::Failure
+        })?;
+
+    let switch = parse_switch(&args.skip);
+    let tip_number = shared.snapshot().tip_number();
+    let from = std::cmp::max(1, args.from.unwrap_or(1));
+    let to = std::cmp::min(tip_number, args.to.unwrap_or(tip_number));
+    if from > to {
+        eprintln!(
+            "Replay error: invalid range {}..{}, the tip is {}",
+            from, to, tip_number
+        );
+        return Err(ExitCode::Cli);
+    }
+
+    if !args.tmp_target.is_dir() {
+        eprintln!(
+            "Replay error: {:?}",
+            "The specified path does not exist or not directory"
+        );
+        return Err(ExitCode::Failure);
+    }
+    let tmp_db_dir = tempfile::tempdir_in(args.tmp_target).map_err(|err| {
+        eprintln!("Replay error: {:?}", err);
+        ExitCode::Failure
+    })?;
+    {
+        let mut tmp_db_config = args.config.db.clone();
+        tmp_db_config.path = tmp_db_dir.path().to_path_buf();
+
+        let (tmp_shared, table) = SharedBuilder::with_db_config(&tmp_db_config)
+            .consensus(args.consensus)
+            .tx_pool_config(args.config.tx_pool)
+            .build()
+            .map_err(|err| {
+                eprintln!("Replay error: {:?}", err);
+                ExitCode::Failure
+            })?;
+
+        // the phases are timed by the chain metrics
+        ckb_metrics::enable(Subsystem::Chain);
+        let chain = ChainService::new(tmp_shared, table);
+        let chain_controller = chain.start(Some("chain"));
+
+        // the blocks before the range have been verified when they were stored
+        if from > 1 {
+            println!("Replaying blocks 1..{} without verification", from - 1);
+            replay_blocks(&shared, &chain_controller, 1, from - 1, Switch::DISABLE_ALL)?;
+        }
+
+        println!("Replaying blocks {}..{} with {:?}", from, to, switch);
+        let phases_before = process_block_phase_durations();
+        let now = Instant::now();
+        let tx_count = replay_blocks(&shared, &chain_controller, from, to, switch)?;
+        let duration = now.elapsed();
+        let phases_after = process_block_phase_durations();
+
+        print_report(
+            to - from + 1,
+            tx_count,
+            duration,
+            &phases_before,
+            &phases_after,
+        );
+    }
+
+    tmp_db_dir.close().map_err(|err| {
+        eprintln!("Replay error: {:?}", err);
+        ExitCode::Failure
+    })?;
+
+    Ok(())
+}
+
+fn parse_switch(skip: &[String]) -> Switch {
+    skip.iter().fold(Switch::NONE, |switch, verification| {
+        switch
+            | match verification.as_str() {
+                "non-contextual" => Switch::DISABLE_NON_CONTEXTUAL,
+                "epoch" => Switch::DISABLE_EPOCH,
+                "uncles" => Switch::DISABLE_UNCLES,
+                "two-phase-commit" => Switch::DISABLE_TWO_PHASE_COMMIT,
+                "dao-header" => Switch::DISABLE_DAOHEADER,
+                "reward" => Switch::DISABLE_REWARD,
+                "all" => Switch::DISABLE_ALL,
+                _ => unreachable!("checked by the possible values of --skip"),
+            }
+    })
+}
+
+// Returns the count of transactions excluding cellbases
+fn replay_blocks(
+    shared: &Shared,
+    chain_controller: &ChainController,
+    from: BlockNumber,
+    to: BlockNumber,
+    switch: Switch,
+) -> Result<usize, ExitCode> {
+    let mut tx_count = 0;
+    let snapshot = shared.snapshot();
+    for number in from..=to {
+        let block = snapshot
+            .get_block_hash(number)
+            .and_then(|hash| snapshot.get_block(&hash))
+            .ok_or_else(|| {
+                eprintln!("Replay error: block {} is not found", number);
+                ExitCode::IO
+            })?;
+        tx_count += block.transactions().len().saturating_sub(1);
+        chain_controller
+            .internal_process_block(Arc::new(block), switch)
+            .map_err(|err| {
+                eprintln!("Replay error: process block {} error: {}", number, err);
+                ExitCode::Failure
+            })?;
+    }
+    Ok(tx_count)
+}
+
+fn print_report(
+    block_count: u64,
+    tx_count: usize,
+    duration: Duration,
+    phases_before: &[(&'static str, u64, f64)],
+    phases_after: &[(&'static str, u64, f64)],
+) {
+    let seconds = duration.as_secs_f64();
+    println!("blocks: {}", block_count);
+    println!("transactions: {}", tx_count);
+    println!("duration: {:?}", duration);
+    println!(
+        "blocks_per_second: {}",
+        if seconds > 0f64 {
+            block_count as f64 / seconds
+        } else {
+            0f64
+        }
+    );
+    println!(
+        "tps: {}",
+        if seconds > 0f64 {
+            tx_count as f64 / seconds
+        } else {
+            0f64
+        }
+    );
+
+    let before: HashMap<_, _> = phases_before
+        .iter()
+        .map(|(phase, count, sum)| (*phase, (*count, *sum)))
+        .collect();
+    for (phase, count, sum) in phases_after {
+        let (count_before, sum_before) = before.get(phase).cloned().unwrap_or_default();
+        let count = count - count_before;
+        let total = sum - sum_before;
+        if count == 0 {
+            println!("phase {}: skipped", phase);
+        } else {
+            println!(
+                "phase {}: total {:.3}s, {:.2}% of duration, average {:.3}ms",
+                phase,
+                total,
+                if seconds > 0f64 {
+                    total * 100f64 / seconds
+                } else {
+                    0f64
+                },
+                total * 1000f64 / count as f64
+            );
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use super::peer::call;
+use ckb_app_config::{CKBAppConfig, ExitCode, ReportArgs};
+use ckb_build_info::Version;
+use ckb_clock::{Clock, SystemClock};
+use ckb_health::HealthRegistry;
+use flate2::{write::GzEncoder, Compression};
+use serde_json::{json, Map, Value};
+use std::fs;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+/// Only the tail of the log file is read, which is large enough for the default 1000 lines.
+const MAX_LOG_BYTES: u64 = 8 * 1024 * 1024;
+/// The health checks may wait for the locks held by the panicked thread.
+const CRASH_HEALTH_TIMEOUT: Duration = Duration::from_secs(5);
+/// The RPC methods whose results are collected as the status of the node.
+const STATUS_METHODS: &[&str] = &[
+    "get_blockchain_info",
+    "get_tip_header",
+    "tx_pool_info",
+    "get_health",
+    "get_memory_usage",
+];
+
+pub fn report(args: ReportArgs, version: Version) -> Result<(), ExitCode> {
+    let mut status = Map::new();
+    for method in STATUS_METHODS {
+        // the node may be down, which is likely the reason of the report
+        let result = call::<Value>(&args.rpc_url, method, json!([]))
+            .unwrap_or_else(|_| json!({ "error": "unavailable" }));
+        status.insert(method.to_string(), result);
+    }
+    let status = serde_json::to_string_pretty(&status).map_err(|err| {
+        eprintln!("Report error: {}", err);
+        ExitCode::Failure
+    })?;
+
+    let target = match args.target {
+        Some(target) => target,
+        None => reports_dir(&args.config.data_dir)?.join(format!("report-{}.tar.gz", now())),
+    };
+    let entries = vec![
+        ("version.txt", version.long().into_bytes()),
+        ("ckb.toml", args.config.redacted()?.into_bytes()),
+        (
+            "ckb.log",
+            recent_logs(args.log_file.as_ref(), args.log_lines).into_bytes(),
+        ),
+        ("status.json", status.into_bytes()),
+    ];
+    write_bundle(&target, &entries).map_err(|err| {
+        eprintln!("Report error: write {} error: {}", target.display(), err);
+        ExitCode::IO
+    })?;
+
+    println!("Report is written to {}", target.display());
+    println!("Please review it before attaching to a bug report");
+    Ok(())
+}
+
+/// Writes a bundle into `<data_dir>/reports` on the first panic of the node.
+///
+/// The status comes from the health checks instead of the RPC, since the RPC server may be
+/// the one which panicked.
+pub fn install_crash_report(config: &CKBAppConfig, version: &Version, health: HealthRegistry) {
+    let redacted_config = config.redacted().unwrap_or_default();
+    let reports_dir = config.data_dir.join("reports");
+    let log_file = config.logger.file.clone();
+    let version = version.long();
+    let reported = AtomicBool::new(false);
+
+    let previous_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        previous_hook(info);
+        if reported.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let thread = thread::current();
+        let panic = format!("thread '{}' {}\n", thread.name().unwrap_or("unnamed"), info);
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+        let health = health.clone();
+        let _ = thread::Builder::new()
+            .name("CrashReport".to_string())
+            .spawn(move || {
+                let report = health.report();
+                let mut status = format!("{}\n", report.status);
+                for service in report.services {
+                    status.push_str(&format!("{}\n", service));
+                }
+                let _ = sender.send(status);
+            });
+        let status = receiver
+            .recv_timeout(CRASH_HEALTH_TIMEOUT)
+            .unwrap_or_else(|_| "unavailable\n".to_string());
+
+        let target = reports_dir.join(format!("crash-{}.tar.gz", now()));
+        let entries = vec![
+            ("version.txt", version.clone().into_bytes()),
+            ("panic.txt", panic.into_bytes()),
+            ("ckb.toml", redacted_config.clone().into_bytes()),
+            ("ckb.log", recent_logs(log_file.as_ref(), 1000).into_bytes()),
+            ("health.txt", status.into_bytes()),
+        ];
+        match fs::create_dir_all(&reports_dir).and_then(|_| write_bundle(&target, &entries)) {
+            Ok(()) => eprintln!("Crash report is written to {}", target.display()),
+            Err(err) => eprintln!("Write crash report {} error: {}", target.display(), err),
+        }
+    }));
+}
+
+fn reports_dir(data_dir: &Path) -> Result<PathBuf, ExitCode> {
+    let dir = data_dir.join("reports");
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+fn now() -> u64 {
+    SystemClock.unix_time_as_millis() / 1000
+}
+
+// Returns the last `lines` lines of the log file.
+fn recent_logs(log_file: Option<&PathBuf>, lines: usize) -> String {
+    let log_file = match log_file {
+        Some(log_file) => log_file,
+        None => return "logging to file is disabled\n".to_string(),
+    };
+    let read = || -> io::Result<String> {
+        let mut file = fs::File::open(log_file)?;
+        let len = file.metadata()?.len();
+        file.seek(SeekFrom::Start(len.saturating_sub(MAX_LOG_BYTES)))?;
+        let mut buf = Vec::new();
+        file.read_to_end(&mut buf)?;
+        Ok(String::from_utf8_lossy(&buf).into_owned())
+    };
+    match read() {
+        Ok(logs) => {
+            let all: Vec<_> = logs.lines().collect();
+            let mut recent = all[all.len().saturating_sub(lines)..].join("\n");
+            recent.push('\n');
+            recent
+        }
+        Err(err) => format!("read {} error: {}\n", log_file.display(), err),
+    }
+}
+
+fn write_bundle(target: &Path, entries: &[(&str, Vec<u8>)]) -> io::Result<()> {
+    let file = fs::File::create(target)?;
+    let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    let mtime = now();
+    for (name, content) in entries {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(mtime);
+        builder.append_data(&mut header, name, content.as_slice())?;
+    }
+    builder.into_inner()?.finish()?;
+    Ok(())
+}
@@ -1,35 +1,61 @@
-use crate::helper::{deadlock_detection, wait_for_exit};
-use ckb_app_config::{BlockAssemblerConfig, ExitCode, RunArgs};
+use super::report::install_crash_report;
+use crate::helper::{deadlock_detection, wait_for_exit_or_signals, SignalHandlers};
+use ckb_app_config::{cli, AppConfig, BlockAssemblerConfig, CKBAppConfig, ExitCode, RunArgs};
 use ckb_build_info::Version;
-use ckb_chain::chain::ChainService;
+use ckb_chain::{backfill::ScriptsBackfill, chain::ChainService, pruner::BlockPruner};
+use ckb_clock::MockableClock;
+use ckb_health::{HealthRegistry, ServiceHealth};
 use ckb_jsonrpc_types::ScriptHashType;
-use ckb_logger::info_target;
+use ckb_logger::{
+    configure_logger_filter, error_target, info_target, update_main_logger, warn_target,
+};
+use ckb_memory_tracker::MemoryEstimators;
 use ckb_network::{
-    BlockingFlag, CKBProtocol, NetworkService, NetworkState, MAX_FRAME_LENGTH_ALERT,
+    BlockingFlag, CKBProtocol, IdentifyFlag, NetworkController, NetworkService, NetworkState,
+    MAX_FRAME_LENGTH_ALERT, MAX_FRAME_LENGTH_BLOCK_FILTER, MAX_FRAME_LENGTH_LIGHT_CLIENT,
     MAX_FRAME_LENGTH_RELAY, MAX_FRAME_LENGTH_SYNC, MAX_FRAME_LENGTH_TIME,
 };
 use ckb_network_alert::alert_relayer::AlertRelayer;
 use ckb_resource::Resource;
 use ckb_rpc::{RpcServer, ServiceBuilder};
 use ckb_shared::shared::{Shared, SharedBuilder};
+use ckb_stop_handler::{ShutdownCoordinator, ShutdownStage};
 use ckb_store::ChainStore;
-use ckb_sync::{NetTimeProtocol, NetworkProtocol, Relayer, SyncShared, Synchronizer};
+use ckb_stratum::StratumServer;
+use ckb_sync::{
+    BlockFilterProtocol, LightClientProtocol, NetTimeProtocol, NetworkProtocol, Relayer,
+    SyncShared, Synchronizer, TxReconciliationProtocol, TX_POOL_INVENTORY_VERSION,
+};
 use ckb_types::{core::cell::setup_system_cell_cache, prelude::*};
 use ckb_util::{Condvar, Mutex};
 use ckb_verification::{GenesisVerifier, Verifier};
+use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
 
 const SECP256K1_BLAKE160_SIGHASH_ALL_ARG_LEN: usize = 20;
+const SHUTDOWN_STAGE_TIMEOUT: Duration = Duration::from_secs(30);
 
 pub fn run(args: RunArgs, version: Version) -> Result<(), ExitCode> {
     deadlock_detection();
 
+    // compared with the config file on reload
+    let running_config = Mutex::new((*args.config).clone());
+
+    ckb_metrics::init(&args.config.metrics).map_err(|err| {
+        eprintln!("Start metrics exporter error: {}", err);
+        ExitCode::Failure
+    })?;
+
     let block_assembler_config = sanitize_block_assembler_config(&args)?;
     let miner_enable = block_assembler_config.is_some();
     let exit_condvar = Arc::new((Mutex::new(()), Condvar::new()));
+    // the system clock, the integration tests set it by the `set_mock_time` RPC
+    let clock = MockableClock::default();
 
     let (shared, table) = SharedBuilder::with_db_config(&args.config.db)
         .consensus(args.consensus)
+        .clock(Arc::new(clock.clone()))
         .tx_pool_config(args.config.tx_pool)
         .notify_config(args.config.notify)
         .store_config(args.config.store)
@@ -62,19 +88,49 @@ pub fn run(args: RunArgs, version: Version) -> Result<(), ExitCode> {
         shared.genesis_hash()
     );
 
-    let sync_shared = Arc::new(SyncShared::new(shared.clone()));
+    let persist_tx_pool = args.config.tx_pool.persist_on_shutdown;
+    let tx_pool_persisted_path = args.config.tx_pool_persisted_path();
+    if persist_tx_pool {
+        load_tx_pool(&shared, &tx_pool_persisted_path);
+        shared
+            .tx_pool_controller()
+            .persist_on_stop(tx_pool_persisted_path);
+    }
+
+    let assume_valid_target = args.config.chain.assume_valid_target.clone();
+    // the scripts skipped by the previous runs are verified even if the target is unset now
+    let scripts_backfill = if assume_valid_target.is_some()
+        || shared.store().get_unverified_scripts_range().is_some()
+    {
+        Some(ScriptsBackfill::new(shared.clone(), chain_controller.clone()).start())
+    } else {
+        None
+    };
+    let block_pruner = if shared.store().prune_keep_blocks() > 0 {
+        Some(BlockPruner::new(shared.clone()).start())
+    } else {
+        None
+    };
+    let sync_shared = Arc::new(
+        SyncShared::new(shared.clone())
+            .with_assume_valid_target(assume_valid_target.map(|hash| hash.pack())),
+    );
+    let light_client_server = args.config.network.light_client_server;
+    let dandelion_config = args.config.network.dandelion.clone();
+    let tx_reconciliation = args.config.network.tx_reconciliation;
+    let mempool_sync = args.config.network.mempool_sync;
     let network_state = Arc::new(
-        NetworkState::from_config(args.config.network).expect("Init network state failed"),
+        NetworkState::from_config(args.config.network)
+            .expect("Init network state failed")
+            .with_clock(Arc::new(clock.clone())),
     );
     let synchronizer = Synchronizer::new(chain_controller.clone(), Arc::clone(&sync_shared));
 
-    let relayer = Relayer::new(
-        chain_controller.clone(),
-        Arc::clone(&sync_shared),
-        args.config.tx_pool.min_fee_rate,
-        args.config.tx_pool.max_tx_verify_cycles,
-    );
+    let relayer = Relayer::new(chain_controller.clone(), Arc::clone(&sync_shared))
+        .with_dandelion(&dandelion_config)
+        .with_mempool_sync(mempool_sync);
     let net_timer = NetTimeProtocol::default();
+    let block_filter = BlockFilterProtocol::new(shared.clone());
     let alert_signature_config = args.config.alert_signature.unwrap_or_default();
     let alert_relayer = AlertRelayer::new(
         version.to_string(),
@@ -93,7 +149,7 @@ pub fn run(args: RunArgs, version: Version) -> Result<(), ExitCode> {
     blocking_recv_flag.disable_disconnected();
     blocking_recv_flag.disable_notify();
 
-    let protocols = vec![
+    let mut protocols = vec![
         CKBProtocol::new(
             "syn".to_string(),
             NetworkProtocol::SYNC.into(),
@@ -102,16 +158,19 @@ pub fn run(args: RunArgs, version: Version) -> Result<(), ExitCode> {
             Box::new(synchronizer.clone()),
             Arc::clone(&network_state),
             blocking_recv_flag,
-        ),
+        )
+        .with_identify_flag(IdentifyFlag::Sync),
         CKBProtocol::new(
             "rel".to_string(),
             NetworkProtocol::RELAY.into(),
-            &["1".to_string()][..],
+            // version 2 adds `GetTxPoolInventory`
+            &["1".to_string(), TX_POOL_INVENTORY_VERSION.to_string()][..],
             MAX_FRAME_LENGTH_RELAY,
             Box::new(relayer),
             Arc::clone(&network_state),
             blocking_recv_flag,
-        ),
+        )
+        .with_identify_flag(IdentifyFlag::Relay),
         CKBProtocol::new(
             "tim".to_string(),
             NetworkProtocol::TIME.into(),
@@ -130,7 +189,45 @@ pub fn run(args: RunArgs, version: Version) -> Result<(), ExitCode> {
             Arc::clone(&network_state),
             no_blocking_flag,
         ),
+        CKBProtocol::new(
+            "flt".to_string(),
+            NetworkProtocol::BLOCK_FILTER.into(),
+            &["1".to_string()][..],
+            MAX_FRAME_LENGTH_BLOCK_FILTER,
+            Box::new(block_filter),
+            Arc::clone(&network_state),
+            blocking_recv_flag,
+        )
+        .with_identify_flag(IdentifyFlag::BlockFilter),
     ];
+    if light_client_server {
+        protocols.push(
+            CKBProtocol::new(
+                "lcl".to_string(),
+                NetworkProtocol::LIGHT_CLIENT.into(),
+                &["1".to_string()][..],
+                MAX_FRAME_LENGTH_LIGHT_CLIENT,
+                Box::new(LightClientProtocol::new(shared.clone())),
+                Arc::clone(&network_state),
+                blocking_recv_flag,
+            )
+            .with_identify_flag(IdentifyFlag::LightClientServer),
+        );
+    }
+    if tx_reconciliation {
+        protocols.push(
+            CKBProtocol::new(
+                "rec".to_string(),
+                NetworkProtocol::TX_RECONCILIATION.into(),
+                &["1".to_string()][..],
+                MAX_FRAME_LENGTH_RELAY,
+                Box::new(TxReconciliationProtocol::new(Arc::clone(&sync_shared))),
+                Arc::clone(&network_state),
+                blocking_recv_flag,
+            )
+            .with_identify_flag(IdentifyFlag::TxReconciliation),
+        );
+    }
 
     let required_protocol_ids = vec![NetworkProtocol::SYNC.into()];
 
@@ -142,15 +239,55 @@ pub fn run(args: RunArgs, version: Version) -> Result<(), ExitCode> {
         version.to_string(),
         Arc::<(Mutex<()>, Condvar)>::clone(&exit_condvar),
     )
-    .start(version, Some("NetworkService"))
+    .start(version.clone(), Some("NetworkService"))
     .expect("Start network service failed");
 
+    let health = HealthRegistry::new();
+    register_health_checks(
+        &health,
+        &shared,
+        &sync_shared,
+        &network_controller,
+        miner_enable,
+    );
+    health.start_self_diagnostic(args.config.health.interval);
+    install_crash_report(&running_config.lock(), &version, health.clone());
+
+    let memory_estimators = MemoryEstimators::new();
+    register_memory_estimators(
+        &memory_estimators,
+        &shared,
+        &sync_shared,
+        &network_controller,
+    );
+    memory_estimators.track(args.config.memory_tracker.interval);
+
+    if let Some(stratum_config) = args.config.stratum.clone() {
+        if miner_enable {
+            StratumServer::new(
+                stratum_config,
+                shared.clone(),
+                chain_controller.clone(),
+                network_controller.clone(),
+            )
+            .start()
+            .map_err(|err| {
+                eprintln!("Start stratum server error: {}", err);
+                ExitCode::Failure
+            })?;
+        } else {
+            warn_target!(
+                crate::LOG_TARGET_MAIN,
+                "Stratum server is disabled because the miner is disabled"
+            );
+        }
+    }
+
     let builder = ServiceBuilder::new(&args.config.rpc)
         .enable_chain(shared.clone())
         .enable_pool(
             shared.clone(),
-            sync_shared,
-            args.config.tx_pool.min_fee_rate,
+            Arc::clone(&sync_shared),
             args.config.rpc.reject_ill_transactions,
         )
         .enable_miner(
@@ -159,24 +296,308 @@ pub fn run(args: RunArgs, version: Version) -> Result<(), ExitCode> {
             chain_controller.clone(),
             miner_enable,
         )
-        .enable_net(network_controller.clone())
-        .enable_stats(shared.clone(), synchronizer, Arc::clone(&alert_notifier))
+        .enable_net(network_controller.clone(), Arc::clone(&sync_shared))
+        .enable_stats(
+            shared.clone(),
+            synchronizer,
+            Arc::clone(&alert_notifier),
+            health,
+            memory_estimators,
+        )
         .enable_experiment(shared.clone())
-        .enable_integration_test(shared.clone(), network_controller.clone(), chain_controller)
-        .enable_alert(alert_verifier, alert_notifier, network_controller)
+        .enable_integration_test(
+            shared.clone(),
+            network_controller.clone(),
+            chain_controller.clone(),
+            clock,
+        )
+        .enable_alert(alert_verifier, alert_notifier, network_controller.clone())
         .enable_indexer(&args.config.indexer, shared.clone())
-        .enable_debug();
+        .enable_debug(shared.clone(), chain_controller.clone());
     let io_handler = builder.build();
 
-    let _rpc_server = RpcServer::new(args.config.rpc, io_handler, shared.notify_controller());
+    let rpc_server = RpcServer::new(args.config.rpc, io_handler, shared.notify_controller());
 
-    wait_for_exit(exit_condvar);
+    let root_dir = args.root_dir;
+    let reload_shared = shared.clone();
+    let reload_network_controller = network_controller.clone();
+    let dump_shared = shared.clone();
+    let dump_network_controller = network_controller.clone();
+    wait_for_exit_or_signals(
+        exit_condvar,
+        SignalHandlers {
+            reload: Some(Box::new(move || {
+                ckb_logger::reopen_log_file();
+                reload_config(
+                    &root_dir,
+                    &running_config,
+                    &reload_shared,
+                    &reload_network_controller,
+                )
+            })),
+            dump: Some(Box::new(move || {
+                dump_state(&dump_shared, &sync_shared, &dump_network_controller)
+            })),
+        },
+    );
 
     info_target!(crate::LOG_TARGET_MAIN, "Finishing work, please wait...");
 
+    let mut coordinator = ShutdownCoordinator::new(SHUTDOWN_STAGE_TIMEOUT);
+    coordinator.register(ShutdownStage::Rpc, "RpcServer", move || rpc_server.close());
+    let protocols_network_controller = network_controller.clone();
+    coordinator.register(ShutdownStage::Sync, "NetworkProtocols", move || {
+        protocols_network_controller.stop_protocols()
+    });
+    coordinator.register_handler(
+        ShutdownStage::TxPool,
+        "VerifyCachePersister",
+        shared.verify_cache_persister_stop_handler(),
+    );
+    coordinator.register_handler(
+        ShutdownStage::TxPool,
+        "TxPoolService",
+        shared.tx_pool_controller().stop_handler(),
+    );
+    if let Some(scripts_backfill) = scripts_backfill {
+        coordinator.register_handler(ShutdownStage::Chain, "ScriptsBackfill", scripts_backfill);
+    }
+    if let Some(block_pruner) = block_pruner {
+        coordinator.register_handler(ShutdownStage::Chain, "BlockPruner", block_pruner);
+    }
+    coordinator.register_handler(
+        ShutdownStage::Chain,
+        "ChainService",
+        chain_controller.stop_handler(),
+    );
+    coordinator.register_handler(
+        ShutdownStage::Chain,
+        "NotifyService",
+        shared.notify_controller().stop_handler(),
+    );
+    coordinator.register_handler(
+        ShutdownStage::Network,
+        "NetworkService",
+        network_controller.stop_handler(),
+    );
+    let store_shared = shared.clone();
+    coordinator.register(ShutdownStage::Store, "TxsVerifyCache", move || {
+        if let Err(err) = store_shared.persist_txs_verify_cache() {
+            error_target!(
+                crate::LOG_TARGET_MAIN,
+                "persist tx verify cache error {}",
+                err
+            );
+        }
+    });
+    let flush_shared = shared.clone();
+    coordinator.register(ShutdownStage::Store, "FlushStore", move || {
+        if let Err(err) = flush_shared.store().db().flush() {
+            error_target!(crate::LOG_TARGET_MAIN, "flush the store error {}", err);
+        }
+    });
+    if !coordinator.shutdown() {
+        warn_target!(
+            crate::LOG_TARGET_MAIN,
+            "Some services were not stopped cleanly"
+        );
+    }
+
     Ok(())
 }
 
+fn load_tx_pool(shared: &Shared, path: &Path) {
+    match shared.tx_pool_controller().load_pool(path.to_path_buf()) {
+        Ok(Ok((accepted, rejected))) => info_target!(
+            crate::LOG_TARGET_MAIN,
+            "tx pool reloaded from {}, {} txs accepted, {} txs rejected",
+            path.display(),
+            accepted,
+            rejected
+        ),
+        Ok(Err(err)) => error_target!(
+            crate::LOG_TARGET_MAIN,
+            "reload tx pool from {} error {}",
+            path.display(),
+            err
+        ),
+        Err(err) => error_target!(crate::LOG_TARGET_MAIN, "reload tx pool error {}", err),
+    }
+}
+
+fn register_health_checks(
+    health: &HealthRegistry,
+    shared: &Shared,
+    sync_shared: &Arc<SyncShared>,
+    network_controller: &NetworkController,
+    miner_enable: bool,
+) {
+    let store_shared = shared.clone();
+    health.register("store", move || {
+        match store_shared.store().get_tip_header() {
+            Some(tip) => ServiceHealth::healthy().detail("tip", tip.number()),
+            None => ServiceHealth::unhealthy("tip header is not found"),
+        }
+    });
+
+    let chain_sync_shared = Arc::clone(sync_shared);
+    health.register("chain", move || {
+        let active_chain = chain_sync_shared.active_chain();
+        let tip = active_chain.tip_header();
+        let tip_age = chain_sync_shared
+            .shared()
+            .clock()
+            .unix_time_as_millis()
+            .saturating_sub(tip.timestamp())
+            / 1000;
+        let health = if active_chain.is_initial_block_download() {
+            ServiceHealth::degraded("initial block download")
+        } else {
+            ServiceHealth::healthy()
+        };
+        health
+            .detail("tip", tip.number())
+            .detail("tip_age_secs", tip_age)
+            .detail(
+                "best_known",
+                active_chain.shared().state().shared_best_header().number(),
+            )
+    });
+
+    let tx_pool_controller = shared.tx_pool_controller().clone();
+    health.register("tx-pool", move || {
+        match tx_pool_controller.get_tx_pool_info() {
+            Ok(info) => ServiceHealth::healthy()
+                .detail("pending", info.pending_size)
+                .detail("proposed", info.proposed_size)
+                .detail("orphan", info.orphan_size)
+                .detail("total_tx_size", info.total_tx_size),
+            Err(err) => ServiceHealth::unhealthy(format!("service is not responding: {}", err)),
+        }
+    });
+
+    let network_controller = network_controller.clone();
+    health.register("network", move || {
+        let peers = network_controller.connected_peers();
+        let outbound = peers.iter().filter(|(_, peer)| peer.is_outbound()).count();
+        let health = if peers.is_empty() {
+            ServiceHealth::degraded("no connected peers")
+        } else {
+            ServiceHealth::healthy()
+        };
+        health
+            .detail("peers", peers.len())
+            .detail("outbound", outbound)
+    });
+
+    health.register("miner", move || {
+        if miner_enable {
+            ServiceHealth::healthy().detail("block_assembler", "enabled")
+        } else {
+            ServiceHealth::healthy().detail("block_assembler", "disabled")
+        }
+    });
+}
+
+fn register_memory_estimators(
+    estimators: &MemoryEstimators,
+    shared: &Shared,
+    sync_shared: &Arc<SyncShared>,
+    network_controller: &NetworkController,
+) {
+    let tx_pool_controller = shared.tx_pool_controller().clone();
+    estimators.register("tx_pool", move || {
+        tx_pool_controller
+            .get_tx_pool_info()
+            .map(|info| (info.pending_size + info.proposed_size, info.total_tx_size))
+            .unwrap_or_default()
+    });
+    let tx_pool_controller = shared.tx_pool_controller().clone();
+    estimators.register("tx_pool_orphan", move || {
+        tx_pool_controller
+            .get_tx_pool_info()
+            .map(|info| (info.orphan_size, info.orphan_tx_size))
+            .unwrap_or_default()
+    });
+
+    let state_sync_shared = Arc::clone(sync_shared);
+    estimators.register("sync_orphan_blocks", move || {
+        state_sync_shared.state().orphan_block_pool_memory()
+    });
+    let state_sync_shared = Arc::clone(sync_shared);
+    estimators.register("sync_header_map", move || {
+        state_sync_shared.state().header_map_memory()
+    });
+    let state_sync_shared = Arc::clone(sync_shared);
+    estimators.register("sync_block_status", move || {
+        state_sync_shared.state().block_status_map_memory()
+    });
+
+    let network_controller = network_controller.clone();
+    estimators.register("peer_store", move || network_controller.peer_store_memory());
+}
+
+// Logs the snapshots of the peers, the tx pool and the sync status, on SIGUSR1.
+fn dump_state(shared: &Shared, sync_shared: &SyncShared, network_controller: &NetworkController) {
+    let peers = network_controller.connected_peers();
+    info_target!(
+        crate::LOG_TARGET_MAIN,
+        "dump peers: {} connected",
+        peers.len()
+    );
+    for (peer_id, peer) in peers {
+        info_target!(
+            crate::LOG_TARGET_MAIN,
+            "dump peer {}: session {}, {}, address {}, connected {:?}, ping {:?}",
+            peer_id.to_base58(),
+            peer.session_id,
+            if peer.is_outbound() {
+                "outbound"
+            } else {
+                "inbound"
+            },
+            peer.connected_addr,
+            peer.connected_time.elapsed(),
+            peer.ping,
+        );
+    }
+
+    match shared.tx_pool_controller().get_tx_pool_info() {
+        Ok(info) => info_target!(
+            crate::LOG_TARGET_MAIN,
+            "dump tx pool: pending {}, proposed {}, orphan {}, total_tx_size {}, \
+             total_tx_cycles {}, last_txs_updated_at {}",
+            info.pending_size,
+            info.proposed_size,
+            info.orphan_size,
+            info.total_tx_size,
+            info.total_tx_cycles,
+            info.last_txs_updated_at,
+        ),
+        Err(err) => error_target!(crate::LOG_TARGET_MAIN, "dump tx pool error: {}", err),
+    }
+
+    let active_chain = sync_shared.active_chain();
+    let tip = active_chain.tip_header();
+    let state = sync_shared.state();
+    let best_known = state.shared_best_header();
+    info_target!(
+        crate::LOG_TARGET_MAIN,
+        "dump sync: tip {} {:#x}, best known {} {:#x}, ibd {}, sync started {}, \
+         inflight blocks {}, orphan blocks {}",
+        tip.number(),
+        tip.hash(),
+        best_known.number(),
+        best_known.hash(),
+        active_chain.is_initial_block_download(),
+        state
+            .n_sync_started()
+            .load(std::sync::atomic::Ordering::Relaxed),
+        state.read_inflight_blocks().total_inflight_count(),
+        state.orphan_block_pool_memory().0,
+    );
+}
+
 fn verify_genesis(shared: &Shared) -> Result<(), ExitCode> {
     GenesisVerifier::new()
         .verify(shared.consensus())
@@ -248,3 +669,94 @@ fn sanitize_block_assembler_config(
     };
     Ok(block_assembler_config)
 }
+
+// Applies the reload-safe changes in the config file, and reports the others which take effect
+// after restart.
+fn reload_config(
+    root_dir: &Path,
+    running: &Mutex<CKBAppConfig>,
+    shared: &Shared,
+    network_controller: &NetworkController,
+) {
+    let reloaded = match AppConfig::load_for_subcommand(root_dir, cli::CMD_RUN)
+        .and_then(|app_config| app_config.into_ckb())
+    {
+        Ok(reloaded) => reloaded,
+        Err(err) => {
+            error_target!(crate::LOG_TARGET_MAIN, "reload config error: {:?}", err);
+            return;
+        }
+    };
+
+    let mut running = running.lock();
+    let changes = running.diff(&reloaded);
+    if changes.reloadable.is_empty() && changes.immutable.is_empty() {
+        info_target!(crate::LOG_TARGET_MAIN, "reload config: nothing changed");
+        return;
+    }
+    for option in &changes.immutable {
+        warn_target!(
+            crate::LOG_TARGET_MAIN,
+            "reload config: reject the change of {}, restart to apply it",
+            option
+        );
+    }
+
+    if changes
+        .reloadable
+        .iter()
+        .any(|option| option == "logger.filter")
+    {
+        running.logger.filter = reloaded.logger.filter.clone();
+        configure_logger_filter(running.logger.filter.as_deref().unwrap_or_default());
+    }
+    if changes
+        .reloadable
+        .iter()
+        .any(|option| option == "logger.color" || option == "logger.log_to_stdout")
+    {
+        running.logger.color = reloaded.logger.color;
+        running.logger.log_to_stdout = reloaded.logger.log_to_stdout;
+        update_main_logger(
+            Some(running.logger.log_to_stdout),
+            None,
+            Some(running.logger.color),
+        );
+    }
+    if changes
+        .reloadable
+        .iter()
+        .any(|option| option.starts_with("network."))
+    {
+        if let Err(err) = network_controller.update_whitelist_peers(&reloaded.network) {
+            error_target!(
+                crate::LOG_TARGET_MAIN,
+                "reload config: update whitelist peers error: {}",
+                err
+            );
+            return;
+        }
+        running.network.whitelist_peers = reloaded.network.whitelist_peers.clone();
+        running.network.whitelist = reloaded.network.whitelist.clone();
+    }
+    if changes
+        .reloadable
+        .iter()
+        .any(|option| option.starts_with("tx_pool."))
+    {
+        if let Err(err) = shared.tx_pool_controller().update_config(reloaded.tx_pool) {
+            error_target!(
+                crate::LOG_TARGET_MAIN,
+                "reload config: update tx pool error: {}",
+                err
+            );
+            return;
+        }
+        running.tx_pool = shared.tx_pool_controller().config();
+    }
+    info_target!(
+        crate::LOG_TARGET_MAIN,
+        "reload config: applied {}",
+        changes.reloadable.join(", ")
+    );
+}
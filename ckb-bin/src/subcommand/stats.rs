@@ -1,11 +1,13 @@
 use ckb_app_config::{ExitCode, StatsArgs};
+use ckb_instrument::ChainIterator;
 use ckb_shared::shared::{Shared, SharedBuilder};
 use ckb_store::ChainStore;
-use ckb_types::core::BlockNumber;
+use ckb_types::core::{BlockNumber, EpochNumber};
 
 pub fn stats(args: StatsArgs) -> Result<(), ExitCode> {
     let stats = Statics::build(args)?;
     stats.print_uncle_rate()?;
+    stats.print_chain_stats()?;
     Ok(())
 }
 
@@ -15,6 +17,14 @@ struct Statics {
     to: BlockNumber,
 }
 
+struct EpochStats {
+    number: EpochNumber,
+    length: BlockNumber,
+    blocks: BlockNumber,
+    start_timestamp: u64,
+    end_timestamp: u64,
+}
+
 impl Statics {
     pub fn build(args: StatsArgs) -> Result<Self, ExitCode> {
         let (shared, _) = SharedBuilder::with_db_config(&args.config.db)
@@ -60,4 +70,93 @@ impl Statics {
         );
         Ok(())
     }
+
+    // exclusively below and above inclusively (from..to], cellbases are not counted as
+    // transactions
+    pub fn print_chain_stats(&self) -> Result<(), ExitCode> {
+        let store = self.shared.store();
+        let from_timestamp = store
+            .get_block_hash(self.from)
+            .and_then(|hash| store.get_block_header(&hash))
+            .map(|header| header.timestamp())
+            .ok_or_else(|| ExitCode::IO)?;
+
+        let mut to_timestamp = from_timestamp;
+        let mut block_nums = 0u64;
+        let mut txs_count = 0u64;
+        let mut txs_fees = 0u64;
+        let mut epochs: Vec<EpochStats> = Vec::new();
+        for block in ChainIterator::range(self.shared.clone(), self.from + 1, self.to) {
+            let header = block.header();
+            block_nums += 1;
+            txs_count += block.transactions().len().saturating_sub(1) as u64;
+            txs_fees += store
+                .get_block_ext(&header.hash())
+                .map(|ext| ext.txs_fees.iter().map(|fee| fee.as_u64()).sum::<u64>())
+                .unwrap_or_default();
+            to_timestamp = header.timestamp();
+
+            let epoch = header.epoch();
+            match epochs.last_mut() {
+                Some(stats) if stats.number == epoch.number() => {
+                    stats.blocks += 1;
+                    stats.end_timestamp = header.timestamp();
+                }
+                _ => epochs.push(EpochStats {
+                    number: epoch.number(),
+                    length: epoch.length(),
+                    blocks: 1,
+                    start_timestamp: header.timestamp(),
+                    end_timestamp: header.timestamp(),
+                }),
+            }
+        }
+        if block_nums != self.to - self.from {
+            eprintln!(
+                "Stats error: expect {} blocks, found {}",
+                self.to - self.from,
+                block_nums
+            );
+            return Err(ExitCode::IO);
+        }
+
+        let seconds = to_timestamp.saturating_sub(from_timestamp) as f64 / 1000f64;
+        println!("transactions: {}", txs_count);
+        println!(
+            "transactions_per_block: {}",
+            txs_count as f64 / block_nums as f64
+        );
+        println!(
+            "average_fee: {} shannons",
+            if txs_count == 0 {
+                0f64
+            } else {
+                txs_fees as f64 / txs_count as f64
+            }
+        );
+        println!(
+            "tps: {}",
+            if seconds > 0f64 {
+                txs_count as f64 / seconds
+            } else {
+                0f64
+            }
+        );
+        for stats in epochs {
+            let duration =
+                stats.end_timestamp.saturating_sub(stats.start_timestamp) as f64 / 1000f64;
+            if stats.blocks == stats.length {
+                println!(
+                    "epoch {}: length {}, duration {}s",
+                    stats.number, stats.length, duration
+                );
+            } else {
+                println!(
+                    "epoch {}: length {}, duration {}s (partial, {} blocks in range)",
+                    stats.number, stats.length, duration, stats.blocks
+                );
+            }
+        }
+        Ok(())
+    }
 }
@@ -2,13 +2,16 @@ use crate::migration::Migrations;
 use crate::snapshot::RocksDBSnapshot;
 use crate::transaction::RocksDBTransaction;
 use crate::{internal_error, Col, Result};
-use ckb_app_config::DBConfig;
+use ckb_app_config::{ColumnFamilyConfig, CompactionStyle, DBConfig};
 use ckb_logger::{info, warn};
+use libc::c_char;
 use rocksdb::ops::{GetColumnFamilys, GetPinnedCF, IterateCF, OpenCF, SetOptions};
 use rocksdb::{
-    ffi, ColumnFamily, DBPinnableSlice, IteratorMode, OptimisticTransactionDB,
-    OptimisticTransactionOptions, Options, WriteOptions,
+    ffi, ffi_util, BlockBasedOptions, ColumnFamily, ColumnFamilyDescriptor, DBCompactionStyle,
+    DBPinnableSlice, Handle, IteratorMode, OptimisticTransactionDB, OptimisticTransactionOptions,
+    Options, WriteOptions,
 };
+use std::ptr;
 use std::sync::Arc;
 
 pub const VERSION_KEY: &str = "db-version";
@@ -16,6 +19,8 @@ pub const VERSION_KEY: &str = "db-version";
 #[derive(Clone)]
 pub struct RocksDB {
     pub(crate) inner: Arc<OptimisticTransactionDB>,
+    columns: u32,
+    sync_wal: bool,
 }
 
 impl RocksDB {
@@ -27,47 +32,65 @@ impl RocksDB {
         let mut opts = Options::default();
         opts.create_if_missing(false);
         opts.create_missing_column_families(true);
+        opts.set_enable_pipelined_write(config.enable_pipelined_write);
+        if let Some(max_open_files) = config.max_open_files {
+            opts.set_max_open_files(max_open_files);
+        }
 
-        let cfnames: Vec<_> = (0..columns).map(|c| c.to_string()).collect();
-        let cf_options: Vec<&str> = cfnames.iter().map(|n| n as &str).collect();
+        // The descriptors are consumed by each open
+        let cf_descriptors = || {
+            (0..columns).map(|c| {
+                let name = c.to_string();
+                let cf_opts = cf_options(config.column_family(&name));
+                ColumnFamilyDescriptor::new(name, cf_opts)
+            })
+        };
 
         let db =
-            OptimisticTransactionDB::open_cf(&opts, &config.path, &cf_options).or_else(|err| {
-                let err_str = err.as_ref();
-                if err_str.starts_with("Invalid argument:")
-                    && err_str.ends_with("does not exist (create_if_missing is false)")
-                {
-                    info!("Initialize a new database");
-                    opts.create_if_missing(true);
-                    let db = OptimisticTransactionDB::open_cf(&opts, &config.path, &cf_options)
+            OptimisticTransactionDB::open_cf_descriptors(&opts, &config.path, cf_descriptors())
+                .or_else(|err| {
+                    let err_str = err.as_ref();
+                    if err_str.starts_with("Invalid argument:")
+                        && err_str.ends_with("does not exist (create_if_missing is false)")
+                    {
+                        info!("Initialize a new database");
+                        opts.create_if_missing(true);
+                        let db = OptimisticTransactionDB::open_cf_descriptors(
+                            &opts,
+                            &config.path,
+                            cf_descriptors(),
+                        )
                         .map_err(|err| {
                             internal_error(format!(
                                 "failed to open a new created database: {}",
                                 err
                             ))
                         })?;
-                    Ok(db)
-                } else if err.as_ref().starts_with("Corruption:") {
-                    warn!("Repairing the rocksdb since {} ...", err);
-                    let mut repair_opts = Options::default();
-                    repair_opts.create_if_missing(false);
-                    repair_opts.create_missing_column_families(false);
-                    OptimisticTransactionDB::repair(repair_opts, &config.path).map_err(|err| {
-                        internal_error(format!("failed to repair the database: {}", err))
-                    })?;
-                    warn!("Opening the repaired rocksdb ...");
-                    OptimisticTransactionDB::open_cf(&opts, &config.path, &cf_options).map_err(
-                        |err| {
+                        Ok(db)
+                    } else if err.as_ref().starts_with("Corruption:") {
+                        warn!("Repairing the rocksdb since {} ...", err);
+                        let mut repair_opts = Options::default();
+                        repair_opts.create_if_missing(false);
+                        repair_opts.create_missing_column_families(false);
+                        OptimisticTransactionDB::repair(repair_opts, &config.path).map_err(
+                            |err| internal_error(format!("failed to repair the database: {}", err)),
+                        )?;
+                        warn!("Opening the repaired rocksdb ...");
+                        OptimisticTransactionDB::open_cf_descriptors(
+                            &opts,
+                            &config.path,
+                            cf_descriptors(),
+                        )
+                        .map_err(|err| {
                             internal_error(format!("failed to open the repaired database: {}", err))
-                        },
-                    )
-                } else {
-                    Err(internal_error(format!(
-                        "failed to open the database: {}",
-                        err
-                    )))
-                }
-            })?;
+                        })
+                    } else {
+                        Err(internal_error(format!(
+                            "failed to open the database: {}",
+                            err
+                        )))
+                    }
+                })?;
 
         if let Some(db_opt) = config.options.as_ref() {
             let rocksdb_options: Vec<(&str, &str)> = db_opt
@@ -80,6 +103,8 @@ impl RocksDB {
 
         let rocks_db = RocksDB {
             inner: Arc::new(db),
+            columns,
+            sync_wal: config.sync_wal,
         };
 
         migrations.migrate(&rocks_db)?;
@@ -123,7 +148,8 @@ impl RocksDB {
 
     /// Set a snapshot at start of transaction by setting set_snapshot=true
     pub fn transaction(&self) -> RocksDBTransaction {
-        let write_options = WriteOptions::default();
+        let mut write_options = WriteOptions::default();
+        write_options.set_sync(self.sync_wal);
         let mut transaction_options = OptimisticTransactionOptions::new();
         transaction_options.set_snapshot(true);
 
@@ -140,11 +166,70 @@ impl RocksDB {
         }
     }
 
+    /// Flushes the memtables of all the columns and waits for it, so the writes do not have to
+    /// be replayed from the WAL at the next start.
+    pub fn flush(&self) -> Result<()> {
+        unsafe {
+            let opts = ffi::rocksdb_flushoptions_create();
+            ffi::rocksdb_flushoptions_set_wait(opts, 1);
+            let mut result = Ok(());
+            for c in 0..self.columns {
+                let name = c.to_string();
+                let cf = match self.inner.cf_handle(&name) {
+                    Some(cf) => cf,
+                    None => {
+                        result = Err(internal_error(format!("column {} not found", name)));
+                        break;
+                    }
+                };
+                let mut err: *mut c_char = ptr::null_mut();
+                ffi::rocksdb_flush_cf(self.inner.base_db_ptr(), opts, cf.handle(), &mut err);
+                if !err.is_null() {
+                    result = Err(internal_error(format!(
+                        "failed to flush column {}: {}",
+                        name,
+                        ffi_util::error_message(err)
+                    )));
+                    break;
+                }
+            }
+            ffi::rocksdb_flushoptions_destroy(opts);
+            result
+        }
+    }
+
     pub fn inner(&self) -> Arc<OptimisticTransactionDB> {
         Arc::clone(&self.inner)
     }
 }
 
+fn cf_options(config: Option<&ColumnFamilyConfig>) -> Options {
+    let mut opts = Options::default();
+    let config = match config {
+        Some(config) => config,
+        None => return opts,
+    };
+    if let Some(compaction_style) = config.compaction_style {
+        opts.set_compaction_style(match compaction_style {
+            CompactionStyle::Level => DBCompactionStyle::Level,
+            CompactionStyle::Universal => DBCompactionStyle::Universal,
+            CompactionStyle::Fifo => DBCompactionStyle::Fifo,
+        });
+    }
+    if config.block_cache_size.is_some() || config.bloom_filter_bits_per_key.is_some() {
+        let mut block_opts = BlockBasedOptions::default();
+        if let Some(block_cache_size) = config.block_cache_size {
+            block_opts.set_lru_cache(block_cache_size);
+        }
+        if let Some(bits_per_key) = config.bloom_filter_bits_per_key {
+            // the full filters rather than the block based ones
+            block_opts.set_bloom_filter(bits_per_key, false);
+        }
+        opts.set_block_based_table_factory(&block_opts);
+    }
+    opts
+}
+
 pub(crate) fn cf_handle(db: &OptimisticTransactionDB, col: Col) -> Result<&ColumnFamily> {
     db.cf_handle(col)
         .ok_or_else(|| internal_error(format!("column {} not found", col)))
@@ -152,7 +237,7 @@ pub(crate) fn cf_handle(db: &OptimisticTransactionDB, col: Col) -> Result<&Colum
 
 #[cfg(test)]
 mod tests {
-    use super::{DBConfig, Result, RocksDB, VERSION_KEY};
+    use super::{ColumnFamilyConfig, CompactionStyle, DBConfig, Result, RocksDB, VERSION_KEY};
     use crate::migration::{DefaultMigration, Migration, Migrations};
     use rocksdb::ops::Get;
     use std::collections::HashMap;
@@ -184,10 +269,56 @@ mod tests {
                 opts.insert("disable_auto_compactions".to_owned(), "true".to_owned());
                 opts
             }),
+            ..Default::default()
         };
         RocksDB::open(&config, 2, Migrations::default()); // no panic
     }
 
+    #[test]
+    fn test_column_family_options() {
+        let tmp_dir = tempfile::Builder::new()
+            .prefix("test_column_family_options")
+            .tempdir()
+            .unwrap();
+        let mut config = DBConfig {
+            path: tmp_dir.as_ref().to_path_buf(),
+            max_open_files: Some(256),
+            enable_pipelined_write: true,
+            sync_wal: true,
+            ..Default::default()
+        };
+        config.column_families.insert(
+            "default".to_owned(),
+            ColumnFamilyConfig {
+                block_cache_size: Some(1 << 20),
+                bloom_filter_bits_per_key: Some(10),
+                ..Default::default()
+            },
+        );
+        config.column_families.insert(
+            "1".to_owned(),
+            ColumnFamilyConfig {
+                compaction_style: Some(CompactionStyle::Universal),
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            config.column_family("0").and_then(|cf| cf.block_cache_size),
+            Some(1 << 20)
+        );
+        assert_eq!(
+            config.column_family("1").and_then(|cf| cf.block_cache_size),
+            None
+        );
+
+        let db = RocksDB::open(&config, 2, Migrations::default());
+        let txn = db.transaction();
+        txn.put("0", &[0], &[0]).unwrap();
+        txn.put("1", &[1], &[1]).unwrap();
+        txn.commit().unwrap();
+        assert_eq!(db.get_pinned("1", &[1]).unwrap().unwrap().as_ref(), &[1]);
+    }
+
     #[test]
     #[should_panic]
     fn test_panic_on_invalid_rocksdb_options() {
@@ -202,6 +333,7 @@ mod tests {
                 opts.insert("letsrock".to_owned(), "true".to_owned());
                 opts
             }),
+            ..Default::default()
         };
         RocksDB::open(&config, 2, Migrations::default()); // panic
     }
@@ -280,6 +412,38 @@ mod tests {
         assert!(vec![4u8, 3, 2].as_slice() == &ret.as_ref()[1..4]);
     }
 
+    #[test]
+    fn flush_all_columns() {
+        let tmp_dir = tempfile::Builder::new()
+            .prefix("flush_all_columns")
+            .tempdir()
+            .unwrap();
+        let config = DBConfig {
+            path: tmp_dir.as_ref().to_path_buf(),
+            ..Default::default()
+        };
+
+        {
+            let db = RocksDB::open_with_check(&config, 2, Migrations::default()).unwrap();
+            let txn = db.transaction();
+            txn.put("0", &[0, 0], &[0, 0, 0]).unwrap();
+            txn.put("1", &[1, 1], &[1, 1, 1]).unwrap();
+            txn.commit().unwrap();
+            db.flush().unwrap();
+        }
+        {
+            let db = RocksDB::open_with_check(&config, 2, Migrations::default()).unwrap();
+            assert!(
+                vec![0u8, 0, 0].as_slice()
+                    == db.get_pinned("0", &[0, 0]).unwrap().unwrap().as_ref()
+            );
+            assert!(
+                vec![1u8, 1, 1].as_slice()
+                    == db.get_pinned("1", &[1, 1]).unwrap().unwrap().as_ref()
+            );
+        }
+    }
+
     #[test]
     fn test_default_migration() {
         let tmp_dir = tempfile::Builder::new()
@@ -1,7 +1,7 @@
 use crate::db::cf_handle;
 use crate::{
-    internal_error, Col, Result, RocksDB, RocksDBSnapshot, RocksDBTransaction,
-    RocksDBTransactionSnapshot,
+    internal_error, secondary_db, Col, Result, RocksDB, RocksDBSnapshot, RocksDBTransaction,
+    RocksDBTransactionSnapshot, SecondaryDB,
 };
 use rocksdb::{ops::IterateCF, ReadOptions};
 pub use rocksdb::{DBIterator as DBIter, Direction, IteratorMode};
@@ -51,3 +51,12 @@ impl DBIterator for RocksDBSnapshot {
             .map_err(internal_error)
     }
 }
+
+impl DBIterator for SecondaryDB {
+    fn iter_opt(&self, col: Col, mode: IteratorMode, readopts: &ReadOptions) -> Result<DBIter> {
+        let cf = secondary_db::cf_handle(&self.inner, col)?;
+        self.inner
+            .iterator_cf_opt(cf, mode, readopts)
+            .map_err(internal_error)
+    }
+}
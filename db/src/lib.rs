@@ -10,12 +10,14 @@ use std::result;
 pub mod db;
 pub mod iter;
 mod migration;
+pub mod secondary_db;
 pub mod snapshot;
 pub mod transaction;
 
 pub use crate::db::RocksDB;
 pub use crate::iter::DBIterator;
 pub use crate::migration::{DefaultMigration, Migration, Migrations};
+pub use crate::secondary_db::SecondaryDB;
 pub use crate::snapshot::RocksDBSnapshot;
 pub use crate::transaction::{RocksDBTransaction, RocksDBTransactionSnapshot};
 pub use rocksdb::{
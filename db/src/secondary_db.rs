@@ -0,0 +1,108 @@
+use crate::{internal_error, Col, Result};
+use ckb_app_config::DBConfig;
+use rocksdb::ops::{GetColumnFamilys, GetPinnedCF, OpenCF};
+use rocksdb::{
+    ColumnFamily, ColumnFamilyDescriptor, DBPinnableSlice, Options, SecondaryDB as RawSecondaryDB,
+    SecondaryOpenDescriptor,
+};
+use std::path::Path;
+use std::sync::Arc;
+
+/// The database opened as a RocksDB secondary instance of the one written by a running node.
+///
+/// It reads the files of the primary without locking them, and sees the new writes after
+/// `try_catch_up_with_primary`. The secondary keeps its own info logs in `secondary_path`, which
+/// must not be shared by the other secondary instances.
+#[derive(Clone)]
+pub struct SecondaryDB {
+    pub(crate) inner: Arc<RawSecondaryDB>,
+}
+
+impl SecondaryDB {
+    pub fn open_cf<P: AsRef<Path>>(
+        config: &DBConfig,
+        columns: u32,
+        secondary_path: P,
+    ) -> Result<Self> {
+        let mut opts = Options::default();
+        opts.create_if_missing(false);
+        opts.create_missing_column_families(false);
+        // the secondary instance requires all the files of the primary open
+        opts.set_max_open_files(-1);
+
+        let cf_descriptors = (0..columns)
+            .map(|c| ColumnFamilyDescriptor::new(c.to_string(), Options::default()))
+            .collect::<Vec<_>>();
+        let descriptor =
+            SecondaryOpenDescriptor::new(secondary_path.as_ref().to_string_lossy().into_owned());
+        let db = RawSecondaryDB::open_cf_descriptors_with_descriptor(
+            &opts,
+            &config.path,
+            cf_descriptors,
+            descriptor,
+        )
+        .map_err(|err| internal_error(format!("failed to open the secondary database: {}", err)))?;
+
+        Ok(SecondaryDB {
+            inner: Arc::new(db),
+        })
+    }
+
+    pub fn get_pinned(&self, col: Col, key: &[u8]) -> Result<Option<DBPinnableSlice>> {
+        let cf = cf_handle(&self.inner, col)?;
+        self.inner.get_pinned_cf(cf, &key).map_err(internal_error)
+    }
+
+    /// Replays the new WAL and MANIFEST entries of the primary, the reads afterwards see the
+    /// writes committed before it.
+    pub fn try_catch_up_with_primary(&self) -> Result<()> {
+        self.inner
+            .try_catch_up_with_primary()
+            .map_err(|err| internal_error(format!("failed to catch up with primary: {}", err)))
+    }
+}
+
+pub(crate) fn cf_handle(db: &RawSecondaryDB, col: Col) -> Result<&ColumnFamily> {
+    db.cf_handle(col)
+        .ok_or_else(|| internal_error(format!("column {} not found", col)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DBConfig, SecondaryDB};
+    use crate::migration::Migrations;
+    use crate::RocksDB;
+
+    #[test]
+    fn test_catch_up_with_primary() {
+        let tmp_dir = tempfile::Builder::new()
+            .prefix("test_catch_up_with_primary")
+            .tempdir()
+            .unwrap();
+        let config = DBConfig {
+            path: tmp_dir.path().join("primary"),
+            ..Default::default()
+        };
+        let primary = RocksDB::open(&config, 2, Migrations::default());
+        let txn = primary.transaction();
+        txn.put("1", &[1], &[1]).unwrap();
+        txn.commit().unwrap();
+
+        let secondary = SecondaryDB::open_cf(&config, 2, tmp_dir.path().join("secondary")).unwrap();
+        assert_eq!(
+            secondary.get_pinned("1", &[1]).unwrap().unwrap().as_ref(),
+            &[1]
+        );
+
+        let txn = primary.transaction();
+        txn.put("1", &[2], &[2]).unwrap();
+        txn.commit().unwrap();
+        assert!(secondary.get_pinned("1", &[2]).unwrap().is_none());
+
+        secondary.try_catch_up_with_primary().unwrap();
+        assert_eq!(
+            secondary.get_pinned("1", &[2]).unwrap().unwrap().as_ref(),
+            &[2]
+        );
+    }
+}
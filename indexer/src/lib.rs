@@ -1,6 +1,16 @@
 mod migrations;
+mod plugin;
+mod script_index;
+mod source;
 mod store;
 mod types;
+mod watch_list;
 
+pub use plugin::{IndexPlugin, PluginStore, PluginTransaction};
+pub use script_index::{
+    Order, Pagination, ScriptIndexer, ScriptKind, ScriptSearchMode, SearchFilter, SearchKey,
+};
+pub use source::{ChainSource, RemoteChainSource};
 pub use store::{DefaultIndexerStore, IndexerStore};
-pub use types::{CellTransaction, LiveCell, TransactionPoint};
+pub use types::{CellTransaction, CellsCapacity, LiveCell, TransactionPoint};
+pub use watch_list::WatchList;
@@ -8,6 +8,8 @@ use ckb_types::{
     prelude::*,
 };
 
+pub(crate) const ADD_FIELDS_TO_LIVE_CELL_VERSION: &str = "20191201091330";
+
 pub struct AddFieldsToLiveCell {
     shared: Shared,
 }
@@ -56,6 +58,6 @@ impl Migration for AddFieldsToLiveCell {
     }
 
     fn version(&self) -> &str {
-        "20191201091330"
+        ADD_FIELDS_TO_LIVE_CELL_VERSION
     }
 }
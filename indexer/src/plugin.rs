@@ -0,0 +1,117 @@
+use ckb_db::{db::RocksDB, Col, DBIterator, Direction, IteratorMode, RocksDBTransaction};
+use ckb_types::core::BlockView;
+use std::sync::Arc;
+
+/// +---------------+-------------------+-------+
+/// |    Column     |        Key        | Value |
+/// +---------------+-------------------+-------+
+/// | COLUMN_PLUGIN | PluginName + key  | bytes |
+/// +---------------+-------------------+-------+
+///
+/// PluginName layout: name_len (1) | name (n)
+pub(crate) const COLUMN_PLUGIN: Col = "9";
+
+/// An additional index built block by block by `ScriptIndexer`, such as NFT registries or UDT
+/// balances.
+///
+/// Plugins attach and detach blocks in the same transaction as the script index, so they
+/// always match the indexed tip. Register plugins before the indexer starts, a plugin added
+/// later only sees the blocks attached after it.
+pub trait IndexPlugin: Send + Sync {
+    /// The unique name which namespaces the keys of the plugin, at most 255 bytes.
+    fn name(&self) -> &str;
+
+    /// Indexes a block attached to the main chain.
+    fn attach_block(&self, txn: &PluginTransaction, block: &BlockView);
+
+    /// Reverts what `attach_block` did, blocks are detached from the tip downward.
+    fn detach_block(&self, txn: &PluginTransaction, block: &BlockView);
+}
+
+fn plugin_prefix(name: &str) -> Vec<u8> {
+    assert!(name.len() <= 255, "plugin name is too long");
+    let mut prefix = Vec::with_capacity(1 + name.len());
+    prefix.push(name.len() as u8);
+    prefix.extend_from_slice(name.as_bytes());
+    prefix
+}
+
+fn plugin_key(prefix: &[u8], key: &[u8]) -> Vec<u8> {
+    let mut plugin_key = Vec::with_capacity(prefix.len() + key.len());
+    plugin_key.extend_from_slice(prefix);
+    plugin_key.extend_from_slice(key);
+    plugin_key
+}
+
+/// The writes of a plugin in the indexer transaction, keys are scoped to the plugin.
+pub struct PluginTransaction<'a> {
+    txn: &'a RocksDBTransaction,
+    prefix: Vec<u8>,
+}
+
+impl<'a> PluginTransaction<'a> {
+    pub(crate) fn new(txn: &'a RocksDBTransaction, name: &str) -> Self {
+        PluginTransaction {
+            txn,
+            prefix: plugin_prefix(name),
+        }
+    }
+
+    /// Reads the value in the transaction, including the writes not committed yet.
+    pub fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.txn
+            .get(COLUMN_PLUGIN, &plugin_key(&self.prefix, key))
+            .expect("indexer db read should be ok")
+            .map(|value| value.to_vec())
+    }
+
+    pub fn put(&self, key: &[u8], value: &[u8]) {
+        self.txn
+            .put(COLUMN_PLUGIN, &plugin_key(&self.prefix, key), value)
+            .expect("txn insert COLUMN_PLUGIN failed");
+    }
+
+    pub fn delete(&self, key: &[u8]) {
+        self.txn
+            .delete(COLUMN_PLUGIN, &plugin_key(&self.prefix, key))
+            .expect("txn delete COLUMN_PLUGIN failed");
+    }
+}
+
+/// Read access to the index of a plugin, for serving queries.
+#[derive(Clone)]
+pub struct PluginStore {
+    db: Arc<RocksDB>,
+    prefix: Vec<u8>,
+}
+
+impl PluginStore {
+    pub(crate) fn new(db: Arc<RocksDB>, name: &str) -> Self {
+        PluginStore {
+            db,
+            prefix: plugin_prefix(name),
+        }
+    }
+
+    pub fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.db
+            .get_pinned(COLUMN_PLUGIN, &plugin_key(&self.prefix, key))
+            .expect("indexer db read should be ok")
+            .map(|value| value.to_vec())
+    }
+
+    /// Returns the entries whose keys start with `key_prefix` in key order, at most `limit`.
+    pub fn get_by_prefix(&self, key_prefix: &[u8], limit: usize) -> Vec<(Vec<u8>, Vec<u8>)> {
+        let from_key = plugin_key(&self.prefix, key_prefix);
+        self.db
+            .iter(
+                COLUMN_PLUGIN,
+                IteratorMode::From(&from_key, Direction::Forward),
+            )
+            .expect("indexer db iter should be ok")
+            .take_while(|(key, _)| key.starts_with(&from_key))
+            .take(limit)
+            .map(|(key, value)| (key[self.prefix.len()..].to_vec(), value.to_vec()))
+            .collect()
+    }
+}
@@ -0,0 +1,1549 @@
+use crate::migrations::ADD_FIELDS_TO_LIVE_CELL_VERSION;
+use crate::plugin::{IndexPlugin, PluginStore, PluginTransaction};
+use crate::source::ChainSource;
+use crate::store::{COLUMNS, INIT_DB_VERSION};
+use crate::types::{
+    CellTransaction, CellsCapacity, LiveCell, LockHashCapacity, LockHashCellOutput,
+    LockHashIndexState, TransactionPoint,
+};
+use ckb_app_config::IndexerConfig;
+use ckb_db::{
+    db::RocksDB, Col, DBIterator, DefaultMigration, Direction, IteratorMode, Migrations,
+    RocksDBTransaction,
+};
+use ckb_logger::{debug, error, trace};
+use ckb_types::{
+    bytes::Bytes,
+    core::{self, BlockNumber, Capacity, CapacityResult},
+    packed::{self, Byte32, CellOutput, LiveCellOutput, OutPoint, Script},
+    prelude::*,
+};
+use crossbeam_channel::Receiver;
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::sync::Arc;
+use std::thread;
+
+/// +---------------------------+------------------+--------------------------+
+/// |          Column           |       Key        |          Value           |
+/// +---------------------------+------------------+--------------------------+
+/// | COLUMN_SCRIPT_INDEX_TIP   | "tip"            | LockHashIndexState       |
+/// | COLUMN_SCRIPT_LIVE_CELL   | ScriptKind+Cell  | LiveCellOutput           |
+/// | COLUMN_SCRIPT_TRANSACTION | ScriptKind+Cell  | Option<TransactionPoint> |
+/// | COLUMN_OUT_POINT_SCRIPT   | OutPoint         | LockHashCellOutput       |
+/// +---------------------------+------------------+--------------------------+
+///
+/// ScriptKind+Cell key layout:
+/// kind (1) | code_hash (32) | hash_type (1) | args (n) | block_number (8, BE) | tx_hash (32) | index (4, BE)
+
+pub(crate) const COLUMN_SCRIPT_INDEX_TIP: Col = "4";
+pub(crate) const COLUMN_SCRIPT_LIVE_CELL: Col = "5";
+pub(crate) const COLUMN_SCRIPT_TRANSACTION: Col = "6";
+pub(crate) const COLUMN_OUT_POINT_SCRIPT: Col = "7";
+
+const TIP_KEY: &[u8] = b"tip";
+// block_number + tx_hash + index
+const CELL_KEY_SUFFIX_LEN: usize = 8 + 32 + 4;
+// Upper bound of the index entries a single page scans, the filters may skip most of them, so
+// the scan stops there and returns the cursor to continue
+const MAX_SCANNED_ENTRIES: usize = 10_000;
+
+/// Which script of a cell the index entry is keyed by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScriptKind {
+    Lock = 0,
+    Type = 1,
+}
+
+/// How `SearchKey::script` matches the indexed scripts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScriptSearchMode {
+    /// Matches the script exactly
+    Exact,
+    /// Matches the scripts with the same code hash and hash type, whose args start with the
+    /// args of the search script
+    Prefix,
+}
+
+impl Default for ScriptSearchMode {
+    fn default() -> Self {
+        ScriptSearchMode::Exact
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Order {
+    Asc,
+    Desc,
+}
+
+/// Additional conditions on the cells matched by the script, ranges are `[start, end)`.
+#[derive(Clone, Debug, Default)]
+pub struct SearchFilter {
+    pub output_data_prefix: Option<Bytes>,
+    pub output_capacity_range: Option<(Capacity, Capacity)>,
+    pub block_range: Option<(BlockNumber, BlockNumber)>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SearchKey {
+    pub script: Script,
+    pub script_kind: ScriptKind,
+    pub script_search_mode: ScriptSearchMode,
+    pub filter: SearchFilter,
+}
+
+/// A page of query results. Pass `last_cursor` as `after_cursor` to fetch the next page, it is
+/// empty once the search reaches the end. A page may hold fewer objects than the limit, even
+/// none, when the search stops after scanning `MAX_SCANNED_ENTRIES` entries.
+pub struct Pagination<T> {
+    pub objects: Vec<T>,
+    pub last_cursor: Vec<u8>,
+}
+
+/// ScriptIndexer indexes every cell on the main chain by its lock script hash and
+/// type script hash. Unlike the lock hash index of `DefaultIndexerStore`, it needs no
+/// registration and follows the chain tip of its `ChainSource`, which is either the local
+/// chain store or a remote node.
+#[derive(Clone)]
+pub struct ScriptIndexer {
+    db: Arc<RocksDB>,
+    source: Arc<dyn ChainSource>,
+    plugins: Vec<Arc<dyn IndexPlugin>>,
+}
+
+impl ScriptIndexer {
+    pub(crate) fn new(db: Arc<RocksDB>, source: Arc<dyn ChainSource>) -> Self {
+        ScriptIndexer {
+            db,
+            source,
+            plugins: Vec::new(),
+        }
+    }
+
+    /// Opens a database of its own for the standalone indexer, which follows a remote node.
+    ///
+    /// The lock hash index columns are left untouched, so don't share the database with
+    /// `DefaultIndexerStore`.
+    pub fn open(config: &IndexerConfig, source: Arc<dyn ChainSource>) -> Self {
+        let mut migrations = Migrations::default();
+        migrations.add_migration(Box::new(DefaultMigration::new(INIT_DB_VERSION)));
+        // the live cell migration only rewrites the lock hash index
+        migrations.add_migration(Box::new(DefaultMigration::new(
+            ADD_FIELDS_TO_LIVE_CELL_VERSION,
+        )));
+        let db = RocksDB::open(&config.db, COLUMNS, migrations);
+        ScriptIndexer::new(Arc::new(db), source)
+    }
+
+    /// Registers a plugin to run in the attach and detach pipeline, see `IndexPlugin`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a plugin with the same name is registered.
+    pub fn register_plugin(&mut self, plugin: Arc<dyn IndexPlugin>) {
+        assert!(
+            self.plugins.iter().all(|p| p.name() != plugin.name()),
+            "plugin {} is registered",
+            plugin.name()
+        );
+        self.plugins.push(plugin);
+    }
+
+    /// The index of the plugin named `name`.
+    pub fn plugin_store(&self, name: &str) -> PluginStore {
+        PluginStore::new(Arc::clone(&self.db), name)
+    }
+
+    /// Starts syncing in a new thread, which syncs once on start and then on every message
+    /// from `new_tip_receiver`, such as new block notifications or timer ticks.
+    pub fn start<S: ToString, T: Send + 'static>(
+        self,
+        thread_name: Option<S>,
+        new_tip_receiver: Receiver<T>,
+    ) {
+        let mut thread_builder = thread::Builder::new();
+        if let Some(name) = thread_name {
+            thread_builder = thread_builder.name(name.to_string());
+        }
+
+        thread_builder
+            .spawn(move || {
+                // catch up with the blocks attached before subscribing
+                self.sync();
+                while new_tip_receiver.recv().is_ok() {
+                    // one sync reaches the latest tip, skip the stale notifications
+                    while new_tip_receiver.try_recv().is_ok() {}
+                    self.sync();
+                }
+            })
+            .expect("start ScriptIndexer failed");
+    }
+
+    /// The last main chain block that has been indexed.
+    pub fn tip(&self) -> Option<LockHashIndexState> {
+        self.db
+            .get_pinned(COLUMN_SCRIPT_INDEX_TIP, TIP_KEY)
+            .expect("indexer db read should be ok")
+            .map(|value| {
+                LockHashIndexState::from_packed(
+                    packed::LockHashIndexStateReader::from_slice(&value)
+                        .expect("verify LockHashIndexState in storage should be ok"),
+                )
+            })
+    }
+
+    /// Returns the live cells matching the search key.
+    pub fn get_cells(
+        &self,
+        search_key: &SearchKey,
+        order: Order,
+        limit: usize,
+        after_cursor: Option<&[u8]>,
+    ) -> Pagination<LiveCell> {
+        self.search(
+            COLUMN_SCRIPT_LIVE_CELL,
+            search_key,
+            order,
+            limit,
+            after_cursor,
+            |created_by, value| {
+                let live_cell_output = live_cell_output(value);
+                let cell_output = live_cell_output.cell_output();
+                let output_data_len = live_cell_output.output_data_len().unpack();
+                let live_cell = LiveCell {
+                    created_by,
+                    cell_output: cell_output.clone(),
+                    output_data_len,
+                    cellbase: live_cell_output.cellbase().unpack(),
+                };
+                (cell_output, Some(output_data_len), live_cell)
+            },
+        )
+    }
+
+    /// Returns the transactions creating or consuming the cells matching the search key.
+    pub fn get_transactions(
+        &self,
+        search_key: &SearchKey,
+        order: Order,
+        limit: usize,
+        after_cursor: Option<&[u8]>,
+    ) -> Pagination<CellTransaction> {
+        self.search(
+            COLUMN_SCRIPT_TRANSACTION,
+            search_key,
+            order,
+            limit,
+            after_cursor,
+            |created_by, value| {
+                let consumed_by = packed::TransactionPointOptReader::from_slice(value)
+                    .expect("verify TransactionPointOpt in storage should be ok")
+                    .to_opt()
+                    .map(TransactionPoint::from_packed);
+                let out_point = OutPoint::new(created_by.tx_hash.clone(), created_by.index);
+                let cell_output = self
+                    .db
+                    .get_pinned(COLUMN_OUT_POINT_SCRIPT, out_point.as_slice())
+                    .expect("indexer db read should be ok")
+                    .and_then(|value| {
+                        packed::LockHashCellOutputReader::from_slice(&value)
+                            .expect("verify LockHashCellOutput in storage should be ok")
+                            .cell_output()
+                            .to_entity()
+                            .to_opt()
+                    })
+                    .expect("indexed cell output");
+                let cell_transaction = CellTransaction {
+                    created_by,
+                    consumed_by,
+                };
+                (cell_output, None, cell_transaction)
+            },
+        )
+    }
+
+    /// Sums the capacity of the live cells matching the search key by lock script, and also by
+    /// type script if `group_by_type` is true. Groups are in the order of their first cells.
+    /// At most `limit` cells are summed, pass `last_cursor` as `after_cursor` to sum the next
+    /// ones, the groups of the pages add up to the total. Like the searches, a page stops after
+    /// scanning `MAX_SCANNED_ENTRIES` entries.
+    pub fn get_grouped_cells_capacity(
+        &self,
+        search_key: &SearchKey,
+        group_by_type: bool,
+        limit: usize,
+        after_cursor: Option<&[u8]>,
+    ) -> CapacityResult<Pagination<CellsCapacity>> {
+        let mut groups: Vec<CellsCapacity> = Vec::new();
+        let mut group_indices: HashMap<(Script, Option<Script>), usize> = HashMap::new();
+        let mut result = Ok(());
+        let last_cursor = self.scan(
+            COLUMN_SCRIPT_LIVE_CELL,
+            search_key,
+            Order::Asc,
+            limit,
+            MAX_SCANNED_ENTRIES,
+            after_cursor,
+            live_cell_output_of,
+            |output, _| {
+                let type_ = if group_by_type {
+                    output.type_().to_opt()
+                } else {
+                    None
+                };
+                let index = *group_indices
+                    .entry((output.lock(), type_.clone()))
+                    .or_insert_with(|| {
+                        groups.push(CellsCapacity {
+                            lock: output.lock(),
+                            type_,
+                            capacity: Capacity::zero(),
+                            cells_count: 0,
+                        });
+                        groups.len() - 1
+                    });
+                let group = &mut groups[index];
+                match group.capacity.safe_add(output.capacity().unpack()) {
+                    Ok(capacity) => {
+                        group.capacity = capacity;
+                        group.cells_count += 1;
+                        true
+                    }
+                    Err(err) => {
+                        result = Err(err);
+                        false
+                    }
+                }
+            },
+        );
+        result.map(|_| Pagination {
+            objects: groups,
+            last_cursor,
+        })
+    }
+
+    /// Sums the capacity of all the live cells matching the search key, at the indexed tip.
+    /// Returns `None` if nothing has been indexed yet. The cells are summed while scanning, they
+    /// are never loaded at once.
+    pub fn get_cells_capacity(
+        &self,
+        search_key: &SearchKey,
+    ) -> CapacityResult<Option<LockHashCapacity>> {
+        let tip = match self.tip() {
+            Some(tip) => tip,
+            None => return Ok(None),
+        };
+        let mut capacity = Capacity::zero();
+        let mut cells_count = 0;
+        let mut result = Ok(());
+        self.scan(
+            COLUMN_SCRIPT_LIVE_CELL,
+            search_key,
+            Order::Asc,
+            usize::max_value(),
+            usize::max_value(),
+            None,
+            live_cell_output_of,
+            |output, _| match capacity.safe_add(output.capacity().unpack()) {
+                Ok(sum) => {
+                    capacity = sum;
+                    cells_count += 1;
+                    true
+                }
+                Err(err) => {
+                    result = Err(err);
+                    false
+                }
+            },
+        );
+        result.map(|_| {
+            Some(LockHashCapacity {
+                capacity,
+                cells_count,
+                block_number: tip.block_number,
+            })
+        })
+    }
+
+    fn search<T, F>(
+        &self,
+        col: Col,
+        search_key: &SearchKey,
+        order: Order,
+        limit: usize,
+        after_cursor: Option<&[u8]>,
+        f: F,
+    ) -> Pagination<T>
+    where
+        F: Fn(TransactionPoint, &[u8]) -> (CellOutput, Option<u64>, T),
+    {
+        let mut objects = Vec::new();
+        let last_cursor = self.scan(
+            col,
+            search_key,
+            order,
+            limit,
+            MAX_SCANNED_ENTRIES,
+            after_cursor,
+            f,
+            |_, object| {
+                objects.push(object);
+                true
+            },
+        );
+        Pagination {
+            objects,
+            last_cursor,
+        }
+    }
+
+    // Visits the entries matching the search key and its filter. `decode` returns the cell
+    // output of an entry, the length of its data if known, which spares reading the data shorter
+    // than `output_data_prefix`, and the object passed to `visit`, which stops the scan by
+    // returning false. Stops after `limit` entries are visited or `max_scanned` entries are
+    // scanned, and returns the cursor of the last scanned one, or the empty cursor at the end.
+    #[allow(clippy::too_many_arguments)]
+    fn scan<T, D, V>(
+        &self,
+        col: Col,
+        search_key: &SearchKey,
+        order: Order,
+        limit: usize,
+        max_scanned: usize,
+        after_cursor: Option<&[u8]>,
+        decode: D,
+        mut visit: V,
+    ) -> Vec<u8>
+    where
+        D: Fn(TransactionPoint, &[u8]) -> (CellOutput, Option<u64>, T),
+        V: FnMut(CellOutput, T) -> bool,
+    {
+        // the empty cursor is returned at the end
+        if after_cursor.map(<[u8]>::is_empty).unwrap_or(false) {
+            return Vec::new();
+        }
+        let prefix = script_prefix(search_key.script_kind, &search_key.script);
+        let from_key = match (after_cursor, order) {
+            (Some(cursor), _) => cursor.to_vec(),
+            (None, Order::Asc) => prefix.clone(),
+            (None, Order::Desc) => prefix_successor(&prefix),
+        };
+        let direction = match order {
+            Order::Asc => Direction::Forward,
+            Order::Desc => Direction::Reverse,
+        };
+        let iter = self
+            .db
+            .iter(col, IteratorMode::From(&from_key, direction))
+            .expect("indexer db iter should be ok");
+        let entries = iter
+            // the cursor itself or the prefix successor
+            .skip_while(|(key, _)| key.as_ref() == from_key.as_slice())
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .filter(|(key, _)| {
+                search_key.script_search_mode == ScriptSearchMode::Prefix
+                    || key.len() == prefix.len() + CELL_KEY_SUFFIX_LEN
+            });
+
+        let filter = &search_key.filter;
+        let mut last_cursor = Vec::new();
+        let (mut visited, mut scanned) = (0, 0);
+        for (key, value) in entries {
+            if visited >= limit || scanned >= max_scanned {
+                // continue after the last scanned entry next time
+                return last_cursor;
+            }
+            scanned += 1;
+            last_cursor = key.to_vec();
+
+            let created_by = point_from_cell_key(&key);
+            if let Some((start, end)) = filter.block_range {
+                if created_by.block_number < start || created_by.block_number >= end {
+                    continue;
+                }
+            }
+            let (cell_output, output_data_len, object) = decode(created_by.clone(), &value);
+            if let Some((start, end)) = filter.output_capacity_range {
+                let capacity: Capacity = cell_output.capacity().unpack();
+                if capacity < start || capacity >= end {
+                    continue;
+                }
+            }
+            if let Some(data_prefix) = filter.output_data_prefix.as_ref() {
+                if output_data_len.map(|len| len < data_prefix.len() as u64) == Some(true) {
+                    continue;
+                }
+                let data = self
+                    .source
+                    .get_cell_data(&created_by.tx_hash, created_by.index)
+                    .unwrap_or_default();
+                if !data.starts_with(data_prefix) {
+                    continue;
+                }
+            }
+            visited += 1;
+            if !visit(cell_output, object) {
+                return last_cursor;
+            }
+        }
+        Vec::new()
+    }
+
+    /// Rolls back the indexed blocks which are no longer on the main chain, then
+    /// indexes the main chain blocks up to the current tip.
+    ///
+    /// Every block is committed together with the indexed tip, so the index always matches
+    /// its tip. When a commit fails or the source is unavailable, it stops and resumes from
+    /// the indexed tip next time. The source may switch to another fork during the sync, a
+    /// block whose parent is not the indexed tip also stops it, the next sync rolls back.
+    pub fn sync(&self) {
+        debug!("Start sync script index with chain source");
+        let mut tip = self.tip();
+        while let Some(index_state) = tip.clone() {
+            let main_chain_hash = match self.source.get_block_hash(index_state.block_number) {
+                Some(hash) => Some(hash),
+                // the indexed tip is beyond the source tip, roll back
+                None => match self.source.tip_number() {
+                    Some(tip_number) if tip_number < index_state.block_number => None,
+                    _ => return,
+                },
+            };
+            if main_chain_hash.as_ref() == Some(&index_state.block_hash) {
+                break;
+            }
+            let block = match self.source.get_block(&index_state.block_hash) {
+                Some(block) => block,
+                None => return,
+            };
+            tip = if block.header().number() == 0 {
+                None
+            } else {
+                Some(LockHashIndexState {
+                    block_number: block.header().number() - 1,
+                    block_hash: block.header().parent_hash(),
+                })
+            };
+            let committed = self.commit_txn(|txn| {
+                txn.update_tip(tip.as_ref());
+                for plugin in self.plugins.iter().rev() {
+                    plugin.detach_block(&PluginTransaction::new(&txn.txn, plugin.name()), &block);
+                }
+                self.detach_block(txn, &block)
+            });
+            if !committed {
+                return;
+            }
+        }
+
+        let tip_number = match self.source.tip_number() {
+            Some(tip_number) => tip_number,
+            None => return,
+        };
+        let start_number = tip
+            .as_ref()
+            .map(|index_state| index_state.block_number + 1)
+            .unwrap_or(0);
+        for block_number in start_number..=tip_number {
+            let block = match self
+                .source
+                .get_block_hash(block_number)
+                .and_then(|hash| self.source.get_block(&hash))
+            {
+                Some(block) => block,
+                None => return,
+            };
+            if let Some(index_state) = tip.as_ref() {
+                if block.header().parent_hash() != index_state.block_hash {
+                    debug!("Chain source switched fork while syncing script index");
+                    return;
+                }
+            }
+            let index_state = LockHashIndexState {
+                block_number,
+                block_hash: block.hash(),
+            };
+            let committed = self.commit_txn(|txn| {
+                self.attach_block(txn, &block);
+                for plugin in &self.plugins {
+                    plugin.attach_block(&PluginTransaction::new(&txn.txn, plugin.name()), &block);
+                }
+                txn.update_tip(Some(&index_state));
+                true
+            });
+            if !committed {
+                return;
+            }
+            tip = Some(index_state);
+        }
+        debug!("End sync script index with chain source");
+    }
+
+    // helper function, the txn is dropped without committing if `process` returns false,
+    // returns false if the txn is dropped or failed to commit
+    fn commit_txn<F>(&self, process: F) -> bool
+    where
+        F: FnOnce(&ScriptIndexTransaction) -> bool,
+    {
+        let txn = ScriptIndexTransaction {
+            txn: self.db.transaction(),
+        };
+        process(&txn) && txn.commit()
+    }
+
+    fn attach_block(&self, txn: &ScriptIndexTransaction, block: &core::BlockView) {
+        trace!("script index attach block {}", block.hash());
+        let block_number = block.header().number();
+        block.transactions().iter().for_each(|tx| {
+            let tx_hash = tx.hash();
+            if !tx.is_cellbase() {
+                tx.inputs()
+                    .into_iter()
+                    .enumerate()
+                    .for_each(|(index, input)| {
+                        let out_point = input.previous_output();
+                        if let Some(cell) = txn.get_cell_output(&out_point) {
+                            let consumed_by = TransactionPoint {
+                                block_number,
+                                tx_hash: tx_hash.clone(),
+                                index: index as u32,
+                            };
+                            let cell_output = cell.cell_output.expect("cached cell output");
+                            for (kind, script) in cell_scripts(&cell_output) {
+                                let key = cell_key(kind, &script, cell.block_number, &out_point);
+                                txn.consume_live_cell(&key, &consumed_by);
+                            }
+                        }
+                    });
+            }
+
+            tx.outputs()
+                .into_iter()
+                .enumerate()
+                .for_each(|(index, output)| {
+                    let index = index as u32;
+                    let live_cell_output = LiveCellOutput::new_builder()
+                        .cell_output(output.clone())
+                        .output_data_len(
+                            (tx.outputs_data()
+                                .get(index as usize)
+                                .expect("verified tx")
+                                .len() as u64)
+                                .pack(),
+                        )
+                        .cellbase(tx.is_cellbase().pack())
+                        .build();
+                    let out_point = OutPoint::new(tx_hash.clone(), index);
+                    for (kind, script) in cell_scripts(&output) {
+                        let key = cell_key(kind, &script, block_number, &out_point);
+                        txn.generate_live_cell(&key, &live_cell_output);
+                    }
+                    let cell = LockHashCellOutput {
+                        lock_hash: output.calc_lock_hash(),
+                        block_number,
+                        cell_output: Some(output),
+                    };
+                    txn.insert_cell_output(&out_point, &cell);
+                });
+        })
+    }
+
+    // Returns false if a transaction creating the restored live cells is unavailable
+    fn detach_block(&self, txn: &ScriptIndexTransaction, block: &core::BlockView) -> bool {
+        trace!("script index detach block {}", block.hash());
+        let block_number = block.header().number();
+        for tx in block.transactions().iter().rev() {
+            let tx_hash = tx.hash();
+            tx.outputs()
+                .into_iter()
+                .enumerate()
+                .for_each(|(index, output)| {
+                    let index = index as u32;
+                    let out_point = OutPoint::new(tx_hash.clone(), index);
+                    for (kind, script) in cell_scripts(&output) {
+                        let key = cell_key(kind, &script, block_number, &out_point);
+                        txn.delete_live_cell(&key);
+                        txn.delete_transaction(&key);
+                    }
+                    txn.delete_cell_output(&out_point);
+                });
+
+            if tx.is_cellbase() {
+                continue;
+            }
+            for input in tx.inputs().into_iter() {
+                let out_point = input.previous_output();
+                let cell = match txn.get_cell_output(&out_point) {
+                    Some(cell) => cell,
+                    None => continue,
+                };
+                let out_point_tx = match self.source.get_transaction(&out_point.tx_hash()) {
+                    Some(out_point_tx) => out_point_tx,
+                    None => return false,
+                };
+                let index: u32 = out_point.index().unpack();
+                let cell_output = cell.cell_output.expect("cached cell output");
+                let live_cell_output = LiveCellOutput::new_builder()
+                    .cell_output(cell_output.clone())
+                    .output_data_len(
+                        (out_point_tx
+                            .outputs_data()
+                            .get(index as usize)
+                            .expect("verified tx")
+                            .len() as u64)
+                            .pack(),
+                    )
+                    .cellbase(out_point_tx.is_cellbase().pack())
+                    .build();
+                for (kind, script) in cell_scripts(&cell_output) {
+                    let key = cell_key(kind, &script, cell.block_number, &out_point);
+                    txn.generate_live_cell(&key, &live_cell_output);
+                }
+            }
+        }
+        true
+    }
+}
+
+fn live_cell_output(value: &[u8]) -> LiveCellOutput {
+    LiveCellOutput::from_slice(value).expect("verify LiveCellOutput in storage should be ok")
+}
+
+// Decodes the entries of COLUMN_SCRIPT_LIVE_CELL for `ScriptIndexer::scan`
+fn live_cell_output_of(
+    _created_by: TransactionPoint,
+    value: &[u8],
+) -> (CellOutput, Option<u64>, ()) {
+    let live_cell_output = live_cell_output(value);
+    (
+        live_cell_output.cell_output(),
+        Some(live_cell_output.output_data_len().unpack()),
+        (),
+    )
+}
+
+fn cell_scripts(output: &CellOutput) -> Vec<(ScriptKind, Script)> {
+    let mut scripts = vec![(ScriptKind::Lock, output.lock())];
+    if let Some(type_script) = output.type_().to_opt() {
+        scripts.push((ScriptKind::Type, type_script));
+    }
+    scripts
+}
+
+fn script_prefix(kind: ScriptKind, script: &Script) -> Vec<u8> {
+    let args = script.args().raw_data();
+    let mut key = Vec::with_capacity(1 + 32 + 1 + args.len() + CELL_KEY_SUFFIX_LEN);
+    key.push(kind as u8);
+    key.extend_from_slice(script.code_hash().as_slice());
+    key.extend_from_slice(script.hash_type().as_slice());
+    key.extend_from_slice(&args);
+    key
+}
+
+fn cell_key(
+    kind: ScriptKind,
+    script: &Script,
+    block_number: BlockNumber,
+    out_point: &OutPoint,
+) -> Vec<u8> {
+    let index: u32 = out_point.index().unpack();
+    let mut key = script_prefix(kind, script);
+    key.extend_from_slice(&block_number.to_be_bytes());
+    key.extend_from_slice(out_point.tx_hash().as_slice());
+    key.extend_from_slice(&index.to_be_bytes());
+    key
+}
+
+fn point_from_cell_key(key: &[u8]) -> TransactionPoint {
+    let suffix = &key[key.len() - CELL_KEY_SUFFIX_LEN..];
+    TransactionPoint {
+        block_number: BlockNumber::from_be_bytes(suffix[..8].try_into().expect("checked length")),
+        tx_hash: Byte32::from_slice(&suffix[8..40]).expect("checked length"),
+        index: u32::from_be_bytes(suffix[40..].try_into().expect("checked length")),
+    }
+}
+
+// The smallest key greater than all keys starting with the prefix, the first byte of a
+// prefix is a `ScriptKind` so it always exists.
+fn prefix_successor(prefix: &[u8]) -> Vec<u8> {
+    let mut key = prefix.to_vec();
+    while key.last() == Some(&0xff) {
+        key.pop();
+    }
+    if let Some(last) = key.last_mut() {
+        *last += 1;
+    }
+    key
+}
+
+struct ScriptIndexTransaction {
+    txn: RocksDBTransaction,
+}
+
+impl ScriptIndexTransaction {
+    fn generate_live_cell(&self, key: &[u8], live_cell_output: &LiveCellOutput) {
+        self.txn
+            .put(COLUMN_SCRIPT_LIVE_CELL, key, live_cell_output.as_slice())
+            .expect("txn insert COLUMN_SCRIPT_LIVE_CELL failed");
+        self.insert_transaction(key, None);
+    }
+
+    fn consume_live_cell(&self, key: &[u8], consumed_by: &TransactionPoint) {
+        self.txn
+            .delete(COLUMN_SCRIPT_LIVE_CELL, key)
+            .expect("txn delete COLUMN_SCRIPT_LIVE_CELL failed");
+        self.insert_transaction(key, Some(consumed_by));
+    }
+
+    fn insert_transaction(&self, key: &[u8], consumed_by: Option<&TransactionPoint>) {
+        let value = packed::TransactionPointOpt::new_builder()
+            .set(consumed_by.map(|point| point.pack()))
+            .build();
+        self.txn
+            .put(COLUMN_SCRIPT_TRANSACTION, key, value.as_slice())
+            .expect("txn insert COLUMN_SCRIPT_TRANSACTION failed");
+    }
+
+    fn delete_live_cell(&self, key: &[u8]) {
+        self.txn
+            .delete(COLUMN_SCRIPT_LIVE_CELL, key)
+            .expect("txn delete COLUMN_SCRIPT_LIVE_CELL failed");
+    }
+
+    fn delete_transaction(&self, key: &[u8]) {
+        self.txn
+            .delete(COLUMN_SCRIPT_TRANSACTION, key)
+            .expect("txn delete COLUMN_SCRIPT_TRANSACTION failed");
+    }
+
+    fn insert_cell_output(&self, out_point: &OutPoint, cell: &LockHashCellOutput) {
+        self.txn
+            .put(
+                COLUMN_OUT_POINT_SCRIPT,
+                out_point.as_slice(),
+                cell.pack().as_slice(),
+            )
+            .expect("txn insert COLUMN_OUT_POINT_SCRIPT failed");
+    }
+
+    fn delete_cell_output(&self, out_point: &OutPoint) {
+        self.txn
+            .delete(COLUMN_OUT_POINT_SCRIPT, out_point.as_slice())
+            .expect("txn delete COLUMN_OUT_POINT_SCRIPT failed");
+    }
+
+    fn get_cell_output(&self, out_point: &OutPoint) -> Option<LockHashCellOutput> {
+        self.txn
+            .get(COLUMN_OUT_POINT_SCRIPT, out_point.as_slice())
+            .expect("indexer db read should be ok")
+            .map(|value| {
+                LockHashCellOutput::from_packed(
+                    packed::LockHashCellOutputReader::from_slice(&value)
+                        .expect("verify LockHashCellOutput in storage should be ok"),
+                )
+            })
+    }
+
+    fn update_tip(&self, index_state: Option<&LockHashIndexState>) {
+        match index_state {
+            Some(index_state) => self
+                .txn
+                .put(
+                    COLUMN_SCRIPT_INDEX_TIP,
+                    TIP_KEY,
+                    index_state.pack().as_slice(),
+                )
+                .expect("txn insert COLUMN_SCRIPT_INDEX_TIP failed"),
+            None => self
+                .txn
+                .delete(COLUMN_SCRIPT_INDEX_TIP, TIP_KEY)
+                .expect("txn delete COLUMN_SCRIPT_INDEX_TIP failed"),
+        }
+    }
+
+    fn commit(self) -> bool {
+        // only log the error, indexer store commit failure should not causing the thread to panic entirely.
+        if let Err(err) = self.txn.commit() {
+            error!("script index db failed to commit txn, error: {:?}", err);
+            return false;
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DefaultIndexerStore;
+    use ckb_app_config::IndexerConfig;
+    use ckb_chain::{
+        chain::{ChainController, ChainService},
+        switch::Switch,
+    };
+    use ckb_chain_spec::consensus::Consensus;
+    use ckb_resource::CODE_HASH_DAO;
+    use ckb_shared::shared::{Shared, SharedBuilder};
+    use ckb_types::{
+        core::{capacity_bytes, BlockBuilder, HeaderBuilder, ScriptHashType, TransactionBuilder},
+        packed::{CellInput, CellOutputBuilder, ScriptBuilder},
+        utilities::{difficulty_to_compact, DIFF_TWO},
+        U256,
+    };
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    fn setup(prefix: &str) -> (ScriptIndexer, ChainController, Shared) {
+        let builder = SharedBuilder::default();
+        let (shared, table) = builder.consensus(Consensus::default()).build().unwrap();
+
+        let tmp_dir = tempfile::Builder::new().prefix(prefix).tempdir().unwrap();
+        let mut config = IndexerConfig::default();
+        config.db.path = tmp_dir.as_ref().to_path_buf();
+        let chain_service = ChainService::new(shared.clone(), table);
+        let chain_controller = chain_service.start::<&str>(None);
+        let store = DefaultIndexerStore::new(&config, shared.clone());
+        (store.script_indexer(), chain_controller, shared)
+    }
+
+    fn script(args: &[u8]) -> Script {
+        ScriptBuilder::default()
+            .code_hash(CODE_HASH_DAO.pack())
+            .hash_type(ScriptHashType::Data.into())
+            .args(Bytes::from(args.to_vec()).pack())
+            .build()
+    }
+
+    fn search_key(script: &Script, script_kind: ScriptKind) -> SearchKey {
+        SearchKey {
+            script: script.clone(),
+            script_kind,
+            script_search_mode: ScriptSearchMode::Exact,
+            filter: SearchFilter::default(),
+        }
+    }
+
+    #[test]
+    fn index_lock_and_type_scripts() {
+        let (indexer, chain, shared) = setup("index_lock_and_type_scripts");
+        let lock = script(b"lock");
+        let type_ = script(b"type");
+
+        let tx11 = TransactionBuilder::default()
+            .output(
+                CellOutputBuilder::default()
+                    .capacity(capacity_bytes!(1000).pack())
+                    .lock(lock.clone())
+                    .type_(Some(type_.clone()).pack())
+                    .build(),
+            )
+            .output_data(Bytes::from(vec![1, 2, 3]).pack())
+            .build();
+        let block1 = BlockBuilder::default()
+            .transaction(tx11.clone())
+            .header(
+                HeaderBuilder::default()
+                    .compact_target(DIFF_TWO.pack())
+                    .number(1.pack())
+                    .parent_hash(shared.genesis_hash())
+                    .build(),
+            )
+            .build();
+
+        let tx21 = TransactionBuilder::default()
+            .input(CellInput::new(OutPoint::new(tx11.hash(), 0), 0))
+            .output(
+                CellOutputBuilder::default()
+                    .capacity(capacity_bytes!(1000).pack())
+                    .lock(lock.clone())
+                    .build(),
+            )
+            .output_data(Default::default())
+            .build();
+        let block2 = BlockBuilder::default()
+            .transaction(tx21.clone())
+            .header(
+                HeaderBuilder::default()
+                    .compact_target(DIFF_TWO.pack())
+                    .number(2.pack())
+                    .parent_hash(block1.header().hash())
+                    .build(),
+            )
+            .build();
+
+        chain
+            .internal_process_block(Arc::new(block1.clone()), Switch::DISABLE_ALL)
+            .unwrap();
+        indexer.sync();
+        assert_eq!(1, indexer.tip().unwrap().block_number);
+
+        let lock_key = search_key(&lock, ScriptKind::Lock);
+        let type_key = search_key(&type_, ScriptKind::Type);
+        let cells = indexer.get_cells(&lock_key, Order::Asc, 100, None).objects;
+        assert_eq!(1, cells.len());
+        assert_eq!(3, cells[0].output_data_len);
+        let cells = indexer.get_cells(&type_key, Order::Asc, 100, None).objects;
+        assert_eq!(1, cells.len());
+        // keyed by kind, a lock script lookup never matches type script entries
+        let cells = indexer.get_cells(&search_key(&lock, ScriptKind::Type), Order::Asc, 100, None);
+        assert!(cells.objects.is_empty());
+
+        chain
+            .internal_process_block(Arc::new(block2), Switch::DISABLE_ALL)
+            .unwrap();
+        indexer.sync();
+        let cells = indexer.get_cells(&lock_key, Order::Asc, 100, None).objects;
+        assert_eq!(1, cells.len());
+        assert_eq!(tx21.hash(), cells[0].created_by.tx_hash);
+        let cells = indexer.get_cells(&type_key, Order::Asc, 100, None);
+        assert!(cells.objects.is_empty());
+        let txs = indexer
+            .get_transactions(&type_key, Order::Asc, 100, None)
+            .objects;
+        assert_eq!(1, txs.len());
+        assert_eq!(
+            tx21.hash(),
+            txs[0].consumed_by.as_ref().unwrap().tx_hash.clone()
+        );
+        let txs = indexer
+            .get_transactions(&lock_key, Order::Desc, 100, None)
+            .objects;
+        assert_eq!(2, txs.len());
+        assert_eq!(tx21.hash(), txs[0].created_by.tx_hash);
+
+        // a heavier fork at height 2 rolls back the spending of the type script cell
+        let block2_fork = BlockBuilder::default()
+            .header(
+                HeaderBuilder::default()
+                    .compact_target(difficulty_to_compact(U256::from(20u64)).pack())
+                    .number(2.pack())
+                    .parent_hash(block1.header().hash())
+                    .build(),
+            )
+            .build();
+        chain
+            .internal_process_block(Arc::new(block2_fork.clone()), Switch::DISABLE_ALL)
+            .unwrap();
+        indexer.sync();
+        assert_eq!(block2_fork.hash(), indexer.tip().unwrap().block_hash);
+        let cells = indexer.get_cells(&type_key, Order::Asc, 100, None).objects;
+        assert_eq!(1, cells.len());
+        assert_eq!(tx11.hash(), cells[0].created_by.tx_hash);
+        let txs = indexer
+            .get_transactions(&lock_key, Order::Asc, 100, None)
+            .objects;
+        assert_eq!(1, txs.len());
+        assert!(txs[0].consumed_by.is_none());
+    }
+
+    #[test]
+    fn search_with_prefix_filters_and_cursor() {
+        let (indexer, chain, shared) = setup("search_with_prefix_filters_and_cursor");
+        let mut parent_hash = shared.genesis_hash();
+        for (number, args) in [b"ab1", b"ab2", b"xy1"].iter().enumerate() {
+            let number = number as u64 + 1;
+            let tx = TransactionBuilder::default()
+                .output(
+                    CellOutputBuilder::default()
+                        .capacity(capacity_bytes!(1000).safe_mul(number).unwrap().pack())
+                        .lock(script(*args))
+                        .build(),
+                )
+                .output_data(Bytes::from(vec![number as u8; 4]).pack())
+                .build();
+            let block = BlockBuilder::default()
+                .transaction(tx)
+                .header(
+                    HeaderBuilder::default()
+                        .compact_target(DIFF_TWO.pack())
+                        .number(number.pack())
+                        .parent_hash(parent_hash.clone())
+                        .build(),
+                )
+                .build();
+            parent_hash = block.hash();
+            chain
+                .internal_process_block(Arc::new(block), Switch::DISABLE_ALL)
+                .unwrap();
+        }
+        indexer.sync();
+
+        let mut key = search_key(&script(b"ab"), ScriptKind::Lock);
+        assert!(indexer
+            .get_cells(&key, Order::Asc, 100, None)
+            .objects
+            .is_empty());
+
+        key.script_search_mode = ScriptSearchMode::Prefix;
+        let page = indexer.get_cells(&key, Order::Asc, 1, None);
+        assert_eq!(1, page.objects.len());
+        assert_eq!(1, page.objects[0].created_by.block_number);
+        assert!(!page.last_cursor.is_empty());
+        let page = indexer.get_cells(&key, Order::Asc, 1, Some(&page.last_cursor));
+        assert_eq!(1, page.objects.len());
+        assert_eq!(2, page.objects[0].created_by.block_number);
+        // the search reaches the end of the prefix
+        assert!(page.last_cursor.is_empty());
+        let page = indexer.get_cells(&key, Order::Asc, 1, Some(&page.last_cursor));
+        assert!(page.objects.is_empty());
+        assert!(page.last_cursor.is_empty());
+
+        let page = indexer.get_cells(&key, Order::Desc, 100, None);
+        let numbers: Vec<_> = page
+            .objects
+            .iter()
+            .map(|cell| cell.created_by.block_number)
+            .collect();
+        assert_eq!(vec![2, 1], numbers);
+
+        key.filter.block_range = Some((2, 3));
+        assert_eq!(
+            1,
+            indexer.get_cells(&key, Order::Asc, 100, None).objects.len()
+        );
+        key.filter.block_range = None;
+        key.filter.output_capacity_range = Some((capacity_bytes!(1500), capacity_bytes!(2500)));
+        let cells = indexer.get_cells(&key, Order::Asc, 100, None).objects;
+        assert_eq!(1, cells.len());
+        assert_eq!(2, cells[0].created_by.block_number);
+        key.filter.output_capacity_range = None;
+        key.filter.output_data_prefix = Some(Bytes::from(vec![1, 1]));
+        let cells = indexer.get_cells(&key, Order::Asc, 100, None).objects;
+        assert_eq!(1, cells.len());
+        assert_eq!(1, cells[0].created_by.block_number);
+    }
+    #[test]
+    fn group_cells_capacity_by_scripts() {
+        let (indexer, chain, shared) = setup("group_cells_capacity_by_scripts");
+        let (lock1, lock2, type_) = (script(b"ab1"), script(b"ab2"), script(b"type"));
+        let output = |capacity: usize, lock: &Script, type_: Option<Script>| {
+            CellOutputBuilder::default()
+                .capacity(Capacity::bytes(capacity).unwrap().pack())
+                .lock(lock.clone())
+                .type_(type_.pack())
+                .build()
+        };
+        let tx = TransactionBuilder::default()
+            .output(output(100, &lock1, None))
+            .output(output(200, &lock2, None))
+            .output(output(300, &lock1, Some(type_.clone())))
+            .output(output(400, &lock1, None))
+            .outputs_data(vec![Bytes::new().pack(); 4])
+            .build();
+        let block = BlockBuilder::default()
+            .transaction(tx)
+            .header(
+                HeaderBuilder::default()
+                    .compact_target(DIFF_TWO.pack())
+                    .number(1.pack())
+                    .parent_hash(shared.genesis_hash())
+                    .build(),
+            )
+            .build();
+        chain
+            .internal_process_block(Arc::new(block), Switch::DISABLE_ALL)
+            .unwrap();
+        indexer.sync();
+
+        let mut key = search_key(&script(b"ab"), ScriptKind::Lock);
+        key.script_search_mode = ScriptSearchMode::Prefix;
+        let groups = indexer
+            .get_grouped_cells_capacity(&key, false, 100, None)
+            .unwrap()
+            .objects;
+        let summary: Vec<_> = groups
+            .iter()
+            .map(|group| (group.lock.clone(), group.capacity, group.cells_count))
+            .collect();
+        assert_eq!(
+            vec![
+                (lock1.clone(), capacity_bytes!(800), 3),
+                (lock2.clone(), capacity_bytes!(200), 1),
+            ],
+            summary
+        );
+        assert!(groups.iter().all(|group| group.type_.is_none()));
+
+        let groups = indexer
+            .get_grouped_cells_capacity(&key, true, 100, None)
+            .unwrap()
+            .objects;
+        let summary: Vec<_> = groups
+            .iter()
+            .map(|group| (group.type_.clone(), group.capacity, group.cells_count))
+            .collect();
+        assert_eq!(
+            vec![
+                (None, capacity_bytes!(500), 2),
+                (Some(type_.clone()), capacity_bytes!(300), 1),
+                (None, capacity_bytes!(200), 1),
+            ],
+            summary
+        );
+
+        let groups = indexer
+            .get_grouped_cells_capacity(&search_key(&type_, ScriptKind::Type), true, 100, None)
+            .unwrap()
+            .objects;
+        assert_eq!(1, groups.len());
+        assert_eq!(lock1, groups[0].lock);
+
+        // summed in pages of 2 cells, the groups of the pages add up to the total
+        let page = indexer
+            .get_grouped_cells_capacity(&key, false, 2, None)
+            .unwrap();
+        let summary: Vec<_> = page
+            .objects
+            .iter()
+            .map(|group| (group.lock.clone(), group.capacity, group.cells_count))
+            .collect();
+        assert_eq!(vec![(lock1.clone(), capacity_bytes!(400), 2)], summary);
+        let page = indexer
+            .get_grouped_cells_capacity(&key, false, 2, Some(&page.last_cursor))
+            .unwrap();
+        let summary: Vec<_> = page
+            .objects
+            .iter()
+            .map(|group| (group.lock.clone(), group.capacity, group.cells_count))
+            .collect();
+        assert_eq!(
+            vec![
+                (lock1.clone(), capacity_bytes!(400), 1),
+                (lock2.clone(), capacity_bytes!(200), 1),
+            ],
+            summary
+        );
+        assert!(page.last_cursor.is_empty());
+    }
+
+    #[test]
+    fn sum_cells_capacity() {
+        let (indexer, chain, shared) = setup("sum_cells_capacity");
+        let (lock1, lock2) = (script(b"ab1"), script(b"ab2"));
+        let mut key = search_key(&script(b"ab"), ScriptKind::Lock);
+        key.script_search_mode = ScriptSearchMode::Prefix;
+        assert!(indexer.get_cells_capacity(&key).unwrap().is_none());
+
+        let output = |capacity: Capacity, lock: &Script| {
+            CellOutputBuilder::default()
+                .capacity(capacity.pack())
+                .lock(lock.clone())
+                .build()
+        };
+        let half = Capacity::shannons(u64::max_value() / 2 + 1);
+        let tx = TransactionBuilder::default()
+            .output(output(capacity_bytes!(100), &lock1))
+            .output(output(capacity_bytes!(200), &lock2))
+            .output(output(capacity_bytes!(300), &lock1))
+            .output(output(half, &script(b"cd1")))
+            .output(output(half, &script(b"cd2")))
+            .outputs_data(vec![Bytes::new().pack(); 5])
+            .build();
+        let block = BlockBuilder::default()
+            .transaction(tx)
+            .header(
+                HeaderBuilder::default()
+                    .compact_target(DIFF_TWO.pack())
+                    .number(1.pack())
+                    .parent_hash(shared.genesis_hash())
+                    .build(),
+            )
+            .build();
+        chain
+            .internal_process_block(Arc::new(block), Switch::DISABLE_ALL)
+            .unwrap();
+        indexer.sync();
+
+        let total = indexer.get_cells_capacity(&key).unwrap().expect("indexed");
+        assert_eq!(capacity_bytes!(600), total.capacity);
+        assert_eq!(3, total.cells_count);
+        assert_eq!(1, total.block_number);
+
+        let key = search_key(&lock2, ScriptKind::Lock);
+        let total = indexer.get_cells_capacity(&key).unwrap().expect("indexed");
+        assert_eq!(capacity_bytes!(200), total.capacity);
+        assert_eq!(1, total.cells_count);
+
+        // the total of the cells locked by `cd` overflows
+        let mut key = search_key(&script(b"cd"), ScriptKind::Lock);
+        key.script_search_mode = ScriptSearchMode::Prefix;
+        assert!(indexer.get_cells_capacity(&key).is_err());
+    }
+
+    // Counts the outputs of every indexed block and in total
+    struct OutputsCountPlugin;
+
+    impl OutputsCountPlugin {
+        fn update_total(txn: &PluginTransaction, delta: i64) {
+            let total = txn
+                .get(b"total")
+                .map(|value| i64::from_le_bytes(value[..].try_into().unwrap()))
+                .unwrap_or(0);
+            txn.put(b"total", &(total + delta).to_le_bytes());
+        }
+    }
+
+    impl IndexPlugin for OutputsCountPlugin {
+        fn name(&self) -> &str {
+            "outputs_count"
+        }
+
+        fn attach_block(&self, txn: &PluginTransaction, block: &core::BlockView) {
+            let count: usize = block
+                .transactions()
+                .iter()
+                .map(|tx| tx.outputs().len())
+                .sum();
+            txn.put(&block.number().to_be_bytes(), &(count as i64).to_le_bytes());
+            Self::update_total(txn, count as i64);
+        }
+
+        fn detach_block(&self, txn: &PluginTransaction, block: &core::BlockView) {
+            let key = block.number().to_be_bytes();
+            let count = i64::from_le_bytes(txn.get(&key).unwrap()[..].try_into().unwrap());
+            txn.delete(&key);
+            Self::update_total(txn, -count);
+        }
+    }
+
+    #[test]
+    fn run_plugins_in_attach_and_detach_pipeline() {
+        let (mut indexer, chain, shared) = setup("run_plugins_in_attach_and_detach_pipeline");
+        indexer.register_plugin(Arc::new(OutputsCountPlugin));
+        let store = indexer.plugin_store("outputs_count");
+        let total = || i64::from_le_bytes(store.get(b"total").unwrap()[..].try_into().unwrap());
+        let genesis_outputs: usize = shared
+            .consensus()
+            .genesis_block()
+            .transactions()
+            .iter()
+            .map(|tx| tx.outputs().len())
+            .sum();
+
+        let tx = TransactionBuilder::default()
+            .outputs(vec![CellOutput::default(); 3])
+            .outputs_data(vec![Bytes::new().pack(); 3])
+            .build();
+        let block1 = BlockBuilder::default()
+            .transaction(tx)
+            .header(
+                HeaderBuilder::default()
+                    .compact_target(DIFF_TWO.pack())
+                    .number(1.pack())
+                    .parent_hash(shared.genesis_hash())
+                    .build(),
+            )
+            .build();
+        chain
+            .internal_process_block(Arc::new(block1.clone()), Switch::DISABLE_ALL)
+            .unwrap();
+        indexer.sync();
+        assert_eq!(genesis_outputs as i64 + 3, total());
+        assert_eq!(2, store.get_by_prefix(&[0; 8][..7], 10).len());
+
+        let block1_fork = BlockBuilder::default()
+            .header(
+                HeaderBuilder::default()
+                    .compact_target(difficulty_to_compact(U256::from(20u64)).pack())
+                    .number(1.pack())
+                    .parent_hash(shared.genesis_hash())
+                    .build(),
+            )
+            .build();
+        chain
+            .internal_process_block(Arc::new(block1_fork), Switch::DISABLE_ALL)
+            .unwrap();
+        indexer.sync();
+        assert_eq!(genesis_outputs as i64, total());
+        assert!(store.get(&1u64.to_be_bytes()).is_none());
+        // the plugin keys are scoped to the plugin
+        assert!(indexer.plugin_store("other").get(b"total").is_none());
+    }
+
+    #[test]
+    fn resume_from_indexed_tip_after_reorg() {
+        let builder = SharedBuilder::default();
+        let (shared, table) = builder.consensus(Consensus::default()).build().unwrap();
+        let chain = ChainService::new(shared.clone(), table).start::<&str>(None);
+        let tmp_dir = tempfile::Builder::new()
+            .prefix("resume_from_indexed_tip_after_reorg")
+            .tempdir()
+            .unwrap();
+        let mut config = IndexerConfig::default();
+        config.db.path = tmp_dir.path().to_path_buf();
+        let lock = script(b"lock");
+
+        let tx11 = TransactionBuilder::default()
+            .output(
+                CellOutputBuilder::default()
+                    .capacity(capacity_bytes!(1000).pack())
+                    .lock(lock.clone())
+                    .build(),
+            )
+            .output_data(Default::default())
+            .build();
+        let block1 = BlockBuilder::default()
+            .transaction(tx11.clone())
+            .header(
+                HeaderBuilder::default()
+                    .compact_target(DIFF_TWO.pack())
+                    .number(1.pack())
+                    .parent_hash(shared.genesis_hash())
+                    .build(),
+            )
+            .build();
+        let tx21 = TransactionBuilder::default()
+            .input(CellInput::new(OutPoint::new(tx11.hash(), 0), 0))
+            .output(
+                CellOutputBuilder::default()
+                    .capacity(capacity_bytes!(1000).pack())
+                    .lock(lock.clone())
+                    .build(),
+            )
+            .output_data(Default::default())
+            .build();
+        let block2 = BlockBuilder::default()
+            .transaction(tx21)
+            .header(
+                HeaderBuilder::default()
+                    .compact_target(DIFF_TWO.pack())
+                    .number(2.pack())
+                    .parent_hash(block1.header().hash())
+                    .build(),
+            )
+            .build();
+        for block in vec![block1.clone(), block2.clone()] {
+            chain
+                .internal_process_block(Arc::new(block), Switch::DISABLE_ALL)
+                .unwrap();
+        }
+        {
+            let indexer = DefaultIndexerStore::new(&config, shared.clone()).script_indexer();
+            indexer.sync();
+            assert_eq!(2, indexer.tip().unwrap().block_number);
+            // the indexer stops here and closes its database
+        }
+
+        // the chain switches to a heavier fork while the indexer is down
+        let block2_fork = BlockBuilder::default()
+            .header(
+                HeaderBuilder::default()
+                    .compact_target(difficulty_to_compact(U256::from(20u64)).pack())
+                    .number(2.pack())
+                    .parent_hash(block1.header().hash())
+                    .build(),
+            )
+            .build();
+        chain
+            .internal_process_block(Arc::new(block2_fork.clone()), Switch::DISABLE_ALL)
+            .unwrap();
+
+        let indexer = DefaultIndexerStore::new(&config, shared.clone()).script_indexer();
+        assert_eq!(block2.hash(), indexer.tip().unwrap().block_hash);
+        indexer.sync();
+        assert_eq!(block2_fork.hash(), indexer.tip().unwrap().block_hash);
+        let key = search_key(&lock, ScriptKind::Lock);
+        let cells = indexer.get_cells(&key, Order::Asc, 100, None).objects;
+        assert_eq!(1, cells.len());
+        assert_eq!(tx11.hash(), cells[0].created_by.tx_hash);
+        let txs = indexer
+            .get_transactions(&key, Order::Asc, 100, None)
+            .objects;
+        assert_eq!(1, txs.len());
+        assert!(txs[0].consumed_by.is_none());
+    }
+
+    // The local chain which goes offline on demand, like an unreachable remote node
+    struct FlakySource {
+        shared: Shared,
+        online: AtomicBool,
+    }
+
+    impl ChainSource for FlakySource {
+        fn tip_number(&self) -> Option<BlockNumber> {
+            ChainSource::tip_number(&self.shared)
+        }
+
+        fn get_block_hash(&self, number: BlockNumber) -> Option<Byte32> {
+            ChainSource::get_block_hash(&self.shared, number)
+        }
+
+        fn get_block(&self, hash: &Byte32) -> Option<core::BlockView> {
+            if self.online.load(Ordering::SeqCst) {
+                ChainSource::get_block(&self.shared, hash)
+            } else {
+                None
+            }
+        }
+
+        fn get_transaction(&self, hash: &Byte32) -> Option<core::TransactionView> {
+            ChainSource::get_transaction(&self.shared, hash)
+        }
+    }
+
+    #[test]
+    fn standalone_sync_stops_when_source_unavailable() {
+        let builder = SharedBuilder::default();
+        let (shared, table) = builder.consensus(Consensus::default()).build().unwrap();
+        let chain = ChainService::new(shared.clone(), table).start::<&str>(None);
+        let tmp_dir = tempfile::Builder::new()
+            .prefix("standalone_sync_stops_when_source_unavailable")
+            .tempdir()
+            .unwrap();
+        let mut config = IndexerConfig::default();
+        config.db.path = tmp_dir.path().to_path_buf();
+        let source = Arc::new(FlakySource {
+            shared: shared.clone(),
+            online: AtomicBool::new(true),
+        });
+        let indexer = ScriptIndexer::open(&config, Arc::clone(&source) as Arc<dyn ChainSource>);
+        let lock = script(b"lock");
+
+        let mut parent_hash = shared.genesis_hash();
+        for number in 1..=2u64 {
+            let tx = TransactionBuilder::default()
+                .output(
+                    CellOutputBuilder::default()
+                        .capacity(capacity_bytes!(1000).pack())
+                        .lock(lock.clone())
+                        .build(),
+                )
+                .output_data(Bytes::from(vec![number as u8]).pack())
+                .build();
+            let block = BlockBuilder::default()
+                .transaction(tx)
+                .header(
+                    HeaderBuilder::default()
+                        .compact_target(DIFF_TWO.pack())
+                        .number(number.pack())
+                        .parent_hash(parent_hash.clone())
+                        .build(),
+                )
+                .build();
+            parent_hash = block.hash();
+            chain
+                .internal_process_block(Arc::new(block), Switch::DISABLE_ALL)
+                .unwrap();
+            if number == 1 {
+                indexer.sync();
+                assert_eq!(1, indexer.tip().unwrap().block_number);
+                source.online.store(false, Ordering::SeqCst);
+            }
+        }
+
+        indexer.sync();
+        assert_eq!(1, indexer.tip().unwrap().block_number);
+        let key = search_key(&lock, ScriptKind::Lock);
+        assert_eq!(
+            1,
+            indexer.get_cells(&key, Order::Asc, 100, None).objects.len()
+        );
+
+        source.online.store(true, Ordering::SeqCst);
+        indexer.sync();
+        assert_eq!(parent_hash, indexer.tip().unwrap().block_hash);
+        let mut key = key;
+        key.filter.output_data_prefix = Some(Bytes::from(vec![2]));
+        let cells = indexer.get_cells(&key, Order::Asc, 100, None).objects;
+        assert_eq!(1, cells.len());
+        assert_eq!(2, cells[0].created_by.block_number);
+    }
+}
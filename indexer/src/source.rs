@@ -0,0 +1,143 @@
+use ckb_jsonrpc_types::{BlockNumber as JsonBlockNumber, BlockView, TransactionWithStatus};
+use ckb_logger::error;
+use ckb_shared::shared::Shared;
+use ckb_store::ChainStore;
+use ckb_types::{
+    bytes::Bytes,
+    core::{self, BlockNumber},
+    packed::{self, Byte32},
+    prelude::*,
+    H256,
+};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+const REMOTE_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The chain followed by `ScriptIndexer`.
+///
+/// `None` means the item does not exist or the source is unavailable for now, the indexer
+/// stops the current sync and retries on the next tip notification.
+pub trait ChainSource: Send + Sync {
+    /// The number of the main chain tip.
+    fn tip_number(&self) -> Option<BlockNumber>;
+
+    /// The hash of the main chain block at `number`.
+    fn get_block_hash(&self, number: BlockNumber) -> Option<Byte32>;
+
+    /// The block with `hash`, which may be no longer on the main chain.
+    fn get_block(&self, hash: &Byte32) -> Option<core::BlockView>;
+
+    fn get_transaction(&self, hash: &Byte32) -> Option<core::TransactionView>;
+
+    fn get_cell_data(&self, tx_hash: &Byte32, index: u32) -> Option<Bytes> {
+        self.get_transaction(tx_hash)
+            .and_then(|tx| tx.outputs_data().get(index as usize))
+            .map(|data| data.raw_data())
+    }
+}
+
+impl ChainSource for Shared {
+    fn tip_number(&self) -> Option<BlockNumber> {
+        Some(self.snapshot().tip_number())
+    }
+
+    fn get_block_hash(&self, number: BlockNumber) -> Option<Byte32> {
+        self.snapshot().get_block_hash(number)
+    }
+
+    fn get_block(&self, hash: &Byte32) -> Option<core::BlockView> {
+        self.snapshot().get_block(hash)
+    }
+
+    fn get_transaction(&self, hash: &Byte32) -> Option<core::TransactionView> {
+        self.snapshot().get_transaction(hash).map(|(tx, _)| tx)
+    }
+
+    fn get_cell_data(&self, tx_hash: &Byte32, index: u32) -> Option<Bytes> {
+        self.snapshot()
+            .get_cell_data(tx_hash, index)
+            .map(|(data, _)| data)
+    }
+}
+
+/// Follows a remote node through its JSON-RPC, used by the standalone indexer which has no
+/// local chain store.
+pub struct RemoteChainSource {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl RemoteChainSource {
+    pub fn new(url: String) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(REMOTE_REQUEST_TIMEOUT)
+            .build()
+            .expect("build remote chain source client");
+        RemoteChainSource { client, url }
+    }
+
+    fn call<T: DeserializeOwned>(&self, method: &str, params: Value) -> Option<T> {
+        let request = json!({
+            "id": 0,
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params,
+        });
+        let mut response: Value = match self
+            .client
+            .post(&self.url)
+            .json(&request)
+            .send()
+            .and_then(|mut response| response.json())
+        {
+            Ok(response) => response,
+            Err(err) => {
+                error!("remote chain source {} request error: {}", method, err);
+                return None;
+            }
+        };
+        if let Some(err) = response.get("error") {
+            error!("remote chain source {} returns error: {}", method, err);
+            return None;
+        }
+        serde_json::from_value(response["result"].take())
+            .map_err(|err| {
+                error!(
+                    "remote chain source {} returns malformed result: {}",
+                    method, err
+                );
+            })
+            .ok()
+    }
+}
+
+impl ChainSource for RemoteChainSource {
+    fn tip_number(&self) -> Option<BlockNumber> {
+        self.call::<JsonBlockNumber>("get_tip_block_number", json!([]))
+            .map(Into::into)
+    }
+
+    fn get_block_hash(&self, number: BlockNumber) -> Option<Byte32> {
+        self.call::<Option<H256>>("get_block_hash", json!([JsonBlockNumber::from(number)]))
+            .and_then(|hash| hash.map(|hash| hash.pack()))
+    }
+
+    fn get_block(&self, hash: &Byte32) -> Option<core::BlockView> {
+        let hash: H256 = hash.unpack();
+        // `get_block` only returns main chain blocks, the rolled back ones are fork blocks
+        match self.call::<Option<BlockView>>("get_block", json!([hash]))? {
+            Some(block) => Some(block.into()),
+            None => self
+                .call::<Option<BlockView>>("get_fork_block", json!([hash]))
+                .and_then(|block| block.map(Into::into)),
+        }
+    }
+
+    fn get_transaction(&self, hash: &Byte32) -> Option<core::TransactionView> {
+        let hash: H256 = hash.unpack();
+        self.call::<Option<TransactionWithStatus>>("get_transaction", json!([hash]))
+            .and_then(|tx| tx.map(|tx| packed::Transaction::from(tx.transaction.inner).into_view()))
+    }
+}
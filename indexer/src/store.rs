@@ -1,8 +1,10 @@
 use crate::migrations;
+use crate::script_index::ScriptIndexer;
 use crate::types::{
     CellTransaction, LiveCell, LockHashCapacity, LockHashCellOutput, LockHashIndex,
     LockHashIndexState, TransactionPoint,
 };
+use crate::watch_list::WatchList;
 use ckb_app_config::IndexerConfig;
 use ckb_db::{
     db::RocksDB, Col, DBIterator, DefaultMigration, Direction, IteratorMode, Migrations,
@@ -17,12 +19,13 @@ use ckb_types::{
     prelude::*,
 };
 use ckb_util::Mutex;
+use crossbeam_channel::RecvTimeoutError;
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::thread;
 use std::time::Duration;
 
-const COLUMNS: u32 = 4;
+pub(crate) const COLUMNS: u32 = 10;
 
 /// +---------------------------------+---------------+--------------------------+
 /// |             Column              |      Key      |          Value           |
@@ -32,12 +35,18 @@ const COLUMNS: u32 = 4;
 /// | COLUMN_LOCK_HASH_TRANSACTION    | LockHashIndex | Option<TransactionPoint> |
 /// | COLUMN_OUT_POINT_LOCK_HASH      | OutPoint      | LockHashCellOutput       |
 /// +---------------------------------+---------------+--------------------------+
+///
+/// Columns 4 to 7 are used by `ScriptIndexer`, see `script_index.rs`.
+/// Column 8 is used by `WatchList`, see `watch_list.rs`.
+/// Column 9 is used by `IndexPlugin`s, see `plugin.rs`.
 
 const COLUMN_LOCK_HASH_INDEX_STATE: Col = "0";
 const COLUMN_LOCK_HASH_LIVE_CELL: Col = "1";
 const COLUMN_LOCK_HASH_TRANSACTION: Col = "2";
 const COLUMN_OUT_POINT_LOCK_HASH: Col = "3";
 
+const NOTIFY_NAME: &str = "indexer_store";
+
 pub trait IndexerStore: Sync + Send {
     fn get_live_cells(
         &self,
@@ -282,7 +291,7 @@ impl IndexerStore for DefaultIndexerStore {
     }
 }
 
-const INIT_DB_VERSION: &str = "20191127135521";
+pub(crate) const INIT_DB_VERSION: &str = "20191127135521";
 
 impl DefaultIndexerStore {
     pub fn new(config: &IndexerConfig, shared: Shared) -> Self {
@@ -308,23 +317,45 @@ impl DefaultIndexerStore {
             thread_builder = thread_builder.name(name.to_string());
         }
 
+        // a reorg rolls the index back right away instead of waiting for the next batch
+        let reorg_subscription = self.shared.notify_controller().subscribe_reorg(NOTIFY_NAME);
         thread_builder
             .spawn(move || loop {
                 self.sync_index_states();
-                thread::sleep(self.batch_interval);
+                match reorg_subscription
+                    .receiver()
+                    .recv_timeout(self.batch_interval)
+                {
+                    Ok(reorg) => debug!(
+                        "Indexer store rolls back to the common ancestor {}",
+                        reorg.common_ancestor.number()
+                    ),
+                    Err(RecvTimeoutError::Timeout) => (),
+                    Err(RecvTimeoutError::Disconnected) => thread::sleep(self.batch_interval),
+                }
             })
             .expect("start DefaultIndexerStore failed");
     }
 
-    // helper function
-    fn commit_txn<F>(&self, process: F)
+    /// The script indexer sharing the database of this store.
+    pub fn script_indexer(&self) -> ScriptIndexer {
+        ScriptIndexer::new(Arc::clone(&self.db), Arc::new(self.shared.clone()))
+    }
+
+    /// The watch list sharing the database of this store.
+    pub fn watch_list(&self) -> WatchList {
+        WatchList::new(Arc::clone(&self.db), self.shared.clone())
+    }
+
+    // helper function, returns false if the txn failed to commit
+    fn commit_txn<F>(&self, process: F) -> bool
     where
         F: FnOnce(&IndexerStoreTransaction),
     {
         let db_txn = self.db.transaction();
         let mut txn = IndexerStoreTransaction { txn: db_txn };
         process(&mut txn);
-        txn.commit();
+        txn.commit()
     }
 
     pub fn sync_index_states(&self) {
@@ -340,9 +371,11 @@ impl DefaultIndexerStore {
             snapshot.get_block_number(&index_state.block_hash.clone())
                 != Some(index_state.block_number)
         });
-        lock_hash_index_states
+        // the index states of the lock hashes which fail to detach stay on the fork chain,
+        // attaching main chain blocks on top of them would corrupt the index, retry next time
+        let detached = lock_hash_index_states
             .iter()
-            .for_each(|(lock_hash, index_state)| {
+            .all(|(lock_hash, index_state)| {
                 let mut index_lock_hashes = HashSet::new();
                 index_lock_hashes.insert(lock_hash.to_owned());
 
@@ -365,8 +398,11 @@ impl DefaultIndexerStore {
                         block_hash: block.header().parent_hash(),
                     };
                     txn.insert_lock_hash_index_state(lock_hash, &index_state);
-                });
+                })
             });
+        if !detached {
+            return;
+        }
 
         // attach blocks until reach tip or txn limit
         let mut lock_hash_index_states = self.get_lock_hash_index_states();
@@ -695,11 +731,13 @@ impl IndexerStoreTransaction {
             })
     }
 
-    fn commit(self) {
+    fn commit(self) -> bool {
         // only log the error, indexer store commit failure should not causing the thread to panic entirely.
         if let Err(err) = self.txn.commit() {
-            error!("indexer db failed to commit txn, error: {:?}", err)
+            error!("indexer db failed to commit txn, error: {:?}", err);
+            return false;
         }
+        true
     }
 }
 
@@ -1,10 +1,13 @@
+use crate::script_index::{Order, ScriptKind, ScriptSearchMode, SearchFilter, SearchKey};
 use ckb_jsonrpc_types::{
-    CellTransaction as JsonCellTransaction, LiveCell as JsonLiveCell,
-    LockHashCapacity as JsonLockHashCapacity, TransactionPoint as JsonTransactionPoint,
+    CellTransaction as JsonCellTransaction, IndexerCellsCapacity, IndexerOrder,
+    IndexerScriptSearchMode, IndexerScriptType, IndexerSearchKey, IndexerSearchKeyFilter,
+    LiveCell as JsonLiveCell, LockHashCapacity as JsonLockHashCapacity,
+    TransactionPoint as JsonTransactionPoint,
 };
 use ckb_types::{
     core::{BlockNumber, Capacity},
-    packed::{self, Byte32, CellOutput, OutPoint},
+    packed::{self, Byte32, CellOutput, OutPoint, Script},
     prelude::*,
 };
 
@@ -26,6 +29,7 @@ pub struct CellTransaction {
     pub consumed_by: Option<TransactionPoint>,
 }
 
+#[derive(Clone)]
 pub struct TransactionPoint {
     pub block_number: BlockNumber,
     pub tx_hash: Byte32,
@@ -54,6 +58,16 @@ pub struct LockHashCapacity {
     pub block_number: BlockNumber,
 }
 
+/// Total capacity of the live cells with the same lock script, and the same type script
+/// when grouped by type script, `type_` is always `None` otherwise.
+#[derive(Debug, Clone)]
+pub struct CellsCapacity {
+    pub lock: Script,
+    pub type_: Option<Script>,
+    pub capacity: Capacity,
+    pub cells_count: u64,
+}
+
 impl Pack<packed::LockHashIndex> for LockHashIndex {
     fn pack(&self) -> packed::LockHashIndex {
         let index: u32 = self.out_point.index().unpack();
@@ -235,3 +249,68 @@ impl From<LockHashCapacity> for JsonLockHashCapacity {
         }
     }
 }
+
+impl From<CellsCapacity> for IndexerCellsCapacity {
+    fn from(cells_capacity: CellsCapacity) -> IndexerCellsCapacity {
+        let CellsCapacity {
+            lock,
+            type_,
+            capacity,
+            cells_count,
+        } = cells_capacity;
+        IndexerCellsCapacity {
+            lock: lock.into(),
+            type_: type_.map(Into::into),
+            capacity: capacity.into(),
+            cells_count: cells_count.into(),
+        }
+    }
+}
+
+impl From<IndexerSearchKey> for SearchKey {
+    fn from(json: IndexerSearchKey) -> SearchKey {
+        let IndexerSearchKey {
+            script,
+            script_type,
+            script_search_mode,
+            filter,
+        } = json;
+        SearchKey {
+            script: script.into(),
+            script_kind: match script_type {
+                IndexerScriptType::Lock => ScriptKind::Lock,
+                IndexerScriptType::Type => ScriptKind::Type,
+            },
+            script_search_mode: match script_search_mode {
+                Some(IndexerScriptSearchMode::Prefix) => ScriptSearchMode::Prefix,
+                Some(IndexerScriptSearchMode::Exact) | None => ScriptSearchMode::Exact,
+            },
+            filter: filter.map(Into::into).unwrap_or_default(),
+        }
+    }
+}
+
+impl From<IndexerSearchKeyFilter> for SearchFilter {
+    fn from(json: IndexerSearchKeyFilter) -> SearchFilter {
+        let IndexerSearchKeyFilter {
+            output_data_prefix,
+            output_capacity_range,
+            block_range,
+        } = json;
+        SearchFilter {
+            output_data_prefix: output_data_prefix.map(|prefix| prefix.into_bytes()),
+            output_capacity_range: output_capacity_range
+                .map(|[start, end]| (start.into(), end.into())),
+            block_range: block_range.map(|[start, end]| (start.into(), end.into())),
+        }
+    }
+}
+
+impl From<IndexerOrder> for Order {
+    fn from(json: IndexerOrder) -> Order {
+        match json {
+            IndexerOrder::Asc => Order::Asc,
+            IndexerOrder::Desc => Order::Desc,
+        }
+    }
+}
@@ -0,0 +1,358 @@
+use crate::types::LockHashIndexState;
+use ckb_db::{db::RocksDB, Col};
+use ckb_logger::{debug, trace};
+use ckb_notify::WatchedTransaction;
+use ckb_shared::{shared::Shared, Snapshot};
+use ckb_store::ChainStore;
+use ckb_types::{
+    core::{self, TransactionView},
+    packed::{self, Byte32, Script},
+    prelude::*,
+};
+use ckb_util::RwLock;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::thread;
+
+/// +------------------------------+-----------+--------+
+/// |            Column            |    Key    | Value  |
+/// +------------------------------+-----------+--------+
+/// | COLUMN_WATCHED_LOCK_SCRIPT   | lock_hash | Script |
+/// +------------------------------+-----------+--------+
+
+pub(crate) const COLUMN_WATCHED_LOCK_SCRIPT: Col = "8";
+
+const NOTIFY_NAME: &str = "watch_list";
+
+/// WatchList tracks the main chain transactions which create or consume cells locked by the
+/// watched lock scripts. It follows the chain tip through new block notifications, and pushes
+/// the matched transactions of every attached block, and of every detached block on reorg,
+/// through `NotifyController::notify_watched_transaction`.
+///
+/// The watched lock scripts are persisted, the transactions are not. Only the blocks attached
+/// after the watch list starts are tracked.
+#[derive(Clone)]
+pub struct WatchList {
+    db: Arc<RocksDB>,
+    shared: Shared,
+    lock_scripts: Arc<RwLock<HashMap<Byte32, Script>>>,
+}
+
+impl WatchList {
+    pub(crate) fn new(db: Arc<RocksDB>, shared: Shared) -> Self {
+        let mut lock_scripts = HashMap::new();
+        db.traverse(COLUMN_WATCHED_LOCK_SCRIPT, |key, value| {
+            let lock_hash = packed::Byte32Reader::from_slice_should_be_ok(key).to_entity();
+            let script = packed::ScriptReader::from_slice_should_be_ok(value).to_entity();
+            lock_scripts.insert(lock_hash, script);
+            Ok(())
+        })
+        .expect("indexer db read should be ok");
+        WatchList {
+            db,
+            shared,
+            lock_scripts: Arc::new(RwLock::new(lock_scripts)),
+        }
+    }
+
+    pub fn start<S: ToString>(self, thread_name: Option<S>) {
+        let new_block_receiver = self
+            .shared
+            .notify_controller()
+            .subscribe_new_block(NOTIFY_NAME);
+        let tip_header = self.shared.snapshot().tip_header().clone();
+        let mut tip = LockHashIndexState {
+            block_number: tip_header.number(),
+            block_hash: tip_header.hash(),
+        };
+
+        let mut thread_builder = thread::Builder::new();
+        if let Some(name) = thread_name {
+            thread_builder = thread_builder.name(name.to_string());
+        }
+
+        thread_builder
+            .spawn(move || {
+                while new_block_receiver.recv().is_ok() {
+                    // one sync reaches the latest tip, skip the stale notifications
+                    while new_block_receiver.try_recv().is_ok() {}
+                    self.sync(&mut tip);
+                }
+            })
+            .expect("start WatchList failed");
+    }
+
+    /// Watches the lock script, returns its hash.
+    pub fn watch(&self, lock_script: Script) -> Byte32 {
+        let lock_hash = lock_script.calc_script_hash();
+        let txn = self.db.transaction();
+        txn.put(
+            COLUMN_WATCHED_LOCK_SCRIPT,
+            lock_hash.as_slice(),
+            lock_script.as_slice(),
+        )
+        .expect("indexer db write should be ok");
+        txn.commit().expect("commit should be ok");
+        self.lock_scripts
+            .write()
+            .insert(lock_hash.clone(), lock_script);
+        lock_hash
+    }
+
+    /// Stops watching the lock script, returns false if it is not watched.
+    pub fn unwatch(&self, lock_hash: &Byte32) -> bool {
+        let txn = self.db.transaction();
+        txn.delete(COLUMN_WATCHED_LOCK_SCRIPT, lock_hash.as_slice())
+            .expect("indexer db delete should be ok");
+        txn.commit().expect("commit should be ok");
+        self.lock_scripts.write().remove(lock_hash).is_some()
+    }
+
+    pub fn lock_scripts(&self) -> Vec<Script> {
+        self.lock_scripts.read().values().cloned().collect()
+    }
+
+    // Detaches the followed blocks which are no longer on the main chain, then attaches the
+    // main chain blocks up to the current tip
+    fn sync(&self, tip: &mut LockHashIndexState) {
+        let snapshot = self.shared.snapshot();
+        while snapshot.get_block_hash(tip.block_number).as_ref() != Some(&tip.block_hash) {
+            let block = snapshot
+                .get_block(&tip.block_hash)
+                .expect("followed block exists");
+            self.notify_block(&snapshot, &block, true);
+            // the genesis block is never detached
+            *tip = LockHashIndexState {
+                block_number: block.header().number() - 1,
+                block_hash: block.header().parent_hash(),
+            };
+        }
+
+        for block_number in (tip.block_number + 1)..=snapshot.tip_header().number() {
+            let block = snapshot
+                .get_block_hash(block_number)
+                .and_then(|hash| snapshot.get_block(&hash))
+                .expect("block exists");
+            self.notify_block(&snapshot, &block, false);
+            *tip = LockHashIndexState {
+                block_number,
+                block_hash: block.hash(),
+            };
+        }
+    }
+
+    fn notify_block(&self, snapshot: &Snapshot, block: &core::BlockView, detached: bool) {
+        let watched: HashSet<Byte32> = self.lock_scripts.read().keys().cloned().collect();
+        if watched.is_empty() {
+            return;
+        }
+        trace!(
+            "watch list {} block {}",
+            if detached { "detach" } else { "attach" },
+            block.hash()
+        );
+        let notify_controller = self.shared.notify_controller();
+        let mut transactions = block.transactions();
+        // roll back the transactions in the reverse order
+        if detached {
+            transactions.reverse();
+        }
+        for tx in transactions {
+            let lock_hashes = watched_lock_hashes(snapshot, &tx, &watched);
+            if lock_hashes.is_empty() {
+                continue;
+            }
+            debug!("watch list matches transaction {}", tx.hash());
+            notify_controller.notify_watched_transaction(WatchedTransaction {
+                lock_hashes,
+                block_number: block.header().number(),
+                block_hash: block.hash(),
+                transaction: tx,
+                detached,
+            });
+        }
+    }
+}
+
+// The watched lock hashes of the cells created or consumed by the transaction
+fn watched_lock_hashes(
+    snapshot: &Snapshot,
+    tx: &TransactionView,
+    watched: &HashSet<Byte32>,
+) -> Vec<Byte32> {
+    let mut lock_hashes = Vec::new();
+    let mut push_output = |output: packed::CellOutput| {
+        let lock_hash = output.calc_lock_hash();
+        if watched.contains(&lock_hash) && !lock_hashes.contains(&lock_hash) {
+            lock_hashes.push(lock_hash);
+        }
+    };
+    if !tx.is_cellbase() {
+        for out_point in tx.input_pts_iter() {
+            let index: u32 = out_point.index().unpack();
+            if let Some(output) = snapshot
+                .get_transaction(&out_point.tx_hash())
+                .and_then(|(input_tx, _)| input_tx.outputs().get(index as usize))
+            {
+                push_output(output);
+            }
+        }
+    }
+    for output in tx.outputs().into_iter() {
+        push_output(output);
+    }
+    lock_hashes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DefaultIndexerStore;
+    use ckb_app_config::IndexerConfig;
+    use ckb_chain::{
+        chain::{ChainController, ChainService},
+        switch::Switch,
+    };
+    use ckb_chain_spec::consensus::Consensus;
+    use ckb_resource::CODE_HASH_DAO;
+    use ckb_shared::shared::SharedBuilder;
+    use ckb_types::{
+        bytes::Bytes,
+        core::{
+            capacity_bytes, BlockBuilder, Capacity, HeaderBuilder, ScriptHashType,
+            TransactionBuilder,
+        },
+        packed::{CellInput, CellOutputBuilder, OutPoint, ScriptBuilder},
+        utilities::{difficulty_to_compact, DIFF_TWO},
+        U256,
+    };
+
+    fn setup(prefix: &str) -> (DefaultIndexerStore, ChainController, Shared) {
+        let builder = SharedBuilder::default();
+        let (shared, table) = builder.consensus(Consensus::default()).build().unwrap();
+
+        let tmp_dir = tempfile::Builder::new().prefix(prefix).tempdir().unwrap();
+        let mut config = IndexerConfig::default();
+        config.db.path = tmp_dir.as_ref().to_path_buf();
+        let chain_service = ChainService::new(shared.clone(), table);
+        let chain_controller = chain_service.start::<&str>(None);
+        let store = DefaultIndexerStore::new(&config, shared.clone());
+        (store, chain_controller, shared)
+    }
+
+    fn script(args: &[u8]) -> Script {
+        ScriptBuilder::default()
+            .code_hash(CODE_HASH_DAO.pack())
+            .hash_type(ScriptHashType::Data.into())
+            .args(Bytes::from(args.to_vec()).pack())
+            .build()
+    }
+
+    #[test]
+    fn watch_and_unwatch_lock_scripts() {
+        let (store, _chain, _shared) = setup("watch_and_unwatch_lock_scripts");
+        let watch_list = store.watch_list();
+        let lock1 = script(b"lock1");
+        let lock2 = script(b"lock2");
+        assert_eq!(lock1.calc_script_hash(), watch_list.watch(lock1.clone()));
+        watch_list.watch(lock2.clone());
+        assert!(watch_list.unwatch(&lock2.calc_script_hash()));
+        assert!(!watch_list.unwatch(&lock2.calc_script_hash()));
+
+        // the watched lock scripts are persisted
+        assert_eq!(vec![lock1], store.watch_list().lock_scripts());
+    }
+
+    #[test]
+    fn notify_watched_transactions() {
+        let (store, chain, shared) = setup("notify_watched_transactions");
+        let watch_list = store.watch_list();
+        let lock = script(b"lock");
+        watch_list.watch(lock.clone());
+        let receiver = shared
+            .notify_controller()
+            .subscribe_watched_transaction("notify_watched_transactions");
+        let mut tip = LockHashIndexState {
+            block_number: 0,
+            block_hash: shared.genesis_hash(),
+        };
+
+        let tx11 = TransactionBuilder::default()
+            .output(
+                CellOutputBuilder::default()
+                    .capacity(capacity_bytes!(1000).pack())
+                    .lock(lock.clone())
+                    .build(),
+            )
+            .output_data(Default::default())
+            .build();
+        let block1 = BlockBuilder::default()
+            .transaction(tx11.clone())
+            .header(
+                HeaderBuilder::default()
+                    .compact_target(DIFF_TWO.pack())
+                    .number(1.pack())
+                    .parent_hash(shared.genesis_hash())
+                    .build(),
+            )
+            .build();
+        // spends the watched cell to another lock script
+        let tx21 = TransactionBuilder::default()
+            .input(CellInput::new(OutPoint::new(tx11.hash(), 0), 0))
+            .output(
+                CellOutputBuilder::default()
+                    .capacity(capacity_bytes!(1000).pack())
+                    .lock(script(b"other"))
+                    .build(),
+            )
+            .output_data(Default::default())
+            .build();
+        let block2 = BlockBuilder::default()
+            .transaction(tx21.clone())
+            .header(
+                HeaderBuilder::default()
+                    .compact_target(DIFF_TWO.pack())
+                    .number(2.pack())
+                    .parent_hash(block1.header().hash())
+                    .build(),
+            )
+            .build();
+
+        for block in vec![block1.clone(), block2.clone()] {
+            chain
+                .internal_process_block(Arc::new(block), Switch::DISABLE_ALL)
+                .unwrap();
+        }
+        watch_list.sync(&mut tip);
+        assert_eq!(block2.hash(), tip.block_hash);
+        let event = receiver.recv().unwrap();
+        assert_eq!(tx11.hash(), event.transaction.hash());
+        assert_eq!(vec![lock.calc_script_hash()], event.lock_hashes);
+        assert!(!event.detached);
+        let event = receiver.recv().unwrap();
+        assert_eq!(tx21.hash(), event.transaction.hash());
+        assert_eq!(2, event.block_number);
+        assert!(!event.detached);
+
+        // a heavier fork at height 2 rolls back the spending of the watched cell
+        let block2_fork = BlockBuilder::default()
+            .header(
+                HeaderBuilder::default()
+                    .compact_target(difficulty_to_compact(U256::from(20u64)).pack())
+                    .number(2.pack())
+                    .parent_hash(block1.header().hash())
+                    .build(),
+            )
+            .build();
+        chain
+            .internal_process_block(Arc::new(block2_fork.clone()), Switch::DISABLE_ALL)
+            .unwrap();
+        watch_list.sync(&mut tip);
+        assert_eq!(block2_fork.hash(), tip.block_hash);
+        let event = receiver.recv().unwrap();
+        assert_eq!(tx21.hash(), event.transaction.hash());
+        assert_eq!(block2.hash(), event.block_hash);
+        assert!(event.detached);
+        assert!(receiver.try_recv().is_err());
+    }
+}
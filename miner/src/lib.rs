@@ -10,21 +10,35 @@ pub use crate::miner::Miner;
 use ckb_jsonrpc_types::BlockTemplate;
 use ckb_types::packed::Block;
 use std::convert::From;
+use std::ops::Range;
 
 #[derive(Clone)]
 pub struct Work {
     work_id: u64,
     block: Block,
+    nonce_range: Range<u128>,
 }
 
 impl From<BlockTemplate> for Work {
     fn from(block_template: BlockTemplate) -> Work {
         let work_id = block_template.work_id;
+        // Nodes which don't lease nonce ranges leave the whole nonce space to us
+        let nonce_range = match block_template.nonce_range.clone() {
+            Some(range) => Range {
+                start: range.start.into(),
+                end: range.end.into(),
+            },
+            None => Range {
+                start: 0,
+                end: u128::max_value(),
+            },
+        };
         let block: Block = block_template.into();
 
         Work {
             work_id: work_id.into(),
             block,
+            nonce_range,
         }
     }
 }
@@ -1,5 +1,5 @@
 use crate::client::Client;
-use crate::worker::{start_worker, WorkerController, WorkerMessage};
+use crate::worker::{partition_nonce, start_worker, WorkerController, WorkerMessage};
 use crate::Work;
 use ckb_app_config::MinerWorkerConfig;
 use ckb_logger::{debug, error, info};
@@ -8,10 +8,12 @@ use ckb_types::{
     packed::{Byte32, Header},
     prelude::*,
     utilities::compact_to_target,
+    U256,
 };
 use crossbeam_channel::{select, unbounded, Receiver};
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
 use lru_cache::LruCache;
+use std::ops::Range;
 use std::sync::Arc;
 use std::thread;
 
@@ -78,8 +80,9 @@ impl Miner {
                     Ok(work) => {
                         let pow_hash= work.block.header().calc_pow_hash();
                         let (target, _,) = compact_to_target(work.block.header().raw().compact_target().unpack());
+                        let nonce_range = work.nonce_range.clone();
                         self.works.insert(pow_hash.clone(), work);
-                        self.notify_workers(WorkerMessage::NewWork{pow_hash, target});
+                        self.notify_new_work(pow_hash, target, &nonce_range);
                     },
                     _ => {
                         error!("work_rx closed");
@@ -138,6 +141,18 @@ impl Miner {
         }
     }
 
+    // Every worker group searches its own part of the nonce range leased to us
+    fn notify_new_work(&self, pow_hash: Byte32, target: U256, nonce_range: &Range<u128>) {
+        let total = self.worker_controllers.len() as u128;
+        for (i, controller) in self.worker_controllers.iter().enumerate() {
+            controller.send_message(WorkerMessage::NewWork {
+                pow_hash: pow_hash.clone(),
+                target: target.clone(),
+                nonce_range: partition_nonce(nonce_range, i as u128, total),
+            });
+        }
+    }
+
     fn notify_workers(&self, message: WorkerMessage) {
         for controller in self.worker_controllers.iter() {
             controller.send_message(message.clone());
@@ -6,8 +6,9 @@ use crossbeam_channel::{Receiver, Sender};
 use indicatif::ProgressBar;
 use rand::{
     distributions::{self as dist, Distribution as _},
-    thread_rng,
+    thread_rng, Rng,
 };
+use std::ops::Range;
 use std::thread;
 use std::time::Duration;
 
@@ -15,6 +16,7 @@ pub struct Dummy {
     delay: Delay,
     start: bool,
     pow_hash: Option<Byte32>,
+    nonce_range: Range<u128>,
     nonce_tx: Sender<(Byte32, u128)>,
     worker_rx: Receiver<WorkerMessage>,
 }
@@ -67,6 +69,10 @@ impl Dummy {
         Self {
             start: true,
             pow_hash: None,
+            nonce_range: Range {
+                start: 0,
+                end: u128::max_value(),
+            },
             delay: config.into(),
             nonce_tx,
             worker_rx,
@@ -76,7 +82,14 @@ impl Dummy {
     fn poll_worker_message(&mut self) {
         if let Ok(msg) = self.worker_rx.recv() {
             match msg {
-                WorkerMessage::NewWork { pow_hash, .. } => self.pow_hash = Some(pow_hash),
+                WorkerMessage::NewWork {
+                    pow_hash,
+                    nonce_range,
+                    ..
+                } => {
+                    self.pow_hash = Some(pow_hash);
+                    self.nonce_range = nonce_range;
+                }
                 WorkerMessage::Stop => {
                     self.start = false;
                 }
@@ -96,13 +109,15 @@ impl Dummy {
 }
 
 impl Worker for Dummy {
-    fn run<G: FnMut() -> u128>(&mut self, mut rng: G, _progress_bar: ProgressBar) {
+    fn run(&mut self, _progress_bar: ProgressBar) {
+        let mut rng = thread_rng();
         let mut current = self.pow_hash.clone();
         loop {
             self.poll_worker_message();
             if current != self.pow_hash && self.start {
                 if let Some(pow_hash) = &self.pow_hash {
-                    self.solve(pow_hash, rng());
+                    let nonce = rng.gen_range(self.nonce_range.start, self.nonce_range.end);
+                    self.solve(pow_hash, nonce);
                 }
             }
 
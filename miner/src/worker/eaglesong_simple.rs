@@ -7,6 +7,8 @@ use ckb_types::{packed::Byte32, U256};
 use crossbeam_channel::{Receiver, Sender};
 use eaglesong::eaglesong;
 use indicatif::ProgressBar;
+use rand::{thread_rng, Rng};
+use std::ops::Range;
 use std::thread;
 use std::time::{Duration, Instant};
 
@@ -14,6 +16,7 @@ pub struct EaglesongSimple {
     start: bool,
     pow_hash: Option<Byte32>,
     target: U256,
+    nonce_range: Range<u128>,
     nonce_tx: Sender<(Byte32, u128)>,
     worker_rx: Receiver<WorkerMessage>,
     nonces_found: u128,
@@ -30,6 +33,10 @@ impl EaglesongSimple {
             start: true,
             pow_hash: None,
             target: U256::zero(),
+            nonce_range: Range {
+                start: 0,
+                end: u128::max_value(),
+            },
             nonce_tx,
             worker_rx,
             nonces_found: 0,
@@ -40,9 +47,14 @@ impl EaglesongSimple {
     fn poll_worker_message(&mut self) {
         if let Ok(msg) = self.worker_rx.try_recv() {
             match msg {
-                WorkerMessage::NewWork { pow_hash, target } => {
+                WorkerMessage::NewWork {
+                    pow_hash,
+                    target,
+                    nonce_range,
+                } => {
                     self.pow_hash = Some(pow_hash);
                     self.target = target;
+                    self.nonce_range = nonce_range;
                 }
                 WorkerMessage::Stop => {
                     self.start = false;
@@ -81,14 +93,16 @@ impl EaglesongSimple {
 const STATE_UPDATE_DURATION_MILLIS: u128 = 500;
 
 impl Worker for EaglesongSimple {
-    fn run<G: FnMut() -> u128>(&mut self, mut rng: G, progress_bar: ProgressBar) {
+    fn run(&mut self, progress_bar: ProgressBar) {
+        let mut rng = thread_rng();
         let mut state_update_counter = 0usize;
         let mut start = Instant::now();
         loop {
             self.poll_worker_message();
             if self.start {
                 if let Some(pow_hash) = self.pow_hash.clone() {
-                    self.solve(&pow_hash, rng());
+                    let nonce = rng.gen_range(self.nonce_range.start, self.nonce_range.end);
+                    self.solve(&pow_hash, nonce);
                     state_update_counter += 1;
 
                     let elapsed = start.elapsed();
@@ -9,7 +9,6 @@ use crossbeam_channel::{unbounded, Sender};
 use dummy::Dummy;
 use eaglesong_simple::EaglesongSimple;
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
-use rand::{random, Rng};
 use std::ops::Range;
 use std::sync::Arc;
 use std::thread;
@@ -18,7 +17,11 @@ use std::thread;
 pub enum WorkerMessage {
     Stop,
     Start,
-    NewWork { pow_hash: Byte32, target: U256 },
+    NewWork {
+        pow_hash: Byte32,
+        target: U256,
+        nonce_range: Range<u128>,
+    },
 }
 
 pub struct WorkerController {
@@ -31,31 +34,39 @@ impl WorkerController {
     }
 
     pub fn send_message(&self, message: WorkerMessage) {
-        for worker_tx in self.inner.iter() {
-            if let Err(err) = worker_tx.send(message.clone()) {
+        let total = self.inner.len() as u128;
+        for (i, worker_tx) in self.inner.iter().enumerate() {
+            // split the nonce range among worker threads
+            let message = match message {
+                WorkerMessage::NewWork {
+                    ref pow_hash,
+                    ref target,
+                    ref nonce_range,
+                } => WorkerMessage::NewWork {
+                    pow_hash: pow_hash.clone(),
+                    target: target.clone(),
+                    nonce_range: partition_nonce(nonce_range, i as u128, total),
+                },
+                ref message => message.clone(),
+            };
+            if let Err(err) = worker_tx.send(message) {
                 error!("worker_tx send error {:?}", err);
             };
         }
     }
 }
 
-fn partition_nonce(id: u128, total: u128) -> Range<u128> {
-    let span = u128::max_value() / total;
-    let start = span * id;
+pub(crate) fn partition_nonce(range: &Range<u128>, id: u128, total: u128) -> Range<u128> {
+    let span = (range.end - range.start) / total;
+    let start = range.start + span * id;
     let end = match id {
         x if x < total - 1 => start + span,
-        x if x == total - 1 => u128::max_value(),
+        x if x == total - 1 => range.end,
         _ => unreachable!(),
     };
     Range { start, end }
 }
 
-fn nonce_generator(range: Range<u128>) -> impl FnMut() -> u128 {
-    let mut rng = rand::thread_rng();
-    let Range { start, end } = range;
-    move || rng.gen_range(start, end)
-}
-
 const PROGRESS_BAR_TEMPLATE: &str = "{prefix:.bold.dim} {spinner:.green} [{elapsed_precise}] {msg}";
 
 pub fn start_worker(
@@ -78,8 +89,7 @@ pub fn start_worker(
                 thread::Builder::new()
                     .name(worker_name.to_string())
                     .spawn(move || {
-                        let rng = || random();
-                        worker.run(rng, pb);
+                        worker.run(pb);
                     })
                     .expect("Start `Dummy` worker thread failed");
                 WorkerController::new(vec![worker_tx])
@@ -98,7 +108,6 @@ pub fn start_worker(
                 let worker_txs = (0..config.threads)
                     .map(|i| {
                         let worker_name = format!("EaglesongSimple-Worker-{}", i);
-                        let nonce_range = partition_nonce(i as u128, config.threads as u128);
                         // `100` is the len of progress bar, we can use any dummy value here,
                         // since we only show the spinner in console.
                         let pb = mp.add(ProgressBar::new(100));
@@ -112,8 +121,7 @@ pub fn start_worker(
                             .spawn(move || {
                                 let mut worker =
                                     EaglesongSimple::new(nonce_tx, worker_rx, extra_hash_function);
-                                let rng = nonce_generator(nonce_range);
-                                worker.run(rng, pb);
+                                worker.run(pb);
                             })
                             .expect("Start `EaglesongSimple` worker thread failed");
                         worker_tx
@@ -129,5 +137,5 @@ pub fn start_worker(
 }
 
 pub trait Worker {
-    fn run<G: FnMut() -> u128>(&mut self, rng: G, progress_bar: ProgressBar);
+    fn run(&mut self, progress_bar: ProgressBar);
 }
@@ -0,0 +1,54 @@
+#[macro_use]
+extern crate criterion;
+extern crate ckb_network;
+
+use ckb_network::{multiaddr::Multiaddr, DiscoveryMessage, IdentifyMessage, Node, Nodes};
+use criterion::Criterion;
+
+fn addrs(size: usize) -> Vec<Multiaddr> {
+    (0..size)
+        .map(|i| {
+            format!("/ip4/10.0.{}.{}/tcp/8115", i / 256 % 256, i % 256)
+                .parse::<Multiaddr>()
+                .unwrap()
+        })
+        .collect()
+}
+
+fn identify_benchmark(c: &mut Criterion) {
+    c.bench_function_over_inputs(
+        "Identify message encode and decode",
+        |b, &&size| {
+            let listen_addrs = addrs(size);
+            let observed_addr = "/ip4/255.0.0.1/tcp/42".parse::<Multiaddr>().unwrap();
+            let identify = vec![0u8; 64];
+            b.iter(|| {
+                let data =
+                    IdentifyMessage::new(listen_addrs.clone(), observed_addr.clone(), &identify)
+                        .encode();
+                IdentifyMessage::decode(&data).expect("decode identify message");
+            });
+        },
+        &[1, 10],
+    );
+}
+
+fn discovery_benchmark(c: &mut Criterion) {
+    c.bench_function_over_inputs(
+        "Discovery nodes encode and decode",
+        |b, &&size| {
+            let items: Vec<_> = addrs(size)
+                .into_iter()
+                .map(|addr| Node::new(vec![addr]))
+                .collect();
+            b.iter(|| {
+                let data = DiscoveryMessage::Nodes(Nodes::new(false, items.clone())).encode();
+                DiscoveryMessage::decode(&data).expect("decode discovery message");
+            });
+        },
+        &[10, 1_000],
+    );
+}
+
+criterion_group!(benches, identify_benchmark, discovery_benchmark);
+criterion_main!(benches);
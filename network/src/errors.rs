@@ -33,6 +33,8 @@ pub enum PeerError {
     NonReserved,
     Banned,
     ReachMaxInboundLimit,
+    ReachMaxInboundPerIpLimit,
+    ReachMaxInboundPerSubnetLimit,
     ReachMaxOutboundLimit,
 }
 
@@ -1,12 +1,16 @@
 mod behaviour;
 mod compress;
 pub mod errors;
+mod message_stats;
+mod metrics;
 pub mod network;
 mod network_group;
 mod peer;
 pub mod peer_registry;
+mod peer_scoring;
 pub mod peer_store;
 mod protocols;
+mod rate_limiter;
 mod services;
 
 #[cfg(test)]
@@ -15,11 +19,24 @@ mod tests;
 pub use crate::{
     behaviour::Behaviour,
     errors::Error,
+    message_stats::ProtocolMessageStats,
     network::{NetworkController, NetworkService, NetworkState},
     peer::{Peer, PeerIdentifyInfo},
     peer_registry::PeerRegistry,
+    peer_scoring::{PeerScore, ScoreAction},
     peer_store::{types::MultiaddrExt, Score},
-    protocols::{CKBProtocol, CKBProtocolContext, CKBProtocolHandler, PeerIndex},
+    protocols::{
+        identify::Flag as IdentifyFlag, CKBProtocol, CKBProtocolContext, CKBProtocolHandler,
+        PeerIndex,
+    },
+    rate_limiter::RateLimitViolation,
+    services::dns_seeding::DnsSeedServer,
+};
+// The message codecs, which are public for the benchmarks
+#[doc(hidden)]
+pub use crate::protocols::{
+    discovery::{DiscoveryMessage, Node, Nodes},
+    identify::{IdentifyExtension, IdentifyMessage},
 };
 pub use p2p::{
     bytes, multiaddr,
@@ -40,6 +57,11 @@ pub const MAX_FRAME_LENGTH_RELAY: usize = 4 * 1024 * 1024;
 pub const MAX_FRAME_LENGTH_TIME: usize = 1024;
 // Max message frame length for alert protocol: 128KB
 pub const MAX_FRAME_LENGTH_ALERT: usize = 128 * 1024;
+// Max message frame length for block filter protocol: 2MB
+pub const MAX_FRAME_LENGTH_BLOCK_FILTER: usize = 2 * 1024 * 1024;
+// Max message frame length for light client protocol: 4MB
+//   NOTE: update this value when block size limit changed
+pub const MAX_FRAME_LENGTH_LIGHT_CLIENT: usize = 4 * 1024 * 1024;
 // Max message frame length for discovery protocol: 512KB
 pub const MAX_FRAME_LENGTH_DISCOVERY: usize = 512 * 1024;
 // Max message frame length for ping protocol: 1KB
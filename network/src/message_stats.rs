@@ -0,0 +1,116 @@
+//! Statistics of the messages the peers send.
+//!
+//! Every session counts the messages and the bytes received on each protocol, including the ones
+//! dropped by the rate limits, and the time the protocol handler takes to process them.
+use p2p::{ProtocolId, SessionId};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// The message statistics of a protocol of a session.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProtocolMessageStats {
+    pub messages: u64,
+    pub bytes: u64,
+    /// The unix time in milliseconds of the last message
+    pub last_received_at: u64,
+    processed_messages: u64,
+    process_time: Duration,
+}
+
+impl ProtocolMessageStats {
+    /// The average time the handler takes to process a message.
+    pub fn average_latency(&self) -> Duration {
+        if self.processed_messages == 0 {
+            Duration::default()
+        } else {
+            Duration::from_nanos(
+                (self.process_time.as_nanos() / u128::from(self.processed_messages)) as u64,
+            )
+        }
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct MessageStats {
+    sessions: HashMap<SessionId, HashMap<ProtocolId, ProtocolMessageStats>>,
+}
+
+impl MessageStats {
+    pub(crate) fn received(
+        &mut self,
+        session_id: SessionId,
+        proto_id: ProtocolId,
+        len: usize,
+        now: u64,
+    ) {
+        let stats = self
+            .sessions
+            .entry(session_id)
+            .or_default()
+            .entry(proto_id)
+            .or_default();
+        stats.messages += 1;
+        stats.bytes += len as u64;
+        stats.last_received_at = now;
+    }
+
+    pub(crate) fn processed(
+        &mut self,
+        session_id: SessionId,
+        proto_id: ProtocolId,
+        process_time: Duration,
+    ) {
+        if let Some(stats) = self
+            .sessions
+            .get_mut(&session_id)
+            .and_then(|protocols| protocols.get_mut(&proto_id))
+        {
+            stats.processed_messages += 1;
+            stats.process_time += process_time;
+        }
+    }
+
+    pub(crate) fn get(&self, session_id: SessionId) -> HashMap<ProtocolId, ProtocolMessageStats> {
+        self.sessions.get(&session_id).cloned().unwrap_or_default()
+    }
+
+    pub(crate) fn remove_session(&mut self, session_id: SessionId) {
+        self.sessions.remove(&session_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_stats() {
+        let mut stats = MessageStats::default();
+        let (session, proto) = (1.into(), 0.into());
+        stats.received(session, proto, 100, 1000);
+        stats.received(session, proto, 50, 2000);
+        stats.received(session, 1.into(), 10, 3000);
+        stats.processed(session, proto, Duration::from_millis(10));
+        stats.processed(session, proto, Duration::from_millis(30));
+
+        let protocols = stats.get(session);
+        let proto_stats = &protocols[&proto];
+        assert_eq!(
+            (
+                proto_stats.messages,
+                proto_stats.bytes,
+                proto_stats.last_received_at
+            ),
+            (2, 150, 2000)
+        );
+        assert_eq!(proto_stats.average_latency(), Duration::from_millis(20));
+        assert_eq!(
+            protocols[&ProtocolId::new(1)].average_latency(),
+            Duration::default()
+        );
+        assert!(stats.get(2.into()).is_empty());
+
+        stats.remove_session(session);
+        assert!(stats.get(session).is_empty());
+    }
+}
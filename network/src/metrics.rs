@@ -0,0 +1,62 @@
+use ckb_metrics::{register_counter_vec, register_gauge_vec, Counter, Gauge, MetricVec, Subsystem};
+use lazy_static::lazy_static;
+use p2p::ProtocolId;
+
+lazy_static! {
+    pub(crate) static ref SESSIONS: MetricVec<Counter> = register_counter_vec(
+        Subsystem::Network,
+        "ckb_network_sessions_total",
+        "Opened and closed p2p sessions",
+        &["event"],
+    );
+    pub(crate) static ref CONNECTED_PEERS: MetricVec<Gauge> = register_gauge_vec(
+        Subsystem::Network,
+        "ckb_network_connected_peers",
+        "Connected peers by direction, inbound or outbound",
+        &["direction"],
+    );
+    static ref MESSAGE_BYTES: MetricVec<Counter> = register_counter_vec(
+        Subsystem::Network,
+        "ckb_network_message_bytes_total",
+        "Bytes of the CKB protocol messages by protocol id and direction, a broadcast is counted once",
+        &["protocol", "direction"],
+    );
+}
+
+pub(crate) fn observe_received(proto_id: ProtocolId, len: usize) {
+    MESSAGE_BYTES
+        .with_label_values(&[&proto_id.to_string(), "in"])
+        .inc_by(len as u64);
+}
+
+pub(crate) fn observe_sent(proto_id: ProtocolId, len: usize) {
+    MESSAGE_BYTES
+        .with_label_values(&[&proto_id.to_string(), "out"])
+        .inc_by(len as u64);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_bytes() {
+        ckb_metrics::enable(Subsystem::Network);
+        // the protocol ids not used by the other tests
+        let (proto_id, other_proto_id) = (ProtocolId::new(250), ProtocolId::new(251));
+        observe_received(proto_id, 10);
+        observe_received(proto_id, 5);
+        observe_sent(proto_id, 7);
+        observe_sent(other_proto_id, 3);
+
+        let bytes = |proto_id: ProtocolId, direction: &str| {
+            MESSAGE_BYTES
+                .with_label_values(&[proto_id.to_string().as_str(), direction])
+                .get()
+        };
+        assert_eq!(bytes(proto_id, "in"), 15);
+        assert_eq!(bytes(proto_id, "out"), 7);
+        assert_eq!(bytes(other_proto_id, "in"), 0);
+        assert_eq!(bytes(other_proto_id, "out"), 3);
+    }
+}
@@ -1,5 +1,8 @@
 use crate::errors::Error;
+use crate::message_stats::{MessageStats, ProtocolMessageStats};
+use crate::metrics::{self, SESSIONS};
 use crate::peer_registry::{ConnectionStatus, PeerRegistry};
+use crate::peer_scoring::{PeerScore, PeerScoring, ScoreAction};
 use crate::peer_store::{
     types::{AddrInfo, BannedAddr, IpPort, MultiaddrExt},
     PeerStore,
@@ -8,20 +11,23 @@ use crate::protocols::{
     disconnect_message::DisconnectMessageProtocol,
     discovery::DiscoveryProtocol,
     feeler::Feeler,
-    identify::{IdentifyCallback, IdentifyProtocol},
+    identify::{Flag, IdentifyCallback, IdentifyProtocol},
     ping::{PingHandler, PingService},
 };
+use crate::rate_limiter::RateLimiter;
 use crate::services::{
     dns_seeding::DnsSeedingService, dump_peer_store::DumpPeerStoreService,
-    outbound_peer::OutboundPeerService, protocol_type_checker::ProtocolTypeCheckerService,
+    outbound_peer::OutboundPeerService, port_mapping,
+    protocol_type_checker::ProtocolTypeCheckerService,
 };
 use crate::{
     Behaviour, CKBProtocol, Peer, ProtocolId, ProtocolVersion, PublicKey, ServiceControl,
     MAX_FRAME_LENGTH_DISCONNECTMSG, MAX_FRAME_LENGTH_DISCOVERY, MAX_FRAME_LENGTH_FEELER,
     MAX_FRAME_LENGTH_IDENTIFY, MAX_FRAME_LENGTH_PING,
 };
-use ckb_app_config::NetworkConfig;
+use ckb_app_config::{MisbehaviorAction, NetworkConfig};
 use ckb_build_info::Version;
+use ckb_clock::{Clock, SystemClock};
 use ckb_logger::{debug, error, info, trace, warn};
 use ckb_stop_handler::{SignalSender, StopHandler};
 use ckb_util::{Condvar, Mutex, RwLock};
@@ -48,9 +54,12 @@ use p2p::{
 use std::{
     cmp::max,
     collections::{HashMap, HashSet},
-    io,
+    io, mem,
     pin::Pin,
-    sync::Arc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
     thread,
     time::{Duration, Instant},
     usize,
@@ -78,11 +87,16 @@ pub struct SessionInfo {
 pub struct NetworkState {
     pub(crate) peer_registry: RwLock<PeerRegistry>,
     pub(crate) peer_store: Mutex<PeerStore>,
+    peer_scoring: Mutex<PeerScoring>,
+    rate_limiter: Mutex<RateLimiter>,
+    message_stats: Mutex<MessageStats>,
     /// Node listened addresses
     pub(crate) listened_addrs: RwLock<Vec<Multiaddr>>,
     dialing_addrs: RwLock<HashMap<PeerId, Instant>>,
 
     pub(crate) protocol_ids: RwLock<HashSet<ProtocolId>>,
+    /// The identify flags the protocols require on the remote end
+    pub(crate) protocol_flags: RwLock<HashMap<ProtocolId, Flag>>,
     /// Node public addresses,
     /// includes manually public addrs and remote peer observed addrs
     public_addrs: RwLock<HashMap<Multiaddr, u8>>,
@@ -92,7 +106,13 @@ pub struct NetworkState {
     local_private_key: secio::SecioKeyPair,
     local_peer_id: PeerId,
     bootnodes: Vec<(PeerId, Multiaddr)>,
+    /// The whitelist of the config, replaced on reloading the config
+    whitelist_peers: RwLock<Vec<(PeerId, Multiaddr)>>,
     pub(crate) config: NetworkConfig,
+    /// Set on shutdown to stop the protocol handlers before the services they depend on
+    pub(crate) protocols_stopped: AtomicBool,
+    /// The time source of the identify timeouts, the peer scores and the message stats
+    pub(crate) clock: Arc<dyn Clock>,
 }
 
 impl NetworkState {
@@ -111,22 +131,32 @@ impl NetworkState {
         ));
         let bootnodes = config.bootnodes()?;
 
-        let whitelist_peers = config
-            .whitelist_peers()?
-            .iter()
-            .map(|(peer_id, _)| peer_id.to_owned())
-            .collect::<Vec<_>>();
+        let whitelist_peers = config.whitelist_peers()?;
         let peer_registry = PeerRegistry::new(
             config.max_inbound_peers(),
             config.max_outbound_peers(),
             config.whitelist_only,
-            whitelist_peers,
+            whitelist_peers
+                .iter()
+                .map(|(peer_id, _)| peer_id.to_owned())
+                .collect(),
+        )
+        .with_inbound_limits_per_ip(
+            config.rate_limit.max_inbound_peers_per_ip,
+            config.rate_limit.max_inbound_peers_per_subnet,
         );
 
+        let peer_scoring = Mutex::new(PeerScoring::new(config.peer_scoring.clone()));
+        let rate_limiter = Mutex::new(RateLimiter::new(config.rate_limit.clone()));
+
         Ok(NetworkState {
             peer_store,
+            peer_scoring,
+            rate_limiter,
+            message_stats: Mutex::new(MessageStats::default()),
             config,
             bootnodes,
+            whitelist_peers: RwLock::new(whitelist_peers),
             peer_registry: RwLock::new(peer_registry),
             dialing_addrs: RwLock::new(HashMap::default()),
             public_addrs: RwLock::new(public_addrs),
@@ -136,9 +166,35 @@ impl NetworkState {
             local_private_key: local_private_key.clone(),
             local_peer_id: local_private_key.public_key().peer_id(),
             protocol_ids: RwLock::new(HashSet::default()),
+            protocol_flags: RwLock::new(HashMap::default()),
+            protocols_stopped: AtomicBool::new(false),
+            clock: Arc::new(SystemClock),
         })
     }
 
+    /// Replaces the system clock, e.g. by a `MockClock` shared by the nodes of a test.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub(crate) fn whitelist_peers(&self) -> Vec<(PeerId, Multiaddr)> {
+        self.whitelist_peers.read().clone()
+    }
+
+    /// Replaces the whitelist, the new peers are dialed by the outbound peer service.
+    pub(crate) fn set_whitelist_peers(&self, whitelist_peers: Vec<(PeerId, Multiaddr)>) {
+        self.with_peer_registry_mut(|reg| {
+            reg.set_whitelist_peers(
+                whitelist_peers
+                    .iter()
+                    .map(|(peer_id, _)| peer_id.to_owned())
+                    .collect(),
+            )
+        });
+        *self.whitelist_peers.write() = whitelist_peers;
+    }
+
     pub(crate) fn report_session(
         &self,
         p2p_control: &ServiceControl,
@@ -181,6 +237,202 @@ impl NetworkState {
         }
     }
 
+    /// Scores the misbehavior of the peer, and bans the peer once the score reaches the ban
+    /// threshold. The caller disconnects the peer unless `ScoreAction::Continue` is returned.
+    /// The peers in the whitelist are trusted, their misbehaviors are not scored.
+    pub(crate) fn report_misbehavior(&self, peer_id: &PeerId, kind: &str) -> ScoreAction {
+        if self.with_peer_registry(|reg| reg.is_whitelist(peer_id)) {
+            debug!("peer {:?} in the whitelist misbehaves {}", peer_id, kind);
+            return ScoreAction::Continue;
+        }
+        let action =
+            self.peer_scoring
+                .lock()
+                .report(peer_id, kind, self.clock.unix_time_as_millis());
+        debug!("peer {:?} misbehaves {}: {:?}", peer_id, kind, action);
+        if let ScoreAction::Ban(duration) = action {
+            return self.ban_misbehaving_peer(peer_id, kind, duration);
+        }
+        action
+    }
+
+    /// Applies the configured action to the misbehavior, `MisbehaviorAction::Score` reports it
+    /// as `report_misbehavior` does. The caller disconnects the peer unless
+    /// `ScoreAction::Continue` is returned. The peers in the whitelist are never punished.
+    pub(crate) fn punish_misbehavior(
+        &self,
+        peer_id: &PeerId,
+        kind: &str,
+        action: MisbehaviorAction,
+    ) -> ScoreAction {
+        match action {
+            MisbehaviorAction::Score => self.report_misbehavior(peer_id, kind),
+            _ if self.with_peer_registry(|reg| reg.is_whitelist(peer_id)) => {
+                debug!("peer {:?} in the whitelist misbehaves {}", peer_id, kind);
+                ScoreAction::Continue
+            }
+            MisbehaviorAction::Warn => {
+                warn!("peer {:?} misbehaves {}", peer_id, kind);
+                ScoreAction::Continue
+            }
+            MisbehaviorAction::Disconnect => {
+                debug!("peer {:?} misbehaves {}: disconnect", peer_id, kind);
+                ScoreAction::Disconnect
+            }
+            MisbehaviorAction::Ban => {
+                let duration = Duration::from_secs(self.config.peer_scoring.ban_time_secs);
+                self.ban_misbehaving_peer(peer_id, kind, duration)
+            }
+        }
+    }
+
+    // Bans the address of the connected peer, the caller disconnects it
+    fn ban_misbehaving_peer(
+        &self,
+        peer_id: &PeerId,
+        kind: &str,
+        duration: Duration,
+    ) -> ScoreAction {
+        let peer = self.with_peer_registry(|reg| {
+            reg.get_key_by_peer_id(peer_id)
+                .and_then(|session_id| reg.get_peer(session_id))
+                .cloned()
+        });
+        match peer {
+            Some(ref peer) => {
+                info!(
+                    "Ban peer {:?} for {} seconds, reason: misbehavior {}",
+                    peer_id,
+                    duration.as_secs(),
+                    kind
+                );
+                if let Err(err) = self.peer_store.lock().ban_addr(
+                    &peer.connected_addr,
+                    duration.as_millis() as u64,
+                    format!("misbehavior {}", kind),
+                ) {
+                    debug!("Failed to ban peer {:?} {:?}", err, peer_id);
+                }
+                ScoreAction::Ban(duration)
+            }
+            None => ScoreAction::Disconnect,
+        }
+    }
+
+    /// Counts the message received from the session against the rate limits, returns whether to
+    /// process it. The messages over the limits are dropped and scored as misbehaviors, so a peer
+    /// which keeps flooding is disconnected and then banned. The peers in the whitelist are not
+    /// limited.
+    pub(crate) fn check_rate_limit(
+        &self,
+        p2p_control: &ServiceControl,
+        session_id: SessionId,
+        proto_id: ProtocolId,
+        len: usize,
+    ) -> bool {
+        let violation =
+            match self
+                .rate_limiter
+                .lock()
+                .check(session_id, proto_id, len, Instant::now())
+            {
+                Ok(()) => return true,
+                Err(violation) => violation,
+            };
+        let peer = self.with_peer_registry(|reg| {
+            reg.get_peer(session_id)
+                .map(|peer| (peer.peer_id.clone(), peer.is_whitelist))
+        });
+        let peer_id = match peer {
+            Some((_, true)) => return true,
+            Some((peer_id, false)) => peer_id,
+            None => return false,
+        };
+        debug!(
+            "session {} exceeds the rate limit on protocol {}: {:?}",
+            session_id, proto_id, violation
+        );
+        if self.report_misbehavior(&peer_id, violation.misbehavior()) != ScoreAction::Continue {
+            if let Err(err) =
+                disconnect_with_message(p2p_control, session_id, violation.misbehavior())
+            {
+                debug!("Disconnect failed {:?}, error: {:?}", session_id, err);
+            }
+        }
+        false
+    }
+
+    pub(crate) fn record_received_message(
+        &self,
+        session_id: SessionId,
+        proto_id: ProtocolId,
+        len: usize,
+    ) {
+        self.message_stats.lock().received(
+            session_id,
+            proto_id,
+            len,
+            self.clock.unix_time_as_millis(),
+        );
+    }
+
+    pub(crate) fn record_processed_message(
+        &self,
+        session_id: SessionId,
+        proto_id: ProtocolId,
+        process_time: Duration,
+    ) {
+        self.message_stats
+            .lock()
+            .processed(session_id, proto_id, process_time);
+    }
+
+    /// Returns the statistics of the messages received from the session on each protocol.
+    pub fn message_stats(
+        &self,
+        session_id: SessionId,
+    ) -> HashMap<ProtocolId, ProtocolMessageStats> {
+        self.message_stats.lock().get(session_id)
+    }
+
+    pub(crate) fn peer_scores(&self) -> Vec<PeerScore> {
+        self.peer_scoring
+            .lock()
+            .scores(self.clock.unix_time_as_millis())
+    }
+
+    /// Scores the misbehavior of the session as `report_misbehavior` does, and disconnects it
+    /// unless `ScoreAction::Continue` is returned.
+    pub(crate) fn report_session_misbehavior(
+        &self,
+        p2p_control: &ServiceControl,
+        session_id: SessionId,
+        kind: &str,
+        reason: &str,
+    ) {
+        let peer_id = match self
+            .with_peer_registry(|reg| reg.get_peer(session_id).map(|peer| peer.peer_id.clone()))
+        {
+            Some(peer_id) => peer_id,
+            None => {
+                debug!(
+                    "Report misbehavior of session({}) failed: not in peer registry",
+                    session_id
+                );
+                return;
+            }
+        };
+        debug!("peer {:?} {}", peer_id, reason);
+        match self.report_misbehavior(&peer_id, kind) {
+            ScoreAction::Continue => {}
+            ScoreAction::Disconnect | ScoreAction::Ban(_) => {
+                if let Err(err) = disconnect_with_message(p2p_control, session_id, reason) {
+                    debug!("Disconnect failed {:?}, error: {:?}", session_id, err);
+                }
+            }
+        }
+    }
+
     pub(crate) fn ban_session(
         &self,
         p2p_control: &ServiceControl,
@@ -262,6 +514,23 @@ impl NetworkState {
         accept_peer_result.map_err(Into::into)
     }
 
+    pub(crate) fn update_peer_metrics(&self) {
+        let (outbound, total) = self.with_peer_registry(|reg| {
+            let outbound = reg
+                .peers()
+                .values()
+                .filter(|peer| peer.is_outbound())
+                .count();
+            (outbound, reg.peers().len())
+        });
+        metrics::CONNECTED_PEERS
+            .with_label_values(&["inbound"])
+            .set((total - outbound) as i64);
+        metrics::CONNECTED_PEERS
+            .with_label_values(&["outbound"])
+            .set(outbound as i64);
+    }
+
     // For restrict lock in inner scope
     pub(crate) fn with_peer_registry<F, T>(&self, callback: F) -> T
     where
@@ -658,6 +927,7 @@ impl ServiceHandle for EventHandler {
                     "SessionOpen({}, {})",
                     session_context.id, session_context.address,
                 );
+                SESSIONS.with_label_values(&["open"]).inc();
                 let peer_id = session_context
                     .remote_pubkey
                     .as_ref()
@@ -677,7 +947,9 @@ impl ServiceHandle for EventHandler {
                         session_context.id, session_context.address,
                     );
                 } else {
-                    match self.network_state.accept_peer(&session_context) {
+                    let accepted = self.network_state.accept_peer(&session_context);
+                    self.network_state.update_peer_metrics();
+                    match accepted {
                         Ok(Some(evicted_peer)) => {
                             debug!(
                                 "evict peer (disonnect it), {} => {}",
@@ -722,6 +994,7 @@ impl ServiceHandle for EventHandler {
                     "SessionClose({}, {})",
                     session_context.id, session_context.address,
                 );
+                SESSIONS.with_label_values(&["close"]).inc();
                 let peer_id = session_context
                     .remote_pubkey
                     .as_ref()
@@ -732,12 +1005,21 @@ impl ServiceHandle for EventHandler {
                     .disconnecting_sessions
                     .write()
                     .remove(&session_context.id);
+                self.network_state
+                    .rate_limiter
+                    .lock()
+                    .remove_session(session_context.id);
+                self.network_state
+                    .message_stats
+                    .lock()
+                    .remove_session(session_context.id);
                 let peer_exists = self
                     .network_state
                     .peer_registry
                     .write()
                     .remove_peer(session_context.id)
                     .is_some();
+                self.network_state.update_peer_metrics();
                 if peer_exists {
                     debug!(
                         "{} closed, remove {} from peer_registry",
@@ -863,6 +1145,7 @@ impl NetworkService {
         let (ping_sender, ping_receiver) = channel(std::u8::MAX as usize);
         let ping_interval = Duration::from_secs(config.ping_interval_secs);
         let ping_timeout = Duration::from_secs(config.ping_timeout_secs);
+        let ping_network_state = Arc::clone(&network_state);
 
         let ping_meta = MetaBuilder::default()
             .id(PING_PROTOCOL_ID.into())
@@ -876,6 +1159,7 @@ impl NetworkService {
             })
             .service_handle(move || {
                 ProtocolHandle::Both(Box::new(PingHandler::new(
+                    ping_network_state,
                     ping_interval,
                     ping_timeout,
                     ping_sender,
@@ -906,8 +1190,18 @@ impl NetworkService {
             .build();
 
         // Identify protocol
+        for protocol in &protocols {
+            if let Some(flag) = protocol.identify_flag() {
+                network_state
+                    .protocol_flags
+                    .write()
+                    .insert(protocol.id(), flag);
+            }
+        }
+        let identify_config = config.identify.clone();
         let identify_callback =
             IdentifyCallback::new(Arc::clone(&network_state), name, client_version);
+        let identify_clock = Arc::clone(&network_state.clock);
         let identify_meta = MetaBuilder::default()
             .id(IDENTIFY_PROTOCOL_ID.into())
             .name(move |_| "/ckb/identify".to_string())
@@ -919,7 +1213,10 @@ impl NetworkService {
                 )
             })
             .service_handle(move || {
-                ProtocolHandle::Both(Box::new(IdentifyProtocol::new(identify_callback)))
+                ProtocolHandle::Both(Box::new(
+                    IdentifyProtocol::new(identify_callback, identify_config)
+                        .with_clock(identify_clock),
+                ))
             })
             .flag(no_blocking_flag)
             .build();
@@ -979,7 +1276,6 @@ impl NetworkService {
         };
         let p2p_service = service_builder
             .key_pair(network_state.local_private_key.clone())
-            .upnp(config.upnp)
             .forever(true)
             .max_connection_number(1024)
             .build(event_handler);
@@ -1017,8 +1313,11 @@ impl NetworkService {
         };
 
         if config.dns_seeding_service_enabled() {
-            let dns_seeding_service =
-                DnsSeedingService::new(Arc::clone(&network_state), config.dns_seeds.clone());
+            let dns_seeding_service = DnsSeedingService::new(
+                Arc::clone(&network_state),
+                config.dns_seeds.clone(),
+                config.dns_seeds_pubkey.clone(),
+            );
             bg_services.push(Box::pin(dns_seeding_service) as Pin<Box<_>>);
         };
 
@@ -1037,7 +1336,7 @@ impl NetworkService {
         let config = self.network_state.config.clone();
 
         // dial whitelist_nodes
-        for (peer_id, addr) in self.network_state.config.whitelist_peers()? {
+        for (peer_id, addr) in self.network_state.whitelist_peers() {
             debug!("dial whitelist_peers {:?} {:?}", peer_id, addr);
             self.network_state
                 .dial_identify(self.p2p_service.control(), &peer_id, addr);
@@ -1119,6 +1418,10 @@ impl NetworkService {
                             }
                         };
                     }
+                    if config.upnp {
+                        let listened_addrs = network_state_1.listened_addrs.read().clone();
+                        port_mapping::spawn(Arc::clone(&network_state_1), listened_addrs);
+                    }
                     start_sender.send(Ok(())).unwrap();
                     loop {
                         if p2p_service.next().await.is_none() {
@@ -1186,6 +1489,19 @@ pub struct NetworkController {
 }
 
 impl NetworkController {
+    /// Returns the handler which stops the network service on shutdown.
+    pub fn stop_handler(&self) -> StopHandler<()> {
+        self.stop.clone()
+    }
+
+    /// Stops dispatching the messages to the protocol handlers, e.g. sync and relay, the
+    /// connections are kept until the network service stops.
+    pub fn stop_protocols(&self) {
+        self.network_state
+            .protocols_stopped
+            .store(true, Ordering::SeqCst);
+    }
+
     pub fn public_urls(&self, max_urls: usize) -> Vec<(String, u8)> {
         self.network_state.public_urls(max_urls)
     }
@@ -1237,11 +1553,39 @@ impl NetworkController {
             .cloned()
     }
 
+    /// Replaces the whitelist peers by the ones in the config, e.g. on reloading the config file.
+    pub fn update_whitelist_peers(&self, config: &NetworkConfig) -> Result<(), Error> {
+        let whitelist_peers = config.whitelist_peers()?;
+        self.network_state.set_whitelist_peers(whitelist_peers);
+        Ok(())
+    }
+
+    /// Bans the network until the unix time in milliseconds, and disconnects the peers in it
+    /// except the whitelisted ones.
     pub fn ban(&self, address: IpNetwork, ban_until: u64, ban_reason: String) -> Result<(), Error> {
         self.network_state
             .peer_store
             .lock()
-            .ban_network(address, ban_until, ban_reason)
+            .ban_network_until(address, ban_until, ban_reason)?;
+        let banned_sessions: Vec<SessionId> = self.network_state.with_peer_registry(|reg| {
+            reg.peers()
+                .values()
+                .filter(|peer| !peer.is_whitelist)
+                .filter(|peer| {
+                    peer.connected_addr
+                        .extract_ip_addr()
+                        .map(|ip_port| address.contains(ip_port.ip))
+                        .unwrap_or(false)
+                })
+                .map(|peer| peer.session_id)
+                .collect()
+        });
+        for session_id in banned_sessions {
+            if let Err(err) = disconnect_with_message(&self.p2p_control, session_id, "banned") {
+                debug!("Disconnect failed {:?}, error: {:?}", session_id, err);
+            }
+        }
+        Ok(())
     }
 
     pub fn unban(&self, address: &IpNetwork) {
@@ -1252,6 +1596,32 @@ impl NetworkController {
             .unban_network(address);
     }
 
+    pub fn clear_banned_addrs(&self) {
+        self.network_state.peer_store.lock().mut_ban_list().clear();
+    }
+
+    /// Addresses and estimated bytes of the peer store. An address is counted with its index
+    /// entries and the heap buffers of the peer id and the multiaddr.
+    pub fn peer_store_memory(&self) -> (usize, usize) {
+        let count = self.network_state.peer_store.lock().addr_manager().count();
+        let entry_size =
+            mem::size_of::<AddrInfo>() + mem::size_of::<IpPort>() + mem::size_of::<u64>() * 3 + 96;
+        (count, count * entry_size)
+    }
+
+    /// Returns the scores of the misbehaving peers, the highest first.
+    pub fn peer_scores(&self) -> Vec<PeerScore> {
+        self.network_state.peer_scores()
+    }
+
+    /// Returns the statistics of the messages received from the session on each protocol.
+    pub fn message_stats(
+        &self,
+        session_id: SessionId,
+    ) -> HashMap<ProtocolId, ProtocolMessageStats> {
+        self.network_state.message_stats(session_id)
+    }
+
     pub fn connected_peers(&self) -> Vec<(PeerId, Peer)> {
         let peers = self
             .network_state
@@ -1280,6 +1650,7 @@ impl NetworkController {
             };
             match result {
                 Ok(()) => {
+                    metrics::observe_sent(proto_id, data.len());
                     return Ok(());
                 }
                 Err(P2pError::IoError(ref err)) if err.kind() == io::ErrorKind::WouldBlock => {
@@ -7,6 +7,8 @@ use std::time::{Duration, Instant};
 #[derive(Clone, Debug)]
 pub struct PeerIdentifyInfo {
     pub client_version: String,
+    /// The version of the identify protocol, 0 for the peers before the versioning
+    pub protocol_version: u32,
 }
 
 #[derive(Clone, Debug)]
@@ -18,6 +20,10 @@ pub struct Peer {
     pub identify_info: Option<PeerIdentifyInfo>,
     pub last_ping_time: Option<Instant>,
     pub last_message_time: Option<Instant>,
+    /// The last time the peer relayed us a new block
+    pub last_block_time: Option<Instant>,
+    /// The last time the peer relayed us new transactions accepted by the tx pool
+    pub last_tx_time: Option<Instant>,
     pub ping: Option<Duration>,
     pub is_feeler: bool,
     pub connected_time: Instant,
@@ -42,6 +48,8 @@ impl Peer {
             ping: None,
             last_ping_time: None,
             last_message_time: None,
+            last_block_time: None,
+            last_tx_time: None,
             connected_time: Instant::now(),
             is_feeler: false,
             peer_id,
@@ -1,14 +1,16 @@
-use crate::peer_store::PeerStore;
+use crate::network_group::{Group, NetworkGroup};
+use crate::peer_store::{types::MultiaddrExt, PeerStore};
 use crate::{
     errors::{Error, PeerError},
     Peer, PeerId, SessionType,
 };
 use ckb_logger::debug;
 use p2p::{multiaddr::Multiaddr, SessionId};
-use rand::seq::SliceRandom;
-use rand::thread_rng;
+use std::collections::hash_map::DefaultHasher;
 use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
 use std::iter::FromIterator;
+use std::time::Instant;
 
 pub(crate) const EVICTION_PROTECT_PEERS: usize = 8;
 
@@ -18,10 +20,16 @@ pub struct PeerRegistry {
     max_inbound: u32,
     // max outbound limitation
     max_outbound: u32,
+    // max inbound limitation of an IP, 0 is unlimited
+    max_inbound_per_ip: u32,
+    // max inbound limitation of a network group, 0 is unlimited
+    max_inbound_per_subnet: u32,
     // Only whitelist peers or allow all peers.
     whitelist_only: bool,
     whitelist_peers: HashSet<PeerId>,
     feeler_peers: HashSet<PeerId>,
+    // the key ordering the network groups protected from eviction
+    netgroup_key: u64,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -43,6 +51,21 @@ where
     }
 }
 
+// Drops at most `n` peers which have the most recent time, the peers without it are kept
+fn drop_most_recent<F>(list: &mut Vec<&Peer>, n: usize, time: F)
+where
+    F: Fn(&Peer) -> Option<Instant>,
+{
+    list.sort_by_key(|peer| time(peer));
+    let keep = list
+        .iter()
+        .rev()
+        .take(n)
+        .filter(|peer| time(peer).is_some())
+        .count();
+    list.truncate(list.len() - keep);
+}
+
 impl PeerRegistry {
     pub fn new(
         max_inbound: u32,
@@ -57,7 +80,31 @@ impl PeerRegistry {
             feeler_peers: HashSet::default(),
             max_inbound,
             max_outbound,
+            max_inbound_per_ip: 0,
+            max_inbound_per_subnet: 0,
             whitelist_only,
+            netgroup_key: rand::random(),
+        }
+    }
+
+    /// Limits the inbound peers connecting from the same IP and the same network group, 0 is
+    /// unlimited.
+    pub fn with_inbound_limits_per_ip(mut self, per_ip: u32, per_subnet: u32) -> Self {
+        self.max_inbound_per_ip = per_ip;
+        self.max_inbound_per_subnet = per_subnet;
+        self
+    }
+
+    /// Whether the peer is in the whitelist, connected or not.
+    pub fn is_whitelist(&self, peer_id: &PeerId) -> bool {
+        self.whitelist_peers.contains(peer_id)
+    }
+
+    /// Replaces the whitelist, the connected peers are marked by the new one.
+    pub(crate) fn set_whitelist_peers(&mut self, whitelist_peers: Vec<PeerId>) {
+        self.whitelist_peers = HashSet::from_iter(whitelist_peers);
+        for peer in self.peers.values_mut() {
+            peer.is_whitelist = self.whitelist_peers.contains(&peer.peer_id);
         }
     }
 
@@ -90,6 +137,7 @@ impl PeerRegistry {
             let connection_status = self.connection_status();
             // check peers connection limitation
             if session_type.is_inbound() {
+                self.check_inbound_limits_per_ip(&remote_addr)?;
                 if connection_status.non_whitelist_inbound >= self.max_inbound {
                     if let Some(evicted_session) = self.try_evict_inbound_peer(peer_store) {
                         evicted_peer = self.remove_peer(evicted_session);
@@ -107,6 +155,38 @@ impl PeerRegistry {
         Ok(evicted_peer)
     }
 
+    // The loopback addresses are not limited, the local nodes and the tests connect from them
+    fn check_inbound_limits_per_ip(&self, remote_addr: &Multiaddr) -> Result<(), PeerError> {
+        let ip = match remote_addr.extract_ip_addr() {
+            Ok(ip_port) if !ip_port.ip.is_loopback() => ip_port.ip,
+            _ => return Ok(()),
+        };
+        let group = remote_addr.network_group();
+        let (same_ip, same_subnet) = self
+            .peers
+            .values()
+            .filter(|peer| peer.is_inbound() && !peer.is_whitelist)
+            .fold((0, 0), |(same_ip, same_subnet), peer| {
+                let is_same_ip = peer
+                    .connected_addr
+                    .extract_ip_addr()
+                    .map(|ip_port| ip_port.ip == ip)
+                    .unwrap_or(false);
+                let is_same_subnet = peer.network_group() == group;
+                (
+                    same_ip + u32::from(is_same_ip),
+                    same_subnet + u32::from(is_same_subnet),
+                )
+            });
+        if self.max_inbound_per_ip > 0 && same_ip >= self.max_inbound_per_ip {
+            return Err(PeerError::ReachMaxInboundPerIpLimit);
+        }
+        if self.max_inbound_per_subnet > 0 && same_subnet >= self.max_inbound_per_subnet {
+            return Err(PeerError::ReachMaxInboundPerSubnetLimit);
+        }
+        Ok(())
+    }
+
     // try to evict an inbound peer
     fn try_evict_inbound_peer(&self, _peer_store: &PeerStore) -> Option<SessionId> {
         let mut candidate_peers = {
@@ -149,6 +229,14 @@ impl PeerRegistry {
                 peer2_last_message.cmp(&peer1_last_message)
             },
         );
+        // Protect peers which most recently relayed us new blocks and new transactions, an
+        // attacker has to do the useful work to be protected
+        drop_most_recent(&mut candidate_peers, EVICTION_PROTECT_PEERS >> 1, |peer| {
+            peer.last_block_time
+        });
+        drop_most_recent(&mut candidate_peers, EVICTION_PROTECT_PEERS >> 1, |peer| {
+            peer.last_tx_time
+        });
         // Protect half peers which have the longest connection time
         let protect_peers = candidate_peers.len() >> 1;
         sort_then_drop(&mut candidate_peers, protect_peers, |peer1, peer2| {
@@ -156,7 +244,7 @@ impl PeerRegistry {
         });
 
         // Group peers by network group
-        let evict_group = candidate_peers
+        let mut groups = candidate_peers
             .into_iter()
             .fold(HashMap::new(), |mut groups, peer| {
                 groups
@@ -165,17 +253,44 @@ impl PeerRegistry {
                     .push(peer);
                 groups
             })
-            .values()
-            .max_by_key(|group| group.len())
-            .cloned()
-            .unwrap_or_else(Vec::new);
-
-        // randomly evict a peer
-        let mut rng = thread_rng();
-        evict_group.choose(&mut rng).map(|peer| {
-            debug!("evict inbound peer {:?}", peer.peer_id);
-            peer.session_id
-        })
+            .into_iter()
+            .collect::<Vec<_>>();
+
+        // Protect the longest connected peer of the network groups picked by a key unknown to
+        // the others, an attacker can't occupy all the groups to keep the diversity
+        groups.sort_by_key(|(group, _)| self.keyed_netgroup(group));
+        for (_, group_peers) in groups.iter_mut().take(EVICTION_PROTECT_PEERS) {
+            if let Some(index) = group_peers
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, peer)| peer.connected_time)
+                .map(|(index, _)| index)
+            {
+                group_peers.swap_remove(index);
+            }
+        }
+
+        // Evict the youngest peer of the largest network group
+        groups
+            .into_iter()
+            .map(|(_, group_peers)| group_peers)
+            .max_by_key(|group_peers| group_peers.len())
+            .and_then(|group_peers| {
+                group_peers
+                    .into_iter()
+                    .max_by_key(|peer| peer.connected_time)
+            })
+            .map(|peer| {
+                debug!("evict inbound peer {:?}", peer.peer_id);
+                peer.session_id
+            })
+    }
+
+    fn keyed_netgroup(&self, group: &Group) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.netgroup_key.hash(&mut hasher);
+        group.hash(&mut hasher);
+        hasher.finish()
     }
 
     pub fn add_feeler(&mut self, peer_id: PeerId) {
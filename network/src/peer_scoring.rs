@@ -0,0 +1,211 @@
+//! Cumulative scores of the peer misbehaviors.
+//!
+//! Each misbehavior kind carries a penalty, which adds up to the score of the peer. The score
+//! decays over time, so a peer which misbehaves occasionally stays connected, while a peer which
+//! keeps misbehaving is disconnected and then banned.
+use ckb_app_config::PeerScoringConfig;
+use p2p::secio::PeerId;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// The default penalties of the misbehavior kinds, the unknown kinds are penalized by the
+/// disconnect threshold.
+const DEFAULT_PENALTIES: &[(&str, u32)] = &[
+    ("identify.duplicate_listen_addrs", 50),
+    ("identify.duplicate_observed_addr", 50),
+    ("identify.timeout", 100),
+    ("identify.invalid_data", 100),
+    ("identify.too_many_addresses", 100),
+    ("discovery.duplicate_get_nodes", 50),
+    ("discovery.duplicate_first_nodes", 50),
+    ("discovery.too_many_items", 100),
+    ("discovery.too_many_addresses", 100),
+    ("rate_limit.message_rate", 10),
+    ("rate_limit.bandwidth", 10),
+    ("sync.malformed_message", 200),
+    ("sync.invalid_message", 200),
+    ("relay.malformed_message", 200),
+    ("relay.invalid_message", 200),
+    ("relay.wrong_cycles", 200),
+    ("relay.invalid_transaction", 200),
+    ("tx_reconciliation.invalid_message", 200),
+    ("block_filter.malformed_message", 200),
+    ("light_client.malformed_message", 200),
+    ("light_client.too_large_request", 200),
+    ("time.malformed_message", 200),
+];
+
+/// The scores below it are forgotten.
+const MIN_SCORE: f64 = 1.0;
+
+/// What to do with the peer after its misbehavior is scored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScoreAction {
+    Continue,
+    Disconnect,
+    Ban(Duration),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PeerScore {
+    pub peer_id: PeerId,
+    pub score: f64,
+    /// The count of the misbehaviors since the score was created
+    pub misbehaviors: u64,
+    pub last_misbehavior: String,
+    /// When the score was last decayed or penalized, in milliseconds since the unix epoch
+    pub updated_at: u64,
+}
+
+pub struct PeerScoring {
+    config: PeerScoringConfig,
+    scores: HashMap<PeerId, PeerScore>,
+}
+
+impl PeerScoring {
+    pub fn new(config: PeerScoringConfig) -> Self {
+        PeerScoring {
+            config,
+            scores: HashMap::default(),
+        }
+    }
+
+    pub fn penalty(&self, kind: &str) -> u32 {
+        self.config
+            .penalties
+            .get(kind)
+            .cloned()
+            .or_else(|| {
+                DEFAULT_PENALTIES
+                    .iter()
+                    .find(|(default_kind, _)| *default_kind == kind)
+                    .map(|(_, penalty)| *penalty)
+            })
+            .unwrap_or(self.config.disconnect_threshold)
+    }
+
+    /// Adds the penalty of the misbehavior to the score of the peer. The score is removed once
+    /// the peer is banned.
+    pub fn report(&mut self, peer_id: &PeerId, kind: &str, now: u64) -> ScoreAction {
+        self.decay(now);
+        let penalty = self.penalty(kind);
+        let score = {
+            let entry = self
+                .scores
+                .entry(peer_id.clone())
+                .or_insert_with(|| PeerScore {
+                    peer_id: peer_id.clone(),
+                    score: 0.0,
+                    misbehaviors: 0,
+                    last_misbehavior: String::new(),
+                    updated_at: now,
+                });
+            entry.score += f64::from(penalty);
+            entry.misbehaviors += 1;
+            entry.last_misbehavior = kind.to_string();
+            entry.score
+        };
+
+        if score >= f64::from(self.config.ban_threshold) {
+            self.scores.remove(peer_id);
+            ScoreAction::Ban(Duration::from_secs(self.config.ban_time_secs))
+        } else if score >= f64::from(self.config.disconnect_threshold) {
+            ScoreAction::Disconnect
+        } else {
+            ScoreAction::Continue
+        }
+    }
+
+    /// Returns the scores at `now`, the highest first.
+    pub fn scores(&mut self, now: u64) -> Vec<PeerScore> {
+        self.decay(now);
+        let mut scores: Vec<_> = self.scores.values().cloned().collect();
+        scores.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        scores
+    }
+
+    fn decay(&mut self, now: u64) {
+        let half_life_millis = self.config.decay_half_life_secs.saturating_mul(1000);
+        if half_life_millis == 0 {
+            return;
+        }
+        for score in self.scores.values_mut() {
+            let elapsed = now.saturating_sub(score.updated_at);
+            score.score *= 0.5f64.powf(elapsed as f64 / half_life_millis as f64);
+            score.updated_at = now.max(score.updated_at);
+        }
+        self.scores.retain(|_, score| score.score >= MIN_SCORE);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scores_add_up_and_decay() {
+        let mut config = PeerScoringConfig::default();
+        config
+            .penalties
+            .insert("identify.invalid_data".to_string(), 60);
+        let mut scoring = PeerScoring::new(config);
+        let peer_id = PeerId::random();
+
+        assert_eq!(
+            scoring.report(&peer_id, "identify.duplicate_listen_addrs", 0),
+            ScoreAction::Continue
+        );
+        assert_eq!(
+            scoring.report(&peer_id, "identify.invalid_data", 0),
+            ScoreAction::Disconnect
+        );
+        assert_eq!(scoring.scores(0)[0].score as u32, 110);
+        assert_eq!(scoring.scores(0)[0].misbehaviors, 2);
+
+        // 110 halves twice after 20 minutes
+        assert_eq!(scoring.scores(20 * 60 * 1000)[0].score as u32, 27);
+        // forgotten after a day
+        assert!(scoring.scores(24 * 60 * 60 * 1000).is_empty());
+    }
+
+    #[test]
+    fn test_ban_resets_score() {
+        let mut scoring = PeerScoring::new(PeerScoringConfig::default());
+        let peer_id = PeerId::random();
+
+        assert_eq!(
+            scoring.report(&peer_id, "unknown", 0),
+            ScoreAction::Disconnect
+        );
+        assert_eq!(
+            scoring.report(&peer_id, "unknown", 0),
+            ScoreAction::Ban(Duration::from_secs(60 * 60))
+        );
+        assert!(scoring.scores(0).is_empty());
+    }
+
+    #[test]
+    fn test_invalid_sync_messages_ban_at_once() {
+        let mut scoring = PeerScoring::new(PeerScoringConfig::default());
+        let peer_id = PeerId::random();
+
+        assert_eq!(
+            scoring.report(&peer_id, "relay.invalid_transaction", 0),
+            ScoreAction::Ban(Duration::from_secs(60 * 60))
+        );
+
+        let mut config = PeerScoringConfig::default();
+        config
+            .penalties
+            .insert("relay.invalid_transaction".to_string(), 100);
+        let mut scoring = PeerScoring::new(config);
+        assert_eq!(
+            scoring.report(&peer_id, "relay.invalid_transaction", 0),
+            ScoreAction::Disconnect
+        );
+    }
+}
@@ -1,13 +1,53 @@
+//! The addresses are organized into buckets keyed by their network groups.
+//!
+//! An address which we never connected to is kept in the "new" table, and moved to the "tried"
+//! table once connected. The addresses of a network group fall into a few buckets of a table, and
+//! the addresses are fetched from the buckets in turn, so a network group flooding us with
+//! addresses gets no more chance than the others, which makes eclipse attacks harder. The buckets
+//! are derived from a random key, which is regenerated on startup, so an attacker can't predict
+//! them.
+use crate::network_group::NetworkGroup;
 use crate::peer_store::types::{AddrInfo, IpPort};
-use rand::Rng;
+use rand::seq::SliceRandom;
+use std::collections::hash_map::DefaultHasher;
 use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+pub(crate) const NEW_BUCKET_COUNT: usize = 1024;
+pub(crate) const TRIED_BUCKET_COUNT: usize = 256;
+/// The addresses of a network group fall into at most this many buckets of the new table
+const NEW_BUCKETS_PER_GROUP: u64 = 16;
+/// The addresses of a network group fall into at most this many buckets of the tried table
+const TRIED_BUCKETS_PER_GROUP: u64 = 4;
+/// The terrible addresses in a bucket are evicted once the bucket reaches this size
+const BUCKET_SIZE: usize = 64;
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum Table {
+    New,
+    Tried,
+}
 
-#[derive(Default)]
 pub struct AddrManager {
     next_id: u64,
     addr_to_id: HashMap<IpPort, u64>,
     id_to_info: HashMap<u64, AddrInfo>,
-    random_ids: Vec<u64>,
+    key: u64,
+    buckets: HashMap<(Table, usize), Vec<u64>>,
+    id_to_bucket: HashMap<u64, (Table, usize)>,
+}
+
+impl Default for AddrManager {
+    fn default() -> Self {
+        AddrManager {
+            next_id: 0,
+            addr_to_id: HashMap::default(),
+            id_to_info: HashMap::default(),
+            key: rand::random(),
+            buckets: HashMap::default(),
+            id_to_bucket: HashMap::default(),
+        }
+    }
 }
 
 impl AddrManager {
@@ -24,10 +64,20 @@ impl AddrManager {
                 return;
             }
         }
+        let table = if addr_info.last_connected_at_ms > 0 {
+            Table::Tried
+        } else {
+            Table::New
+        };
+        let bucket = (table, self.bucket_index(table, &addr_info));
+        self.evict_terrible(bucket);
+
+        let ids = self.buckets.entry(bucket).or_default();
+        addr_info.random_id_pos = ids.len();
+        ids.push(id);
         self.addr_to_id.insert(key, id);
-        addr_info.random_id_pos = self.random_ids.len();
         self.id_to_info.insert(id, addr_info);
-        self.random_ids.push(id);
+        self.id_to_bucket.insert(id, bucket);
         self.next_id += 1;
     }
 
@@ -36,27 +86,65 @@ impl AddrManager {
     where
         F: Fn(&AddrInfo) -> bool,
     {
+        self.fetch_random_in(None, count, filter)
+    }
+
+    /// randomly return addrs in the table that worth to try or connect.
+    pub fn fetch_random_in_table<F>(
+        &mut self,
+        table: Table,
+        count: usize,
+        filter: F,
+    ) -> Vec<AddrInfo>
+    where
+        F: Fn(&AddrInfo) -> bool,
+    {
+        self.fetch_random_in(Some(table), count, filter)
+    }
+
+    // Takes an address from each bucket in turn, the buckets and the addresses in a bucket are
+    // shuffled.
+    fn fetch_random_in<F>(&mut self, table: Option<Table>, count: usize, filter: F) -> Vec<AddrInfo>
+    where
+        F: Fn(&AddrInfo) -> bool,
+    {
+        let mut rng = rand::thread_rng();
+        let mut buckets: Vec<Vec<u64>> = self
+            .buckets
+            .iter()
+            .filter(|((bucket_table, _), _)| table.map_or(true, |table| table == *bucket_table))
+            .map(|(_, ids)| {
+                let mut ids = ids.clone();
+                ids.shuffle(&mut rng);
+                ids
+            })
+            .collect();
+        buckets.shuffle(&mut rng);
+
         let mut duplicate_ips = HashSet::new();
         let mut addr_infos = Vec::with_capacity(count);
-        let mut rng = rand::thread_rng();
         let now_ms = faketime::unix_time_as_millis();
-        for i in 0..self.random_ids.len() {
-            // reuse the for loop to shuffle random ids
-            // https://en.wikipedia.org/wiki/Fisher%E2%80%93Yates_shuffle
-            let j = rng.gen_range(i, self.random_ids.len());
-            self.swap_random_id(j, i);
-            let addr_info: AddrInfo = self.id_to_info[&self.random_ids[i]].to_owned();
-            let is_unique_ip = duplicate_ips.insert(addr_info.ip_port.ip);
-            // A trick to make our tests work
-            // TODO remove this after fix the network tests.
-            let is_test_ip =
-                addr_info.ip_port.ip.is_unspecified() || addr_info.ip_port.ip.is_loopback();
-            if (is_test_ip || is_unique_ip) && !addr_info.is_terrible(now_ms) && filter(&addr_info)
-            {
-                addr_infos.push(addr_info);
-            }
-            if addr_infos.len() == count {
-                break;
+        let max_bucket_len = buckets.iter().map(Vec::len).max().unwrap_or(0);
+        for i in 0..max_bucket_len {
+            for ids in &buckets {
+                let addr_info = match ids.get(i) {
+                    Some(id) => &self.id_to_info[id],
+                    None => continue,
+                };
+                let is_unique_ip = duplicate_ips.insert(addr_info.ip_port.ip);
+                // A trick to make our tests work
+                // TODO remove this after fix the network tests.
+                let is_test_ip =
+                    addr_info.ip_port.ip.is_unspecified() || addr_info.ip_port.ip.is_loopback();
+                if (is_test_ip || is_unique_ip)
+                    && !addr_info.is_terrible(now_ms)
+                    && filter(addr_info)
+                {
+                    addr_infos.push(addr_info.to_owned());
+                }
+                if addr_infos.len() == count {
+                    return addr_infos;
+                }
             }
         }
         addr_infos
@@ -66,20 +154,36 @@ impl AddrManager {
         self.addr_to_id.len()
     }
 
+    /// Returns the count of the addresses in the table.
+    pub fn table_count(&self, table: Table) -> usize {
+        self.buckets
+            .iter()
+            .filter(|((bucket_table, _), _)| *bucket_table == table)
+            .map(|(_, ids)| ids.len())
+            .sum()
+    }
+
     pub fn addrs_iter(&self) -> impl Iterator<Item = &AddrInfo> {
         self.id_to_info.values()
     }
 
     pub fn remove(&mut self, addr: &IpPort) -> Option<AddrInfo> {
-        if let Some(id) = self.addr_to_id.remove(&addr) {
-            let random_id_pos = self.id_to_info.get(&id).expect("exists").random_id_pos;
-            // swap with last index, then remove the last index
-            self.swap_random_id(random_id_pos, self.random_ids.len() - 1);
-            self.random_ids.pop();
-            self.id_to_info.remove(&id)
-        } else {
-            None
+        let id = self.addr_to_id.remove(&addr)?;
+        let bucket = self.id_to_bucket.remove(&id).expect("exists");
+        let pos = self.id_to_info.get(&id).expect("exists").random_id_pos;
+        let ids = self.buckets.get_mut(&bucket).expect("exists");
+        // swap with last index, then remove the last index
+        ids.swap_remove(pos);
+        if let Some(moved_id) = ids.get(pos) {
+            self.id_to_info
+                .get_mut(moved_id)
+                .expect("exists")
+                .random_id_pos = pos;
         }
+        if ids.is_empty() {
+            self.buckets.remove(&bucket);
+        }
+        self.id_to_info.remove(&id)
     }
 
     pub fn get(&self, addr: &IpPort) -> Option<&AddrInfo> {
@@ -96,20 +200,39 @@ impl AddrManager {
         }
     }
 
-    /// swap random_id i and j,
-    /// this function keep random_id_pos in consistency
-    fn swap_random_id(&mut self, i: usize, j: usize) {
-        if i == j {
-            return;
+    /// The bucket of the address is picked among the few buckets of its network group by the
+    /// address itself.
+    fn bucket_index(&self, table: Table, addr_info: &AddrInfo) -> usize {
+        let (bucket_count, buckets_per_group) = match table {
+            Table::New => (NEW_BUCKET_COUNT, NEW_BUCKETS_PER_GROUP),
+            Table::Tried => (TRIED_BUCKET_COUNT, TRIED_BUCKETS_PER_GROUP),
+        };
+        let slot = self.hash(&(table, addr_info.ip_port)) % buckets_per_group;
+        let group = addr_info.addr.network_group();
+        (self.hash(&(table, group, slot)) % bucket_count as u64) as usize
+    }
+
+    fn hash<T: Hash>(&self, value: &T) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.key.hash(&mut hasher);
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn evict_terrible(&mut self, bucket: (Table, usize)) {
+        let ids = match self.buckets.get(&bucket) {
+            Some(ids) if ids.len() >= BUCKET_SIZE => ids,
+            _ => return,
+        };
+        let now_ms = faketime::unix_time_as_millis();
+        let terrible: Vec<IpPort> = ids
+            .iter()
+            .map(|id| &self.id_to_info[id])
+            .filter(|addr_info| addr_info.is_terrible(now_ms))
+            .map(AddrInfo::ip_port)
+            .collect();
+        for addr in terrible {
+            self.remove(&addr);
         }
-        self.id_to_info
-            .get_mut(&self.random_ids[i])
-            .expect("exists")
-            .random_id_pos = j;
-        self.id_to_info
-            .get_mut(&self.random_ids[j])
-            .expect("exists")
-            .random_id_pos = i;
-        self.random_ids.swap(i, j);
     }
 }
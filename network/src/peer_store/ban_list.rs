@@ -65,6 +65,11 @@ impl BanList {
         false
     }
 
+    /// Lifts all the bans
+    pub fn clear(&mut self) {
+        self.inner.clear();
+    }
+
     pub fn get_banned_addrs(&self) -> Vec<BannedAddr> {
         self.inner.values().map(ToOwned::to_owned).collect()
     }
@@ -2,7 +2,7 @@ use crate::{
     errors::{PeerStoreError, Result},
     network_group::{Group, NetworkGroup},
     peer_store::{
-        addr_manager::AddrManager,
+        addr_manager::{AddrManager, Table},
         ban_list::BanList,
         types::{ip_to_network, AddrInfo, BannedAddr, MultiaddrExt, PeerInfo},
         Behaviour, Multiaddr, PeerScoreConfig, ReportResult, Status, ADDR_COUNT_LIMIT,
@@ -12,7 +12,7 @@ use crate::{
 };
 use ipnetwork::IpNetwork;
 use std::cell::{Ref, RefCell};
-use std::collections::{hash_map::Entry, HashMap};
+use std::collections::{hash_map::Entry, HashMap, HashSet};
 
 #[derive(Default)]
 pub struct PeerStore {
@@ -129,29 +129,44 @@ impl PeerStore {
         let ban_list = self.ban_list.borrow();
         let peers = self.peers.borrow();
         // get addrs that can attempt.
-        self.addr_manager
+        let addrs = self
+            .addr_manager
             .fetch_random(count, |peer_addr: &AddrInfo| {
                 !ban_list.is_addr_banned(&peer_addr.addr)
                     && !peers.contains_key(&peer_addr.peer_id)
                     && !peer_addr.tried_in_last_minute(now_ms)
-            })
+            });
+        rank_by_transport(addrs)
     }
 
     /// Get peers for feeler connection, this method randomly return peer addrs that we never
-    /// connected to.
+    /// connected to. The addrs in the new table are preferred, then the tried addrs which have
+    /// not been connected for a long time.
     pub fn fetch_addrs_to_feeler(&mut self, count: usize) -> Vec<AddrInfo> {
         let now_ms = faketime::unix_time_as_millis();
         let addr_expired_ms = now_ms - ADDR_TIMEOUT_MS;
         // get expired or never successed addrs.
         let ban_list = self.ban_list.borrow();
         let peers = self.peers.borrow();
-        self.addr_manager
-            .fetch_random(count, |peer_addr: &AddrInfo| {
-                !ban_list.is_addr_banned(&peer_addr.addr)
-                    && !peers.contains_key(&peer_addr.peer_id)
-                    && !peer_addr.tried_in_last_minute(now_ms)
-                    && !peer_addr.had_connected(addr_expired_ms)
-            })
+        let filter = |peer_addr: &AddrInfo| {
+            !ban_list.is_addr_banned(&peer_addr.addr)
+                && !peers.contains_key(&peer_addr.peer_id)
+                && !peer_addr.tried_in_last_minute(now_ms)
+                && !peer_addr.had_connected(addr_expired_ms)
+        };
+        let mut addrs = self
+            .addr_manager
+            .fetch_random_in_table(Table::New, count, filter);
+        if addrs.len() < count {
+            let fetched: HashSet<_> = addrs.iter().map(AddrInfo::ip_port).collect();
+            let rest = self.addr_manager.fetch_random_in_table(
+                Table::Tried,
+                count - addrs.len(),
+                |peer_addr| !fetched.contains(&peer_addr.ip_port()) && filter(peer_addr),
+            );
+            addrs.extend(rest);
+        }
+        addrs
     }
 
     /// return valid addrs that success connected, used for discovery.
@@ -187,10 +202,20 @@ impl PeerStore {
         ban_reason: String,
     ) -> Result<()> {
         let now_ms = faketime::unix_time_as_millis();
+        self.ban_network_until(network, now_ms + timeout_ms, ban_reason)
+    }
+
+    /// Ban the network until the unix time in milliseconds
+    pub(crate) fn ban_network_until(
+        &mut self,
+        network: IpNetwork,
+        ban_until: u64,
+        ban_reason: String,
+    ) -> Result<()> {
         let ban_addr = BannedAddr {
             address: network,
-            ban_until: now_ms + timeout_ms,
-            created_at: now_ms,
+            ban_until,
+            created_at: faketime::unix_time_as_millis(),
             ban_reason,
         };
         self.mut_ban_list().ban(ban_addr);
@@ -248,3 +273,12 @@ impl PeerStore {
         Ok(())
     }
 }
+
+/// Keeps the address of the preferred transport of each peer, a peer listening on both TCP and
+/// WebSocket is dialed once, via TCP.
+fn rank_by_transport(mut addrs: Vec<AddrInfo>) -> Vec<AddrInfo> {
+    addrs.sort_by_key(|addr| addr.addr.transport_type());
+    let mut peer_ids = HashSet::new();
+    addrs.retain(|addr| peer_ids.insert(addr.peer_id.clone()));
+    addrs
+}
@@ -145,11 +145,22 @@ pub fn ip_to_network(ip: IpAddr) -> IpNetwork {
     }
 }
 
+/// The transports of the p2p addresses, ordered by the preference when dialing a peer listening
+/// on several of them.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TransportType {
+    /// `/ip4/127.0.0.1/tcp/8115`
+    Tcp,
+    /// `/ip4/127.0.0.1/tcp/8116/ws`, for the browser-based or proxied peers
+    Ws,
+}
+
 pub trait MultiaddrExt {
     /// extract IP from multiaddr,
     fn extract_ip_addr(&self) -> Result<IpPort, Error>;
     fn exclude_p2p(&self) -> Multiaddr;
     fn attach_p2p(&self, peer_id: &PeerId) -> Result<Multiaddr, Error>;
+    fn transport_type(&self) -> TransportType;
 }
 
 impl MultiaddrExt for Multiaddr {
@@ -185,4 +196,12 @@ impl MultiaddrExt for Multiaddr {
         addr.push(multiaddr::Protocol::P2P(peer_id_hash));
         Ok(addr)
     }
+
+    fn transport_type(&self) -> TransportType {
+        if self.iter().any(|proto| proto == Protocol::WS) {
+            TransportType::Ws
+        } else {
+            TransportType::Tcp
+        }
+    }
 }
@@ -27,8 +27,22 @@ pub enum Misbehavior {
     TooManyAddresses(usize),
 }
 
+impl Misbehavior {
+    /// The kind to look up the penalty, see `PeerScoringConfig`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Misbehavior::DuplicateGetNodes => "discovery.duplicate_get_nodes",
+            Misbehavior::DuplicateFirstNodes => "discovery.duplicate_first_nodes",
+            Misbehavior::TooManyItems { .. } => "discovery.too_many_items",
+            Misbehavior::TooManyAddresses(_) => "discovery.too_many_addresses",
+        }
+    }
+}
+
 /// Misbehavior report result
 pub enum MisbehaveResult {
+    /// Continue to run
+    Continue,
     /// Disconnect this peer
     Disconnect,
 }
@@ -37,7 +51,7 @@ impl MisbehaveResult {
     pub fn is_disconnect(&self) -> bool {
         match self {
             MisbehaveResult::Disconnect => true,
-            // _ => false,
+            _ => false,
         }
     }
 }
@@ -28,7 +28,7 @@ use rand::seq::SliceRandom;
 use tokio::time::Interval;
 
 // use crate::peer_store::Behaviour;
-use crate::NetworkState;
+use crate::{NetworkState, ScoreAction};
 
 const CHECK_INTERVAL: Duration = Duration::from_secs(3);
 
@@ -461,9 +461,15 @@ impl AddressManager for DiscoveryAddressManager {
         }
     }
 
-    fn misbehave(&mut self, _session_id: SessionId, _kind: Misbehavior) -> MisbehaveResult {
-        // FIXME:
-        MisbehaveResult::Disconnect
+    fn misbehave(&mut self, session_id: SessionId, kind: Misbehavior) -> MisbehaveResult {
+        let peer_id = match self.sessions.read().get(&session_id) {
+            Some(peer_id) => peer_id.clone(),
+            None => return MisbehaveResult::Disconnect,
+        };
+        match self.network_state.report_misbehavior(&peer_id, kind.kind()) {
+            ScoreAction::Continue => MisbehaveResult::Continue,
+            ScoreAction::Disconnect | ScoreAction::Ban(_) => MisbehaveResult::Disconnect,
+        }
     }
 
     fn get_random(&mut self, n: usize) -> Vec<Multiaddr> {
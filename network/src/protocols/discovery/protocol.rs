@@ -189,11 +189,23 @@ pub struct Nodes {
     pub(crate) items: Vec<Node>,
 }
 
+impl Nodes {
+    pub fn new(announce: bool, items: Vec<Node>) -> Self {
+        Nodes { announce, items }
+    }
+}
+
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct Node {
     pub(crate) addresses: Vec<Multiaddr>,
 }
 
+impl Node {
+    pub fn new(addresses: Vec<Multiaddr>) -> Self {
+        Node { addresses }
+    }
+}
+
 impl std::fmt::Display for DiscoveryMessage {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
         match self {
@@ -216,3 +228,37 @@ impl std::fmt::Display for DiscoveryMessage {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_decode() {
+        let items = vec![
+            Node::new(vec!["/ip4/1.1.1.1/tcp/8115".parse().unwrap()]),
+            Node::new(vec![
+                "/ip4/2.2.2.2/tcp/8115".parse().unwrap(),
+                "/ip4/3.3.3.3/tcp/8115".parse().unwrap(),
+            ]),
+        ];
+        for message in vec![
+            DiscoveryMessage::Nodes(Nodes::new(true, items.clone())),
+            DiscoveryMessage::Nodes(Nodes::new(false, Vec::new())),
+            DiscoveryMessage::GetNodes {
+                version: 0,
+                count: 1000,
+                listen_port: Some(8115),
+            },
+            DiscoveryMessage::GetNodes {
+                version: 0,
+                count: 1000,
+                listen_port: None,
+            },
+        ] {
+            let data = message.clone().encode();
+            assert_eq!(DiscoveryMessage::decode(&data), Some(message));
+        }
+        assert_eq!(DiscoveryMessage::decode(b"invalid"), None);
+    }
+}
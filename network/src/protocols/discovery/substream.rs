@@ -23,6 +23,7 @@ use tokio_util::codec::Framed;
 
 use super::addr::{AddrKnown, AddressManager, Misbehavior, RawAddr};
 use super::protocol::{DiscoveryCodec, DiscoveryMessage, Node, Nodes};
+use crate::peer_store::types::{MultiaddrExt, TransportType};
 
 // FIXME: should be a more high level version number
 const VERSION: u32 = 0;
@@ -386,10 +387,13 @@ impl Substream {
             sender: context.control().clone(),
         };
         let listen_port = if context.session.ty.is_outbound() {
+            // the port of the TCP listener, the remote rewrites it into the inbound address
             context
                 .listens()
                 .iter()
-                .map(|address| multiaddr_to_socketaddr(address).unwrap().port())
+                .filter(|address| address.transport_type() == TransportType::Tcp)
+                .filter_map(|address| multiaddr_to_socketaddr(address))
+                .map(|socket_addr| socket_addr.port())
                 .next()
         } else {
             None
@@ -434,13 +438,15 @@ impl RemoteAddress {
 
     fn update_port(&mut self, port: u16) {
         if let RemoteAddress::Init(ref addr) = self {
+            // the port is the one of the TCP listener, even if the session is via WebSocket
             let addr = addr
                 .into_iter()
-                .map(|proto| {
+                .filter_map(|proto| {
                     match proto {
                         // TODO: other transport, UDP for example
-                        Protocol::TCP(_) => Protocol::TCP(port),
-                        value => value,
+                        Protocol::TCP(_) => Some(Protocol::TCP(port)),
+                        Protocol::WS => None,
+                        value => Some(value),
                     }
                 })
                 .collect();
@@ -1,7 +1,9 @@
 use std::collections::HashMap;
 use std::sync::Arc;
-use std::time::{Duration, Instant};
+use std::time::Duration;
 
+use ckb_app_config::IdentifyConfig;
+use ckb_clock::{Clock, SystemClock};
 use ckb_logger::{debug, error, trace, warn};
 use p2p::{
     bytes::Bytes,
@@ -11,23 +13,24 @@ use p2p::{
     service::{SessionType, TargetProtocol},
     traits::ServiceProtocol,
     utils::{is_reachable, multiaddr_to_socketaddr},
-    SessionId,
+    ProtocolId, SessionId,
 };
 
 mod protocol;
 
-use crate::{network::FEELER_PROTOCOL_ID, NetworkState, PeerIdentifyInfo};
+use crate::{
+    network::FEELER_PROTOCOL_ID,
+    peer_store::types::{MultiaddrExt, TransportType},
+    NetworkState, PeerIdentifyInfo, ScoreAction,
+};
 use ckb_types::{packed, prelude::*};
 
-use protocol::IdentifyMessage;
+pub use protocol::{IdentifyExtension, IdentifyMessage};
 
 const MAX_RETURN_LISTEN_ADDRS: usize = 10;
-const BAN_ON_NOT_SAME_NET: Duration = Duration::from_secs(5 * 60);
 const CHECK_TIMEOUT_TOKEN: u64 = 100;
 // Check timeout interval (seconds)
 const CHECK_TIMEOUT_INTERVAL: u64 = 1;
-const DEFAULT_TIMEOUT: u64 = 8;
-const MAX_ADDRS: usize = 10;
 
 /// The misbehavior to report to underlying peer storage
 pub enum Misbehavior {
@@ -43,6 +46,20 @@ pub enum Misbehavior {
     TooManyAddresses(usize),
 }
 
+impl Misbehavior {
+    /// The kind to look up the penalty, see `PeerScoringConfig`, and the action, see
+    /// `IdentifyConfig`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Misbehavior::DuplicateListenAddrs => "identify.duplicate_listen_addrs",
+            Misbehavior::DuplicateObservedAddr => "identify.duplicate_observed_addr",
+            Misbehavior::Timeout => "identify.timeout",
+            Misbehavior::InvalidData => "identify.invalid_data",
+            Misbehavior::TooManyAddresses(_) => "identify.too_many_addresses",
+        }
+    }
+}
+
 /// Misbehavior report result
 pub enum MisbehaveResult {
     /// Continue to run
@@ -62,11 +79,12 @@ impl MisbehaveResult {
 
 /// The trait to communicate with underlying peer storage
 pub trait Callback: Clone + Send {
-    /// Received custom message
+    /// Received custom message, along with the protocol version and the extensions of the identify
+    /// message
     fn received_identify(
         &mut self,
         context: &mut ProtocolContextMutRef,
-        identify: &[u8],
+        message: &IdentifyMessage,
     ) -> MisbehaveResult;
     /// Get custom identify message
     fn identify(&mut self) -> &[u8];
@@ -83,26 +101,38 @@ pub trait Callback: Clone + Send {
     ) -> MisbehaveResult;
     /// Report misbehavior
     fn misbehave(&mut self, peer: &PeerId, kind: Misbehavior) -> MisbehaveResult;
+    /// Counts the received message against the rate limits, returns whether to process it
+    fn check_rate_limit(&mut self, context: &mut ProtocolContextMutRef, len: usize) -> bool;
 }
 
 /// Identify protocol
 pub struct IdentifyProtocol<T> {
     callback: T,
+    config: IdentifyConfig,
     remote_infos: HashMap<SessionId, RemoteInfo>,
     secio_enabled: bool,
     global_ip_only: bool,
+    clock: Arc<dyn Clock>,
 }
 
 impl<T: Callback> IdentifyProtocol<T> {
-    pub fn new(callback: T) -> IdentifyProtocol<T> {
+    pub fn new(callback: T, config: IdentifyConfig) -> IdentifyProtocol<T> {
         IdentifyProtocol {
             callback,
+            config,
             remote_infos: HashMap::default(),
             secio_enabled: true,
             global_ip_only: true,
+            clock: Arc::new(SystemClock),
         }
     }
 
+    /// The time source of the identify timeouts, the system clock by default
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
     /// Turning off global ip only mode will allow any ip to be broadcast, default is true
     // pub fn global_ip_only(mut self, global_ip_only: bool) -> Self {
     //     self.global_ip_only = global_ip_only;
@@ -124,7 +154,7 @@ impl<T: Callback> IdentifyProtocol<T> {
             debug!("remote({:?}) repeat send observed address", info.peer_id);
             self.callback
                 .misbehave(&info.peer_id, Misbehavior::DuplicateListenAddrs)
-        } else if listens.len() > MAX_ADDRS {
+        } else if listens.len() > self.config.max_addrs {
             self.callback
                 .misbehave(&info.peer_id, Misbehavior::TooManyAddresses(listens.len()))
         } else {
@@ -184,14 +214,15 @@ impl<T: Callback> IdentifyProtocol<T> {
 pub(crate) struct RemoteInfo {
     peer_id: PeerId,
     session: SessionContext,
-    connected_at: Instant,
+    // unix time in milliseconds
+    connected_at: u64,
     timeout: Duration,
     listen_addrs: Option<Vec<Multiaddr>>,
     observed_addr: Option<Multiaddr>,
 }
 
 impl RemoteInfo {
-    fn new(session: SessionContext, timeout: Duration) -> RemoteInfo {
+    fn new(session: SessionContext, timeout: Duration, connected_at: u64) -> RemoteInfo {
         let peer_id = session
             .remote_pubkey
             .as_ref()
@@ -200,7 +231,7 @@ impl RemoteInfo {
         RemoteInfo {
             peer_id,
             session,
-            connected_at: Instant::now(),
+            connected_at,
             timeout,
             listen_addrs: None,
             observed_addr: None,
@@ -232,7 +263,11 @@ impl<T: Callback> ServiceProtocol for IdentifyProtocol<T> {
             return;
         }
 
-        let remote_info = RemoteInfo::new(session.clone(), Duration::from_secs(DEFAULT_TIMEOUT));
+        let remote_info = RemoteInfo::new(
+            session.clone(),
+            Duration::from_secs(self.config.timeout_secs),
+            self.clock.unix_time_as_millis(),
+        );
         trace!("IdentifyProtocol sconnected from {:?}", remote_info.peer_id);
         self.remote_infos.insert(session.id, remote_info);
 
@@ -245,7 +280,7 @@ impl<T: Callback> ServiceProtocol for IdentifyProtocol<T> {
                     .map(|socket_addr| !self.global_ip_only || is_reachable(socket_addr.ip()))
                     .unwrap_or(false)
             })
-            .take(MAX_ADDRS)
+            .take(self.config.max_addrs)
             .cloned()
             .collect();
 
@@ -279,13 +314,16 @@ impl<T: Callback> ServiceProtocol for IdentifyProtocol<T> {
         }
 
         let session = context.session;
+        if !self.callback.check_rate_limit(&mut context, data.len()) {
+            return;
+        }
 
         match IdentifyMessage::decode(&data) {
             Some(message) => {
                 // Need to interrupt processing, avoid pollution
                 if self
                     .callback
-                    .received_identify(&mut context, message.identify)
+                    .received_identify(&mut context, &message)
                     .is_disconnect()
                     || self
                         .process_listens(&mut context, message.listen_addrs)
@@ -322,10 +360,10 @@ impl<T: Callback> ServiceProtocol for IdentifyProtocol<T> {
             return;
         }
 
-        let now = Instant::now();
+        let now = self.clock.unix_time_as_millis();
         for (session_id, info) in &self.remote_infos {
             if (info.listen_addrs.is_none() || info.observed_addr.is_none())
-                && (info.connected_at + info.timeout) <= now
+                && info.connected_at + info.timeout.as_millis() as u64 <= now
             {
                 debug!("{:?} receive identify message timeout", info.peer_id);
                 if self
@@ -352,7 +390,17 @@ impl IdentifyCallback {
         name: String,
         client_version: String,
     ) -> IdentifyCallback {
-        let flags = Flags(Flag::FullNode as u64);
+        let mut flags = Flags::default();
+        for flag in network_state.protocol_flags.read().values() {
+            flags.insert(*flag);
+        }
+        // the nodes before the capability flags only know `FullNode`, and the flags can't be
+        // empty
+        if flags == Flags::default()
+            || (flags.contains(Flag::Sync.into()) && flags.contains(Flag::Relay.into()))
+        {
+            flags.insert(Flag::FullNode);
+        }
 
         IdentifyCallback {
             network_state,
@@ -360,6 +408,19 @@ impl IdentifyCallback {
         }
     }
 
+    /// Returns the local protocols the remote end supports according to its flags.
+    fn supported_protocols(&self, remote_flags: Flags, ignore_flags: bool) -> Vec<ProtocolId> {
+        let protocol_flags = self.network_state.protocol_flags.read();
+        self.network_state.get_protocol_ids(|id| {
+            id != FEELER_PROTOCOL_ID.into()
+                && (ignore_flags
+                    || protocol_flags
+                        .get(&id)
+                        .map(|flag| remote_flags.supports(*flag))
+                        .unwrap_or(true))
+        })
+    }
+
     fn listen_addrs(&self) -> Vec<Multiaddr> {
         let mut addrs = self.network_state.public_addrs(MAX_RETURN_LISTEN_ADDRS * 2);
         addrs.sort_by(|a, b| a.1.cmp(&b.1));
@@ -379,14 +440,16 @@ impl Callback for IdentifyCallback {
     fn received_identify(
         &mut self,
         context: &mut ProtocolContextMutRef,
-        identify: &[u8],
+        message: &IdentifyMessage,
     ) -> MisbehaveResult {
-        match self.identify.verify(identify) {
+        match self.identify.verify(message.identify) {
             None => {
                 self.network_state.ban_session(
                     context.control(),
                     context.session.id,
-                    BAN_ON_NOT_SAME_NET,
+                    Duration::from_secs(
+                        self.network_state.config.identify.ban_on_not_same_net_secs,
+                    ),
                     "The nodes are not on the same network".to_string(),
                 );
                 MisbehaveResult::Disconnect
@@ -397,6 +460,7 @@ impl Callback for IdentifyCallback {
                         if let Some(peer) = registry.get_peer_mut(context.session.id) {
                             peer.identify_info = Some(PeerIdentifyInfo {
                                 client_version: version,
+                                protocol_version: message.protocol_version,
                             })
                         }
                     });
@@ -417,19 +481,20 @@ impl Callback for IdentifyCallback {
                             context.session.id,
                             TargetProtocol::Single(FEELER_PROTOCOL_ID.into()),
                         );
-                    } else if flags.contains(self.identify.flags) {
-                        registry_client_version(client_version);
-
-                        // The remote end can support all local protocols.
-                        let protos = self
+                    } else {
+                        // Only open the protocols both ends support, the peers in the whitelist
+                        // are trusted to support all
+                        let is_whitelist = self
                             .network_state
-                            .get_protocol_ids(|id| id != FEELER_PROTOCOL_ID.into());
+                            .with_peer_registry(|reg| reg.is_whitelist(&peer_id));
+                        let protos = self.supported_protocols(flags, is_whitelist);
+                        if protos.is_empty() {
+                            return MisbehaveResult::Disconnect;
+                        }
+                        registry_client_version(client_version);
 
                         let _ = context
                             .open_protocols(context.session.id, TargetProtocol::Multi(protos));
-                    } else {
-                        // The remote end cannot support all local protocols.
-                        return MisbehaveResult::Disconnect;
                     }
                 } else {
                     registry_client_version(client_version);
@@ -491,26 +556,49 @@ impl Callback for IdentifyCallback {
             return MisbehaveResult::Continue;
         }
 
+        // the observed ip with the port and the transport of each listen address
         let observed_addrs_iter = self
             .listen_addrs()
             .into_iter()
-            .filter_map(|listen_addr| multiaddr_to_socketaddr(&listen_addr))
-            .map(|socket_addr| {
-                addr.iter()
+            .filter_map(|listen_addr| {
+                multiaddr_to_socketaddr(&listen_addr)
+                    .map(|socket_addr| (socket_addr, listen_addr.transport_type()))
+            })
+            .map(|(socket_addr, transport_type)| {
+                let mut observed_addr = addr
+                    .iter()
                     .filter_map(|proto| match proto {
-                        Protocol::P2P(_) => None,
+                        Protocol::P2P(_) | Protocol::WS => None,
                         Protocol::TCP(_) => Some(Protocol::TCP(socket_addr.port())),
                         value => Some(value),
                     })
-                    .collect::<Multiaddr>()
+                    .collect::<Multiaddr>();
+                if transport_type == TransportType::Ws {
+                    observed_addr.push(Protocol::WS);
+                }
+                observed_addr
             });
         self.network_state.add_observed_addrs(observed_addrs_iter);
         // NOTE: for future usage
         MisbehaveResult::Continue
     }
 
-    fn misbehave(&mut self, _peer_id: &PeerId, _kind: Misbehavior) -> MisbehaveResult {
-        MisbehaveResult::Disconnect
+    fn misbehave(&mut self, peer_id: &PeerId, kind: Misbehavior) -> MisbehaveResult {
+        let kind = kind.kind();
+        let action = self.network_state.config.identify.misbehavior_action(kind);
+        match self.network_state.punish_misbehavior(peer_id, kind, action) {
+            ScoreAction::Continue => MisbehaveResult::Continue,
+            ScoreAction::Disconnect | ScoreAction::Ban(_) => MisbehaveResult::Disconnect,
+        }
+    }
+
+    fn check_rate_limit(&mut self, context: &mut ProtocolContextMutRef, len: usize) -> bool {
+        self.network_state.check_rate_limit(
+            context.control(),
+            context.session.id,
+            context.proto_id,
+            len,
+        )
     }
 }
 
@@ -546,7 +634,9 @@ impl Identify {
     }
 
     fn verify<'a>(&self, data: &'a [u8]) -> Option<(Flags, String)> {
-        let reader = packed::IdentifyReader::from_slice(data).ok()?;
+        // Tolerate the fields appended by the later versions, so adding them doesn't get the
+        // peers banned as on another network
+        let reader = packed::IdentifyReader::from_compatible_slice(data).ok()?;
 
         let name = reader.name().as_utf8().ok()?.to_owned();
         if self.name != name {
@@ -565,11 +655,23 @@ impl Identify {
     }
 }
 
+/// The capabilities advertised in the identify message, a protocol which requires a flag is only
+/// opened with the peers advertising it.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 #[repr(u64)]
-enum Flag {
-    /// Support all protocol
+pub enum Flag {
+    /// Support sync and relay, the nodes before the other flags advertise it only
     FullNode = 0x1,
+    /// Serve light client protocol
+    LightClientServer = 0x2,
+    /// Relay transactions and compact blocks
+    Relay = 0x4,
+    /// Serve headers and blocks
+    Sync = 0x8,
+    /// Serve block filters
+    BlockFilter = 0x10,
+    /// Reconcile the relayed transactions by sketches instead of flooding their hashes
+    TxReconciliation = 0x20,
 }
 
 #[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
@@ -580,6 +682,21 @@ impl Flags {
     fn contains(self, flags: Flags) -> bool {
         (self.0 & flags.0) == flags.0
     }
+
+    /// Check if the capability is advertised, `FullNode` implies the capabilities which all the
+    /// full nodes have.
+    fn supports(self, flag: Flag) -> bool {
+        self.contains(flag.into())
+            || (self.contains(Flag::FullNode.into())
+                && match flag {
+                    Flag::Relay | Flag::Sync | Flag::BlockFilter => true,
+                    Flag::FullNode | Flag::LightClientServer | Flag::TxReconciliation => false,
+                })
+    }
+
+    fn insert(&mut self, flag: Flag) {
+        self.0 |= flag as u64;
+    }
 }
 
 impl From<Flag> for Flags {
@@ -593,3 +710,24 @@ impl From<u64> for Flags {
         Flags(value)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flags_supports() {
+        let legacy = Flags::from(Flag::FullNode);
+        assert!(legacy.supports(Flag::Sync));
+        assert!(legacy.supports(Flag::BlockFilter));
+        assert!(!legacy.supports(Flag::LightClientServer));
+        assert!(!legacy.supports(Flag::TxReconciliation));
+
+        let mut sync_only = Flags::from(Flag::Sync);
+        assert!(sync_only.supports(Flag::Sync));
+        assert!(!sync_only.supports(Flag::Relay));
+        sync_only.insert(Flag::LightClientServer);
+        assert!(sync_only.supports(Flag::LightClientServer));
+        assert!(!sync_only.supports(Flag::BlockFilter));
+    }
+}
@@ -3,27 +3,53 @@ use p2p::{bytes::Bytes, multiaddr::Multiaddr};
 use ckb_types::{packed, prelude::*};
 use std::convert::TryFrom;
 
+/// The version of the identify protocol this node speaks, the peers before the versioning speak
+/// the version 0, whose messages have neither the version nor the extensions.
+pub const IDENTIFY_PROTOCOL_VERSION: u32 = 1;
+
+/// A type-length-value extension of the identify message. The new capabilities are added as the
+/// extensions of the new kinds, which the peers not knowing them ignore, instead of the new
+/// fields.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct IdentifyExtension<'a> {
+    pub kind: u32,
+    pub value: &'a [u8],
+}
+
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct IdentifyMessage<'a> {
     pub(crate) listen_addrs: Vec<Multiaddr>,
     pub(crate) observed_addr: Multiaddr,
     pub(crate) identify: &'a [u8],
+    pub(crate) protocol_version: u32,
+    pub(crate) extensions: Vec<IdentifyExtension<'a>>,
 }
 
 impl<'a> IdentifyMessage<'a> {
-    pub(crate) fn new(
-        listen_addrs: Vec<Multiaddr>,
-        observed_addr: Multiaddr,
-        identify: &'a [u8],
-    ) -> Self {
+    pub fn new(listen_addrs: Vec<Multiaddr>, observed_addr: Multiaddr, identify: &'a [u8]) -> Self {
         IdentifyMessage {
             listen_addrs,
             observed_addr,
             identify,
+            protocol_version: IDENTIFY_PROTOCOL_VERSION,
+            extensions: Vec::new(),
         }
     }
 
-    pub(crate) fn encode(self) -> Bytes {
+    pub fn extensions(mut self, extensions: Vec<IdentifyExtension<'a>>) -> Self {
+        self.extensions = extensions;
+        self
+    }
+
+    /// The value of the extension of the kind, if the peer sent it.
+    pub fn extension(&self, kind: u32) -> Option<&'a [u8]> {
+        self.extensions
+            .iter()
+            .find(|extension| extension.kind == kind)
+            .map(|extension| extension.value)
+    }
+
+    pub fn encode(self) -> Bytes {
         let identify = packed::Bytes::new_builder()
             .set(self.identify.to_vec().into_iter().map(Into::into).collect())
             .build();
@@ -54,29 +80,124 @@ impl<'a> IdentifyMessage<'a> {
         }
         let listen_addrs = packed::AddressVec::new_builder().set(listen_addrs).build();
 
-        packed::IdentifyMessage::new_builder()
+        let extensions = packed::IdentifyExtensionVec::new_builder()
+            .set(
+                self.extensions
+                    .into_iter()
+                    .map(|extension| {
+                        packed::IdentifyExtension::new_builder()
+                            .kind(extension.kind.pack())
+                            .value(extension.value.pack())
+                            .build()
+                    })
+                    .collect(),
+            )
+            .build();
+
+        // the peers of the version 0 read it as the `IdentifyMessage` and ignore the new fields
+        packed::IdentifyMessageV1::new_builder()
             .listen_addrs(listen_addrs)
             .observed_addr(observed_addr)
             .identify(identify)
+            .protocol_version(self.protocol_version.pack())
+            .extensions(extensions)
             .build()
             .as_bytes()
     }
 
-    pub(crate) fn decode(data: &'a [u8]) -> Option<Self> {
-        let reader = packed::IdentifyMessageReader::from_compatible_slice(data).ok()?;
-
-        let identify = reader.identify().raw_data();
-        let observed_addr =
-            Multiaddr::try_from(reader.observed_addr().bytes().raw_data().to_vec()).ok()?;
-        let mut listen_addrs = Vec::with_capacity(reader.listen_addrs().len());
-        for addr in reader.listen_addrs().iter() {
-            listen_addrs.push(Multiaddr::try_from(addr.bytes().raw_data().to_vec()).ok()?)
+    pub fn decode(data: &'a [u8]) -> Option<Self> {
+        // the fields after the known ones are the fields of the later versions
+        match packed::IdentifyMessageV1Reader::from_compatible_slice(data) {
+            Ok(reader) => {
+                let extensions = reader
+                    .extensions()
+                    .iter()
+                    .map(|extension| IdentifyExtension {
+                        kind: extension.kind().unpack(),
+                        value: extension.value().raw_data(),
+                    })
+                    .collect();
+                Some(IdentifyMessage {
+                    listen_addrs: decode_listen_addrs(reader.listen_addrs())?,
+                    observed_addr: decode_addr(reader.observed_addr())?,
+                    identify: reader.identify().raw_data(),
+                    protocol_version: reader.protocol_version().unpack(),
+                    extensions,
+                })
+            }
+            Err(_) => {
+                let reader = packed::IdentifyMessageReader::from_compatible_slice(data).ok()?;
+                Some(IdentifyMessage {
+                    listen_addrs: decode_listen_addrs(reader.listen_addrs())?,
+                    observed_addr: decode_addr(reader.observed_addr())?,
+                    identify: reader.identify().raw_data(),
+                    protocol_version: 0,
+                    extensions: Vec::new(),
+                })
+            }
         }
+    }
+}
+
+fn decode_addr(addr: packed::AddressReader) -> Option<Multiaddr> {
+    Multiaddr::try_from(addr.bytes().raw_data().to_vec()).ok()
+}
 
-        Some(IdentifyMessage {
+fn decode_listen_addrs(addrs: packed::AddressVecReader) -> Option<Vec<Multiaddr>> {
+    addrs.iter().map(decode_addr).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn legacy_encode(message: &IdentifyMessage) -> Bytes {
+        let address = |addr: &Multiaddr| {
+            packed::Address::new_builder()
+                .bytes(addr.to_vec().pack())
+                .build()
+        };
+        packed::IdentifyMessage::new_builder()
+            .listen_addrs(
+                packed::AddressVec::new_builder()
+                    .set(message.listen_addrs.iter().map(address).collect())
+                    .build(),
+            )
+            .observed_addr(address(&message.observed_addr))
+            .identify(message.identify.pack())
+            .build()
+            .as_bytes()
+    }
+
+    fn build_message(identify: &[u8]) -> IdentifyMessage {
+        IdentifyMessage::new(
+            vec!["/ip4/1.1.1.1/tcp/8115".parse().unwrap()],
+            "/ip4/2.2.2.2/tcp/8115".parse().unwrap(),
             identify,
-            observed_addr,
-            listen_addrs,
-        })
+        )
+    }
+
+    #[test]
+    fn test_decode_versions() {
+        let message = build_message(b"identify").extensions(vec![IdentifyExtension {
+            kind: 1,
+            value: b"value",
+        }]);
+        let data = message.clone().encode();
+        let decoded = IdentifyMessage::decode(&data).unwrap();
+        assert_eq!(decoded, message);
+        assert_eq!(decoded.extension(1), Some(&b"value"[..]));
+        assert_eq!(decoded.extension(2), None);
+
+        // the peers of the version 0 can still read the message
+        let reader = packed::IdentifyMessageReader::from_compatible_slice(&data).unwrap();
+        assert_eq!(reader.identify().raw_data(), b"identify");
+
+        // and the messages of the version 0 are read without the extensions
+        let legacy = IdentifyMessage::decode(&legacy_encode(&message)).unwrap();
+        assert_eq!(legacy.protocol_version, 0);
+        assert!(legacy.extensions.is_empty());
+        assert_eq!(legacy.listen_addrs, message.listen_addrs);
+        assert_eq!(legacy.observed_addr, message.observed_addr);
     }
 }
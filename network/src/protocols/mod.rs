@@ -18,17 +18,19 @@ use p2p::{
 };
 use std::{
     pin::Pin,
-    sync::Arc,
+    sync::{atomic::Ordering, Arc},
     task::{Context, Poll},
-    time::Duration,
+    time::{Duration, Instant},
 };
 use tokio_util::codec::length_delimited;
 
 pub type PeerIndex = SessionId;
 pub type BoxedFutureTask = Pin<Box<dyn Future<Output = ()> + 'static + Send>>;
 
+use self::identify::Flag;
 use crate::{
     compress::{compress, decompress},
+    metrics,
     network::disconnect_with_message,
     Behaviour, Error, NetworkState, Peer, ProtocolVersion,
 };
@@ -63,6 +65,9 @@ pub trait CKBProtocolContext: Send {
     fn connected_peers(&self) -> Vec<PeerIndex>;
     fn report_peer(&self, peer_index: PeerIndex, behaviour: Behaviour);
     fn ban_peer(&self, peer_index: PeerIndex, duration: Duration, reason: String);
+    /// Scores the misbehavior of the peer by its kind, see `PeerScoringConfig`, the peer is
+    /// disconnected or banned once its score reaches the thresholds.
+    fn report_misbehavior(&self, peer_index: PeerIndex, kind: &str, reason: &str);
     fn send_paused(&self) -> bool;
     // Other methods
     fn protocol_id(&self) -> ProtocolId;
@@ -107,6 +112,8 @@ pub struct CKBProtocol {
     handler: Box<dyn CKBProtocolHandler>,
     network_state: Arc<NetworkState>,
     flag: BlockingFlag,
+    // the identify flag the remote end must advertise to open the protocol
+    identify_flag: Option<Flag>,
 }
 
 impl CKBProtocol {
@@ -131,9 +138,17 @@ impl CKBProtocol {
                 versions.to_vec()
             },
             flag,
+            identify_flag: None,
         }
     }
 
+    /// Only opens the protocol with the peers advertising `flag` in the identify message, the
+    /// local node advertises it as well.
+    pub fn with_identify_flag(mut self, flag: Flag) -> Self {
+        self.identify_flag = Some(flag);
+        self
+    }
+
     pub fn id(&self) -> ProtocolId {
         self.id
     }
@@ -142,6 +157,10 @@ impl CKBProtocol {
         self.protocol_name.clone()
     }
 
+    pub(crate) fn identify_flag(&self) -> Option<Flag> {
+        self.identify_flag
+    }
+
     pub fn match_version(&self, version: ProtocolVersion) -> bool {
         self.supported_versions.contains(&version)
     }
@@ -227,12 +246,26 @@ impl ServiceProtocol for CKBHandler {
     }
 
     fn received(&mut self, context: ProtocolContextMutRef, data: Bytes) {
+        if self.network_state.protocols_stopped.load(Ordering::SeqCst) {
+            return;
+        }
         trace!(
             "[received message]: {}, {}, length={}",
             self.proto_id,
             context.session.id,
             data.len()
         );
+        metrics::observe_received(self.proto_id, data.len());
+        self.network_state
+            .record_received_message(context.session.id, self.proto_id, data.len());
+        if !self.network_state.check_rate_limit(
+            context.control(),
+            context.session.id,
+            self.proto_id,
+            data.len(),
+        ) {
+            return;
+        }
         let pending_data_size = context.session.pending_data_size();
         let send_paused = pending_data_size >= self.network_state.config.max_send_buffer();
         let nc = DefaultCKBProtocolContext {
@@ -242,10 +275,16 @@ impl ServiceProtocol for CKBHandler {
             send_paused,
         };
         let peer_index = context.session.id;
+        let start = Instant::now();
         self.handler.received(Arc::new(nc), peer_index, data);
+        self.network_state
+            .record_processed_message(peer_index, self.proto_id, start.elapsed());
     }
 
     fn notify(&mut self, context: &mut ProtocolContext, token: u64) {
+        if self.network_state.protocols_stopped.load(Ordering::SeqCst) {
+            return;
+        }
         if token == std::u64::MAX {
             trace!("protocol handler heart beat {}", self.proto_id);
         } else {
@@ -260,6 +299,9 @@ impl ServiceProtocol for CKBHandler {
     }
 
     fn poll(mut self: Pin<&mut Self>, _nc: &mut Context, context: &mut ProtocolContext) {
+        if self.network_state.protocols_stopped.load(Ordering::SeqCst) {
+            return;
+        }
         let nc = DefaultCKBProtocolContext {
             proto_id: self.proto_id,
             network_state: Arc::clone(&self.network_state),
@@ -300,6 +342,7 @@ impl CKBProtocolContext for DefaultCKBProtocolContext {
             peer_index,
             data.len()
         );
+        metrics::observe_sent(proto_id, data.len());
         self.p2p_control
             .quick_send_message_to(peer_index, proto_id, data)?;
         Ok(())
@@ -311,11 +354,13 @@ impl CKBProtocolContext for DefaultCKBProtocolContext {
             peer_index,
             data.len()
         );
+        metrics::observe_sent(self.proto_id, data.len());
         self.p2p_control
             .quick_send_message_to(peer_index, self.proto_id, data)?;
         Ok(())
     }
     fn quick_filter_broadcast(&self, target: TargetSession, data: Bytes) -> Result<(), Error> {
+        metrics::observe_sent(self.proto_id, data.len());
         self.p2p_control
             .quick_filter_broadcast(target, self.proto_id, data)?;
         Ok(())
@@ -341,6 +386,7 @@ impl CKBProtocolContext for DefaultCKBProtocolContext {
             peer_index,
             data.len()
         );
+        metrics::observe_sent(proto_id, data.len());
         self.p2p_control
             .send_message_to(peer_index, proto_id, data)?;
         Ok(())
@@ -352,11 +398,13 @@ impl CKBProtocolContext for DefaultCKBProtocolContext {
             peer_index,
             data.len()
         );
+        metrics::observe_sent(self.proto_id, data.len());
         self.p2p_control
             .send_message_to(peer_index, self.proto_id, data)?;
         Ok(())
     }
     fn filter_broadcast(&self, target: TargetSession, data: Bytes) -> Result<(), Error> {
+        metrics::observe_sent(self.proto_id, data.len());
         self.p2p_control
             .filter_broadcast(target, self.proto_id, data)?;
         Ok(())
@@ -399,6 +447,10 @@ impl CKBProtocolContext for DefaultCKBProtocolContext {
         self.network_state
             .ban_session(&self.p2p_control, peer_index, duration, reason);
     }
+    fn report_misbehavior(&self, peer_index: PeerIndex, kind: &str, reason: &str) {
+        self.network_state
+            .report_session_misbehavior(&self.p2p_control, peer_index, kind, reason);
+    }
 
     fn protocol_id(&self) -> ProtocolId {
         self.proto_id
@@ -48,6 +48,7 @@ pub enum Event {
 /// The interval means that we send ping to peers.
 /// The timeout means that consider peer is timeout if during a timeout we still have not received pong from a peer
 pub struct PingHandler {
+    network_state: Arc<NetworkState>,
     interval: Duration,
     timeout: Duration,
     connected_session_ids: HashMap<SessionId, PingStatus>,
@@ -55,8 +56,14 @@ pub struct PingHandler {
 }
 
 impl PingHandler {
-    pub fn new(interval: Duration, timeout: Duration, event_sender: Sender<Event>) -> PingHandler {
+    pub fn new(
+        network_state: Arc<NetworkState>,
+        interval: Duration,
+        timeout: Duration,
+        event_sender: Sender<Event>,
+    ) -> PingHandler {
         PingHandler {
+            network_state,
             interval,
             timeout,
             connected_session_ids: Default::default(),
@@ -153,6 +160,14 @@ impl ServiceProtocol for PingHandler {
 
     fn received(&mut self, context: ProtocolContextMutRef, data: Bytes) {
         let session = context.session;
+        if !self.network_state.check_rate_limit(
+            context.control(),
+            session.id,
+            context.proto_id,
+            data.len(),
+        ) {
+            return;
+        }
         if let Some(peer_id) = self
             .connected_session_ids
             .get(&session.id)
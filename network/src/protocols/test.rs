@@ -103,7 +103,9 @@ fn net_service_start(name: String) -> Node {
         public_addresses: vec![],
         bootnodes: vec![],
         dns_seeds: vec![],
+        dns_seeds_pubkey: None,
         whitelist_peers: vec![],
+        whitelist: Default::default(),
         whitelist_only: false,
         max_peers: 19,
         max_outbound_peers: 5,
@@ -117,7 +119,14 @@ fn net_service_start(name: String) -> Node {
         discovery_local_address: true,
         upnp: false,
         bootnode_mode: true,
+        light_client_server: false,
+        tx_reconciliation: false,
+        mempool_sync: false,
         max_send_buffer: None,
+        peer_scoring: Default::default(),
+        rate_limit: Default::default(),
+        dandelion: Default::default(),
+        identify: Default::default(),
     };
 
     let network_state =
@@ -144,11 +153,13 @@ fn net_service_start(name: String) -> Node {
     let (ping_sender, ping_receiver) = channel(std::u8::MAX as usize);
     let ping_interval = Duration::from_secs(5);
     let ping_timeout = Duration::from_secs(10);
+    let ping_network_state = Arc::clone(&network_state);
 
     let ping_meta = MetaBuilder::default()
         .id(PING_PROTOCOL_ID.into())
         .service_handle(move || {
             ProtocolHandle::Both(Box::new(PingHandler::new(
+                ping_network_state,
                 ping_interval,
                 ping_timeout,
                 ping_sender,
@@ -171,7 +182,10 @@ fn net_service_start(name: String) -> Node {
     let identify_meta = MetaBuilder::default()
         .id(IDENTIFY_PROTOCOL_ID.into())
         .service_handle(move || {
-            ProtocolHandle::Both(Box::new(IdentifyProtocol::new(identify_callback)))
+            ProtocolHandle::Both(Box::new(IdentifyProtocol::new(
+                identify_callback,
+                Default::default(),
+            )))
         })
         .build();
 
@@ -0,0 +1,169 @@
+//! Rate limits of the messages the peers send.
+//!
+//! A session has a token bucket for the message count of each protocol, and a bucket for the
+//! bytes of all protocols. The buckets refill at the configured rates up to the bursts, and every
+//! received message takes tokens out. A message may overdraw a bucket which still has tokens, so
+//! a message bigger than the burst still passes, while a message arriving at an empty bucket
+//! violates the limit.
+use ckb_app_config::RateLimitConfig;
+use p2p::{ProtocolId, SessionId};
+use std::cmp::max;
+use std::collections::HashMap;
+use std::time::Instant;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RateLimitViolation {
+    MessageRate,
+    Bandwidth,
+}
+
+impl RateLimitViolation {
+    /// The misbehavior kind to score the violation.
+    pub fn misbehavior(self) -> &'static str {
+        match self {
+            RateLimitViolation::MessageRate => "rate_limit.message_rate",
+            RateLimitViolation::Bandwidth => "rate_limit.bandwidth",
+        }
+    }
+}
+
+struct TokenBucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl TokenBucket {
+    fn new(burst: f64, now: Instant) -> Self {
+        TokenBucket {
+            tokens: burst,
+            updated_at: now,
+        }
+    }
+
+    fn take(&mut self, amount: f64, rate: f64, burst: f64, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate).min(burst);
+        self.updated_at = now;
+        if self.tokens > 0.0 {
+            self.tokens -= amount;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+pub(crate) struct RateLimiter {
+    config: RateLimitConfig,
+    messages: HashMap<(SessionId, ProtocolId), TokenBucket>,
+    bytes: HashMap<SessionId, TokenBucket>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(config: RateLimitConfig) -> Self {
+        RateLimiter {
+            config,
+            messages: HashMap::default(),
+            bytes: HashMap::default(),
+        }
+    }
+
+    /// Takes the tokens of the message received from the session, the message is dropped on
+    /// violations.
+    pub(crate) fn check(
+        &mut self,
+        session_id: SessionId,
+        proto_id: ProtocolId,
+        len: usize,
+        now: Instant,
+    ) -> Result<(), RateLimitViolation> {
+        if self.config.messages_per_sec > 0 {
+            let rate = f64::from(self.config.messages_per_sec);
+            let burst = f64::from(max(self.config.message_burst, self.config.messages_per_sec));
+            let bucket = self
+                .messages
+                .entry((session_id, proto_id))
+                .or_insert_with(|| TokenBucket::new(burst, now));
+            if !bucket.take(1.0, rate, burst, now) {
+                return Err(RateLimitViolation::MessageRate);
+            }
+        }
+        if self.config.bytes_per_sec > 0 {
+            let rate = self.config.bytes_per_sec as f64;
+            let burst = max(self.config.byte_burst, self.config.bytes_per_sec) as f64;
+            let bucket = self
+                .bytes
+                .entry(session_id)
+                .or_insert_with(|| TokenBucket::new(burst, now));
+            if !bucket.take(len as f64, rate, burst, now) {
+                return Err(RateLimitViolation::Bandwidth);
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn remove_session(&mut self, session_id: SessionId) {
+        self.messages.retain(|(id, _), _| *id != session_id);
+        self.bytes.remove(&session_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_message_rate() {
+        let mut limiter = RateLimiter::new(RateLimitConfig {
+            messages_per_sec: 10,
+            message_burst: 20,
+            bytes_per_sec: 0,
+            ..Default::default()
+        });
+        let now = Instant::now();
+        let (session, other_session, proto) = (1.into(), 2.into(), 0.into());
+        for _ in 0..20 {
+            assert_eq!(limiter.check(session, proto, 1, now), Ok(()));
+        }
+        assert_eq!(
+            limiter.check(session, proto, 1, now),
+            Err(RateLimitViolation::MessageRate)
+        );
+        // the protocols and the sessions are limited separately
+        assert_eq!(limiter.check(session, 1.into(), 1, now), Ok(()));
+        assert_eq!(limiter.check(other_session, proto, 1, now), Ok(()));
+
+        let later = now + Duration::from_millis(100);
+        assert_eq!(limiter.check(session, proto, 1, later), Ok(()));
+        assert_eq!(
+            limiter.check(session, proto, 1, later),
+            Err(RateLimitViolation::MessageRate)
+        );
+
+        limiter.remove_session(session);
+        assert_eq!(limiter.check(session, proto, 1, later), Ok(()));
+    }
+
+    #[test]
+    fn test_bandwidth() {
+        let mut limiter = RateLimiter::new(RateLimitConfig {
+            messages_per_sec: 0,
+            bytes_per_sec: 1000,
+            byte_burst: 0,
+            ..Default::default()
+        });
+        let now = Instant::now();
+        let (session, proto) = (1.into(), 0.into());
+        // a message bigger than the burst overdraws the bucket
+        assert_eq!(limiter.check(session, proto, 3000, now), Ok(()));
+        assert_eq!(
+            limiter.check(session, 1.into(), 1, now + Duration::from_secs(1)),
+            Err(RateLimitViolation::Bandwidth)
+        );
+        assert_eq!(
+            limiter.check(session, proto, 1, now + Duration::from_millis(2001)),
+            Ok(())
+        );
+    }
+}
@@ -16,12 +16,11 @@ use secp256k1::key::PublicKey;
 use tokio::time::Interval;
 
 mod seed_record;
+mod server;
 
 use crate::NetworkState;
 use seed_record::SeedRecord;
-
-// FIXME: should replace this later
-const TXT_VERIFY_PUBKEY: &str = "";
+pub use server::DnsSeedServer;
 
 pub(crate) struct DnsSeedingService {
     network_state: Arc<NetworkState>,
@@ -29,10 +28,16 @@ pub(crate) struct DnsSeedingService {
     // Because tokio timer is not reliable
     check_interval: Interval,
     seeds: Vec<String>,
+    // The hex of the public key signing the records, any signer is accepted if absent
+    pubkey: Option<String>,
 }
 
 impl DnsSeedingService {
-    pub(crate) fn new(network_state: Arc<NetworkState>, seeds: Vec<String>) -> DnsSeedingService {
+    pub(crate) fn new(
+        network_state: Arc<NetworkState>,
+        seeds: Vec<String>,
+        pubkey: Option<String>,
+    ) -> DnsSeedingService {
         let wait_until = if network_state
             .with_peer_store_mut(|peer_store| peer_store.fetch_random_addrs(1).is_empty())
         {
@@ -47,15 +52,11 @@ impl DnsSeedingService {
             wait_until,
             check_interval,
             seeds,
+            pubkey,
         }
     }
 
     fn seeding(&self) -> Result<(), Box<dyn Error>> {
-        // TODO: DNS seeding is disabled now, may enable in the future (need discussed)
-        if TXT_VERIFY_PUBKEY.is_empty() {
-            return Ok(());
-        }
-
         let enough_outbound = self.network_state.with_peer_registry(|reg| {
             reg.peers()
                 .values()
@@ -68,11 +69,18 @@ impl DnsSeedingService {
             return Ok(());
         }
 
-        let mut pubkey_bytes = [4u8; 65];
-        hex_decode(TXT_VERIFY_PUBKEY.as_bytes(), &mut pubkey_bytes[1..65])
-            .map_err(|err| format!("parse key({}) error: {:?}", TXT_VERIFY_PUBKEY, err))?;
-        let pubkey = PublicKey::from_slice(&pubkey_bytes)
-            .map_err(|err| format!("create PublicKey failed: {:?}", err))?;
+        let pubkey = match self.pubkey {
+            Some(ref pubkey) => {
+                let hex = pubkey.trim_start_matches("0x");
+                let mut pubkey_bytes = vec![0u8; hex.len() / 2];
+                hex_decode(hex.as_bytes(), &mut pubkey_bytes)
+                    .map_err(|err| format!("parse key({}) error: {:?}", pubkey, err))?;
+                let pubkey = PublicKey::from_slice(&pubkey_bytes)
+                    .map_err(|err| format!("create PublicKey failed: {:?}", err))?;
+                Some(pubkey)
+            }
+            None => None,
+        };
 
         let resolver = DnsConfig::load_default()
             .map_err(|err| format!("Failed to load system configuration: {}", err))
@@ -88,7 +96,7 @@ impl DnsSeedingService {
                 Ok(records) => {
                     for record in records {
                         match std::str::from_utf8(&record.data) {
-                            Ok(record) => match SeedRecord::decode_with_pubkey(&record, &pubkey) {
+                            Ok(record) => match decode_record(&record, pubkey.as_ref()) {
                                 Ok(seed_record) => {
                                     let address = seed_record.address();
                                     trace!("got dns txt address: {}", address);
@@ -134,6 +142,20 @@ impl DnsSeedingService {
     }
 }
 
+fn decode_record(
+    record: &str,
+    pubkey: Option<&PublicKey>,
+) -> Result<SeedRecord, seed_record::SeedRecordError> {
+    match pubkey {
+        Some(pubkey) => SeedRecord::decode_with_pubkey(record, pubkey),
+        None => {
+            let seed_record = SeedRecord::decode(record)?;
+            seed_record.check()?;
+            Ok(seed_record)
+        }
+    }
+}
+
 impl Future for DnsSeedingService {
     type Output = ();
 
@@ -13,13 +13,14 @@ use p2p::{
     utils::{is_reachable, socketaddr_to_multiaddr},
 };
 use secp256k1::{
-    key::PublicKey,
+    key::{PublicKey, SecretKey},
     recovery::{RecoverableSignature, RecoveryId},
     Message,
 };
 
 lazy_static! {
-    static ref SECP256K1: secp256k1::Secp256k1<secp256k1::All> = secp256k1::Secp256k1::new();
+    pub(crate) static ref SECP256K1: secp256k1::Secp256k1<secp256k1::All> =
+        secp256k1::Secp256k1::new();
 }
 const SEP: char = ';';
 
@@ -52,6 +53,22 @@ pub struct SeedRecord {
 }
 
 impl SeedRecord {
+    pub fn new(
+        ip: IpAddr,
+        port: u16,
+        peer_id: Option<PeerId>,
+        valid_until: u64,
+        pubkey: PublicKey,
+    ) -> SeedRecord {
+        SeedRecord {
+            ip,
+            port,
+            peer_id,
+            valid_until,
+            pubkey,
+        }
+    }
+
     pub fn check(&self) -> Result<(), SeedRecordError> {
         if !is_reachable(self.ip) {
             return Err(SeedRecordError::InvalidIp(self.ip));
@@ -136,6 +153,25 @@ impl SeedRecord {
         }
     }
 
+    // Design for human readable
+    pub fn encode(&self, privkey: &SecretKey) -> Result<String, SeedRecordError> {
+        if PublicKey::from_secret_key(&SECP256K1, privkey) != self.pubkey {
+            return Err(SeedRecordError::KeyNotMatch);
+        }
+
+        let data = Self::data_to_sign(self.ip, self.port, self.peer_id.as_ref(), self.valid_until);
+        let hash = blake2b_256(&data);
+        let message = Message::from_slice(&hash).expect("create message error");
+
+        let signature = SECP256K1.sign_recoverable(&message, privkey);
+        let (recid, signed_data) = signature.serialize_compact();
+        let mut sig = [0u8; 65];
+        sig[0..64].copy_from_slice(&signed_data[0..64]);
+        sig[64] = recid.to_i32() as u8;
+        let signature_string = bs58::encode(&sig[..]).into_string();
+        Ok(vec![data, signature_string].join(&SEP.to_string()))
+    }
+
     pub fn address(&self) -> Multiaddr {
         let socket_addr = SocketAddr::new(self.ip, self.port);
         let mut multi_addr = socketaddr_to_multiaddr(socket_addr);
@@ -164,7 +200,6 @@ pub enum SeedRecordError {
     InvalidSignature,
     VerifyFailed,
     SeedTimeout,
-    #[cfg(test)]
     KeyNotMatch,
 }
 
@@ -173,7 +208,6 @@ mod tests {
     use super::*;
 
     use rand::Rng;
-    use secp256k1::key::SecretKey;
 
     struct Generator;
 
@@ -196,44 +230,6 @@ mod tests {
         }
     }
 
-    impl SeedRecord {
-        fn new(
-            ip: IpAddr,
-            port: u16,
-            peer_id: Option<PeerId>,
-            valid_until: u64,
-            pubkey: PublicKey,
-        ) -> SeedRecord {
-            SeedRecord {
-                ip,
-                port,
-                peer_id,
-                valid_until,
-                pubkey,
-            }
-        }
-
-        // Design for human readable
-        fn encode(&self, privkey: &SecretKey) -> Result<String, SeedRecordError> {
-            if PublicKey::from_secret_key(&SECP256K1, privkey) != self.pubkey {
-                return Err(SeedRecordError::KeyNotMatch);
-            }
-
-            let data =
-                Self::data_to_sign(self.ip, self.port, self.peer_id.as_ref(), self.valid_until);
-            let hash = blake2b_256(&data);
-            let message = Message::from_slice(&hash).expect("create message error");
-
-            let signature = SECP256K1.sign_recoverable(&message, privkey);
-            let (recid, signed_data) = signature.serialize_compact();
-            let mut sig = [0u8; 65];
-            sig[0..64].copy_from_slice(&signed_data[0..64]);
-            sig[64] = recid.to_i32() as u8;
-            let signature_string = bs58::encode(&sig[..]).into_string();
-            Ok(vec![data, signature_string].join(&SEP.to_string()))
-        }
-    }
-
     fn now_ts() -> u64 {
         SystemTime::now()
             .duration_since(UNIX_EPOCH)
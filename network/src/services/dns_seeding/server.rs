@@ -0,0 +1,393 @@
+//! The DNS seed server of `ckb dnsseed`.
+//!
+//! It answers the TXT queries of the seed domain over UDP with the signed records of a curated
+//! sample of the good addresses in the peer store, which the node running in the same directory
+//! keeps dumping. Only the minimal DNS messages the seeding clients send are supported.
+use std::{
+    error::Error,
+    fs, io,
+    net::{SocketAddr, UdpSocket},
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use ckb_app_config::NetworkConfig;
+use ckb_logger::{debug, info};
+use p2p::utils::is_reachable;
+use rand::seq::SliceRandom;
+use secp256k1::key::{PublicKey, SecretKey};
+use std::collections::HashMap;
+
+use super::seed_record::{SeedRecord, SECP256K1};
+use crate::network_group::{Group, NetworkGroup};
+use crate::peer_store::{ban_list::BanList, types::AddrInfo, PeerStore};
+
+// Reload the peer store and sign the records again at this interval, also the TTL of the answers
+const REFRESH_INTERVAL: Duration = Duration::from_secs(600);
+// The records stay valid for a day, in case the server stops refreshing them
+const RECORD_VALID_SECS: u64 = 24 * 3600;
+// Only the addresses connected in the last 3 days are served
+const GOOD_ADDR_EXPIRES_MS: u64 = 3 * 24 * 3600 * 1000;
+const MAX_CURATED_ADDRS: usize = 256;
+const MAX_ADDRS_PER_GROUP: usize = 2;
+// The UDP payload limit without EDNS
+const MAX_UDP_PAYLOAD: usize = 512;
+
+const HEADER_LEN: usize = 12;
+const FLAG_QR: u16 = 0x8000;
+const FLAG_AA: u16 = 0x0400;
+const FLAG_RD: u16 = 0x0100;
+const OPCODE_MASK: u16 = 0x7800;
+const RCODE_FORMERR: u16 = 1;
+const RCODE_NOTIMP: u16 = 4;
+const RCODE_REFUSED: u16 = 5;
+const TYPE_TXT: u16 = 16;
+const TYPE_ANY: u16 = 255;
+const CLASS_IN: u16 = 1;
+
+pub struct DnsSeedServer {
+    // The lowercase labels of the seed domain
+    domain: Vec<String>,
+    peer_store_path: PathBuf,
+    secret_key: SecretKey,
+    pubkey: PublicKey,
+    records: Vec<String>,
+    refreshed_at: Option<Instant>,
+}
+
+impl DnsSeedServer {
+    /// Creates the server of the domain, signing the records with the network secret key.
+    pub fn new(config: &NetworkConfig, domain: &str) -> Result<Self, Box<dyn Error>> {
+        // generates the key if the node has never run
+        config.fetch_private_key()?;
+        let secret_key = SecretKey::from_slice(&fs::read(config.secret_key_path())?)
+            .map_err(|err| format!("invalid network secret key: {:?}", err))?;
+        let pubkey = PublicKey::from_secret_key(&SECP256K1, &secret_key);
+        let domain = labels(domain);
+        if domain.is_empty() {
+            return Err("the seed domain is empty".into());
+        }
+        Ok(DnsSeedServer {
+            domain,
+            peer_store_path: config.peer_store_path(),
+            secret_key,
+            pubkey,
+            records: Vec::new(),
+            refreshed_at: None,
+        })
+    }
+
+    /// The hex of the public key verifying the records, for `network.dns_seeds_pubkey`.
+    pub fn pubkey_hex(&self) -> String {
+        faster_hex::hex_string(&self.pubkey.serialize()).expect("hex string")
+    }
+
+    /// Serves the queries on the UDP address until an IO error.
+    pub fn run(mut self, listen: SocketAddr) -> io::Result<()> {
+        let socket = UdpSocket::bind(listen)?;
+        socket.set_read_timeout(Some(Duration::from_secs(1)))?;
+        info!("DNS seed server listens on {}", listen);
+        let mut buf = [0u8; MAX_UDP_PAYLOAD];
+        loop {
+            if self
+                .refreshed_at
+                .map(|at| at.elapsed() >= REFRESH_INTERVAL)
+                .unwrap_or(true)
+            {
+                self.refresh();
+            }
+            let (len, peer) = match socket.recv_from(&mut buf) {
+                Ok(received) => received,
+                Err(ref err)
+                    if err.kind() == io::ErrorKind::WouldBlock
+                        || err.kind() == io::ErrorKind::TimedOut =>
+                {
+                    continue;
+                }
+                Err(err) => return Err(err),
+            };
+            if let Some(response) = self.respond(&buf[..len]) {
+                if let Err(err) = socket.send_to(&response, peer) {
+                    debug!("failed to answer {}: {}", peer, err);
+                }
+            }
+        }
+    }
+
+    fn refresh(&mut self) {
+        let peer_store = PeerStore::load_from_dir_or_default(&self.peer_store_path);
+        let now_ms = faketime::unix_time_as_millis();
+        let valid_until = now_ms / 1000 + RECORD_VALID_SECS;
+        let addrs = curate(
+            peer_store.addr_manager().addrs_iter(),
+            &peer_store.ban_list(),
+            now_ms,
+        );
+        self.records = addrs
+            .into_iter()
+            .filter_map(|addr| {
+                SeedRecord::new(
+                    addr.ip_port.ip,
+                    addr.ip_port.port,
+                    Some(addr.peer_id),
+                    valid_until,
+                    self.pubkey,
+                )
+                .encode(&self.secret_key)
+                .ok()
+            })
+            .collect();
+        self.refreshed_at = Some(Instant::now());
+        info!("DNS seed server serves {} addresses", self.records.len());
+    }
+
+    fn respond(&self, packet: &[u8]) -> Option<Vec<u8>> {
+        let query = match parse_query(packet) {
+            Ok(query) => query,
+            Err(Some((id, flags, rcode))) => return Some(header_only(id, flags, rcode)),
+            Err(None) => return None,
+        };
+        if query.name != self.domain {
+            return Some(response(packet, &query, RCODE_REFUSED, &[]));
+        }
+        let answers: Vec<&str> = if query.qtype == TYPE_TXT || query.qtype == TYPE_ANY {
+            self.records
+                .choose_multiple(&mut rand::thread_rng(), self.records.len())
+                .map(String::as_str)
+                .collect()
+        } else {
+            Vec::new()
+        };
+        Some(response(packet, &query, 0, &answers))
+    }
+}
+
+/// Selects the good addresses to serve: reachable, not banned, connected recently and not
+/// failing since, the most recently connected first and at most a few of each network group.
+pub(crate) fn curate<'a, I>(addrs: I, ban_list: &BanList, now_ms: u64) -> Vec<AddrInfo>
+where
+    I: Iterator<Item = &'a AddrInfo>,
+{
+    let expires_ms = now_ms.saturating_sub(GOOD_ADDR_EXPIRES_MS);
+    let mut good: Vec<&AddrInfo> = addrs
+        .filter(|addr| {
+            is_reachable(addr.ip_port.ip)
+                && !ban_list.is_addr_banned(&addr.addr)
+                && addr.had_connected(expires_ms)
+                && addr.attempts_count == 0
+        })
+        .collect();
+    good.sort_by_key(|addr| std::cmp::Reverse(addr.last_connected_at_ms));
+
+    let mut groups: HashMap<Group, usize> = HashMap::new();
+    good.into_iter()
+        .filter(|addr| {
+            let count = groups.entry(addr.addr.network_group()).or_insert(0);
+            *count += 1;
+            *count <= MAX_ADDRS_PER_GROUP
+        })
+        .take(MAX_CURATED_ADDRS)
+        .cloned()
+        .collect()
+}
+
+fn labels(domain: &str) -> Vec<String> {
+    domain
+        .trim_end_matches('.')
+        .split('.')
+        .filter(|label| !label.is_empty())
+        .map(str::to_ascii_lowercase)
+        .collect()
+}
+
+#[derive(Debug)]
+struct Query {
+    id: u16,
+    flags: u16,
+    name: Vec<String>,
+    qtype: u16,
+    // The end of the question section
+    end: usize,
+}
+
+fn read_u16(packet: &[u8], offset: usize) -> Option<u16> {
+    packet
+        .get(offset..offset + 2)
+        .map(|bytes| u16::from(bytes[0]) << 8 | u16::from(bytes[1]))
+}
+
+// Parses a query of a single question, the error is the header of the error response, or `None`
+// if the packet should be dropped
+fn parse_query(packet: &[u8]) -> Result<Query, Option<(u16, u16, u16)>> {
+    if packet.len() < HEADER_LEN {
+        return Err(None);
+    }
+    let id = read_u16(packet, 0).expect("checked length");
+    let flags = read_u16(packet, 2).expect("checked length");
+    if flags & FLAG_QR != 0 {
+        return Err(None);
+    }
+    if flags & OPCODE_MASK != 0 {
+        return Err(Some((id, flags, RCODE_NOTIMP)));
+    }
+    let formerr = Some((id, flags, RCODE_FORMERR));
+    if read_u16(packet, 4) != Some(1) {
+        return Err(formerr);
+    }
+
+    let mut name = Vec::new();
+    let mut offset = HEADER_LEN;
+    loop {
+        let len = *packet.get(offset).ok_or(formerr)? as usize;
+        offset += 1;
+        if len == 0 {
+            break;
+        }
+        // the compression pointers are not expected in the question of a query
+        if len & 0xc0 != 0 || offset + len > HEADER_LEN + 255 {
+            return Err(formerr);
+        }
+        let label = packet.get(offset..offset + len).ok_or(formerr)?;
+        name.push(String::from_utf8_lossy(label).to_ascii_lowercase());
+        offset += len;
+    }
+    let qtype = read_u16(packet, offset).ok_or(formerr)?;
+    if read_u16(packet, offset + 2).ok_or(formerr)? != CLASS_IN {
+        return Err(Some((id, flags, RCODE_REFUSED)));
+    }
+    Ok(Query {
+        id,
+        flags,
+        name,
+        qtype,
+        end: offset + 4,
+    })
+}
+
+fn header(id: u16, flags: u16, rcode: u16, questions: u16, answers: u16) -> Vec<u8> {
+    let flags = FLAG_QR | FLAG_AA | (flags & (OPCODE_MASK | FLAG_RD)) | rcode;
+    let mut header = Vec::with_capacity(MAX_UDP_PAYLOAD);
+    for value in &[id, flags, questions, answers, 0, 0] {
+        header.extend_from_slice(&value.to_be_bytes());
+    }
+    header
+}
+
+fn header_only(id: u16, flags: u16, rcode: u16) -> Vec<u8> {
+    header(id, flags, rcode, 0, 0)
+}
+
+// The response echoing the question, with as many TXT answers as fit in a UDP payload
+fn response(packet: &[u8], query: &Query, rcode: u16, answers: &[&str]) -> Vec<u8> {
+    let mut records = Vec::new();
+    let mut len = query.end;
+    let mut count = 0u16;
+    for answer in answers {
+        let data = &answer.as_bytes()[..answer.len().min(255)];
+        // name pointer, type, class, ttl, rdlength and the character string
+        let record_len = 2 + 2 + 2 + 4 + 2 + 1 + data.len();
+        if len + record_len > MAX_UDP_PAYLOAD {
+            break;
+        }
+        records.extend_from_slice(&[0xc0, HEADER_LEN as u8]);
+        records.extend_from_slice(&TYPE_TXT.to_be_bytes());
+        records.extend_from_slice(&CLASS_IN.to_be_bytes());
+        records.extend_from_slice(&(REFRESH_INTERVAL.as_secs() as u32).to_be_bytes());
+        records.extend_from_slice(&(data.len() as u16 + 1).to_be_bytes());
+        records.push(data.len() as u8);
+        records.extend_from_slice(data);
+        len += record_len;
+        count += 1;
+    }
+    let mut response = header(query.id, query.flags, rcode, 1, count);
+    response.extend_from_slice(&packet[HEADER_LEN..query.end]);
+    response.extend_from_slice(&records);
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::peer_store::types::IpPort;
+    use p2p::secio::PeerId;
+
+    fn build_query(name: &str, qtype: u16) -> Vec<u8> {
+        let mut packet = vec![0x12, 0x34, 0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 0];
+        for label in name.split('.') {
+            packet.push(label.len() as u8);
+            packet.extend_from_slice(label.as_bytes());
+        }
+        packet.push(0);
+        packet.extend_from_slice(&qtype.to_be_bytes());
+        packet.extend_from_slice(&CLASS_IN.to_be_bytes());
+        packet
+    }
+
+    fn build_server(records: Vec<String>) -> DnsSeedServer {
+        let secret_key = SecretKey::from_slice(&[1u8; 32]).unwrap();
+        DnsSeedServer {
+            domain: labels("seed.example.com."),
+            peer_store_path: PathBuf::new(),
+            secret_key,
+            pubkey: PublicKey::from_secret_key(&SECP256K1, &secret_key),
+            records,
+            refreshed_at: None,
+        }
+    }
+
+    fn addr_info(ip: &str, last_connected_at_ms: u64) -> AddrInfo {
+        let ip_port = IpPort {
+            ip: ip.parse().unwrap(),
+            port: 8115,
+        };
+        let addr = format!("/ip4/{}/tcp/8115", ip).parse().unwrap();
+        AddrInfo::new(PeerId::random(), ip_port, addr, last_connected_at_ms, 100)
+    }
+
+    #[test]
+    fn test_respond() {
+        let records: Vec<String> = (0..10).map(|i| format!("{:0200}", i)).collect();
+        let server = build_server(records);
+
+        let query = build_query("Seed.Example.com", TYPE_TXT);
+        let response = server.respond(&query).unwrap();
+        assert_eq!(&response[..2], &[0x12, 0x34]);
+        // QR, AA and RD, no error
+        assert_eq!(read_u16(&response, 2), Some(0x8500));
+        // the answers fit in the UDP payload
+        assert_eq!(read_u16(&response, 6), Some(2));
+        assert!(response.len() <= MAX_UDP_PAYLOAD);
+        assert_eq!(&response[HEADER_LEN..query.len()], &query[HEADER_LEN..]);
+
+        let response = server.respond(&build_query("seed.example.com", 1)).unwrap();
+        assert_eq!(read_u16(&response, 6), Some(0));
+
+        let response = server.respond(&build_query("other.com", TYPE_TXT)).unwrap();
+        assert_eq!(read_u16(&response, 2), Some(0x8500 | RCODE_REFUSED));
+
+        assert!(server.respond(&query[..HEADER_LEN + 3]).is_some());
+        assert!(server.respond(&query[..4]).is_none());
+    }
+
+    #[test]
+    fn test_curate() {
+        let now_ms = GOOD_ADDR_EXPIRES_MS * 2;
+        let mut failing = addr_info("1.1.1.2", now_ms - 1);
+        failing.mark_tried(now_ms);
+        let addrs = vec![
+            addr_info("1.1.1.1", now_ms - 3),
+            failing,
+            addr_info("1.1.2.1", now_ms - 2),
+            addr_info("1.1.3.1", now_ms - 1),
+            addr_info("2.2.2.2", 1),
+            addr_info("127.0.0.1", now_ms - 1),
+            addr_info("3.3.3.3", now_ms - 4),
+        ];
+        let ban_list = BanList::new();
+        let curated: Vec<String> = curate(addrs.iter(), &ban_list, now_ms)
+            .into_iter()
+            .map(|addr| addr.ip_port.ip.to_string())
+            .collect();
+        assert_eq!(curated, vec!["1.1.3.1", "1.1.2.1", "3.3.3.3"]);
+    }
+}
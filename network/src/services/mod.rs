@@ -1,4 +1,5 @@
 pub(crate) mod dns_seeding;
 pub(crate) mod dump_peer_store;
 pub(crate) mod outbound_peer;
+pub(crate) mod port_mapping;
 pub(crate) mod protocol_type_checker;
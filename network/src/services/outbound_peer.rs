@@ -78,13 +78,7 @@ impl OutboundPeerService {
     }
 
     fn try_dial_whitelist(&self) {
-        // This will never panic because network start has already been checked
-        for (peer_id, addr) in self
-            .network_state
-            .config
-            .whitelist_peers()
-            .expect("address must be correct")
-        {
+        for (peer_id, addr) in self.network_state.whitelist_peers() {
             if self.network_state.query_session_id(&peer_id).is_none() {
                 self.network_state
                     .dial_identify(&self.p2p_control, &peer_id, addr);
@@ -0,0 +1,203 @@
+//! Maps the listened ports on the NAT gateway, so the nodes behind a home router become
+//! reachable.
+//!
+//! UPnP IGD is tried first, then NAT-PMP on the default gateway. The mappings are leased and
+//! renewed in a thread of their own. The external addresses are fed into the observed addresses,
+//! which are advertised through identify once the node succeeds to dial itself through them.
+use crate::NetworkState;
+use ckb_logger::{debug, info, warn};
+use p2p::multiaddr::{Multiaddr, Protocol};
+use std::io;
+use std::iter;
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4, UdpSocket};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+const LEASE_SECS: u32 = 60 * 60;
+/// Renews the mappings before the leases expire
+const RENEW_INTERVAL: Duration = Duration::from_secs(LEASE_SECS as u64 / 2);
+const SEARCH_TIMEOUT: Duration = Duration::from_secs(5);
+const MAPPING_DESCRIPTION: &str = "ckb";
+
+const NATPMP_PORT: u16 = 5351;
+const NATPMP_TIMEOUT: Duration = Duration::from_secs(2);
+const NATPMP_OP_EXTERNAL_ADDRESS: u8 = 0;
+const NATPMP_OP_MAP_TCP: u8 = 2;
+/// The server sets the bit on the op code of the response
+const NATPMP_RESPONSE_BIT: u8 = 128;
+
+/// Starts mapping the TCP ports of the listened addresses.
+pub(crate) fn spawn(network_state: Arc<NetworkState>, listened_addrs: Vec<Multiaddr>) {
+    let ports: Vec<u16> = listened_addrs.iter().filter_map(tcp_port).collect();
+    if ports.is_empty() {
+        return;
+    }
+    let spawned = thread::Builder::new()
+        .name("PortMapping".to_string())
+        .spawn(move || {
+            let mut mapped_addrs: Vec<Multiaddr> = Vec::new();
+            loop {
+                for &port in &ports {
+                    let external = match map_port(port) {
+                        Ok((ip, external_port)) => iter::once(Protocol::IP4(ip))
+                            .chain(iter::once(Protocol::TCP(external_port)))
+                            .collect::<Multiaddr>(),
+                        Err(err) => {
+                            debug!("port mapping of {} failed: {}", port, err);
+                            continue;
+                        }
+                    };
+                    // the renewals of a mapping are not the votes of new observations
+                    if !mapped_addrs.contains(&external) {
+                        info!("port {} is mapped to {}", port, external);
+                        network_state.add_observed_addrs(iter::once(external.clone()));
+                        mapped_addrs.push(external);
+                    }
+                }
+                thread::sleep(RENEW_INTERVAL);
+            }
+        });
+    if let Err(err) = spawned {
+        warn!("start port mapping failed: {}", err);
+    }
+}
+
+fn tcp_port(addr: &Multiaddr) -> Option<u16> {
+    addr.iter().find_map(|proto| match proto {
+        Protocol::TCP(port) => Some(port),
+        _ => None,
+    })
+}
+
+/// Maps the port by UPnP, or NAT-PMP if no UPnP gateway is found, returns the external address.
+fn map_port(port: u16) -> Result<(Ipv4Addr, u16), String> {
+    map_port_by_upnp(port).or_else(|upnp_err| {
+        map_port_by_natpmp(port)
+            .map_err(|natpmp_err| format!("upnp: {}, nat-pmp: {}", upnp_err, natpmp_err))
+    })
+}
+
+fn map_port_by_upnp(port: u16) -> Result<(Ipv4Addr, u16), String> {
+    let gateway = igd::search_gateway(igd::SearchOptions {
+        timeout: Some(SEARCH_TIMEOUT),
+        ..Default::default()
+    })
+    .map_err(|err| err.to_string())?;
+    let local_ip = local_ip_to(*gateway.addr.ip()).map_err(|err| err.to_string())?;
+    gateway
+        .add_port(
+            igd::PortMappingProtocol::TCP,
+            port,
+            SocketAddrV4::new(local_ip, port),
+            LEASE_SECS,
+            MAPPING_DESCRIPTION,
+        )
+        .map_err(|err| err.to_string())?;
+    let external_ip = gateway.get_external_ip().map_err(|err| err.to_string())?;
+    Ok((external_ip, port))
+}
+
+fn map_port_by_natpmp(port: u16) -> Result<(Ipv4Addr, u16), String> {
+    let gateway = default_gateway().ok_or_else(|| "no default gateway".to_string())?;
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).map_err(|err| err.to_string())?;
+    socket
+        .set_read_timeout(Some(NATPMP_TIMEOUT))
+        .map_err(|err| err.to_string())?;
+    socket
+        .connect((gateway, NATPMP_PORT))
+        .map_err(|err| err.to_string())?;
+
+    let response = natpmp_request(&socket, &[0, NATPMP_OP_EXTERNAL_ADDRESS], 12)?;
+    let external_ip = Ipv4Addr::new(response[8], response[9], response[10], response[11]);
+
+    let mut request = vec![0, NATPMP_OP_MAP_TCP, 0, 0];
+    request.extend_from_slice(&port.to_be_bytes());
+    request.extend_from_slice(&port.to_be_bytes());
+    request.extend_from_slice(&LEASE_SECS.to_be_bytes());
+    let response = natpmp_request(&socket, &request, 16)?;
+    let external_port = u16::from_be_bytes([response[10], response[11]]);
+    Ok((external_ip, external_port))
+}
+
+// Sends the request and checks the header of the response.
+fn natpmp_request(socket: &UdpSocket, request: &[u8], len: usize) -> Result<Vec<u8>, String> {
+    socket.send(request).map_err(|err| err.to_string())?;
+    let mut response = vec![0u8; len];
+    let received = socket.recv(&mut response).map_err(|err| err.to_string())?;
+    if received < len || response[1] != request[1] | NATPMP_RESPONSE_BIT {
+        return Err("invalid nat-pmp response".to_string());
+    }
+    match u16::from_be_bytes([response[2], response[3]]) {
+        0 => Ok(response),
+        code => Err(format!("nat-pmp result code {}", code)),
+    }
+}
+
+// The address of the interface which routes to the gateway.
+fn local_ip_to(gateway: Ipv4Addr) -> io::Result<Ipv4Addr> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?;
+    socket.connect((gateway, NATPMP_PORT))?;
+    match socket.local_addr()? {
+        SocketAddr::V4(addr) => Ok(*addr.ip()),
+        SocketAddr::V6(addr) => Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!("unexpected local address {}", addr),
+        )),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn default_gateway() -> Option<Ipv4Addr> {
+    let routes = std::fs::read_to_string("/proc/net/route").ok()?;
+    parse_default_gateway(&routes)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn default_gateway() -> Option<Ipv4Addr> {
+    None
+}
+
+// The columns of `/proc/net/route` are separated by whitespaces, the addresses are the hex of the
+// network byte order words in the host byte order.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_default_gateway(routes: &str) -> Option<Ipv4Addr> {
+    routes.lines().skip(1).find_map(|line| {
+        let mut columns = line.split_whitespace().skip(1);
+        if columns.next() != Some("00000000") {
+            return None;
+        }
+        columns
+            .next()
+            .and_then(|gateway| u32::from_str_radix(gateway, 16).ok())
+            .map(|gateway| Ipv4Addr::from(gateway.to_le_bytes()))
+            .filter(|gateway| !gateway.is_unspecified())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_default_gateway() {
+        let routes = "Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\n\
+                      eth0\t0001A8C0\t00000000\t0001\t0\t0\t0\t00FFFFFF\n\
+                      eth0\t00000000\t0101A8C0\t0003\t0\t0\t0\t00000000\n";
+        assert_eq!(
+            parse_default_gateway(routes),
+            Some(Ipv4Addr::new(192, 168, 1, 1))
+        );
+        let routes = "Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\n\
+                      eth0\t0001A8C0\t00000000\t0001\t0\t0\t0\t00FFFFFF\n";
+        assert_eq!(parse_default_gateway(routes), None);
+    }
+
+    #[test]
+    fn test_tcp_port() {
+        let addr: Multiaddr = "/ip4/0.0.0.0/tcp/8115".parse().unwrap();
+        assert_eq!(tcp_port(&addr), Some(8115));
+        let addr: Multiaddr = "/ip4/0.0.0.0".parse().unwrap();
+        assert_eq!(tcp_port(&addr), None);
+    }
+}
@@ -1,7 +1,7 @@
 use crate::{
     multiaddr::Multiaddr,
     peer_store::{
-        addr_manager::AddrManager,
+        addr_manager::{AddrManager, Table},
         types::{AddrInfo, MultiaddrExt},
     },
     PeerId,
@@ -45,3 +45,46 @@ proptest! {
         assert_eq!(addrs.len(), count);
     }
 }
+
+fn new_addr_info(addr: &str, last_connected_at_ms: u64) -> AddrInfo {
+    let addr: Multiaddr = addr.parse().unwrap();
+    let ip_addr = addr.extract_ip_addr().unwrap();
+    AddrInfo::new(PeerId::random(), ip_addr, addr, last_connected_at_ms, 0)
+}
+
+#[test]
+fn test_flooding_group_does_not_crowd_out_others() {
+    let mut addr_manager = AddrManager::default();
+    for i in 0..1000u32 {
+        let ip = Ipv4Addr::from((225 << 24) + i);
+        addr_manager.add(new_addr_info(&format!("/ip4/{}/tcp/42", ip), 0));
+    }
+    let lonely = new_addr_info("/ip4/10.0.0.1/tcp/42", 0);
+    addr_manager.add(lonely.clone());
+
+    // the flooding group takes at most 16 buckets, every bucket is visited in the first round
+    let addrs = addr_manager.fetch_random(17, |_| true);
+    assert!(addrs.iter().any(|addr| addr.ip_port() == lonely.ip_port()));
+}
+
+#[test]
+fn test_tables() {
+    let mut addr_manager = AddrManager::default();
+    let addr = new_addr_info("/ip4/10.0.0.1/tcp/42", 0);
+    addr_manager.add(addr.clone());
+    addr_manager.add(new_addr_info("/ip4/10.1.0.1/tcp/42", 0));
+    assert_eq!(addr_manager.table_count(Table::New), 2);
+    assert_eq!(addr_manager.table_count(Table::Tried), 0);
+
+    // moved to the tried table once connected
+    addr_manager.add(new_addr_info("/ip4/10.0.0.1/tcp/42", 1));
+    assert_eq!(addr_manager.table_count(Table::New), 1);
+    assert_eq!(addr_manager.table_count(Table::Tried), 1);
+    let fetched = addr_manager.fetch_random_in_table(Table::Tried, 2, |_| true);
+    assert_eq!(fetched.len(), 1);
+    assert_eq!(fetched[0].ip_port(), addr.ip_port());
+
+    assert!(addr_manager.remove(&addr.ip_port()).is_some());
+    assert_eq!(addr_manager.table_count(Table::Tried), 0);
+    assert_eq!(addr_manager.count(), 1);
+}
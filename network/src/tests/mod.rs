@@ -1,4 +1,5 @@
 mod addr_manager;
+mod network_state;
 mod peer_registry;
 mod peer_store;
 mod peer_store_db;
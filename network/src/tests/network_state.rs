@@ -0,0 +1,129 @@
+use crate::{
+    multiaddr::Multiaddr,
+    peer_scoring::ScoreAction,
+    protocols::identify::{Callback, IdentifyCallback, Misbehavior},
+    NetworkState, PeerId,
+};
+use ckb_app_config::{IdentifyConfig, MisbehaviorAction, NetworkConfig, WhitelistConfig};
+use std::sync::Arc;
+use tempfile::TempDir;
+
+fn network_state(
+    dir: &TempDir,
+    whitelist: WhitelistConfig,
+    identify: IdentifyConfig,
+) -> NetworkState {
+    let config = NetworkConfig {
+        listen_addresses: vec![],
+        public_addresses: vec![],
+        bootnodes: vec![],
+        dns_seeds: vec![],
+        dns_seeds_pubkey: None,
+        whitelist_peers: vec![],
+        whitelist,
+        whitelist_only: false,
+        max_peers: 19,
+        max_outbound_peers: 5,
+        path: dir.path().to_path_buf(),
+        ping_interval_secs: 15,
+        ping_timeout_secs: 20,
+        connect_outbound_interval_secs: 1,
+        discovery_local_address: true,
+        upnp: false,
+        bootnode_mode: false,
+        light_client_server: false,
+        tx_reconciliation: false,
+        mempool_sync: false,
+        max_send_buffer: None,
+        peer_scoring: Default::default(),
+        rate_limit: Default::default(),
+        dandelion: Default::default(),
+        identify,
+    };
+    NetworkState::from_config(config).expect("Init network state failed")
+}
+
+#[test]
+fn test_whitelist_peers_are_not_punished() {
+    let dir = TempDir::new().unwrap();
+    let trusted = PeerId::random();
+    let addr = format!("/ip4/192.168.1.2/tcp/8115/p2p/{}", trusted.to_base58())
+        .parse::<Multiaddr>()
+        .unwrap();
+    let state = network_state(
+        &dir,
+        WhitelistConfig { peers: vec![addr] },
+        Default::default(),
+    );
+    assert!(state.with_peer_registry(|reg| reg.is_whitelist(&trusted)));
+    assert!(state.whitelist_peers().contains(&(
+        trusted.clone(),
+        "/ip4/192.168.1.2/tcp/8115".parse().unwrap()
+    )));
+
+    // an unknown misbehavior disconnects the other peers, and bans them the second time
+    for _ in 0..2 {
+        assert_eq!(
+            state.report_misbehavior(&trusted, "unknown"),
+            ScoreAction::Continue
+        );
+    }
+    for action in [MisbehaviorAction::Disconnect, MisbehaviorAction::Ban].iter() {
+        assert_eq!(
+            state.punish_misbehavior(&trusted, "unknown", *action),
+            ScoreAction::Continue
+        );
+    }
+
+    let other = PeerId::random();
+    assert!(!state.with_peer_registry(|reg| reg.is_whitelist(&other)));
+    assert_eq!(
+        state.report_misbehavior(&other, "unknown"),
+        ScoreAction::Disconnect
+    );
+    assert_eq!(
+        state.punish_misbehavior(&other, "unknown", MisbehaviorAction::Disconnect),
+        ScoreAction::Disconnect
+    );
+}
+
+#[test]
+fn test_identify_misbehavior_actions() {
+    let dir = TempDir::new().unwrap();
+    let mut identify = IdentifyConfig::default();
+    identify
+        .misbehavior_actions
+        .insert("identify.timeout".to_string(), MisbehaviorAction::Warn);
+    identify
+        .misbehavior_actions
+        .insert("identify.invalid_data".to_string(), MisbehaviorAction::Ban);
+    identify.misbehavior_actions.insert(
+        "identify.duplicate_observed_addr".to_string(),
+        MisbehaviorAction::Disconnect,
+    );
+    let state = Arc::new(network_state(&dir, Default::default(), identify));
+    let mut callback = IdentifyCallback::new(state, "test".to_string(), "0.1.0".to_string());
+    let peer_id = PeerId::random();
+
+    // warned only, however many times
+    for _ in 0..3 {
+        assert!(!callback
+            .misbehave(&peer_id, Misbehavior::Timeout)
+            .is_disconnect());
+    }
+    // scored by default, the warnings above are not counted
+    assert!(!callback
+        .misbehave(&peer_id, Misbehavior::DuplicateListenAddrs)
+        .is_disconnect());
+    assert!(callback
+        .misbehave(&peer_id, Misbehavior::DuplicateObservedAddr)
+        .is_disconnect());
+    // the peer is not connected, there is no address to ban
+    assert!(callback
+        .misbehave(&peer_id, Misbehavior::InvalidData)
+        .is_disconnect());
+    assert_eq!(
+        IdentifyConfig::default().misbehavior_action("identify.timeout"),
+        MisbehaviorAction::Score
+    );
+}
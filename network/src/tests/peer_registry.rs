@@ -16,6 +16,7 @@ fn test_accept_inbound_peer_in_reserve_only_mode() {
 
     // whitelist_only mode: only accept whitelist_peer
     let mut peers = PeerRegistry::new(3, 3, true, vec![whitelist_peer.clone()]);
+    assert!(peers.is_whitelist(&whitelist_peer));
     let err = peers
         .accept_peer(
             PeerId::random(),
@@ -41,6 +42,34 @@ fn test_accept_inbound_peer_in_reserve_only_mode() {
         .expect("accept");
 }
 
+#[test]
+fn test_set_whitelist_peers() {
+    let mut peer_store = PeerStore::default();
+    let peer = PeerId::random();
+    let addr = "/ip4/127.0.0.1/tcp/42".parse::<Multiaddr>().unwrap();
+    let session_id = 1.into();
+    let mut peers = PeerRegistry::new(3, 3, false, vec![]);
+    peers
+        .accept_peer(
+            peer.clone(),
+            addr,
+            session_id,
+            SessionType::Inbound,
+            &mut peer_store,
+        )
+        .expect("accept");
+    assert!(!peers.get_peer(session_id).unwrap().is_whitelist);
+
+    // the connected peer is marked by the new whitelist
+    peers.set_whitelist_peers(vec![peer.clone()]);
+    assert!(peers.is_whitelist(&peer));
+    assert!(peers.get_peer(session_id).unwrap().is_whitelist);
+
+    peers.set_whitelist_peers(vec![]);
+    assert!(!peers.is_whitelist(&peer));
+    assert!(!peers.get_peer(session_id).unwrap().is_whitelist);
+}
+
 #[test]
 fn test_accept_inbound_peer_until_full() {
     let mut peer_store = PeerStore::default();
@@ -110,6 +139,47 @@ fn test_accept_inbound_peer_until_full() {
     );
 }
 
+#[test]
+fn test_accept_inbound_peers_per_ip() {
+    fn accept(
+        peers: &mut PeerRegistry,
+        peer_store: &mut PeerStore,
+        addr: &str,
+        session_id: usize,
+    ) -> Result<Option<crate::Peer>, Error> {
+        peers.accept_peer(
+            PeerId::random(),
+            addr.parse::<Multiaddr>().unwrap(),
+            session_id.into(),
+            SessionType::Inbound,
+            peer_store,
+        )
+    }
+
+    let mut peer_store = PeerStore::default();
+    let mut peers = PeerRegistry::new(10, 3, false, vec![]).with_inbound_limits_per_ip(2, 3);
+    accept(&mut peers, &mut peer_store, "/ip4/1.2.3.4/tcp/42", 1).expect("accept");
+    accept(&mut peers, &mut peer_store, "/ip4/1.2.3.4/tcp/43", 2).expect("accept");
+    let err = accept(&mut peers, &mut peer_store, "/ip4/1.2.3.4/tcp/44", 3).unwrap_err();
+    assert_eq!(
+        format!("{}", err),
+        format!("{}", Error::Peer(PeerError::ReachMaxInboundPerIpLimit))
+    );
+
+    accept(&mut peers, &mut peer_store, "/ip4/1.2.5.6/tcp/42", 4).expect("accept");
+    let err = accept(&mut peers, &mut peer_store, "/ip4/1.2.7.8/tcp/42", 5).unwrap_err();
+    assert_eq!(
+        format!("{}", err),
+        format!("{}", Error::Peer(PeerError::ReachMaxInboundPerSubnetLimit))
+    );
+
+    // the loopback addresses are not limited
+    for session_id in 6..=8 {
+        let addr = format!("/ip4/127.0.0.1/tcp/{}", session_id);
+        accept(&mut peers, &mut peer_store, &addr, session_id).expect("accept");
+    }
+}
+
 #[test]
 fn test_accept_inbound_peer_eviction() {
     // eviction inbound peer
@@ -214,3 +284,87 @@ fn test_accept_inbound_peer_eviction() {
     // should evict from one of evict_targets
     assert_eq!(len_after_eviction, evict_targets.len() - 1);
 }
+
+#[test]
+fn test_inbound_peer_eviction_protects_useful_and_diverse_peers() {
+    let mut peer_store = PeerStore::default();
+    let addr = "/ip4/192.168.0.1/tcp/42".parse::<Multiaddr>().unwrap();
+    let lone_group_addr = "/ip4/10.0.0.1/tcp/42".parse::<Multiaddr>().unwrap();
+    let fillers_count = 2 * EVICTION_PROTECT_PEERS;
+    let mut peers_registry = PeerRegistry::new(fillers_count as u32 + 7, 3, false, vec![]);
+    let mut accept = |peers_registry: &mut PeerRegistry, addr: &Multiaddr, session_id: usize| {
+        let peer_id = PeerId::random();
+        peers_registry
+            .accept_peer(
+                peer_id.clone(),
+                addr.clone(),
+                session_id.into(),
+                SessionType::Inbound,
+                &mut peer_store,
+            )
+            .expect("accept");
+        peer_id
+    };
+
+    // to prevent time error, we set now to 60s ago.
+    let now = Instant::now() - Duration::from_secs(60);
+    // the fillers are protected by the lowest ping and the most recent messages
+    for session_id in 0..fillers_count {
+        accept(&mut peers_registry, &addr, session_id);
+        let peer = peers_registry.get_peer_mut(session_id.into()).unwrap();
+        if session_id < EVICTION_PROTECT_PEERS {
+            peer.ping = Some(Duration::from_secs(0));
+        } else {
+            peer.last_message_time = Some(now + Duration::from_secs(60));
+        }
+    }
+    let block_relayer = accept(&mut peers_registry, &addr, 100);
+    let tx_relayer = accept(&mut peers_registry, &addr, 101);
+    let oldest = accept(&mut peers_registry, &addr, 102);
+    let second_oldest = accept(&mut peers_registry, &addr, 106);
+    let lone_group = accept(&mut peers_registry, &lone_group_addr, 103);
+    let oldest_of_group = accept(&mut peers_registry, &addr, 104);
+    let youngest_of_group = accept(&mut peers_registry, &addr, 105);
+    for (session_id, connected_time) in vec![
+        (100, now),
+        (101, now),
+        (102, now - Duration::from_secs(40)),
+        (106, now - Duration::from_secs(30)),
+        (103, now + Duration::from_secs(50)),
+        (104, now - Duration::from_secs(20)),
+        (105, now - Duration::from_secs(10)),
+    ] {
+        let peer = peers_registry.get_peer_mut(session_id.into()).unwrap();
+        peer.connected_time = connected_time;
+    }
+    peers_registry
+        .get_peer_mut(100.into())
+        .unwrap()
+        .last_block_time = Some(now);
+    peers_registry
+        .get_peer_mut(101.into())
+        .unwrap()
+        .last_tx_time = Some(now);
+
+    let new_addr = "/ip4/172.16.0.1/tcp/42".parse::<Multiaddr>().unwrap();
+    let evicted_peer = peers_registry
+        .accept_peer(
+            PeerId::random(),
+            new_addr,
+            2000.into(),
+            SessionType::Inbound,
+            &mut peer_store,
+        )
+        .expect("accept")
+        .expect("evict a peer");
+    assert_eq!(evicted_peer.peer_id, youngest_of_group);
+    for peer_id in &[
+        block_relayer,
+        tx_relayer,
+        oldest,
+        lone_group,
+        oldest_of_group,
+    ] {
+        assert!(peers_registry.get_key_by_peer_id(peer_id).is_some());
+    }
+}
@@ -1,6 +1,9 @@
 use crate::{
     multiaddr::{self, Multiaddr},
-    peer_store::{types::MultiaddrExt, PeerStore, Status, ADDR_COUNT_LIMIT},
+    peer_store::{
+        types::{MultiaddrExt, TransportType},
+        PeerStore, Status, ADDR_COUNT_LIMIT,
+    },
     Behaviour, PeerId, SessionType,
 };
 
@@ -63,6 +66,32 @@ fn test_ban_peer() {
     assert!(peer_store.is_addr_banned(&addr));
 }
 
+#[test]
+fn test_ban_network_until() {
+    let mut peer_store: PeerStore = Default::default();
+    let addr: Multiaddr = "/ip4/192.168.0.42/tcp/42".parse().unwrap();
+    let now_ms = faketime::unix_time_as_millis();
+    peer_store
+        .ban_network_until(
+            "192.168.0.0/24".parse().unwrap(),
+            now_ms - 1,
+            "expired".into(),
+        )
+        .unwrap();
+    assert!(!peer_store.is_addr_banned(&addr));
+    peer_store
+        .ban_network_until(
+            "192.168.0.0/24".parse().unwrap(),
+            now_ms + 10_000,
+            "no reason".into(),
+        )
+        .unwrap();
+    assert!(peer_store.is_addr_banned(&addr));
+    assert!(!peer_store.is_addr_banned(&"/ip4/192.168.1.42/tcp/42".parse().unwrap()));
+    peer_store.mut_ban_list().clear();
+    assert!(!peer_store.is_addr_banned(&addr));
+}
+
 #[test]
 fn test_attempt_ban() {
     let mut peer_store: PeerStore = Default::default();
@@ -90,6 +119,26 @@ fn test_fetch_addrs_to_attempt() {
     assert!(peer_store.fetch_addrs_to_attempt(1).is_empty());
 }
 
+#[test]
+fn test_fetch_addrs_to_attempt_by_transport() {
+    let mut peer_store: PeerStore = Default::default();
+    let peer_id = PeerId::random();
+    let ws_addr = "/ip4/127.0.0.1/tcp/43/ws".parse::<Multiaddr>().unwrap();
+    peer_store
+        .add_addr(peer_id.clone(), ws_addr.clone())
+        .unwrap();
+    let addrs = peer_store.fetch_addrs_to_attempt(2);
+    assert_eq!(addrs.len(), 1);
+    assert_eq!(addrs[0].addr.transport_type(), TransportType::Ws);
+
+    // the peer listening on both transports is dialed via TCP
+    let tcp_addr = "/ip4/127.0.0.1/tcp/42".parse::<Multiaddr>().unwrap();
+    peer_store.add_addr(peer_id, tcp_addr.clone()).unwrap();
+    let addrs = peer_store.fetch_addrs_to_attempt(2);
+    assert_eq!(addrs.len(), 1);
+    assert_eq!(addrs[0].addr, tcp_addr);
+}
+
 #[test]
 fn test_fetch_addrs_to_attempt_in_last_minutes() {
     let mut peer_store: PeerStore = Default::default();
@@ -0,0 +1,282 @@
+use crate::{ReorgEvent, NOTIFY_CHANNEL_SIZE, WEBHOOK_TIMEOUT};
+use ckb_app_config::{HookConfig, HookEvent};
+use ckb_logger::{error, warn};
+use ckb_types::{core::BlockView, packed::Alert, prelude::*};
+use crossbeam_channel::{bounded, Sender, TrySendError};
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::thread;
+
+struct Event {
+    kind: HookEvent,
+    reorg_depth: u64,
+    json: String,
+    vars: Vec<(&'static str, String)>,
+}
+
+fn event_name(kind: HookEvent) -> &'static str {
+    match kind {
+        HookEvent::NewBlock => "new_block",
+        HookEvent::Reorg => "reorg",
+        HookEvent::NetworkAlert => "network_alert",
+    }
+}
+
+/// Runs the configured hooks in a thread of its own, so slow commands and webhooks don't hold
+/// the notify service back.
+pub(crate) struct Hooks {
+    configs: Vec<HookConfig>,
+    sender: Option<Sender<Event>>,
+}
+
+impl Hooks {
+    pub(crate) fn start(configs: Vec<HookConfig>) -> Self {
+        if configs.is_empty() {
+            return Hooks {
+                configs,
+                sender: None,
+            };
+        }
+        let (sender, receiver) = bounded::<Event>(NOTIFY_CHANNEL_SIZE);
+        let hook_configs = configs.clone();
+        thread::Builder::new()
+            .name("NotifyHooks".to_string())
+            .spawn(move || {
+                // exits when the notify service is stopped
+                while let Ok(event) = receiver.recv() {
+                    for config in hook_configs.iter().filter(|config| matches(config, &event)) {
+                        run(config, &event);
+                    }
+                }
+            })
+            .expect("Start notify hooks failed");
+        Hooks {
+            configs,
+            sender: Some(sender),
+        }
+    }
+
+    pub(crate) fn new_block(&self, block: &BlockView) {
+        if !self.listens(HookEvent::NewBlock) {
+            return;
+        }
+        let header = block.header();
+        let json: ckb_jsonrpc_types::HeaderView = header.clone().into();
+        self.trigger(Event {
+            kind: HookEvent::NewBlock,
+            reorg_depth: 0,
+            json: serde_json::to_string(&json).expect("serialization should be ok"),
+            vars: vec![
+                ("block_hash", format!("{:#x}", header.hash())),
+                ("block_number", header.number().to_string()),
+                ("timestamp", header.timestamp().to_string()),
+                ("transactions_count", block.transactions().len().to_string()),
+            ],
+        });
+    }
+
+    pub(crate) fn reorg(&self, reorg: &ReorgEvent) {
+        if !self.listens(HookEvent::Reorg) {
+            return;
+        }
+        let fork_number = reorg
+            .detached_blocks
+            .first()
+            .map(|header| header.number().saturating_sub(1))
+            .unwrap_or_default();
+        let (tip_hash, tip_number) = reorg
+            .attached_blocks
+            .last()
+            .map(|header| (format!("{:#x}", header.hash()), header.number().to_string()))
+            .unwrap_or_default();
+        let detached_hashes = reorg
+            .detached_blocks
+            .iter()
+            .map(|header| format!("{:#x}", header.hash()))
+            .collect::<Vec<_>>()
+            .join(",");
+        let json: ckb_jsonrpc_types::ReorgEvent = reorg.clone().into();
+        self.trigger(Event {
+            kind: HookEvent::Reorg,
+            reorg_depth: reorg.detached_blocks.len() as u64,
+            json: serde_json::to_string(&json).expect("serialization should be ok"),
+            vars: vec![
+                ("depth", reorg.detached_blocks.len().to_string()),
+                ("fork_number", fork_number.to_string()),
+                ("tip_hash", tip_hash),
+                ("tip_number", tip_number),
+                ("detached_hashes", detached_hashes),
+            ],
+        });
+    }
+
+    pub(crate) fn network_alert(&self, alert: &Alert) {
+        if !self.listens(HookEvent::NetworkAlert) {
+            return;
+        }
+        let raw = alert.raw();
+        let id: u32 = raw.id().unpack();
+        let priority: u32 = raw.priority().unpack();
+        let message = String::from_utf8_lossy(&raw.message().raw_data()).to_string();
+        let json: ckb_jsonrpc_types::Alert = alert.clone().into();
+        self.trigger(Event {
+            kind: HookEvent::NetworkAlert,
+            reorg_depth: 0,
+            json: serde_json::to_string(&json).expect("serialization should be ok"),
+            vars: vec![
+                ("alert_id", id.to_string()),
+                ("alert_priority", priority.to_string()),
+                ("alert_message", escape_json(&message)),
+            ],
+        });
+    }
+
+    fn listens(&self, kind: HookEvent) -> bool {
+        self.configs.iter().any(|config| config.event == kind)
+    }
+
+    fn trigger(&self, event: Event) {
+        if let Some(sender) = self.sender.as_ref() {
+            if let Err(TrySendError::Full(event)) = sender.try_send(event) {
+                warn!(
+                    "notify hooks lag behind, drop the {} event",
+                    event_name(event.kind)
+                );
+            }
+        }
+    }
+}
+
+fn matches(config: &HookConfig, event: &Event) -> bool {
+    config.event == event.kind
+        && (event.kind != HookEvent::Reorg
+            || event.reorg_depth >= config.min_reorg_depth.unwrap_or(1))
+}
+
+/// Escapes the string to be embedded in a JSON string literal.
+fn escape_json(value: &str) -> String {
+    let quoted = serde_json::to_string(value).expect("serialization should be ok");
+    quoted[1..quoted.len() - 1].to_string()
+}
+
+/// Replaces `{event}`, `{json}` and the event variables in the template, unknown placeholders
+/// are kept as is.
+fn render(template: &str, event: &Event) -> String {
+    let mut rendered = template
+        .replace("{event}", event_name(event.kind))
+        .replace("{json}", &event.json);
+    for (name, value) in &event.vars {
+        rendered = rendered.replace(&format!("{{{}}}", name), value);
+    }
+    rendered
+}
+
+fn run(config: &HookConfig, event: &Event) {
+    let payload = match config.payload.as_ref() {
+        Some(template) => render(template, event),
+        None => event.json.clone(),
+    };
+
+    if let Some(command) = config.command.as_ref() {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c")
+            .arg(command)
+            .env("CKB_HOOK_EVENT", event_name(event.kind))
+            .stdin(Stdio::piped());
+        for (name, value) in &event.vars {
+            cmd.env(format!("CKB_{}", name.to_uppercase()), value);
+        }
+        let result = cmd.spawn().and_then(|mut child| {
+            if let Some(mut stdin) = child.stdin.take() {
+                // the command may exit without reading the payload
+                let _ = stdin.write_all(payload.as_bytes());
+            }
+            child.wait()
+        });
+        match result {
+            Ok(status) if !status.success() => error!(
+                "{} hook command `{}` exits with {}",
+                event_name(event.kind),
+                command,
+                status
+            ),
+            Err(err) => error!(
+                "failed to run {} hook command `{}`, error: {}",
+                event_name(event.kind),
+                command,
+                err
+            ),
+            _ => {}
+        }
+    }
+
+    if let Some(url) = config.webhook.as_ref() {
+        let result = reqwest::Client::builder()
+            .timeout(WEBHOOK_TIMEOUT)
+            .build()
+            .and_then(|client| {
+                client
+                    .post(url)
+                    .header(reqwest::header::CONTENT_TYPE, "application/json")
+                    .body(payload)
+                    .send()
+            })
+            .and_then(|response| response.error_for_status());
+        if let Err(err) = result {
+            error!(
+                "failed to post {} hook webhook: {}, error: {}",
+                event_name(event.kind),
+                url,
+                err
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reorg_event(depth: u64) -> Event {
+        Event {
+            kind: HookEvent::Reorg,
+            reorg_depth: depth,
+            json: "{}".to_string(),
+            vars: vec![
+                ("depth", depth.to_string()),
+                ("fork_number", "9".to_string()),
+            ],
+        }
+    }
+
+    #[test]
+    fn render_payload_template() {
+        let event = reorg_event(3);
+        assert_eq!(
+            render(
+                r#"{"text": "{event} of {depth} blocks after {fork_number} {unknown}", "data": {json}}"#,
+                &event
+            ),
+            r#"{"text": "reorg of 3 blocks after 9 {unknown}", "data": {}}"#
+        );
+        assert_eq!(escape_json("say \"hi\"\n"), r#"say \"hi\"\n"#);
+    }
+
+    #[test]
+    fn match_deep_reorg_only() {
+        let config = HookConfig {
+            event: HookEvent::Reorg,
+            command: None,
+            webhook: None,
+            payload: None,
+            min_reorg_depth: Some(3),
+        };
+        assert!(!matches(&config, &reorg_event(2)));
+        assert!(matches(&config, &reorg_event(3)));
+        let config = HookConfig {
+            min_reorg_depth: None,
+            ..config
+        };
+        assert!(matches(&config, &reorg_event(1)));
+    }
+}
@@ -1,28 +1,208 @@
+mod hooks;
+mod publisher;
+
+use crate::hooks::Hooks;
+use crate::publisher::Publisher;
 use ckb_app_config::NotifyConfig;
-use ckb_logger::{debug, error, trace};
+use ckb_logger::{debug, error, trace, warn};
 use ckb_stop_handler::{SignalSender, StopHandler};
 use ckb_types::{
-    core::{service::Request, BlockView},
-    packed::Alert,
+    core::{service::Request, BlockNumber, BlockView, HeaderView, TransactionView, UncleBlockView},
+    packed::{Alert, Byte32},
+    prelude::*,
 };
-use crossbeam_channel::{bounded, select, Receiver, RecvError, Sender};
+use crossbeam_channel::{bounded, select, Receiver, RecvError, Sender, TryRecvError, TrySendError};
 use std::collections::HashMap;
 use std::process::Command;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
 use std::thread;
+use std::time::Duration;
+
+pub use crate::publisher::{topic_name, NotificationSink, TcpSink};
 
 pub const SIGNAL_CHANNEL_SIZE: usize = 1;
 pub const REGISTER_CHANNEL_SIZE: usize = 2;
 pub const NOTIFY_CHANNEL_SIZE: usize = 128;
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub type NotifyRegister<M> = Sender<Request<String, Subscription<M>>>;
+
+/// The receiving end of a topic subscription.
+///
+/// Every subscription buffers at most `NOTIFY_CHANNEL_SIZE` events. When the buffer is full,
+/// new events are dropped for this subscriber instead of blocking the service and the other
+/// subscribers, and the drops are counted as lag.
+pub struct Subscription<T> {
+    receiver: Receiver<T>,
+    lagged: Arc<AtomicU64>,
+}
+
+impl<T> Subscription<T> {
+    pub fn recv(&self) -> Result<T, RecvError> {
+        self.receiver.recv()
+    }
+
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        self.receiver.try_recv()
+    }
+
+    /// The underlying channel, for `select!`.
+    pub fn receiver(&self) -> &Receiver<T> {
+        &self.receiver
+    }
+
+    /// Drops the lag detection and keeps the channel only.
+    pub fn into_receiver(self) -> Receiver<T> {
+        self.receiver
+    }
+
+    /// Returns the number of events dropped since the last call and resets it.
+    pub fn take_lagged(&self) -> u64 {
+        self.lagged.swap(0, Ordering::SeqCst)
+    }
+}
+
+struct Subscribers<T> {
+    topic: &'static str,
+    subscribers: HashMap<String, (Sender<T>, Arc<AtomicU64>)>,
+}
+
+impl<T: Clone> Subscribers<T> {
+    fn new(topic: &'static str) -> Self {
+        Subscribers {
+            topic,
+            subscribers: HashMap::default(),
+        }
+    }
+
+    fn register(&mut self, msg: Result<Request<String, Subscription<T>>, RecvError>) {
+        match msg {
+            Ok(Request {
+                responder,
+                arguments: name,
+            }) => {
+                debug!("Register {} {:?}", self.topic, name);
+                let (sender, receiver) = bounded::<T>(NOTIFY_CHANNEL_SIZE);
+                let lagged = Arc::new(AtomicU64::new(0));
+                self.subscribers.insert(name, (sender, Arc::clone(&lagged)));
+                let _ = responder.send(Subscription { receiver, lagged });
+            }
+            _ => debug!("Register {} channel is closed", self.topic),
+        }
+    }
 
-pub type NotifyRegister<M> = Sender<Request<String, Receiver<M>>>;
+    /// Sends the event to all subscribers and returns it for the side effects.
+    fn notify(&mut self, msg: Result<T, RecvError>) -> Option<T> {
+        let event = match msg {
+            Ok(event) => event,
+            _ => {
+                debug!("{} channel is closed", self.topic);
+                return None;
+            }
+        };
+        let topic = self.topic;
+        self.subscribers.retain(
+            |name, (sender, lagged)| match sender.try_send(event.clone()) {
+                Ok(()) => true,
+                Err(TrySendError::Full(_)) => {
+                    let count = lagged.fetch_add(1, Ordering::SeqCst) + 1;
+                    warn!(
+                        "{} subscriber {} lags behind, {} events dropped",
+                        topic, name, count
+                    );
+                    true
+                }
+                Err(TrySendError::Disconnected(_)) => {
+                    debug!("{} subscriber {} is gone", topic, name);
+                    false
+                }
+            },
+        );
+        Some(event)
+    }
+}
+
+/// A main chain reorganization, the blocks are ordered from the lowest to the highest.
+#[derive(Clone, Debug)]
+pub struct ReorgEvent {
+    /// The last block shared by the detached and the attached blocks
+    pub common_ancestor: HeaderView,
+    pub detached_blocks: Vec<HeaderView>,
+    pub attached_blocks: Vec<HeaderView>,
+    /// The transaction hashes of the attached blocks, in the order of `attached_blocks`
+    pub attached_tx_hashes: Vec<Vec<Byte32>>,
+}
+
+/// A main chain transaction which creates or consumes cells locked by the watched lock scripts.
+#[derive(Clone, Debug)]
+pub struct WatchedTransaction {
+    /// The watched lock hashes of the created and consumed cells
+    pub lock_hashes: Vec<Byte32>,
+    pub block_number: BlockNumber,
+    pub block_hash: Byte32,
+    pub transaction: TransactionView,
+    /// The block has been detached from the main chain, the transaction is rolled back
+    pub detached: bool,
+}
+
+/// A transaction which the tx pool refuses to accept.
+#[derive(Clone, Debug)]
+pub struct RejectedTransaction {
+    pub transaction: TransactionView,
+    pub reason: String,
+}
+
+impl From<RejectedTransaction> for ckb_jsonrpc_types::RejectedTransaction {
+    fn from(input: RejectedTransaction) -> Self {
+        ckb_jsonrpc_types::RejectedTransaction {
+            transaction: input.transaction.into(),
+            reason: input.reason,
+        }
+    }
+}
+
+impl From<WatchedTransaction> for ckb_jsonrpc_types::WatchedTransaction {
+    fn from(input: WatchedTransaction) -> Self {
+        ckb_jsonrpc_types::WatchedTransaction {
+            lock_hashes: input.lock_hashes.iter().map(Unpack::unpack).collect(),
+            block_number: input.block_number.into(),
+            block_hash: input.block_hash.unpack(),
+            transaction: input.transaction.into(),
+            detached: input.detached,
+        }
+    }
+}
+
+impl From<ReorgEvent> for ckb_jsonrpc_types::ReorgEvent {
+    fn from(input: ReorgEvent) -> Self {
+        ckb_jsonrpc_types::ReorgEvent {
+            common_ancestor: input.common_ancestor.into(),
+            detached_blocks: input.detached_blocks.into_iter().map(Into::into).collect(),
+            attached_blocks: input.attached_blocks.into_iter().map(Into::into).collect(),
+        }
+    }
+}
 
 #[derive(Clone)]
 pub struct NotifyController {
     stop: StopHandler<()>,
     new_block_register: NotifyRegister<BlockView>,
     new_block_notifier: Sender<BlockView>,
+    new_uncle_register: NotifyRegister<UncleBlockView>,
+    new_uncle_notifier: Sender<UncleBlockView>,
+    reorg_register: NotifyRegister<ReorgEvent>,
+    reorg_notifier: Sender<ReorgEvent>,
+    new_transaction_register: NotifyRegister<TransactionView>,
+    new_transaction_notifier: Sender<TransactionView>,
+    rejected_transaction_register: NotifyRegister<RejectedTransaction>,
+    rejected_transaction_notifier: Sender<RejectedTransaction>,
     network_alert_register: NotifyRegister<Alert>,
     network_alert_notifier: Sender<Alert>,
+    watched_transaction_register: NotifyRegister<WatchedTransaction>,
+    watched_transaction_notifier: Sender<WatchedTransaction>,
 }
 
 impl Drop for NotifyController {
@@ -33,28 +213,66 @@ impl Drop for NotifyController {
 
 pub struct NotifyService {
     config: NotifyConfig,
-    new_block_subscribers: HashMap<String, Sender<BlockView>>,
-    network_alert_subscribers: HashMap<String, Sender<Alert>>,
+    hooks: Hooks,
+    publisher: Publisher,
+    new_block_subscribers: Subscribers<BlockView>,
+    new_uncle_subscribers: Subscribers<UncleBlockView>,
+    reorg_subscribers: Subscribers<ReorgEvent>,
+    new_transaction_subscribers: Subscribers<TransactionView>,
+    rejected_transaction_subscribers: Subscribers<RejectedTransaction>,
+    network_alert_subscribers: Subscribers<Alert>,
+    watched_transaction_subscribers: Subscribers<WatchedTransaction>,
 }
 
 impl NotifyService {
     pub fn new(config: NotifyConfig) -> Self {
         Self {
+            hooks: Hooks::start(config.hooks.clone()),
+            publisher: Publisher::new(config.publisher.as_ref()),
             config,
-            new_block_subscribers: HashMap::default(),
-            network_alert_subscribers: HashMap::default(),
+            new_block_subscribers: Subscribers::new("new_block"),
+            new_uncle_subscribers: Subscribers::new("new_uncle"),
+            reorg_subscribers: Subscribers::new("reorg"),
+            new_transaction_subscribers: Subscribers::new("new_transaction"),
+            rejected_transaction_subscribers: Subscribers::new("rejected_transaction"),
+            network_alert_subscribers: Subscribers::new("network_alert"),
+            watched_transaction_subscribers: Subscribers::new("watched_transaction"),
         }
     }
 
+    /// Plugs in a transport of the published chain events, such as ZeroMQ, in addition to the
+    /// TCP subscribers of `notifier.publisher`.
+    pub fn add_sink(mut self, sink: Box<dyn NotificationSink>) -> Self {
+        self.publisher.add_sink(sink);
+        self
+    }
+
     // remove `allow` tag when https://github.com/crossbeam-rs/crossbeam/issues/404 is solved
     #[allow(clippy::zero_ptr, clippy::drop_copy)]
     pub fn start<S: ToString>(mut self, thread_name: Option<S>) -> NotifyController {
+        self.publisher.start();
         let (signal_sender, signal_receiver) = bounded::<()>(SIGNAL_CHANNEL_SIZE);
         let (new_block_register, new_block_register_receiver) = bounded(REGISTER_CHANNEL_SIZE);
         let (new_block_sender, new_block_receiver) = bounded::<BlockView>(NOTIFY_CHANNEL_SIZE);
+        let (new_uncle_register, new_uncle_register_receiver) = bounded(REGISTER_CHANNEL_SIZE);
+        let (new_uncle_sender, new_uncle_receiver) = bounded::<UncleBlockView>(NOTIFY_CHANNEL_SIZE);
+        let (reorg_register, reorg_register_receiver) = bounded(REGISTER_CHANNEL_SIZE);
+        let (reorg_sender, reorg_receiver) = bounded::<ReorgEvent>(NOTIFY_CHANNEL_SIZE);
+        let (new_transaction_register, new_transaction_register_receiver) =
+            bounded(REGISTER_CHANNEL_SIZE);
+        let (new_transaction_sender, new_transaction_receiver) =
+            bounded::<TransactionView>(NOTIFY_CHANNEL_SIZE);
+        let (rejected_transaction_register, rejected_transaction_register_receiver) =
+            bounded(REGISTER_CHANNEL_SIZE);
+        let (rejected_transaction_sender, rejected_transaction_receiver) =
+            bounded::<RejectedTransaction>(NOTIFY_CHANNEL_SIZE);
         let (network_alert_register, network_alert_register_receiver) =
             bounded(REGISTER_CHANNEL_SIZE);
         let (network_alert_sender, network_alert_receiver) = bounded::<Alert>(NOTIFY_CHANNEL_SIZE);
+        let (watched_transaction_register, watched_transaction_register_receiver) =
+            bounded(REGISTER_CHANNEL_SIZE);
+        let (watched_transaction_sender, watched_transaction_receiver) =
+            bounded::<WatchedTransaction>(NOTIFY_CHANNEL_SIZE);
 
         let mut thread_builder = thread::Builder::new();
         if let Some(name) = thread_name {
@@ -66,10 +284,26 @@ impl NotifyService {
                     recv(signal_receiver) -> _ => {
                         break;
                     }
-                    recv(new_block_register_receiver) -> msg => self.handle_register_new_block(msg),
+                    recv(new_block_register_receiver) -> msg => self.new_block_subscribers.register(msg),
                     recv(new_block_receiver) -> msg => self.handle_notify_new_block(msg),
-                    recv(network_alert_register_receiver) -> msg => self.handle_register_network_alert(msg),
+                    recv(new_uncle_register_receiver) -> msg => self.new_uncle_subscribers.register(msg),
+                    recv(new_uncle_receiver) -> msg => {
+                        self.new_uncle_subscribers.notify(msg);
+                    }
+                    recv(reorg_register_receiver) -> msg => self.reorg_subscribers.register(msg),
+                    recv(reorg_receiver) -> msg => self.handle_notify_reorg(msg),
+                    recv(new_transaction_register_receiver) -> msg => self.new_transaction_subscribers.register(msg),
+                    recv(new_transaction_receiver) -> msg => {
+                        self.new_transaction_subscribers.notify(msg);
+                    }
+                    recv(rejected_transaction_register_receiver) -> msg => self.rejected_transaction_subscribers.register(msg),
+                    recv(rejected_transaction_receiver) -> msg => {
+                        self.rejected_transaction_subscribers.notify(msg);
+                    }
+                    recv(network_alert_register_receiver) -> msg => self.network_alert_subscribers.register(msg),
                     recv(network_alert_receiver) -> msg => self.handle_notify_network_alert(msg),
+                    recv(watched_transaction_register_receiver) -> msg => self.watched_transaction_subscribers.register(msg),
+                    recv(watched_transaction_receiver) -> msg => self.handle_notify_watched_transaction(msg),
                 }
             })
             .expect("Start notify service failed");
@@ -77,103 +311,101 @@ impl NotifyService {
         NotifyController {
             new_block_register,
             new_block_notifier: new_block_sender,
+            new_uncle_register,
+            new_uncle_notifier: new_uncle_sender,
+            reorg_register,
+            reorg_notifier: reorg_sender,
+            new_transaction_register,
+            new_transaction_notifier: new_transaction_sender,
+            rejected_transaction_register,
+            rejected_transaction_notifier: rejected_transaction_sender,
             network_alert_register,
             network_alert_notifier: network_alert_sender,
+            watched_transaction_register,
+            watched_transaction_notifier: watched_transaction_sender,
             stop: StopHandler::new(SignalSender::Crossbeam(signal_sender), join_handle),
         }
     }
 
-    fn handle_register_new_block(
-        &mut self,
-        msg: Result<Request<String, Receiver<BlockView>>, RecvError>,
-    ) {
-        match msg {
-            Ok(Request {
-                responder,
-                arguments: name,
-            }) => {
-                debug!("Register new_block {:?}", name);
-                let (sender, receiver) = bounded::<BlockView>(NOTIFY_CHANNEL_SIZE);
-                self.new_block_subscribers.insert(name, sender);
-                let _ = responder.send(receiver);
-            }
-            _ => debug!("Register new_block channel is closed"),
-        }
-    }
-
     fn handle_notify_new_block(&mut self, msg: Result<BlockView, RecvError>) {
-        match msg {
-            Ok(block) => {
-                trace!("event new block {:?}", block);
-                // notify all subscribers
-                for subscriber in self.new_block_subscribers.values() {
-                    let _ = subscriber.send(block.clone());
-                }
-                // notify script
-                if let Some(script) = self.config.new_block_notify_script.as_ref() {
-                    let args = [format!("{:#x}", block.hash())];
-                    if let Err(err) = Command::new(script).args(&args).status() {
-                        error!(
-                            "failed to run new_block_notify_script: {} {}, error: {}",
-                            script, args[0], err
-                        );
-                    }
+        if let Some(block) = self.new_block_subscribers.notify(msg) {
+            trace!("event new block {:?}", block);
+            self.hooks.new_block(&block);
+            self.publisher.new_block(&block);
+            // notify script
+            if let Some(script) = self.config.new_block_notify_script.as_ref() {
+                let args = [format!("{:#x}", block.hash())];
+                if let Err(err) = Command::new(script).args(&args).status() {
+                    error!(
+                        "failed to run new_block_notify_script: {} {}, error: {}",
+                        script, args[0], err
+                    );
                 }
             }
-            _ => debug!("new block channel is closed"),
         }
     }
 
-    fn handle_register_network_alert(
-        &mut self,
-        msg: Result<Request<String, Receiver<Alert>>, RecvError>,
-    ) {
-        match msg {
-            Ok(Request {
-                responder,
-                arguments: name,
-            }) => {
-                debug!("Register network_alert {:?}", name);
-                let (sender, receiver) = bounded::<Alert>(NOTIFY_CHANNEL_SIZE);
-                self.network_alert_subscribers.insert(name, sender);
-                let _ = responder.send(receiver);
-            }
-            _ => debug!("Register network_alert channel is closed"),
+    fn handle_notify_reorg(&mut self, msg: Result<ReorgEvent, RecvError>) {
+        if let Some(reorg) = self.reorg_subscribers.notify(msg) {
+            trace!("event reorg {:?}", reorg);
+            self.hooks.reorg(&reorg);
+            self.publisher.reorg(&reorg);
         }
     }
 
     fn handle_notify_network_alert(&mut self, msg: Result<Alert, RecvError>) {
-        match msg {
-            Ok(alert) => {
-                trace!("event network alert {:?}", alert);
-                // notify all subscribers
-                for subscriber in self.network_alert_subscribers.values() {
-                    let _ = subscriber.send(alert.clone());
+        if let Some(alert) = self.network_alert_subscribers.notify(msg) {
+            trace!("event network alert {:?}", alert);
+            self.hooks.network_alert(&alert);
+            // notify script
+            if let Some(script) = self.config.network_alert_notify_script.as_ref() {
+                let args = [alert
+                    .as_reader()
+                    .raw()
+                    .message()
+                    .as_utf8()
+                    .expect("alert message should be utf8")
+                    .to_owned()];
+                if let Err(err) = Command::new(script).args(&args).status() {
+                    error!(
+                        "failed to run network_alert_notify_script: {} {}, error: {}",
+                        script, args[0], err
+                    );
                 }
-                // notify script
-                if let Some(script) = self.config.network_alert_notify_script.as_ref() {
-                    let args = [alert
-                        .as_reader()
-                        .raw()
-                        .message()
-                        .as_utf8()
-                        .expect("alert message should be utf8")
-                        .to_owned()];
-                    if let Err(err) = Command::new(script).args(&args).status() {
-                        error!(
-                            "failed to run network_alert_notify_script: {} {}, error: {}",
-                            script, args[0], err
-                        );
-                    }
+            }
+        }
+    }
+
+    fn handle_notify_watched_transaction(&mut self, msg: Result<WatchedTransaction, RecvError>) {
+        if let Some(watched_transaction) = self.watched_transaction_subscribers.notify(msg) {
+            trace!("event watched transaction {:?}", watched_transaction);
+            // notify webhook
+            if let Some(url) = self.config.watched_transaction_webhook.as_ref() {
+                let tx_hash = watched_transaction.transaction.hash();
+                let body: ckb_jsonrpc_types::WatchedTransaction = watched_transaction.into();
+                let result = reqwest::Client::builder()
+                    .timeout(WEBHOOK_TIMEOUT)
+                    .build()
+                    .and_then(|client| client.post(url).json(&body).send())
+                    .and_then(|response| response.error_for_status());
+                if let Err(err) = result {
+                    error!(
+                        "failed to post watched_transaction_webhook: {} {:#x}, error: {}",
+                        url, tx_hash, err
+                    );
                 }
             }
-            _ => debug!("network alert channel is closed"),
         }
     }
 }
 
 impl NotifyController {
-    pub fn subscribe_new_block<S: ToString>(&self, name: S) -> Receiver<BlockView> {
+    /// Returns the handler which stops the notify service on shutdown.
+    pub fn stop_handler(&self) -> StopHandler<()> {
+        self.stop.clone()
+    }
+
+    pub fn subscribe_new_block<S: ToString>(&self, name: S) -> Subscription<BlockView> {
         Request::call(&self.new_block_register, name.to_string())
             .expect("Subscribe new block should be OK")
     }
@@ -182,7 +414,50 @@ impl NotifyController {
         let _ = self.new_block_notifier.send(block);
     }
 
-    pub fn subscribe_network_alert<S: ToString>(&self, name: S) -> Receiver<Alert> {
+    /// Subscribes the blocks which are not or no longer on the main chain.
+    pub fn subscribe_new_uncle<S: ToString>(&self, name: S) -> Subscription<UncleBlockView> {
+        Request::call(&self.new_uncle_register, name.to_string())
+            .expect("Subscribe new uncle should be OK")
+    }
+
+    pub fn notify_new_uncle(&self, uncle: UncleBlockView) {
+        let _ = self.new_uncle_notifier.send(uncle);
+    }
+
+    /// Subscribes the main chain switches which detach blocks, published before the new tip
+    /// block.
+    pub fn subscribe_reorg<S: ToString>(&self, name: S) -> Subscription<ReorgEvent> {
+        Request::call(&self.reorg_register, name.to_string()).expect("Subscribe reorg should be OK")
+    }
+
+    pub fn notify_reorg(&self, reorg: ReorgEvent) {
+        let _ = self.reorg_notifier.send(reorg);
+    }
+
+    /// Subscribes the transactions accepted into the tx pool.
+    pub fn subscribe_new_transaction<S: ToString>(&self, name: S) -> Subscription<TransactionView> {
+        Request::call(&self.new_transaction_register, name.to_string())
+            .expect("Subscribe new transaction should be OK")
+    }
+
+    pub fn notify_new_transaction(&self, tx: TransactionView) {
+        let _ = self.new_transaction_notifier.send(tx);
+    }
+
+    /// Subscribes the transactions which fail to enter the tx pool.
+    pub fn subscribe_rejected_transaction<S: ToString>(
+        &self,
+        name: S,
+    ) -> Subscription<RejectedTransaction> {
+        Request::call(&self.rejected_transaction_register, name.to_string())
+            .expect("Subscribe rejected transaction should be OK")
+    }
+
+    pub fn notify_rejected_transaction(&self, rejected: RejectedTransaction) {
+        let _ = self.rejected_transaction_notifier.send(rejected);
+    }
+
+    pub fn subscribe_network_alert<S: ToString>(&self, name: S) -> Subscription<Alert> {
         Request::call(&self.network_alert_register, name.to_string())
             .expect("Subscribe network alert should be OK")
     }
@@ -190,4 +465,50 @@ impl NotifyController {
     pub fn notify_network_alert(&self, alert: Alert) {
         let _ = self.network_alert_notifier.send(alert);
     }
+
+    pub fn subscribe_watched_transaction<S: ToString>(
+        &self,
+        name: S,
+    ) -> Subscription<WatchedTransaction> {
+        Request::call(&self.watched_transaction_register, name.to_string())
+            .expect("Subscribe watched transaction should be OK")
+    }
+
+    pub fn notify_watched_transaction(&self, watched_transaction: WatchedTransaction) {
+        let _ = self.watched_transaction_notifier.send(watched_transaction);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ckb_types::core::{BlockBuilder, TransactionBuilder};
+
+    #[test]
+    fn lagged_subscriber_does_not_block_others() {
+        let notify_controller = NotifyService::new(Default::default()).start(Some("test"));
+        let slow = notify_controller.subscribe_new_transaction("slow");
+        let fast = notify_controller.subscribe_new_transaction("fast");
+        let barrier = notify_controller.subscribe_new_uncle("barrier");
+
+        let total = NOTIFY_CHANNEL_SIZE + 10;
+        for _ in 0..total {
+            notify_controller.notify_new_transaction(TransactionBuilder::default().build());
+            fast.recv().expect("fast subscriber receives every event");
+        }
+        // the service handles events one by one, the transactions are done once the uncle arrives
+        notify_controller.notify_new_uncle(BlockBuilder::default().build().as_uncle());
+        barrier
+            .recv()
+            .expect("barrier subscriber receives the uncle");
+
+        assert_eq!(slow.take_lagged(), 10);
+        assert_eq!(slow.take_lagged(), 0);
+        assert_eq!(fast.take_lagged(), 0);
+        let mut received = 0;
+        while slow.try_recv().is_ok() {
+            received += 1;
+        }
+        assert_eq!(received, NOTIFY_CHANNEL_SIZE);
+    }
 }
@@ -0,0 +1,321 @@
+//! Publishes the chain events to the external subscribers.
+//!
+//! The events are published as `<topic> <payload>` lines, where the payload of `new_block` and
+//! `confirmed_transaction` is the hash, and the payload of `reorg` is the event JSON. The
+//! built-in sink serves the lines to the TCP subscribers, other transports such as ZeroMQ plug in
+//! by implementing `NotificationSink`.
+//!
+//! A transaction is confirmed once its block gets `confirmations` confirmations on the main
+//! chain. The recent blocks are held back until then, and a reorg drops the detached ones, so a
+//! transaction of a block detached within the confirmations is never published.
+use crate::{ReorgEvent, NOTIFY_CHANNEL_SIZE};
+use ckb_app_config::{PublisherConfig, PublisherTopic};
+use ckb_logger::{debug, error, info, warn};
+use ckb_types::{
+    core::{BlockNumber, BlockView},
+    packed::Byte32,
+};
+use ckb_util::Mutex;
+use crossbeam_channel::{bounded, Sender, TrySendError};
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// A subscriber which does not read its socket is dropped after it.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub fn topic_name(topic: PublisherTopic) -> &'static str {
+    match topic {
+        PublisherTopic::NewBlock => "new_block",
+        PublisherTopic::Reorg => "reorg",
+        PublisherTopic::ConfirmedTransaction => "confirmed_transaction",
+    }
+}
+
+/// The transport of the published events.
+pub trait NotificationSink: Send {
+    fn publish(&mut self, topic: PublisherTopic, payload: &str);
+}
+
+/// Writes the events as lines to the connected TCP subscribers.
+pub struct TcpSink {
+    streams: Arc<Mutex<Vec<TcpStream>>>,
+}
+
+impl TcpSink {
+    /// Listens on the address and starts the thread which accepts the subscribers.
+    pub fn bind(listen_address: &str) -> io::Result<Self> {
+        let listener = TcpListener::bind(listen_address)?;
+        info!("notify publisher listen on {}", listen_address);
+        let streams: Arc<Mutex<Vec<TcpStream>>> = Default::default();
+        let accepted = Arc::clone(&streams);
+        thread::Builder::new()
+            .name("NotifyPublisherListener".to_string())
+            .spawn(move || {
+                for stream in listener.incoming() {
+                    match stream.and_then(|stream| {
+                        stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+                        Ok(stream)
+                    }) {
+                        Ok(stream) => {
+                            debug!(
+                                "notify publisher subscriber {:?} connected",
+                                stream.peer_addr()
+                            );
+                            accepted.lock().push(stream);
+                        }
+                        Err(err) => warn!("notify publisher accept error: {}", err),
+                    }
+                }
+            })?;
+        Ok(TcpSink { streams })
+    }
+}
+
+impl NotificationSink for TcpSink {
+    fn publish(&mut self, topic: PublisherTopic, payload: &str) {
+        let line = format!("{} {}\n", topic_name(topic), payload);
+        self.streams.lock().retain(|mut stream| {
+            let written = stream.write_all(line.as_bytes()).is_ok();
+            if !written {
+                debug!(
+                    "notify publisher subscriber {:?} is gone",
+                    stream.peer_addr()
+                );
+                let _ = stream.shutdown(std::net::Shutdown::Both);
+            }
+            written
+        });
+    }
+}
+
+/// Turns the chain events into the topics and sends them to the sinks in a thread of its own, so
+/// slow subscribers don't hold the notify service back.
+pub(crate) struct Publisher {
+    topics: Vec<PublisherTopic>,
+    confirmations: BlockNumber,
+    sinks: Vec<Box<dyn NotificationSink>>,
+    sender: Option<Sender<(PublisherTopic, String)>>,
+    /// The main chain blocks waiting for the confirmations, and their transaction hashes
+    unconfirmed: BTreeMap<BlockNumber, Vec<Byte32>>,
+}
+
+impl Publisher {
+    pub(crate) fn new(config: Option<&PublisherConfig>) -> Self {
+        let mut publisher = Publisher {
+            topics: Vec::new(),
+            confirmations: 1,
+            sinks: Vec::new(),
+            sender: None,
+            unconfirmed: BTreeMap::new(),
+        };
+        if let Some(config) = config {
+            publisher.topics = if config.topics.is_empty() {
+                vec![
+                    PublisherTopic::NewBlock,
+                    PublisherTopic::Reorg,
+                    PublisherTopic::ConfirmedTransaction,
+                ]
+            } else {
+                config.topics.clone()
+            };
+            publisher.confirmations = config.confirmations.unwrap_or(1).max(1);
+            if let Some(listen_address) = config.listen_address.as_ref() {
+                match TcpSink::bind(listen_address) {
+                    Ok(sink) => publisher.sinks.push(Box::new(sink)),
+                    Err(err) => error!(
+                        "failed to start notify publisher on {}, error: {}",
+                        listen_address, err
+                    ),
+                }
+            }
+        }
+        publisher
+    }
+
+    pub(crate) fn add_sink(&mut self, sink: Box<dyn NotificationSink>) {
+        self.sinks.push(sink);
+    }
+
+    pub(crate) fn start(&mut self) {
+        if self.sinks.is_empty() || self.topics.is_empty() {
+            return;
+        }
+        let mut sinks = std::mem::replace(&mut self.sinks, Vec::new());
+        let (sender, receiver) = bounded::<(PublisherTopic, String)>(NOTIFY_CHANNEL_SIZE);
+        thread::Builder::new()
+            .name("NotifyPublisher".to_string())
+            .spawn(move || {
+                // exits when the notify service is stopped
+                while let Ok((topic, payload)) = receiver.recv() {
+                    for sink in sinks.iter_mut() {
+                        sink.publish(topic, &payload);
+                    }
+                }
+            })
+            .expect("Start notify publisher failed");
+        self.sender = Some(sender);
+    }
+
+    pub(crate) fn new_block(&mut self, block: &BlockView) {
+        if self.sender.is_none() {
+            return;
+        }
+        let header = block.header();
+        self.publish(PublisherTopic::NewBlock, || format!("{:#x}", header.hash()));
+        self.track(header.number(), block.tx_hashes().to_vec());
+    }
+
+    pub(crate) fn reorg(&mut self, reorg: &ReorgEvent) {
+        if self.sender.is_none() {
+            return;
+        }
+        self.publish(PublisherTopic::Reorg, || {
+            let json: ckb_jsonrpc_types::ReorgEvent = reorg.clone().into();
+            serde_json::to_string(&json).expect("serialization should be ok")
+        });
+        self.unconfirmed
+            .split_off(&(reorg.common_ancestor.number() + 1));
+        // the tip block is tracked by the following new block event
+        let attached = reorg.attached_blocks.len().saturating_sub(1);
+        for (header, tx_hashes) in reorg
+            .attached_blocks
+            .iter()
+            .zip(reorg.attached_tx_hashes.iter())
+            .take(attached)
+        {
+            self.track(header.number(), tx_hashes.clone());
+        }
+    }
+
+    // Holds the block back and publishes the transactions of the blocks which get enough
+    // confirmations.
+    fn track(&mut self, number: BlockNumber, tx_hashes: Vec<Byte32>) {
+        if !self.listens(PublisherTopic::ConfirmedTransaction) {
+            return;
+        }
+        self.unconfirmed.split_off(&number);
+        self.unconfirmed.insert(number, tx_hashes);
+        let confirmed_number = match (number + 1).checked_sub(self.confirmations) {
+            Some(confirmed_number) => confirmed_number,
+            None => return,
+        };
+        let unconfirmed = self.unconfirmed.split_off(&(confirmed_number + 1));
+        let confirmed = std::mem::replace(&mut self.unconfirmed, unconfirmed);
+        for tx_hash in confirmed.values().flatten() {
+            self.publish(PublisherTopic::ConfirmedTransaction, || {
+                format!("{:#x}", tx_hash)
+            });
+        }
+    }
+
+    fn listens(&self, topic: PublisherTopic) -> bool {
+        self.topics.contains(&topic)
+    }
+
+    fn publish<F: FnOnce() -> String>(&self, topic: PublisherTopic, payload: F) {
+        if !self.listens(topic) {
+            return;
+        }
+        if let Some(sender) = self.sender.as_ref() {
+            if let Err(TrySendError::Full(_)) = sender.try_send((topic, payload())) {
+                warn!(
+                    "notify publisher lags behind, drop the {} event",
+                    topic_name(topic)
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ckb_types::{
+        core::{BlockBuilder, TransactionBuilder},
+        packed,
+        prelude::*,
+    };
+    use crossbeam_channel::{unbounded, Receiver};
+
+    struct ChannelSink(Sender<String>);
+
+    impl NotificationSink for ChannelSink {
+        fn publish(&mut self, topic: PublisherTopic, payload: &str) {
+            let _ = self.0.send(format!("{} {}", topic_name(topic), payload));
+        }
+    }
+
+    fn start_publisher(confirmations: u64) -> (Publisher, Receiver<String>) {
+        let mut publisher = Publisher::new(None);
+        publisher.topics = vec![PublisherTopic::ConfirmedTransaction];
+        publisher.confirmations = confirmations;
+        let (sender, receiver) = unbounded();
+        publisher.add_sink(Box::new(ChannelSink(sender)));
+        publisher.start();
+        (publisher, receiver)
+    }
+
+    fn block(number: BlockNumber, seed: u64) -> BlockView {
+        let tx = TransactionBuilder::default()
+            .output(
+                packed::CellOutput::new_builder()
+                    .capacity(seed.pack())
+                    .build(),
+            )
+            .output_data(Default::default())
+            .build();
+        BlockBuilder::default()
+            .number(number.pack())
+            .transaction(tx)
+            .build()
+    }
+
+    fn published(publisher: Publisher, receiver: Receiver<String>) -> Vec<String> {
+        // the thread exits once the sender is dropped
+        drop(publisher);
+        receiver.iter().collect()
+    }
+
+    #[test]
+    fn publish_transactions_after_confirmations() {
+        let (mut publisher, receiver) = start_publisher(2);
+        let blocks: Vec<_> = (1..=3).map(|number| block(number, number)).collect();
+        for block in &blocks {
+            publisher.new_block(block);
+        }
+        let expected: Vec<_> = blocks[..2]
+            .iter()
+            .map(|block| format!("confirmed_transaction {:#x}", block.tx_hashes()[0]))
+            .collect();
+        assert_eq!(published(publisher, receiver), expected);
+    }
+
+    #[test]
+    fn drop_detached_transactions() {
+        let (mut publisher, receiver) = start_publisher(2);
+        let main = block(1, 1);
+        let detached = block(2, 2);
+        let attached = vec![block(2, 20), block(3, 30)];
+        publisher.new_block(&main);
+        publisher.new_block(&detached);
+        publisher.reorg(&ReorgEvent {
+            common_ancestor: main.header(),
+            detached_blocks: vec![detached.header()],
+            attached_blocks: attached.iter().map(BlockView::header).collect(),
+            attached_tx_hashes: attached
+                .iter()
+                .map(|block| block.tx_hashes().to_vec())
+                .collect(),
+        });
+        publisher.new_block(&attached[1]);
+        let expected: Vec<_> = vec![&main, &attached[0]]
+            .into_iter()
+            .map(|block| format!("confirmed_transaction {:#x}", block.tx_hashes()[0]))
+            .collect();
+        assert_eq!(published(publisher, receiver), expected);
+    }
+}
@@ -1,26 +1,20 @@
 use super::PowEngine;
-use ckb_types::{packed::Header, prelude::*, utilities::compact_to_target, U256};
+use ckb_types::{packed::Header, prelude::*, U256};
 use eaglesong::eaglesong;
 
 pub struct EaglesongPowEngine;
 
 impl PowEngine for EaglesongPowEngine {
     fn verify(&self, header: &Header) -> bool {
+        let output = self.pow_output(header).expect("eaglesong output");
+        crate::meet_target(header, &output)
+    }
+
+    fn pow_output(&self, header: &Header) -> Option<U256> {
         let input =
             crate::pow_message(&header.as_reader().calc_pow_hash(), header.nonce().unpack());
         let mut output = [0u8; 32];
         eaglesong(&input, &mut output);
-
-        let (block_target, overflow) = compact_to_target(header.raw().compact_target().unpack());
-
-        if block_target.is_zero() || overflow {
-            return false;
-        }
-
-        if U256::from_big_endian(&output[..]).expect("bound checked") > block_target {
-            return false;
-        }
-
-        true
+        Some(U256::from_big_endian(&output[..]).expect("bound checked"))
     }
 }
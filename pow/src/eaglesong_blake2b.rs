@@ -1,12 +1,17 @@
 use super::PowEngine;
 use ckb_hash::blake2b_256;
-use ckb_types::{packed::Header, prelude::*, utilities::compact_to_target, U256};
+use ckb_types::{packed::Header, prelude::*, U256};
 use eaglesong::eaglesong;
 
 pub struct EaglesongBlake2bPowEngine;
 
 impl PowEngine for EaglesongBlake2bPowEngine {
     fn verify(&self, header: &Header) -> bool {
+        let output = self.pow_output(header).expect("eaglesong blake2b output");
+        crate::meet_target(header, &output)
+    }
+
+    fn pow_output(&self, header: &Header) -> Option<U256> {
         let input =
             crate::pow_message(&header.as_reader().calc_pow_hash(), header.nonce().unpack());
         let output = {
@@ -14,17 +19,6 @@ impl PowEngine for EaglesongBlake2bPowEngine {
             eaglesong(&input, &mut output_tmp);
             blake2b_256(&output_tmp)
         };
-
-        let (block_target, overflow) = compact_to_target(header.raw().compact_target().unpack());
-
-        if block_target.is_zero() || overflow {
-            return false;
-        }
-
-        if U256::from_big_endian(&output[..]).expect("bound checked") > block_target {
-            return false;
-        }
-
-        true
+        Some(U256::from_big_endian(&output[..]).expect("bound checked"))
     }
 }
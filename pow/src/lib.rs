@@ -2,6 +2,8 @@ use byteorder::{ByteOrder, LittleEndian};
 use ckb_types::{
     packed::{Byte32, Header},
     prelude::*,
+    utilities::compact_to_target,
+    U256,
 };
 use serde::{Deserialize, Serialize};
 use std::any::Any;
@@ -57,6 +59,18 @@ pub fn pow_message(pow_hash: &Byte32, nonce: u128) -> [u8; 48] {
 
 pub trait PowEngine: Send + Sync + AsAny {
     fn verify(&self, header: &Header) -> bool;
+
+    /// Returns the output of the pow function which is compared against the target, `None` if
+    /// the engine does not compute one.
+    fn pow_output(&self, _header: &Header) -> Option<U256> {
+        None
+    }
+}
+
+/// Returns whether the pow output meets the compact target of the header.
+pub(crate) fn meet_target(header: &Header, output: &U256) -> bool {
+    let (block_target, overflow) = compact_to_target(header.raw().compact_target().unpack());
+    !block_target.is_zero() && !overflow && *output <= block_target
 }
 
 pub trait AsAny {
@@ -4,7 +4,8 @@
 mod template;
 
 pub use self::template::{
-    TemplateContext, AVAILABLE_SPECS, DEFAULT_P2P_PORT, DEFAULT_RPC_PORT, DEFAULT_SPEC,
+    profile_default_spec, TemplateContext, AVAILABLE_PROFILES, AVAILABLE_SPECS, DEFAULT_P2P_PORT,
+    DEFAULT_RPC_PORT, DEFAULT_SPEC,
 };
 pub use std::io::{Error, Result};
 
@@ -223,6 +224,7 @@ mod tests {
         let root_dir = mkdir();
         let context = TemplateContext {
             spec: "dev",
+            profile: None,
             rpc_port: "7000",
             p2p_port: "8000",
             log_to_file: true,
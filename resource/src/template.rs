@@ -1,11 +1,23 @@
 pub const DEFAULT_SPEC: &str = "mainnet";
 pub const AVAILABLE_SPECS: &[&str] = &["mainnet", "testnet", "staging", "dev"];
+/// Profiles of `ckb init`, which tune the config files for a kind of deployment.
+pub const AVAILABLE_PROFILES: &[&str] = &["mainnet", "testnet", "dev-fast", "mining", "rpc-public"];
 pub const DEFAULT_RPC_PORT: &str = "8114";
 pub const DEFAULT_P2P_PORT: &str = "8115";
 
+/// The chain a profile initializes when `--chain` is not given.
+pub fn profile_default_spec(profile: &str) -> &'static str {
+    match profile {
+        "testnet" => "testnet",
+        "dev-fast" => "dev",
+        _ => DEFAULT_SPEC,
+    }
+}
+
 const START_MARKER: &str = " # {{";
 const END_MAKER: &str = "# }}";
 const WILDCARD_BRANCH: &str = "# _ => ";
+const PROFILE_BRANCH_PREFIX: &str = "# profile:";
 
 use std::io;
 
@@ -13,6 +25,9 @@ pub struct Template<T>(T);
 
 pub struct TemplateContext<'a> {
     pub spec: &'a str,
+    /// Selects the `# profile:<name> => ` branches, which are put before the others to take
+    /// precedence over the spec branches.
+    pub profile: Option<&'a str>,
     pub spec_source: &'a str,
     pub rpc_port: &'a str,
     pub p2p_port: &'a str,
@@ -60,6 +75,9 @@ where
         context: &TemplateContext<'c>,
     ) -> io::Result<()> {
         let spec_branch = format!("# {} => ", context.spec);
+        let profile_branch = context
+            .profile
+            .map(|profile| format!("{}{} => ", PROFILE_BRANCH_PREFIX, profile));
 
         let mut state = TemplateState::SearchStartMarker;
         for line in self.0.as_ref().lines() {
@@ -80,6 +98,12 @@ where
                             &start_line[..(start_line.len() - START_MARKER.len())],
                         )?;
                         state = TemplateState::SearchStartMarker;
+                    } else if let Some(branch) = profile_branch
+                        .as_ref()
+                        .filter(|branch| line.starts_with(branch.as_str()))
+                    {
+                        writeln(w, &line[branch.len()..], context)?;
+                        state = TemplateState::SearchEndMarker;
                     } else if line.starts_with(&spec_branch) {
                         writeln(w, &line[spec_branch.len()..], context)?;
                         state = TemplateState::SearchEndMarker;
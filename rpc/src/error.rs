@@ -1,8 +1,18 @@
+use ckb_error::Error as CKBError;
+use ckb_script::{ScriptGroupType, TransactionScriptError};
+use ckb_types::{packed, prelude::*, H256};
 use jsonrpc_core::{Error, ErrorCode};
+use serde_json::json;
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum RPCError {
     Invalid = -3,
+    /// The requested data is deleted by a pruned node
+    Pruned = -4,
+    /// The transaction violates the local tx pool policy, e.g. the min fee rate
+    PolicyRejected = -5,
+    /// The tx pool queue stays full for `queue_timeout_millis`, the request may be retried later
+    QueueFull = -6,
 }
 
 impl RPCError {
@@ -13,4 +23,32 @@ impl RPCError {
             data: None,
         }
     }
+
+    pub fn pruned_block(block_hash: &packed::Byte32) -> Error {
+        Self::custom(
+            RPCError::Pruned,
+            format!("the body of block {:#x} is pruned", block_hash),
+        )
+    }
+
+    // Same as custom, and fills the error data with the failed script group
+    // details when the error is raised by a transaction script.
+    pub fn custom_with_error(err: RPCError, message: String, error: &CKBError) -> Error {
+        let mut rpc_error = Self::custom(err, message);
+        if let Some(script_error) = error.downcast_ref::<TransactionScriptError>() {
+            let group_type = match script_error.group_type {
+                ScriptGroupType::Lock => "lock",
+                ScriptGroupType::Type => "type",
+            };
+            let script_hash: H256 = script_error.script_hash.unpack();
+            rpc_error.data = Some(json!({
+                "script_group_type": group_type,
+                "script_group_index": script_error.index,
+                "script_hash": script_hash,
+                "exit_code": script_error.exit_code(),
+                "cycles": script_error.cycles,
+            }));
+        }
+        rpc_error
+    }
 }
@@ -1,4 +1,5 @@
 pub(crate) mod error;
+pub(crate) mod middleware;
 pub(crate) mod module;
 pub(crate) mod server;
 pub(crate) mod service_builder;
@@ -9,4 +10,7 @@ mod test;
 pub use crate::server::RpcServer;
 pub use crate::service_builder::ServiceBuilder;
 
-pub type IoHandler = jsonrpc_pubsub::PubSubHandler<Option<crate::module::SubscriptionSession>>;
+pub type IoHandler = jsonrpc_pubsub::PubSubHandler<
+    Option<crate::module::SubscriptionSession>,
+    crate::middleware::MetricsMiddleware,
+>;
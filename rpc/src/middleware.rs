@@ -0,0 +1,63 @@
+use ckb_metrics::{
+    register_counter_vec, register_histogram_vec, Counter, Histogram, MetricVec, Subsystem,
+};
+use jsonrpc_core::futures::{future::Either, Future};
+use jsonrpc_core::middleware::{Middleware, NoopFuture};
+use jsonrpc_core::{Call, ErrorCode, Metadata, Output};
+use lazy_static::lazy_static;
+use std::time::Instant;
+
+lazy_static! {
+    static ref CALLS: MetricVec<Counter> = register_counter_vec(
+        Subsystem::Rpc,
+        "ckb_rpc_calls_total",
+        "RPC calls by method and result",
+        &["method", "result"],
+    );
+    static ref CALL_DURATION: MetricVec<Histogram> = register_histogram_vec(
+        Subsystem::Rpc,
+        "ckb_rpc_call_duration_seconds",
+        "Time to handle an RPC call by method",
+        &["method"],
+    );
+}
+
+/// Records the count and duration of the RPC calls.
+#[derive(Clone, Debug, Default)]
+pub struct MetricsMiddleware;
+
+impl<M: Metadata> Middleware<M> for MetricsMiddleware {
+    type Future = NoopFuture;
+    type CallFuture = Box<dyn Future<Item = Option<Output>, Error = ()> + Send>;
+
+    fn on_call<F, X>(&self, call: Call, meta: M, next: F) -> Either<Self::CallFuture, X>
+    where
+        F: Fn(Call, M) -> X + Send + Sync,
+        X: Future<Item = Option<Output>, Error = ()> + Send + 'static,
+    {
+        let method = match &call {
+            Call::MethodCall(method_call) => method_call.method.clone(),
+            Call::Notification(notification) => notification.method.clone(),
+            Call::Invalid { .. } => return Either::B(next(call, meta)),
+        };
+        let started_at = Instant::now();
+        Either::A(Box::new(next(call, meta).map(move |output| {
+            let result = match &output {
+                Some(Output::Failure(failure)) => {
+                    // don't create series for the arbitrary method names of the requests
+                    if failure.error.code == ErrorCode::MethodNotFound {
+                        CALLS.with_label_values(&["unknown", "error"]).inc();
+                        return output;
+                    }
+                    "error"
+                }
+                _ => "ok",
+            };
+            CALL_DURATION
+                .with_label_values(&[&method])
+                .observe_duration(started_at.elapsed());
+            CALLS.with_label_values(&[&method, result]).inc();
+            output
+        })))
+    }
+}
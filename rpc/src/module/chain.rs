@@ -1,7 +1,8 @@
 use crate::error::RPCError;
 use ckb_jsonrpc_types::{
     BlockEconomicState, BlockNumber, BlockReward, BlockView, CellOutputWithOutPoint,
-    CellWithStatus, EpochNumber, EpochView, HeaderView, OutPoint, TransactionWithStatus,
+    CellWithStatus, EpochNumber, EpochView, HeaderView, JsonBytes, OutPoint, TransactionProof,
+    TransactionWithStatus,
 };
 use ckb_logger::{error, warn};
 use ckb_reward_calculator::RewardCalculator;
@@ -11,6 +12,7 @@ use ckb_types::{
     core::{self, cell::CellProvider},
     packed,
     prelude::*,
+    utilities::{merkle_root, MerkleProof},
     H256,
 };
 use jsonrpc_core::{Error, Result};
@@ -66,6 +68,22 @@ pub trait ChainRpc {
 
     #[rpc(name = "get_block_economic_state")]
     fn get_block_economic_state(&self, _hash: H256) -> Result<Option<BlockEconomicState>>;
+
+    #[rpc(name = "get_block_filter")]
+    fn get_block_filter(&self, _hash: H256) -> Result<Option<JsonBytes>>;
+
+    #[rpc(name = "get_fork_block")]
+    fn get_fork_block(&self, _hash: H256) -> Result<Option<BlockView>>;
+
+    #[rpc(name = "get_transaction_proof")]
+    fn get_transaction_proof(
+        &self,
+        _tx_hashes: Vec<H256>,
+        _block_hash: Option<H256>,
+    ) -> Result<TransactionProof>;
+
+    #[rpc(name = "verify_transaction_proof")]
+    fn verify_transaction_proof(&self, _tx_proof: TransactionProof) -> Result<Vec<H256>>;
 }
 
 pub(crate) struct ChainRpcImpl {
@@ -78,12 +96,20 @@ impl ChainRpc for ChainRpcImpl {
         if !snapshot.is_main_chain(&hash.pack()) {
             return Ok(None);
         }
+        if snapshot.is_pruned(&hash.pack()) {
+            return Err(RPCError::pruned_block(&hash.pack()));
+        }
 
         Ok(snapshot.get_block(&hash.pack()).map(Into::into))
     }
 
     fn get_block_by_number(&self, number: BlockNumber) -> Result<Option<BlockView>> {
         let snapshot = self.shared.snapshot();
+        if let Some(hash) = snapshot.get_block_hash(number.into()) {
+            if snapshot.is_pruned(&hash) {
+                return Err(RPCError::pruned_block(&hash));
+            }
+        }
         let ret = snapshot
             .get_block_hash(number.into())
             .and_then(|hash| snapshot.get_block(&hash));
@@ -154,14 +180,21 @@ impl ChainRpc for ChainRpcImpl {
             })
         };
 
-        Ok(tx.or_else(|| {
-            self.shared
-                .snapshot()
-                .get_transaction(&hash)
-                .map(|(tx, block_hash)| {
-                    TransactionWithStatus::with_committed(tx, block_hash.unpack())
-                })
-        }))
+        if tx.is_some() {
+            return Ok(tx);
+        }
+        let snapshot = self.shared.snapshot();
+        match snapshot.get_transaction(&hash) {
+            Some((tx, block_hash)) => Ok(Some(TransactionWithStatus::with_committed(
+                tx,
+                block_hash.unpack(),
+            ))),
+            None => match snapshot.get_transaction_info(&hash) {
+                // committed, but the body of the block is pruned
+                Some(info) => Err(RPCError::pruned_block(&info.block_hash)),
+                None => Ok(None),
+            },
+        }
     }
 
     fn get_block_hash(&self, number: BlockNumber) -> Result<Option<H256>> {
@@ -222,6 +255,9 @@ impl ChainRpc for ChainRpcImpl {
             }
 
             let block_hash = block_hash.unwrap();
+            if snapshot.is_pruned(&block_hash) {
+                return Err(RPCError::pruned_block(&block_hash));
+            }
             let block = snapshot
                 .get_block(&block_hash)
                 .ok_or_else(Error::internal_error)?;
@@ -356,4 +392,116 @@ impl ChainRpc for ChainRpcImpl {
                 .map(Into::into)
         }))
     }
+
+    fn get_block_filter(&self, hash: H256) -> Result<Option<JsonBytes>> {
+        let snapshot = self.shared.snapshot();
+        if !snapshot.is_main_chain(&hash.pack()) {
+            return Ok(None);
+        }
+
+        Ok(snapshot.get_block_filter(&hash.pack()).map(Into::into))
+    }
+
+    fn get_fork_block(&self, hash: H256) -> Result<Option<BlockView>> {
+        let snapshot = self.shared.snapshot();
+        if snapshot.is_main_chain(&hash.pack()) {
+            return Ok(None);
+        }
+
+        Ok(snapshot.get_block(&hash.pack()).map(Into::into))
+    }
+
+    fn get_transaction_proof(
+        &self,
+        tx_hashes: Vec<H256>,
+        block_hash: Option<H256>,
+    ) -> Result<TransactionProof> {
+        if tx_hashes.is_empty() {
+            return Err(Error::invalid_params("tx_hashes is empty"));
+        }
+        let snapshot = self.shared.snapshot();
+        let tx_hashes: Vec<packed::Byte32> = tx_hashes.iter().map(Pack::pack).collect();
+        // The block is located by the committed transactions in the main chain if not specified
+        let block_hash = match block_hash {
+            Some(block_hash) => block_hash.pack(),
+            None => {
+                let mut block_hashes = tx_hashes.iter().map(|tx_hash| {
+                    snapshot
+                        .get_transaction_info(tx_hash)
+                        .map(|info| info.block_hash)
+                        .ok_or_else(|| {
+                            Error::invalid_params(format!(
+                                "transaction {:#x} is not committed in the main chain",
+                                tx_hash
+                            ))
+                        })
+                });
+                let block_hash = block_hashes.next().expect("tx_hashes is not empty")?;
+                for other in block_hashes {
+                    if other? != block_hash {
+                        return Err(Error::invalid_params(
+                            "the transactions are not in the same block",
+                        ));
+                    }
+                }
+                block_hash
+            }
+        };
+        if snapshot.is_pruned(&block_hash) {
+            return Err(RPCError::pruned_block(&block_hash));
+        }
+
+        let block = snapshot.get_block(&block_hash).ok_or_else(|| {
+            Error::invalid_params(format!("block {:#x} is not found", block_hash))
+        })?;
+        let proof = snapshot
+            .get_transactions_proof(&block_hash, &tx_hashes)
+            .ok_or_else(|| {
+                Error::invalid_params(format!(
+                    "not all the transactions are in block {:#x}",
+                    block_hash
+                ))
+            })?;
+        Ok(TransactionProof {
+            block_hash: block_hash.unpack(),
+            witnesses_root: merkle_root(block.tx_witness_hashes()).unpack(),
+            proof: proof.into(),
+        })
+    }
+
+    fn verify_transaction_proof(&self, tx_proof: TransactionProof) -> Result<Vec<H256>> {
+        let snapshot = self.shared.snapshot();
+        let block_hash = tx_proof.block_hash.pack();
+        if snapshot.is_pruned(&block_hash) {
+            return Err(RPCError::pruned_block(&block_hash));
+        }
+        let header = snapshot.get_block_header(&block_hash).ok_or_else(|| {
+            Error::invalid_params(format!("block {:#x} is not found", block_hash))
+        })?;
+
+        // The proof indices are of the tree nodes, the leaf `i` of `n` is `n - 1 + i`
+        let block_tx_hashes = snapshot.get_block_txs_hashes(&block_hash);
+        let first_leaf_index = (block_tx_hashes.len() as u32).saturating_sub(1);
+        let proof: MerkleProof = tx_proof.proof.into();
+        let tx_hashes = proof
+            .indices()
+            .iter()
+            .map(|index| {
+                index
+                    .checked_sub(first_leaf_index)
+                    .and_then(|i| block_tx_hashes.get(i as usize))
+                    .cloned()
+            })
+            .collect::<Option<Vec<_>>>();
+        let transactions_root = tx_hashes
+            .as_ref()
+            .and_then(|tx_hashes| proof.root(tx_hashes))
+            .map(|raw_root| merkle_root(&[raw_root, tx_proof.witnesses_root.pack()]));
+        match (tx_hashes, transactions_root) {
+            (Some(tx_hashes), Some(root)) if root == header.transactions_root() => {
+                Ok(tx_hashes.iter().map(Unpack::unpack).collect())
+            }
+            _ => Err(Error::invalid_params("invalid transaction proof")),
+        }
+    }
 }
@@ -1,4 +1,11 @@
-use ckb_logger::configure_logger_filter;
+use crate::error::RPCError;
+use ckb_chain::chain::ChainController;
+use ckb_jsonrpc_types::MainLoggerConfig;
+use ckb_logger::{
+    configure_logger_filter, logger_filter, update_logger_filter, update_main_logger,
+};
+use ckb_shared::shared::Shared;
+use ckb_types::{prelude::*, H256};
 use jsonrpc_core::Result;
 use jsonrpc_derive::rpc;
 use std::time;
@@ -9,9 +16,33 @@ pub trait DebugRpc {
     fn jemalloc_profiling_dump(&self) -> Result<()>;
     #[rpc(name = "set_logger_filter")]
     fn set_logger_filter(&self, filter: String) -> Result<()>;
+    // Change the levels of the modules in the directives only, e.g. `ckb-sync=debug`
+    #[rpc(name = "update_logger_filter")]
+    fn update_logger_filter(&self, directives: String) -> Result<()>;
+    #[rpc(name = "get_logger_filter")]
+    fn get_logger_filter(&self) -> Result<String>;
+    // Change the filter and the outputs of the main logger, the absent options are left unchanged
+    #[rpc(name = "update_main_logger")]
+    fn update_main_logger(&self, config: MainLoggerConfig) -> Result<()>;
+    // Log every syscall with arguments while verifying scripts of the transaction
+    #[rpc(name = "trace_transaction_syscalls")]
+    fn trace_transaction_syscalls(&self, tx_hash: H256) -> Result<()>;
+    #[rpc(name = "untrace_transaction_syscalls")]
+    fn untrace_transaction_syscalls(&self, tx_hash: H256) -> Result<bool>;
+    // Mark the block invalid, the main chain is rewound to its parent and its descendants are
+    // refused until it is reconsidered
+    // curl -d '{"id": 2, "jsonrpc": "2.0", "method":"invalidate_block","params": ["0xa5f5c85987a15de25661e5a214f2c1449cd803f071acc7999820f25246471f40"]}' -H 'content-type:application/json' 'http://localhost:8114'
+    #[rpc(name = "invalidate_block")]
+    fn invalidate_block(&self, block_hash: H256) -> Result<()>;
+    // Undo `invalidate_block`, the main chain switches back to the block if it is better
+    #[rpc(name = "reconsider_block")]
+    fn reconsider_block(&self, block_hash: H256) -> Result<()>;
 }
 
-pub(crate) struct DebugRpcImpl {}
+pub(crate) struct DebugRpcImpl {
+    pub chain: ChainController,
+    pub shared: Shared,
+}
 
 impl DebugRpc for DebugRpcImpl {
     fn jemalloc_profiling_dump(&self) -> Result<()> {
@@ -28,4 +59,48 @@ impl DebugRpc for DebugRpcImpl {
         configure_logger_filter(&filter);
         Ok(())
     }
+
+    fn update_logger_filter(&self, directives: String) -> Result<()> {
+        update_logger_filter(&directives);
+        Ok(())
+    }
+
+    fn get_logger_filter(&self) -> Result<String> {
+        Ok(logger_filter())
+    }
+
+    fn update_main_logger(&self, config: MainLoggerConfig) -> Result<()> {
+        let MainLoggerConfig {
+            filter,
+            to_stdout,
+            to_file,
+            color,
+        } = config;
+        if let Some(filter) = filter {
+            configure_logger_filter(&filter);
+        }
+        update_main_logger(to_stdout, to_file, color);
+        Ok(())
+    }
+
+    fn trace_transaction_syscalls(&self, tx_hash: H256) -> Result<()> {
+        self.shared.traced_transactions().trace(tx_hash.pack());
+        Ok(())
+    }
+
+    fn untrace_transaction_syscalls(&self, tx_hash: H256) -> Result<bool> {
+        Ok(self.shared.traced_transactions().untrace(&tx_hash.pack()))
+    }
+
+    fn invalidate_block(&self, block_hash: H256) -> Result<()> {
+        self.chain
+            .invalidate_block(block_hash.pack())
+            .map_err(|err| RPCError::custom(RPCError::Invalid, err.to_string()))
+    }
+
+    fn reconsider_block(&self, block_hash: H256) -> Result<()> {
+        self.chain
+            .reconsider_block(block_hash.pack())
+            .map_err(|err| RPCError::custom(RPCError::Invalid, err.to_string()))
+    }
 }
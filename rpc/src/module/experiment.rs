@@ -1,14 +1,20 @@
 use crate::error::RPCError;
 use ckb_dao::DaoCalculator;
-use ckb_fee_estimator::MAX_CONFIRM_BLOCKS;
+use ckb_fee_estimator::{EstimateMode as CoreEstimateMode, MAX_CONFIRM_BLOCKS};
 use ckb_jsonrpc_types::{
-    Capacity, DryRunResult, EstimateResult, OutPoint, Script, Transaction, Uint64,
+    Capacity, DryRunResult, EstimateMode, EstimateResult, MockCell, OutPoint, Script,
+    ScriptGroupProfile, ScriptGroupType, Transaction, Uint64,
 };
 use ckb_logger::error;
+use ckb_script::{
+    ScriptGroupProfile as CoreScriptGroupProfile, ScriptGroupType as CoreScriptGroupType,
+};
 use ckb_shared::{shared::Shared, Snapshot};
 use ckb_store::ChainStore;
 use ckb_types::{
-    core::cell::{resolve_transaction, CellProvider, CellStatus, HeaderChecker},
+    core::cell::{
+        resolve_transaction, CellMeta, CellMetaBuilder, CellProvider, CellStatus, HeaderChecker,
+    },
     packed,
     prelude::*,
     H256,
@@ -16,7 +22,7 @@ use ckb_types::{
 use ckb_verification::ScriptVerifier;
 use jsonrpc_core::{Error, Result};
 use jsonrpc_derive::rpc;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 #[rpc(server)]
 pub trait ExperimentRpc {
@@ -26,8 +32,15 @@ pub trait ExperimentRpc {
     #[rpc(name = "_compute_script_hash")]
     fn compute_script_hash(&self, script: Script) -> Result<H256>;
 
+    // Pass `profile` to also get cycles and wall time of each script group,
+    // `mock_cells` are used in place of the chain state when resolving the tx
     #[rpc(name = "dry_run_transaction")]
-    fn dry_run_transaction(&self, _tx: Transaction) -> Result<DryRunResult>;
+    fn dry_run_transaction(
+        &self,
+        _tx: Transaction,
+        _profile: Option<bool>,
+        _mock_cells: Option<Vec<MockCell>>,
+    ) -> Result<DryRunResult>;
 
     // Calculate the maximum withdraw one can get, given a referenced DAO cell,
     // and a withdraw block hash
@@ -37,7 +50,11 @@ pub trait ExperimentRpc {
 
     // Estimate fee
     #[rpc(name = "estimate_fee_rate")]
-    fn estimate_fee_rate(&self, expect_confirm_blocks: Uint64) -> Result<EstimateResult>;
+    fn estimate_fee_rate(
+        &self,
+        expect_confirm_blocks: Uint64,
+        mode: Option<EstimateMode>,
+    ) -> Result<EstimateResult>;
 }
 
 pub(crate) struct ExperimentRpcImpl {
@@ -55,9 +72,19 @@ impl ExperimentRpc for ExperimentRpcImpl {
         Ok(script.calc_script_hash().unpack())
     }
 
-    fn dry_run_transaction(&self, tx: Transaction) -> Result<DryRunResult> {
+    fn dry_run_transaction(
+        &self,
+        tx: Transaction,
+        profile: Option<bool>,
+        mock_cells: Option<Vec<MockCell>>,
+    ) -> Result<DryRunResult> {
         let tx: packed::Transaction = tx.into();
-        DryRunner::new(&self.shared).run(tx)
+        let dry_runner = DryRunner::new(&self.shared).mock_cells(mock_cells.unwrap_or_default());
+        if profile.unwrap_or(false) {
+            dry_runner.profile(tx)
+        } else {
+            dry_runner.run(tx)
+        }
     }
 
     fn calculate_dao_maximum_withdraw(&self, out_point: OutPoint, hash: H256) -> Result<Capacity> {
@@ -70,7 +97,11 @@ impl ExperimentRpc for ExperimentRpcImpl {
         }
     }
 
-    fn estimate_fee_rate(&self, expect_confirm_blocks: Uint64) -> Result<EstimateResult> {
+    fn estimate_fee_rate(
+        &self,
+        expect_confirm_blocks: Uint64,
+        mode: Option<EstimateMode>,
+    ) -> Result<EstimateResult> {
         let expect_confirm_blocks = expect_confirm_blocks.value() as usize;
         // A tx need 1 block to propose, then 2 block to get confirmed
         // so at least confirm blocks is 3 blocks.
@@ -85,7 +116,11 @@ impl ExperimentRpc for ExperimentRpcImpl {
         }
 
         let tx_pool = self.shared.tx_pool_controller();
-        let fee_rate = tx_pool.estimate_fee_rate(expect_confirm_blocks);
+        let mode = match mode {
+            Some(EstimateMode::Mempool) => CoreEstimateMode::Mempool,
+            Some(EstimateMode::Historical) | None => CoreEstimateMode::Historical,
+        };
+        let fee_rate = tx_pool.estimate_fee_rate(expect_confirm_blocks, mode);
         if let Err(e) = fee_rate {
             error!("send estimate_fee_rate request error {}", e);
             return Err(Error::internal_error());
@@ -108,10 +143,14 @@ impl ExperimentRpc for ExperimentRpcImpl {
 // DryRunner dry run given transaction, and return the result, including execution cycles.
 pub(crate) struct DryRunner<'a> {
     shared: &'a Shared,
+    mock_cells: HashMap<packed::OutPoint, CellMeta>,
 }
 
 impl<'a> CellProvider for DryRunner<'a> {
     fn cell(&self, out_point: &packed::OutPoint, with_data: bool) -> CellStatus {
+        if let Some(cell_meta) = self.mock_cells.get(out_point) {
+            return CellStatus::live_cell(cell_meta.clone());
+        }
         let snapshot = self.shared.snapshot();
         snapshot
             .get_cell_meta(&out_point.tx_hash(), out_point.index().unpack())
@@ -137,7 +176,25 @@ impl<'a> HeaderChecker for DryRunner<'a> {
 
 impl<'a> DryRunner<'a> {
     pub(crate) fn new(shared: &'a Shared) -> Self {
-        Self { shared }
+        Self {
+            shared,
+            mock_cells: HashMap::new(),
+        }
+    }
+
+    // Mock cells shadow the cells with the same out point on chain
+    pub(crate) fn mock_cells(mut self, mock_cells: Vec<MockCell>) -> Self {
+        for mock_cell in mock_cells {
+            let out_point: packed::OutPoint = mock_cell.out_point.into();
+            let cell_meta = CellMetaBuilder::from_cell_output(
+                mock_cell.output.into(),
+                mock_cell.data.into_bytes(),
+            )
+            .out_point(out_point.clone())
+            .build();
+            self.mock_cells.insert(out_point, cell_meta);
+        }
+        self
     }
 
     pub(crate) fn run(&self, tx: packed::Transaction) -> Result<DryRunResult> {
@@ -146,14 +203,72 @@ impl<'a> DryRunner<'a> {
             Ok(resolved) => {
                 let consensus = snapshot.consensus();
                 let max_cycles = consensus.max_block_cycles;
-                match ScriptVerifier::new(&resolved, snapshot).verify(max_cycles) {
+                let trace_syscalls = self
+                    .shared
+                    .traced_transactions()
+                    .contains(&resolved.transaction.hash());
+                match ScriptVerifier::new(&resolved, snapshot)
+                    .vm_version(consensus.vm_version(snapshot.epoch_ext().number()))
+                    .trace_syscalls(trace_syscalls)
+                    .verify(max_cycles)
+                {
                     Ok(cycles) => Ok(DryRunResult {
                         cycles: cycles.into(),
+                        profile: None,
+                    }),
+                    Err(err) => Err(RPCError::custom_with_error(
+                        RPCError::Invalid,
+                        format!("{:?}", err),
+                        &err,
+                    )),
+                }
+            }
+            Err(err) => Err(RPCError::custom(RPCError::Invalid, format!("{:?}", err))),
+        }
+    }
+
+    pub(crate) fn profile(&self, tx: packed::Transaction) -> Result<DryRunResult> {
+        let snapshot: &Snapshot = &self.shared.snapshot();
+        match resolve_transaction(tx.into_view(), &mut HashSet::new(), self, self) {
+            Ok(resolved) => {
+                let consensus = snapshot.consensus();
+                let max_cycles = consensus.max_block_cycles;
+                let trace_syscalls = self
+                    .shared
+                    .traced_transactions()
+                    .contains(&resolved.transaction.hash());
+                match ScriptVerifier::new(&resolved, snapshot)
+                    .vm_version(consensus.vm_version(snapshot.epoch_ext().number()))
+                    .trace_syscalls(trace_syscalls)
+                    .profile(max_cycles)
+                {
+                    Ok((cycles, profiles)) => Ok(DryRunResult {
+                        cycles: cycles.into(),
+                        profile: Some(profiles.into_iter().map(to_json_profile).collect()),
                     }),
-                    Err(err) => Err(RPCError::custom(RPCError::Invalid, format!("{:?}", err))),
+                    Err(err) => Err(RPCError::custom_with_error(
+                        RPCError::Invalid,
+                        format!("{:?}", err),
+                        &err,
+                    )),
                 }
             }
             Err(err) => Err(RPCError::custom(RPCError::Invalid, format!("{:?}", err))),
         }
     }
 }
+
+fn to_json_profile(profile: CoreScriptGroupProfile) -> ScriptGroupProfile {
+    let group_type = match profile.group_type {
+        CoreScriptGroupType::Lock => ScriptGroupType::Lock,
+        CoreScriptGroupType::Type => ScriptGroupType::Type,
+    };
+    ScriptGroupProfile {
+        group_type,
+        index: (profile.index as u32).into(),
+        script_hash: profile.script_hash.unpack(),
+        cycles: profile.cycles.into(),
+        elapsed_micros: (profile.elapsed.as_micros() as u64).into(),
+        vm_version: (profile.vm_version as u32).into(),
+    }
+}
@@ -1,6 +1,9 @@
-use ckb_indexer::IndexerStore;
+use crate::error::RPCError;
+use ckb_indexer::{IndexerStore, Pagination, ScriptIndexer, WatchList};
 use ckb_jsonrpc_types::{
-    BlockNumber, CellTransaction, LiveCell, LockHashCapacity, LockHashIndexState, Uint64,
+    BlockNumber, CellTransaction, IndexerCellsCapacity, IndexerOrder, IndexerPagination,
+    IndexerSearchKey, JsonBytes, LiveCell, LockHashCapacity, LockHashIndexState, Script, Uint32,
+    Uint64,
 };
 use ckb_types::{prelude::*, H256};
 use jsonrpc_core::Result;
@@ -41,10 +44,107 @@ pub trait IndexerRpc {
 
     #[rpc(name = "get_capacity_by_lock_hash")]
     fn get_capacity_by_lock_hash(&self, _lock_hash: H256) -> Result<Option<LockHashCapacity>>;
+
+    #[rpc(name = "get_cells")]
+    fn get_cells(
+        &self,
+        _search_key: IndexerSearchKey,
+        _order: IndexerOrder,
+        _limit: Uint32,
+        _after_cursor: Option<JsonBytes>,
+    ) -> Result<IndexerPagination<LiveCell>>;
+
+    #[rpc(name = "get_transactions")]
+    fn get_transactions(
+        &self,
+        _search_key: IndexerSearchKey,
+        _order: IndexerOrder,
+        _limit: Uint32,
+        _after_cursor: Option<JsonBytes>,
+    ) -> Result<IndexerPagination<CellTransaction>>;
+
+    #[rpc(name = "get_cells_capacity")]
+    fn get_cells_capacity(&self, _search_key: IndexerSearchKey)
+        -> Result<Option<LockHashCapacity>>;
+
+    #[rpc(name = "get_grouped_cells_capacity")]
+    fn get_grouped_cells_capacity(
+        &self,
+        _search_key: IndexerSearchKey,
+        _limit: Uint32,
+        _after_cursor: Option<JsonBytes>,
+        _group_by_type: Option<bool>,
+    ) -> Result<IndexerPagination<IndexerCellsCapacity>>;
+
+    #[rpc(name = "watch_lock_script")]
+    fn watch_lock_script(&self, _lock_script: Script) -> Result<H256>;
+
+    #[rpc(name = "unwatch_lock_script")]
+    fn unwatch_lock_script(&self, _lock_hash: H256) -> Result<bool>;
+
+    #[rpc(name = "get_watched_lock_scripts")]
+    fn get_watched_lock_scripts(&self) -> Result<Vec<Script>>;
 }
 
 pub(crate) struct IndexerRpcImpl<WS> {
-    pub store: WS,
+    // None in the standalone indexer, which follows a remote node
+    pub store: Option<WS>,
+    // None when `indexer.index_scripts` is disabled
+    pub script_indexer: Option<ScriptIndexer>,
+    // None when `indexer.watch_list` is disabled
+    pub watch_list: Option<WatchList>,
+}
+
+impl<WS> IndexerRpcImpl<WS> {
+    fn store(&self) -> Result<&WS> {
+        self.store.as_ref().ok_or_else(|| {
+            RPCError::custom(
+                RPCError::Invalid,
+                "Lock hash index is unavailable in the standalone indexer".to_owned(),
+            )
+        })
+    }
+
+    fn script_indexer(&self) -> Result<&ScriptIndexer> {
+        self.script_indexer.as_ref().ok_or_else(|| {
+            RPCError::custom(
+                RPCError::Invalid,
+                "Script index is disabled, enable `indexer.index_scripts` to use it".to_owned(),
+            )
+        })
+    }
+
+    fn watch_list(&self) -> Result<&WatchList> {
+        self.watch_list.as_ref().ok_or_else(|| {
+            RPCError::custom(
+                RPCError::Invalid,
+                "Watch list is disabled, enable `indexer.watch_list` to use it".to_owned(),
+            )
+        })
+    }
+}
+
+// Upper bound of the objects returned by a single `get_cells` or `get_transactions` call
+const MAX_SEARCH_LIMIT: usize = 50;
+// Upper bound of the cells summed by a single `get_grouped_cells_capacity` call
+const MAX_SUM_LIMIT: usize = 10_000;
+
+fn check_limit(limit: Uint32, max_limit: usize) -> Result<usize> {
+    let limit = limit.value() as usize;
+    if limit == 0 || limit > max_limit {
+        return Err(RPCError::custom(
+            RPCError::Invalid,
+            format!("limit must be between 1 and {}", max_limit),
+        ));
+    }
+    Ok(limit)
+}
+
+fn into_json_pagination<T, U: From<T>>(pagination: Pagination<T>) -> IndexerPagination<U> {
+    IndexerPagination {
+        objects: pagination.objects.into_iter().map(Into::into).collect(),
+        last_cursor: JsonBytes::from_vec(pagination.last_cursor),
+    }
 }
 
 impl<WS: IndexerStore + 'static> IndexerRpc for IndexerRpcImpl<WS> {
@@ -58,7 +158,7 @@ impl<WS: IndexerStore + 'static> IndexerRpc for IndexerRpcImpl<WS> {
         let lock_hash = lock_hash.pack();
         let per_page = (per_page.value() as usize).min(50);
         Ok(self
-            .store
+            .store()?
             .get_live_cells(
                 &lock_hash,
                 (page.value() as usize).saturating_mul(per_page),
@@ -80,7 +180,7 @@ impl<WS: IndexerStore + 'static> IndexerRpc for IndexerRpcImpl<WS> {
         let lock_hash = lock_hash.pack();
         let per_page = (per_page.value() as usize).min(50);
         Ok(self
-            .store
+            .store()?
             .get_transactions(
                 &lock_hash,
                 (page.value() as usize).saturating_mul(per_page),
@@ -98,7 +198,7 @@ impl<WS: IndexerStore + 'static> IndexerRpc for IndexerRpcImpl<WS> {
         index_from: Option<BlockNumber>,
     ) -> Result<LockHashIndexState> {
         let state = self
-            .store
+            .store()?
             .insert_lock_hash(&lock_hash.pack(), index_from.map(Into::into));
         Ok(LockHashIndexState {
             lock_hash,
@@ -108,13 +208,13 @@ impl<WS: IndexerStore + 'static> IndexerRpc for IndexerRpcImpl<WS> {
     }
 
     fn deindex_lock_hash(&self, lock_hash: H256) -> Result<()> {
-        self.store.remove_lock_hash(&lock_hash.pack());
+        self.store()?.remove_lock_hash(&lock_hash.pack());
         Ok(())
     }
 
     fn get_lock_hash_index_states(&self) -> Result<Vec<LockHashIndexState>> {
         let states = self
-            .store
+            .store()?
             .get_lock_hash_index_states()
             .into_iter()
             .map(|(lock_hash, state)| LockHashIndexState {
@@ -128,6 +228,86 @@ impl<WS: IndexerStore + 'static> IndexerRpc for IndexerRpcImpl<WS> {
 
     fn get_capacity_by_lock_hash(&self, lock_hash: H256) -> Result<Option<LockHashCapacity>> {
         let lock_hash = lock_hash.pack();
-        Ok(self.store.get_capacity(&lock_hash).map(Into::into))
+        Ok(self.store()?.get_capacity(&lock_hash).map(Into::into))
+    }
+
+    fn get_cells(
+        &self,
+        search_key: IndexerSearchKey,
+        order: IndexerOrder,
+        limit: Uint32,
+        after_cursor: Option<JsonBytes>,
+    ) -> Result<IndexerPagination<LiveCell>> {
+        let limit = check_limit(limit, MAX_SEARCH_LIMIT)?;
+        let pagination = self.script_indexer()?.get_cells(
+            &search_key.into(),
+            order.into(),
+            limit,
+            after_cursor.as_ref().map(JsonBytes::as_bytes),
+        );
+        Ok(into_json_pagination(pagination))
+    }
+
+    fn get_transactions(
+        &self,
+        search_key: IndexerSearchKey,
+        order: IndexerOrder,
+        limit: Uint32,
+        after_cursor: Option<JsonBytes>,
+    ) -> Result<IndexerPagination<CellTransaction>> {
+        let limit = check_limit(limit, MAX_SEARCH_LIMIT)?;
+        let pagination = self.script_indexer()?.get_transactions(
+            &search_key.into(),
+            order.into(),
+            limit,
+            after_cursor.as_ref().map(JsonBytes::as_bytes),
+        );
+        Ok(into_json_pagination(pagination))
+    }
+
+    fn get_cells_capacity(&self, search_key: IndexerSearchKey) -> Result<Option<LockHashCapacity>> {
+        Ok(self
+            .script_indexer()?
+            .get_cells_capacity(&search_key.into())
+            .map_err(|err| RPCError::custom(RPCError::Invalid, err.to_string()))?
+            .map(Into::into))
+    }
+
+    fn get_grouped_cells_capacity(
+        &self,
+        search_key: IndexerSearchKey,
+        limit: Uint32,
+        after_cursor: Option<JsonBytes>,
+        group_by_type: Option<bool>,
+    ) -> Result<IndexerPagination<IndexerCellsCapacity>> {
+        let limit = check_limit(limit, MAX_SUM_LIMIT)?;
+        let pagination = self
+            .script_indexer()?
+            .get_grouped_cells_capacity(
+                &search_key.into(),
+                group_by_type.unwrap_or_default(),
+                limit,
+                after_cursor.as_ref().map(JsonBytes::as_bytes),
+            )
+            .map_err(|err| RPCError::custom(RPCError::Invalid, err.to_string()))?;
+        Ok(into_json_pagination(pagination))
+    }
+
+    fn watch_lock_script(&self, lock_script: Script) -> Result<H256> {
+        let lock_hash = self.watch_list()?.watch(lock_script.into());
+        Ok(lock_hash.unpack())
+    }
+
+    fn unwatch_lock_script(&self, lock_hash: H256) -> Result<bool> {
+        Ok(self.watch_list()?.unwatch(&lock_hash.pack()))
+    }
+
+    fn get_watched_lock_scripts(&self) -> Result<Vec<Script>> {
+        Ok(self
+            .watch_list()?
+            .lock_scripts()
+            .into_iter()
+            .map(Into::into)
+            .collect())
     }
 }
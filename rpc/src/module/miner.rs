@@ -1,82 +1,135 @@
 use crate::error::RPCError;
 use ckb_chain::chain::ChainController;
-use ckb_jsonrpc_types::{Block, BlockTemplate, Uint64, Version};
+use ckb_jsonrpc_types::{Block, BlockTemplate, NonceRange, Uint128, Uint64, Version, Work};
 use ckb_logger::{debug, error};
 use ckb_network::NetworkController;
 use ckb_shared::{shared::Shared, Snapshot};
 use ckb_sync::NetworkProtocol;
-use ckb_types::{core, packed, prelude::*, H256};
+use ckb_tx_pool::LongpollId;
+use ckb_types::{core, packed, prelude::*, utilities::compact_to_target, H256};
+use ckb_util::Mutex;
 use ckb_verification::{HeaderResolverWrapper, HeaderVerifier, Verifier};
-use faketime::unix_time_as_millis;
-use jsonrpc_core::{Error, Result};
+use futures03::{FutureExt, TryFutureExt};
+use jsonrpc_core::{
+    futures::{future, Future},
+    BoxFuture, Error, Result,
+};
 use jsonrpc_derive::rpc;
+use lru_cache::LruCache;
 use std::collections::HashSet;
-use std::fmt::Debug;
+use std::fmt::{Debug, Display};
 use std::sync::Arc;
+use std::time::Duration;
+
+const WORK_CACHE_SIZE: usize = 32;
+// The nonce space of a work is split into `2^NONCE_LEASE_BITS` ranges, every
+// `get_block_template` call on the same work leases the next one.
+const NONCE_LEASE_BITS: u32 = 32;
+const DEFAULT_LONGPOLL_TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_LONGPOLL_TIMEOUT: Duration = Duration::from_secs(120);
 
 #[rpc(server)]
 pub trait MinerRpc {
-    // curl -d '{"id": 2, "jsonrpc": "2.0", "method":"get_block_template","params": ["0x1b1c832d02fdb4339f9868c8a8636c3d9dd10bd53ac7ce99595825bd6beeffb3", 1000, 1000]}' -H 'content-type:application/json' 'http://localhost:8114'
+    // curl -d '{"id": 2, "jsonrpc": "2.0", "method":"get_block_template","params": ["0x1b1c832d02fdb4339f9868c8a8636c3d9dd10bd53ac7ce99595825bd6beeffb3", 1000, 1000, "fifo"]}' -H 'content-type:application/json' 'http://localhost:8114'
     #[rpc(name = "get_block_template")]
     fn get_block_template(
         &self,
         bytes_limit: Option<Uint64>,
         proposals_limit: Option<Uint64>,
         max_version: Option<Version>,
-    ) -> Result<BlockTemplate>;
+        strategy: Option<String>,
+    ) -> BoxFuture<BlockTemplate>;
+
+    // curl -d '{"id": 2, "jsonrpc": "2.0", "method":"get_block_template_longpoll","params": ["a5f5c85987a15de25661e5a214f2c1449cd803f071acc7999820f25246471f400000017267a9a4fb", "0x7530", null, null, null, null]}' -H 'content-type:application/json' 'http://localhost:8114'
+    #[rpc(name = "get_block_template_longpoll")]
+    fn get_block_template_longpoll(
+        &self,
+        longpoll_id: String,
+        timeout: Option<Uint64>,
+        bytes_limit: Option<Uint64>,
+        proposals_limit: Option<Uint64>,
+        max_version: Option<Version>,
+        strategy: Option<String>,
+    ) -> BoxFuture<BlockTemplate>;
 
     // curl -d '{"id": 2, "jsonrpc": "2.0", "method":"submit_block","params": [{"header":{}, "uncles":[], "transactions":[], "proposals":[]}]}' -H 'content-type:application/json' 'http://localhost:8114'
     #[rpc(name = "submit_block")]
     fn submit_block(&self, _work_id: String, _data: Block) -> Result<H256>;
+
+    // curl -d '{"id": 2, "jsonrpc": "2.0", "method":"get_work","params": []}' -H 'content-type:application/json' 'http://localhost:8114'
+    #[rpc(name = "get_work")]
+    fn get_work(&self) -> Result<Work>;
+
+    // curl -d '{"id": 2, "jsonrpc": "2.0", "method":"submit_work","params": ["0x1b1c832d02fdb4339f9868c8a8636c3d9dd10bd53ac7ce99595825bd6beeffb3", "0x0"]}' -H 'content-type:application/json' 'http://localhost:8114'
+    #[rpc(name = "submit_work")]
+    fn submit_work(&self, _pow_hash: H256, _nonce: Uint128) -> Result<bool>;
 }
 
 pub(crate) struct MinerRpcImpl {
-    pub network_controller: NetworkController,
-    pub shared: Shared,
-    pub chain: ChainController,
+    network_controller: NetworkController,
+    shared: Shared,
+    chain: ChainController,
+    // Blocks handed out by `get_work`, keyed by their pow hash
+    works: Mutex<LruCache<packed::Byte32, (u64, packed::Block)>>,
+    // Next nonce lease index of each work id
+    nonce_leases: Arc<Mutex<LruCache<u64, u64>>>,
 }
 
-impl MinerRpc for MinerRpcImpl {
-    fn get_block_template(
-        &self,
-        bytes_limit: Option<Uint64>,
-        proposals_limit: Option<Uint64>,
-        max_version: Option<Version>,
-    ) -> Result<BlockTemplate> {
-        let bytes_limit = match bytes_limit {
-            Some(b) => Some(b.into()),
-            None => None,
-        };
-
-        let proposals_limit = match proposals_limit {
-            Some(b) => Some(b.into()),
-            None => None,
-        };
+impl MinerRpcImpl {
+    pub fn new(
+        network_controller: NetworkController,
+        shared: Shared,
+        chain: ChainController,
+    ) -> Self {
+        MinerRpcImpl {
+            network_controller,
+            shared,
+            chain,
+            works: Mutex::new(LruCache::new(WORK_CACHE_SIZE)),
+            nonce_leases: Arc::new(Mutex::new(LruCache::new(WORK_CACHE_SIZE))),
+        }
+    }
 
-        let tx_pool = self.shared.tx_pool_controller();
+    // Rejects the names of the strategies not registered in the tx pool
+    fn check_selection_strategy(&self, strategy: Option<&String>) -> Result<()> {
+        match strategy {
+            Some(name)
+                if !self
+                    .shared
+                    .tx_pool_controller()
+                    .has_selection_strategy(name) =>
+            {
+                Err(Error::invalid_params(format!(
+                    "unknown selection strategy {}",
+                    name
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
 
-        let get_block_template =
-            tx_pool.get_block_template(bytes_limit, proposals_limit, max_version.map(Into::into));
-        if let Err(e) = get_block_template {
-            error!("send get_block_template request error {}", e);
-            return Err(Error::internal_error());
+    // Leases a nonce range of the work to the caller once the template is built
+    fn lease_block_template<F, E>(&self, get_block_template: F) -> BoxFuture<BlockTemplate>
+    where
+        F: std::future::Future<
+                Output = std::result::Result<std::result::Result<BlockTemplate, E>, E>,
+            > + Send
+            + 'static,
+        E: Display,
+    {
+        let nonce_leases = Arc::clone(&self.nonce_leases);
+        let lease_block_template = async move {
+            let mut block_template = wait_block_template(get_block_template).await?;
+            block_template.nonce_range = Some(lease_nonce_range(
+                &nonce_leases,
+                block_template.work_id.into(),
+            ));
+            Ok(block_template)
         };
-
-        get_block_template.unwrap().map_err(|err| {
-            error!("get_block_template result error {}", err);
-            Error::internal_error()
-        })
+        Box::new(lease_block_template.boxed().compat())
     }
 
-    fn submit_block(&self, work_id: String, data: Block) -> Result<H256> {
-        // TODO: this API is intended to be used in a trusted environment, thus it should pass the
-        // verifier. We use sentry to capture errors found here to discovery issues early, which
-        // should be removed later.
-        let _scope_guard = sentry::Hub::current().push_scope();
-        sentry::configure_scope(|scope| scope.set_extra("work_id", work_id.clone().into()));
-
-        debug!("[{}] submit block", work_id);
-        let block: packed::Block = data.into();
+    fn process_block(&self, work_id: &str, block: packed::Block) -> Result<H256> {
         let block: Arc<core::BlockView> = Arc::new(block.into_view());
         let header = block.header();
 
@@ -85,13 +138,13 @@ impl MinerRpc for MinerRpcImpl {
         let resolver = HeaderResolverWrapper::new(&header, snapshot);
         HeaderVerifier::new(snapshot, &self.shared.consensus())
             .verify(&resolver)
-            .map_err(|err| handle_submit_error(&work_id, &err))?;
+            .map_err(|err| handle_submit_error(work_id, &err))?;
 
         // Verify and insert block
         let is_new = self
             .chain
             .process_block(Arc::clone(&block))
-            .map_err(|err| handle_submit_error(&work_id, &err))?;
+            .map_err(|err| handle_submit_error(work_id, &err))?;
 
         // Announce only new block
         if is_new {
@@ -99,7 +152,7 @@ impl MinerRpc for MinerRpcImpl {
                 "[block_relay] announce new block {} {} {}",
                 header.number(),
                 header.hash(),
-                unix_time_as_millis()
+                self.shared.clock().unix_time_as_millis()
             );
             let content = packed::CompactBlock::build_from_block(&block, &HashSet::new());
             let message = packed::RelayMessage::new_builder().set(content).build();
@@ -115,6 +168,176 @@ impl MinerRpc for MinerRpcImpl {
     }
 }
 
+impl MinerRpc for MinerRpcImpl {
+    fn get_block_template(
+        &self,
+        bytes_limit: Option<Uint64>,
+        proposals_limit: Option<Uint64>,
+        max_version: Option<Version>,
+        strategy: Option<String>,
+    ) -> BoxFuture<BlockTemplate> {
+        if let Err(err) = self.check_selection_strategy(strategy.as_ref()) {
+            return Box::new(future::err(err));
+        }
+        let bytes_limit = match bytes_limit {
+            Some(b) => Some(b.into()),
+            None => None,
+        };
+
+        let proposals_limit = match proposals_limit {
+            Some(b) => Some(b.into()),
+            None => None,
+        };
+
+        let get_block_template = self.shared.tx_pool_controller().get_block_template_async(
+            bytes_limit,
+            proposals_limit,
+            max_version.map(Into::into),
+            strategy,
+        );
+        self.lease_block_template(get_block_template)
+    }
+
+    fn get_block_template_longpoll(
+        &self,
+        longpoll_id: String,
+        timeout: Option<Uint64>,
+        bytes_limit: Option<Uint64>,
+        proposals_limit: Option<Uint64>,
+        max_version: Option<Version>,
+        strategy: Option<String>,
+    ) -> BoxFuture<BlockTemplate> {
+        if let Err(err) = self.check_selection_strategy(strategy.as_ref()) {
+            return Box::new(future::err(err));
+        }
+        let longpoll_id = match longpoll_id.parse::<LongpollId>() {
+            Ok(longpoll_id) => longpoll_id,
+            Err(err) => return Box::new(future::err(Error::invalid_params(err))),
+        };
+        let timeout = timeout
+            .map(|timeout| Duration::from_millis(timeout.into()))
+            .unwrap_or(DEFAULT_LONGPOLL_TIMEOUT)
+            .min(MAX_LONGPOLL_TIMEOUT);
+
+        let get_block_template = self
+            .shared
+            .tx_pool_controller()
+            .get_block_template_longpoll(
+                Some(longpoll_id),
+                timeout,
+                bytes_limit.map(Into::into),
+                proposals_limit.map(Into::into),
+                max_version.map(Into::into),
+                strategy,
+            );
+        self.lease_block_template(get_block_template)
+    }
+
+    fn submit_block(&self, work_id: String, data: Block) -> Result<H256> {
+        // TODO: this API is intended to be used in a trusted environment, thus it should pass the
+        // verifier. We use sentry to capture errors found here to discovery issues early, which
+        // should be removed later.
+        let _scope_guard = sentry::Hub::current().push_scope();
+        sentry::configure_scope(|scope| scope.set_extra("work_id", work_id.clone().into()));
+
+        debug!("[{}] submit block", work_id);
+        self.process_block(&work_id, data.into())
+    }
+
+    fn get_work(&self) -> Result<Work> {
+        // the whole nonce space of the work is the miner's, so no range is leased
+        let get_block_template = self
+            .shared
+            .tx_pool_controller()
+            .get_block_template_async(None, None, None, None);
+        let block_template = wait_block_template(get_block_template)
+            .boxed()
+            .compat()
+            .wait()?;
+        let work_id: u64 = block_template.work_id.into();
+        let block: packed::Block = block_template.into();
+        let raw_header = block.header().raw();
+        let pow_hash = block.header().calc_pow_hash();
+        let target = work_target(raw_header.compact_target().unpack())?;
+
+        self.works.lock().insert(pow_hash.clone(), (work_id, block));
+
+        Ok(Work {
+            work_id: work_id.into(),
+            pow_hash: pow_hash.unpack(),
+            target,
+            number: raw_header.number().unpack(),
+        })
+    }
+
+    fn submit_work(&self, pow_hash: H256, nonce: Uint128) -> Result<bool> {
+        let work = self.works.lock().get_refresh(&pow_hash.pack()).cloned();
+        match work {
+            Some((work_id, block)) => {
+                let header = packed::Header::new_builder()
+                    .raw(block.header().raw())
+                    .nonce(nonce.pack())
+                    .build();
+                let block = block.as_builder().header(header).build();
+                let work_id = work_id.to_string();
+                debug!("[{}] submit work", work_id);
+                self.process_block(&work_id, block).map(|_| true)
+            }
+            None => {
+                debug!("submit work with unknown pow hash {:#x}", pow_hash);
+                Ok(false)
+            }
+        }
+    }
+}
+
+async fn wait_block_template<F, E>(get_block_template: F) -> Result<BlockTemplate>
+where
+    F: std::future::Future<Output = std::result::Result<std::result::Result<BlockTemplate, E>, E>>,
+    E: Display,
+{
+    let get_block_template = get_block_template.await.map_err(|e| {
+        error!("send get_block_template request error {}", e);
+        Error::internal_error()
+    })?;
+    get_block_template.map_err(|err| {
+        error!("get_block_template result error {}", err);
+        Error::internal_error()
+    })
+}
+
+// The target of the work in big endian, the compact targets out of 256 bits are rejected
+fn work_target(compact_target: u32) -> Result<H256> {
+    let (target, overflow) = compact_to_target(compact_target);
+    if overflow {
+        return Err(RPCError::custom(
+            RPCError::Invalid,
+            format!("compact target {:#x} overflows", compact_target),
+        ));
+    }
+    let mut target_bytes = [0u8; 32];
+    target
+        .into_big_endian(&mut target_bytes)
+        .expect("bound checked");
+    Ok(target_bytes.into())
+}
+
+// Leases the next nonce range of the work
+fn lease_nonce_range(nonce_leases: &Mutex<LruCache<u64, u64>>, work_id: u64) -> NonceRange {
+    let index = {
+        let mut leases = nonce_leases.lock();
+        let index = leases.get_refresh(&work_id).cloned().unwrap_or(0);
+        leases.insert(work_id, (index + 1) % (1 << NONCE_LEASE_BITS));
+        index
+    };
+    let span = 1u128 << (128 - NONCE_LEASE_BITS);
+    let start = span * u128::from(index);
+    NonceRange {
+        start: start.into(),
+        end: start.saturating_add(span).into(),
+    }
+}
+
 fn handle_submit_error<E: Debug + ToString>(work_id: &str, err: &E) -> Error {
     error!("[{}] submit_block error: {:?}", work_id, err);
     capture_submit_error(err);
@@ -128,3 +351,30 @@ fn capture_submit_error<D: Debug>(err: &D) {
         || capture_message(&format!("submit_block {:?}", err), Level::Error),
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_work_target() {
+        let mut expected = [0u8; 32];
+        expected[1] = 0xff;
+        expected[2] = 0xff;
+        assert_eq!(work_target(0x2000_ffff).unwrap(), H256::from(expected));
+        assert!(work_target(0x2100_ffff).is_err());
+    }
+
+    #[test]
+    fn test_lease_nonce_range() {
+        let nonce_leases = Mutex::new(LruCache::new(WORK_CACHE_SIZE));
+        let span = 1u128 << (128 - NONCE_LEASE_BITS);
+        let first = lease_nonce_range(&nonce_leases, 1);
+        let second = lease_nonce_range(&nonce_leases, 1);
+        assert_eq!(u128::from(first.start), 0);
+        assert_eq!(u128::from(first.end), span);
+        assert_eq!(u128::from(second.start), span);
+        // every work leases its own ranges
+        assert_eq!(u128::from(lease_nonce_range(&nonce_leases, 2).start), 0);
+    }
+}
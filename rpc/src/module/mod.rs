@@ -6,6 +6,8 @@ mod indexer;
 mod miner;
 mod net;
 mod pool;
+#[cfg(feature = "script-debugger")]
+mod script_debugger;
 mod stats;
 mod subscription;
 mod test;
@@ -18,6 +20,8 @@ pub(crate) use self::indexer::{IndexerRpc, IndexerRpcImpl};
 pub(crate) use self::miner::{MinerRpc, MinerRpcImpl};
 pub(crate) use self::net::{NetworkRpc, NetworkRpcImpl};
 pub(crate) use self::pool::{PoolRpc, PoolRpcImpl};
+#[cfg(feature = "script-debugger")]
+pub(crate) use self::script_debugger::{ScriptDebuggerRpc, ScriptDebuggerRpcImpl};
 pub(crate) use self::stats::{StatsRpc, StatsRpcImpl};
 pub(crate) use self::subscription::{SubscriptionRpc, SubscriptionRpcImpl, SubscriptionSession};
 pub(crate) use self::test::{IntegrationTestRpc, IntegrationTestRpcImpl};
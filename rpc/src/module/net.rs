@@ -1,10 +1,17 @@
 use crate::error::RPCError;
-use ckb_jsonrpc_types::{BannedAddr, Node, NodeAddress, Timestamp};
-use ckb_network::{MultiaddrExt, NetworkController};
+use ckb_jsonrpc_types::{
+    BannedAddr, CompactBlockStats, MessageStats, Node, NodeAddress, PeerScore, Timestamp,
+};
+use ckb_network::{
+    multiaddr::{Multiaddr, Protocol},
+    MultiaddrExt, NetworkController, PeerId,
+};
+use ckb_sync::SyncShared;
 use faketime::unix_time_as_millis;
 use jsonrpc_core::Result;
 use jsonrpc_derive::rpc;
 use std::collections::HashMap;
+use std::sync::Arc;
 
 const MAX_ADDRS: usize = 50;
 const DEFAULT_BAN_DURATION: u64 = 24 * 60 * 60 * 1000; // 1 day
@@ -23,6 +30,10 @@ pub trait NetworkRpc {
     #[rpc(name = "get_banned_addresses")]
     fn get_banned_addresses(&self) -> Result<Vec<BannedAddr>>;
 
+    // curl -d '{"id": 2, "jsonrpc": "2.0", "method":"get_peer_scores","params": []}' -H 'content-type:application/json' 'http://localhost:8114'
+    #[rpc(name = "get_peer_scores")]
+    fn get_peer_scores(&self) -> Result<Vec<PeerScore>>;
+
     // curl -d '{"id": 2, "jsonrpc": "2.0", "method":"set_ban","params": ["192.168.0.0/24", "insert"]}' -H 'content-type:application/json' 'http://localhost:8114'
     #[rpc(name = "set_ban")]
     fn set_ban(
@@ -33,10 +44,19 @@ pub trait NetworkRpc {
         absolute: Option<bool>,
         reason: Option<String>,
     ) -> Result<()>;
+
+    // curl -d '{"id": 2, "jsonrpc": "2.0", "method":"clear_banned_addresses","params": []}' -H 'content-type:application/json' 'http://localhost:8114'
+    #[rpc(name = "clear_banned_addresses")]
+    fn clear_banned_addresses(&self) -> Result<()>;
+
+    // curl -d '{"id": 2, "jsonrpc": "2.0", "method":"dial_node","params": ["/ip4/192.168.2.100/tcp/8115/p2p/QmUsZHPbjjzU627UZFt4k8j6ycEcNvXRnVGxCPKqwbAfQS"]}' -H 'content-type:application/json' 'http://localhost:8114'
+    #[rpc(name = "dial_node")]
+    fn dial_node(&self, address: String) -> Result<()>;
 }
 
 pub(crate) struct NetworkRpcImpl {
     pub network_controller: NetworkController,
+    pub sync_shared: Arc<SyncShared>,
 }
 
 impl NetworkRpc for NetworkRpcImpl {
@@ -44,6 +64,8 @@ impl NetworkRpc for NetworkRpcImpl {
         Ok(Node {
             version: self.network_controller.node_version().to_string(),
             is_outbound: None,
+            compact_block_stats: None,
+            message_stats: Vec::new(),
             node_id: self.network_controller.node_id(),
             addresses: self
                 .network_controller
@@ -59,6 +81,7 @@ impl NetworkRpc for NetworkRpcImpl {
 
     fn get_peers(&self) -> Result<Vec<Node>> {
         let peers = self.network_controller.connected_peers();
+        let compact_block_stats = self.sync_shared.state().peers().compact_block_stats();
         let mut nodes = Vec::with_capacity(peers.len());
         for (peer_id, peer) in peers.into_iter() {
             let mut addresses = vec![&peer.connected_addr];
@@ -84,6 +107,20 @@ impl NetworkRpc for NetworkRpcImpl {
                 }
             }
 
+            let mut message_stats: Vec<MessageStats> = self
+                .network_controller
+                .message_stats(peer.session_id)
+                .into_iter()
+                .map(|(protocol_id, stats)| MessageStats {
+                    protocol_id: (protocol_id.value() as u64).into(),
+                    messages: stats.messages.into(),
+                    bytes: stats.bytes.into(),
+                    last_received_at: stats.last_received_at.into(),
+                    average_latency: (stats.average_latency().as_micros() as u64).into(),
+                })
+                .collect();
+            message_stats.sort_by_key(|stats| stats.protocol_id.value());
+
             nodes.push(Node {
                 is_outbound: Some(peer.is_outbound()),
                 version: peer
@@ -92,6 +129,17 @@ impl NetworkRpc for NetworkRpcImpl {
                     .unwrap_or_else(|| "unknown".to_string()),
                 node_id: peer_id.to_base58(),
                 addresses: node_addresses.values().cloned().collect(),
+                compact_block_stats: compact_block_stats.get(&peer.session_id).map(|stats| {
+                    CompactBlockStats {
+                        compact_blocks: stats.compact_blocks.into(),
+                        short_ids: stats.short_ids.into(),
+                        short_id_hits: stats.short_id_hits.into(),
+                        fallbacks: stats.fallbacks.into(),
+                        collisions: stats.collisions.into(),
+                        full_block_announcement: stats.prefers_full_blocks(),
+                    }
+                }),
+                message_stats,
             });
         }
 
@@ -112,6 +160,21 @@ impl NetworkRpc for NetworkRpcImpl {
             .collect())
     }
 
+    fn get_peer_scores(&self) -> Result<Vec<PeerScore>> {
+        Ok(self
+            .network_controller
+            .peer_scores()
+            .into_iter()
+            .map(|score| PeerScore {
+                node_id: score.peer_id.to_base58(),
+                score: (score.score as u64).into(),
+                misbehaviors: score.misbehaviors.into(),
+                last_misbehavior: score.last_misbehavior,
+                updated_at: score.updated_at.into(),
+            })
+            .collect())
+    }
+
     fn set_ban(
         &self,
         address: String,
@@ -153,4 +216,27 @@ impl NetworkRpc for NetworkRpcImpl {
         }
         Ok(())
     }
+
+    fn clear_banned_addresses(&self) -> Result<()> {
+        self.network_controller.clear_banned_addrs();
+        Ok(())
+    }
+
+    fn dial_node(&self, address: String) -> Result<()> {
+        let mut addr: Multiaddr = address
+            .parse()
+            .map_err(|_| RPCError::custom(RPCError::Invalid, "invalid address".to_owned()))?;
+        let peer_id = match addr.pop() {
+            Some(Protocol::P2P(key)) => PeerId::from_bytes(key.to_vec()).ok(),
+            _ => None,
+        }
+        .ok_or_else(|| {
+            RPCError::custom(
+                RPCError::Invalid,
+                "address should end with /p2p/<peer_id>".to_owned(),
+            )
+        })?;
+        self.network_controller.add_node(&peer_id, addr);
+        Ok(())
+    }
 }
@@ -1,16 +1,22 @@
 use crate::error::RPCError;
 use ckb_chain_spec::consensus::Consensus;
+use ckb_error::Error as CKBError;
 use ckb_fee_estimator::FeeRate;
-use ckb_jsonrpc_types::{OutputsValidator, Transaction, TxPoolInfo};
+use ckb_jsonrpc_types::{
+    FeeHistogramBin, LocalTxStatus, OutputsValidator, Status, Transaction, TxPoolAcceptResult,
+    TxPoolFeeHistogram, TxPoolInfo, TxPoolLimits,
+};
 use ckb_logger::error;
 use ckb_network::PeerIndex;
 use ckb_script::IllTransactionChecker;
 use ckb_shared::shared::Shared;
 use ckb_sync::SyncShared;
-use ckb_tx_pool::error::SubmitTxError;
+use ckb_tx_pool::error::{SubmitTxError, TrySendError};
+use ckb_tx_pool::LocalTxStatus as TrackedStatus;
 use ckb_types::{core, packed, prelude::*, H256};
 use ckb_verification::{Since, SinceMetric};
-use jsonrpc_core::{Error, Result};
+use futures03::{FutureExt, TryFutureExt};
+use jsonrpc_core::{futures::future, BoxFuture, Error, Result};
 use jsonrpc_derive::rpc;
 use std::convert::TryInto;
 use std::sync::Arc;
@@ -23,17 +29,31 @@ pub trait PoolRpc {
         &self,
         _tx: Transaction,
         _outputs_validator: Option<OutputsValidator>,
-    ) -> Result<H256>;
+    ) -> BoxFuture<H256>;
 
     // curl -d '{"params": [], "method": "tx_pool_info", "jsonrpc": "2.0", "id": 2}' -H 'content-type:application/json' http://localhost:8114
     #[rpc(name = "tx_pool_info")]
     fn tx_pool_info(&self) -> Result<TxPoolInfo>;
+
+    #[rpc(name = "get_tx_pool_fee_histogram")]
+    fn get_tx_pool_fee_histogram(&self) -> Result<TxPoolFeeHistogram>;
+
+    #[rpc(name = "test_tx_pool_accept")]
+    fn test_tx_pool_accept(&self, _txs: Vec<Transaction>) -> BoxFuture<Vec<TxPoolAcceptResult>>;
+
+    // Changes the limits of the running tx pool, the transactions already in the pool are kept
+    #[rpc(name = "set_tx_pool_config")]
+    fn set_tx_pool_config(&self, _limits: TxPoolLimits) -> Result<()>;
+
+    // The status of a transaction sent by `send_transaction`, including the reason it is dropped
+    // from the pool for. Returns null for the transactions not sent to this node.
+    #[rpc(name = "get_transaction_status")]
+    fn get_transaction_status(&self, _hash: H256) -> Result<Option<LocalTxStatus>>;
 }
 
 pub(crate) struct PoolRpcImpl {
     sync_shared: Arc<SyncShared>,
     shared: Shared,
-    min_fee_rate: FeeRate,
     reject_ill_transactions: bool,
 }
 
@@ -41,13 +61,11 @@ impl PoolRpcImpl {
     pub fn new(
         shared: Shared,
         sync_shared: Arc<SyncShared>,
-        min_fee_rate: FeeRate,
         reject_ill_transactions: bool,
     ) -> PoolRpcImpl {
         PoolRpcImpl {
             sync_shared,
             shared,
-            min_fee_rate,
             reject_ill_transactions,
         }
     }
@@ -58,7 +76,7 @@ impl PoolRpc for PoolRpcImpl {
         &self,
         tx: Transaction,
         outputs_validator: Option<OutputsValidator>,
-    ) -> Result<H256> {
+    ) -> BoxFuture<H256> {
         let tx: packed::Transaction = tx.into();
         let tx: core::TransactionView = tx.into_view();
 
@@ -68,59 +86,51 @@ impl PoolRpc for PoolRpcImpl {
             }
             Some(OutputsValidator::Passthrough) | None => Ok(()),
         } {
-            return Err(RPCError::custom(RPCError::Invalid, e));
+            return Box::new(future::err(RPCError::custom(RPCError::Invalid, e)));
         }
 
         if self.reject_ill_transactions {
             if let Err(e) = IllTransactionChecker::new(&tx).check() {
-                return Err(RPCError::custom(RPCError::Invalid, format!("{:#}", e)));
+                return Box::new(future::err(RPCError::custom(
+                    RPCError::Invalid,
+                    format!("{:#}", e),
+                )));
             }
         }
 
+        // The RPC thread is released while the pool is busy with the transaction
         let tx_pool = self.shared.tx_pool_controller();
-        let submit_txs = tx_pool.submit_txs(vec![tx.clone()]);
-
-        if let Err(e) = submit_txs {
-            error!("send submit_txs request error {}", e);
-            return Err(Error::internal_error());
-        }
-
-        match submit_txs.unwrap() {
-            Ok(_) => {
-                // workaround: we are using `PeerIndex(usize::max)` to indicate that tx hash source is itself.
-                let peer_index = PeerIndex::new(usize::max_value());
-                let hash = tx.hash();
-                self.sync_shared
-                    .state()
-                    .tx_hashes()
-                    .entry(peer_index)
-                    .or_default()
-                    .insert(hash.clone());
-                Ok(hash.unpack())
-            }
-            Err(e) => {
-                if let Some(e) = e.downcast_ref::<SubmitTxError>() {
-                    match *e {
-                        SubmitTxError::LowFeeRate(min_fee) => {
-                            return Err(RPCError::custom(
-                                RPCError::Invalid,
-                                format!(
-                                    "transaction fee rate lower than min_fee_rate: {} shannons/KB, min fee for current tx: {}",
-                                    self.min_fee_rate, min_fee,
-                                ),
-                            ));
-                        }
-                        SubmitTxError::ExceededMaximumAncestorsCount => {
-                            return Err(RPCError::custom(
-                                RPCError::Invalid,
-                                    "transaction exceeded maximum ancestors count limit, try send it later".to_string(),
-                            ));
-                        }
-                    }
+        let min_fee_rate = tx_pool.config().min_fee_rate;
+        let submit_txs = tx_pool.submit_txs_async(vec![tx.clone()]);
+        let sync_shared = Arc::clone(&self.sync_shared);
+        let send_transaction = async move {
+            let submit_txs = submit_txs.await.map_err(|e| {
+                if e.downcast_ref::<TrySendError>().map(TrySendError::is_full) == Some(true) {
+                    return RPCError::custom(
+                        RPCError::QueueFull,
+                        "the tx pool queue is full, retry later".to_owned(),
+                    );
+                }
+                error!("send submit_txs request error {}", e);
+                Error::internal_error()
+            })?;
+            match submit_txs {
+                Ok(_) => {
+                    // workaround: we are using `PeerIndex(usize::max)` to indicate that tx hash source is itself.
+                    let peer_index = PeerIndex::new(usize::max_value());
+                    let hash = tx.hash();
+                    sync_shared
+                        .state()
+                        .tx_hashes()
+                        .entry(peer_index)
+                        .or_default()
+                        .insert(hash.clone());
+                    Ok(hash.unpack())
                 }
-                Err(RPCError::custom(RPCError::Invalid, format!("{:#}", e)))
+                Err(e) => Err(submit_tx_error(&e, min_fee_rate)),
             }
-        }
+        };
+        Box::new(send_transaction.boxed().compat())
     }
 
     fn tx_pool_info(&self) -> Result<TxPoolInfo> {
@@ -139,10 +149,175 @@ impl PoolRpc for PoolRpcImpl {
             orphan: (tx_pool_info.orphan_size as u64).into(),
             total_tx_size: (tx_pool_info.total_tx_size as u64).into(),
             total_tx_cycles: tx_pool_info.total_tx_cycles.into(),
-            min_fee_rate: self.min_fee_rate.as_u64().into(),
+            min_fee_rate: tx_pool.config().min_fee_rate.as_u64().into(),
+            min_entry_fee_rate: tx_pool_info.min_entry_fee_rate.as_u64().into(),
             last_txs_updated_at: tx_pool_info.last_txs_updated_at.into(),
         })
     }
+
+    fn get_tx_pool_fee_histogram(&self) -> Result<TxPoolFeeHistogram> {
+        let tx_pool = self.shared.tx_pool_controller();
+        let histogram = tx_pool.get_fee_histogram().map_err(|e| {
+            error!("send get_fee_histogram request error {}", e);
+            Error::internal_error()
+        })?;
+
+        Ok(TxPoolFeeHistogram {
+            bins: histogram
+                .bins
+                .into_iter()
+                .map(|bin| FeeHistogramBin {
+                    fee_rate: bin.fee_rate.as_u64().into(),
+                    vbytes: bin.vbytes.into(),
+                    cycles: bin.cycles.into(),
+                })
+                .collect(),
+            pending: (histogram.pending_count as u64).into(),
+            gap: (histogram.gap_count as u64).into(),
+            proposed: (histogram.proposed_count as u64).into(),
+            orphan: (histogram.orphan_count as u64).into(),
+        })
+    }
+
+    fn test_tx_pool_accept(&self, txs: Vec<Transaction>) -> BoxFuture<Vec<TxPoolAcceptResult>> {
+        let txs: Vec<core::TransactionView> = txs
+            .into_iter()
+            .map(|tx| packed::Transaction::from(tx).into_view())
+            .collect();
+        let tx_hashes: Vec<H256> = txs.iter().map(|tx| tx.hash().unpack()).collect();
+
+        let tx_pool = self.shared.tx_pool_controller();
+        let min_fee_rate = tx_pool.config().min_fee_rate;
+        let dry_run_txs = tx_pool.dry_run_txs_async(txs);
+        let test_tx_pool_accept = async move {
+            let dry_run_txs = dry_run_txs.await.map_err(|e| {
+                error!("send dry_run_txs request error {}", e);
+                Error::internal_error()
+            })?;
+            Ok(tx_hashes
+                .into_iter()
+                .zip(dry_run_txs.into_iter())
+                .map(|(tx_hash, result)| match result {
+                    Ok(cache_entry) => TxPoolAcceptResult {
+                        tx_hash,
+                        allowed: true,
+                        cycles: Some(cache_entry.cycles.into()),
+                        fee: Some(cache_entry.fee.into()),
+                        reject_reason: None,
+                    },
+                    Err(e) => TxPoolAcceptResult {
+                        tx_hash,
+                        allowed: false,
+                        cycles: None,
+                        fee: None,
+                        reject_reason: Some(submit_tx_error(&e, min_fee_rate).message),
+                    },
+                })
+                .collect())
+        };
+        Box::new(test_tx_pool_accept.boxed().compat())
+    }
+
+    fn set_tx_pool_config(&self, limits: TxPoolLimits) -> Result<()> {
+        let tx_pool = self.shared.tx_pool_controller();
+        let mut config = tx_pool.config();
+        if let Some(max_mem_size) = limits.max_mem_size {
+            config.max_mem_size = max_mem_size.value() as usize;
+        }
+        if let Some(max_cycles) = limits.max_cycles {
+            config.max_cycles = max_cycles.value();
+        }
+        if let Some(min_fee_rate) = limits.min_fee_rate {
+            config.min_fee_rate = FeeRate::from_u64(min_fee_rate.value());
+        }
+        tx_pool.update_config(config).map_err(|e| {
+            error!("send update_config request error {}", e);
+            Error::internal_error()
+        })
+    }
+
+    fn get_transaction_status(&self, hash: H256) -> Result<Option<LocalTxStatus>> {
+        let tx_pool = self.shared.tx_pool_controller();
+        let status = tx_pool.get_local_tx_status(hash.pack()).map_err(|e| {
+            error!("send get_local_tx_status request error {}", e);
+            Error::internal_error()
+        })?;
+        Ok(status.map(|status| {
+            let (status, block_hash, reason) = match status {
+                TrackedStatus::Pending => (Status::Pending, None, None),
+                TrackedStatus::Proposed => (Status::Proposed, None, None),
+                TrackedStatus::Committed(block_hash) => {
+                    (Status::Committed, Some(block_hash.unpack()), None)
+                }
+                TrackedStatus::Rejected(reason) => (Status::Rejected, None, Some(reason)),
+            };
+            LocalTxStatus {
+                status,
+                block_hash,
+                reason,
+            }
+        }))
+    }
+}
+
+fn submit_tx_error(e: &CKBError, min_fee_rate: FeeRate) -> Error {
+    if let Some(e) = e.downcast_ref::<SubmitTxError>() {
+        match *e {
+            SubmitTxError::LowFeeRate { required, got } => {
+                return RPCError::custom(
+                    RPCError::PolicyRejected,
+                    format!(
+                        "transaction fee rate lower than min_fee_rate: {} shannons/KB, min fee for current tx: {}, got: {}",
+                        min_fee_rate, required, got,
+                    ),
+                );
+            }
+            SubmitTxError::DustOutput {
+                index,
+                required,
+                got,
+            } => {
+                return RPCError::custom(
+                    RPCError::PolicyRejected,
+                    format!(
+                        "transaction output {} capacity lower than min_output_capacity: {} shannons, got: {}",
+                        index, required, got,
+                    ),
+                );
+            }
+            SubmitTxError::ExceededMaximumTxSize { limit, got } => {
+                return RPCError::custom(
+                    RPCError::PolicyRejected,
+                    format!(
+                        "transaction size exceeded max_tx_size: {} bytes, got: {}",
+                        limit, got,
+                    ),
+                );
+            }
+            SubmitTxError::ExceededMaximumAncestorsCount => {
+                return RPCError::custom(
+                    RPCError::Invalid,
+                    "transaction exceeded maximum ancestors count limit, try send it later"
+                        .to_string(),
+                );
+            }
+            SubmitTxError::ExceededMaximumAncestorsSize => {
+                return RPCError::custom(
+                    RPCError::Invalid,
+                    "transaction exceeded maximum ancestors size limit, try send it later"
+                        .to_string(),
+                );
+            }
+            SubmitTxError::ExceededMaximumDescendantsCount
+            | SubmitTxError::ExceededMaximumDescendantsSize => {
+                return RPCError::custom(
+                    RPCError::Invalid,
+                    "transaction exceeded maximum descendants limit of its in-pool ancestors, try send it later".to_string(),
+                );
+            }
+        }
+    }
+    RPCError::custom_with_error(RPCError::Invalid, format!("{:#}", e), e)
 }
 
 struct DefaultOutputsValidator<'a> {
@@ -284,9 +459,31 @@ fn extract_since_from_secp256k1_blake160_multisig_all_args(script: &packed::Scri
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ckb_shared::shared::SharedBuilder;
     use ckb_test_chain_utils::ckb_testnet_consensus;
     use ckb_types::{core, packed};
 
+    #[test]
+    fn test_set_tx_pool_config() {
+        let (shared, _table) = SharedBuilder::default().build().unwrap();
+        let sync_shared = Arc::new(SyncShared::new(shared.clone()));
+        let rpc = PoolRpcImpl::new(shared.clone(), sync_shared, true);
+        let origin = shared.tx_pool_controller().config();
+
+        rpc.set_tx_pool_config(TxPoolLimits {
+            max_cycles: Some((origin.max_cycles * 2).into()),
+            min_fee_rate: Some(4242u64.into()),
+            ..Default::default()
+        })
+        .unwrap();
+        let config = shared.tx_pool_controller().config();
+        assert_eq!(config.max_cycles, origin.max_cycles * 2);
+        assert_eq!(config.min_fee_rate, FeeRate::from_u64(4242));
+        // the absent limits are left unchanged
+        assert_eq!(config.max_mem_size, origin.max_mem_size);
+        assert_eq!(rpc.tx_pool_info().unwrap().min_fee_rate.value(), 4242);
+    }
+
     #[test]
     fn test_default_outputs_validator() {
         let consensus = ckb_testnet_consensus();
@@ -0,0 +1,143 @@
+use crate::error::RPCError;
+use crate::module::experiment::DryRunner;
+use ckb_jsonrpc_types::{ScriptGroupType, Transaction};
+use ckb_logger::{error, info};
+use ckb_script::{
+    ScriptDebugger, ScriptGroupType as CoreScriptGroupType, TransactionScriptsVerifier,
+};
+use ckb_shared::shared::Shared;
+use ckb_store::data_loader_wrapper::DataLoaderWrapper;
+use ckb_types::{core::cell::resolve_transaction, packed, prelude::*, H256};
+use jsonrpc_core::Result;
+use jsonrpc_derive::rpc;
+use std::collections::HashSet;
+use std::io::ErrorKind;
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Only dev chains may run scripts under the debugger
+const DEV_CHAIN_ID: &str = "ckb_dev";
+// The debugger gives up if no client connects in time, so an abandoned session does not keep
+// its thread and port forever
+const ACCEPT_TIMEOUT: Duration = Duration::from_secs(60);
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[rpc(server)]
+pub trait ScriptDebuggerRpc {
+    // Run the given script group of a transaction step by step, the debugger
+    // accepts one connection on `listen_address`, which must be a loopback address,
+    // within 60 seconds.
+    #[rpc(name = "_debug_transaction_script")]
+    fn debug_transaction_script(
+        &self,
+        _tx: Transaction,
+        _script_group_type: ScriptGroupType,
+        _script_hash: H256,
+        _listen_address: String,
+    ) -> Result<()>;
+}
+
+pub(crate) struct ScriptDebuggerRpcImpl {
+    pub shared: Shared,
+}
+
+impl ScriptDebuggerRpc for ScriptDebuggerRpcImpl {
+    fn debug_transaction_script(
+        &self,
+        tx: Transaction,
+        script_group_type: ScriptGroupType,
+        script_hash: H256,
+        listen_address: String,
+    ) -> Result<()> {
+        if self.shared.consensus().id != DEV_CHAIN_ID {
+            return Err(RPCError::custom(
+                RPCError::Invalid,
+                "script debugger is only available on dev chains".to_owned(),
+            ));
+        }
+        let address: SocketAddr = listen_address.parse().map_err(|err| {
+            RPCError::custom(RPCError::Invalid, format!("{}: {}", listen_address, err))
+        })?;
+        if !address.ip().is_loopback() {
+            return Err(RPCError::custom(
+                RPCError::Invalid,
+                "script debugger must listen on a loopback address".to_owned(),
+            ));
+        }
+
+        let tx: packed::Transaction = tx.into();
+        let dry_runner = DryRunner::new(&self.shared);
+        let resolved = resolve_transaction(
+            tx.into_view(),
+            &mut HashSet::new(),
+            &dry_runner,
+            &dry_runner,
+        )
+        .map_err(|err| RPCError::custom(RPCError::Invalid, format!("{:?}", err)))?;
+        let listener = TcpListener::bind(address)
+            .and_then(|listener| listener.set_nonblocking(true).map(|_| listener))
+            .map_err(|err| RPCError::custom(RPCError::Invalid, format!("{}", err)))?;
+
+        let snapshot = Arc::clone(&self.shared.snapshot());
+        let script_group_type = match script_group_type {
+            ScriptGroupType::Lock => CoreScriptGroupType::Lock,
+            ScriptGroupType::Type => CoreScriptGroupType::Type,
+        };
+        let script_hash: packed::Byte32 = script_hash.pack();
+        thread::Builder::new()
+            .name("ScriptDebugger".to_owned())
+            .spawn(move || {
+                let stream = match accept(&listener, ACCEPT_TIMEOUT) {
+                    Ok(Some(stream)) => stream,
+                    Ok(None) => {
+                        info!(
+                            "script debugger: no client connected to {} in {:?}",
+                            address, ACCEPT_TIMEOUT
+                        );
+                        return;
+                    }
+                    Err(err) => {
+                        error!("script debugger accept error: {}", err);
+                        return;
+                    }
+                };
+                let max_cycles = snapshot.consensus().max_block_cycles;
+                let data_loader = DataLoaderWrapper::new(&*snapshot);
+                let verifier = TransactionScriptsVerifier::new(&resolved, &data_loader);
+                match ScriptDebugger::new(&verifier).serve(
+                    &script_group_type,
+                    &script_hash,
+                    max_cycles,
+                    stream,
+                ) {
+                    Ok(cycles) => info!("script debugger finished, cycles: {}", cycles),
+                    Err(err) => info!("script debugger finished with error: {}", err),
+                }
+            })
+            .map_err(|err| RPCError::custom(RPCError::Invalid, format!("{}", err)))?;
+        Ok(())
+    }
+}
+
+// Waits for a connection on the non-blocking listener until the timeout, the stream is switched
+// back to blocking for the debugger.
+fn accept(listener: &TcpListener, timeout: Duration) -> std::io::Result<Option<TcpStream>> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                stream.set_nonblocking(false)?;
+                return Ok(Some(stream));
+            }
+            Err(ref err) if err.kind() == ErrorKind::WouldBlock => {
+                if Instant::now() >= deadline {
+                    return Ok(None);
+                }
+                thread::sleep(ACCEPT_POLL_INTERVAL);
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
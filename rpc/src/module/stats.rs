@@ -1,4 +1,9 @@
-use ckb_jsonrpc_types::{AlertMessage, ChainInfo, PeerState};
+use ckb_health::HealthRegistry;
+use ckb_jsonrpc_types::{
+    AlertMessage, ChainInfo, HealthDetail, HealthInfo, HealthStatus, MemoryUsage, PeerState,
+    ServiceHealth, StructureMemoryUsage,
+};
+use ckb_memory_tracker::MemoryEstimators;
 use ckb_network_alert::notifier::Notifier as AlertNotifier;
 use ckb_shared::shared::Shared;
 use ckb_sync::Synchronizer;
@@ -15,12 +20,20 @@ pub trait StatsRpc {
 
     #[rpc(name = "get_peers_state")]
     fn get_peers_state(&self) -> Result<Vec<PeerState>>;
+
+    #[rpc(name = "get_health")]
+    fn get_health(&self) -> Result<HealthInfo>;
+
+    #[rpc(name = "get_memory_usage")]
+    fn get_memory_usage(&self) -> Result<MemoryUsage>;
 }
 
 pub(crate) struct StatsRpcImpl {
     pub shared: Shared,
     pub synchronizer: Synchronizer,
     pub alert_notifier: Arc<Mutex<AlertNotifier>>,
+    pub health: HealthRegistry,
+    pub memory_estimators: MemoryEstimators,
 }
 
 impl StatsRpc for StatsRpcImpl {
@@ -71,4 +84,51 @@ impl StatsRpc for StatsRpcImpl {
             .map(|(peer, blocks)| PeerState::new(peer.value(), 0, blocks.len()))
             .collect())
     }
+
+    fn get_health(&self) -> Result<HealthInfo> {
+        let report = self.health.report();
+        Ok(HealthInfo {
+            status: convert_status(report.status),
+            services: report
+                .services
+                .into_iter()
+                .map(|service| ServiceHealth {
+                    name: service.name,
+                    status: convert_status(service.status),
+                    reason: service.reason,
+                    details: service
+                        .details
+                        .into_iter()
+                        .map(|(key, value)| HealthDetail { key, value })
+                        .collect(),
+                })
+                .collect(),
+        })
+    }
+
+    fn get_memory_usage(&self) -> Result<MemoryUsage> {
+        let allocator_stats = ckb_memory_tracker::allocator_stats();
+        Ok(MemoryUsage {
+            allocated: allocator_stats.map(|stats| stats.allocated.into()),
+            resident: allocator_stats.map(|stats| stats.resident.into()),
+            structures: self
+                .memory_estimators
+                .estimate()
+                .into_iter()
+                .map(|estimate| StructureMemoryUsage {
+                    name: estimate.name,
+                    entries: (estimate.entries as u64).into(),
+                    bytes: (estimate.bytes as u64).into(),
+                })
+                .collect(),
+        })
+    }
+}
+
+fn convert_status(status: ckb_health::HealthStatus) -> HealthStatus {
+    match status {
+        ckb_health::HealthStatus::Healthy => HealthStatus::Healthy,
+        ckb_health::HealthStatus::Degraded => HealthStatus::Degraded,
+        ckb_health::HealthStatus::Unhealthy => HealthStatus::Unhealthy,
+    }
 }
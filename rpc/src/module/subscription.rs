@@ -1,4 +1,4 @@
-use ckb_logger::error;
+use ckb_logger::{error, warn};
 use ckb_notify::NotifyController;
 use crossbeam_channel::select;
 use jsonrpc_core::{futures::Future, Metadata, Result};
@@ -45,6 +45,10 @@ impl PubSubMetadata for SubscriptionSession {
 pub enum Topic {
     NewTipHeader,
     NewTipBlock,
+    NewTransaction,
+    Reorg,
+    WatchedTransaction,
+    RejectedTransaction,
 }
 
 #[allow(clippy::needless_return)]
@@ -127,8 +131,14 @@ impl SubscriptionRpc for SubscriptionRpcImpl {
 
 impl SubscriptionRpcImpl {
     pub fn new<S: ToString>(notify_controller: NotifyController, thread_name: Option<S>) -> Self {
-        let new_block_receiver =
-            notify_controller.subscribe_new_block(thread_name.as_ref().unwrap().to_string());
+        let name = thread_name.as_ref().unwrap().to_string();
+        let new_block_subscription = notify_controller.subscribe_new_block(&name);
+        let new_transaction_subscription = notify_controller.subscribe_new_transaction(&name);
+        let reorg_subscription = notify_controller.subscribe_reorg(&name);
+        let watched_transaction_subscription =
+            notify_controller.subscribe_watched_transaction(&name);
+        let rejected_transaction_subscription =
+            notify_controller.subscribe_rejected_transaction(&name);
 
         let subscription_rpc_impl = SubscriptionRpcImpl::default();
         let subscribers = Arc::clone(&subscription_rpc_impl.subscribers);
@@ -140,8 +150,9 @@ impl SubscriptionRpcImpl {
         thread_builder
             .spawn(move || loop {
                 select! {
-                    recv(new_block_receiver) -> msg => match msg {
+                    recv(new_block_subscription.receiver()) -> msg => match msg {
                         Ok(block) => {
+                            warn_lagged("new_block", new_block_subscription.take_lagged());
                             let subscribers = subscribers.read().expect("acquiring subscribers read lock");
                             if let Some(new_tip_header_subscribers) = subscribers.get(&Topic::NewTipHeader) {
                                 let header: ckb_jsonrpc_types::HeaderView  = block.header().into();
@@ -163,6 +174,74 @@ impl SubscriptionRpcImpl {
                             break;
                         },
                     }
+                    recv(new_transaction_subscription.receiver()) -> msg => match msg {
+                        Ok(tx) => {
+                            warn_lagged("new_transaction", new_transaction_subscription.take_lagged());
+                            let subscribers = subscribers.read().expect("acquiring subscribers read lock");
+                            if let Some(new_transaction_subscribers) = subscribers.get(&Topic::NewTransaction) {
+                                let tx: ckb_jsonrpc_types::TransactionView = tx.into();
+                                let json_string = Ok(serde_json::to_string(&tx).expect("serialization should be ok"));
+                                for sink in new_transaction_subscribers.values() {
+                                    let _ = sink.notify(json_string.clone()).wait();
+                                }
+                            }
+                        },
+                        _ => {
+                            error!("new_transaction_receiver closed");
+                            break;
+                        },
+                    }
+                    recv(reorg_subscription.receiver()) -> msg => match msg {
+                        Ok(reorg) => {
+                            warn_lagged("reorg", reorg_subscription.take_lagged());
+                            let subscribers = subscribers.read().expect("acquiring subscribers read lock");
+                            if let Some(reorg_subscribers) = subscribers.get(&Topic::Reorg) {
+                                let reorg: ckb_jsonrpc_types::ReorgEvent = reorg.into();
+                                let json_string = Ok(serde_json::to_string(&reorg).expect("serialization should be ok"));
+                                for sink in reorg_subscribers.values() {
+                                    let _ = sink.notify(json_string.clone()).wait();
+                                }
+                            }
+                        },
+                        _ => {
+                            error!("reorg_receiver closed");
+                            break;
+                        },
+                    }
+                    recv(watched_transaction_subscription.receiver()) -> msg => match msg {
+                        Ok(watched_transaction) => {
+                            warn_lagged("watched_transaction", watched_transaction_subscription.take_lagged());
+                            let subscribers = subscribers.read().expect("acquiring subscribers read lock");
+                            if let Some(watched_transaction_subscribers) = subscribers.get(&Topic::WatchedTransaction) {
+                                let watched_transaction: ckb_jsonrpc_types::WatchedTransaction = watched_transaction.into();
+                                let json_string = Ok(serde_json::to_string(&watched_transaction).expect("serialization should be ok"));
+                                for sink in watched_transaction_subscribers.values() {
+                                    let _ = sink.notify(json_string.clone()).wait();
+                                }
+                            }
+                        },
+                        _ => {
+                            error!("watched_transaction_receiver closed");
+                            break;
+                        },
+                    }
+                    recv(rejected_transaction_subscription.receiver()) -> msg => match msg {
+                        Ok(rejected_transaction) => {
+                            warn_lagged("rejected_transaction", rejected_transaction_subscription.take_lagged());
+                            let subscribers = subscribers.read().expect("acquiring subscribers read lock");
+                            if let Some(rejected_transaction_subscribers) = subscribers.get(&Topic::RejectedTransaction) {
+                                let rejected_transaction: ckb_jsonrpc_types::RejectedTransaction = rejected_transaction.into();
+                                let json_string = Ok(serde_json::to_string(&rejected_transaction).expect("serialization should be ok"));
+                                for sink in rejected_transaction_subscribers.values() {
+                                    let _ = sink.notify(json_string.clone()).wait();
+                                }
+                            }
+                        },
+                        _ => {
+                            error!("rejected_transaction_receiver closed");
+                            break;
+                        },
+                    }
                 }
             })
             .expect("Start SubscriptionRpc thread failed");
@@ -170,3 +249,12 @@ impl SubscriptionRpcImpl {
         subscription_rpc_impl
     }
 }
+
+fn warn_lagged(topic: &str, lagged: u64) {
+    if lagged > 0 {
+        warn!(
+            "subscription {} lags behind, {} events are not pushed",
+            topic, lagged
+        );
+    }
+}
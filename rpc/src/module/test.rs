@@ -1,6 +1,7 @@
 use crate::error::RPCError;
 use ckb_chain::{chain::ChainController, switch::Switch};
-use ckb_jsonrpc_types::{Block, BlockView, Cycle, Transaction};
+use ckb_clock::MockableClock;
+use ckb_jsonrpc_types::{Block, BlockView, Cycle, Timestamp, Transaction};
 use ckb_logger::error;
 use ckb_network::NetworkController;
 use ckb_shared::shared::Shared;
@@ -29,12 +30,19 @@ pub trait IntegrationTestRpc {
 
     #[rpc(name = "get_fork_block")]
     fn get_fork_block(&self, _hash: H256) -> Result<Option<BlockView>>;
+
+    // Freezes the clock of the node at the timestamp in milliseconds, or restores the system
+    // clock when it is null.
+    // curl -d '{"id": 2, "jsonrpc": "2.0", "method":"set_mock_time","params": ["0x16e7e2ad000"]}' -H 'content-type:application/json' 'http://localhost:8114'
+    #[rpc(name = "set_mock_time")]
+    fn set_mock_time(&self, timestamp: Option<Timestamp>) -> Result<()>;
 }
 
 pub(crate) struct IntegrationTestRpcImpl {
     pub network_controller: NetworkController,
     pub shared: Shared,
     pub chain: ChainController,
+    pub clock: MockableClock,
 }
 
 impl IntegrationTestRpc for IntegrationTestRpcImpl {
@@ -97,4 +105,10 @@ impl IntegrationTestRpc for IntegrationTestRpcImpl {
 
         Ok(snapshot.get_block(&hash.pack()).map(Into::into))
     }
+
+    fn set_mock_time(&self, timestamp: Option<Timestamp>) -> Result<()> {
+        self.clock
+            .set_mock_time(timestamp.map(|timestamp| timestamp.value()));
+        Ok(())
+    }
 }
@@ -94,4 +94,15 @@ impl RpcServer {
     pub fn http_address(&self) -> &SocketAddr {
         self.http.address()
     }
+
+    /// Stops accepting the requests, the requests in processing may still be running.
+    pub fn close(self) {
+        self.http.close();
+        if let Some(tcp) = self._tcp {
+            tcp.close();
+        }
+        if let Some(ws) = self._ws {
+            ws.close();
+        }
+    }
 }
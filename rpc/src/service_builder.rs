@@ -1,21 +1,27 @@
+use crate::middleware::MetricsMiddleware;
 use crate::module::{
     AlertRpc, AlertRpcImpl, ChainRpc, ChainRpcImpl, DebugRpc, DebugRpcImpl, ExperimentRpc,
     ExperimentRpcImpl, IndexerRpc, IndexerRpcImpl, IntegrationTestRpc, IntegrationTestRpcImpl,
     MinerRpc, MinerRpcImpl, NetworkRpc, NetworkRpcImpl, PoolRpc, PoolRpcImpl, StatsRpc,
     StatsRpcImpl,
 };
+#[cfg(feature = "script-debugger")]
+use crate::module::{ScriptDebuggerRpc, ScriptDebuggerRpcImpl};
 use crate::IoHandler;
 use ckb_app_config::IndexerConfig;
 use ckb_app_config::RpcConfig;
 use ckb_chain::chain::ChainController;
-use ckb_fee_estimator::FeeRate;
-use ckb_indexer::DefaultIndexerStore;
+use ckb_clock::MockableClock;
+use ckb_health::HealthRegistry;
+use ckb_indexer::{DefaultIndexerStore, ScriptIndexer};
+use ckb_memory_tracker::MemoryEstimators;
 use ckb_network::NetworkController;
 use ckb_network_alert::{notifier::Notifier as AlertNotifier, verifier::Verifier as AlertVerifier};
 use ckb_shared::shared::Shared;
 use ckb_sync::SyncShared;
 use ckb_sync::Synchronizer;
 use ckb_util::Mutex;
+use jsonrpc_core::MetaIoHandler;
 use std::sync::Arc;
 
 pub struct ServiceBuilder<'a> {
@@ -27,7 +33,7 @@ impl<'a> ServiceBuilder<'a> {
     pub fn new(config: &'a RpcConfig) -> Self {
         Self {
             config,
-            io_handler: IoHandler::default(),
+            io_handler: IoHandler::new(MetaIoHandler::with_middleware(MetricsMiddleware)),
         }
     }
     pub fn enable_chain(mut self, shared: Shared) -> Self {
@@ -44,12 +50,10 @@ impl<'a> ServiceBuilder<'a> {
         mut self,
         shared: Shared,
         sync_shared: Arc<SyncShared>,
-        min_fee_rate: FeeRate,
         reject_ill_transactions: bool,
     ) -> Self {
         let rpc_method =
-            PoolRpcImpl::new(shared, sync_shared, min_fee_rate, reject_ill_transactions)
-                .to_delegate();
+            PoolRpcImpl::new(shared, sync_shared, reject_ill_transactions).to_delegate();
         if self.config.pool_enable() {
             self.io_handler.extend_with(rpc_method);
         } else {
@@ -65,12 +69,7 @@ impl<'a> ServiceBuilder<'a> {
         chain: ChainController,
         enable: bool,
     ) -> Self {
-        let rpc_method = MinerRpcImpl {
-            shared,
-            chain,
-            network_controller,
-        }
-        .to_delegate();
+        let rpc_method = MinerRpcImpl::new(network_controller, shared, chain).to_delegate();
         if enable && self.config.miner_enable() {
             self.io_handler.extend_with(rpc_method);
         } else {
@@ -79,8 +78,16 @@ impl<'a> ServiceBuilder<'a> {
         self
     }
 
-    pub fn enable_net(mut self, network_controller: NetworkController) -> Self {
-        let rpc_method = NetworkRpcImpl { network_controller }.to_delegate();
+    pub fn enable_net(
+        mut self,
+        network_controller: NetworkController,
+        sync_shared: Arc<SyncShared>,
+    ) -> Self {
+        let rpc_method = NetworkRpcImpl {
+            network_controller,
+            sync_shared,
+        }
+        .to_delegate();
         if self.config.net_enable() {
             self.io_handler.extend_with(rpc_method);
         } else {
@@ -94,11 +101,15 @@ impl<'a> ServiceBuilder<'a> {
         shared: Shared,
         synchronizer: Synchronizer,
         alert_notifier: Arc<Mutex<AlertNotifier>>,
+        health: HealthRegistry,
+        memory_estimators: MemoryEstimators,
     ) -> Self {
         let rpc_method = StatsRpcImpl {
             shared,
             synchronizer,
             alert_notifier,
+            health,
+            memory_estimators,
         }
         .to_delegate();
         if self.config.stats_enable() {
@@ -124,11 +135,13 @@ impl<'a> ServiceBuilder<'a> {
         shared: Shared,
         network_controller: NetworkController,
         chain: ChainController,
+        clock: MockableClock,
     ) -> Self {
         let rpc_method = IntegrationTestRpcImpl {
             shared,
             network_controller,
             chain,
+            clock,
         }
         .to_delegate();
         if self.config.integration_test_enable() {
@@ -156,9 +169,31 @@ impl<'a> ServiceBuilder<'a> {
     }
 
     pub fn enable_indexer(mut self, indexer_config: &IndexerConfig, shared: Shared) -> Self {
-        let store = DefaultIndexerStore::new(indexer_config, shared);
+        let store = DefaultIndexerStore::new(indexer_config, shared.clone());
+        let script_indexer = if indexer_config.index_scripts {
+            let script_indexer = store.script_indexer();
+            let new_block_receiver = shared
+                .notify_controller()
+                .subscribe_new_block("script_indexer")
+                .into_receiver();
+            script_indexer
+                .clone()
+                .start(Some("ScriptIndexer"), new_block_receiver);
+            Some(script_indexer)
+        } else {
+            None
+        };
+        let watch_list = if indexer_config.watch_list {
+            let watch_list = store.watch_list();
+            watch_list.clone().start(Some("WatchList"));
+            Some(watch_list)
+        } else {
+            None
+        };
         let rpc_method = IndexerRpcImpl {
-            store: store.clone(),
+            store: Some(store.clone()),
+            script_indexer,
+            watch_list,
         }
         .to_delegate();
         if self.config.indexer_enable() {
@@ -170,13 +205,46 @@ impl<'a> ServiceBuilder<'a> {
         self
     }
 
-    pub fn enable_debug(mut self) -> Self {
+    /// Serves the script index of the standalone indexer, the lock hash index methods return
+    /// errors since there is no local chain store to follow.
+    pub fn enable_standalone_indexer(mut self, script_indexer: ScriptIndexer) -> Self {
+        let rpc_method = IndexerRpcImpl::<DefaultIndexerStore> {
+            store: None,
+            script_indexer: Some(script_indexer),
+            watch_list: None,
+        }
+        .to_delegate();
+        if self.config.indexer_enable() {
+            self.io_handler.extend_with(rpc_method)
+        } else {
+            self.update_disabled_methods("Indexer", rpc_method);
+        }
+        self
+    }
+
+    pub fn enable_debug(mut self, shared: Shared, chain: ChainController) -> Self {
         if self.config.debug_enable() {
-            self.io_handler.extend_with(DebugRpcImpl {}.to_delegate());
+            self.io_handler.extend_with(
+                DebugRpcImpl {
+                    chain,
+                    shared: shared.clone(),
+                }
+                .to_delegate(),
+            );
+            self.enable_script_debugger(shared);
         }
         self
     }
 
+    #[cfg(feature = "script-debugger")]
+    fn enable_script_debugger(&mut self, shared: Shared) {
+        self.io_handler
+            .extend_with(ScriptDebuggerRpcImpl { shared }.to_delegate());
+    }
+
+    #[cfg(not(feature = "script-debugger"))]
+    fn enable_script_debugger(&mut self, _shared: Shared) {}
+
     fn update_disabled_methods<I, M>(&mut self, module: &str, rpc_method: I)
     where
         I: IntoIterator<Item = (String, M)>,
@@ -3,14 +3,16 @@ use crate::module::{
     MinerRpcImpl, NetworkRpc, NetworkRpcImpl, PoolRpc, PoolRpcImpl, StatsRpc, StatsRpcImpl,
 };
 use crate::RpcServer;
-use ckb_app_config::{IndexerConfig, NetworkAlertConfig, NetworkConfig};
+use ckb_app_config::{IndexerConfig, NetworkAlertConfig, NetworkConfig, TxPoolConfig};
 use ckb_chain::chain::{ChainController, ChainService};
 use ckb_chain_spec::consensus::{Consensus, ConsensusBuilder};
 use ckb_dao::DaoCalculator;
 use ckb_dao_utils::genesis_dao_data;
 use ckb_fee_estimator::FeeRate;
+use ckb_health::HealthRegistry;
 use ckb_indexer::{DefaultIndexerStore, IndexerStore};
 use ckb_jsonrpc_types::{Block as JsonBlock, Uint64};
+use ckb_memory_tracker::MemoryEstimators;
 use ckb_network::{NetworkService, NetworkState};
 use ckb_network_alert::alert_relayer::AlertRelayer;
 use ckb_notify::NotifyService;
@@ -140,8 +142,11 @@ fn next_block(shared: &Shared, parent: &HeaderView) -> BlockView {
 
 // Setup the running environment
 fn setup_node(height: u64) -> (Shared, ChainController, RpcServer) {
+    let mut tx_pool_config = TxPoolConfig::default();
+    tx_pool_config.min_fee_rate = FeeRate::zero();
     let (shared, table) = SharedBuilder::default()
         .consensus(always_success_consensus())
+        .tx_pool_config(tx_pool_config)
         .build()
         .unwrap();
     let chain_controller = ChainService::new(shared.clone(), table).start::<&str>(None);
@@ -226,12 +231,11 @@ fn setup_node(height: u64) -> (Shared, ChainController, RpcServer) {
         }
         .to_delegate(),
     );
-    io.extend_with(
-        PoolRpcImpl::new(shared.clone(), sync_shared, FeeRate::zero(), true).to_delegate(),
-    );
+    io.extend_with(PoolRpcImpl::new(shared.clone(), Arc::clone(&sync_shared), true).to_delegate());
     io.extend_with(
         NetworkRpcImpl {
             network_controller: network_controller.clone(),
+            sync_shared,
         }
         .to_delegate(),
     );
@@ -240,12 +244,16 @@ fn setup_node(height: u64) -> (Shared, ChainController, RpcServer) {
             shared: shared.clone(),
             synchronizer,
             alert_notifier,
+            health: HealthRegistry::new(),
+            memory_estimators: MemoryEstimators::new(),
         }
         .to_delegate(),
     );
     io.extend_with(
         IndexerRpcImpl {
-            store: indexer_store,
+            store: Some(indexer_store),
+            script_indexer: None,
+            watch_list: None,
         }
         .to_delegate(),
     );
@@ -256,12 +264,8 @@ fn setup_node(height: u64) -> (Shared, ChainController, RpcServer) {
         .to_delegate(),
     );
     io.extend_with(
-        MinerRpcImpl {
-            shared: shared.clone(),
-            chain: chain_controller.clone(),
-            network_controller,
-        }
-        .to_delegate(),
+        MinerRpcImpl::new(network_controller, shared.clone(), chain_controller.clone())
+            .to_delegate(),
     );
     let http = ServerBuilder::new(io)
         .cors(DomainsValidation::AllowOnly(vec![
@@ -1,4 +1,6 @@
+use crate::ScriptGroupType;
 use ckb_error::{Error, ErrorKind};
+use ckb_types::{core::Cycle, packed::Byte32};
 use failure::Fail;
 
 #[derive(Fail, Debug, PartialEq, Eq, Clone)]
@@ -11,6 +13,11 @@ pub enum ScriptError {
     #[fail(display = "ExceededMaximumCycles")]
     ExceededMaximumCycles,
 
+    /// The scripts run longer than the local wall time budget, this depends on
+    /// the node's hardware so it doesn't prove the transaction invalid
+    #[fail(display = "ExceededMaximumTime")]
+    ExceededMaximumTime,
+
     /// `script.type_hash` hits multiple cells with different data
     #[fail(display = "MultipleMatches")]
     MultipleMatches,
@@ -29,3 +36,33 @@ impl From<ScriptError> for Error {
         error.context(ErrorKind::Script).into()
     }
 }
+
+/// A script error together with the script group that raised it,
+/// displayed the same as the inner error.
+#[derive(Fail, Debug, PartialEq, Eq, Clone)]
+#[fail(display = "{}", error)]
+pub struct TransactionScriptError {
+    pub error: ScriptError,
+    pub group_type: ScriptGroupType,
+    /// The index of the group within its lock or type groups
+    pub index: usize,
+    pub script_hash: Byte32,
+    /// Cycles consumed by the group until it failed
+    pub cycles: Cycle,
+}
+
+impl TransactionScriptError {
+    /// Non-zero exit code returned by the script, if the script did exit
+    pub fn exit_code(&self) -> Option<i8> {
+        match self.error {
+            ScriptError::ValidationFailure(code) => Some(code),
+            _ => None,
+        }
+    }
+}
+
+impl From<TransactionScriptError> for Error {
+    fn from(error: TransactionScriptError) -> Self {
+        error.context(ErrorKind::Script).into()
+    }
+}
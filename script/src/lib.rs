@@ -1,13 +1,22 @@
 pub mod cost_model;
 mod error;
 mod ill_transaction_checker;
+mod resumable;
+#[cfg(feature = "debugger")]
+mod script_debugger;
 mod syscalls;
 mod type_id;
 mod verify;
 
-pub use crate::error::ScriptError;
+pub use crate::error::{ScriptError, TransactionScriptError};
 pub use crate::ill_transaction_checker::IllTransactionChecker;
-pub use crate::verify::{ScriptGroup, ScriptGroupType, TransactionScriptsVerifier};
+pub use crate::resumable::{ResumableVerification, VerifyProgress};
+#[cfg(feature = "debugger")]
+pub use crate::script_debugger::ScriptDebugger;
+pub use crate::syscalls::TracedTransactions;
+pub use crate::verify::{
+    ScriptGroup, ScriptGroupProfile, ScriptGroupType, TransactionScriptsVerifier,
+};
 
 /// re-export DataLoader
 pub use ckb_script_data_loader::DataLoader;
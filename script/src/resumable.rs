@@ -0,0 +1,143 @@
+use crate::{
+    verify::{internal_error, CoreMachineType, ScriptGroup, TransactionScriptsVerifier},
+    DataLoader, ScriptError, ScriptGroupType,
+};
+use ckb_error::Error;
+use ckb_types::core::Cycle;
+use ckb_vm::{
+    decoder::{build_imac_decoder, Decoder},
+    DefaultMachine, Machine, SupportMachine,
+};
+
+/// Progress of a resumable verification.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VerifyProgress {
+    /// The cycle budget is exhausted, holds the cycles consumed so far
+    Suspended(Cycle),
+    /// All script groups passed, holds the total cycles
+    Completed(Cycle),
+}
+
+// ResumableVerification runs the script groups of a transaction in chunks, it
+// yields to the caller once the cycle budget passed to `resume` is consumed.
+// The machines run on the interpreter, which is several times slower than the
+// default machine, and the verification borrows the transaction, so the node
+// does not use it, it is for the tools stepping through the scripts.
+pub struct ResumableVerification<'a, DL> {
+    verifier: &'a TransactionScriptsVerifier<'a, DL>,
+    // the groups in the order and with the indexes of `script_groups`
+    groups: Vec<(ScriptGroupType, usize, &'a ScriptGroup)>,
+    next_group: usize,
+    current: Option<DefaultMachine<'a, CoreMachineType>>,
+    decoder: Decoder,
+    max_cycles: Cycle,
+    // cycles consumed by finished script groups
+    cycles: Cycle,
+}
+
+impl<'a, DL: DataLoader> ResumableVerification<'a, DL> {
+    pub(crate) fn new(
+        verifier: &'a TransactionScriptsVerifier<'a, DL>,
+        // the groups in the order and with the indexes of `script_groups`
+        groups: Vec<(ScriptGroupType, usize, &'a ScriptGroup)>,
+        max_cycles: Cycle,
+    ) -> Self {
+        ResumableVerification {
+            verifier,
+            groups,
+            next_group: 0,
+            current: None,
+            decoder: build_imac_decoder::<u64>(),
+            max_cycles,
+            cycles: 0,
+        }
+    }
+
+    /// Run until `cycle_budget` more cycles are consumed or all script groups finish. A script
+    /// error is reported with its group the same as `TransactionScriptsVerifier::verify` does.
+    pub fn resume(&mut self, cycle_budget: Cycle) -> Result<VerifyProgress, Error> {
+        let suspend_at = self.consumed_cycles().saturating_add(cycle_budget);
+        loop {
+            let (group_type, index, group) = match self.groups.get(self.next_group) {
+                Some((group_type, index, group)) => (group_type.clone(), *index, *group),
+                None => return Ok(VerifyProgress::Completed(self.cycles)),
+            };
+            if self.current.is_none() {
+                if TransactionScriptsVerifier::<DL>::is_type_id_group(group) {
+                    let cycles = self.verifier.verify_indexed_script_group(
+                        &group_type,
+                        index,
+                        group,
+                        self.max_cycles,
+                    )?;
+                    self.next_group += 1;
+                    self.add_cycles(cycles)?;
+                    continue;
+                }
+                let machine = self
+                    .verifier
+                    .build_stepping_machine(group, self.max_cycles)
+                    .map_err(|e| {
+                        self.verifier
+                            .script_group_error(&group_type, index, group, e, 0)
+                    })?;
+                self.current = Some(machine);
+            }
+
+            let machine = self.current.as_mut().expect("checked above");
+            while machine.running() {
+                if let Err(e) = machine.step(&mut self.decoder) {
+                    let cycles = machine.cycles();
+                    self.current = None;
+                    self.next_group += 1;
+                    return Err(self.verifier.script_group_error(
+                        &group_type,
+                        index,
+                        group,
+                        internal_error(e),
+                        cycles,
+                    ));
+                }
+                if machine.running() && self.cycles.saturating_add(machine.cycles()) >= suspend_at {
+                    return Ok(VerifyProgress::Suspended(
+                        self.cycles.saturating_add(machine.cycles()),
+                    ));
+                }
+            }
+            let (code, cycles) = (machine.exit_code(), machine.cycles());
+            self.current = None;
+            self.next_group += 1;
+            if code != 0 {
+                return Err(self.verifier.script_group_error(
+                    &group_type,
+                    index,
+                    group,
+                    ScriptError::ValidationFailure(code).into(),
+                    cycles,
+                ));
+            }
+            self.add_cycles(cycles)?;
+        }
+    }
+
+    fn consumed_cycles(&self) -> Cycle {
+        let current = self
+            .current
+            .as_ref()
+            .map(|machine| machine.cycles())
+            .unwrap_or(0);
+        self.cycles.saturating_add(current)
+    }
+
+    fn add_cycles(&mut self, cycles: Cycle) -> Result<(), Error> {
+        let current_cycles = self
+            .cycles
+            .checked_add(cycles)
+            .ok_or(ScriptError::ExceededMaximumCycles)?;
+        if current_cycles > self.max_cycles {
+            return Err(ScriptError::ExceededMaximumCycles.into());
+        }
+        self.cycles = current_cycles;
+        Ok(())
+    }
+}
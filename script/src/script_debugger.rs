@@ -0,0 +1,161 @@
+use crate::{
+    verify::{CoreMachineType, ScriptGroupType, TransactionScriptsVerifier},
+    DataLoader, ScriptError,
+};
+use ckb_error::{Error, InternalErrorKind};
+use ckb_types::{core::Cycle, packed::Byte32};
+use ckb_vm::{
+    decoder::build_imac_decoder, CoreMachine, DefaultMachine, Machine, Memory, SupportMachine,
+};
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+
+// Upper bound of bytes returned by a single `memory` command
+const MAX_MEMORY_DUMP: u64 = 4096;
+
+// ScriptDebugger runs a single script group of a transaction step by step,
+// driven by a line based text protocol over a local socket so external
+// debugger UIs can attach to it.
+//
+// Commands, one per line, addresses and lengths are hex or decimal:
+//
+//   break <addr>          set a breakpoint
+//   delete <addr>         remove a breakpoint
+//   step                  execute one instruction
+//   continue              run until a breakpoint is hit or the script exits
+//   registers             dump pc and general purpose registers
+//   memory <addr> <len>   dump memory as hex
+//   quit                  abort the debugging session
+//
+// Every reply is a single line, starting with `ok`, `stopped`, `exited` or `error`.
+pub struct ScriptDebugger<'a, DL> {
+    verifier: &'a TransactionScriptsVerifier<'a, DL>,
+    breakpoints: HashSet<u64>,
+}
+
+impl<'a, DL: DataLoader> ScriptDebugger<'a, DL> {
+    pub fn new(verifier: &'a TransactionScriptsVerifier<'a, DL>) -> Self {
+        ScriptDebugger {
+            verifier,
+            breakpoints: HashSet::new(),
+        }
+    }
+
+    pub fn serve(
+        &mut self,
+        script_group_type: &ScriptGroupType,
+        script_hash: &Byte32,
+        max_cycles: Cycle,
+        stream: TcpStream,
+    ) -> Result<Cycle, Error> {
+        let group = self
+            .verifier
+            .find_script_group(script_group_type, script_hash)
+            .ok_or(ScriptError::InvalidCodeHash)?;
+        let mut machine = self.verifier.build_stepping_machine(group, max_cycles)?;
+
+        let mut decoder = build_imac_decoder::<u64>();
+        let mut writer = stream.try_clone().map_err(io_error)?;
+        let reader = BufReader::new(stream);
+        for line in reader.lines() {
+            let line = line.map_err(io_error)?;
+            let mut words = line.split_whitespace();
+            let reply = match (words.next(), words.next(), words.next()) {
+                (Some("break"), Some(addr), None) => parse_number(addr).map(|addr| {
+                    self.breakpoints.insert(addr);
+                    "ok".to_owned()
+                }),
+                (Some("delete"), Some(addr), None) => parse_number(addr).map(|addr| {
+                    self.breakpoints.remove(&addr);
+                    "ok".to_owned()
+                }),
+                (Some("step"), None, None) => Ok(self.resume(&mut machine, &mut decoder, true)),
+                (Some("continue"), None, None) => {
+                    Ok(self.resume(&mut machine, &mut decoder, false))
+                }
+                (Some("registers"), None, None) => Ok(dump_registers(&machine)),
+                (Some("memory"), Some(addr), Some(len)) => parse_number(addr)
+                    .and_then(|addr| parse_number(len).map(|len| (addr, len)))
+                    .map(|(addr, len)| dump_memory(&mut machine, addr, len)),
+                (Some("quit"), None, None) => break,
+                _ => Err(format!("unknown command: {}", line)),
+            };
+            let reply = reply.unwrap_or_else(|err| format!("error {}", err));
+            writeln!(writer, "{}", reply).map_err(io_error)?;
+            if !machine.running() {
+                break;
+            }
+        }
+
+        if machine.running() {
+            Err(InternalErrorKind::VM
+                .reason("script debugging session aborted")
+                .into())
+        } else if machine.exit_code() == 0 {
+            Ok(machine.cycles())
+        } else {
+            Err(ScriptError::ValidationFailure(machine.exit_code()).into())
+        }
+    }
+
+    fn resume(
+        &self,
+        machine: &mut DefaultMachine<'_, CoreMachineType>,
+        decoder: &mut ckb_vm::decoder::Decoder,
+        single_step: bool,
+    ) -> String {
+        while machine.running() {
+            if let Err(err) = machine.step(decoder) {
+                machine.set_running(false);
+                return format!("error {:?}", err);
+            }
+            if single_step || self.breakpoints.contains(machine.pc()) {
+                break;
+            }
+        }
+        if machine.running() {
+            format!("stopped pc {:#x} cycles {}", machine.pc(), machine.cycles())
+        } else {
+            format!(
+                "exited code {} cycles {}",
+                machine.exit_code(),
+                machine.cycles()
+            )
+        }
+    }
+}
+
+fn dump_registers(machine: &DefaultMachine<'_, CoreMachineType>) -> String {
+    let registers = machine
+        .registers()
+        .iter()
+        .map(|value| format!("{:#x}", value))
+        .collect::<Vec<_>>()
+        .join(" ");
+    format!("ok pc {:#x} registers {}", machine.pc(), registers)
+}
+
+fn dump_memory(machine: &mut DefaultMachine<'_, CoreMachineType>, addr: u64, len: u64) -> String {
+    let mut bytes = String::new();
+    for offset in 0..len.min(MAX_MEMORY_DUMP) {
+        match machine.memory_mut().load8(&addr.wrapping_add(offset)) {
+            Ok(byte) => bytes.push_str(&format!("{:02x}", byte)),
+            Err(err) => return format!("error {:?}", err),
+        }
+    }
+    format!("ok {}", bytes)
+}
+
+fn parse_number(input: &str) -> Result<u64, String> {
+    let parsed = if input.starts_with("0x") {
+        u64::from_str_radix(&input[2..], 16)
+    } else {
+        input.parse()
+    };
+    parsed.map_err(|_| format!("invalid number: {}", input))
+}
+
+fn io_error(error: std::io::Error) -> Error {
+    InternalErrorKind::System.reason(error).into()
+}
@@ -8,13 +8,14 @@ use crate::{
 use byteorder::{LittleEndian, WriteBytesExt};
 use ckb_types::{
     core::{cell::CellMeta, Capacity},
-    packed::CellOutput,
+    packed::{Byte32, CellOutput, Script},
     prelude::*,
 };
 use ckb_vm::{
     registers::{A0, A3, A4, A5, A7},
     Error as VMError, Register, SupportMachine, Syscalls,
 };
+use std::collections::HashMap;
 
 pub struct LoadCell<'a> {
     outputs: &'a [CellMeta],
@@ -22,6 +23,7 @@ pub struct LoadCell<'a> {
     resolved_cell_deps: &'a [CellMeta],
     group_inputs: &'a [usize],
     group_outputs: &'a [usize],
+    script_hashes: Option<&'a HashMap<Script, Byte32>>,
 }
 
 impl<'a> LoadCell<'a> {
@@ -38,9 +40,24 @@ impl<'a> LoadCell<'a> {
             resolved_cell_deps,
             group_inputs,
             group_outputs,
+            script_hashes: None,
         }
     }
 
+    // Reuse the script hashes already computed for the transaction, e.g. while
+    // grouping its scripts, instead of hashing the same script in every group.
+    pub fn with_script_hashes(mut self, script_hashes: &'a HashMap<Script, Byte32>) -> Self {
+        self.script_hashes = Some(script_hashes);
+        self
+    }
+
+    fn script_hash(&self, script: &Script) -> Byte32 {
+        self.script_hashes
+            .and_then(|script_hashes| script_hashes.get(script))
+            .cloned()
+            .unwrap_or_else(|| script.calc_script_hash())
+    }
+
     fn fetch_cell(&self, source: Source, index: usize) -> Result<&'a CellMeta, u8> {
         match source {
             Source::Transaction(SourceEntry::Input) => {
@@ -120,7 +137,7 @@ impl<'a> LoadCell<'a> {
                 (SUCCESS, store_data(machine, data)?)
             }
             CellField::LockHash => {
-                let hash = output.calc_lock_hash();
+                let hash = self.script_hash(&output.lock());
                 let bytes = hash.as_bytes();
                 (SUCCESS, store_data(machine, &bytes)?)
             }
@@ -133,7 +150,7 @@ impl<'a> LoadCell<'a> {
             },
             CellField::TypeHash => match output.type_().to_opt() {
                 Some(type_) => {
-                    let hash = type_.calc_script_hash();
+                    let hash = self.script_hash(&type_);
                     let bytes = hash.as_bytes();
                     (SUCCESS, store_data(machine, &bytes)?)
                 }
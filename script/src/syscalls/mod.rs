@@ -7,6 +7,7 @@ mod load_script;
 mod load_script_hash;
 mod load_tx;
 mod load_witness;
+mod tracer;
 mod utils;
 
 pub use self::debugger::Debugger;
@@ -18,6 +19,7 @@ pub use self::load_script::LoadScript;
 pub use self::load_script_hash::LoadScriptHash;
 pub use self::load_tx::LoadTx;
 pub use self::load_witness::LoadWitness;
+pub use self::tracer::{SyscallTracer, TracedTransactions};
 
 use ckb_vm::Error;
 
@@ -926,6 +928,72 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_load_cached_script_hashes() {
+        let mut machine = DefaultCoreMachine::<u64, SparseMemory<u64>>::default();
+        let size_addr: u64 = 0;
+        let addr: u64 = 100;
+
+        let lock = Script::new_builder()
+            .args(Bytes::from(vec![1]).pack())
+            .hash_type(ScriptHashType::Data.into())
+            .build();
+        let type_ = Script::new_builder()
+            .args(Bytes::from(vec![2]).pack())
+            .hash_type(ScriptHashType::Type.into())
+            .build();
+        let mut input_cell = build_cell_meta(1000, Bytes::new());
+        input_cell.cell_output = input_cell
+            .cell_output
+            .clone()
+            .as_builder()
+            .lock(lock.clone())
+            .type_(Some(type_.clone()).pack())
+            .build();
+        let outputs = vec![];
+        let resolved_inputs = vec![input_cell];
+        let resolved_cell_deps = vec![];
+        let group_inputs = vec![];
+        let group_outputs = vec![];
+        // the cached lock hash is loaded as is, while the type missing in the
+        // cache is still hashed
+        let cached_lock_hash: Byte32 = [7u8; 32].pack();
+        let mut script_hashes = HashMap::default();
+        script_hashes.insert(lock, cached_lock_hash.clone());
+        let mut load_cell = LoadCell::new(
+            &outputs,
+            &resolved_inputs,
+            &resolved_cell_deps,
+            &group_inputs,
+            &group_outputs,
+        )
+        .with_script_hashes(&script_hashes);
+
+        for (field, hash) in &[
+            (CellField::LockHash, cached_lock_hash),
+            (CellField::TypeHash, type_.calc_script_hash()),
+        ] {
+            machine.set_register(A0, addr); // addr
+            machine.set_register(A1, size_addr); // size_addr
+            machine.set_register(A2, 0); // offset
+            machine.set_register(A3, 0); //index
+            machine.set_register(A4, u64::from(Source::Transaction(SourceEntry::Input))); //source: 1 input
+            machine.set_register(A5, *field as u64); //field
+            machine.set_register(A7, LOAD_CELL_BY_FIELD_SYSCALL_NUMBER); // syscall number
+            assert!(machine.memory_mut().store64(&size_addr, &64).is_ok());
+
+            assert!(load_cell.ecall(&mut machine).is_ok());
+            assert_eq!(machine.registers()[A0], u64::from(SUCCESS));
+            assert_eq!(machine.memory_mut().load64(&size_addr), Ok(32));
+            for (i, addr) in (addr..addr + 32).enumerate() {
+                assert_eq!(
+                    machine.memory_mut().load8(&addr),
+                    Ok(u64::from(hash.as_slice()[i]))
+                );
+            }
+        }
+    }
+
     fn _test_load_witness(data: &[u8], source: SourceEntry) -> Result<(), TestCaseError> {
         let mut machine = DefaultCoreMachine::<u64, SparseMemory<u64>>::default();
         let size_addr: u64 = 0;
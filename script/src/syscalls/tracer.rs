@@ -0,0 +1,132 @@
+use crate::syscalls::{
+    DEBUG_PRINT_SYSCALL_NUMBER, LOAD_CELL_BY_FIELD_SYSCALL_NUMBER,
+    LOAD_CELL_DATA_AS_CODE_SYSCALL_NUMBER, LOAD_CELL_DATA_SYSCALL_NUMBER, LOAD_CELL_SYSCALL_NUMBER,
+    LOAD_HEADER_BY_FIELD_SYSCALL_NUMBER, LOAD_HEADER_SYSCALL_NUMBER,
+    LOAD_INPUT_BY_FIELD_SYSCALL_NUMBER, LOAD_INPUT_SYSCALL_NUMBER, LOAD_SCRIPT_HASH_SYSCALL_NUMBER,
+    LOAD_SCRIPT_SYSCALL_NUMBER, LOAD_TRANSACTION_SYSCALL_NUMBER, LOAD_TX_HASH_SYSCALL_NUMBER,
+    LOAD_WITNESS_SYSCALL_NUMBER,
+};
+#[cfg(feature = "logging")]
+use ckb_logger::info;
+use ckb_types::packed::Byte32;
+use ckb_util::RwLock;
+use ckb_vm::{
+    registers::{A0, A1, A2, A3, A4, A5, A7},
+    Error as VMError, Register, SupportMachine, Syscalls,
+};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// The transactions whose syscalls are logged while verifying their scripts, until untraced.
+/// Cloned handles share the same set.
+#[derive(Clone, Default)]
+pub struct TracedTransactions {
+    inner: Arc<RwLock<HashSet<Byte32>>>,
+}
+
+impl TracedTransactions {
+    /// Log every syscall invoked by scripts of the transaction, until untraced.
+    pub fn trace(&self, tx_hash: Byte32) {
+        self.inner.write().insert(tx_hash);
+    }
+
+    /// Stop logging syscalls of the transaction, returns whether it was traced.
+    pub fn untrace(&self, tx_hash: &Byte32) -> bool {
+        self.inner.write().remove(tx_hash)
+    }
+
+    pub fn contains(&self, tx_hash: &Byte32) -> bool {
+        let traced = self.inner.read();
+        !traced.is_empty() && traced.contains(tx_hash)
+    }
+}
+
+// SyscallTracer is installed in front of the real syscalls, it only logs the
+// syscall number and arguments and never handles the ecall itself.
+pub struct SyscallTracer {
+    tx_hash: Byte32,
+    script_hash: Byte32,
+}
+
+impl SyscallTracer {
+    pub fn new(tx_hash: Byte32, script_hash: Byte32) -> SyscallTracer {
+        SyscallTracer {
+            tx_hash,
+            script_hash,
+        }
+    }
+}
+
+fn syscall_name(number: u64) -> &'static str {
+    match number {
+        LOAD_TRANSACTION_SYSCALL_NUMBER => "load_transaction",
+        LOAD_SCRIPT_SYSCALL_NUMBER => "load_script",
+        LOAD_TX_HASH_SYSCALL_NUMBER => "load_tx_hash",
+        LOAD_SCRIPT_HASH_SYSCALL_NUMBER => "load_script_hash",
+        LOAD_CELL_SYSCALL_NUMBER => "load_cell",
+        LOAD_HEADER_SYSCALL_NUMBER => "load_header",
+        LOAD_INPUT_SYSCALL_NUMBER => "load_input",
+        LOAD_WITNESS_SYSCALL_NUMBER => "load_witness",
+        LOAD_CELL_BY_FIELD_SYSCALL_NUMBER => "load_cell_by_field",
+        LOAD_HEADER_BY_FIELD_SYSCALL_NUMBER => "load_header_by_field",
+        LOAD_INPUT_BY_FIELD_SYSCALL_NUMBER => "load_input_by_field",
+        LOAD_CELL_DATA_AS_CODE_SYSCALL_NUMBER => "load_cell_data_as_code",
+        LOAD_CELL_DATA_SYSCALL_NUMBER => "load_cell_data",
+        DEBUG_PRINT_SYSCALL_NUMBER => "debug",
+        _ => "unknown",
+    }
+}
+
+impl<Mac: SupportMachine> Syscalls<Mac> for SyscallTracer {
+    fn initialize(&mut self, _machine: &mut Mac) -> Result<(), VMError> {
+        Ok(())
+    }
+
+    fn ecall(&mut self, machine: &mut Mac) -> Result<bool, VMError> {
+        let registers = machine.registers();
+        let number = registers[A7].to_u64();
+        let args = [A0, A1, A2, A3, A4, A5]
+            .iter()
+            .map(|index| format!("{:#x}", registers[*index].to_u64()))
+            .collect::<Vec<_>>();
+        let message = format!(
+            "syscall trace, transaction: {}, script group: {}, {}({}) args: [{}], cycles: {}",
+            self.tx_hash,
+            self.script_hash,
+            syscall_name(number),
+            number,
+            args.join(", "),
+            machine.cycles()
+        );
+        #[cfg(feature = "logging")]
+        info!("{}", message);
+        #[cfg(not(feature = "logging"))]
+        drop(message);
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ckb_types::{h256, prelude::*, H256};
+
+    #[test]
+    fn test_traced_transactions() {
+        let traced = TracedTransactions::default();
+        let shared = traced.clone();
+        let tx_hash: Byte32 = h256!("0x1").pack();
+        let other_hash: Byte32 = h256!("0x2").pack();
+        assert!(!traced.contains(&tx_hash));
+
+        shared.trace(tx_hash.clone());
+        assert!(traced.contains(&tx_hash));
+        assert!(!traced.contains(&other_hash));
+        // a fresh set does not see the transactions traced by the others
+        assert!(!TracedTransactions::default().contains(&tx_hash));
+
+        assert!(traced.untrace(&tx_hash));
+        assert!(!shared.contains(&tx_hash));
+        assert!(!traced.untrace(&tx_hash));
+    }
+}
@@ -1,13 +1,14 @@
 use crate::{
     cost_model::{instruction_cycles, transferred_byte_cycles},
+    resumable::{ResumableVerification, VerifyProgress},
     syscalls::{
         Debugger, LoadCell, LoadCellData, LoadHeader, LoadInput, LoadScript, LoadScriptHash,
-        LoadTx, LoadWitness,
+        LoadTx, LoadWitness, SyscallTracer,
     },
     type_id::TypeIdSystemScript,
-    DataLoader, ScriptError,
+    DataLoader, ScriptError, TransactionScriptError,
 };
-use ckb_chain_spec::consensus::TYPE_ID_CODE_HASH;
+use ckb_chain_spec::consensus::{VmVersion, TYPE_ID_CODE_HASH};
 use ckb_error::{Error, InternalErrorKind};
 #[cfg(feature = "logging")]
 use ckb_logger::{debug, info};
@@ -23,22 +24,24 @@ use ckb_types::{
 #[cfg(has_asm)]
 use ckb_vm::{
     machine::asm::{AsmCoreMachine, AsmMachine},
-    DefaultMachineBuilder, Error as VMInternalError, InstructionCycleFunc, SupportMachine,
-    Syscalls,
+    DefaultMachine, DefaultMachineBuilder, Error as VMInternalError, InstructionCycleFunc,
+    SupportMachine, Syscalls,
 };
 #[cfg(not(has_asm))]
 use ckb_vm::{
-    DefaultCoreMachine, DefaultMachineBuilder, Error as VMInternalError, InstructionCycleFunc,
-    SparseMemory, SupportMachine, Syscalls, TraceMachine, WXorXMemory,
+    DefaultCoreMachine, DefaultMachine, DefaultMachineBuilder, Error as VMInternalError,
+    InstructionCycleFunc, SparseMemory, SupportMachine, Syscalls, TraceMachine, WXorXMemory,
 };
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::cmp;
+use std::collections::{BTreeMap, HashMap};
 use std::convert::TryFrom;
+use std::time::{Duration, Instant};
 
 #[cfg(has_asm)]
-type CoreMachineType = Box<AsmCoreMachine>;
+pub(crate) type CoreMachineType = Box<AsmCoreMachine>;
 #[cfg(not(has_asm))]
-type CoreMachineType = DefaultCoreMachine<u64, WXorXMemory<u64, SparseMemory<u64>>>;
+pub(crate) type CoreMachineType = DefaultCoreMachine<u64, WXorXMemory<u64, SparseMemory<u64>>>;
 
 // A script group is defined as scripts that share the same hash.
 // A script group will only be executed once per transaction, the
@@ -67,20 +70,39 @@ pub enum ScriptGroupType {
     Type,
 }
 
+// Cycles and wall time consumed by a single script group, index is the
+// position of the group within its lock or type groups in execution order.
+#[derive(Clone, Debug)]
+pub struct ScriptGroupProfile {
+    pub group_type: ScriptGroupType,
+    pub index: usize,
+    pub script_hash: Byte32,
+    pub cycles: Cycle,
+    pub elapsed: Duration,
+    pub vm_version: VmVersion,
+}
+
 // This struct leverages CKB VM to verify transaction inputs.
 // FlatBufferBuilder owned Vec<u8> that grows as needed, in the
 // future, we might refactor this to share buffer to achieve zero-copy
 pub struct TransactionScriptsVerifier<'a, DL> {
     data_loader: &'a DL,
-    debug_printer: Box<dyn Fn(&Byte32, &str)>,
+    debug_printer: Box<dyn Fn(&Byte32, &str) + Send + Sync>,
+    trace_syscalls: bool,
+    vm_version: VmVersion,
 
     outputs: Vec<CellMeta>,
     rtx: &'a ResolvedTransaction,
 
     binaries_by_data_hash: HashMap<Byte32, Bytes>,
     binaries_by_type_hash: HashMap<Byte32, (Bytes, bool)>,
-    lock_groups: HashMap<Byte32, ScriptGroup>,
-    type_groups: HashMap<Byte32, ScriptGroup>,
+    // ordered by the script hashes, so the groups run and are indexed in the same order on
+    // every node
+    lock_groups: BTreeMap<Byte32, ScriptGroup>,
+    type_groups: BTreeMap<Byte32, ScriptGroup>,
+    // hashes of the input locks and the input and output types, computed once
+    // while grouping and shared by the syscalls of all the groups
+    script_hashes: HashMap<Script, Byte32>,
 }
 
 impl<'a, DL: DataLoader> TransactionScriptsVerifier<'a, DL> {
@@ -124,19 +146,29 @@ impl<'a, DL: DataLoader> TransactionScriptsVerifier<'a, DL> {
             }
         }
 
-        let mut lock_groups = HashMap::default();
-        let mut type_groups = HashMap::default();
+        let mut lock_groups = BTreeMap::default();
+        let mut type_groups = BTreeMap::default();
+        // inputs sharing a lock share the script, so it is hashed only once
+        let mut script_hashes: HashMap<Script, Byte32> = HashMap::default();
+        let mut script_hash = |script: &Script| {
+            if let Some(hash) = script_hashes.get(script) {
+                return hash.clone();
+            }
+            let hash = script.calc_script_hash();
+            script_hashes.insert(script.clone(), hash.clone());
+            hash
+        };
         for (i, cell_meta) in resolved_inputs.iter().enumerate() {
             // here we are only pre-processing the data, verify method validates
             // each input has correct script setup.
             let output = &cell_meta.cell_output;
             let lock_group_entry = lock_groups
-                .entry(output.calc_lock_hash())
+                .entry(script_hash(&output.lock()))
                 .or_insert_with(|| ScriptGroup::new(&output.lock()));
             lock_group_entry.input_indices.push(i);
             if let Some(t) = &output.type_().to_opt() {
                 let type_group_entry = type_groups
-                    .entry(t.calc_script_hash())
+                    .entry(script_hash(t))
                     .or_insert_with(|| ScriptGroup::new(&t));
                 type_group_entry.input_indices.push(i);
             }
@@ -144,7 +176,7 @@ impl<'a, DL: DataLoader> TransactionScriptsVerifier<'a, DL> {
         for (i, output) in rtx.transaction.outputs().into_iter().enumerate() {
             if let Some(t) = &output.type_().to_opt() {
                 let type_group_entry = type_groups
-                    .entry(t.calc_script_hash())
+                    .entry(script_hash(t))
                     .or_insert_with(|| ScriptGroup::new(&t));
                 type_group_entry.output_indices.push(i);
             }
@@ -152,12 +184,15 @@ impl<'a, DL: DataLoader> TransactionScriptsVerifier<'a, DL> {
 
         TransactionScriptsVerifier {
             data_loader,
+            trace_syscalls: false,
+            vm_version: VmVersion::default(),
             binaries_by_data_hash,
             binaries_by_type_hash,
             outputs,
             rtx,
             lock_groups,
             type_groups,
+            script_hashes,
             debug_printer: Box::new(
                 #[allow(unused_variables)]
                 |hash: &Byte32, message: &str| {
@@ -168,10 +203,25 @@ impl<'a, DL: DataLoader> TransactionScriptsVerifier<'a, DL> {
         }
     }
 
-    pub fn set_debug_printer<F: Fn(&Byte32, &str) + 'static>(&mut self, func: F) {
+    pub fn set_debug_printer<F: Fn(&Byte32, &str) + Send + Sync + 'static>(&mut self, func: F) {
         self.debug_printer = Box::new(func);
     }
 
+    // Log every syscall with its arguments, e.g. for the transactions in `TracedTransactions`
+    pub fn set_trace_syscalls(&mut self, trace_syscalls: bool) {
+        self.trace_syscalls = trace_syscalls;
+    }
+
+    // Run scripts on the VM version activated at the epoch the transaction is
+    // verified in, see `Consensus::vm_version`.
+    pub fn set_vm_version(&mut self, vm_version: VmVersion) {
+        self.vm_version = vm_version;
+    }
+
+    pub fn vm_version(&self) -> VmVersion {
+        self.vm_version
+    }
+
     #[inline]
     fn inputs(&self) -> CellInputVec {
         self.rtx.transaction.inputs()
@@ -219,6 +269,7 @@ impl<'a, DL: DataLoader> TransactionScriptsVerifier<'a, DL> {
             group_inputs,
             group_outputs,
         )
+        .with_script_hashes(&self.script_hashes)
     }
 
     fn build_load_cell_data(
@@ -292,20 +343,159 @@ impl<'a, DL: DataLoader> TransactionScriptsVerifier<'a, DL> {
     }
 
     pub fn verify(&self, max_cycles: Cycle) -> Result<Cycle, Error> {
+        self.verify_groups(max_cycles, None)
+    }
+
+    // Same as verify, but also records cycles and wall time of every script
+    // group, which is useful when optimizing contracts.
+    pub fn profile(&self, max_cycles: Cycle) -> Result<(Cycle, Vec<ScriptGroupProfile>), Error> {
+        let mut profiles = Vec::new();
+        let cycles = self.verify_groups(max_cycles, Some(&mut profiles))?;
+        Ok((cycles, profiles))
+    }
+
+    // The script hash and wall time of a group are only computed when
+    // `profiles` is given, `verify` doesn't pay for them.
+    fn verify_groups(
+        &self,
+        max_cycles: Cycle,
+        mut profiles: Option<&mut Vec<ScriptGroupProfile>>,
+    ) -> Result<Cycle, Error> {
         let mut cycles: Cycle = 0;
 
         // Now run each script group
-        for group in self.lock_groups.values().chain(self.type_groups.values()) {
-            let cycle = self.verify_script_group(group, max_cycles).map_err(|e| {
-                #[cfg(feature = "logging")]
-                info!(
-                    "Error validating script group {} of transaction {}: {}",
-                    group.script.calc_script_hash(),
-                    self.hash(),
-                    e
-                );
-                e
-            })?;
+        for (group_type, index, group) in self.script_groups() {
+            let cycle = match profiles.as_mut() {
+                Some(profiles) => {
+                    let started_at = Instant::now();
+                    let cycle =
+                        self.verify_indexed_script_group(&group_type, index, group, max_cycles)?;
+                    let profile = ScriptGroupProfile {
+                        group_type,
+                        index,
+                        script_hash: group.script.calc_script_hash(),
+                        cycles: cycle,
+                        elapsed: started_at.elapsed(),
+                        vm_version: self.vm_version,
+                    };
+                    #[cfg(feature = "logging")]
+                    debug!(
+                        "Profile {:?} script group #{} {} of transaction {}: {} cycles in {:?} on VM {:?}",
+                        profile.group_type,
+                        profile.index,
+                        profile.script_hash,
+                        self.hash(),
+                        profile.cycles,
+                        profile.elapsed,
+                        profile.vm_version
+                    );
+                    profiles.push(profile);
+                    cycle
+                }
+                None => self.verify_indexed_script_group(&group_type, index, group, max_cycles)?,
+            };
+            let current_cycles = cycles
+                .checked_add(cycle)
+                .ok_or(ScriptError::ExceededMaximumCycles)?;
+            if current_cycles > max_cycles {
+                return Err(ScriptError::ExceededMaximumCycles.into());
+            }
+            cycles = current_cycles;
+        }
+        Ok(cycles)
+    }
+
+    // The script groups in execution order, the lock groups first, then by
+    // the script hashes, each with its position within the lock or type groups.
+    pub fn script_groups(&self) -> Vec<(ScriptGroupType, usize, &ScriptGroup)> {
+        let lock_groups = self
+            .lock_groups
+            .values()
+            .enumerate()
+            .map(|(index, group)| (ScriptGroupType::Lock, index, group));
+        let type_groups = self
+            .type_groups
+            .values()
+            .enumerate()
+            .map(|(index, group)| (ScriptGroupType::Type, index, group));
+        lock_groups.chain(type_groups).collect()
+    }
+
+    // Run a script group returned by `script_groups`, the groups can be run
+    // independently, even on different threads, and the caller is responsible
+    // for summing up the cycles against `max_cycles`.
+    pub fn verify_indexed_script_group(
+        &self,
+        group_type: &ScriptGroupType,
+        index: usize,
+        group: &ScriptGroup,
+        max_cycles: Cycle,
+    ) -> Result<Cycle, Error> {
+        self.verify_script_group_with_cycles(group, max_cycles)
+            .map_err(|(e, cycles)| self.script_group_error(group_type, index, group, e, cycles))
+    }
+
+    // Attach the group to a script error it raised, so `verify` and the
+    // resumable verification report the same details.
+    pub(crate) fn script_group_error(
+        &self,
+        group_type: &ScriptGroupType,
+        index: usize,
+        group: &ScriptGroup,
+        e: Error,
+        cycles: Cycle,
+    ) -> Error {
+        #[cfg(feature = "logging")]
+        info!(
+            "Error validating script group {} of transaction {}: {}",
+            group.script.calc_script_hash(),
+            self.hash(),
+            e
+        );
+        match e.downcast_ref::<ScriptError>() {
+            Some(error) => TransactionScriptError {
+                error: error.clone(),
+                group_type: group_type.clone(),
+                index,
+                script_hash: group.script.calc_script_hash(),
+                cycles,
+            }
+            .into(),
+            None => e,
+        }
+    }
+
+    // Same as verify, but `check` is called every time a script group runs
+    // out of a cycle budget, which starts at `first_budget` and doubles each
+    // time, the verification is aborted if it returns an error. The group is
+    // then run again from the start with the bigger budget on the default
+    // machine, so the verification costs at most twice as much as `verify`.
+    pub fn verify_with_checks<F: FnMut() -> Result<(), Error>>(
+        &self,
+        max_cycles: Cycle,
+        first_budget: Cycle,
+        mut check: F,
+    ) -> Result<Cycle, Error> {
+        let mut budget = cmp::max(first_budget, 1);
+        let mut cycles: Cycle = 0;
+        for (group_type, index, group) in self.script_groups() {
+            let cycle = loop {
+                let group_max_cycles = cmp::min(budget, max_cycles);
+                match self.verify_script_group_with_cycles(group, group_max_cycles) {
+                    Ok(cycle) => break cycle,
+                    Err((e, _))
+                        if group_max_cycles < max_cycles
+                            && e.downcast_ref::<ScriptError>()
+                                == Some(&ScriptError::ExceededMaximumCycles) =>
+                    {
+                        check()?;
+                        budget = budget.saturating_mul(2);
+                    }
+                    Err((e, cycles)) => {
+                        return Err(self.script_group_error(&group_type, index, group, e, cycles))
+                    }
+                }
+            };
             let current_cycles = cycles
                 .checked_add(cycle)
                 .ok_or(ScriptError::ExceededMaximumCycles)?;
@@ -317,6 +507,28 @@ impl<'a, DL: DataLoader> TransactionScriptsVerifier<'a, DL> {
         Ok(cycles)
     }
 
+    // Verify script groups in chunks of `chunk_cycles`, `on_suspend` is called
+    // with the consumed cycles every time the chunk budget is exhausted, the
+    // verification is aborted if it returns an error.
+    pub fn chunked_verify<F: FnMut(Cycle) -> Result<(), Error>>(
+        &self,
+        max_cycles: Cycle,
+        chunk_cycles: Cycle,
+        mut on_suspend: F,
+    ) -> Result<Cycle, Error> {
+        let mut verification = self.resumable(max_cycles);
+        loop {
+            match verification.resume(chunk_cycles)? {
+                VerifyProgress::Suspended(cycles) => on_suspend(cycles)?,
+                VerifyProgress::Completed(cycles) => return Ok(cycles),
+            }
+        }
+    }
+
+    pub fn resumable(&'a self, max_cycles: Cycle) -> ResumableVerification<'a, DL> {
+        ResumableVerification::new(self, self.script_groups(), max_cycles)
+    }
+
     // Run a single script in current transaction, while this is not useful for
     // CKB itself, it can be very helpful when building a CKB debugger.
     pub fn verify_single(
@@ -331,16 +543,33 @@ impl<'a, DL: DataLoader> TransactionScriptsVerifier<'a, DL> {
         }
     }
 
-    fn verify_script_group(&self, group: &ScriptGroup, max_cycles: Cycle) -> Result<Cycle, Error> {
-        if group.script.code_hash() == TYPE_ID_CODE_HASH.pack()
+    pub(crate) fn is_type_id_group(group: &ScriptGroup) -> bool {
+        group.script.code_hash() == TYPE_ID_CODE_HASH.pack()
             && Into::<u8>::into(group.script.hash_type()) == Into::<u8>::into(ScriptHashType::Type)
-        {
+    }
+
+    pub(crate) fn verify_script_group(
+        &self,
+        group: &ScriptGroup,
+        max_cycles: Cycle,
+    ) -> Result<Cycle, Error> {
+        self.verify_script_group_with_cycles(group, max_cycles)
+            .map_err(|(e, _cycles)| e)
+    }
+
+    // On failure, also returns the cycles consumed by the script group
+    fn verify_script_group_with_cycles(
+        &self,
+        group: &ScriptGroup,
+        max_cycles: Cycle,
+    ) -> Result<Cycle, (Error, Cycle)> {
+        if Self::is_type_id_group(group) {
             let verifier = TypeIdSystemScript {
                 rtx: self.rtx,
                 script_group: group,
                 max_cycles,
             };
-            verifier.verify()
+            verifier.verify().map_err(|e| (e, 0))
         } else {
             self.run(&group, max_cycles)
         }
@@ -365,8 +594,12 @@ impl<'a, DL: DataLoader> TransactionScriptsVerifier<'a, DL> {
         &'a self,
         script_group: &'a ScriptGroup,
     ) -> Vec<Box<(dyn Syscalls<CoreMachineType> + 'a)>> {
-        let current_script_hash = script_group.script.calc_script_hash();
-        vec![
+        let current_script_hash = self
+            .script_hashes
+            .get(&script_group.script)
+            .cloned()
+            .unwrap_or_else(|| script_group.script.calc_script_hash());
+        let mut syscalls: Vec<Box<(dyn Syscalls<CoreMachineType> + 'a)>> = vec![
             Box::new(self.build_load_script_hash(current_script_hash.clone())),
             Box::new(self.build_load_tx()),
             Box::new(
@@ -384,19 +617,65 @@ impl<'a, DL: DataLoader> TransactionScriptsVerifier<'a, DL> {
                     &script_group.output_indices,
                 ),
             ),
-            Box::new(Debugger::new(current_script_hash, &self.debug_printer)),
-        ]
+            Box::new(Debugger::new(
+                current_script_hash.clone(),
+                &self.debug_printer,
+            )),
+        ];
+        if self.trace_syscalls {
+            syscalls.insert(
+                0,
+                Box::new(SyscallTracer::new(self.hash(), current_script_hash)),
+            );
+        }
+        syscalls
     }
 
-    fn run(&self, script_group: &ScriptGroup, max_cycles: Cycle) -> Result<Cycle, Error> {
+    // Every VM version maps to a core machine with its own ISA and features,
+    // add an arm here when a hard fork introduces a new version.
+    fn new_core_machine(&self, max_cycles: Cycle) -> CoreMachineType {
+        match self.vm_version {
+            #[cfg(has_asm)]
+            VmVersion::V0 | VmVersion::V1 => AsmCoreMachine::new_with_max_cycles(max_cycles),
+            #[cfg(not(has_asm))]
+            VmVersion::V0 | VmVersion::V1 => DefaultCoreMachine::<
+                u64,
+                WXorXMemory<u64, SparseMemory<u64>>,
+            >::new_with_max_cycles(max_cycles),
+        }
+    }
+
+    // Build a machine with the program of the script group loaded, which is
+    // driven step by step by the caller instead of running to completion.
+    pub(crate) fn build_stepping_machine(
+        &'a self,
+        script_group: &'a ScriptGroup,
+        max_cycles: Cycle,
+    ) -> Result<DefaultMachine<'a, CoreMachineType>, Error> {
         let program = self.extract_script(&script_group.script)?;
-        #[cfg(has_asm)]
-        let core_machine = AsmCoreMachine::new_with_max_cycles(max_cycles);
-        #[cfg(not(has_asm))]
-        let core_machine =
-            DefaultCoreMachine::<u64, WXorXMemory<u64, SparseMemory<u64>>>::new_with_max_cycles(
-                max_cycles,
-            );
+        let core_machine = self.new_core_machine(max_cycles);
+        let machine_builder = DefaultMachineBuilder::<CoreMachineType>::new(core_machine)
+            .instruction_cycle_func(self.cost_model());
+        let mut machine = self
+            .generate_syscalls(script_group)
+            .into_iter()
+            .fold(machine_builder, |builder, syscall| builder.syscall(syscall))
+            .build();
+        let bytes = machine
+            .load_program(&program, &[])
+            .map_err(internal_error)?;
+        machine
+            .add_cycles(transferred_byte_cycles(bytes))
+            .map_err(internal_error)?;
+        machine.set_running(true);
+        Ok(machine)
+    }
+
+    fn run(&self, script_group: &ScriptGroup, max_cycles: Cycle) -> Result<Cycle, (Error, Cycle)> {
+        let program = self
+            .extract_script(&script_group.script)
+            .map_err(|e| (e, 0))?;
+        let core_machine = self.new_core_machine(max_cycles);
         let machine_builder = DefaultMachineBuilder::<CoreMachineType>::new(core_machine)
             .instruction_cycle_func(self.cost_model());
         let machine_builder = self
@@ -410,21 +689,26 @@ impl<'a, DL: DataLoader> TransactionScriptsVerifier<'a, DL> {
         let mut machine = TraceMachine::new(default_machine);
         let bytes = machine
             .load_program(&program, &[])
-            .map_err(internal_error)?;
+            .map_err(|e| (internal_error(e), 0))?;
         machine
             .machine
             .add_cycles(transferred_byte_cycles(bytes))
-            .map_err(internal_error)?;
-        let code = machine.run().map_err(internal_error)?;
+            .map_err(|e| (internal_error(e), 0))?;
+        let code = machine
+            .run()
+            .map_err(|e| (internal_error(e), machine.machine.cycles()))?;
         if code == 0 {
             Ok(machine.machine.cycles())
         } else {
-            Err(ScriptError::ValidationFailure(code).into())
+            Err((
+                ScriptError::ValidationFailure(code).into(),
+                machine.machine.cycles(),
+            ))
         }
     }
 }
 
-fn internal_error(error: VMInternalError) -> Error {
+pub(crate) fn internal_error(error: VMInternalError) -> Error {
     if error == VMInternalError::InvalidCycles {
         return ScriptError::ExceededMaximumCycles.into();
     }
@@ -563,6 +847,258 @@ mod tests {
         assert!(verifier.verify(600).is_ok());
     }
 
+    #[test]
+    fn check_chunked_verify() {
+        let (always_success_cell, always_success_cell_data, always_success_script) =
+            always_success_cell();
+        let output = CellOutputBuilder::default()
+            .capacity(capacity_bytes!(100).pack())
+            .lock(always_success_script.clone())
+            .build();
+        let input = CellInput::new(OutPoint::null(), 0);
+
+        let transaction = TransactionBuilder::default().input(input).build();
+
+        let dummy_cell = CellMetaBuilder::from_cell_output(output, Bytes::new())
+            .transaction_info(default_transaction_info())
+            .build();
+        let always_success_cell = CellMetaBuilder::from_cell_output(
+            always_success_cell.clone(),
+            always_success_cell_data.to_owned(),
+        )
+        .transaction_info(default_transaction_info())
+        .build();
+
+        let rtx = ResolvedTransaction {
+            transaction,
+            resolved_cell_deps: vec![always_success_cell],
+            resolved_inputs: vec![dummy_cell],
+            resolved_dep_groups: vec![],
+        };
+
+        let store = new_store();
+        let data_loader = DataLoaderWrapper::new(&store);
+
+        let verifier = TransactionScriptsVerifier::new(&rtx, &data_loader);
+        let cycles = verifier.verify(600).unwrap();
+        let mut suspended = 0;
+        let chunked_cycles = verifier
+            .chunked_verify(600, 1, |_| {
+                suspended += 1;
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(cycles, chunked_cycles);
+        assert!(suspended > 0);
+    }
+
+    #[test]
+    fn check_verify_with_checks() {
+        let (always_success_cell, always_success_cell_data, always_success_script) =
+            always_success_cell();
+        let output = CellOutputBuilder::default()
+            .capacity(capacity_bytes!(100).pack())
+            .lock(always_success_script.clone())
+            .build();
+        let input = CellInput::new(OutPoint::null(), 0);
+
+        let transaction = TransactionBuilder::default().input(input).build();
+
+        let dummy_cell = CellMetaBuilder::from_cell_output(output, Bytes::new())
+            .transaction_info(default_transaction_info())
+            .build();
+        let always_success_cell = CellMetaBuilder::from_cell_output(
+            always_success_cell.clone(),
+            always_success_cell_data.to_owned(),
+        )
+        .transaction_info(default_transaction_info())
+        .build();
+
+        let rtx = ResolvedTransaction {
+            transaction,
+            resolved_cell_deps: vec![always_success_cell],
+            resolved_inputs: vec![dummy_cell],
+            resolved_dep_groups: vec![],
+        };
+
+        let store = new_store();
+        let data_loader = DataLoaderWrapper::new(&store);
+
+        let verifier = TransactionScriptsVerifier::new(&rtx, &data_loader);
+        let cycles = verifier.verify(600).unwrap();
+        // the budgets 1, 2, 4, ..., 512 run out before the script finishes
+        let mut checks = 0;
+        let checked_cycles = verifier
+            .verify_with_checks(600, 1, || {
+                checks += 1;
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(cycles, checked_cycles);
+        assert_eq!(checks, 10);
+
+        // no check when the first budget is enough
+        let checked_cycles = verifier
+            .verify_with_checks(600, 600, || panic!("no check"))
+            .unwrap();
+        assert_eq!(cycles, checked_cycles);
+
+        let result =
+            verifier.verify_with_checks(600, 1, || Err(ScriptError::ExceededMaximumTime.into()));
+        assert_error_eq!(result.unwrap_err(), ScriptError::ExceededMaximumTime);
+
+        // the cycles limit is still checked
+        let result = verifier.verify_with_checks(cycles - 1, 1, || Ok(()));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn check_type_script_group_dedup() {
+        let (always_success_cell, always_success_cell_data, always_success_script) =
+            always_success_cell();
+        let type_script = always_success_script
+            .clone()
+            .as_builder()
+            .args(Bytes::from(vec![1]).pack())
+            .build();
+        let other_type_script = always_success_script
+            .clone()
+            .as_builder()
+            .args(Bytes::from(vec![2]).pack())
+            .build();
+        let output = CellOutputBuilder::default()
+            .capacity(capacity_bytes!(100).pack())
+            .lock(always_success_script.clone())
+            .type_(Some(type_script.clone()).pack())
+            .build();
+
+        let transaction = TransactionBuilder::default()
+            .input(CellInput::new(OutPoint::null(), 0))
+            .input(CellInput::new(OutPoint::null(), 0))
+            .output(output.clone())
+            .output(
+                output
+                    .clone()
+                    .as_builder()
+                    .type_(Some(other_type_script.clone()).pack())
+                    .build(),
+            )
+            .output(output.clone())
+            .output_data(Bytes::new().pack())
+            .output_data(Bytes::new().pack())
+            .output_data(Bytes::new().pack())
+            .build();
+
+        let dummy_cell = CellMetaBuilder::from_cell_output(output, Bytes::new())
+            .transaction_info(default_transaction_info())
+            .build();
+        let always_success_cell = CellMetaBuilder::from_cell_output(
+            always_success_cell.clone(),
+            always_success_cell_data.to_owned(),
+        )
+        .transaction_info(default_transaction_info())
+        .build();
+
+        let rtx = ResolvedTransaction {
+            transaction,
+            resolved_cell_deps: vec![always_success_cell],
+            resolved_inputs: vec![dummy_cell.clone(), dummy_cell],
+            resolved_dep_groups: vec![],
+        };
+
+        let store = new_store();
+        let data_loader = DataLoaderWrapper::new(&store);
+        let verifier = TransactionScriptsVerifier::new(&rtx, &data_loader);
+
+        // inputs and outputs sharing a type script fall into the same group
+        assert_eq!(verifier.lock_groups.len(), 1);
+        assert_eq!(verifier.type_groups.len(), 2);
+        let group = verifier
+            .find_script_group(&ScriptGroupType::Type, &type_script.calc_script_hash())
+            .unwrap();
+        assert_eq!(group.input_indices, vec![0, 1]);
+        assert_eq!(group.output_indices, vec![0, 2]);
+        let other_group = verifier
+            .find_script_group(
+                &ScriptGroupType::Type,
+                &other_type_script.calc_script_hash(),
+            )
+            .unwrap();
+        assert!(other_group.input_indices.is_empty());
+        assert_eq!(other_group.output_indices, vec![1]);
+
+        // and each group is executed exactly once
+        let (cycles, profiles) = verifier.profile(CYCLE_BOUND).unwrap();
+        assert_eq!(profiles.len(), 3);
+        assert_eq!(cycles, ALWAYS_SUCCESS_SCRIPT_CYCLE * 3);
+    }
+
+    #[test]
+    fn check_script_groups_order() {
+        let (always_success_cell, always_success_cell_data, always_success_script) =
+            always_success_cell();
+        let always_success_cell = CellMetaBuilder::from_cell_output(
+            always_success_cell.clone(),
+            always_success_cell_data.to_owned(),
+        )
+        .transaction_info(default_transaction_info())
+        .build();
+        let input_cells: Vec<_> = (0..8u8)
+            .map(|i| {
+                let script = always_success_script
+                    .clone()
+                    .as_builder()
+                    .args(Bytes::from(vec![i]).pack())
+                    .build();
+                let output = CellOutputBuilder::default()
+                    .capacity(capacity_bytes!(100).pack())
+                    .lock(script.clone())
+                    .type_(Some(script).pack())
+                    .build();
+                CellMetaBuilder::from_cell_output(output, Bytes::new())
+                    .transaction_info(default_transaction_info())
+                    .build()
+            })
+            .collect();
+        let resolve = |cells: Vec<CellMeta>| ResolvedTransaction {
+            transaction: TransactionBuilder::default()
+                .inputs(cells.iter().map(|_| CellInput::new(OutPoint::null(), 0)))
+                .build(),
+            resolved_cell_deps: vec![always_success_cell.clone()],
+            resolved_inputs: cells,
+            resolved_dep_groups: vec![],
+        };
+        let store = new_store();
+        let data_loader = DataLoaderWrapper::new(&store);
+        let group_hashes = |rtx: &ResolvedTransaction| {
+            TransactionScriptsVerifier::new(rtx, &data_loader)
+                .script_groups()
+                .into_iter()
+                .map(|(group_type, index, group)| {
+                    (group_type, index, group.script.calc_script_hash())
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let rtx = resolve(input_cells.clone());
+        let groups = group_hashes(&rtx);
+        assert_eq!(groups.len(), 16);
+        let mut hashes: Vec<_> = input_cells
+            .iter()
+            .map(|cell| cell.cell_output.calc_lock_hash())
+            .collect();
+        hashes.sort();
+        // the lock groups first, each kind ordered by the script hashes
+        for (i, hash) in hashes.iter().enumerate() {
+            assert_eq!(groups[i], (ScriptGroupType::Lock, i, hash.clone()));
+            assert_eq!(groups[i + 8], (ScriptGroupType::Type, i, hash.clone()));
+        }
+        // the same whatever the order of the inputs
+        assert_eq!(groups, group_hashes(&rtx));
+        let reversed = resolve(input_cells.into_iter().rev().collect());
+        assert_eq!(groups, group_hashes(&reversed));
+    }
+
     #[test]
     fn check_signature() {
         let mut file = open_cell_always_success();
@@ -863,6 +1399,73 @@ mod tests {
         );
     }
 
+    #[test]
+    fn check_script_error_details() {
+        let mut file = open_cell_always_failure();
+        let mut buffer = Vec::new();
+        file.read_to_end(&mut buffer).unwrap();
+
+        let code_hash = blake2b_256(&buffer);
+        let dep_out_point = OutPoint::new(h256!("0x123").pack(), 8);
+        let cell_dep = CellDep::new_builder().out_point(dep_out_point).build();
+        let data = Bytes::from(buffer);
+        let output = CellOutputBuilder::default()
+            .capacity(Capacity::bytes(data.len()).unwrap().pack())
+            .build();
+        let dep_cell = CellMetaBuilder::from_cell_output(output, data)
+            .transaction_info(default_transaction_info())
+            .build();
+
+        let script = Script::new_builder()
+            .code_hash(code_hash.pack())
+            .hash_type(ScriptHashType::Data.into())
+            .build();
+        let input = CellInput::new(OutPoint::null(), 0);
+
+        let transaction = TransactionBuilder::default()
+            .input(input)
+            .cell_dep(cell_dep)
+            .build();
+
+        let output = CellOutputBuilder::default()
+            .capacity(capacity_bytes!(100).pack())
+            .lock(script.clone())
+            .build();
+        let dummy_cell = CellMetaBuilder::from_cell_output(output, Bytes::new())
+            .transaction_info(default_transaction_info())
+            .build();
+
+        let rtx = ResolvedTransaction {
+            transaction,
+            resolved_cell_deps: vec![dep_cell],
+            resolved_inputs: vec![dummy_cell],
+            resolved_dep_groups: vec![],
+        };
+
+        let store = new_store();
+        let data_loader = DataLoaderWrapper::new(&store);
+        let verifier = TransactionScriptsVerifier::new(&rtx, &data_loader);
+
+        let error = verifier.verify(100_000_000).unwrap_err();
+        let details = error
+            .downcast_ref::<TransactionScriptError>()
+            .expect("script error details");
+        assert_eq!(details.group_type, ScriptGroupType::Lock);
+        assert_eq!(details.index, 0);
+        assert_eq!(details.script_hash, script.calc_script_hash());
+        assert_eq!(details.exit_code(), Some(-1));
+        assert!(details.cycles > 0);
+
+        // the chunked verification reports the same group
+        let chunked_error = verifier
+            .chunked_verify(100_000_000, 1, |_| Ok(()))
+            .unwrap_err();
+        assert_eq!(
+            chunked_error.downcast_ref::<TransactionScriptError>(),
+            Some(details)
+        );
+    }
+
     #[test]
     fn check_invalid_dep_reference() {
         let mut file = open_cell_always_success();
@@ -1,3 +1,5 @@
+mod migrations;
 pub mod shared;
+mod verify_cache;
 
 pub use ckb_snapshot::{Snapshot, SnapshotMgr};
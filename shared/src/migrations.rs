@@ -0,0 +1,111 @@
+use ckb_app_config::StoreConfig;
+use ckb_db::{Migration, Result, RocksDB};
+use ckb_logger::info;
+use ckb_store::{ChainDB, ChainStore};
+
+// Blocks appended to the chain root MMR per database transaction
+const BATCH: u64 = 10_000;
+
+/// Build the chain root MMR over the main chain of databases created before it was introduced
+pub struct ChainRootMMR {
+    version: String,
+}
+
+impl ChainRootMMR {
+    pub fn new(version: &str) -> Self {
+        Self {
+            version: version.to_string(),
+        }
+    }
+}
+
+impl Migration for ChainRootMMR {
+    fn migrate(&self, db: &RocksDB) -> Result<()> {
+        let chain_db = ChainDB::new(db.clone(), StoreConfig::default());
+        let tip_number = match chain_db.get_tip_header() {
+            Some(header) => header.number(),
+            None => return Ok(()),
+        };
+        let mut block_number = 0;
+        while block_number <= tip_number {
+            let txn = chain_db.begin_transaction();
+            for _ in 0..BATCH {
+                if block_number > tip_number {
+                    break;
+                }
+                let block_hash = chain_db
+                    .get_block_hash(block_number)
+                    .expect("main chain block hash is indexed");
+                txn.insert_chain_root_leaf(block_number, &block_hash)?;
+                block_number += 1;
+            }
+            txn.commit()?;
+            info!("Built the chain root MMR up to block {}", block_number - 1);
+        }
+        Ok(())
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+}
+
+/// Build the block filters and the filter hashes of the main chain blocks attached before they
+/// were introduced, so light clients can fetch them from the genesis
+pub struct BlockFilters {
+    version: String,
+}
+
+impl BlockFilters {
+    pub fn new(version: &str) -> Self {
+        Self {
+            version: version.to_string(),
+        }
+    }
+}
+
+impl Migration for BlockFilters {
+    fn migrate(&self, db: &RocksDB) -> Result<()> {
+        let chain_db = ChainDB::new(db.clone(), StoreConfig::default());
+        let tip_number = match chain_db.get_tip_header() {
+            Some(header) => header.number(),
+            None => return Ok(()),
+        };
+        let mut block_number = 0;
+        while block_number <= tip_number {
+            let txn = chain_db.begin_transaction();
+            for _ in 0..BATCH {
+                if block_number > tip_number {
+                    break;
+                }
+                let block_hash = chain_db
+                    .get_block_hash(block_number)
+                    .expect("main chain block hash is indexed");
+                // the filter hashes are chained, the ones built within this transaction are
+                // read back from it
+                if txn.get_block_filter_hash(&block_hash).is_none() {
+                    let block = match chain_db.get_block(&block_hash) {
+                        Some(block) => block,
+                        None => {
+                            txn.commit()?;
+                            info!(
+                                "Stop building the block filters at the pruned block {}",
+                                block_number
+                            );
+                            return Ok(());
+                        }
+                    };
+                    txn.insert_block_filter(&block)?;
+                }
+                block_number += 1;
+            }
+            txn.commit()?;
+            info!("Built the block filters up to block {}", block_number - 1);
+        }
+        Ok(())
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+}
@@ -1,32 +1,49 @@
+use crate::migrations::{BlockFilters, ChainRootMMR};
+use crate::verify_cache::{load_tx_verify_cache, persist_tx_verify_cache};
 use crate::{Snapshot, SnapshotMgr};
 use arc_swap::Guard;
 use ckb_app_config::{BlockAssemblerConfig, DBConfig, NotifyConfig, StoreConfig, TxPoolConfig};
+use ckb_async_runtime::Handle;
 use ckb_chain_spec::consensus::Consensus;
 use ckb_chain_spec::SpecError;
+use ckb_clock::{Clock, SystemClock};
 use ckb_db::{DefaultMigration, Migrations, RocksDB};
 use ckb_error::{Error, InternalErrorKind};
+use ckb_logger::error;
 use ckb_notify::{NotifyController, NotifyService};
 use ckb_proposal_table::{ProposalTable, ProposalView};
+use ckb_script::TracedTransactions;
+use ckb_stop_handler::{SignalSender, StopHandler};
 use ckb_store::ChainDB;
 use ckb_store::{ChainStore, COLUMNS};
-use ckb_tx_pool::{TokioRwLock, TxPoolController, TxPoolServiceBuilder};
+use ckb_tx_pool::{TxPoolController, TxPoolServiceBuilder};
 use ckb_types::{
     core::{EpochExt, HeaderView},
     packed::Byte32,
     U256,
 };
-use ckb_verification::cache::TxVerifyCache;
+use ckb_verification::{
+    cache::{CacheEntry, TxVerifyCache},
+    new_verify_pool, VerifyPool,
+};
+use crossbeam_channel::{bounded, RecvTimeoutError};
 use std::collections::HashSet;
 use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
 
 #[derive(Clone)]
 pub struct Shared {
     pub(crate) store: ChainDB,
     pub(crate) tx_pool_controller: TxPoolController,
     pub(crate) notify_controller: NotifyController,
-    pub(crate) txs_verify_cache: Arc<TokioRwLock<TxVerifyCache>>,
+    pub(crate) txs_verify_cache: Arc<TxVerifyCache>,
+    pub(crate) verify_pool: VerifyPool,
     pub(crate) consensus: Arc<Consensus>,
     pub(crate) snapshot_mgr: Arc<SnapshotMgr>,
+    pub(crate) verify_cache_persister: StopHandler<()>,
+    pub(crate) traced_transactions: TracedTransactions,
+    pub(crate) clock: Arc<dyn Clock>,
 }
 
 impl Shared {
@@ -36,6 +53,7 @@ impl Shared {
         tx_pool_config: TxPoolConfig,
         notify_config: NotifyConfig,
         block_assembler_config: Option<BlockAssemblerConfig>,
+        clock: Arc<dyn Clock>,
     ) -> Result<(Self, ProposalTable), Error> {
         let (tip_header, epoch) = Self::init_store(&store, &consensus)?;
         let total_difficulty = store
@@ -46,9 +64,8 @@ impl Shared {
 
         let consensus = Arc::new(consensus);
 
-        let txs_verify_cache = Arc::new(TokioRwLock::new(TxVerifyCache::new(
-            tx_pool_config.max_verify_cache_size,
-        )));
+        let txs_verify_cache = Arc::new(TxVerifyCache::new(tx_pool_config.max_verify_cache_size));
+        let verify_pool = new_verify_pool(tx_pool_config.verify_workers);
         let snapshot = Arc::new(Snapshot::new(
             tip_header,
             total_difficulty,
@@ -58,31 +75,113 @@ impl Shared {
             Arc::clone(&consensus),
         ));
         let snapshot_mgr = Arc::new(SnapshotMgr::new(Arc::clone(&snapshot)));
+        let notify_controller = NotifyService::new(notify_config).start(Some("NotifyService"));
+        let traced_transactions = TracedTransactions::default();
 
         let tx_pool_builder = TxPoolServiceBuilder::new(
             tx_pool_config,
             Arc::clone(&snapshot),
             block_assembler_config,
             Arc::clone(&txs_verify_cache),
+            Arc::clone(&verify_pool),
             Arc::clone(&snapshot_mgr),
+            notify_controller.clone(),
+            traced_transactions.clone(),
+            Arc::clone(&clock),
         );
 
         let tx_pool_controller = tx_pool_builder.start();
 
-        let notify_controller = NotifyService::new(notify_config).start(Some("NotifyService"));
+        let verify_cache_persister = Self::start_verify_cache_persister(
+            store.clone(),
+            Arc::clone(&consensus),
+            Arc::clone(&txs_verify_cache),
+            tx_pool_controller.handle().clone(),
+            tx_pool_config.verify_cache_persist_interval,
+        );
 
         let shared = Shared {
             store,
             consensus,
             txs_verify_cache,
+            verify_pool,
             snapshot_mgr,
             tx_pool_controller,
             notify_controller,
+            verify_cache_persister,
+            traced_transactions,
+            clock,
         };
 
         Ok((shared, proposal_table))
     }
 
+    // Loads the persisted entries in the background, so a large cache does not hold the startup
+    // back, then persists the cache every `interval_secs` unless it is 0, until it is stopped.
+    fn start_verify_cache_persister(
+        store: ChainDB,
+        consensus: Arc<Consensus>,
+        txs_verify_cache: Arc<TxVerifyCache>,
+        handle: Handle,
+        interval_secs: u64,
+    ) -> StopHandler<()> {
+        let (signal_sender, signal_receiver) = bounded::<()>(1);
+        let thread = thread::Builder::new()
+            .name("VerifyCachePersister".to_string())
+            .spawn(move || {
+                match load_tx_verify_cache(&store, &consensus) {
+                    Ok(entries) => {
+                        let (sender, receiver) = bounded(1);
+                        let cache = Arc::clone(&txs_verify_cache);
+                        handle.spawn(async move {
+                            cache.warm_up(entries).await;
+                            let _ = sender.send(());
+                        });
+                        let _ = receiver.recv();
+                    }
+                    Err(err) => error!("load tx verify cache error {}", err),
+                }
+                if interval_secs == 0 {
+                    let _ = signal_receiver.recv();
+                    return;
+                }
+                loop {
+                    match signal_receiver.recv_timeout(Duration::from_secs(interval_secs)) {
+                        Err(RecvTimeoutError::Timeout) => {}
+                        _ => break,
+                    }
+                    let entries = Self::fetch_txs_verify_cache(&txs_verify_cache, &handle);
+                    if let Err(err) = persist_tx_verify_cache(&store, &consensus, entries) {
+                        error!("persist tx verify cache error {}", err);
+                    }
+                }
+            })
+            .expect("Start VerifyCachePersister failed");
+        StopHandler::new(SignalSender::Crossbeam(signal_sender), thread)
+    }
+
+    fn fetch_txs_verify_cache(
+        txs_verify_cache: &Arc<TxVerifyCache>,
+        handle: &Handle,
+    ) -> Vec<(Byte32, CacheEntry)> {
+        let (sender, receiver) = bounded(1);
+        let txs_verify_cache = Arc::clone(txs_verify_cache);
+        handle.spawn(async move {
+            let entries = txs_verify_cache.entries().await;
+            if let Err(e) = sender.send(entries) {
+                error!("fetch tx verify cache error {:?}", e);
+            }
+        });
+        receiver.recv().unwrap_or_default()
+    }
+
+    /// Write the tx verify cache to store so that a restarted node reloads it
+    pub fn persist_txs_verify_cache(&self) -> Result<(), Error> {
+        let entries =
+            Self::fetch_txs_verify_cache(&self.txs_verify_cache, self.tx_pool_controller.handle());
+        persist_tx_verify_cache(&self.store, &self.consensus, entries)
+    }
+
     pub(crate) fn init_proposal_table(
         store: &ChainDB,
         consensus: &Consensus,
@@ -150,14 +249,34 @@ impl Shared {
         &self.tx_pool_controller
     }
 
-    pub fn txs_verify_cache(&self) -> Arc<TokioRwLock<TxVerifyCache>> {
+    pub fn txs_verify_cache(&self) -> Arc<TxVerifyCache> {
         Arc::clone(&self.txs_verify_cache)
     }
 
+    /// The transactions whose syscalls are logged by the chain and the tx-pool
+    pub fn traced_transactions(&self) -> &TracedTransactions {
+        &self.traced_transactions
+    }
+
+    /// The handler of the thread persisting the tx verify cache periodically, stop it before the
+    /// tx pool, whose runtime it reads the cache on.
+    pub fn verify_cache_persister_stop_handler(&self) -> StopHandler<()> {
+        self.verify_cache_persister.clone()
+    }
+
+    pub fn verify_pool(&self) -> VerifyPool {
+        Arc::clone(&self.verify_pool)
+    }
+
     pub fn notify_controller(&self) -> &NotifyController {
         &self.notify_controller
     }
 
+    /// The time source of the services of the node, see `SharedBuilder::clock`
+    pub fn clock(&self) -> &Arc<dyn Clock> {
+        &self.clock
+    }
+
     pub fn snapshot(&self) -> Guard<Arc<Snapshot>> {
         self.snapshot_mgr.load()
     }
@@ -208,6 +327,7 @@ pub struct SharedBuilder {
     store_config: Option<StoreConfig>,
     block_assembler_config: Option<BlockAssemblerConfig>,
     notify_config: Option<NotifyConfig>,
+    clock: Option<Arc<dyn Clock>>,
 }
 
 impl Default for SharedBuilder {
@@ -219,16 +339,21 @@ impl Default for SharedBuilder {
             notify_config: None,
             store_config: None,
             block_assembler_config: None,
+            clock: None,
         }
     }
 }
 
 const INIT_DB_VERSION: &str = "20191127135521";
+const CHAIN_ROOT_MMR_DB_VERSION: &str = "20200608000000";
+const BLOCK_FILTERS_DB_VERSION: &str = "20200610000000";
 
 impl SharedBuilder {
     pub fn with_db_config(config: &DBConfig) -> Self {
         let mut migrations = Migrations::default();
         migrations.add_migration(Box::new(DefaultMigration::new(INIT_DB_VERSION)));
+        migrations.add_migration(Box::new(ChainRootMMR::new(CHAIN_ROOT_MMR_DB_VERSION)));
+        migrations.add_migration(Box::new(BlockFilters::new(BLOCK_FILTERS_DB_VERSION)));
 
         let db = RocksDB::open(config, COLUMNS, migrations);
         SharedBuilder {
@@ -238,6 +363,7 @@ impl SharedBuilder {
             notify_config: None,
             store_config: None,
             block_assembler_config: None,
+            clock: None,
         }
     }
 }
@@ -268,11 +394,18 @@ impl SharedBuilder {
         self
     }
 
+    /// The system clock by default, tests may advance a `MockClock` instead
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
     pub fn build(self) -> Result<(Shared, ProposalTable), Error> {
         let consensus = self.consensus.unwrap_or_else(Consensus::default);
         let tx_pool_config = self.tx_pool_config.unwrap_or_else(Default::default);
         let notify_config = self.notify_config.unwrap_or_else(Default::default);
         let store_config = self.store_config.unwrap_or_else(Default::default);
+        let clock = self.clock.unwrap_or_else(|| Arc::new(SystemClock));
         let store = ChainDB::new(self.db, store_config);
         Shared::init(
             store,
@@ -280,6 +413,7 @@ impl SharedBuilder {
             tx_pool_config,
             notify_config,
             self.block_assembler_config,
+            clock,
         )
     }
 }
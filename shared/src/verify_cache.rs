@@ -0,0 +1,198 @@
+use ckb_chain_spec::consensus::Consensus;
+use ckb_error::{Error, InternalErrorKind};
+use ckb_logger::info;
+use ckb_store::{ChainDB, ChainStore, COLUMN_META, COLUMN_TX_VERIFY_CACHE};
+use ckb_types::{
+    core::{Capacity, Cycle},
+    packed::Byte32,
+    prelude::*,
+};
+use ckb_verification::cache::CacheEntry;
+use std::convert::TryInto;
+
+// value layout: tx_hash(32) | cycles(8, le) | fee(8, le)
+const ENTRY_SIZE: usize = 32 + 8 + 8;
+// The consensus the persisted entries are verified under
+const META_TX_VERIFY_CACHE_CONSENSUS_KEY: &[u8] = b"TX_VERIFY_CACHE_CONSENSUS";
+
+// The consensus parameters the cycles of a transaction depend on. A hard fork or a different
+// chain changes them, and the persisted entries are dropped then.
+fn consensus_fingerprint(consensus: &Consensus) -> Vec<u8> {
+    let mut fingerprint = Vec::new();
+    fingerprint.extend_from_slice(consensus.genesis_hash().as_slice());
+    fingerprint.extend_from_slice(&consensus.max_block_cycles().to_le_bytes());
+    fingerprint.extend_from_slice(&consensus.tx_version().to_le_bytes());
+    fingerprint.extend_from_slice(consensus.type_id_code_hash().as_bytes());
+    for (epoch, version) in &consensus.vm_version_activations {
+        fingerprint.extend_from_slice(&epoch.to_le_bytes());
+        fingerprint.push(*version as u8);
+    }
+    fingerprint
+}
+
+// Load the persisted entries, they are keyed by a big-endian sequence so
+// traversing the column yields them from least to most recently used.
+pub(crate) fn load_tx_verify_cache(
+    store: &ChainDB,
+    consensus: &Consensus,
+) -> Result<Vec<(Byte32, CacheEntry)>, Error> {
+    let consensus_matched = store
+        .get(COLUMN_META, META_TX_VERIFY_CACHE_CONSENSUS_KEY)
+        .map(|raw| raw.as_ref() == consensus_fingerprint(consensus).as_slice())
+        .unwrap_or(false);
+    if !consensus_matched {
+        info!("the consensus changed, drop the persisted tx verify cache");
+        return Ok(Vec::new());
+    }
+    let mut entries = Vec::new();
+    store.db().traverse(COLUMN_TX_VERIFY_CACHE, |_key, value| {
+        if value.len() != ENTRY_SIZE {
+            return Err(InternalErrorKind::DataCorrupted
+                .reason("invalid tx verify cache entry")
+                .into());
+        }
+        let tx_hash = Byte32::from_slice(&value[..32])
+            .map_err(|err| InternalErrorKind::DataCorrupted.reason(format!("{}", err)))?;
+        let cycles = Cycle::from_le_bytes(value[32..40].try_into().expect("checked length"));
+        let fee = u64::from_le_bytes(value[40..].try_into().expect("checked length"));
+        entries.push((tx_hash, CacheEntry::new(cycles, Capacity::shannons(fee))));
+        Ok(())
+    })?;
+    Ok(entries)
+}
+
+// Replace the persisted entries with `entries`, which must be ordered from
+// least to most recently used.
+pub(crate) fn persist_tx_verify_cache(
+    store: &ChainDB,
+    consensus: &Consensus,
+    entries: Vec<(Byte32, CacheEntry)>,
+) -> Result<(), Error> {
+    let mut stale_keys = Vec::new();
+    store.db().traverse(COLUMN_TX_VERIFY_CACHE, |key, _value| {
+        stale_keys.push(key.to_vec());
+        Ok(())
+    })?;
+
+    let txn = store.begin_transaction();
+    for key in stale_keys {
+        txn.delete(COLUMN_TX_VERIFY_CACHE, &key)?;
+    }
+    txn.insert_raw(
+        COLUMN_META,
+        META_TX_VERIFY_CACHE_CONSENSUS_KEY,
+        &consensus_fingerprint(consensus),
+    )?;
+    for (index, (tx_hash, entry)) in entries.into_iter().enumerate() {
+        let mut value = Vec::with_capacity(ENTRY_SIZE);
+        value.extend_from_slice(tx_hash.as_slice());
+        value.extend_from_slice(&entry.cycles.to_le_bytes());
+        value.extend_from_slice(&entry.fee.as_u64().to_le_bytes());
+        txn.insert_raw(
+            COLUMN_TX_VERIFY_CACHE,
+            &(index as u64).to_be_bytes(),
+            &value,
+        )?;
+    }
+    txn.commit()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::SharedBuilder;
+    use ckb_app_config::StoreConfig;
+    use ckb_async_runtime::Handle;
+    use ckb_chain_spec::consensus::ConsensusBuilder;
+    use ckb_db::RocksDB;
+    use ckb_store::COLUMNS;
+    use ckb_verification::cache::TxVerifyCache;
+    use std::future::Future;
+    use std::sync::Arc;
+
+    fn entries(count: u8) -> Vec<(Byte32, CacheEntry)> {
+        (0..count)
+            .map(|i| {
+                let entry = CacheEntry::new(u64::from(i) * 100, Capacity::shannons(i.into()));
+                ([i; 32].pack(), entry)
+            })
+            .collect()
+    }
+
+    fn block_on<F>(handle: &Handle, future: F) -> F::Output
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+        handle.spawn(async move {
+            let _ = sender.send(future.await);
+        });
+        receiver.recv().expect("future done")
+    }
+
+    #[test]
+    fn persist_and_load_tx_verify_cache() {
+        let store = ChainDB::new(RocksDB::open_tmp(COLUMNS), StoreConfig::default());
+        let consensus = Consensus::default();
+        assert!(load_tx_verify_cache(&store, &consensus).unwrap().is_empty());
+
+        persist_tx_verify_cache(&store, &consensus, entries(3)).unwrap();
+        assert_eq!(
+            entries(3),
+            load_tx_verify_cache(&store, &consensus).unwrap()
+        );
+
+        // the stale entries are replaced
+        persist_tx_verify_cache(&store, &consensus, entries(2)).unwrap();
+        assert_eq!(
+            entries(2),
+            load_tx_verify_cache(&store, &consensus).unwrap()
+        );
+    }
+
+    #[test]
+    fn drop_persisted_tx_verify_cache_on_consensus_change() {
+        let store = ChainDB::new(RocksDB::open_tmp(COLUMNS), StoreConfig::default());
+        let consensus = Consensus::default();
+        let forked = ConsensusBuilder::default()
+            .max_block_cycles(consensus.max_block_cycles() + 1)
+            .build();
+        persist_tx_verify_cache(&store, &consensus, entries(3)).unwrap();
+
+        assert!(load_tx_verify_cache(&store, &forked).unwrap().is_empty());
+        assert_eq!(
+            entries(3),
+            load_tx_verify_cache(&store, &consensus).unwrap()
+        );
+
+        // persisted under the new consensus, the old one sees nothing
+        persist_tx_verify_cache(&store, &forked, entries(1)).unwrap();
+        assert_eq!(entries(1), load_tx_verify_cache(&store, &forked).unwrap());
+        assert!(load_tx_verify_cache(&store, &consensus).unwrap().is_empty());
+    }
+
+    #[test]
+    fn restore_tx_verify_cache_after_restart() {
+        let (shared, _) = SharedBuilder::default().build().unwrap();
+        let handle = shared.tx_pool_controller().handle().clone();
+        let cache = shared.txs_verify_cache();
+        block_on(&handle, async move { cache.insert_batch(entries(3)).await });
+        shared.persist_txs_verify_cache().unwrap();
+
+        // the restarted node loads the persisted entries in the background, they don't override
+        // the entries cached since it started
+        let loaded = load_tx_verify_cache(shared.store(), shared.consensus()).unwrap();
+        let cached: (Byte32, _) = ([0; 32].pack(), CacheEntry::new(1, Capacity::zero()));
+        let cache = Arc::new(TxVerifyCache::new(100));
+        let inserted = cached.clone();
+        let restored = block_on(&handle, async move {
+            cache.insert_batch(vec![inserted]).await;
+            cache.warm_up(loaded).await;
+            cache.entries().await
+        });
+        let mut expected = entries(3);
+        expected[0] = cached;
+        assert_eq!(expected, restored);
+    }
+}
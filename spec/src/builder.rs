@@ -0,0 +1,216 @@
+use crate::consensus::Consensus;
+use crate::{ChainSpec, DepGroupResource, IssuedCell, Params, SatoshiGift, SystemCell};
+use ckb_pow::Pow;
+use ckb_resource::Resource;
+use ckb_types::{
+    core::{BlockNumber, Capacity, Cycle, EpochNumber, EpochNumberWithFraction},
+    packed,
+};
+use std::error::Error;
+
+/// Builds a chain spec from Rust code, e.g. a dev chain for the integration tests.
+///
+/// The builder starts from a complete spec, usually the bundled dev chain, and the expected
+/// genesis hash is dropped on `build` since the genesis is likely changed.
+///
+/// ```
+/// use ckb_chain_spec::ChainSpecBuilder;
+/// use ckb_types::{core::Capacity, packed};
+///
+/// let consensus = ChainSpecBuilder::dev()
+///     .name("ckb_integration")
+///     .issued_cell(Capacity::shannons(1_000_000_000_000), packed::Script::default())
+///     .genesis_epoch_length(100)
+///     .build_consensus()
+///     .expect("build consensus");
+/// assert_eq!(consensus.genesis_epoch_ext().length(), 100);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ChainSpecBuilder {
+    spec: ChainSpec,
+}
+
+impl ChainSpecBuilder {
+    /// Starts from the bundled dev chain, which uses the dummy pow engine, the bundled system
+    /// scripts, and issues cells to the well-known dev keys.
+    pub fn dev() -> Self {
+        let spec = ChainSpec::load_from(&Resource::bundled("specs/dev.toml".to_string()))
+            .expect("load bundled dev spec");
+        ChainSpecBuilder::from_spec(spec)
+    }
+
+    pub fn from_spec(spec: ChainSpec) -> Self {
+        ChainSpecBuilder { spec }
+    }
+
+    pub fn name<S: Into<String>>(mut self, name: S) -> Self {
+        self.spec.name = name.into();
+        self
+    }
+
+    pub fn timestamp(mut self, timestamp: u64) -> Self {
+        self.spec.genesis.timestamp = timestamp;
+        self
+    }
+
+    pub fn compact_target(mut self, compact_target: u32) -> Self {
+        self.spec.genesis.compact_target = compact_target;
+        self
+    }
+
+    pub fn genesis_message<S: Into<String>>(mut self, message: S) -> Self {
+        self.spec.genesis.genesis_cell.message = message.into();
+        self
+    }
+
+    /// Appends an issued cell to the genesis cellbase.
+    pub fn issued_cell(mut self, capacity: Capacity, lock: packed::Script) -> Self {
+        self.spec.genesis.issued_cells.push(IssuedCell {
+            capacity,
+            lock: lock.into(),
+        });
+        self
+    }
+
+    /// Replaces all the issued cells, including the ones of the dev keys.
+    pub fn issued_cells(mut self, issued_cells: Vec<IssuedCell>) -> Self {
+        self.spec.genesis.issued_cells = issued_cells;
+        self
+    }
+
+    /// Appends a system cell after the bundled ones, set `capacity` to `None` to use the
+    /// minimal capacity.
+    pub fn system_cell(
+        mut self,
+        file: Resource,
+        create_type_id: bool,
+        capacity: Option<u64>,
+    ) -> Self {
+        self.spec.genesis.system_cells.push(SystemCell {
+            create_type_id,
+            capacity,
+            file,
+        });
+        self
+    }
+
+    pub fn dep_group<S: Into<String>>(mut self, name: S, files: Vec<Resource>) -> Self {
+        self.spec.genesis.dep_groups.push(DepGroupResource {
+            name: name.into(),
+            files,
+        });
+        self
+    }
+
+    pub fn satoshi_gift(mut self, satoshi_gift: SatoshiGift) -> Self {
+        self.spec.genesis.satoshi_gift = satoshi_gift;
+        self
+    }
+
+    pub fn params(mut self, params: Params) -> Self {
+        self.spec.params = params;
+        self
+    }
+
+    pub fn initial_primary_epoch_reward(mut self, reward: Capacity) -> Self {
+        self.spec.params.initial_primary_epoch_reward = reward;
+        self
+    }
+
+    pub fn secondary_epoch_reward(mut self, reward: Capacity) -> Self {
+        self.spec.params.secondary_epoch_reward = reward;
+        self
+    }
+
+    pub fn primary_epoch_reward_halving_interval(mut self, interval: EpochNumber) -> Self {
+        self.spec.params.primary_epoch_reward_halving_interval = interval;
+        self
+    }
+
+    pub fn epoch_duration_target(mut self, seconds: u64) -> Self {
+        self.spec.params.epoch_duration_target = seconds;
+        self
+    }
+
+    pub fn genesis_epoch_length(mut self, length: BlockNumber) -> Self {
+        self.spec.params.genesis_epoch_length = length;
+        self
+    }
+
+    pub fn permanent_difficulty_in_dummy(mut self, permanent: bool) -> Self {
+        self.spec.params.permanent_difficulty_in_dummy = permanent;
+        self
+    }
+
+    pub fn cellbase_maturity(mut self, maturity: EpochNumberWithFraction) -> Self {
+        self.spec.params.cellbase_maturity = maturity.full_value();
+        self
+    }
+
+    pub fn max_block_cycles(mut self, max_block_cycles: Cycle) -> Self {
+        self.spec.params.max_block_cycles = max_block_cycles;
+        self
+    }
+
+    pub fn max_block_bytes(mut self, max_block_bytes: u64) -> Self {
+        self.spec.params.max_block_bytes = max_block_bytes;
+        self
+    }
+
+    pub fn pow(mut self, pow: Pow) -> Self {
+        self.spec.pow = pow;
+        self
+    }
+
+    pub fn build(mut self) -> ChainSpec {
+        self.spec.genesis.hash = None;
+        self.spec
+    }
+
+    /// Builds the spec and its consensus, the genesis block is checked as the spec files.
+    pub fn build_consensus(self) -> Result<Consensus, Box<dyn Error>> {
+        self.build().build_consensus()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ckb_types::{core::ScriptHashType, prelude::*};
+
+    #[test]
+    fn test_build_dev_consensus() {
+        let dev = ChainSpecBuilder::dev().build_consensus().unwrap();
+        let lock = packed::Script::new_builder()
+            .args([1u8; 20][..].pack())
+            .hash_type(ScriptHashType::Data.into())
+            .build();
+        let consensus = ChainSpecBuilder::dev()
+            .name("ckb_builder")
+            .issued_cell(Capacity::shannons(1_000_000_000_000), lock.clone())
+            .genesis_epoch_length(100)
+            .max_block_cycles(1_000_000)
+            .build_consensus()
+            .unwrap();
+
+        assert_eq!(consensus.id, "ckb_builder");
+        assert_ne!(consensus.genesis_hash(), dev.genesis_hash());
+        assert_eq!(consensus.genesis_epoch_ext().length(), 100);
+        assert_eq!(consensus.max_block_cycles(), 1_000_000);
+        let cellbase = &consensus.genesis_block().transactions()[0];
+        assert_eq!(cellbase.outputs().into_iter().last().unwrap().lock(), lock);
+    }
+
+    #[test]
+    fn test_build_without_system_cells() {
+        let result = ChainSpecBuilder::dev()
+            .issued_cells(Vec::new())
+            .build()
+            .build_consensus();
+        assert!(result.is_ok());
+
+        let mut spec = ChainSpecBuilder::dev().build();
+        spec.genesis.system_cells.clear();
+        assert!(ChainSpecBuilder::from_spec(spec).build_consensus().is_err());
+    }
+}
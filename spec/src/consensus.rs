@@ -245,6 +245,7 @@ impl ConsensusBuilder {
                 primary_epoch_reward_halving_interval:
                     DEFAULT_PRIMARY_EPOCH_REWARD_HALVING_INTERVAL,
                 permanent_difficulty_in_dummy: false,
+                vm_version_activations: vec![(0, VmVersion::V0)],
             },
         }
     }
@@ -384,6 +385,31 @@ impl ConsensusBuilder {
         self.inner.permanent_difficulty_in_dummy = permanent;
         self
     }
+
+    // Scripts in blocks since `epoch` run on `version`
+    #[must_use]
+    pub fn vm_version_activation(mut self, epoch: EpochNumber, version: VmVersion) -> Self {
+        let activations = &mut self.inner.vm_version_activations;
+        activations.retain(|(activated_at, _)| *activated_at != epoch);
+        activations.push((epoch, version));
+        activations.sort();
+        self
+    }
+}
+
+/// Versions of CKB-VM, i.e. the ISA and syscalls available to scripts.
+/// New versions are only introduced by hard forks, see `Consensus::vm_version`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum VmVersion {
+    V0 = 0,
+    /// Reserved for the next hard fork, runs scripts the same as `V0` until its changes land
+    V1 = 1,
+}
+
+impl Default for VmVersion {
+    fn default() -> Self {
+        VmVersion::V0
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -431,6 +457,8 @@ pub struct Consensus {
     pub primary_epoch_reward_halving_interval: EpochNumber,
     // Keep difficulty be permanent if the pow is dummy
     pub permanent_difficulty_in_dummy: bool,
+    // VM versions and the epochs they are activated at, sorted by epoch
+    pub vm_version_activations: Vec<(EpochNumber, VmVersion)>,
 }
 
 // genesis difficulty should not be zero
@@ -558,6 +586,11 @@ impl Consensus {
         self.tx_version
     }
 
+    // The VM version scripts run on in the given epoch
+    pub fn vm_version(&self, epoch: EpochNumber) -> VmVersion {
+        activated_in(&self.vm_version_activations, epoch).unwrap_or_default()
+    }
+
     pub fn type_id_code_hash(&self) -> &H256 {
         &self.type_id_code_hash
     }
@@ -785,12 +818,65 @@ fn u256_low_u64(u: U256) -> u64 {
     u.0[0]
 }
 
+// The value of the latest activation no later than `epoch`, the activations are sorted by epoch
+fn activated_in<T: Copy>(activations: &[(EpochNumber, T)], epoch: EpochNumber) -> Option<T> {
+    activations
+        .iter()
+        .rev()
+        .find(|(activated_at, _)| *activated_at <= epoch)
+        .map(|(_, value)| *value)
+}
+
 #[cfg(test)]
 pub mod test {
     use super::*;
     use ckb_types::core::{capacity_bytes, BlockBuilder, HeaderBuilder, TransactionBuilder};
     use ckb_types::packed::Bytes;
 
+    #[test]
+    fn test_activated_in() {
+        let activations = [(2, 'a'), (5, 'b'), (9, 'c')];
+        assert_eq!(activated_in(&activations, 0), None);
+        assert_eq!(activated_in(&activations, 1), None);
+        assert_eq!(activated_in(&activations, 2), Some('a'));
+        assert_eq!(activated_in(&activations, 4), Some('a'));
+        assert_eq!(activated_in(&activations, 5), Some('b'));
+        assert_eq!(activated_in(&activations, 8), Some('b'));
+        assert_eq!(activated_in(&activations, 9), Some('c'));
+        assert_eq!(
+            activated_in(&activations, EpochNumber::max_value()),
+            Some('c')
+        );
+        assert_eq!(activated_in::<char>(&[], 9), None);
+    }
+
+    #[test]
+    fn test_vm_version() {
+        let consensus = ConsensusBuilder::default().build();
+        assert_eq!(consensus.vm_version(0), VmVersion::V0);
+        assert_eq!(
+            consensus.vm_version(EpochNumber::max_value()),
+            VmVersion::V0
+        );
+
+        // a later activation replaces the one of the same epoch and keeps the order
+        let consensus = ConsensusBuilder::default()
+            .vm_version_activation(10, VmVersion::V0)
+            .vm_version_activation(3, VmVersion::V0)
+            .vm_version_activation(10, VmVersion::V1)
+            .build();
+        assert_eq!(
+            consensus.vm_version_activations,
+            vec![(0, VmVersion::V0), (3, VmVersion::V0), (10, VmVersion::V1)]
+        );
+        for epoch in &[0, 2, 3, 9] {
+            assert_eq!(consensus.vm_version(*epoch), VmVersion::V0);
+        }
+        for epoch in &[10, 11, EpochNumber::max_value()] {
+            assert_eq!(consensus.vm_version(*epoch), VmVersion::V1);
+        }
+    }
+
     #[test]
     fn test_init_epoch_reward() {
         let cellbase = TransactionBuilder::default()
@@ -38,8 +38,10 @@ use std::error::Error;
 use std::fmt;
 use std::sync::Arc;
 
+pub use builder::ChainSpecBuilder;
 pub use error::SpecError;
 
+mod builder;
 pub mod consensus;
 mod error;
 
@@ -1,7 +1,7 @@
 use ckb_app_config::StoreConfig;
 use ckb_types::{
     bytes::Bytes,
-    core::{HeaderView, TransactionView, UncleBlockVecView},
+    core::{BlockNumber, HeaderView, TransactionView, UncleBlockVecView},
     packed::{Byte32, ProposalShortIdVec},
 };
 use ckb_util::Mutex;
@@ -14,6 +14,9 @@ pub struct StoreCache {
     pub block_tx_hashes: Mutex<LruCache<Byte32, Vec<Byte32>>>,
     pub block_uncles: Mutex<LruCache<Byte32, UncleBlockVecView>>,
     pub cellbase: Mutex<LruCache<Byte32, TransactionView>>,
+    /// The number of the last pruned block, loaded by `ChainDB::new` and updated by
+    /// `ChainDB::prune_blocks`, so reading a block does not look it up in the store
+    pub pruned_block_number: Mutex<Option<BlockNumber>>,
 }
 
 impl Default for StoreCache {
@@ -31,6 +34,7 @@ impl StoreCache {
             block_tx_hashes: Mutex::new(LruCache::new(config.block_tx_hashes_cache_size)),
             block_uncles: Mutex::new(LruCache::new(config.block_uncles_cache_size)),
             cellbase: Mutex::new(LruCache::new(config.cellbase_cache_size)),
+            pruned_block_number: Mutex::new(None),
         }
     }
 }
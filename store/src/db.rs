@@ -1,7 +1,8 @@
 use crate::cache::StoreCache;
+use crate::pruning::PRUNE_SAFETY_DEPTH;
 use crate::store::ChainStore;
 use crate::transaction::StoreTransaction;
-use crate::StoreSnapshot;
+use crate::{StoreSnapshot, COLUMN_META, META_PRUNED_BLOCK_NUMBER_KEY};
 use ckb_app_config::StoreConfig;
 use ckb_chain_spec::consensus::Consensus;
 use ckb_db::{
@@ -11,14 +12,17 @@ use ckb_db::{
 use ckb_error::Error;
 use ckb_types::{
     core::{BlockExt, TransactionMeta},
+    packed,
     prelude::*,
 };
+use std::cmp;
 use std::sync::Arc;
 
 #[derive(Clone)]
 pub struct ChainDB {
     db: RocksDB,
-    cache: Arc<StoreCache>,
+    pub(crate) cache: Arc<StoreCache>,
+    prune_keep_blocks: u64,
 }
 
 impl<'a> ChainStore<'a> for ChainDB {
@@ -42,12 +46,29 @@ impl<'a> ChainStore<'a> for ChainDB {
 impl ChainDB {
     pub fn new(db: RocksDB, config: StoreConfig) -> Self {
         let cache = StoreCache::from_config(config);
+        // never prune the blocks which may be detached by a reorg
+        let prune_keep_blocks = if config.prune_keep_blocks == 0 {
+            0
+        } else {
+            cmp::max(config.prune_keep_blocks, PRUNE_SAFETY_DEPTH)
+        };
+        let pruned_block_number = db
+            .get_pinned(COLUMN_META, META_PRUNED_BLOCK_NUMBER_KEY)
+            .expect("db operation should be ok")
+            .map(|raw| packed::Uint64Reader::from_slice_should_be_ok(&raw[..]).unpack());
+        *cache.pruned_block_number.lock() = pruned_block_number;
         ChainDB {
             db,
             cache: Arc::new(cache),
+            prune_keep_blocks,
         }
     }
 
+    /// The number of the latest blocks whose bodies are kept, 0 means pruning is disabled.
+    pub fn prune_keep_blocks(&self) -> u64 {
+        self.prune_keep_blocks
+    }
+
     pub fn db(&self) -> &RocksDB {
         &self.db
     }
@@ -114,6 +135,7 @@ impl ChainDB {
         db_txn.insert_block_epoch_index(&genesis_hash, &last_block_hash_in_previous_epoch)?;
         db_txn.insert_epoch_ext(&last_block_hash_in_previous_epoch, &epoch)?;
         db_txn.attach_block(genesis)?;
+        db_txn.insert_chain_root_leaf(0, &genesis_hash)?;
         db_txn.commit()?;
         Ok(())
     }
@@ -125,7 +147,11 @@ mod tests {
     use super::*;
     use ckb_chain_spec::consensus::ConsensusBuilder;
     use ckb_db::RocksDB;
-    use ckb_types::packed;
+    use ckb_types::{
+        bytes::Bytes,
+        packed,
+        utilities::{block_filter_match_any, calc_block_filter_hash, merkle_root},
+    };
 
     fn setup_db(columns: u32) -> RocksDB {
         RocksDB::open_tmp(columns)
@@ -207,4 +233,130 @@ mod tests {
 
         assert_eq!(block.header(), store.get_tip_header().unwrap());
     }
+
+    #[test]
+    fn attach_and_detach_block_filter() {
+        let db = setup_db(COLUMNS);
+        let store = ChainDB::new(db, Default::default());
+        let lock = packed::Script::new_builder()
+            .args(Bytes::from(vec![1, 2, 3]).pack())
+            .build();
+        let output = packed::CellOutput::new_builder().lock(lock).build();
+        let tx = packed::Transaction::new_builder()
+            .raw(
+                packed::RawTransaction::new_builder()
+                    .outputs(vec![output.clone()].pack())
+                    .outputs_data(vec![Bytes::new().pack()].pack())
+                    .build(),
+            )
+            .build();
+        let block = packed::Block::new_builder()
+            .transactions(vec![tx].pack())
+            .build()
+            .into_view();
+        let hash = block.hash();
+
+        let txn = store.begin_transaction();
+        txn.insert_block(&block).unwrap();
+        txn.attach_block(&block).unwrap();
+        txn.commit().unwrap();
+        let filter = store.get_block_filter(&hash).unwrap();
+        assert!(block_filter_match_any(
+            &hash,
+            &filter.raw_data(),
+            &[output.calc_lock_hash()]
+        ));
+        assert_eq!(
+            store.get_block_filter_hash(&hash),
+            Some(calc_block_filter_hash(
+                &packed::Byte32::zero(),
+                &filter.raw_data()
+            ))
+        );
+
+        let txn = store.begin_transaction();
+        txn.detach_block(&block).unwrap();
+        txn.commit().unwrap();
+        assert!(store.get_block_filter(&hash).is_none());
+        assert!(store.get_block_filter_hash(&hash).is_none());
+    }
+
+    #[test]
+    fn insert_and_delete_chain_root_leaves() {
+        let db = setup_db(COLUMNS);
+        let store = ChainDB::new(db, Default::default());
+        let hashes: Vec<packed::Byte32> = (0..10u8).map(|i| [i; 32].pack()).collect();
+
+        let txn = store.begin_transaction();
+        for (number, hash) in hashes.iter().enumerate() {
+            txn.insert_chain_root_leaf(number as u64, hash).unwrap();
+        }
+        txn.commit().unwrap();
+        let root = store.get_chain_root(9).unwrap();
+        for (number, hash) in hashes.iter().enumerate() {
+            let proof = store.get_chain_root_proof(number as u64, 9).unwrap();
+            assert!(proof.verify(&root, number as u64, hash));
+        }
+        assert!(store.get_chain_root_proof(10, 9).is_none());
+        assert!(store.get_chain_root(10).is_none());
+
+        let root_5 = store.get_chain_root(5).unwrap();
+        let txn = store.begin_transaction();
+        for number in (6..10).rev() {
+            txn.delete_chain_root_leaf(number).unwrap();
+        }
+        txn.commit().unwrap();
+        assert!(store.get_chain_root(6).is_none());
+        assert_eq!(store.get_chain_root(5), Some(root_5));
+        assert!(store
+            .begin_transaction()
+            .insert_chain_root_leaf(7, &hashes[7])
+            .is_err());
+    }
+
+    #[test]
+    fn get_transactions_proof() {
+        let db = setup_db(COLUMNS);
+        let store = ChainDB::new(db, Default::default());
+        let txs: Vec<packed::Transaction> = (0..3u64)
+            .map(|i| {
+                let output = packed::CellOutput::new_builder().capacity(i.pack()).build();
+                packed::Transaction::new_builder()
+                    .raw(
+                        packed::RawTransaction::new_builder()
+                            .outputs(vec![output].pack())
+                            .outputs_data(vec![Bytes::new().pack()].pack())
+                            .build(),
+                    )
+                    .build()
+            })
+            .collect();
+        let block = packed::Block::new_builder()
+            .transactions(txs.pack())
+            .build()
+            .into_view();
+        let txn = store.begin_transaction();
+        txn.insert_block(&block).unwrap();
+        txn.commit().unwrap();
+
+        let tx_hashes = block.tx_hashes();
+        let root = merkle_root(tx_hashes);
+        let proof = store
+            .get_transactions_proof(&block.hash(), &[tx_hashes[2].clone(), tx_hashes[0].clone()])
+            .unwrap();
+        let first_leaf_index = tx_hashes.len() as u32 - 1;
+        let leaves: Vec<_> = proof
+            .indices()
+            .iter()
+            .map(|index| tx_hashes[(index - first_leaf_index) as usize].clone())
+            .collect();
+        assert_eq!(proof.root(&leaves), Some(root));
+
+        assert!(store
+            .get_transactions_proof(&block.hash(), &[packed::Byte32::zero()])
+            .is_none());
+        assert!(store
+            .get_transactions_proof(&packed::Byte32::zero(), &[tx_hashes[0].clone()])
+            .is_none());
+    }
 }
@@ -1,19 +1,24 @@
 mod cache;
 pub mod data_loader_wrapper;
 mod db;
+mod metrics;
+mod pruning;
+mod readonly;
 mod snapshot;
 mod store;
 mod transaction;
 
 pub use cache::StoreCache;
 pub use db::ChainDB;
+pub use pruning::PRUNE_SAFETY_DEPTH;
+pub use readonly::ChainStoreReadonly;
 pub use snapshot::StoreSnapshot;
 pub use store::ChainStore;
 pub use transaction::StoreTransaction;
 
 use ckb_db::Col;
 
-pub const COLUMNS: u32 = 12;
+pub const COLUMNS: u32 = 16;
 pub const COLUMN_INDEX: Col = "0";
 pub const COLUMN_BLOCK_HEADER: Col = "1";
 pub const COLUMN_BLOCK_BODY: Col = "2";
@@ -26,6 +31,13 @@ pub const COLUMN_BLOCK_EPOCH: Col = "8";
 pub const COLUMN_EPOCH: Col = "9";
 pub const COLUMN_CELL_SET: Col = "10";
 pub const COLUMN_UNCLES: Col = "11";
+pub const COLUMN_TX_VERIFY_CACHE: Col = "12";
+pub const COLUMN_BLOCK_FILTER: Col = "13";
+pub const COLUMN_CHAIN_ROOT_MMR: Col = "14";
+pub const COLUMN_BLOCK_FILTER_HASH: Col = "15";
 
 const META_TIP_HEADER_KEY: &[u8] = b"TIP_HEADER";
 const META_CURRENT_EPOCH_KEY: &[u8] = b"CURRENT_EPOCH";
+const META_PRUNED_BLOCK_NUMBER_KEY: &[u8] = b"PRUNED_BLOCK_NUMBER";
+const META_INVALIDATED_BLOCKS_KEY: &[u8] = b"INVALIDATED_BLOCKS";
+const META_UNVERIFIED_SCRIPTS_KEY: &[u8] = b"UNVERIFIED_SCRIPTS";
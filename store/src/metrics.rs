@@ -0,0 +1,10 @@
+use ckb_metrics::{register_histogram, Histogram, Subsystem};
+use lazy_static::lazy_static;
+
+lazy_static! {
+    pub(crate) static ref COMMIT_DURATION: Histogram = register_histogram(
+        Subsystem::Store,
+        "ckb_store_commit_duration_seconds",
+        "Time to commit a store transaction",
+    );
+}
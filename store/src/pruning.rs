@@ -0,0 +1,184 @@
+//! Pruning of the old block bodies.
+//!
+//! A pruned node deletes the transactions of the main chain blocks older than
+//! `prune_keep_blocks`, while the headers, uncles, proposals, the cell set and the recent blocks
+//! are retained. The cell data are read from the block bodies, so a transaction is only pruned
+//! once all its outputs are dead, and a reorg may revive the outputs consumed by the recent
+//! blocks, so the transactions spent within `PRUNE_SAFETY_DEPTH` are kept as well. A transaction
+//! retained this way stays after its block is pruned. The blocks whose scripts are not verified
+//! yet, see `get_unverified_scripts_range`, are not pruned until they are.
+use crate::db::ChainDB;
+use crate::store::ChainStore;
+use crate::COLUMN_BLOCK_BODY;
+use ckb_error::Error;
+use ckb_types::{core::BlockNumber, packed, prelude::*};
+use std::cmp;
+use std::collections::HashSet;
+
+/// The depth of the deepest supported reorg on a pruned node, the blocks within it are never
+/// pruned.
+pub const PRUNE_SAFETY_DEPTH: BlockNumber = 1_000;
+/// Blocks are pruned in batches, so the recent spends are collected once for a batch.
+const PRUNE_BATCH_SIZE: BlockNumber = 100;
+/// At most this many blocks are pruned by a call, so catching up does not stall the chain.
+const MAX_PRUNE_BLOCKS: BlockNumber = 1_000;
+
+impl ChainDB {
+    /// Prunes the next batch of the blocks out of the kept depth, returns the number of the last
+    /// pruned block, or `None` if it is not the time to prune.
+    pub fn prune_blocks(&self) -> Result<Option<BlockNumber>, Error> {
+        let keep_blocks = self.prune_keep_blocks();
+        if keep_blocks == 0 {
+            return Ok(None);
+        }
+        let tip_number = match self.get_tip_header() {
+            Some(tip) => tip.number(),
+            None => return Ok(None),
+        };
+        let previous = self.get_pruned_block_number();
+        let pruned = previous.unwrap_or(0);
+        let mut target = tip_number.saturating_sub(keep_blocks);
+        if let Some((start, _)) = self.get_unverified_scripts_range() {
+            target = cmp::min(target, start.saturating_sub(1));
+        }
+        if target < pruned + PRUNE_BATCH_SIZE {
+            return Ok(None);
+        }
+        let target = cmp::min(target, pruned + MAX_PRUNE_BLOCKS);
+
+        let recent_spent = self.recent_spent_txs(tip_number);
+        let txn = self.begin_transaction();
+        for number in (pruned + 1)..=target {
+            let block_hash = match self.get_block_hash(number) {
+                Some(block_hash) => block_hash,
+                None => break,
+            };
+            for (index, tx_hash) in self.get_block_txs_hashes(&block_hash).iter().enumerate() {
+                if self.get_tx_meta(tx_hash).is_some() || recent_spent.contains(tx_hash) {
+                    continue;
+                }
+                let key = packed::TransactionKey::new_builder()
+                    .block_hash(block_hash.clone())
+                    .index(index.pack())
+                    .build();
+                txn.delete(COLUMN_BLOCK_BODY, key.as_slice())?;
+            }
+        }
+        txn.insert_pruned_block_number(target)?;
+        if let Err(err) = txn.commit() {
+            *self.cache.pruned_block_number.lock() = previous;
+            return Err(err);
+        }
+        Ok(Some(target))
+    }
+
+    // The transactions whose outputs are consumed by the blocks within the safety depth
+    fn recent_spent_txs(&self, tip_number: BlockNumber) -> HashSet<packed::Byte32> {
+        let from = tip_number.saturating_sub(PRUNE_SAFETY_DEPTH) + 1;
+        (from..=tip_number)
+            .filter_map(|number| self.get_block_hash(number))
+            .flat_map(|block_hash| self.get_block_body(&block_hash))
+            .flat_map(|tx| {
+                tx.input_pts_iter()
+                    .map(|out_point| out_point.tx_hash())
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::COLUMNS;
+    use ckb_app_config::StoreConfig;
+    use ckb_chain_spec::consensus::ConsensusBuilder;
+    use ckb_db::RocksDB;
+    use ckb_types::core::{BlockBuilder, BlockView, Capacity, TransactionBuilder, TransactionMeta};
+
+    fn insert_main_chain_block(store: &ChainDB, block: &BlockView) {
+        let txn = store.begin_transaction();
+        txn.insert_block(block).unwrap();
+        txn.attach_block(block).unwrap();
+        txn.insert_tip_header(&block.header()).unwrap();
+        txn.commit().unwrap();
+    }
+
+    #[test]
+    fn test_prune_dead_transactions() {
+        let config = StoreConfig {
+            prune_keep_blocks: 1,
+            ..Default::default()
+        };
+        let store = ChainDB::new(RocksDB::open_tmp(COLUMNS), config);
+        assert_eq!(store.prune_keep_blocks(), PRUNE_SAFETY_DEPTH);
+        let consensus = ConsensusBuilder::default().build();
+        store.init(&consensus).unwrap();
+
+        let tx = |seed: u64| {
+            TransactionBuilder::default()
+                .output(
+                    packed::CellOutput::new_builder()
+                        .capacity(Capacity::shannons(seed).pack())
+                        .build(),
+                )
+                .output_data(Default::default())
+                .build()
+        };
+        let dead = tx(1);
+        let live = tx(2);
+        let spent_recently = tx(3);
+        let mut parent = consensus.genesis_block().header();
+        let tip_number = PRUNE_SAFETY_DEPTH + PRUNE_BATCH_SIZE;
+        for number in 1..=tip_number {
+            let mut builder = BlockBuilder::default()
+                .parent_hash(parent.hash())
+                .number(number.pack());
+            if number == 1 {
+                builder =
+                    builder.transactions(vec![dead.clone(), live.clone(), spent_recently.clone()]);
+            }
+            if number == tip_number {
+                let spend = TransactionBuilder::default()
+                    .input(packed::CellInput::new(
+                        packed::OutPoint::new(spent_recently.hash(), 0),
+                        0,
+                    ))
+                    .build();
+                builder = builder.transaction(spend);
+            }
+            let block = builder.build();
+            insert_main_chain_block(&store, &block);
+            parent = block.header();
+        }
+        let txn = store.begin_transaction();
+        let meta = TransactionMeta::new(1, 0, store.get_block_hash(1).unwrap(), 1, false);
+        txn.update_cell_set(&live.hash(), &meta.pack()).unwrap();
+        txn.commit().unwrap();
+
+        // the blocks whose scripts are not verified yet are kept
+        let txn = store.begin_transaction();
+        txn.insert_unverified_scripts_range(50, 60).unwrap();
+        txn.commit().unwrap();
+        assert_eq!(store.prune_blocks().unwrap(), None);
+        let txn = store.begin_transaction();
+        txn.delete_unverified_scripts_range().unwrap();
+        txn.commit().unwrap();
+
+        assert_eq!(store.prune_blocks().unwrap(), Some(PRUNE_BATCH_SIZE));
+        assert_eq!(store.prune_blocks().unwrap(), None);
+        let block_hash = store.get_block_hash(1).unwrap();
+        assert!(store.is_pruned(&block_hash));
+        assert!(store.get_block(&block_hash).is_none());
+        assert!(store.get_block_header(&block_hash).is_some());
+        assert!(store.get_transaction(&dead.hash()).is_none());
+        assert!(store.get_transaction(&live.hash()).is_some());
+        assert!(store.get_transaction(&spent_recently.hash()).is_some());
+        assert!(!store.is_pruned(&store.get_block_hash(0).unwrap()));
+
+        // the pruned number is loaded when the store is opened again
+        let reopened = ChainDB::new(store.db().clone(), config);
+        assert!(reopened.is_pruned(&block_hash));
+        assert!(!reopened.is_pruned(&store.get_block_hash(PRUNE_BATCH_SIZE + 1).unwrap()));
+    }
+}
@@ -0,0 +1,113 @@
+use crate::cache::StoreCache;
+use crate::store::ChainStore;
+use crate::COLUMNS;
+use ckb_app_config::DBConfig;
+use ckb_db::{
+    iter::{DBIter, DBIterator, IteratorMode},
+    Col, DBPinnableSlice, SecondaryDB,
+};
+use ckb_error::Error;
+use ckb_logger::warn;
+use ckb_stop_handler::{SignalSender, StopHandler};
+use crossbeam_channel::{bounded, RecvTimeoutError};
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+/// The chain store read by the processes other than the node, e.g. the explorers and the
+/// indexers, from the data directory of a running node.
+///
+/// The database is opened as a RocksDB secondary instance, which sees the blocks committed by
+/// the node after `catch_up`. The store has no cache, since the cached entries may be stale.
+#[derive(Clone)]
+pub struct ChainStoreReadonly {
+    db: SecondaryDB,
+}
+
+impl<'a> ChainStore<'a> for ChainStoreReadonly {
+    type Vector = DBPinnableSlice<'a>;
+
+    fn cache(&'a self) -> Option<&'a StoreCache> {
+        None
+    }
+
+    fn get(&'a self, col: Col, key: &[u8]) -> Option<Self::Vector> {
+        self.db
+            .get_pinned(col, key)
+            .expect("db operation should be ok")
+    }
+
+    fn get_iter(&self, col: Col, mode: IteratorMode) -> DBIter {
+        self.db.iter(col, mode).expect("db operation should be ok")
+    }
+}
+
+impl ChainStoreReadonly {
+    /// Opens the database of `config.path`, `secondary_path` is a directory owned by this
+    /// instance.
+    pub fn open<P: AsRef<Path>>(config: &DBConfig, secondary_path: P) -> Result<Self, Error> {
+        let db = SecondaryDB::open_cf(config, COLUMNS, secondary_path)?;
+        Ok(ChainStoreReadonly { db })
+    }
+
+    /// Catches up with the blocks committed by the node since the last call.
+    pub fn catch_up(&self) -> Result<(), Error> {
+        self.db.try_catch_up_with_primary()
+    }
+
+    /// Spawns a thread catching up every `interval` until the returned handler is stopped.
+    pub fn spawn_tailing(&self, interval: Duration) -> StopHandler<()> {
+        let (signal_sender, signal_receiver) = bounded::<()>(1);
+        let store = self.clone();
+        let thread = thread::Builder::new()
+            .name("ChainStoreReadonlyTailing".to_string())
+            .spawn(move || loop {
+                match signal_receiver.recv_timeout(interval) {
+                    Err(RecvTimeoutError::Timeout) => {
+                        if let Err(err) = store.catch_up() {
+                            warn!("readonly chain store failed to catch up: {}", err);
+                        }
+                    }
+                    _ => break,
+                }
+            })
+            .expect("Start ChainStoreReadonlyTailing failed");
+        StopHandler::new(SignalSender::Crossbeam(signal_sender), thread)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ChainStoreReadonly;
+    use crate::{ChainDB, ChainStore, COLUMNS};
+    use ckb_app_config::{DBConfig, StoreConfig};
+    use ckb_chain_spec::consensus::ConsensusBuilder;
+    use ckb_db::{Migrations, RocksDB};
+
+    #[test]
+    fn test_read_blocks_of_primary() {
+        let tmp_dir = tempfile::Builder::new()
+            .prefix("test_read_blocks_of_primary")
+            .tempdir()
+            .unwrap();
+        let config = DBConfig {
+            path: tmp_dir.path().join("db"),
+            ..Default::default()
+        };
+        let db = RocksDB::open(&config, COLUMNS, Migrations::default());
+        let store = ChainDB::new(db, StoreConfig::default());
+
+        let readonly = ChainStoreReadonly::open(&config, tmp_dir.path().join("secondary")).unwrap();
+        assert!(readonly.get_tip_header().is_none());
+
+        let consensus = ConsensusBuilder::default().build();
+        store.init(&consensus).unwrap();
+        readonly.catch_up().unwrap();
+        let genesis = consensus.genesis_block();
+        assert_eq!(
+            readonly.get_tip_header().map(|header| header.hash()),
+            Some(genesis.hash())
+        );
+        assert_eq!(readonly.get_block(&genesis.hash()).as_ref(), Some(genesis));
+    }
+}
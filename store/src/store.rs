@@ -1,9 +1,10 @@
 use crate::cache::StoreCache;
 use crate::{
-    COLUMN_BLOCK_BODY, COLUMN_BLOCK_EPOCH, COLUMN_BLOCK_EXT, COLUMN_BLOCK_HEADER,
-    COLUMN_BLOCK_PROPOSAL_IDS, COLUMN_BLOCK_UNCLE, COLUMN_CELL_SET, COLUMN_EPOCH, COLUMN_INDEX,
-    COLUMN_META, COLUMN_TRANSACTION_INFO, COLUMN_UNCLES, META_CURRENT_EPOCH_KEY,
-    META_TIP_HEADER_KEY,
+    COLUMN_BLOCK_BODY, COLUMN_BLOCK_EPOCH, COLUMN_BLOCK_EXT, COLUMN_BLOCK_FILTER,
+    COLUMN_BLOCK_FILTER_HASH, COLUMN_BLOCK_HEADER, COLUMN_BLOCK_PROPOSAL_IDS, COLUMN_BLOCK_UNCLE,
+    COLUMN_CELL_SET, COLUMN_CHAIN_ROOT_MMR, COLUMN_EPOCH, COLUMN_INDEX, COLUMN_META,
+    COLUMN_TRANSACTION_INFO, COLUMN_UNCLES, META_CURRENT_EPOCH_KEY, META_INVALIDATED_BLOCKS_KEY,
+    META_PRUNED_BLOCK_NUMBER_KEY, META_TIP_HEADER_KEY, META_UNVERIFIED_SCRIPTS_KEY,
 };
 use ckb_chain_spec::consensus::Consensus;
 use ckb_db::{
@@ -19,7 +20,9 @@ use ckb_types::{
     },
     packed::{self, OutPoint},
     prelude::*,
+    utilities::{mmr_size_from_leaves, MMRProof, MerkleProof, CBMT, MMR},
 };
+use std::collections::HashMap;
 
 pub struct CellProviderWrapper<'a, S>(&'a S);
 
@@ -32,8 +35,11 @@ pub trait ChainStore<'a>: Send + Sync + Sized {
         CellProviderWrapper(self)
     }
 
-    /// Get block by block header hash
+    /// Get block by block header hash, returns `None` if the body of the block is pruned
     fn get_block(&'a self, h: &packed::Byte32) -> Option<BlockView> {
+        if self.is_pruned(h) {
+            return None;
+        }
         self.get_block_header(h).map(|header| {
             let body = self.get_block_body(h);
             let uncles = self
@@ -115,6 +121,29 @@ pub trait ChainStore<'a>: Send + Sync + Sized {
         ret
     }
 
+    /// Get the proof that the transactions are in the block, it verifies against the merkle root
+    /// of the block transaction hashes. Returns `None` if the block body is not found or any of
+    /// the transactions is not in it.
+    fn get_transactions_proof(
+        &'a self,
+        block_hash: &packed::Byte32,
+        tx_hashes: &[packed::Byte32],
+    ) -> Option<MerkleProof> {
+        let block_tx_hashes = self.get_block_txs_hashes(block_hash);
+        let positions: HashMap<&packed::Byte32, u32> = block_tx_hashes
+            .iter()
+            .enumerate()
+            .map(|(index, tx_hash)| (tx_hash, index as u32))
+            .collect();
+        let mut indices = tx_hashes
+            .iter()
+            .map(|tx_hash| positions.get(tx_hash).cloned())
+            .collect::<Option<Vec<_>>>()?;
+        indices.sort();
+        indices.dedup();
+        CBMT::build_merkle_proof(&block_tx_hashes, &indices)
+    }
+
     /// Get proposal short id by block header hash
     fn get_block_proposal_txs_ids(
         &'a self,
@@ -207,12 +236,13 @@ pub trait ChainStore<'a>: Send + Sync + Sized {
             .map(Into::into)
     }
 
-    /// Get commit transaction and block hash by its hash
+    /// Get commit transaction and block hash by its hash, returns `None` if the transaction is
+    /// pruned
     fn get_transaction(
         &'a self,
         hash: &packed::Byte32,
     ) -> Option<(TransactionView, packed::Byte32)> {
-        self.get_transaction_info_packed(hash).map(|info| {
+        self.get_transaction_info_packed(hash).and_then(|info| {
             self.get(COLUMN_BLOCK_BODY, info.key().as_slice())
                 .map(|slice| {
                     let reader =
@@ -220,7 +250,6 @@ pub trait ChainStore<'a>: Send + Sync + Sized {
                     let hash = info.as_reader().key().block_hash().to_entity();
                     (reader.unpack(), hash)
                 })
-                .expect("since tx info is existed, so tx data should be existed")
         })
     }
 
@@ -367,6 +396,90 @@ pub trait ChainStore<'a>: Send + Sync + Sized {
             .and_then(|index| self.get_epoch_ext(&index))
     }
 
+    /// Get the compact filter of a block, which commits to the lock and type script hashes of
+    /// the cells it creates and consumes
+    fn get_block_filter(&'a self, block_hash: &packed::Byte32) -> Option<packed::Bytes> {
+        self.get(COLUMN_BLOCK_FILTER, block_hash.as_slice())
+            .map(|raw| packed::BytesReader::from_slice_should_be_ok(&raw.as_ref()).to_entity())
+    }
+
+    /// Get the filter hash of a main chain block, which chains the filters from the genesis
+    fn get_block_filter_hash(&'a self, block_hash: &packed::Byte32) -> Option<packed::Byte32> {
+        self.get(COLUMN_BLOCK_FILTER_HASH, block_hash.as_slice())
+            .map(|raw| packed::Byte32Reader::from_slice_should_be_ok(&raw.as_ref()).to_entity())
+    }
+
+    /// Get the node at `pos` of the chain root MMR, whose leaves are the main chain block hashes
+    fn get_chain_root_mmr_node(&'a self, pos: u64) -> Option<packed::Byte32> {
+        let key: packed::Uint64 = pos.pack();
+        self.get(COLUMN_CHAIN_ROOT_MMR, key.as_slice())
+            .map(|raw| packed::Byte32Reader::from_slice_should_be_ok(&raw.as_ref()).to_entity())
+    }
+
+    /// Get the chain root, the MMR root of the main chain block hashes from genesis to
+    /// `block_number`
+    fn get_chain_root(&'a self, block_number: BlockNumber) -> Option<packed::Byte32> {
+        let mmr_size = mmr_size_from_leaves(block_number + 1);
+        MMR::new(mmr_size, |pos| self.get_chain_root_mmr_node(pos)).get_root()
+    }
+
+    /// Get the proof that the main chain block `block_number` is an ancestor of the main chain
+    /// block `last_block_number`, it verifies against the chain root of `last_block_number`
+    fn get_chain_root_proof(
+        &'a self,
+        block_number: BlockNumber,
+        last_block_number: BlockNumber,
+    ) -> Option<MMRProof> {
+        if block_number > last_block_number {
+            return None;
+        }
+        let mmr_size = mmr_size_from_leaves(last_block_number + 1);
+        MMR::new(mmr_size, |pos| self.get_chain_root_mmr_node(pos)).gen_proof(block_number)
+    }
+
+    /// Get the number of the last main chain block whose body is pruned
+    fn get_pruned_block_number(&'a self) -> Option<BlockNumber> {
+        if let Some(cache) = self.cache() {
+            return *cache.pruned_block_number.lock();
+        }
+        self.get(COLUMN_META, META_PRUNED_BLOCK_NUMBER_KEY)
+            .map(|raw| packed::Uint64Reader::from_slice_should_be_ok(&raw.as_ref()[..]).unpack())
+    }
+
+    /// Get the hashes of the blocks invalidated manually by the operator
+    fn get_invalidated_blocks(&'a self) -> Vec<packed::Byte32> {
+        self.get(COLUMN_META, META_INVALIDATED_BLOCKS_KEY)
+            .map(|raw| {
+                packed::Byte32VecReader::from_slice_should_be_ok(&raw.as_ref()[..])
+                    .to_entity()
+                    .into_iter()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Get the range of the main chain block numbers, inclusive, whose scripts are skipped by the
+    /// assume-valid sync and not verified yet
+    fn get_unverified_scripts_range(&'a self) -> Option<(BlockNumber, BlockNumber)> {
+        self.get(COLUMN_META, META_UNVERIFIED_SCRIPTS_KEY)
+            .map(|raw| {
+                let range: Vec<BlockNumber> =
+                    packed::Uint64VecReader::from_slice_should_be_ok(&raw.as_ref()[..]).unpack();
+                (range[0], range[1])
+            })
+    }
+
+    /// Whether the body of the block is pruned. The header, uncles, proposals and the
+    /// transactions which still have live cells are retained.
+    fn is_pruned(&'a self, hash: &packed::Byte32) -> bool {
+        self.get_pruned_block_number()
+            .and_then(|pruned| {
+                self.get_block_number(hash)
+                    .map(|number| number > 0 && number <= pruned)
+            })
+            .unwrap_or(false)
+    }
+
     fn is_uncle(&'a self, hash: &packed::Byte32) -> bool {
         self.get(COLUMN_UNCLES, hash.as_slice()).is_some()
     }
@@ -1,22 +1,26 @@
 use crate::cache::StoreCache;
+use crate::metrics::COMMIT_DURATION;
 use crate::store::ChainStore;
 use crate::{
-    COLUMN_BLOCK_BODY, COLUMN_BLOCK_EPOCH, COLUMN_BLOCK_EXT, COLUMN_BLOCK_HEADER,
-    COLUMN_BLOCK_PROPOSAL_IDS, COLUMN_BLOCK_UNCLE, COLUMN_CELL_SET, COLUMN_EPOCH, COLUMN_INDEX,
-    COLUMN_META, COLUMN_TRANSACTION_INFO, COLUMN_UNCLES, META_CURRENT_EPOCH_KEY,
-    META_TIP_HEADER_KEY,
+    COLUMN_BLOCK_BODY, COLUMN_BLOCK_EPOCH, COLUMN_BLOCK_EXT, COLUMN_BLOCK_FILTER,
+    COLUMN_BLOCK_FILTER_HASH, COLUMN_BLOCK_HEADER, COLUMN_BLOCK_PROPOSAL_IDS, COLUMN_BLOCK_UNCLE,
+    COLUMN_CELL_SET, COLUMN_CHAIN_ROOT_MMR, COLUMN_EPOCH, COLUMN_INDEX, COLUMN_META,
+    COLUMN_TRANSACTION_INFO, COLUMN_UNCLES, META_CURRENT_EPOCH_KEY, META_INVALIDATED_BLOCKS_KEY,
+    META_PRUNED_BLOCK_NUMBER_KEY, META_TIP_HEADER_KEY, META_UNVERIFIED_SCRIPTS_KEY,
 };
 use ckb_db::{
     iter::{DBIter, DBIterator, IteratorMode},
     Col, DBVector, RocksDBTransaction, RocksDBTransactionSnapshot,
 };
-use ckb_error::Error;
+use ckb_error::{Error, InternalErrorKind};
 use ckb_types::{
-    core::{BlockExt, BlockView, EpochExt, HeaderView},
+    core::{BlockExt, BlockNumber, BlockView, EpochExt, HeaderView},
     packed,
     prelude::*,
+    utilities::{build_block_filter, calc_block_filter_hash, mmr_size_from_leaves, MMR},
 };
 use std::sync::Arc;
+use std::time::Instant;
 
 pub struct StoreTransaction {
     pub(crate) inner: RocksDBTransaction,
@@ -74,7 +78,10 @@ impl StoreTransaction {
     }
 
     pub fn commit(&self) -> Result<(), Error> {
-        self.inner.commit()
+        let started_at = Instant::now();
+        let result = self.inner.commit();
+        COMMIT_DURATION.observe_duration(started_at.elapsed());
+        result
     }
 
     pub fn get_snapshot(&self) -> StoreTransactionSnapshot<'_> {
@@ -100,6 +107,37 @@ impl StoreTransaction {
         self.insert_raw(COLUMN_META, META_TIP_HEADER_KEY, h.hash().as_slice())
     }
 
+    /// The cached number is updated at once, so the blocks are seen pruned before the commit
+    /// deletes their bodies, a reader never gets a partial body.
+    pub fn insert_pruned_block_number(&self, number: BlockNumber) -> Result<(), Error> {
+        let packed_number: packed::Uint64 = number.pack();
+        self.insert_raw(
+            COLUMN_META,
+            META_PRUNED_BLOCK_NUMBER_KEY,
+            packed_number.as_slice(),
+        )?;
+        *self.cache.pruned_block_number.lock() = Some(number);
+        Ok(())
+    }
+
+    pub fn insert_invalidated_blocks(&self, hashes: &[packed::Byte32]) -> Result<(), Error> {
+        let hashes: packed::Byte32Vec = hashes.to_vec().pack();
+        self.insert_raw(COLUMN_META, META_INVALIDATED_BLOCKS_KEY, hashes.as_slice())
+    }
+
+    pub fn insert_unverified_scripts_range(
+        &self,
+        start: BlockNumber,
+        end: BlockNumber,
+    ) -> Result<(), Error> {
+        let range: packed::Uint64Vec = [start, end][..].pack();
+        self.insert_raw(COLUMN_META, META_UNVERIFIED_SCRIPTS_KEY, range.as_slice())
+    }
+
+    pub fn delete_unverified_scripts_range(&self) -> Result<(), Error> {
+        self.delete(COLUMN_META, META_UNVERIFIED_SCRIPTS_KEY)
+    }
+
     pub fn insert_block(&self, block: &BlockView) -> Result<(), Error> {
         let hash = block.hash();
         let header = block.header().pack();
@@ -150,6 +188,7 @@ impl StoreTransaction {
                 .build();
             self.insert_raw(COLUMN_TRANSACTION_INFO, tx_hash.as_slice(), info.as_slice())?;
         }
+        self.insert_block_filter(block)?;
         let block_number: packed::Uint64 = block.number().pack();
         self.insert_raw(COLUMN_INDEX, block_number.as_slice(), block_hash.as_slice())?;
         for uncle in block.uncles().into_iter() {
@@ -162,6 +201,36 @@ impl StoreTransaction {
         self.insert_raw(COLUMN_INDEX, block_hash.as_slice(), block_number.as_slice())
     }
 
+    /// Insert the filter of the main chain block, and its filter hash if the one of the parent
+    /// exists, the transaction infos of the block must be inserted before.
+    pub fn insert_block_filter(&self, block: &BlockView) -> Result<(), Error> {
+        let block_hash = block.hash();
+        let filter: packed::Bytes =
+            build_block_filter(&block_hash, &self.block_script_hashes(block)).pack();
+        self.insert_raw(
+            COLUMN_BLOCK_FILTER,
+            block_hash.as_slice(),
+            filter.as_slice(),
+        )?;
+        // the chain of the filter hashes starts from the genesis, it is broken at the blocks
+        // attached before the filter hashes were introduced
+        let parent_block_filter_hash = if block.is_genesis() {
+            Some(packed::Byte32::zero())
+        } else {
+            self.get_block_filter_hash(&block.data().header().raw().parent_hash())
+        };
+        if let Some(parent_block_filter_hash) = parent_block_filter_hash {
+            let block_filter_hash =
+                calc_block_filter_hash(&parent_block_filter_hash, &filter.raw_data());
+            self.insert_raw(
+                COLUMN_BLOCK_FILTER_HASH,
+                block_hash.as_slice(),
+                block_filter_hash.as_slice(),
+            )?;
+        }
+        Ok(())
+    }
+
     pub fn detach_block(&self, block: &BlockView) -> Result<(), Error> {
         for tx_hash in block.tx_hashes().iter() {
             self.delete(COLUMN_TRANSACTION_INFO, tx_hash.as_slice())?;
@@ -169,11 +238,76 @@ impl StoreTransaction {
         for uncle in block.uncles().into_iter() {
             self.delete(COLUMN_UNCLES, uncle.hash().as_slice())?;
         }
+        self.delete(COLUMN_BLOCK_FILTER, block.hash().as_slice())?;
+        self.delete(COLUMN_BLOCK_FILTER_HASH, block.hash().as_slice())?;
         let block_number = block.data().header().raw().number();
         self.delete(COLUMN_INDEX, block_number.as_slice())?;
         self.delete(COLUMN_INDEX, block.hash().as_slice())
     }
 
+    /// Append the hash of the main chain block `block_number` to the chain root MMR, the
+    /// blocks before it must have been appended.
+    pub fn insert_chain_root_leaf(
+        &self,
+        block_number: BlockNumber,
+        block_hash: &packed::Byte32,
+    ) -> Result<(), Error> {
+        let mut mmr = MMR::new(mmr_size_from_leaves(block_number), |pos| {
+            self.get_chain_root_mmr_node(pos)
+        });
+        if mmr.push(block_hash.clone()).is_none() {
+            return Err(InternalErrorKind::DataCorrupted
+                .reason(format!(
+                    "chain root MMR is missing nodes below {}",
+                    block_number
+                ))
+                .into());
+        }
+        let (start_pos, nodes) = mmr.into_batch();
+        for (offset, node) in nodes.iter().enumerate() {
+            let pos: packed::Uint64 = (start_pos + offset as u64).pack();
+            self.insert_raw(COLUMN_CHAIN_ROOT_MMR, pos.as_slice(), node.as_slice())?;
+        }
+        Ok(())
+    }
+
+    /// Remove the hash of the main chain block `block_number`, which must be the last leaf,
+    /// from the chain root MMR.
+    pub fn delete_chain_root_leaf(&self, block_number: BlockNumber) -> Result<(), Error> {
+        for pos in mmr_size_from_leaves(block_number)..mmr_size_from_leaves(block_number + 1) {
+            let pos: packed::Uint64 = pos.pack();
+            self.delete(COLUMN_CHAIN_ROOT_MMR, pos.as_slice())?;
+        }
+        Ok(())
+    }
+
+    // Lock and type script hashes of all cells created or consumed by the block, the inputs are
+    // resolved through the transaction infos, which must be inserted before.
+    fn block_script_hashes(&self, block: &BlockView) -> Vec<packed::Byte32> {
+        let mut hashes = Vec::new();
+        let mut push_output = |output: &packed::CellOutput| {
+            hashes.push(output.calc_lock_hash());
+            if let Some(type_script) = output.type_().to_opt() {
+                hashes.push(type_script.calc_script_hash());
+            }
+        };
+        for tx in block.transactions() {
+            if !tx.is_cellbase() {
+                for out_point in tx.input_pts_iter() {
+                    if let Some(cell_meta) =
+                        self.get_cell_meta(&out_point.tx_hash(), out_point.index().unpack())
+                    {
+                        push_output(&cell_meta.cell_output);
+                    }
+                }
+            }
+            for output in tx.outputs().into_iter() {
+                push_output(&output);
+            }
+        }
+        hashes
+    }
+
     pub fn insert_block_epoch_index(
         &self,
         block_hash: &packed::Byte32,
@@ -0,0 +1,156 @@
+use ckb_types::{
+    core::BlockNumber,
+    packed::{self, Byte32},
+    prelude::*,
+    utilities::compact_to_target,
+    U256,
+};
+use serde_json::{json, Value};
+use std::collections::{HashSet, VecDeque};
+
+/// The bytes of the nonce searched by a miner, the rest 4 bytes are the `extranonce1` of the
+/// session.
+pub(crate) const EXTRANONCE2_SIZE: usize = 12;
+/// The recent jobs whose shares are still accepted.
+const MAX_JOBS: usize = 8;
+
+pub(crate) struct Job {
+    pub job_id: u64,
+    pub work_id: u64,
+    pub block: packed::Block,
+    pub pow_hash: Byte32,
+    pub block_target: U256,
+    pub created_at: u64,
+    submitted: HashSet<u128>,
+}
+
+impl Job {
+    pub fn new(job_id: u64, work_id: u64, block: packed::Block, created_at: u64) -> Self {
+        let pow_hash = block.header().calc_pow_hash();
+        let (block_target, _) = compact_to_target(block.header().raw().compact_target().unpack());
+        Job {
+            job_id,
+            work_id,
+            block,
+            pow_hash,
+            block_target,
+            created_at,
+            submitted: HashSet::new(),
+        }
+    }
+
+    pub fn parent_hash(&self) -> Byte32 {
+        self.block.header().raw().parent_hash()
+    }
+
+    pub fn number(&self) -> BlockNumber {
+        self.block.header().raw().number().unpack()
+    }
+
+    pub fn notify_params(&self, clean_jobs: bool) -> Value {
+        json!([
+            format!("{:x}", self.job_id),
+            hex(self.pow_hash.as_slice()),
+            self.number(),
+            hex(self.parent_hash().as_slice()),
+            clean_jobs,
+        ])
+    }
+
+    /// Records the nonce, returns false if it has been submitted.
+    pub fn record_nonce(&mut self, nonce: u128) -> bool {
+        self.submitted.insert(nonce)
+    }
+
+    pub fn solve(&self, nonce: u128) -> packed::Block {
+        let header = self.block.header().as_builder().nonce(nonce.pack()).build();
+        self.block.clone().as_builder().header(header).build()
+    }
+}
+
+/// The recent jobs, the latest last.
+#[derive(Default)]
+pub(crate) struct Jobs {
+    next_job_id: u64,
+    jobs: VecDeque<Job>,
+}
+
+impl Jobs {
+    pub fn push(&mut self, work_id: u64, block: packed::Block, created_at: u64, clean: bool) {
+        if clean {
+            self.jobs.clear();
+        }
+        while self.jobs.len() >= MAX_JOBS {
+            self.jobs.pop_front();
+        }
+        self.next_job_id += 1;
+        self.jobs
+            .push_back(Job::new(self.next_job_id, work_id, block, created_at));
+    }
+
+    pub fn latest(&self) -> Option<&Job> {
+        self.jobs.back()
+    }
+
+    pub fn get_mut(&mut self, job_id: &str) -> Option<&mut Job> {
+        let job_id = u64::from_str_radix(job_id, 16).ok()?;
+        self.jobs.iter_mut().find(|job| job.job_id == job_id)
+    }
+}
+
+/// Composes the nonce from the `extranonce1` of the session and the hex `extranonce2` of the
+/// miner.
+pub(crate) fn compose_nonce(extranonce1: u32, extranonce2: &str) -> Option<u128> {
+    if extranonce2.len() != EXTRANONCE2_SIZE * 2 {
+        return None;
+    }
+    let extranonce2 = u128::from_str_radix(extranonce2, 16).ok()?;
+    Some(u128::from(extranonce1) << (EXTRANONCE2_SIZE * 8) | extranonce2)
+}
+
+pub(crate) fn target_hex(target: &U256) -> String {
+    let mut bytes = [0u8; 32];
+    target.into_big_endian(&mut bytes).expect("bound checked");
+    hex(&bytes)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ckb_types::core::BlockBuilder;
+
+    #[test]
+    fn test_compose_nonce() {
+        assert_eq!(
+            compose_nonce(0x0102_0304, "000000000000000000000005"),
+            Some(0x0102_0304_0000_0000_0000_0000_0000_0005)
+        );
+        assert_eq!(compose_nonce(1, "05"), None);
+        assert_eq!(compose_nonce(1, "zz0000000000000000000005"), None);
+    }
+
+    #[test]
+    fn test_jobs() {
+        let block = |number: u64| BlockBuilder::default().number(number.pack()).build().data();
+        let mut jobs = Jobs::default();
+        for number in 0..10 {
+            jobs.push(number, block(number), 0, false);
+        }
+        assert_eq!(jobs.latest().map(|job| job.job_id), Some(10));
+        assert!(jobs.get_mut("1").is_none());
+        assert!(jobs.get_mut("a").is_some());
+
+        let job = jobs.get_mut("a").unwrap();
+        assert!(job.record_nonce(7));
+        assert!(!job.record_nonce(7));
+        assert_eq!(job.solve(7).header().nonce().unpack(), 7u128);
+
+        jobs.push(10, block(10), 0, true);
+        assert!(jobs.get_mut("a").is_none());
+        assert!(jobs.get_mut("b").is_some());
+    }
+}
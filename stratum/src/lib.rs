@@ -0,0 +1,14 @@
+//! A stratum server which serves the block templates of the block assembler to the miners.
+//!
+//! The miners speak the line delimited JSON-RPC over TCP. A miner subscribes to get its
+//! `extranonce1` and the size of `extranonce2`, authorizes, and then receives the jobs by
+//! `mining.notify`. The nonce of a solution is `extranonce1 ‖ extranonce2` in big endian, so the
+//! miners search disjoint nonce spaces on the same job.
+//!
+//! The jobs are refreshed on the new tip, or on the new transactions of the tx pool once the
+//! current job is older than `job_refresh_interval`.
+mod job;
+mod protocol;
+mod server;
+
+pub use crate::server::StratumServer;
@@ -0,0 +1,102 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+pub(crate) const METHOD_SUBSCRIBE: &str = "mining.subscribe";
+pub(crate) const METHOD_AUTHORIZE: &str = "mining.authorize";
+pub(crate) const METHOD_SUBMIT: &str = "mining.submit";
+pub(crate) const METHOD_NOTIFY: &str = "mining.notify";
+pub(crate) const METHOD_SET_TARGET: &str = "mining.set_target";
+
+// The error codes used by the stratum pools
+pub(crate) const ERROR_OTHER: i64 = 20;
+pub(crate) const ERROR_JOB_NOT_FOUND: i64 = 21;
+pub(crate) const ERROR_DUPLICATE_SHARE: i64 = 22;
+pub(crate) const ERROR_LOW_DIFFICULTY_SHARE: i64 = 23;
+pub(crate) const ERROR_UNAUTHORIZED: i64 = 24;
+pub(crate) const ERROR_NOT_SUBSCRIBED: i64 = 25;
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct Request {
+    #[serde(default)]
+    pub id: Value,
+    pub method: String,
+    #[serde(default)]
+    pub params: Vec<Value>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct Response {
+    pub id: Value,
+    pub result: Value,
+    pub error: Value,
+}
+
+impl Response {
+    pub fn ok(id: Value, result: Value) -> Self {
+        Response {
+            id,
+            result,
+            error: Value::Null,
+        }
+    }
+
+    pub fn error(id: Value, code: i64, message: &str) -> Self {
+        Response {
+            id,
+            result: Value::Null,
+            error: json!([code, message, Value::Null]),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct Notification {
+    pub id: Value,
+    pub method: &'static str,
+    pub params: Value,
+}
+
+impl Notification {
+    pub fn new(method: &'static str, params: Value) -> Self {
+        Notification {
+            id: Value::Null,
+            method,
+            params,
+        }
+    }
+}
+
+/// Serializes a message into a line.
+pub(crate) fn to_line<T: Serialize>(message: &T) -> String {
+    let mut line = serde_json::to_string(message).expect("serialize stratum message");
+    line.push('\n');
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_messages() {
+        let request: Request = serde_json::from_str(
+            r#"{"id": 4, "method": "mining.submit", "params": ["worker", "1", "00ff"]}"#,
+        )
+        .expect("parse request");
+        assert_eq!(request.method, METHOD_SUBMIT);
+        assert_eq!(request.params.len(), 3);
+
+        assert_eq!(
+            to_line(&Response::error(
+                json!(4),
+                ERROR_JOB_NOT_FOUND,
+                "job not found"
+            )),
+            "{\"id\":4,\"result\":null,\"error\":[21,\"job not found\",null]}\n"
+        );
+        assert_eq!(
+            to_line(&Notification::new(METHOD_SET_TARGET, json!(["ff"]))),
+            "{\"id\":null,\"method\":\"mining.set_target\",\"params\":[\"ff\"]}\n"
+        );
+    }
+}
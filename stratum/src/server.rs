@@ -0,0 +1,382 @@
+use crate::job::{compose_nonce, target_hex, Jobs, EXTRANONCE2_SIZE};
+use crate::protocol::*;
+use ckb_app_config::StratumConfig;
+use ckb_chain::chain::ChainController;
+use ckb_logger::{debug, error, info, warn};
+use ckb_network::NetworkController;
+use ckb_shared::{shared::Shared, Snapshot};
+use ckb_sync::NetworkProtocol;
+use ckb_types::{core, packed, prelude::*, U256};
+use ckb_util::Mutex;
+use ckb_verification::{HeaderResolverWrapper, HeaderVerifier, Verifier};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// A miner which does not read its socket is dropped after it.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+
+struct Session {
+    stream: TcpStream,
+    subscribed: bool,
+    authorized: bool,
+}
+
+/// Serves the jobs to the connected miners, the clones share the same sessions and jobs.
+#[derive(Clone)]
+pub struct StratumServer {
+    config: StratumConfig,
+    shared: Shared,
+    chain: ChainController,
+    network_controller: NetworkController,
+    sessions: Arc<Mutex<HashMap<u32, Session>>>,
+    jobs: Arc<Mutex<Jobs>>,
+    next_session_id: Arc<AtomicU32>,
+}
+
+impl StratumServer {
+    pub fn new(
+        config: StratumConfig,
+        shared: Shared,
+        chain: ChainController,
+        network_controller: NetworkController,
+    ) -> Self {
+        StratumServer {
+            config,
+            shared,
+            chain,
+            network_controller,
+            sessions: Default::default(),
+            jobs: Default::default(),
+            next_session_id: Default::default(),
+        }
+    }
+
+    /// Listens on `listen_address` and starts the threads which accept the miners and refresh
+    /// the jobs.
+    pub fn start(self) -> io::Result<()> {
+        let listener = TcpListener::bind(&self.config.listen_address)?;
+        info!("stratum server listen on {}", self.config.listen_address);
+
+        let server = self.clone();
+        thread::Builder::new()
+            .name("StratumListener".to_string())
+            .spawn(move || {
+                for stream in listener.incoming() {
+                    match stream {
+                        Ok(stream) => server.accept(stream),
+                        Err(err) => warn!("stratum accept error: {}", err),
+                    }
+                }
+            })?;
+
+        thread::Builder::new()
+            .name("StratumJobs".to_string())
+            .spawn(move || {
+                let poll_interval = Duration::from_millis(self.config.poll_interval);
+                let mut last_txs_updated_at = 0;
+                loop {
+                    self.refresh_job(&mut last_txs_updated_at);
+                    thread::sleep(poll_interval);
+                }
+            })?;
+        Ok(())
+    }
+
+    fn accept(&self, stream: TcpStream) {
+        let session_id = self.next_session_id.fetch_add(1, Ordering::SeqCst);
+        let writer = match stream
+            .set_write_timeout(Some(WRITE_TIMEOUT))
+            .and_then(|_| stream.try_clone())
+        {
+            Ok(writer) => writer,
+            Err(err) => {
+                warn!("stratum session setup error: {}", err);
+                return;
+            }
+        };
+        self.sessions.lock().insert(
+            session_id,
+            Session {
+                stream: writer,
+                subscribed: false,
+                authorized: false,
+            },
+        );
+
+        let server = self.clone();
+        if let Err(err) = thread::Builder::new()
+            .name("StratumSession".to_string())
+            .spawn(move || {
+                debug!("stratum session {} connected", session_id);
+                for line in BufReader::new(stream).lines() {
+                    let line = match line {
+                        Ok(line) => line,
+                        Err(_) => break,
+                    };
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    match serde_json::from_str::<Request>(&line) {
+                        Ok(request) => server.handle(session_id, request),
+                        Err(err) => {
+                            debug!("stratum session {} invalid request: {}", session_id, err);
+                            break;
+                        }
+                    }
+                }
+                server.sessions.lock().remove(&session_id);
+                debug!("stratum session {} disconnected", session_id);
+            })
+        {
+            error!("failed to spawn the thread of stratum session: {}", err);
+            self.sessions.lock().remove(&session_id);
+        }
+    }
+
+    fn handle(&self, session_id: u32, request: Request) {
+        let id = request.id.clone();
+        let response = match request.method.as_str() {
+            METHOD_SUBSCRIBE => {
+                self.update_session(session_id, |session| session.subscribed = true);
+                Response::ok(
+                    id,
+                    json!([Value::Null, format!("{:08x}", session_id), EXTRANONCE2_SIZE]),
+                )
+            }
+            METHOD_AUTHORIZE => {
+                self.update_session(session_id, |session| session.authorized = true);
+                Response::ok(id, Value::Bool(true))
+            }
+            METHOD_SUBMIT => match self.submit(session_id, &request.params) {
+                Ok(()) => Response::ok(id, Value::Bool(true)),
+                Err((code, message)) => Response::error(id, code, &message),
+            },
+            _ => Response::error(id, ERROR_OTHER, "unknown method"),
+        };
+        self.send(session_id, &to_line(&response));
+
+        // The miner gets the current job right after subscribing
+        if request.method == METHOD_SUBSCRIBE {
+            let lines = self
+                .jobs
+                .lock()
+                .latest()
+                .map(|job| self.job_lines(job.block_target, job.notify_params(true)));
+            if let Some(lines) = lines {
+                self.send(session_id, &lines);
+            }
+        }
+    }
+
+    fn update_session<F: FnOnce(&mut Session)>(&self, session_id: u32, f: F) {
+        if let Some(session) = self.sessions.lock().get_mut(&session_id) {
+            f(session);
+        }
+    }
+
+    fn send(&self, session_id: u32, lines: &str) {
+        let mut sessions = self.sessions.lock();
+        let failed = match sessions.get_mut(&session_id) {
+            Some(session) => session.stream.write_all(lines.as_bytes()).is_err(),
+            None => false,
+        };
+        if failed {
+            // the reader thread quits once the socket is shut down
+            if let Some(session) = sessions.remove(&session_id) {
+                let _ = session.stream.shutdown(std::net::Shutdown::Both);
+            }
+        }
+    }
+
+    fn broadcast(&self, lines: &str) {
+        let mut sessions = self.sessions.lock();
+        let failed: Vec<u32> = sessions
+            .iter_mut()
+            .filter(|(_, session)| session.subscribed)
+            .filter_map(|(session_id, session)| {
+                session
+                    .stream
+                    .write_all(lines.as_bytes())
+                    .err()
+                    .map(|_| *session_id)
+            })
+            .collect();
+        for session_id in failed {
+            if let Some(session) = sessions.remove(&session_id) {
+                let _ = session.stream.shutdown(std::net::Shutdown::Both);
+            }
+        }
+    }
+
+    // The shares must meet the share target, which is never harder than the block target.
+    fn share_target(&self, block_target: U256) -> U256 {
+        if self.config.share_difficulty == 0 {
+            return block_target;
+        }
+        let share_target = U256::max_value() / U256::from(self.config.share_difficulty);
+        if share_target > block_target {
+            share_target
+        } else {
+            block_target
+        }
+    }
+
+    fn job_lines(&self, block_target: U256, notify_params: Value) -> String {
+        let set_target = Notification::new(
+            METHOD_SET_TARGET,
+            json!([target_hex(&self.share_target(block_target))]),
+        );
+        let notify = Notification::new(METHOD_NOTIFY, notify_params);
+        format!("{}{}", to_line(&set_target), to_line(&notify))
+    }
+
+    // Creates a new job on the new tip, or on the new transactions once the current job expires,
+    // and notifies the miners.
+    fn refresh_job(&self, last_txs_updated_at: &mut u64) {
+        let tip_hash = self.shared.snapshot().tip_hash();
+        let tx_pool = self.shared.tx_pool_controller();
+        let txs_updated_at = match tx_pool.get_tx_pool_info() {
+            Ok(info) => info.last_txs_updated_at,
+            Err(err) => {
+                error!("stratum get tx pool info error: {}", err);
+                return;
+            }
+        };
+        let now = self.shared.clock().unix_time_as_millis();
+        let (new_tip, expired) = match self.jobs.lock().latest() {
+            Some(job) => (
+                job.parent_hash() != tip_hash,
+                now.saturating_sub(job.created_at) >= self.config.job_refresh_interval * 1000,
+            ),
+            None => (true, true),
+        };
+        if !new_tip && !(expired && txs_updated_at != *last_txs_updated_at) {
+            return;
+        }
+
+        let template = match tx_pool.get_block_template(None, None, None) {
+            Ok(Ok(template)) => template,
+            Ok(Err(err)) | Err(err) => {
+                error!("stratum get block template error: {}", err);
+                return;
+            }
+        };
+        *last_txs_updated_at = txs_updated_at;
+        let work_id: u64 = template.work_id.into();
+        let block: packed::Block = template.into();
+        let lines = {
+            let mut jobs = self.jobs.lock();
+            jobs.push(work_id, block, now, new_tip);
+            let job = jobs.latest().expect("job pushed");
+            debug!(
+                "stratum job {:x} of work {} at {}, clean {}",
+                job.job_id,
+                work_id,
+                job.number(),
+                new_tip
+            );
+            self.job_lines(job.block_target, job.notify_params(new_tip))
+        };
+        self.broadcast(&lines);
+    }
+
+    fn submit(&self, session_id: u32, params: &[Value]) -> Result<(), (i64, String)> {
+        let (subscribed, authorized) = self
+            .sessions
+            .lock()
+            .get(&session_id)
+            .map(|session| (session.subscribed, session.authorized))
+            .unwrap_or_default();
+        if !subscribed {
+            return Err((ERROR_NOT_SUBSCRIBED, "not subscribed".to_string()));
+        }
+        if !authorized {
+            return Err((ERROR_UNAUTHORIZED, "unauthorized worker".to_string()));
+        }
+
+        let invalid = || (ERROR_OTHER, "invalid params".to_string());
+        let worker = params.get(0).and_then(Value::as_str).ok_or_else(invalid)?;
+        let job_id = params.get(1).and_then(Value::as_str).ok_or_else(invalid)?;
+        let nonce = params
+            .get(2)
+            .and_then(Value::as_str)
+            .and_then(|extranonce2| compose_nonce(session_id, extranonce2))
+            .ok_or_else(invalid)?;
+
+        let (work_id, block, block_target) = {
+            let mut jobs = self.jobs.lock();
+            let job = jobs
+                .get_mut(job_id)
+                .ok_or_else(|| (ERROR_JOB_NOT_FOUND, "job not found".to_string()))?;
+            if !job.record_nonce(nonce) {
+                return Err((ERROR_DUPLICATE_SHARE, "duplicate share".to_string()));
+            }
+            (job.work_id, job.solve(nonce), job.block_target)
+        };
+
+        let engine = self.shared.consensus().pow_engine();
+        if let Some(output) = engine.pow_output(&block.header()) {
+            if output > self.share_target(block_target) {
+                return Err((
+                    ERROR_LOW_DIFFICULTY_SHARE,
+                    "low difficulty share".to_string(),
+                ));
+            }
+            if output > block_target {
+                debug!("stratum share of job {} accepted from {}", job_id, worker);
+                return Ok(());
+            }
+        }
+
+        info!(
+            "stratum block {} of work {} found by {}",
+            block.header().raw().number().unpack(),
+            work_id,
+            worker
+        );
+        self.process_block(block).map_err(|err| (ERROR_OTHER, err))
+    }
+
+    fn process_block(&self, block: packed::Block) -> Result<(), String> {
+        let block: Arc<core::BlockView> = Arc::new(block.into_view());
+        let header = block.header();
+
+        // Verify header
+        let snapshot: &Snapshot = &self.shared.snapshot();
+        let resolver = HeaderResolverWrapper::new(&header, snapshot);
+        HeaderVerifier::new(snapshot, &self.shared.consensus())
+            .verify(&resolver)
+            .map_err(|err| err.to_string())?;
+
+        // Verify and insert block
+        let is_new = self
+            .chain
+            .process_block(Arc::clone(&block))
+            .map_err(|err| err.to_string())?;
+
+        // Announce only new block
+        if is_new {
+            debug!(
+                "[block_relay] announce new block {} {} {}",
+                header.number(),
+                header.hash(),
+                self.shared.clock().unix_time_as_millis()
+            );
+            let content = packed::CompactBlock::build_from_block(&block, &HashSet::new());
+            let message = packed::RelayMessage::new_builder().set(content).build();
+            if let Err(err) = self
+                .network_controller
+                .quick_broadcast(NetworkProtocol::RELAY.into(), message.as_bytes())
+            {
+                error!("Broadcast new block failed: {:?}", err);
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,257 @@
+use ckb_logger::{debug, info};
+use ckb_network::{bytes::Bytes, CKBProtocolContext, CKBProtocolHandler, PeerIndex};
+use ckb_shared::shared::Shared;
+use ckb_store::ChainStore;
+use ckb_types::{core::BlockNumber, packed, prelude::*};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// Upper bound of filters returned by a single `BlockFilters` message
+const MAX_FILTERS_PER_MESSAGE: u64 = 1000;
+// Upper bound of total filters size of a single `BlockFilters` message, leave room for the
+// block hashes and the message header in the frame
+const MAX_FILTERS_SIZE: usize = ckb_network::MAX_FRAME_LENGTH_BLOCK_FILTER / 2;
+// A peer is served `GetBlockFilters` at most once per this interval, which reads the filters of
+// up to `MAX_FILTERS_PER_MESSAGE` blocks, the requests arriving earlier are ignored
+const GET_BLOCK_FILTERS_INTERVAL: Duration = Duration::from_millis(100);
+// Upper bound of filter hashes returned by a single `BlockFilterHashes` message
+const MAX_FILTER_HASHES_PER_MESSAGE: u64 = 2000;
+// The check points are the filter hashes of every `CHECK_POINT_INTERVAL` blocks, a light client
+// downloads the filter hashes between two check points from any peer and checks them against
+// the check points
+const CHECK_POINT_INTERVAL: u64 = 2000;
+// Upper bound of check points returned by a single `BlockFilterCheckPoints` message
+const MAX_CHECK_POINTS_PER_MESSAGE: u64 = 2000;
+
+/// Serve compact block filters of the main chain to light clients, along with the filter hashes
+/// and their check points, which the light clients check the filters against
+#[derive(Clone)]
+pub struct BlockFilterProtocol {
+    shared: Shared,
+    get_block_filters_limiter: PeerRequestLimiter,
+}
+
+impl BlockFilterProtocol {
+    pub fn new(shared: Shared) -> Self {
+        BlockFilterProtocol {
+            shared,
+            get_block_filters_limiter: PeerRequestLimiter::new(GET_BLOCK_FILTERS_INTERVAL),
+        }
+    }
+
+    fn get_block_filters(&self, start_number: BlockNumber) -> packed::BlockFilters {
+        let snapshot = self.shared.snapshot();
+        let end_number = snapshot
+            .tip_number()
+            .min(start_number.saturating_add(MAX_FILTERS_PER_MESSAGE - 1));
+        let mut block_hashes = Vec::new();
+        let mut filters = Vec::new();
+        let mut filters_size = 0;
+        for number in start_number..=end_number {
+            // Blocks attached before filters were introduced have no filter, stop there
+            let (block_hash, filter) = match snapshot.get_block_hash(number).and_then(|hash| {
+                snapshot
+                    .get_block_filter(&hash)
+                    .map(|filter| (hash, filter))
+            }) {
+                Some(pair) => pair,
+                None => break,
+            };
+            filters_size += filter.as_slice().len();
+            if filters_size > MAX_FILTERS_SIZE && !filters.is_empty() {
+                break;
+            }
+            block_hashes.push(block_hash);
+            filters.push(filter);
+        }
+        packed::BlockFilters::new_builder()
+            .start_number(start_number.pack())
+            .block_hashes(block_hashes.pack())
+            .filters(filters.pack())
+            .build()
+    }
+
+    fn get_block_filter_hashes(&self, start_number: BlockNumber) -> packed::BlockFilterHashes {
+        let snapshot = self.shared.snapshot();
+        let get_block_filter_hash = |number| {
+            snapshot
+                .get_block_hash(number)
+                .and_then(|hash| snapshot.get_block_filter_hash(&hash))
+        };
+        let parent_block_filter_hash = if start_number == 0 {
+            Some(packed::Byte32::zero())
+        } else {
+            get_block_filter_hash(start_number - 1)
+        };
+        let mut block_filter_hashes = Vec::new();
+        // The chain of the filter hashes is broken at the blocks attached before they were
+        // introduced, return nothing
+        if parent_block_filter_hash.is_some() {
+            let end_number = snapshot
+                .tip_number()
+                .min(start_number.saturating_add(MAX_FILTER_HASHES_PER_MESSAGE - 1));
+            block_filter_hashes = (start_number..=end_number)
+                .map(get_block_filter_hash)
+                .take_while(Option::is_some)
+                .flatten()
+                .collect();
+        }
+        packed::BlockFilterHashes::new_builder()
+            .start_number(start_number.pack())
+            .parent_block_filter_hash(parent_block_filter_hash.unwrap_or_default())
+            .block_filter_hashes(block_filter_hashes.pack())
+            .build()
+    }
+
+    fn get_block_filter_check_points(
+        &self,
+        start_number: BlockNumber,
+    ) -> packed::BlockFilterCheckPoints {
+        let snapshot = self.shared.snapshot();
+        let tip_number = snapshot.tip_number();
+        let block_filter_hashes: Vec<packed::Byte32> = (0..MAX_CHECK_POINTS_PER_MESSAGE)
+            .map(|index| start_number.saturating_add(index * CHECK_POINT_INTERVAL))
+            .take_while(|number| *number <= tip_number)
+            .map(|number| {
+                snapshot
+                    .get_block_hash(number)
+                    .and_then(|hash| snapshot.get_block_filter_hash(&hash))
+            })
+            .take_while(Option::is_some)
+            .flatten()
+            .collect();
+        packed::BlockFilterCheckPoints::new_builder()
+            .start_number(start_number.pack())
+            .block_filter_hashes(block_filter_hashes.pack())
+            .build()
+    }
+}
+
+// Serves a request of a peer only if the last one served is at least `interval` ago
+#[derive(Clone)]
+struct PeerRequestLimiter {
+    interval: Duration,
+    served_at: HashMap<PeerIndex, Instant>,
+}
+
+impl PeerRequestLimiter {
+    fn new(interval: Duration) -> Self {
+        PeerRequestLimiter {
+            interval,
+            served_at: HashMap::default(),
+        }
+    }
+
+    fn allow(&mut self, peer_index: PeerIndex, now: Instant) -> bool {
+        match self.served_at.get(&peer_index) {
+            Some(served_at) if now.saturating_duration_since(*served_at) < self.interval => false,
+            _ => {
+                self.served_at.insert(peer_index, now);
+                true
+            }
+        }
+    }
+
+    fn remove(&mut self, peer_index: PeerIndex) {
+        self.served_at.remove(&peer_index);
+    }
+}
+
+impl CKBProtocolHandler for BlockFilterProtocol {
+    fn init(&mut self, _nc: Arc<dyn CKBProtocolContext + Sync>) {}
+
+    fn disconnected(&mut self, _nc: Arc<dyn CKBProtocolContext + Sync>, peer_index: PeerIndex) {
+        self.get_block_filters_limiter.remove(peer_index);
+    }
+
+    fn received(
+        &mut self,
+        nc: Arc<dyn CKBProtocolContext + Sync>,
+        peer_index: PeerIndex,
+        data: Bytes,
+    ) {
+        let message = match packed::BlockFilterMessageReader::from_slice(&data) {
+            Ok(message) => message.to_enum(),
+            Err(_) => {
+                info!("Peer {} sends us malformed message", peer_index);
+                nc.report_misbehavior(
+                    peer_index,
+                    "block_filter.malformed_message",
+                    "send us a malformed message",
+                );
+                return;
+            }
+        };
+
+        match message {
+            packed::BlockFilterMessageUnionReader::GetBlockFilters(reader) => {
+                if !self
+                    .get_block_filters_limiter
+                    .allow(peer_index, Instant::now())
+                {
+                    debug!("Peer {} requests block filters too often", peer_index);
+                    return;
+                }
+                let start_number: BlockNumber = reader.start_number().unpack();
+                let block_filters = self.get_block_filters(start_number);
+                let message = packed::BlockFilterMessage::new_builder()
+                    .set(block_filters)
+                    .build();
+                if let Err(err) = nc.send_message_to(peer_index, message.as_bytes()) {
+                    debug!("block_filter send message error: {:?}", err);
+                }
+            }
+            packed::BlockFilterMessageUnionReader::GetBlockFilterHashes(reader) => {
+                let start_number: BlockNumber = reader.start_number().unpack();
+                let block_filter_hashes = self.get_block_filter_hashes(start_number);
+                let message = packed::BlockFilterMessage::new_builder()
+                    .set(block_filter_hashes)
+                    .build();
+                if let Err(err) = nc.send_message_to(peer_index, message.as_bytes()) {
+                    debug!("block_filter send message error: {:?}", err);
+                }
+            }
+            packed::BlockFilterMessageUnionReader::GetBlockFilterCheckPoints(reader) => {
+                let start_number: BlockNumber = reader.start_number().unpack();
+                let check_points = self.get_block_filter_check_points(start_number);
+                let message = packed::BlockFilterMessage::new_builder()
+                    .set(check_points)
+                    .build();
+                if let Err(err) = nc.send_message_to(peer_index, message.as_bytes()) {
+                    debug!("block_filter send message error: {:?}", err);
+                }
+            }
+            packed::BlockFilterMessageUnionReader::BlockFilters(_)
+            | packed::BlockFilterMessageUnionReader::BlockFilterHashes(_)
+            | packed::BlockFilterMessageUnionReader::BlockFilterCheckPoints(_) => {
+                debug!("Peer {} sends us unrequested block filters", peer_index);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_peer_request_limiter() {
+        let mut limiter = PeerRequestLimiter::new(Duration::from_millis(100));
+        let now = Instant::now();
+        let (peer1, peer2) = (PeerIndex::new(1), PeerIndex::new(2));
+
+        assert!(limiter.allow(peer1, now));
+        assert!(!limiter.allow(peer1, now + Duration::from_millis(99)));
+        // the peers are limited separately
+        assert!(limiter.allow(peer2, now + Duration::from_millis(99)));
+        assert!(limiter.allow(peer1, now + Duration::from_millis(100)));
+
+        // a refused request doesn't delay the next one
+        assert!(!limiter.allow(peer1, now + Duration::from_millis(150)));
+        assert!(limiter.allow(peer1, now + Duration::from_millis(200)));
+
+        limiter.remove(peer2);
+        assert!(limiter.allow(peer2, now + Duration::from_millis(100)));
+    }
+}
@@ -3,22 +3,28 @@
 //! Sync module implement ckb sync protocol as specified here:
 //! https://github.com/nervosnetwork/rfcs/tree/master/rfcs/0000-block-sync-protocol
 
+mod block_filter;
 mod block_status;
+mod light_client;
 mod net_time_checker;
 mod orphan_block_pool;
 mod relayer;
 mod status;
 mod synchronizer;
+mod tx_reconciliation;
 mod types;
 
 #[cfg(test)]
 mod tests;
 
+pub use crate::block_filter::BlockFilterProtocol;
+pub use crate::light_client::LightClientProtocol;
 pub use crate::net_time_checker::NetTimeProtocol;
-pub use crate::relayer::Relayer;
+pub use crate::relayer::{ReconstructionResult, Relayer, TX_POOL_INVENTORY_VERSION};
 pub use crate::status::{Status, StatusCode};
 pub use crate::synchronizer::Synchronizer;
-pub use crate::types::SyncShared;
+pub use crate::tx_reconciliation::TxReconciliationProtocol;
+pub use crate::types::{CompactBlockStats, SyncShared};
 use std::time::Duration;
 
 pub const MAX_HEADERS_LEN: usize = 2_000;
@@ -44,6 +50,9 @@ pub enum NetworkProtocol {
     RELAY = 101,
     TIME = 102,
     ALERT = 110,
+    BLOCK_FILTER = 120,
+    LIGHT_CLIENT = 121,
+    TX_RECONCILIATION = 122,
 }
 
 impl Into<ProtocolId> for NetworkProtocol {
@@ -69,6 +78,13 @@ pub const MAX_LOCATOR_SIZE: usize = 101;
 
 pub const BLOCK_DOWNLOAD_TIMEOUT: u64 = 30 * 1000; // 30s
 
+// Stall scores of the block download, a peer is disconnected once its score reaches the limit.
+// A block timed out adds the limit at once, while a peer stalling the download window is only
+// disconnected if it does so repeatedly, and every block it delivers in time takes a point off.
+pub const STALL_SCORE_LIMIT: u32 = 100;
+pub const STALL_SCORE_TIMEOUT: u32 = STALL_SCORE_LIMIT;
+pub const STALL_SCORE_STALL: u32 = 25;
+
 // Size of the "block download window": how far ahead of our current height do we fetch?
 // Larger windows tolerate larger download speed differences between peers, but increase the
 // potential degree of disordering of blocks.
@@ -0,0 +1,286 @@
+use ckb_logger::{debug, info};
+use ckb_network::{bytes::Bytes, CKBProtocolContext, CKBProtocolHandler, PeerIndex};
+use ckb_shared::{shared::Shared, Snapshot};
+use ckb_store::ChainStore;
+use ckb_types::{
+    core::{BlockNumber, BlockView},
+    packed,
+    prelude::*,
+    utilities::{merkle_root, CBMT},
+};
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+
+// Upper bound of hashes in a single light client request
+const MAX_REQUEST_ITEMS: usize = 1000;
+// Upper bound of filtered blocks size in a single response, leave room for the other fields
+// in the frame. Requested items which don't fit are neither returned nor reported missing,
+// the client should request them again.
+const MAX_FILTERED_BLOCKS_SIZE: usize = ckb_network::MAX_FRAME_LENGTH_LIGHT_CLIENT / 2;
+
+/// Serve light clients, such as mobile wallets, with main chain headers, transactions with
+/// merkle proofs and blocks filtered by scripts.
+///
+/// All responses carry the current tip header, so clients can check that the returned
+/// headers are on the chain they follow.
+#[derive(Clone)]
+pub struct LightClientProtocol {
+    shared: Shared,
+}
+
+impl LightClientProtocol {
+    pub fn new(shared: Shared) -> Self {
+        LightClientProtocol { shared }
+    }
+
+    fn get_block_headers(
+        &self,
+        message: packed::GetBlockHeadersReader,
+    ) -> packed::LightClientMessage {
+        let snapshot = self.shared.snapshot();
+        let mut headers = Vec::new();
+        let mut missing_block_hashes = Vec::new();
+        for block_hash in message.block_hashes().iter().map(|hash| hash.to_entity()) {
+            match snapshot
+                .get_block_header(&block_hash)
+                .filter(|_| snapshot.is_main_chain(&block_hash))
+            {
+                Some(header) => headers.push(header.data()),
+                None => missing_block_hashes.push(block_hash),
+            }
+        }
+        let content = packed::SendBlockHeaders::new_builder()
+            .tip_header(snapshot.tip_header().data())
+            .headers(headers.pack())
+            .missing_block_hashes(missing_block_hashes.pack())
+            .build();
+        packed::LightClientMessage::new_builder()
+            .set(content)
+            .build()
+    }
+
+    fn get_transactions_proof(
+        &self,
+        message: packed::GetTransactionsProofReader,
+    ) -> packed::LightClientMessage {
+        let snapshot = self.shared.snapshot();
+        let mut blocks: BTreeMap<BlockNumber, (packed::Byte32, Vec<u32>)> = BTreeMap::new();
+        let mut missing_tx_hashes = Vec::new();
+        for tx_hash in message.tx_hashes().iter().map(|hash| hash.to_entity()) {
+            // Only transactions of the main chain have transaction infos
+            match snapshot.get_transaction_info(&tx_hash) {
+                Some(info) => blocks
+                    .entry(info.block_number)
+                    .or_insert_with(|| (info.block_hash, Vec::new()))
+                    .1
+                    .push(info.index as u32),
+                None => missing_tx_hashes.push(tx_hash),
+            }
+        }
+
+        let filtered_blocks =
+            collect_filtered_blocks(blocks.into_iter().map(|(_, (block_hash, mut tx_indices))| {
+                tx_indices.sort();
+                tx_indices.dedup();
+                snapshot
+                    .get_block(&block_hash)
+                    .map(|block| build_filtered_block(&block, &tx_indices))
+            }));
+        let content = packed::SendTransactionsProof::new_builder()
+            .tip_header(snapshot.tip_header().data())
+            .filtered_blocks(filtered_blocks.pack())
+            .missing_tx_hashes(missing_tx_hashes.pack())
+            .build();
+        packed::LightClientMessage::new_builder()
+            .set(content)
+            .build()
+    }
+
+    fn get_filtered_blocks(
+        &self,
+        message: packed::GetFilteredBlocksReader,
+    ) -> packed::LightClientMessage {
+        let snapshot = self.shared.snapshot();
+        let script_hashes: HashSet<packed::Byte32> = message
+            .script_hashes()
+            .iter()
+            .map(|hash| hash.to_entity())
+            .collect();
+        let mut blocks = Vec::new();
+        let mut missing_block_hashes = Vec::new();
+        for block_hash in message.block_hashes().iter().map(|hash| hash.to_entity()) {
+            match snapshot
+                .get_block(&block_hash)
+                .filter(|_| snapshot.is_main_chain(&block_hash))
+            {
+                Some(block) => blocks.push(block),
+                None => missing_block_hashes.push(block_hash),
+            }
+        }
+
+        let filtered_blocks = collect_filtered_blocks(blocks.iter().map(|block| {
+            let tx_indices = matched_transactions(&snapshot, block, &script_hashes);
+            Some(build_filtered_block(block, &tx_indices))
+        }));
+        let content = packed::SendFilteredBlocks::new_builder()
+            .tip_header(snapshot.tip_header().data())
+            .filtered_blocks(filtered_blocks.pack())
+            .missing_block_hashes(missing_block_hashes.pack())
+            .build();
+        packed::LightClientMessage::new_builder()
+            .set(content)
+            .build()
+    }
+}
+
+// Take filtered blocks until the size budget is used up, at least one block is returned
+fn collect_filtered_blocks<I>(filtered_blocks: I) -> Vec<packed::LightFilteredBlock>
+where
+    I: Iterator<Item = Option<packed::LightFilteredBlock>>,
+{
+    let mut collected = Vec::new();
+    let mut total_size = 0;
+    for filtered_block in filtered_blocks.flatten() {
+        total_size += filtered_block.as_slice().len();
+        if total_size > MAX_FILTERED_BLOCKS_SIZE && !collected.is_empty() {
+            break;
+        }
+        collected.push(filtered_block);
+    }
+    collected
+}
+
+// Indices of the transactions which create or consume cells locked or typed by any of the scripts
+fn matched_transactions(
+    snapshot: &Snapshot,
+    block: &BlockView,
+    script_hashes: &HashSet<packed::Byte32>,
+) -> Vec<u32> {
+    let matches = |output: &packed::CellOutput| {
+        script_hashes.contains(&output.calc_lock_hash())
+            || output
+                .type_()
+                .to_opt()
+                .map(|script| script_hashes.contains(&script.calc_script_hash()))
+                .unwrap_or(false)
+    };
+    block
+        .transactions()
+        .iter()
+        .enumerate()
+        .filter(|(_, tx)| {
+            tx.outputs().into_iter().any(|output| matches(&output))
+                || (!tx.is_cellbase()
+                    && tx.input_pts_iter().any(|out_point| {
+                        snapshot
+                            .get_cell_meta(&out_point.tx_hash(), out_point.index().unpack())
+                            .map(|cell_meta| matches(&cell_meta.cell_output))
+                            .unwrap_or(false)
+                    }))
+        })
+        .map(|(index, _)| index as u32)
+        .collect()
+}
+
+// The proof commits the transaction hashes to the raw transactions root, the witnesses root is
+// attached so clients can recompute the transactions root in the header. Transactions are in
+// the same order as the proof indices.
+fn build_filtered_block(block: &BlockView, tx_indices: &[u32]) -> packed::LightFilteredBlock {
+    let (transactions, proof) = if tx_indices.is_empty() {
+        (Vec::new(), packed::MerkleProof::default())
+    } else {
+        let tx_hashes = block.tx_hashes();
+        let proof = CBMT::build_merkle_proof(tx_hashes, tx_indices)
+            .expect("transaction indices are in the block");
+        let first_leaf_index = tx_hashes.len() as u32 - 1;
+        let transactions = proof
+            .indices()
+            .iter()
+            .map(|index| {
+                block
+                    .data()
+                    .transactions()
+                    .get((index - first_leaf_index) as usize)
+                    .expect("proof indices are in the block")
+            })
+            .collect();
+        let proof = packed::MerkleProof::new_builder()
+            .indices(proof.indices().pack())
+            .lemmas(proof.lemmas().iter().cloned().pack())
+            .build();
+        (transactions, proof)
+    };
+    packed::LightFilteredBlock::new_builder()
+        .header(block.header().data())
+        .witnesses_root(merkle_root(block.tx_witness_hashes()))
+        .transactions(transactions.pack())
+        .proof(proof)
+        .build()
+}
+
+impl CKBProtocolHandler for LightClientProtocol {
+    fn init(&mut self, _nc: Arc<dyn CKBProtocolContext + Sync>) {}
+
+    fn received(
+        &mut self,
+        nc: Arc<dyn CKBProtocolContext + Sync>,
+        peer_index: PeerIndex,
+        data: Bytes,
+    ) {
+        let message = match packed::LightClientMessageReader::from_slice(&data) {
+            Ok(message) => message.to_enum(),
+            Err(_) => {
+                info!("Peer {} sends us malformed message", peer_index);
+                nc.report_misbehavior(
+                    peer_index,
+                    "light_client.malformed_message",
+                    "send us a malformed message",
+                );
+                return;
+            }
+        };
+
+        let response = match message {
+            packed::LightClientMessageUnionReader::GetBlockHeaders(reader)
+                if reader.block_hashes().len() <= MAX_REQUEST_ITEMS =>
+            {
+                self.get_block_headers(reader)
+            }
+            packed::LightClientMessageUnionReader::GetTransactionsProof(reader)
+                if reader.tx_hashes().len() <= MAX_REQUEST_ITEMS =>
+            {
+                self.get_transactions_proof(reader)
+            }
+            packed::LightClientMessageUnionReader::GetFilteredBlocks(reader)
+                if reader.block_hashes().len() <= MAX_REQUEST_ITEMS
+                    && reader.script_hashes().len() <= MAX_REQUEST_ITEMS =>
+            {
+                self.get_filtered_blocks(reader)
+            }
+            packed::LightClientMessageUnionReader::GetBlockHeaders(_)
+            | packed::LightClientMessageUnionReader::GetTransactionsProof(_)
+            | packed::LightClientMessageUnionReader::GetFilteredBlocks(_) => {
+                info!(
+                    "Peer {} requests more than {} items",
+                    peer_index, MAX_REQUEST_ITEMS
+                );
+                nc.report_misbehavior(
+                    peer_index,
+                    "light_client.too_large_request",
+                    "send us a too large request",
+                );
+                return;
+            }
+            _ => {
+                debug!(
+                    "Peer {} sends us unrequested light client message",
+                    peer_index
+                );
+                return;
+            }
+        };
+        if let Err(err) = nc.send_message_to(peer_index, response.as_bytes()) {
+            debug!("light_client send message error: {:?}", err);
+        }
+    }
+}
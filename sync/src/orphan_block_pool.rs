@@ -57,6 +57,24 @@ impl OrphanBlockPool {
         removed
     }
 
+    pub fn len(&self) -> usize {
+        self.parents.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Serialized size of all the orphan blocks
+    pub fn total_size(&self) -> usize {
+        self.blocks
+            .read()
+            .values()
+            .flat_map(HashMap::values)
+            .map(|(_, block)| block.data().total_size())
+            .sum()
+    }
+
     pub fn get_block(&self, hash: &packed::Byte32) -> Option<core::BlockView> {
         // acquire the `blocks` read lock first, guarantee ordering of acquisition is same as `remove_blocks_by_parent`, avoids deadlocking
         let guard = self.blocks.read();
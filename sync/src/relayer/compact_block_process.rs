@@ -203,18 +203,28 @@ impl<'a> CompactBlockProcess<'a> {
             // Accept block
             // `relayer.accept_block` will make sure the validity of block before persisting
             // into database
+            let short_ids = compact_block.short_ids().len() as u64;
+            let record_compact_block = |short_id_hits: u64| {
+                shared
+                    .state()
+                    .peers()
+                    .record_compact_block(self.peer, short_ids, short_id_hits)
+            };
             match ret {
                 ReconstructionResult::Block(block) => {
+                    record_compact_block(short_ids);
                     pending_compact_blocks.remove(&block_hash);
                     self.relayer
                         .accept_block(self.nc.as_ref(), self.peer, block);
                     return Status::ok();
                 }
                 ReconstructionResult::Missing(transactions, uncles) => {
+                    record_compact_block(short_ids.saturating_sub(transactions.len() as u64));
                     missing_transactions = transactions.into_iter().map(|i| i as u32).collect();
                     missing_uncles = uncles.into_iter().map(|i| i as u32).collect();
                 }
                 ReconstructionResult::Collided => {
+                    record_compact_block(0);
                     missing_transactions = compact_block
                         .short_id_indexes()
                         .into_iter()
@@ -251,6 +261,11 @@ impl<'a> CompactBlockProcess<'a> {
             return StatusCode::BlocksInFlightReachLimit.with_context(block_hash);
         }
 
+        shared
+            .state()
+            .peers()
+            .record_compact_block_fallback(self.peer, collision);
+
         let status = if collision {
             StatusCode::CompactBlockMeetsShortIdsCollision.with_context(&block_hash)
         } else {
@@ -0,0 +1,148 @@
+//! Dandelion relay of the local transactions.
+//!
+//! In the stem phase a local transaction is announced to the stem peer only, rather than to all
+//! the peers, so the peers can't tell the origin by the first announcement. The stem peer is an
+//! outbound peer picked at random for an epoch, the inbound peers are never picked since an
+//! attacker controls whom it connects to us. The stem peer diffuses the transaction, it enters
+//! the fluff phase once it is heard from any other peer. If it is not heard before the embargo
+//! expires, say the stem peer drops it, the node fluffs it itself.
+//!
+//! The relay protocol has no stem messages, so the stem path is a single hop.
+use ckb_app_config::DandelionConfig;
+use ckb_network::PeerIndex;
+use ckb_types::packed::Byte32;
+use rand::{seq::SliceRandom, thread_rng};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+pub(crate) struct Dandelion {
+    epoch: Duration,
+    embargo: Duration,
+    /// The stem peer of the epoch, and when the epoch ends
+    stem_peer: Option<(PeerIndex, Instant)>,
+    /// The transactions in the stem phase, and when their embargoes expire
+    embargoes: HashMap<Byte32, Instant>,
+}
+
+impl Dandelion {
+    pub(crate) fn new(config: &DandelionConfig) -> Self {
+        Dandelion {
+            epoch: Duration::from_secs(config.epoch_secs),
+            embargo: Duration::from_secs(config.embargo_secs),
+            stem_peer: None,
+            embargoes: HashMap::default(),
+        }
+    }
+
+    /// Returns the stem peer of the epoch, a new one is picked when the epoch ends or the peer is
+    /// gone. Returns `None` if there are no outbound peers.
+    pub(crate) fn stem_peer(
+        &mut self,
+        outbound_peers: &[PeerIndex],
+        now: Instant,
+    ) -> Option<PeerIndex> {
+        match self.stem_peer {
+            Some((peer, epoch_ends_at))
+                if epoch_ends_at > now && outbound_peers.contains(&peer) =>
+            {
+                Some(peer)
+            }
+            _ => {
+                self.stem_peer = outbound_peers
+                    .choose(&mut thread_rng())
+                    .map(|peer| (*peer, now + self.epoch));
+                self.stem_peer.map(|(peer, _)| peer)
+            }
+        }
+    }
+
+    /// Puts the transaction announced to the stem peer under embargo.
+    pub(crate) fn stem(&mut self, tx_hash: Byte32, now: Instant) {
+        self.embargoes.insert(tx_hash, now + self.embargo);
+    }
+
+    /// The transactions announced by a peer other than the stem peer have been diffused.
+    pub(crate) fn observe(&mut self, peer: PeerIndex, tx_hashes: impl Iterator<Item = Byte32>) {
+        if self.embargoes.is_empty() || self.stem_peer.map(|(stem_peer, _)| stem_peer) == Some(peer)
+        {
+            return;
+        }
+        for tx_hash in tx_hashes {
+            self.embargoes.remove(&tx_hash);
+        }
+    }
+
+    /// Takes the transactions whose embargoes expire, they are to be fluffed by the node itself.
+    pub(crate) fn take_expired(&mut self, now: Instant) -> Vec<Byte32> {
+        let expired: Vec<Byte32> = self
+            .embargoes
+            .iter()
+            .filter(|(_, expires_at)| **expires_at <= now)
+            .map(|(tx_hash, _)| tx_hash.clone())
+            .collect();
+        for tx_hash in &expired {
+            self.embargoes.remove(tx_hash);
+        }
+        expired
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ckb_types::{h256, prelude::*};
+
+    fn dandelion() -> Dandelion {
+        Dandelion::new(&DandelionConfig {
+            enable: true,
+            epoch_secs: 60,
+            embargo_secs: 10,
+        })
+    }
+
+    #[test]
+    fn test_stem_peer_of_epoch() {
+        let mut dandelion = dandelion();
+        let now = Instant::now();
+        assert_eq!(dandelion.stem_peer(&[], now), None);
+
+        let peers = vec![1.into(), 2.into(), 3.into()];
+        let stem_peer = dandelion.stem_peer(&peers, now).expect("picked");
+        assert!(peers.contains(&stem_peer));
+        let later = now + Duration::from_secs(30);
+        assert_eq!(dandelion.stem_peer(&peers, later), Some(stem_peer));
+
+        // the stem peer is gone
+        let others: Vec<PeerIndex> = peers.into_iter().filter(|p| *p != stem_peer).collect();
+        let new_stem_peer = dandelion.stem_peer(&others, later).expect("picked");
+        assert!(others.contains(&new_stem_peer));
+
+        // the epoch ends
+        let next_epoch = later + Duration::from_secs(60);
+        assert_eq!(dandelion.stem_peer(&[4.into()], next_epoch), Some(4.into()));
+    }
+
+    #[test]
+    fn test_embargo() {
+        let mut dandelion = dandelion();
+        let now = Instant::now();
+        let stem_peer = dandelion.stem_peer(&[1.into()], now).expect("picked");
+        let (heard, lost) = (h256!("0x1").pack(), h256!("0x2").pack());
+        dandelion.stem(heard.clone(), now);
+        dandelion.stem(lost.clone(), now);
+
+        // the stem peer doesn't end the stem phase
+        dandelion.observe(stem_peer, vec![lost.clone()].into_iter());
+        dandelion.observe(2.into(), vec![heard].into_iter());
+        assert!(dandelion
+            .take_expired(now + Duration::from_secs(9))
+            .is_empty());
+        assert_eq!(
+            dandelion.take_expired(now + Duration::from_secs(10)),
+            vec![lost]
+        );
+        assert!(dandelion
+            .take_expired(now + Duration::from_secs(20))
+            .is_empty());
+    }
+}
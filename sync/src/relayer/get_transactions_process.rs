@@ -8,7 +8,7 @@ use std::sync::Arc;
 pub struct GetTransactionsProcess<'a> {
     message: packed::GetRelayTransactionsReader<'a>,
     relayer: &'a Relayer,
-    nc: Arc<dyn CKBProtocolContext>,
+    nc: Arc<dyn CKBProtocolContext + Sync>,
     peer: PeerIndex,
 }
 
@@ -16,7 +16,7 @@ impl<'a> GetTransactionsProcess<'a> {
     pub fn new(
         message: packed::GetRelayTransactionsReader<'a>,
         relayer: &'a Relayer,
-        nc: Arc<dyn CKBProtocolContext>,
+        nc: Arc<dyn CKBProtocolContext + Sync>,
         peer: PeerIndex,
     ) -> Self {
         GetTransactionsProcess {
@@ -48,27 +48,29 @@ impl<'a> GetTransactionsProcess<'a> {
             tx_hashes
         );
 
-        let transactions: Vec<_> = {
-            let tx_pool = self.relayer.shared.shared().tx_pool_controller();
-
-            let fetch_txs_with_cycles = tx_pool.fetch_txs_with_cycles(
-                tx_hashes
-                    .iter()
-                    .map(|tx_hash| packed::ProposalShortId::from_tx_hash(&tx_hash.to_entity()))
-                    .collect(),
-            );
-
-            if let Err(e) = fetch_txs_with_cycles {
-                debug_target!(
-                    crate::LOG_TARGET_RELAY,
-                    "relayer tx_pool_controller send fetch_txs_with_cycles error: {:?}",
-                    e,
-                );
-                return Status::ok();
+        // The transactions are sent once fetched, so the relayer does not wait for the pool
+        let tx_pool = self.relayer.shared.shared().tx_pool_controller();
+        let fetch_txs_with_cycles = tx_pool.fetch_txs_with_cycles_async(
+            tx_hashes
+                .iter()
+                .map(|tx_hash| packed::ProposalShortId::from_tx_hash(&tx_hash.to_entity()))
+                .collect(),
+        );
+        let nc = self.nc;
+        let peer = self.peer;
+        tx_pool.handle().spawn(async move {
+            let fetch_txs_with_cycles = match fetch_txs_with_cycles.await {
+                Ok(fetch_txs_with_cycles) => fetch_txs_with_cycles,
+                Err(e) => {
+                    debug_target!(
+                        crate::LOG_TARGET_RELAY,
+                        "relayer tx_pool_controller send fetch_txs_with_cycles error: {:?}",
+                        e,
+                    );
+                    return;
+                }
             };
-
-            fetch_txs_with_cycles
-                .unwrap()
+            let transactions = fetch_txs_with_cycles
                 .into_iter()
                 .map(|(_, (tx, cycles))| {
                     packed::RelayTransaction::new_builder()
@@ -76,43 +78,66 @@ impl<'a> GetTransactionsProcess<'a> {
                         .transaction(tx.data())
                         .build()
                 })
-                .collect()
-        };
-
-        if !transactions.is_empty() {
-            let mut relay_bytes = 0;
-            let mut relay_txs = Vec::new();
-            for tx in transactions {
-                if relay_bytes + tx.total_size() > MAX_RELAY_TXS_BYTES_PER_BATCH {
-                    self.send_relay_transactions(relay_txs.drain(..).collect());
-                    relay_bytes = tx.total_size();
-                    relay_txs.push(tx);
-                } else {
-                    relay_bytes += tx.total_size();
-                    relay_txs.push(tx);
-                }
-            }
-            if !relay_txs.is_empty() {
-                attempt!(self.send_relay_transactions(relay_txs));
+                .collect();
+            let status = send_relay_transactions(nc.as_ref(), peer, transactions);
+            if !status.is_ok() {
+                debug_target!(
+                    crate::LOG_TARGET_RELAY,
+                    "relayer send relay transactions to {} error: {}",
+                    peer,
+                    status,
+                );
             }
-        }
+        });
         Status::ok()
     }
+}
 
-    fn send_relay_transactions(&self, txs: Vec<packed::RelayTransaction>) -> Status {
-        let message = packed::RelayMessage::new_builder()
-            .set(
-                packed::RelayTransactions::new_builder()
-                    .transactions(packed::RelayTransactionVec::new_builder().set(txs).build())
-                    .build(),
-            )
-            .build();
-
-        if let Err(err) = self.nc.send_message_to(self.peer, message.as_bytes()) {
-            return StatusCode::Network
-                .with_context(format!("Send RelayTransactions error: {:?}", err));
+// Sends the transactions in batches, each of which stays under `MAX_RELAY_TXS_BYTES_PER_BATCH`.
+fn send_relay_transactions(
+    nc: &dyn CKBProtocolContext,
+    peer: PeerIndex,
+    transactions: Vec<packed::RelayTransaction>,
+) -> Status {
+    let mut relay_bytes = 0;
+    let mut relay_txs = Vec::new();
+    for tx in transactions {
+        if relay_bytes + tx.total_size() > MAX_RELAY_TXS_BYTES_PER_BATCH {
+            attempt!(send_relay_transactions_batch(
+                nc,
+                peer,
+                relay_txs.drain(..).collect()
+            ));
+            relay_bytes = tx.total_size();
+            relay_txs.push(tx);
+        } else {
+            relay_bytes += tx.total_size();
+            relay_txs.push(tx);
         }
-        crate::relayer::log_sent_metric(message.to_enum().item_name());
-        Status::ok()
     }
+    if !relay_txs.is_empty() {
+        attempt!(send_relay_transactions_batch(nc, peer, relay_txs));
+    }
+    Status::ok()
+}
+
+fn send_relay_transactions_batch(
+    nc: &dyn CKBProtocolContext,
+    peer: PeerIndex,
+    txs: Vec<packed::RelayTransaction>,
+) -> Status {
+    let message = packed::RelayMessage::new_builder()
+        .set(
+            packed::RelayTransactions::new_builder()
+                .transactions(packed::RelayTransactionVec::new_builder().set(txs).build())
+                .build(),
+        )
+        .build();
+
+    if let Err(err) = nc.send_message_to(peer, message.as_bytes()) {
+        return StatusCode::Network
+            .with_context(format!("Send RelayTransactions error: {:?}", err));
+    }
+    crate::relayer::log_sent_metric(message.to_enum().item_name());
+    Status::ok()
 }
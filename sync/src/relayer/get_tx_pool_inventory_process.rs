@@ -0,0 +1,68 @@
+use crate::relayer::{Relayer, MAX_RELAY_TXS_NUM_PER_BATCH};
+use crate::{Status, StatusCode};
+use ckb_network::{CKBProtocolContext, PeerIndex};
+use ckb_types::{packed, prelude::*};
+use std::sync::Arc;
+
+pub struct GetTxPoolInventoryProcess<'a> {
+    message: packed::GetTxPoolInventoryReader<'a>,
+    relayer: &'a Relayer,
+    nc: Arc<dyn CKBProtocolContext>,
+    peer: PeerIndex,
+}
+
+impl<'a> GetTxPoolInventoryProcess<'a> {
+    pub fn new(
+        message: packed::GetTxPoolInventoryReader<'a>,
+        relayer: &'a Relayer,
+        nc: Arc<dyn CKBProtocolContext>,
+        peer: PeerIndex,
+    ) -> Self {
+        GetTxPoolInventoryProcess {
+            message,
+            relayer,
+            nc,
+            peer,
+        }
+    }
+
+    pub fn execute(self) -> Status {
+        {
+            let state = self.relayer.shared().state();
+            let mut peers_state = state.peers().state.write();
+            match peers_state.get_mut(&self.peer) {
+                Some(peer_state) if !peer_state.tx_pool_inventory_served => {
+                    peer_state.tx_pool_inventory_served = true;
+                }
+                _ => return Status::ignored(),
+            }
+        }
+
+        let limit: u32 = self.message.limit().unpack();
+        let limit = ::std::cmp::min(limit as usize, MAX_RELAY_TXS_NUM_PER_BATCH);
+        let tx_pool = self.relayer.shared().shared().tx_pool_controller();
+        let tx_hashes = match tx_pool.inventory_snapshot(limit) {
+            Ok(tx_hashes) => tx_hashes,
+            Err(err) => {
+                return StatusCode::TxPool
+                    .with_context(format!("TxPool inventory_snapshot error: {:?}", err));
+            }
+        };
+        if tx_hashes.is_empty() {
+            return Status::ok();
+        }
+
+        // The inventory is announced as the relayed transactions, the requester asks for the
+        // ones it misses
+        let content = packed::RelayTransactionHashes::new_builder()
+            .tx_hashes(tx_hashes.pack())
+            .build();
+        let message = packed::RelayMessage::new_builder().set(content).build();
+        if let Err(err) = self.nc.send_message_to(self.peer, message.as_bytes()) {
+            return StatusCode::Network
+                .with_context(format!("Send RelayTransactionHashes error: {:?}", err));
+        }
+        crate::relayer::log_sent_metric(message.to_enum().item_name());
+        Status::ok()
+    }
+}
@@ -4,9 +4,11 @@ mod block_transactions_verifier;
 mod block_uncles_verifier;
 mod compact_block_process;
 mod compact_block_verifier;
+mod dandelion;
 mod get_block_proposal_process;
 mod get_block_transactions_process;
 mod get_transactions_process;
+mod get_tx_pool_inventory_process;
 #[cfg(test)]
 mod tests;
 mod transaction_hashes_process;
@@ -15,27 +17,29 @@ mod transactions_process;
 use self::block_proposal_process::BlockProposalProcess;
 use self::block_transactions_process::BlockTransactionsProcess;
 use self::compact_block_process::CompactBlockProcess;
+use self::dandelion::Dandelion;
 use self::get_block_proposal_process::GetBlockProposalProcess;
 use self::get_block_transactions_process::GetBlockTransactionsProcess;
 use self::get_transactions_process::GetTransactionsProcess;
+use self::get_tx_pool_inventory_process::GetTxPoolInventoryProcess;
 use self::transaction_hashes_process::TransactionHashesProcess;
 use self::transactions_process::TransactionsProcess;
 use crate::block_status::BlockStatus;
 use crate::types::{ActiveChain, SyncShared};
-use crate::{Status, StatusCode, BAD_MESSAGE_BAN_TIME};
+use crate::{Status, StatusCode};
+use ckb_app_config::DandelionConfig;
 use ckb_chain::chain::ChainController;
-use ckb_fee_estimator::FeeRate;
 use ckb_logger::{debug_target, error_target, info_target, metric, trace_target, warn_target};
 use ckb_network::{
     bytes::Bytes, tokio, CKBProtocolContext, CKBProtocolHandler, PeerIndex, TargetSession,
 };
 use ckb_types::core::BlockView;
 use ckb_types::{
-    core::{self, Cycle},
+    core,
     packed::{self, Byte32, ProposalShortId},
     prelude::*,
 };
-use ckb_util::Mutex;
+use ckb_util::{LinkedHashSet, Mutex};
 use faketime::unix_time_as_millis;
 use ratelimit_meter::KeyedRateLimiter;
 use std::collections::{HashMap, HashSet};
@@ -46,10 +50,13 @@ pub const TX_PROPOSAL_TOKEN: u64 = 0;
 pub const ASK_FOR_TXS_TOKEN: u64 = 1;
 pub const TX_HASHES_TOKEN: u64 = 2;
 pub const SEARCH_ORPHAN_POOL_TOKEN: u64 = 3;
+pub const REBROADCAST_TXS_TOKEN: u64 = 4;
 
 pub const MAX_RELAY_PEERS: usize = 128;
 pub const MAX_RELAY_TXS_NUM_PER_BATCH: usize = 32767;
 pub const MAX_RELAY_TXS_BYTES_PER_BATCH: usize = 1024 * 1024;
+// The relay protocol version since which `GetTxPoolInventory` is understood
+pub const TX_POOL_INVENTORY_VERSION: &str = "2";
 
 #[derive(Debug, Eq, PartialEq)]
 pub enum ReconstructionResult {
@@ -63,18 +70,13 @@ pub enum ReconstructionResult {
 pub struct Relayer {
     chain: ChainController,
     pub(crate) shared: Arc<SyncShared>,
-    pub(crate) min_fee_rate: FeeRate,
-    pub(crate) max_tx_verify_cycles: Cycle,
     rate_limiter: Arc<Mutex<KeyedRateLimiter<(PeerIndex, u32)>>>,
+    dandelion: Option<Arc<Mutex<Dandelion>>>,
+    mempool_sync: bool,
 }
 
 impl Relayer {
-    pub fn new(
-        chain: ChainController,
-        shared: Arc<SyncShared>,
-        min_fee_rate: FeeRate,
-        max_tx_verify_cycles: Cycle,
-    ) -> Self {
+    pub fn new(chain: ChainController, shared: Arc<SyncShared>) -> Self {
         // setup a rate limiter keyed by peer and message type that lets through 30 requests per second
         // current max rps is 10 (ASK_FOR_TXS_TOKEN / TX_PROPOSAL_TOKEN), 30 is a flexible hard cap with buffer
         let rate_limiter = Arc::new(Mutex::new(KeyedRateLimiter::per_second(
@@ -83,12 +85,27 @@ impl Relayer {
         Relayer {
             chain,
             shared,
-            min_fee_rate,
-            max_tx_verify_cycles,
             rate_limiter,
+            dandelion: None,
+            mempool_sync: false,
         }
     }
 
+    /// Relays the local transactions by Dandelion if it is enabled.
+    pub fn with_dandelion(mut self, config: &DandelionConfig) -> Self {
+        if config.enable {
+            self.dandelion = Some(Arc::new(Mutex::new(Dandelion::new(config))));
+        }
+        self
+    }
+
+    /// Requests the pool inventory of the outbound peers once connected, the missing
+    /// transactions are then asked for as the announced ones.
+    pub fn with_mempool_sync(mut self, enable: bool) -> Self {
+        self.mempool_sync = enable;
+        self
+    }
+
     pub fn shared(&self) -> &Arc<SyncShared> {
         &self.shared
     }
@@ -150,6 +167,9 @@ impl Relayer {
             packed::RelayMessageUnionReader::BlockProposal(reader) => {
                 BlockProposalProcess::new(reader, self).execute()
             }
+            packed::RelayMessageUnionReader::GetTxPoolInventory(reader) => {
+                GetTxPoolInventoryProcess::new(reader, self, nc, peer).execute()
+            }
         }
     }
 
@@ -175,16 +195,15 @@ impl Relayer {
             });
         }
 
-        if let Some(ban_time) = status.should_ban() {
+        if status.should_ban().is_some() {
             error_target!(
                 crate::LOG_TARGET_RELAY,
-                "receive {} from {}, ban {:?} for {}",
+                "receive {} from {}, misbehavior {}",
                 item_name,
                 peer,
-                ban_time,
                 status
             );
-            nc.ban_peer(peer, ban_time, status.to_string());
+            nc.report_misbehavior(peer, "relay.invalid_message", &status.to_string());
         } else if status.should_warn() {
             warn_target!(
                 crate::LOG_TARGET_RELAY,
@@ -254,6 +273,26 @@ impl Relayer {
         }
     }
 
+    fn request_tx_pool_inventory(&self, nc: &dyn CKBProtocolContext, peer: PeerIndex) {
+        // the transactions can't be verified against a stale tip
+        if self.shared.active_chain().is_initial_block_download() {
+            return;
+        }
+        let content = packed::GetTxPoolInventory::new_builder()
+            .limit((MAX_RELAY_TXS_NUM_PER_BATCH as u32).pack())
+            .build();
+        let message = packed::RelayMessage::new_builder().set(content).build();
+        if let Err(err) = nc.send_message_to(peer, message.as_bytes()) {
+            debug_target!(
+                crate::LOG_TARGET_RELAY,
+                "relayer send GetTxPoolInventory error: {:?}",
+                err,
+            );
+        } else {
+            log_sent_metric(message.to_enum().item_name());
+        }
+    }
+
     pub fn accept_block(
         &self,
         nc: &dyn CKBProtocolContext,
@@ -280,29 +319,61 @@ impl Relayer {
                 boxed.header().hash(),
                 unix_time_as_millis()
             );
+            // the peers relaying us new blocks are protected from the inbound eviction
+            nc.with_peer_mut(
+                peer,
+                Box::new(|peer| {
+                    peer.last_block_time = Some(Instant::now());
+                }),
+            );
             let block_hash = boxed.hash();
             self.shared().state().remove_header_view(&block_hash);
-            let cb = packed::CompactBlock::build_from_block(&boxed, &HashSet::new());
-            let message = packed::RelayMessage::new_builder().set(cb).build();
-
             let selected_peers: Vec<PeerIndex> = nc
                 .connected_peers()
                 .into_iter()
                 .filter(|target_peer| peer != *target_peer)
                 .take(MAX_RELAY_PEERS)
                 .collect();
-            if let Err(err) =
-                nc.quick_filter_broadcast(TargetSession::Multi(selected_peers), message.as_bytes())
-            {
-                debug_target!(
-                    crate::LOG_TARGET_RELAY,
-                    "relayer send block when accept block error: {:?}",
-                    err,
-                );
+            // The peers missing most of the transactions get the compact block with all the
+            // transactions prefilled
+            let peers = self.shared().state().peers();
+            let (full_block_peers, compact_block_peers): (Vec<PeerIndex>, Vec<PeerIndex>) =
+                selected_peers
+                    .into_iter()
+                    .partition(|target_peer| peers.prefers_full_blocks(*target_peer));
+
+            let cb = packed::CompactBlock::build_from_block(&boxed, &HashSet::new());
+            self.broadcast_compact_block(nc, cb, compact_block_peers);
+            if !full_block_peers.is_empty() {
+                let prefilled = (1..boxed.transactions().len()).collect();
+                let cb = packed::CompactBlock::build_from_block(&boxed, &prefilled);
+                self.broadcast_compact_block(nc, cb, full_block_peers);
             }
         }
     }
 
+    fn broadcast_compact_block(
+        &self,
+        nc: &dyn CKBProtocolContext,
+        compact_block: packed::CompactBlock,
+        peers: Vec<PeerIndex>,
+    ) {
+        if peers.is_empty() {
+            return;
+        }
+        let message = packed::RelayMessage::new_builder()
+            .set(compact_block)
+            .build();
+        if let Err(err) = nc.quick_filter_broadcast(TargetSession::Multi(peers), message.as_bytes())
+        {
+            debug_target!(
+                crate::LOG_TARGET_RELAY,
+                "relayer send block when accept block error: {:?}",
+                err,
+            );
+        }
+    }
+
     // nodes should attempt to reconstruct the full block by taking the prefilledtxn transactions
     // from the original CompactBlock message and placing them in the marked positions,
     // then for each short transaction ID from the original compact_block message, in order,
@@ -583,8 +654,27 @@ impl Relayer {
         }
         let mut selected: HashMap<PeerIndex, Vec<Byte32>> = HashMap::default();
         {
-            let peer_tx_hashes = self.shared.state().take_tx_hashes();
+            let mut peer_tx_hashes = self.shared.state().take_tx_hashes();
             let mut known_txs = self.shared.state().known_txs();
+            let mut tx_reconciliation = self.shared.state().tx_reconciliation();
+            if let Some(dandelion) = self.dandelion.as_ref() {
+                let stem_hashes = stem_local_tx_hashes(
+                    nc,
+                    &connected_peers,
+                    &mut dandelion.lock(),
+                    &mut peer_tx_hashes,
+                );
+                if let Some((stem_peer, hashes)) = stem_hashes {
+                    let hashes: Vec<Byte32> = hashes
+                        .into_iter()
+                        .filter(|tx_hash| known_txs.insert(stem_peer, tx_hash.clone()))
+                        .take(MAX_RELAY_TXS_NUM_PER_BATCH)
+                        .collect();
+                    if !hashes.is_empty() {
+                        selected.insert(stem_peer, hashes);
+                    }
+                }
+            }
 
             for (peer_index, tx_hashes) in peer_tx_hashes.into_iter() {
                 for tx_hash in tx_hashes {
@@ -596,6 +686,9 @@ impl Relayer {
                         })
                         .take(MAX_RELAY_PEERS)
                     {
+                        if tx_reconciliation.add(peer, tx_hash.clone()) {
+                            continue;
+                        }
                         let hashes = selected
                             .entry(peer)
                             .or_insert_with(|| Vec::with_capacity(MAX_RELAY_TXS_NUM_PER_BATCH));
@@ -622,6 +715,103 @@ impl Relayer {
             }
         }
     }
+
+    // Announces the local transactions staying in the pool to the peers again, whether they are
+    // known to the peers or not, since the peers may have dropped them.
+    pub fn rebroadcast_local_txs(&self, nc: &dyn CKBProtocolContext) {
+        let connected_peers = nc.connected_peers();
+        if connected_peers.is_empty() {
+            return;
+        }
+        let tx_hashes = match self
+            .shared
+            .shared()
+            .tx_pool_controller()
+            .get_local_txs_to_rebroadcast()
+        {
+            Ok(tx_hashes) => tx_hashes,
+            Err(err) => {
+                error_target!(
+                    crate::LOG_TARGET_RELAY,
+                    "get_local_txs_to_rebroadcast error {:?}",
+                    err,
+                );
+                return;
+            }
+        };
+        if tx_hashes.is_empty() {
+            return;
+        }
+        debug_target!(
+            crate::LOG_TARGET_RELAY,
+            "rebroadcast {} local txs",
+            tx_hashes.len()
+        );
+
+        let peers: Vec<PeerIndex> = connected_peers.into_iter().take(MAX_RELAY_PEERS).collect();
+        for hashes in tx_hashes.chunks(MAX_RELAY_TXS_NUM_PER_BATCH) {
+            let content = packed::RelayTransactionHashes::new_builder()
+                .tx_hashes(hashes.to_vec().pack())
+                .build();
+            let message = packed::RelayMessage::new_builder().set(content).build();
+            if let Err(err) =
+                nc.filter_broadcast(TargetSession::Multi(peers.clone()), message.as_bytes())
+            {
+                debug_target!(
+                    crate::LOG_TARGET_RELAY,
+                    "relayer rebroadcast TransactionHashes error: {:?}",
+                    err,
+                );
+            }
+        }
+    }
+}
+
+// Takes the local transactions out of the ones to broadcast, and puts them under embargo to
+// announce to the stem peer only. The transactions whose embargoes expire are put back to be
+// fluffed, as are the local ones if there are no outbound peers to stem through.
+fn stem_local_tx_hashes(
+    nc: &dyn CKBProtocolContext,
+    connected_peers: &[PeerIndex],
+    dandelion: &mut Dandelion,
+    peer_tx_hashes: &mut HashMap<PeerIndex, LinkedHashSet<Byte32>>,
+) -> Option<(PeerIndex, Vec<Byte32>)> {
+    let now = Instant::now();
+    // `PeerIndex(usize::max)` is the source of the local transactions, see `send_transaction`
+    let local = PeerIndex::new(usize::max_value());
+    let expired = dandelion.take_expired(now);
+    let mut stem_hashes = None;
+    if let Some(local_hashes) = peer_tx_hashes.remove(&local) {
+        let outbound_peers: Vec<PeerIndex> = connected_peers
+            .iter()
+            .filter(|peer| {
+                nc.get_peer(**peer)
+                    .map(|peer| peer.is_outbound())
+                    .unwrap_or(false)
+            })
+            .cloned()
+            .collect();
+        match dandelion.stem_peer(&outbound_peers, now) {
+            Some(stem_peer) => {
+                for tx_hash in &local_hashes {
+                    dandelion.stem(tx_hash.clone(), now);
+                }
+                stem_hashes = Some((stem_peer, local_hashes.into_iter().collect()));
+            }
+            None => {
+                peer_tx_hashes.insert(local, local_hashes);
+            }
+        }
+    }
+    if !expired.is_empty() {
+        debug_target!(
+            crate::LOG_TARGET_RELAY,
+            "fluff {} local txs whose embargoes expire",
+            expired.len()
+        );
+        peer_tx_hashes.entry(local).or_default().extend(expired);
+    }
+    stem_hashes
 }
 
 impl CKBProtocolHandler for Relayer {
@@ -635,6 +825,8 @@ impl CKBProtocolHandler for Relayer {
         // todo: remove when the asynchronous verification is completed
         nc.set_notify(Duration::from_secs(5), SEARCH_ORPHAN_POOL_TOKEN)
             .expect("set_notify at init is ok");
+        nc.set_notify(Duration::from_secs(10), REBROADCAST_TXS_TOKEN)
+            .expect("set_notify at init is ok");
     }
 
     fn received(
@@ -656,10 +848,10 @@ impl CKBProtocolHandler for Relayer {
                     "Peer {} sends us a malformed message",
                     peer_index
                 );
-                nc.ban_peer(
+                nc.report_misbehavior(
                     peer_index,
-                    BAD_MESSAGE_BAN_TIME,
-                    String::from("send us a malformed message"),
+                    "relay.malformed_message",
+                    "send us a malformed message",
                 );
                 return;
             }
@@ -703,6 +895,13 @@ impl CKBProtocolHandler for Relayer {
         );
         let protocol = nc.protocol_id();
         let version = version.to_string();
+        let is_outbound = nc
+            .get_peer(peer_index)
+            .map(|peer| peer.is_outbound())
+            .unwrap_or(false);
+        if self.mempool_sync && is_outbound && version == TX_POOL_INVENTORY_VERSION {
+            self.request_tx_pool_inventory(nc.as_ref(), peer_index);
+        }
         nc.with_peer_mut(
             peer_index,
             Box::new(move |peer| {
@@ -748,6 +947,9 @@ impl CKBProtocolHandler for Relayer {
                     &self.shared.active_chain().tip_header().hash(),
                 )
             }),
+            REBROADCAST_TXS_TOKEN => {
+                tokio::task::block_in_place(|| self.rebroadcast_local_txs(nc.as_ref()))
+            }
             _ => unreachable!(),
         }
         trace_target!(
@@ -0,0 +1,72 @@
+use crate::relayer::get_tx_pool_inventory_process::GetTxPoolInventoryProcess;
+use crate::relayer::tests::helper::{build_chain, new_transaction, MockProtocalContext};
+use crate::types::PeerFlags;
+use crate::Status;
+use ckb_network::PeerIndex;
+use ckb_tx_pool::{PlugTarget, TxEntry};
+use ckb_types::prelude::*;
+use ckb_types::{core::Capacity, packed};
+use std::sync::Arc;
+
+#[test]
+fn test_serve_inventory_once() {
+    let (relayer, always_success_out_point) = build_chain(5);
+    let transaction = new_transaction(&relayer, 1, &always_success_out_point);
+    {
+        let tx_pool = relayer.shared.shared().tx_pool_controller();
+        let entry = TxEntry::new(transaction.clone(), 0, Capacity::shannons(0), 0, vec![]);
+        tx_pool
+            .plug_entry(vec![entry], PlugTarget::Pending)
+            .unwrap();
+    }
+
+    let nc = Arc::new(MockProtocalContext::default());
+    let peer_index: PeerIndex = 100.into();
+    let content = packed::GetTxPoolInventory::new_builder()
+        .limit(10u32.pack())
+        .build();
+
+    // the peers without the sync state are ignored
+    let process = GetTxPoolInventoryProcess::new(
+        content.as_reader(),
+        &relayer,
+        Arc::<MockProtocalContext>::clone(&nc),
+        peer_index,
+    );
+    assert_eq!(process.execute(), Status::ignored());
+
+    relayer
+        .shared
+        .state()
+        .peers()
+        .on_connected(peer_index, PeerFlags::default());
+    let process = GetTxPoolInventoryProcess::new(
+        content.as_reader(),
+        &relayer,
+        Arc::<MockProtocalContext>::clone(&nc),
+        peer_index,
+    );
+    assert_eq!(process.execute(), Status::ok());
+
+    let content = packed::RelayTransactionHashes::new_builder()
+        .tx_hashes(vec![transaction.hash()].pack())
+        .build();
+    let message = packed::RelayMessage::new_builder().set(content).build();
+    assert_eq!(
+        nc.sent_messages_to.borrow().clone(),
+        vec![(peer_index, message.as_bytes())]
+    );
+
+    // served once per connection
+    let content = packed::GetTxPoolInventory::new_builder()
+        .limit(10u32.pack())
+        .build();
+    let process = GetTxPoolInventoryProcess::new(
+        content.as_reader(),
+        &relayer,
+        Arc::<MockProtocalContext>::clone(&nc),
+        peer_index,
+    );
+    assert_eq!(process.execute(), Status::ignored());
+    assert_eq!(nc.sent_messages_to.borrow().len(), 1);
+}
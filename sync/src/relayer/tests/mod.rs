@@ -4,5 +4,6 @@ mod block_transactions_verifier;
 mod compact_block;
 mod compact_block_process;
 mod compact_block_verifier;
+mod get_tx_pool_inventory_process;
 mod helper;
 mod reconstruct_block;
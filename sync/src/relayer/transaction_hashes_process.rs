@@ -40,6 +40,13 @@ impl<'a> TransactionHashesProcess<'a> {
             }
         }
 
+        if let Some(dandelion) = self.relayer.dandelion.as_ref() {
+            dandelion.lock().observe(
+                self.peer,
+                self.message.tx_hashes().iter().map(|x| x.to_entity()),
+            );
+        }
+
         let hashes: Vec<Byte32> = {
             let tx_filter = state.tx_filter();
             self.message
@@ -3,6 +3,7 @@ use crate::{Status, StatusCode};
 use ckb_error::{Error, ErrorKind, InternalError, InternalErrorKind};
 use ckb_logger::debug_target;
 use ckb_network::{CKBProtocolContext, PeerIndex};
+use ckb_script::{ScriptError, TransactionScriptError};
 use ckb_types::{
     core::{Cycle, TransactionView},
     packed,
@@ -13,9 +14,7 @@ use ckb_verification::cache::CacheEntry;
 use ckb_verification::TransactionError;
 use sentry::{capture_message, with_scope, Level};
 use std::sync::Arc;
-use std::time::Duration;
-
-const DEFAULT_BAN_TIME: Duration = Duration::from_secs(3600 * 24 * 3);
+use std::time::Instant;
 
 pub struct TransactionsProcess<'a> {
     message: packed::RelayTransactionsReader<'a>,
@@ -77,7 +76,9 @@ impl<'a> TransactionsProcess<'a> {
         }
 
         let mut notify_txs = Vec::with_capacity(txs.len());
-        let max_tx_verify_cycles = self.relayer.max_tx_verify_cycles;
+        // the tx pool config may be updated at runtime
+        let tx_pool_config = self.relayer.shared().shared().tx_pool_controller().config();
+        let max_tx_verify_cycles = tx_pool_config.max_tx_verify_cycles;
         let relay_cycles_vec: Vec<_> = txs
             .into_iter()
             .filter_map(|(tx, relay_cycles)| {
@@ -104,10 +105,11 @@ impl<'a> TransactionsProcess<'a> {
         let nc = Arc::clone(&self.nc);
         let peer_index = self.peer;
         let shared = Arc::clone(self.relayer.shared());
-        let min_fee_rate = self.relayer.min_fee_rate;
+        let min_fee_rate = tx_pool_config.min_fee_rate;
 
         let callback = Box::new(move |ret: Result<Vec<CacheEntry>, Error>| match ret {
             Ok(cache_entry_vec) => {
+                let mut accepted = false;
                 for ((tx_hash, relay_cycles, tx_size), cache_entry) in relay_cycles_vec
                     .into_iter()
                     .zip(cache_entry_vec.into_iter())
@@ -131,6 +133,7 @@ impl<'a> TransactionsProcess<'a> {
                             .entry(peer_index)
                             .or_insert_with(LinkedHashSet::default);
                         entry.insert(tx_hash);
+                        accepted = true;
                     } else {
                         debug_target!(
                             crate::LOG_TARGET_RELAY,
@@ -141,14 +144,23 @@ impl<'a> TransactionsProcess<'a> {
                             relay_cycles,
                         );
 
-                        nc.ban_peer(
+                        nc.report_misbehavior(
                             peer_index,
-                            DEFAULT_BAN_TIME,
-                            String::from("send us a transaction with wrong cycles"),
+                            "relay.wrong_cycles",
+                            "send us a transaction with wrong cycles",
                         );
-                        break;
+                        return;
                     }
                 }
+                // the peers relaying us new transactions are protected from the inbound eviction
+                if accepted {
+                    nc.with_peer_mut(
+                        peer_index,
+                        Box::new(|peer| {
+                            peer.last_tx_time = Some(Instant::now());
+                        }),
+                    );
+                }
             }
             Err(err) => {
                 if is_malformed(&err) {
@@ -162,21 +174,15 @@ impl<'a> TransactionsProcess<'a> {
                         |scope| scope.set_fingerprint(Some(&["ckb-sync", "relay-invalid-tx"])),
                         || {
                             capture_message(
-                                &format!(
-                                    "Ban peer {} for {} seconds, reason: \
-                                     relay invalid tx, error: {:?}",
-                                    peer_index,
-                                    DEFAULT_BAN_TIME.as_secs(),
-                                    err
-                                ),
+                                &format!("Peer {} relays invalid tx, error: {:?}", peer_index, err),
                                 Level::Info,
                             )
                         },
                     );
-                    nc.ban_peer(
+                    nc.report_misbehavior(
                         peer_index,
-                        DEFAULT_BAN_TIME,
-                        String::from("send us an invalid transaction"),
+                        "relay.invalid_transaction",
+                        "send us an invalid transaction",
                     );
                 } else {
                     debug_target!(
@@ -204,7 +210,14 @@ fn is_malformed(error: &Error) -> bool {
             .downcast_ref::<TransactionError>()
             .expect("error kind checked")
             .is_malformed_tx(),
-        ErrorKind::Script => true,
+        // running out of the local wall time budget says nothing about the tx
+        ErrorKind::Script => {
+            let script_error = error
+                .downcast_ref::<TransactionScriptError>()
+                .map(|details| &details.error)
+                .or_else(|| error.downcast_ref::<ScriptError>());
+            script_error != Some(&ScriptError::ExceededMaximumTime)
+        }
         ErrorKind::Internal => {
             error
                 .downcast_ref::<InternalError>()
@@ -14,7 +14,7 @@ use self::in_ibd_process::InIBDProcess;
 use crate::block_status::BlockStatus;
 use crate::types::{HeaderView, IBDState, PeerFlags, Peers, SyncShared};
 use crate::{
-    Status, StatusCode, BAD_MESSAGE_BAN_TIME, CHAIN_SYNC_TIMEOUT, EVICTION_HEADERS_RESPONSE_TIME,
+    Status, StatusCode, CHAIN_SYNC_TIMEOUT, EVICTION_HEADERS_RESPONSE_TIME,
     HEADERS_DOWNLOAD_TIMEOUT_BASE, HEADERS_DOWNLOAD_TIMEOUT_PER_HEADER, MAX_HEADERS_LEN,
     MAX_OUTBOUND_PEERS_TO_PROTECT_FROM_DISCONNECT, POW_SPACE,
 };
@@ -25,8 +25,7 @@ use ckb_network::{
 };
 use ckb_types::{core, packed, prelude::*};
 use failure::Error as FailureError;
-use faketime::unix_time_as_millis;
-use std::cmp::min;
+use std::cmp::{min, Reverse};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -161,12 +160,12 @@ impl Synchronizer {
             });
         }
 
-        if let Some(ban_time) = status.should_ban() {
+        if status.should_ban().is_some() {
             error!(
-                "receive {} from {}, ban {:?} for {}",
-                item_name, peer, ban_time, status
+                "receive {} from {}, misbehavior {}",
+                item_name, peer, status
             );
-            nc.ban_peer(peer, ban_time, status.to_string());
+            nc.report_misbehavior(peer, "sync.invalid_message", &status.to_string());
         } else if status.should_warn() {
             warn!("receive {} from {}, {}", item_name, peer, status);
         } else if !status.is_ok() {
@@ -179,7 +178,7 @@ impl Synchronizer {
     }
 
     pub fn predict_headers_sync_time(&self, header: &core::HeaderView) -> u64 {
-        let now = unix_time_as_millis();
+        let now = self.shared.shared().clock().unix_time_as_millis();
         let expected_headers = min(
             MAX_HEADERS_LEN as u64,
             now.saturating_sub(header.timestamp()) / POW_SPACE,
@@ -268,8 +267,9 @@ impl Synchronizer {
         let active_chain = self.shared.active_chain();
         let is_initial_header_sync = self.shared.state().is_initial_header_sync();
         let mut eviction = Vec::new();
+        let clock = self.shared.shared().clock();
         for (peer, state) in peer_states.iter_mut() {
-            let now = unix_time_as_millis();
+            let now = clock.unix_time_as_millis();
 
             // headers_sync_timeout
             if let Some(timeout) = state.headers_sync_timeout {
@@ -352,7 +352,7 @@ impl Synchronizer {
     }
 
     fn start_sync_headers(&self, nc: &dyn CKBProtocolContext) {
-        let now = unix_time_as_millis();
+        let now = self.shared.shared().clock().unix_time_as_millis();
         let active_chain = self.shared.active_chain();
         let ibd = active_chain.is_initial_block_download();
         let peers: Vec<PeerIndex> = self
@@ -479,12 +479,14 @@ impl Synchronizer {
                 .map(|(peer_id, _)| peer_id)
                 .cloned()
                 .collect();
+            // prefer the fast peers, and the ones which stall less among them
             peers.sort_by_key(|id| {
                 state
                     .get(id)
-                    .map_or(crate::INIT_BLOCKS_IN_TRANSIT_PER_PEER, |d| d.task_count())
+                    .map_or((Reverse(crate::INIT_BLOCKS_IN_TRANSIT_PER_PEER), 0), |d| {
+                        (Reverse(d.task_count()), d.stall_score())
+                    })
             });
-            peers.reverse();
             peers
         };
 
@@ -570,10 +572,10 @@ impl CKBProtocolHandler for Synchronizer {
             Ok(msg) => msg.to_enum(),
             _ => {
                 info!("Peer {} sends us a malformed message", peer_index);
-                nc.ban_peer(
+                nc.report_misbehavior(
                     peer_index,
-                    BAD_MESSAGE_BAN_TIME,
-                    String::from("send us a malformed message"),
+                    "sync.malformed_message",
+                    "send us a malformed message",
                 );
                 return;
             }
@@ -1200,6 +1202,7 @@ mod tests {
         }
         fn report_peer(&self, _peer_index: PeerIndex, _behaviour: Behaviour) {}
         fn ban_peer(&self, _peer_index: PeerIndex, _duration: Duration, _reason: String) {}
+        fn report_misbehavior(&self, _peer_index: PeerIndex, _kind: &str, _reason: &str) {}
         // Other methods
         fn protocol_id(&self) -> ProtocolId {
             unimplemented!();
@@ -1536,16 +1539,16 @@ mod tests {
             assert!({ network_context.disconnected.lock().is_empty() });
             assert_eq!(
                 peer_state.get(&3.into()).unwrap().chain_sync.timeout,
-                unix_time_as_millis() + EVICTION_HEADERS_RESPONSE_TIME
+                faketime::unix_time_as_millis() + EVICTION_HEADERS_RESPONSE_TIME
             );
             assert_eq!(
                 peer_state.get(&4.into()).unwrap().chain_sync.timeout,
-                unix_time_as_millis() + EVICTION_HEADERS_RESPONSE_TIME
+                faketime::unix_time_as_millis() + EVICTION_HEADERS_RESPONSE_TIME
             );
         }
         faketime::write_millis(
             &faketime_file,
-            unix_time_as_millis() + EVICTION_HEADERS_RESPONSE_TIME + 1,
+            faketime::unix_time_as_millis() + EVICTION_HEADERS_RESPONSE_TIME + 1,
         )
         .expect("write millis");
         synchronizer.eviction(&network_context);
@@ -194,3 +194,33 @@ fn inflight_trace_number_state() {
     assert_eq!(inflight_blocks.peer_can_fetch_count(3.into()), 8);
     assert_eq!(inflight_blocks.peer_can_fetch_count(4.into()), 8);
 }
+
+#[cfg(not(disable_faketime))]
+#[test]
+fn inflight_blocks_stall_score() {
+    let faketime_file = faketime::millis_tempfile(0).expect("create faketime file");
+    faketime::enable(&faketime_file);
+
+    let mut inflight_blocks = InflightBlocks::default();
+    let hashes = [
+        h256!("0x1").pack(),
+        h256!("0x2").pack(),
+        h256!("0x3").pack(),
+        h256!("0x4").pack(),
+    ];
+
+    // The peer stalls the download window repeatedly, and is disconnected at the 4th time
+    for (i, hash) in hashes.iter().enumerate() {
+        let number = i as u64 + 1;
+        assert!(inflight_blocks.insert(1.into(), (number, hash.clone()).into()));
+        inflight_blocks.mark_slow_block(number - 1);
+        faketime::write_millis(&faketime_file, number * 2000).expect("write millis");
+
+        let list = inflight_blocks.prune(number - 1);
+        if number < 4 {
+            assert!(list.is_empty());
+        } else {
+            assert_eq!(list, HashSet::from_iter(vec![1.into()]));
+        }
+    }
+}
@@ -242,6 +242,7 @@ impl CKBProtocolContext for TestNetworkContext {
     }
     fn report_peer(&self, _peer_index: PeerIndex, _behaviour: Behaviour) {}
     fn ban_peer(&self, _peer_index: PeerIndex, _duration: Duration, _reason: String) {}
+    fn report_misbehavior(&self, _peer_index: PeerIndex, _kind: &str, _reason: &str) {}
     // Other methods
     fn protocol_id(&self) -> ProtocolId {
         self.protocol
@@ -0,0 +1,477 @@
+//! Erlay-style reconciliation of the relayed transactions.
+//!
+//! The relayer puts the hashes it would flood to a peer supporting the protocol into the
+//! reconciliation set of the peer instead. The node reconciles the sets with its outbound peers
+//! periodically: it requests a sketch of the set of the peer with the size of its own set, the
+//! peer sends a sketch sized by the estimated difference, and the node decodes the difference of
+//! the short ids. Then either side announces the transactions the other one lacks through the
+//! relay protocol, which fetches them as usual. Both sides flood their sets if the decoding
+//! fails.
+mod sketch;
+
+use self::sketch::{Sketch, MAX_SKETCH_CELLS};
+use crate::relayer::MAX_RELAY_TXS_NUM_PER_BATCH;
+use crate::types::SyncShared;
+use crate::NetworkProtocol;
+use ckb_hash::new_blake2b;
+use ckb_logger::{debug_target, info_target};
+use ckb_network::{bytes::Bytes, CKBProtocolContext, CKBProtocolHandler, PeerIndex};
+use ckb_types::{
+    packed::{self, Byte32},
+    prelude::*,
+};
+use ckb_util::LinkedHashSet;
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const RECONCILE_TOKEN: u64 = 0;
+const RECONCILE_INTERVAL: Duration = Duration::from_secs(2);
+// A reconciliation the peer doesn't answer in time fails
+const RECONCILE_TIMEOUT: Duration = Duration::from_secs(30);
+// The hashes beyond it are flooded
+const MAX_RECONCILIATION_SET_SIZE: usize = 10_000;
+// The estimated difference is the difference of the set sizes plus a quarter of the smaller
+// set, the share of the transactions which one side has not received from the others yet
+const ESTIMATE_MIN_SET_DIVISOR: usize = 4;
+const ESTIMATE_SLACK: usize = 8;
+
+#[derive(Default)]
+pub(crate) struct TxReconciliationState {
+    peers: HashMap<PeerIndex, PeerReconciliation>,
+}
+
+struct PeerReconciliation {
+    // Only the node initiates the reconciliations with its outbound peers
+    is_initiator: bool,
+    set: LinkedHashSet<Byte32>,
+    in_flight: Option<InFlight>,
+}
+
+// The set snapshot being reconciled, the hashes added since are reconciled next time.
+struct InFlight {
+    started_at: Instant,
+    tx_hashes: HashMap<u64, Byte32>,
+}
+
+impl InFlight {
+    fn new(salt: u64, tx_hashes: impl Iterator<Item = Byte32>) -> Self {
+        InFlight {
+            started_at: Instant::now(),
+            tx_hashes: tx_hashes
+                .map(|tx_hash| (short_id(salt, &tx_hash), tx_hash))
+                .collect(),
+        }
+    }
+
+    fn sketch(&self, cells: usize) -> Sketch {
+        Sketch::new(cells, self.tx_hashes.keys().cloned())
+    }
+}
+
+impl TxReconciliationState {
+    pub(crate) fn connected(&mut self, peer: PeerIndex, is_initiator: bool) {
+        self.peers.insert(
+            peer,
+            PeerReconciliation {
+                is_initiator,
+                set: LinkedHashSet::default(),
+                in_flight: None,
+            },
+        );
+    }
+
+    pub(crate) fn disconnected(&mut self, peer: PeerIndex) {
+        self.peers.remove(&peer);
+    }
+
+    /// Adds the hash to reconcile with the peer, returns false if the peer doesn't reconcile or
+    /// the set is full, the hash is to be flooded then.
+    pub(crate) fn add(&mut self, peer: PeerIndex, tx_hash: Byte32) -> bool {
+        match self.peers.get_mut(&peer) {
+            Some(reconciliation) if reconciliation.set.len() < MAX_RECONCILIATION_SET_SIZE => {
+                reconciliation.set.insert(tx_hash);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // Snapshots the sets of the outbound peers to reconcile, returns the salts and the set sizes
+    // to request, and the snapshots timed out to flood.
+    fn start(
+        &mut self,
+        now: Instant,
+    ) -> (Vec<(PeerIndex, u64, usize)>, Vec<(PeerIndex, Vec<Byte32>)>) {
+        let mut requests = Vec::new();
+        let mut timed_out = Vec::new();
+        for (peer, reconciliation) in self.peers.iter_mut() {
+            if !reconciliation.is_initiator {
+                continue;
+            }
+            if let Some(in_flight) = reconciliation.in_flight.as_ref() {
+                if now.duration_since(in_flight.started_at) < RECONCILE_TIMEOUT {
+                    continue;
+                }
+                let in_flight = reconciliation.in_flight.take().expect("checked");
+                timed_out.push((
+                    *peer,
+                    in_flight.tx_hashes.into_iter().map(|(_, h)| h).collect(),
+                ));
+            }
+            if reconciliation.set.is_empty() {
+                continue;
+            }
+            let salt = rand::random();
+            let set = std::mem::take(&mut reconciliation.set);
+            let in_flight = InFlight::new(salt, set.into_iter());
+            requests.push((*peer, salt, in_flight.tx_hashes.len()));
+            reconciliation.in_flight = Some(in_flight);
+        }
+        (requests, timed_out)
+    }
+
+    // Snapshots the set on the request of the peer, returns the sketch sized by the estimated
+    // difference.
+    fn respond(&mut self, peer: PeerIndex, salt: u64, remote_set_size: usize) -> Option<Sketch> {
+        let reconciliation = self.peers.get_mut(&peer)?;
+        if reconciliation.is_initiator {
+            return None;
+        }
+        // the snapshot the peer gave up on is reconciled again
+        let mut tx_hashes: Vec<Byte32> = reconciliation
+            .in_flight
+            .take()
+            .map(|in_flight| in_flight.tx_hashes.into_iter().map(|(_, h)| h).collect())
+            .unwrap_or_default();
+        tx_hashes.extend(std::mem::take(&mut reconciliation.set).into_iter());
+        let in_flight = InFlight::new(salt, tx_hashes.into_iter());
+        let local_set_size = in_flight.tx_hashes.len();
+        let estimated_difference = local_set_size.max(remote_set_size)
+            - local_set_size.min(remote_set_size)
+            + local_set_size.min(remote_set_size) / ESTIMATE_MIN_SET_DIVISOR
+            + ESTIMATE_SLACK;
+        let sketch = in_flight.sketch(Sketch::cells_for(estimated_difference));
+        reconciliation.in_flight = Some(in_flight);
+        Some(sketch)
+    }
+
+    // Decodes the sketch of the peer against the snapshot, returns the hashes to announce to
+    // the peer, and the short ids to request from it, or `None` if the decoding fails.
+    fn decode(
+        &mut self,
+        peer: PeerIndex,
+        remote: &Sketch,
+    ) -> Option<(Vec<Byte32>, Option<Vec<u64>>)> {
+        let reconciliation = self.peers.get_mut(&peer)?;
+        if !reconciliation.is_initiator {
+            return None;
+        }
+        let in_flight = reconciliation.in_flight.take()?;
+        match in_flight.sketch(remote.cells()).decode(remote) {
+            Some(difference) => {
+                let announced = difference
+                    .local
+                    .iter()
+                    .filter_map(|short_id| in_flight.tx_hashes.get(short_id).cloned())
+                    .collect();
+                Some((announced, Some(difference.remote)))
+            }
+            None => Some((
+                in_flight.tx_hashes.into_iter().map(|(_, h)| h).collect(),
+                None,
+            )),
+        }
+    }
+
+    // Finishes the reconciliation the peer initiated, returns the hashes to announce to it.
+    fn finish(&mut self, peer: PeerIndex, missing_short_ids: Option<Vec<u64>>) -> Vec<Byte32> {
+        let in_flight = match self
+            .peers
+            .get_mut(&peer)
+            .filter(|reconciliation| !reconciliation.is_initiator)
+            .and_then(|reconciliation| reconciliation.in_flight.take())
+        {
+            Some(in_flight) => in_flight,
+            None => return Vec::new(),
+        };
+        match missing_short_ids {
+            Some(short_ids) => short_ids
+                .iter()
+                .filter_map(|short_id| in_flight.tx_hashes.get(short_id).cloned())
+                .collect(),
+            None => in_flight.tx_hashes.into_iter().map(|(_, h)| h).collect(),
+        }
+    }
+}
+
+// The salted short id of the transaction, the peers can't grind the transactions colliding
+// in the sketches without knowing the salt.
+fn short_id(salt: u64, tx_hash: &Byte32) -> u64 {
+    let mut ret = [0u8; 32];
+    let mut blake2b = new_blake2b();
+    blake2b.update(&salt.to_le_bytes());
+    blake2b.update(tx_hash.as_slice());
+    blake2b.finalize(&mut ret);
+    u64::from_le_bytes(ret[..8].try_into().expect("32 bytes hash"))
+}
+
+/// Reconcile the relayed transactions with the peers supporting it
+#[derive(Clone)]
+pub struct TxReconciliationProtocol {
+    shared: Arc<SyncShared>,
+}
+
+impl TxReconciliationProtocol {
+    pub fn new(shared: Arc<SyncShared>) -> Self {
+        TxReconciliationProtocol { shared }
+    }
+
+    fn reconcile(&self, nc: &dyn CKBProtocolContext) {
+        let (requests, timed_out) = self
+            .shared
+            .state()
+            .tx_reconciliation()
+            .start(Instant::now());
+        for (peer, tx_hashes) in timed_out {
+            debug_target!(
+                crate::LOG_TARGET_RELAY,
+                "reconciliation with peer {} timed out, flood {} txs",
+                peer,
+                tx_hashes.len()
+            );
+            announce_tx_hashes(nc, peer, tx_hashes);
+        }
+        for (peer, salt, set_size) in requests {
+            let content = packed::ReqReconciliation::new_builder()
+                .salt(salt.pack())
+                .set_size((set_size as u32).pack())
+                .build();
+            let message = packed::TxReconciliationMessage::new_builder()
+                .set(content)
+                .build();
+            if let Err(err) = nc.send_message_to(peer, message.as_bytes()) {
+                debug_target!(
+                    crate::LOG_TARGET_RELAY,
+                    "tx_reconciliation send message error: {:?}",
+                    err
+                );
+            }
+        }
+    }
+
+    fn process(
+        &self,
+        nc: &dyn CKBProtocolContext,
+        peer: PeerIndex,
+        message: packed::TxReconciliationMessageUnionReader,
+    ) -> Result<(), &'static str> {
+        match message {
+            packed::TxReconciliationMessageUnionReader::ReqReconciliation(reader) => {
+                let salt: u64 = reader.salt().unpack();
+                let set_size: u32 = reader.set_size().unpack();
+                let sketch =
+                    self.shared
+                        .state()
+                        .tx_reconciliation()
+                        .respond(peer, salt, set_size as usize);
+                if let Some(sketch) = sketch {
+                    let content = packed::ReconciliationSketch::new_builder()
+                        .sketch(sketch.to_bytes().pack())
+                        .build();
+                    let message = packed::TxReconciliationMessage::new_builder()
+                        .set(content)
+                        .build();
+                    if let Err(err) = nc.send_message_to(peer, message.as_bytes()) {
+                        debug_target!(
+                            crate::LOG_TARGET_RELAY,
+                            "tx_reconciliation send message error: {:?}",
+                            err
+                        );
+                    }
+                }
+            }
+            packed::TxReconciliationMessageUnionReader::ReconciliationSketch(reader) => {
+                let sketch = Sketch::from_slice(reader.sketch().raw_data())
+                    .ok_or("send us a malformed sketch")?;
+                let decoded = self
+                    .shared
+                    .state()
+                    .tx_reconciliation()
+                    .decode(peer, &sketch);
+                if let Some((announced, missing_short_ids)) = decoded {
+                    if missing_short_ids.is_none() {
+                        debug_target!(
+                            crate::LOG_TARGET_RELAY,
+                            "reconciliation with peer {} failed, flood {} txs",
+                            peer,
+                            announced.len()
+                        );
+                    }
+                    let content = packed::ReconciliationDiff::new_builder()
+                        .success(missing_short_ids.is_some().pack())
+                        .missing_short_ids(missing_short_ids.unwrap_or_default().pack())
+                        .build();
+                    let message = packed::TxReconciliationMessage::new_builder()
+                        .set(content)
+                        .build();
+                    if let Err(err) = nc.send_message_to(peer, message.as_bytes()) {
+                        debug_target!(
+                            crate::LOG_TARGET_RELAY,
+                            "tx_reconciliation send message error: {:?}",
+                            err
+                        );
+                    }
+                    announce_tx_hashes(nc, peer, announced);
+                }
+            }
+            packed::TxReconciliationMessageUnionReader::ReconciliationDiff(reader) => {
+                let missing_short_ids = reader.missing_short_ids();
+                if missing_short_ids.len() > MAX_SKETCH_CELLS {
+                    return Err("send us too many missing short ids");
+                }
+                // unpacking panics on a malformed `Bool`
+                let success = reader.success().as_slice()[0] != 0;
+                let missing_short_ids = if success {
+                    Some(missing_short_ids.unpack())
+                } else {
+                    None
+                };
+                let announced = self
+                    .shared
+                    .state()
+                    .tx_reconciliation()
+                    .finish(peer, missing_short_ids);
+                announce_tx_hashes(nc, peer, announced);
+            }
+        }
+        Ok(())
+    }
+}
+
+// Announces the hashes through the relay protocol, the peer fetches the transactions it lacks.
+fn announce_tx_hashes(nc: &dyn CKBProtocolContext, peer: PeerIndex, tx_hashes: Vec<Byte32>) {
+    for chunk in tx_hashes.chunks(MAX_RELAY_TXS_NUM_PER_BATCH) {
+        let content = packed::RelayTransactionHashes::new_builder()
+            .tx_hashes(chunk.to_vec().pack())
+            .build();
+        let message = packed::RelayMessage::new_builder().set(content).build();
+        if let Err(err) = nc.send_message(NetworkProtocol::RELAY.into(), peer, message.as_bytes()) {
+            debug_target!(
+                crate::LOG_TARGET_RELAY,
+                "tx_reconciliation send TransactionHashes error: {:?}",
+                err
+            );
+        }
+    }
+}
+
+impl CKBProtocolHandler for TxReconciliationProtocol {
+    fn init(&mut self, nc: Arc<dyn CKBProtocolContext + Sync>) {
+        nc.set_notify(RECONCILE_INTERVAL, RECONCILE_TOKEN)
+            .expect("set_notify at init is ok");
+    }
+
+    fn connected(
+        &mut self,
+        nc: Arc<dyn CKBProtocolContext + Sync>,
+        peer_index: PeerIndex,
+        version: &str,
+    ) {
+        info_target!(
+            crate::LOG_TARGET_RELAY,
+            "TxReconciliationProtocol({}).connected peer={}",
+            version,
+            peer_index
+        );
+        let is_outbound = nc
+            .get_peer(peer_index)
+            .map(|peer| peer.is_outbound())
+            .unwrap_or(false);
+        self.shared
+            .state()
+            .tx_reconciliation()
+            .connected(peer_index, is_outbound);
+    }
+
+    fn disconnected(&mut self, _nc: Arc<dyn CKBProtocolContext + Sync>, peer_index: PeerIndex) {
+        info_target!(
+            crate::LOG_TARGET_RELAY,
+            "TxReconciliationProtocol.disconnected peer={}",
+            peer_index
+        );
+        self.shared
+            .state()
+            .tx_reconciliation()
+            .disconnected(peer_index);
+    }
+
+    fn received(
+        &mut self,
+        nc: Arc<dyn CKBProtocolContext + Sync>,
+        peer_index: PeerIndex,
+        data: Bytes,
+    ) {
+        let result = packed::TxReconciliationMessageReader::from_slice(&data)
+            .map_err(|_| "send us a malformed message")
+            .and_then(|message| self.process(nc.as_ref(), peer_index, message.to_enum()));
+        if let Err(reason) = result {
+            info_target!(crate::LOG_TARGET_RELAY, "Peer {} {}", peer_index, reason);
+            nc.report_misbehavior(peer_index, "tx_reconciliation.invalid_message", reason);
+        }
+    }
+
+    fn notify(&mut self, nc: Arc<dyn CKBProtocolContext + Sync>, token: u64) {
+        if token == RECONCILE_TOKEN && !self.shared.active_chain().is_initial_block_download() {
+            self.reconcile(nc.as_ref());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ckb_types::{h256, prelude::*};
+
+    #[test]
+    fn test_reconcile_sets() {
+        let (initiator_peer, responder_peer) = (PeerIndex::new(1), PeerIndex::new(2));
+        let mut initiator = TxReconciliationState::default();
+        let mut responder = TxReconciliationState::default();
+        initiator.connected(responder_peer, true);
+        responder.connected(initiator_peer, false);
+
+        let (shared, only_initiator, only_responder) = (
+            h256!("0x1").pack(),
+            h256!("0x2").pack(),
+            h256!("0x3").pack(),
+        );
+        for tx_hash in &[shared.clone(), only_initiator.clone()] {
+            assert!(initiator.add(responder_peer, tx_hash.clone()));
+        }
+        for tx_hash in &[shared, only_responder.clone()] {
+            assert!(responder.add(initiator_peer, tx_hash.clone()));
+        }
+        assert!(!initiator.add(PeerIndex::new(3), only_initiator.clone()));
+
+        let (requests, timed_out) = initiator.start(Instant::now());
+        assert!(timed_out.is_empty());
+        let (peer, salt, set_size) = requests[0];
+        assert_eq!((peer, set_size), (responder_peer, 2));
+        // the responder never initiates
+        assert!(responder.start(Instant::now()).0.is_empty());
+
+        let sketch = responder
+            .respond(initiator_peer, salt, set_size)
+            .expect("responded");
+        let (announced, missing_short_ids) = initiator
+            .decode(responder_peer, &sketch)
+            .expect("in flight");
+        assert_eq!(announced, vec![only_initiator]);
+        assert_eq!(
+            responder.finish(initiator_peer, missing_short_ids),
+            vec![only_responder]
+        );
+    }
+}
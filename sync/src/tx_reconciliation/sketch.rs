@@ -0,0 +1,205 @@
+//! Invertible Bloom lookup tables of the transaction short ids.
+//!
+//! Every short id is added to one cell in each of the `HASH_COUNT` sub tables. Subtracting the
+//! sketch of the peer leaves the short ids in only one of the sets, which are peeled from the
+//! pure cells, the cells holding a single short id. The decoding fails when the difference
+//! exceeds the capacity, then the sets are flooded instead.
+use ckb_types::bytes::Bytes;
+use std::convert::TryInto;
+
+const HASH_COUNT: usize = 3;
+// count: i32, short id xor: u64, check xor: u32
+const CELL_SIZE: usize = 4 + 8 + 4;
+/// Upper bound of the cells of a sketch, it decodes about 6000 differences
+pub(crate) const MAX_SKETCH_CELLS: usize = 3 * 3000;
+const MIN_SKETCH_CELLS: usize = 3 * 4;
+
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq)]
+struct Cell {
+    count: i32,
+    id_sum: u64,
+    check_sum: u32,
+}
+
+impl Cell {
+    fn toggle(&mut self, short_id: u64, count: i32) {
+        self.count = self.count.wrapping_add(count);
+        self.id_sum ^= short_id;
+        self.check_sum ^= check_hash(short_id);
+    }
+
+    fn is_empty(&self) -> bool {
+        *self == Cell::default()
+    }
+
+    fn is_pure(&self) -> bool {
+        (self.count == 1 || self.count == -1) && self.check_sum == check_hash(self.id_sum)
+    }
+}
+
+/// The short ids only in the local set, and the ones only in the remote set.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct Difference {
+    pub(crate) local: Vec<u64>,
+    pub(crate) remote: Vec<u64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Sketch {
+    cells: Vec<Cell>,
+}
+
+impl Sketch {
+    /// The cells which decode `capacity` differences with a high probability.
+    pub(crate) fn cells_for(capacity: usize) -> usize {
+        let cells = capacity
+            .saturating_mul(2)
+            .max(MIN_SKETCH_CELLS)
+            .min(MAX_SKETCH_CELLS);
+        // every sub table has the same size
+        cells + (HASH_COUNT - cells % HASH_COUNT) % HASH_COUNT
+    }
+
+    pub(crate) fn new(cells: usize, short_ids: impl Iterator<Item = u64>) -> Self {
+        let mut sketch = Sketch {
+            cells: vec![Cell::default(); cells],
+        };
+        for short_id in short_ids {
+            sketch.toggle(short_id, 1);
+        }
+        sketch
+    }
+
+    pub(crate) fn cells(&self) -> usize {
+        self.cells.len()
+    }
+
+    fn toggle(&mut self, short_id: u64, count: i32) {
+        let table_size = self.cells.len() / HASH_COUNT;
+        for table in 0..HASH_COUNT {
+            let index =
+                table * table_size + (cell_hash(short_id, table) % table_size as u64) as usize;
+            self.cells[index].toggle(short_id, count);
+        }
+    }
+
+    /// Decodes the difference from the remote sketch of the same size, returns `None` if it
+    /// exceeds the capacity.
+    pub(crate) fn decode(mut self, remote: &Sketch) -> Option<Difference> {
+        if self.cells.len() != remote.cells.len() {
+            return None;
+        }
+        for (cell, remote_cell) in self.cells.iter_mut().zip(remote.cells.iter()) {
+            cell.count = cell.count.wrapping_sub(remote_cell.count);
+            cell.id_sum ^= remote_cell.id_sum;
+            cell.check_sum ^= remote_cell.check_sum;
+        }
+
+        let mut difference = Difference::default();
+        loop {
+            let pure = match self.cells.iter().find(|cell| cell.is_pure()) {
+                Some(cell) => *cell,
+                None => break,
+            };
+            if pure.count == 1 {
+                difference.local.push(pure.id_sum);
+            } else {
+                difference.remote.push(pure.id_sum);
+            }
+            self.toggle(pure.id_sum, -pure.count);
+        }
+        if self.cells.iter().all(Cell::is_empty) {
+            Some(difference)
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn to_bytes(&self) -> Bytes {
+        let mut bytes = Vec::with_capacity(self.cells.len() * CELL_SIZE);
+        for cell in &self.cells {
+            bytes.extend_from_slice(&cell.count.to_le_bytes());
+            bytes.extend_from_slice(&cell.id_sum.to_le_bytes());
+            bytes.extend_from_slice(&cell.check_sum.to_le_bytes());
+        }
+        bytes.into()
+    }
+
+    pub(crate) fn from_slice(slice: &[u8]) -> Option<Self> {
+        if slice.len() % CELL_SIZE != 0 {
+            return None;
+        }
+        let cells = slice.len() / CELL_SIZE;
+        if cells == 0 || cells % HASH_COUNT != 0 || cells > MAX_SKETCH_CELLS {
+            return None;
+        }
+        let cells = slice
+            .chunks(CELL_SIZE)
+            .map(|chunk| Cell {
+                count: i32::from_le_bytes(chunk[0..4].try_into().expect("checked len")),
+                id_sum: u64::from_le_bytes(chunk[4..12].try_into().expect("checked len")),
+                check_sum: u32::from_le_bytes(chunk[12..16].try_into().expect("checked len")),
+            })
+            .collect();
+        Some(Sketch { cells })
+    }
+}
+
+// SplitMix64 finalizer, the short ids are already salted hashes, the mixing only has to
+// decorrelate the sub tables and the check sums.
+fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+fn cell_hash(short_id: u64, table: usize) -> u64 {
+    mix(short_id.wrapping_add((table as u64 + 1).wrapping_mul(0x9e37_79b9_7f4a_7c15)))
+}
+
+fn check_hash(short_id: u64) -> u32 {
+    (mix(!short_id) >> 32) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_difference() {
+        let local: Vec<u64> = (0..1000).map(mix).collect();
+        let mut remote: Vec<u64> = local[10..].to_vec();
+        remote.extend((1000..1020).map(mix));
+
+        let cells = Sketch::cells_for(40);
+        let remote_sketch = Sketch::from_slice(&Sketch::new(cells, remote.into_iter()).to_bytes())
+            .expect("well formed");
+        let mut difference = Sketch::new(cells, local.iter().cloned())
+            .decode(&remote_sketch)
+            .expect("decoded");
+        difference.local.sort();
+        difference.remote.sort();
+        let mut expected_local = local[..10].to_vec();
+        expected_local.sort();
+        let mut expected_remote: Vec<u64> = (1000..1020).map(mix).collect();
+        expected_remote.sort();
+        assert_eq!(difference.local, expected_local);
+        assert_eq!(difference.remote, expected_remote);
+    }
+
+    #[test]
+    fn test_decode_over_capacity() {
+        let cells = Sketch::cells_for(4);
+        let local = Sketch::new(cells, (0..100).map(mix));
+        let remote = Sketch::new(cells, (100..200).map(mix));
+        assert_eq!(local.decode(&remote), None);
+    }
+
+    #[test]
+    fn test_malformed_sketch() {
+        assert_eq!(Sketch::from_slice(&[]), None);
+        assert_eq!(Sketch::from_slice(&[0u8; CELL_SIZE * 2]), None);
+        assert_eq!(Sketch::from_slice(&[0u8; CELL_SIZE * 3 + 1]), None);
+        assert!(Sketch::from_slice(&[0u8; CELL_SIZE * 3]).is_some());
+    }
+}
@@ -1,13 +1,15 @@
 use crate::block_status::BlockStatus;
 use crate::orphan_block_pool::OrphanBlockPool;
-use crate::BLOCK_DOWNLOAD_TIMEOUT;
+use crate::tx_reconciliation::TxReconciliationState;
 use crate::{NetworkProtocol, SUSPEND_SYNC_TIME};
+use crate::{BLOCK_DOWNLOAD_TIMEOUT, STALL_SCORE_LIMIT, STALL_SCORE_STALL, STALL_SCORE_TIMEOUT};
 use crate::{
     FIRST_LEVEL_MAX, INIT_BLOCKS_IN_TRANSIT_PER_PEER, MAX_BLOCKS_IN_TRANSIT_PER_PEER,
     MAX_HEADERS_LEN, MAX_TIP_AGE, RETRY_ASK_TX_TIMEOUT_INCREASE,
 };
-use ckb_chain::chain::ChainController;
+use ckb_chain::{chain::ChainController, switch::Switch};
 use ckb_chain_spec::consensus::Consensus;
+use ckb_clock::{Clock, SystemClock};
 use ckb_logger::{debug, debug_target, error, metric};
 use ckb_network::{CKBProtocolContext, PeerIndex};
 use ckb_shared::{shared::Shared, Snapshot};
@@ -23,7 +25,6 @@ use ckb_util::{Mutex, MutexGuard};
 use ckb_util::{RwLock, RwLockReadGuard, RwLockWriteGuard};
 use ckb_verification::HeaderResolverWrapper;
 use failure::Error as FailureError;
-use faketime::unix_time_as_millis;
 use lru_cache::LruCache;
 use std::cmp;
 use std::collections::{btree_map::Entry, BTreeMap, HashMap, HashSet};
@@ -46,6 +47,12 @@ const TX_ASKED_SIZE: usize = TX_FILTER_SIZE;
 const ORPHAN_BLOCK_SIZE: usize = 1024;
 // 2 ** 13 < 6 * 1800 < 2 ** 14
 const ONE_DAY_BLOCK_NUMBER: u64 = 8192;
+// The hit rate of the short ids is measured over about this many recent short ids
+const COMPACT_BLOCK_STATS_WINDOW: u64 = 2_000;
+// The hit rate is trusted once this many recent short ids are seen
+const MIN_SHORT_IDS_TO_ADAPT: u64 = 100;
+// The peers whose compact blocks hit fewer short ids than this are announced the full blocks
+const LOW_SHORT_ID_HIT_RATE: f64 = 0.5;
 
 // State used to enforce CHAIN_SYNC_TIMEOUT
 // Only in effect for connections that are outbound, non-manual,
@@ -77,6 +84,62 @@ pub struct PeerFlags {
     pub is_whitelist: bool,
 }
 
+/// The reconstruction statistics of the compact blocks received from a peer.
+#[derive(Clone, Default, Debug, PartialEq, Eq)]
+pub struct CompactBlockStats {
+    /// The compact blocks which were reconstructed or tried to
+    pub compact_blocks: u64,
+    /// The short ids in those compact blocks
+    pub short_ids: u64,
+    /// The short ids resolved from the tx-pool
+    pub short_id_hits: u64,
+    /// The compact blocks whose missing transactions or uncles were requested from the peer
+    pub fallbacks: u64,
+    /// The fallbacks which requested all the transactions because of short id collisions
+    pub collisions: u64,
+    // The counts of the recent short ids, halved once they exceed the window
+    recent_short_ids: u64,
+    recent_short_id_hits: u64,
+}
+
+impl CompactBlockStats {
+    pub fn record_reconstruction(&mut self, short_ids: u64, short_id_hits: u64) {
+        self.compact_blocks += 1;
+        self.short_ids += short_ids;
+        self.short_id_hits += short_id_hits;
+        self.recent_short_ids += short_ids;
+        self.recent_short_id_hits += short_id_hits;
+        if self.recent_short_ids > COMPACT_BLOCK_STATS_WINDOW {
+            self.recent_short_ids /= 2;
+            self.recent_short_id_hits /= 2;
+        }
+    }
+
+    pub fn record_fallback(&mut self, collision: bool) {
+        self.fallbacks += 1;
+        if collision {
+            self.collisions += 1;
+        }
+    }
+
+    /// The hit rate of the recent short ids, `None` until enough short ids are seen.
+    pub fn recent_hit_rate(&self) -> Option<f64> {
+        if self.recent_short_ids < MIN_SHORT_IDS_TO_ADAPT {
+            None
+        } else {
+            Some(self.recent_short_id_hits as f64 / self.recent_short_ids as f64)
+        }
+    }
+
+    /// Our tx-pool misses most transactions of the peer, so the peer probably misses ours as
+    /// well, announcing the full blocks saves it a round trip.
+    pub fn prefers_full_blocks(&self) -> bool {
+        self.recent_hit_rate()
+            .map(|hit_rate| hit_rate < LOW_SHORT_ID_HIT_RATE)
+            .unwrap_or(false)
+    }
+}
+
 #[derive(Clone, Default, Debug)]
 pub struct PeerState {
     // only use on header sync
@@ -94,6 +157,9 @@ pub struct PeerState {
     // use on ibd concurrent block download
     // save `get_headers` locator hashes here
     pub unknown_header_list: Vec<Byte32>,
+    pub compact_block_stats: CompactBlockStats,
+    // the pool inventory is served once per connection
+    pub tx_pool_inventory_served: bool,
 }
 
 impl PeerState {
@@ -109,6 +175,8 @@ impl PeerState {
             best_known_header: None,
             last_common_header: None,
             unknown_header_list: Vec::new(),
+            compact_block_stats: CompactBlockStats::default(),
+            tx_pool_inventory_served: false,
         }
     }
 
@@ -129,8 +197,7 @@ impl PeerState {
         self.headers_sync_timeout = Some(headers_sync_timeout);
     }
 
-    pub fn suspend_sync(&mut self, suspend_time: u64) {
-        let now = unix_time_as_millis();
+    pub fn suspend_sync(&mut self, now: u64, suspend_time: u64) {
         self.sync_started = false;
         self.chain_sync.not_sync_until = Some(now + suspend_time);
         self.headers_sync_timeout = None;
@@ -257,11 +324,8 @@ pub struct InflightState {
 }
 
 impl InflightState {
-    fn new(peer: PeerIndex) -> Self {
-        Self {
-            peer,
-            timestamp: unix_time_as_millis(),
-        }
+    fn new(peer: PeerIndex, timestamp: u64) -> Self {
+        Self { peer, timestamp }
     }
 }
 
@@ -285,6 +349,7 @@ pub struct DownloadScheduler {
     task_count: usize,
     timeout_count: usize,
     breakthroughs_count: usize,
+    stall_score: u32,
     hashes: HashSet<BlockNumberAndHash>,
 }
 
@@ -295,6 +360,7 @@ impl Default for DownloadScheduler {
             task_count: INIT_BLOCKS_IN_TRANSIT_PER_PEER,
             breakthroughs_count: 0,
             timeout_count: 0,
+            stall_score: 0,
         }
     }
 }
@@ -312,8 +378,15 @@ impl DownloadScheduler {
         self.task_count
     }
 
-    fn adjust(&mut self, time: u64, len: u64) {
-        let now = unix_time_as_millis();
+    pub(crate) fn stall_score(&self) -> u32 {
+        self.stall_score
+    }
+
+    fn add_stall_score(&mut self, score: u32) {
+        self.stall_score = self.stall_score.saturating_add(score);
+    }
+
+    fn adjust(&mut self, now: u64, time: u64, len: u64) {
         // 8 means default max outbound
         // All synchronization tests are based on the assumption of 8 nodes.
         // If the number of nodes is increased, the number of requests and processing time will increase,
@@ -329,7 +402,11 @@ impl DownloadScheduler {
         // causing all responses to be in the range of reduced tasks.
         // So the adjustment to reduce the number of tasks will be calculated by modulo 3 with the actual number of triggers.
         // Adjust for each block received.
-        match ((now - time) / quotient).saturating_sub(remainder * 100) {
+        let elapsed = ((now - time) / quotient).saturating_sub(remainder * 100);
+        if elapsed <= 1000 {
+            self.stall_score = self.stall_score.saturating_sub(1);
+        }
+        match elapsed {
             // Within 500ms of response, considered better to communicate with that node's network
             0..=500 => {
                 if self.task_count < FIRST_LEVEL_MAX - 1 {
@@ -378,18 +455,12 @@ pub struct InflightBlocks {
     compact_reconstruct_inflight: HashMap<Byte32, HashSet<PeerIndex>>,
     pub(crate) restart_number: BlockNumber,
     pub(crate) adjustment: bool,
+    clock: Arc<dyn Clock>,
 }
 
 impl Default for InflightBlocks {
     fn default() -> Self {
-        InflightBlocks {
-            download_schedulers: HashMap::default(),
-            inflight_states: BTreeMap::default(),
-            trace_number: HashMap::default(),
-            compact_reconstruct_inflight: HashMap::default(),
-            restart_number: 0,
-            adjustment: true,
-        }
+        InflightBlocks::with_clock(Arc::new(SystemClock))
     }
 }
 
@@ -423,6 +494,18 @@ impl fmt::Debug for InflightBlocks {
 }
 
 impl InflightBlocks {
+    pub fn with_clock(clock: Arc<dyn Clock>) -> Self {
+        InflightBlocks {
+            download_schedulers: HashMap::default(),
+            inflight_states: BTreeMap::default(),
+            trace_number: HashMap::default(),
+            compact_reconstruct_inflight: HashMap::default(),
+            restart_number: 0,
+            adjustment: true,
+            clock,
+        }
+    }
+
     pub fn blocks_iter(&self) -> impl Iterator<Item = (&PeerIndex, &HashSet<BlockNumberAndHash>)> {
         self.download_schedulers.iter().map(|(k, v)| (k, &v.hashes))
     }
@@ -474,7 +557,7 @@ impl InflightBlocks {
     }
 
     pub fn mark_slow_block(&mut self, tip: BlockNumber) {
-        let now = faketime::unix_time_as_millis();
+        let now = self.clock.unix_time_as_millis();
         for key in self.inflight_states.keys() {
             if key.number > tip + 1 {
                 break;
@@ -484,7 +567,7 @@ impl InflightBlocks {
     }
 
     pub fn prune(&mut self, tip: BlockNumber) -> HashSet<PeerIndex> {
-        let now = unix_time_as_millis();
+        let now = self.clock.unix_time_as_millis();
         let prev_count = self.total_inflight_count();
         let mut disconnect_list = HashSet::new();
 
@@ -504,13 +587,18 @@ impl InflightBlocks {
                 break;
             }
             if value.timestamp + BLOCK_DOWNLOAD_TIMEOUT < now {
-                download_schedulers
-                    .get_mut(&value.peer)
-                    .map(|set| set.hashes.remove(key));
+                match download_schedulers.get_mut(&value.peer) {
+                    Some(set) => {
+                        set.hashes.remove(key);
+                        set.add_stall_score(STALL_SCORE_TIMEOUT);
+                    }
+                    None => {
+                        disconnect_list.insert(value.peer);
+                    }
+                }
                 if !trace.is_empty() {
                     trace.remove(&key);
                 }
-                disconnect_list.insert(value.peer);
                 remove_key.push(key.clone());
             }
         }
@@ -519,16 +607,6 @@ impl InflightBlocks {
             states.remove(&key);
         }
 
-        download_schedulers.retain(|k, v| {
-            // task number zero means this peer's response is very slow
-            if v.task_count == 0 {
-                disconnect_list.insert(*k);
-                false
-            } else {
-                true
-            }
-        });
-
         if self.restart_number != 0 && tip + 1 > self.restart_number {
             self.restart_number = 0;
         }
@@ -547,12 +625,14 @@ impl InflightBlocks {
                 if let Some(state) = states.remove(key) {
                     if let Some(d) = download_schedulers.get_mut(&state.peer) {
                         d.punish();
+                        d.add_stall_score(STALL_SCORE_STALL);
                         d.hashes.remove(key);
                     };
                 } else if let Some(v) = compact_inflight.remove(&key.hash) {
                     for peer in v {
                         if let Some(d) = download_schedulers.get_mut(&peer) {
                             d.punish();
+                            d.add_stall_score(STALL_SCORE_STALL);
                         }
                     }
                 }
@@ -564,6 +644,25 @@ impl InflightBlocks {
             true
         });
 
+        download_schedulers.retain(|k, v| {
+            if v.stall_score >= STALL_SCORE_LIMIT {
+                debug!(
+                    "disconnect stalling peer {}, stall score {}",
+                    k, v.stall_score
+                );
+                // the peers which are not disconnected, e.g. the whitelist, start over
+                v.stall_score = 0;
+                disconnect_list.insert(*k);
+            }
+            // task number zero means this peer's response is very slow
+            if v.task_count == 0 {
+                disconnect_list.insert(*k);
+                false
+            } else {
+                true
+            }
+        });
+
         if prev_count == 0 {
             metric!({
                 "topic": "blocks_in_flight",
@@ -580,27 +679,25 @@ impl InflightBlocks {
     }
 
     pub fn insert(&mut self, peer: PeerIndex, block: BlockNumberAndHash) -> bool {
+        let now = self.clock.unix_time_as_millis();
         if !self.compact_reconstruct_inflight.is_empty()
             && self.compact_reconstruct_inflight.contains_key(&block.hash)
         {
             // Give the compact block a deadline of 1.5 seconds
-            self.trace_number
-                .entry(block)
-                .or_insert(unix_time_as_millis() + 500);
+            self.trace_number.entry(block).or_insert(now + 500);
             return false;
         }
         let state = self.inflight_states.entry(block.clone());
         match state {
             Entry::Occupied(_entry) => return false,
-            Entry::Vacant(entry) => entry.insert(InflightState::new(peer)),
+            Entry::Vacant(entry) => entry.insert(InflightState::new(peer, now)),
         };
 
         if self.restart_number >= block.number {
             // All new requests smaller than restart_number mean that they are cleaned up and
             // cannot be immediately marked as cleaned up again, so give it a normal response time of 1.5s.
             // (timeout check is 1s, plus 0.5s given in advance)
-            self.trace_number
-                .insert(block.clone(), unix_time_as_millis() + 500);
+            self.trace_number.insert(block.clone(), now + 500);
         }
 
         let download_scheduler = self
@@ -633,6 +730,7 @@ impl InflightBlocks {
     }
 
     pub fn remove_by_block(&mut self, block: BlockNumberAndHash) -> bool {
+        let now = self.clock.unix_time_as_millis();
         let download_schedulers = &mut self.download_schedulers;
         let trace = &mut self.trace_number;
         let compact = &mut self.compact_reconstruct_inflight;
@@ -647,7 +745,7 @@ impl InflightBlocks {
                         compact.remove(&block.hash);
                     }
                     if adjustment {
-                        set.adjust(state.timestamp, len);
+                        set.adjust(now, state.timestamp, len);
                     }
                     if !trace.is_empty() {
                         trace.remove(&block);
@@ -656,7 +754,7 @@ impl InflightBlocks {
                 state.timestamp
             })
             .map(|timestamp| {
-                let elapsed = unix_time_as_millis().saturating_sub(timestamp);
+                let elapsed = now.saturating_sub(timestamp);
                 metric!({
                     "topic": "blocks_in_flight",
                     "fields": { "total": self.total_inflight_count(), "elapsed": elapsed }
@@ -717,6 +815,37 @@ impl Peers {
         }
     }
 
+    pub fn record_compact_block(&self, peer: PeerIndex, short_ids: u64, short_id_hits: u64) {
+        self.state.write().entry(peer).and_modify(|peer_state| {
+            peer_state
+                .compact_block_stats
+                .record_reconstruction(short_ids, short_id_hits)
+        });
+    }
+
+    pub fn record_compact_block_fallback(&self, peer: PeerIndex, collision: bool) {
+        self.state
+            .write()
+            .entry(peer)
+            .and_modify(|peer_state| peer_state.compact_block_stats.record_fallback(collision));
+    }
+
+    pub fn compact_block_stats(&self) -> HashMap<PeerIndex, CompactBlockStats> {
+        self.state
+            .read()
+            .iter()
+            .map(|(peer, peer_state)| (*peer, peer_state.compact_block_stats.clone()))
+            .collect()
+    }
+
+    pub fn prefers_full_blocks(&self, peer: PeerIndex) -> bool {
+        self.state
+            .read()
+            .get(&peer)
+            .map(|peer_state| peer_state.compact_block_stats.prefers_full_blocks())
+            .unwrap_or(false)
+    }
+
     pub fn getheaders_received(&self, _peer: PeerIndex) {
         // TODO:
     }
@@ -851,7 +980,7 @@ impl HeaderView {
         F: FnMut(&Byte32) -> Option<HeaderView>,
         G: Fn(BlockNumber, &HeaderView) -> Option<HeaderView>,
     {
-        let timestamp = unix_time_as_millis();
+        let started_at = Instant::now();
         let mut current = self;
         if number > current.number() {
             return None;
@@ -889,7 +1018,7 @@ impl HeaderView {
             "topic": "get_ancestor",
             "fields": {
                 "steps": steps,
-                "elapsed": unix_time_as_millis().saturating_sub(timestamp),
+                "elapsed": started_at.elapsed().as_millis() as u64,
                 "base_number": base_number,
                 "target_number": number,
                 "ancestor_number": current.number()
@@ -937,6 +1066,8 @@ type PendingCompactBlockMap = HashMap<
 pub struct SyncShared {
     shared: Shared,
     state: Arc<SyncState>,
+    // The blocks which are ancestors of it skip the script verification during IBD
+    assume_valid_target: Option<Byte32>,
 }
 
 impl SyncShared {
@@ -966,17 +1097,27 @@ impl SyncShared {
             orphan_block_pool: OrphanBlockPool::with_capacity(ORPHAN_BLOCK_SIZE),
             inflight_proposals: Mutex::new(HashSet::default()),
             inflight_transactions: Mutex::new(LruCache::new(TX_ASKED_SIZE)),
-            inflight_blocks: RwLock::new(InflightBlocks::default()),
+            inflight_blocks: RwLock::new(InflightBlocks::with_clock(Arc::clone(shared.clock()))),
             pending_get_headers: RwLock::new(LruCache::new(GET_HEADERS_CACHE_SIZE)),
             tx_hashes: Mutex::new(HashMap::default()),
+            tx_reconciliation: Mutex::new(TxReconciliationState::default()),
+            clock: Arc::clone(shared.clock()),
         };
 
         SyncShared {
             shared,
             state: Arc::new(state),
+            assume_valid_target: None,
         }
     }
 
+    /// Skips the script verification of the blocks which are ancestors of the `target` during
+    /// IBD, the scripts are verified later by `ScriptsBackfill`.
+    pub fn with_assume_valid_target(mut self, target: Option<Byte32>) -> Self {
+        self.assume_valid_target = target;
+        self
+    }
+
     pub fn shared(&self) -> &Shared {
         &self.shared
     }
@@ -1063,7 +1204,12 @@ impl SyncShared {
         peer: PeerIndex,
         block: Arc<core::BlockView>,
     ) -> Result<bool, FailureError> {
-        let ret = chain.process_block(Arc::clone(&block));
+        let switch = if self.is_assumed_valid(&block) {
+            Switch::DISABLE_SCRIPT
+        } else {
+            Switch::NONE
+        };
+        let ret = chain.internal_process_block(Arc::clone(&block), switch);
         if ret.is_err() {
             error!("accept block {:?} {:?}", block, ret);
             self.state
@@ -1085,6 +1231,19 @@ impl SyncShared {
         Ok(ret?)
     }
 
+    fn is_assumed_valid(&self, block: &core::BlockView) -> bool {
+        let target = match self.assume_valid_target {
+            Some(ref target) => target,
+            None => return false,
+        };
+        let active_chain = self.active_chain();
+        active_chain.is_initial_block_download()
+            && active_chain
+                .get_ancestor(target, block.number())
+                .map(|header| header.hash() == block.hash())
+                .unwrap_or(false)
+    }
+
     // Update the header_map
     // Update the block_status_map
     // Update the shared_best_header if need
@@ -1201,9 +1360,38 @@ pub struct SyncState {
 
     /* cached for sending bulk */
     tx_hashes: Mutex<HashMap<PeerIndex, LinkedHashSet<Byte32>>>,
+    /* the hashes to reconcile instead of flooding */
+    tx_reconciliation: Mutex<TxReconciliationState>,
+    clock: Arc<dyn Clock>,
 }
 
 impl SyncState {
+    /// Entries and estimated bytes of the header map, a header is shared by its heap buffer, which
+    /// also holds the header hash.
+    pub fn header_map_memory(&self) -> (usize, usize) {
+        let len = self.header_map.read().len();
+        let entry_size = mem::size_of::<Byte32>()
+            + mem::size_of::<HeaderView>()
+            + packed::Header::TOTAL_SIZE
+            + 32;
+        (len, len * entry_size)
+    }
+
+    /// Entries and estimated bytes of the block status map
+    pub fn block_status_map_memory(&self) -> (usize, usize) {
+        let len = self.block_status_map.lock().len();
+        let entry_size = mem::size_of::<Byte32>() + mem::size_of::<BlockStatus>() + 32;
+        (len, len * entry_size)
+    }
+
+    /// Blocks and serialized bytes of the orphan block pool
+    pub fn orphan_block_pool_memory(&self) -> (usize, usize) {
+        (
+            self.orphan_block_pool.len(),
+            self.orphan_block_pool.total_size(),
+        )
+    }
+
     pub fn n_sync_started(&self) -> &AtomicUsize {
         &self.n_sync_started
     }
@@ -1254,13 +1442,20 @@ impl SyncState {
         self.tx_hashes.lock()
     }
 
+    pub(crate) fn tx_reconciliation(&self) -> MutexGuard<TxReconciliationState> {
+        self.tx_reconciliation.lock()
+    }
+
     pub fn take_tx_hashes(&self) -> HashMap<PeerIndex, LinkedHashSet<Byte32>> {
         let mut map = self.tx_hashes.lock();
         mem::take(&mut *map)
     }
 
     pub fn is_initial_header_sync(&self) -> bool {
-        unix_time_as_millis().saturating_sub(self.shared_best_header().timestamp()) > MAX_TIP_AGE
+        self.clock
+            .unix_time_as_millis()
+            .saturating_sub(self.shared_best_header().timestamp())
+            > MAX_TIP_AGE
     }
 
     pub fn shared_best_header(&self) -> HeaderView {
@@ -1284,7 +1479,7 @@ impl SyncState {
     }
 
     pub(crate) fn suspend_sync(&self, peer_state: &mut PeerState) {
-        peer_state.suspend_sync(SUSPEND_SYNC_TIME);
+        peer_state.suspend_sync(self.clock.unix_time_as_millis(), SUSPEND_SYNC_TIME);
         assert_ne!(
             self.n_sync_started().fetch_sub(1, Ordering::Release),
             0,
@@ -1481,7 +1676,12 @@ impl ActiveChain {
         // Once this function has returned false, it must remain false.
         if self.state.ibd_finished.load(Ordering::Relaxed) {
             false
-        } else if unix_time_as_millis().saturating_sub(self.tip_header().timestamp()) > MAX_TIP_AGE
+        } else if self
+            .state
+            .clock
+            .unix_time_as_millis()
+            .saturating_sub(self.tip_header().timestamp())
+            > MAX_TIP_AGE
         {
             true
         } else {
@@ -1754,7 +1954,7 @@ impl Into<bool> for IBDState {
 
 #[cfg(test)]
 mod tests {
-    use super::HeaderView;
+    use super::{CompactBlockStats, HeaderView, COMPACT_BLOCK_STATS_WINDOW};
     use ckb_types::{
         core::{BlockNumber, HeaderBuilder},
         packed::Byte32,
@@ -1841,4 +2041,26 @@ mod tests {
             assert_eq!(found_0_header.hash(), view_0.hash());
         }
     }
+
+    #[test]
+    fn test_compact_block_stats_adapt_to_recent_hit_rate() {
+        let mut stats = CompactBlockStats::default();
+        stats.record_reconstruction(50, 0);
+        assert!(stats.recent_hit_rate().is_none());
+        assert!(!stats.prefers_full_blocks());
+
+        stats.record_reconstruction(50, 10);
+        stats.record_fallback(false);
+        assert!((stats.recent_hit_rate().unwrap() - 0.1).abs() < f64::EPSILON);
+        assert!(stats.prefers_full_blocks());
+
+        // the low hit rate is forgotten once the pool catches up
+        for _ in 0..COMPACT_BLOCK_STATS_WINDOW / 50 {
+            stats.record_reconstruction(50, 50);
+        }
+        assert!(!stats.prefers_full_blocks());
+        assert_eq!(stats.compact_blocks, 2 + COMPACT_BLOCK_STATS_WINDOW / 50);
+        assert_eq!(stats.fallbacks, 1);
+        assert_eq!(stats.collisions, 0);
+    }
 }
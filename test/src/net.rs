@@ -100,7 +100,9 @@ impl Net {
             public_addresses: vec![],
             bootnodes: vec![],
             dns_seeds: vec![],
+            dns_seeds_pubkey: None,
             whitelist_peers: vec![],
+            whitelist: Default::default(),
             whitelist_only: false,
             max_peers: self.num_nodes(),
             max_outbound_peers: self.num_nodes(),
@@ -111,7 +113,14 @@ impl Net {
             discovery_local_address: true,
             upnp: false,
             bootnode_mode: false,
+            light_client_server: false,
+            tx_reconciliation: false,
+            mempool_sync: false,
             max_send_buffer: None,
+            peer_scoring: Default::default(),
+            rate_limit: Default::default(),
+            dandelion: Default::default(),
+            identify: Default::default(),
         };
 
         let network_state =
@@ -227,6 +227,14 @@ impl RpcClient {
             .map(|x| x.pack())
     }
 
+    /// Freezes the clock of the node at `timestamp` in milliseconds, or restores the system
+    /// clock when it is `None`.
+    pub fn set_mock_time(&self, timestamp: Option<u64>) {
+        self.inner
+            .set_mock_time(timestamp.map(Into::into))
+            .expect("rpc call set_mock_time")
+    }
+
     pub fn get_live_cells_by_lock_hash(
         &self,
         lock_hash: Byte32,
@@ -364,6 +372,7 @@ jsonrpc!(pub struct Inner {
     pub fn add_node(&self, peer_id: String, address: String) -> ();
     pub fn remove_node(&self, peer_id: String) -> ();
     pub fn process_block_without_verify(&self, _data: Block) -> Option<H256>;
+    pub fn set_mock_time(&self, timestamp: Option<Timestamp>) -> ();
 
     pub fn get_live_cells_by_lock_hash(&self, lock_hash: H256, page: Uint64, per_page: Uint64, reverse_order: Option<bool>) -> Vec<LiveCell>;
     pub fn get_transactions_by_lock_hash(&self, lock_hash: H256, page: Uint64, per_page: Uint64, reverse_order: Option<bool>) -> Vec<CellTransaction>;
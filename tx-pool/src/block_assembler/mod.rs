@@ -1,4 +1,5 @@
 mod candidate_uncles;
+mod selection;
 
 use crate::component::entry::TxEntry;
 use crate::error::BlockAssemblerError as Error;
@@ -20,18 +21,33 @@ use ckb_types::{
 };
 use failure::Error as FailureError;
 use lru_cache::LruCache;
-use std::collections::HashSet;
+pub(crate) use selection::sanitize_selection;
+pub use selection::{
+    AncestorsScoreStrategy, Candidates, FifoStrategy, SelectionLimits, SelectionStrategies,
+    SelectionStrategy, DEFAULT_SELECTION_STRATEGY, FIFO_SELECTION_STRATEGY,
+};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::sync::{atomic::AtomicU64, Arc};
 use tokio::sync::Mutex;
 
 const BLOCK_TEMPLATE_TIMEOUT: u64 = 3000;
 const TEMPLATE_CACHE_SIZE: usize = 10;
 
+/// The parts of a template which only depend on the tip and the uncles, they are reused to
+/// rebuild the template when only the transactions changed.
+#[derive(Clone)]
+pub struct TemplateBase {
+    pub cellbase: TransactionView,
+    pub uncles: Vec<UncleBlockView>,
+    pub current_epoch: EpochExt,
+    pub uncles_updated_at: u64,
+}
+
 pub struct TemplateCache {
     pub time: u64,
-    pub uncles_updated_at: u64,
     pub txs_updated_at: u64,
     pub template: BlockTemplate,
+    pub base: TemplateBase,
 }
 
 impl TemplateCache {
@@ -40,12 +56,13 @@ impl TemplateCache {
     }
 
     pub fn is_modified(&self, last_uncles_updated_at: u64, last_txs_updated_at: u64) -> bool {
-        last_uncles_updated_at != self.uncles_updated_at
+        last_uncles_updated_at != self.base.uncles_updated_at
             || last_txs_updated_at != self.txs_updated_at
     }
 }
 
-pub type BlockTemplateCacheKey = (Byte32, Cycle, u64, Version);
+/// The tip hash, the bytes limit, the proposals limit, the version and the selection strategy.
+pub type BlockTemplateCacheKey = (Byte32, Cycle, u64, Version, String);
 
 #[derive(Clone)]
 pub struct BlockAssembler {
@@ -121,10 +138,38 @@ impl BlockAssembler {
             required,
             cycles: Some(tx.cycles.into()),
             depends: depends.map(|deps| deps.into_iter().map(|x| u64::from(x).into()).collect()),
+            fee: Some(tx.fee.into()),
             data: tx.transaction.data().into(),
         }
     }
 
+    /// Transforms the selected txs, ordered parents first, with the indices of their ancestors
+    /// among them, so that the pool software trimming a tx can trim its descendants too.
+    pub(crate) fn transform_txs(entries: &[TxEntry]) -> Vec<TransactionTemplate> {
+        let mut indices: HashMap<Byte32, u32> = HashMap::with_capacity(entries.len());
+        let mut ancestors: Vec<BTreeSet<u32>> = Vec::with_capacity(entries.len());
+        entries
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| {
+                let mut depends = BTreeSet::new();
+                let parents = entry
+                    .related_out_points
+                    .iter()
+                    .filter_map(|out_point| indices.get(&out_point.tx_hash()).cloned());
+                for parent in parents {
+                    depends.insert(parent);
+                    depends.extend(ancestors[parent as usize].iter().cloned());
+                }
+                indices.insert(entry.transaction.hash(), index as u32);
+                let template =
+                    Self::transform_tx(entry, false, Some(depends.iter().cloned().collect()));
+                ancestors.push(depends);
+                template
+            })
+            .collect()
+    }
+
     pub(crate) fn calculate_txs_size_limit(
         bytes_limit: u64,
         cellbase: Transaction,
@@ -233,3 +278,62 @@ impl BlockAssembler {
         uncles
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ckb_types::{core::cell::get_related_dep_out_points, packed::OutPoint};
+
+    fn build_entry(inputs: &[&Byte32], fee: u64) -> TxEntry {
+        let tx = inputs
+            .iter()
+            .fold(TransactionBuilder::default(), |builder, input| {
+                builder.input(CellInput::new(OutPoint::new((*input).to_owned(), 0), 0))
+            })
+            .output(
+                CellOutput::new_builder()
+                    .capacity(Capacity::bytes(1).unwrap().pack())
+                    .build(),
+            )
+            .output_data(Bytes::new().pack())
+            .build();
+        let related_dep_out_points = get_related_dep_out_points(&tx, |_| None).unwrap();
+        TxEntry::new(
+            tx,
+            100,
+            Capacity::shannons(fee),
+            100,
+            related_dep_out_points,
+        )
+    }
+
+    #[test]
+    fn test_transform_txs() {
+        let a = build_entry(&[&Byte32::zero()], 100);
+        let b = build_entry(&[&a.transaction.hash()], 200);
+        let c = build_entry(&[&[1u8; 32].pack()], 300);
+        let d = build_entry(&[&b.transaction.hash(), &c.transaction.hash()], 400);
+        let templates = BlockAssembler::transform_txs(&[a, b, c, d]);
+
+        let depends: Vec<Vec<u64>> = templates
+            .iter()
+            .map(|template| {
+                template
+                    .depends
+                    .as_ref()
+                    .unwrap()
+                    .iter()
+                    .map(|index| index.value())
+                    .collect()
+            })
+            .collect();
+        assert_eq!(depends, vec![vec![], vec![0], vec![], vec![0, 1, 2]]);
+        assert_eq!(
+            templates
+                .iter()
+                .map(|template| template.fee.unwrap().value())
+                .collect::<Vec<_>>(),
+            vec![100, 200, 300, 400]
+        );
+    }
+}
@@ -0,0 +1,304 @@
+//! The strategies selecting the proposed transactions committed by the block templates.
+//!
+//! A strategy is chosen per `get_block_template` request by its name, the built-in ones are
+//! `ancestors_score`, the default, and `fifo`. Others, including closures, can be registered with
+//! `TxPoolController::register_selection_strategy`, so the inclusion policy can be changed without
+//! changing the assembler. Whatever a strategy returns, the assembler only keeps the transactions
+//! which follow their in-pool ancestors and fit in the limits.
+use crate::component::commit_txs_scanner::CommitTxsScanner;
+use crate::component::entry::TxEntry;
+use crate::component::proposed::ProposedPool;
+use ckb_fee_estimator::FeeRate;
+use ckb_types::{core::Cycle, packed::ProposalShortId};
+use ckb_util::RwLock;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// The name of the default strategy.
+pub const DEFAULT_SELECTION_STRATEGY: &str = "ancestors_score";
+/// The name of the first in first out strategy.
+pub const FIFO_SELECTION_STRATEGY: &str = "fifo";
+
+// Give up packaging after so many consecutive packages not fitting in the limits
+const MAX_CONSECUTIVE_FAILED: usize = 500;
+
+/// The proposed transactions which can be committed.
+pub struct Candidates<'a> {
+    proposed: &'a ProposedPool,
+}
+
+impl<'a> Candidates<'a> {
+    pub(crate) fn new(proposed: &'a ProposedPool) -> Self {
+        Candidates { proposed }
+    }
+
+    /// All the candidates, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = &'a TxEntry> {
+        self.proposed.entries()
+    }
+
+    pub fn get(&self, id: &ProposalShortId) -> Option<&'a TxEntry> {
+        self.proposed.get(id)
+    }
+
+    /// The in-pool ancestors of the candidate, which must be committed before it.
+    pub fn ancestors(&self, id: &ProposalShortId) -> HashSet<ProposalShortId> {
+        self.proposed.get_ancestors(id)
+    }
+}
+
+/// The limits of the committed transactions.
+#[derive(Clone, Copy, Debug)]
+pub struct SelectionLimits {
+    pub size: usize,
+    pub cycles: Cycle,
+    /// The fee rate of every transaction package, the transaction and its uncommitted ancestors
+    pub min_fee_rate: FeeRate,
+}
+
+/// Selects the transactions to commit, in the order of the commitment.
+pub trait SelectionStrategy: Send + Sync {
+    fn select(&self, candidates: &Candidates, limits: &SelectionLimits) -> Vec<TxEntry>;
+}
+
+impl<F> SelectionStrategy for F
+where
+    F: Fn(&Candidates, &SelectionLimits) -> Vec<TxEntry> + Send + Sync,
+{
+    fn select(&self, candidates: &Candidates, limits: &SelectionLimits) -> Vec<TxEntry> {
+        self(candidates, limits)
+    }
+}
+
+/// Packages the transactions with the highest fee rates including their ancestors first.
+pub struct AncestorsScoreStrategy;
+
+impl SelectionStrategy for AncestorsScoreStrategy {
+    fn select(&self, candidates: &Candidates, limits: &SelectionLimits) -> Vec<TxEntry> {
+        CommitTxsScanner::new(candidates.proposed)
+            .txs_to_commit(limits.size, limits.cycles, limits.min_fee_rate)
+            .0
+    }
+}
+
+/// Packages the transactions in the order they entered the pool, regardless of the fees above
+/// the minimum fee rate.
+pub struct FifoStrategy;
+
+impl SelectionStrategy for FifoStrategy {
+    fn select(&self, candidates: &Candidates, limits: &SelectionLimits) -> Vec<TxEntry> {
+        let mut entries: Vec<&TxEntry> = candidates.iter().collect();
+        entries.sort_by_key(|entry| (entry.timestamp, entry.ancestors_count));
+
+        let mut fetched: HashSet<ProposalShortId> = HashSet::new();
+        let mut selected = Vec::new();
+        let (mut size, mut cycles) = (0usize, 0 as Cycle);
+        let mut consecutive_failed = 0;
+        for entry in entries {
+            let id = entry.transaction.proposal_short_id();
+            if fetched.contains(&id) {
+                continue;
+            }
+            // the entry with its uncommitted ancestors
+            let mut package: Vec<&TxEntry> = candidates
+                .ancestors(&id)
+                .iter()
+                .filter(|ancestor| !fetched.contains(ancestor))
+                .filter_map(|ancestor| candidates.get(ancestor))
+                .collect();
+            package.push(entry);
+            let package_size: usize = package.iter().map(|entry| entry.size).sum();
+            let package_cycles: Cycle = package.iter().map(|entry| entry.cycles).sum();
+            let package_fee = package.iter().map(|entry| entry.fee.as_u64()).sum::<u64>();
+            if package_fee < limits.min_fee_rate.fee(package_size).as_u64() {
+                continue;
+            }
+            if size.saturating_add(package_size) > limits.size
+                || cycles.saturating_add(package_cycles) > limits.cycles
+            {
+                consecutive_failed += 1;
+                if consecutive_failed > MAX_CONSECUTIVE_FAILED {
+                    break;
+                }
+                continue;
+            }
+            consecutive_failed = 0;
+            size += package_size;
+            cycles += package_cycles;
+            // if A is an ancestor of B, B.ancestors_count must be larger than A
+            package.sort_by_key(|entry| entry.ancestors_count);
+            for entry in package {
+                fetched.insert(entry.transaction.proposal_short_id());
+                selected.push(entry.to_owned());
+            }
+        }
+        selected
+    }
+}
+
+/// Keeps the selected entries which follow their in-pool ancestors and fit in the limits, returns
+/// them with their total size and cycles.
+pub(crate) fn sanitize_selection(
+    candidates: &Candidates,
+    limits: &SelectionLimits,
+    selected: Vec<TxEntry>,
+) -> (Vec<TxEntry>, usize, Cycle) {
+    let mut fetched: HashSet<ProposalShortId> = HashSet::with_capacity(selected.len());
+    let mut entries = Vec::with_capacity(selected.len());
+    let (mut size, mut cycles) = (0usize, 0 as Cycle);
+    for entry in selected {
+        let id = entry.transaction.proposal_short_id();
+        if fetched.contains(&id)
+            || candidates.get(&id).is_none()
+            || !candidates
+                .ancestors(&id)
+                .iter()
+                .all(|ancestor| fetched.contains(ancestor))
+            || size.saturating_add(entry.size) > limits.size
+            || cycles.saturating_add(entry.cycles) > limits.cycles
+        {
+            continue;
+        }
+        fetched.insert(id);
+        size += entry.size;
+        cycles += entry.cycles;
+        entries.push(entry);
+    }
+    (entries, size, cycles)
+}
+
+/// The registered strategies by their names.
+#[derive(Clone)]
+pub struct SelectionStrategies {
+    inner: Arc<RwLock<HashMap<String, Arc<dyn SelectionStrategy>>>>,
+}
+
+impl Default for SelectionStrategies {
+    fn default() -> Self {
+        let strategies = SelectionStrategies {
+            inner: Default::default(),
+        };
+        strategies.register(
+            DEFAULT_SELECTION_STRATEGY.to_owned(),
+            Arc::new(AncestorsScoreStrategy),
+        );
+        strategies.register(FIFO_SELECTION_STRATEGY.to_owned(), Arc::new(FifoStrategy));
+        strategies
+    }
+}
+
+impl SelectionStrategies {
+    /// Registers the strategy, replaces the one of the same name if any.
+    pub fn register(&self, name: String, strategy: Arc<dyn SelectionStrategy>) {
+        self.inner.write().insert(name, strategy);
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<dyn SelectionStrategy>> {
+        self.inner.read().get(name).cloned()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.inner.read().contains_key(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::component::container::PackageLimits;
+    use ckb_types::{
+        bytes::Bytes,
+        core::{cell::get_related_dep_out_points, Capacity, TransactionBuilder, TransactionView},
+        packed::{Byte32, CellInput, CellOutput, OutPoint},
+        prelude::*,
+    };
+
+    fn build_tx(input: &Byte32, index: u32) -> TransactionView {
+        TransactionBuilder::default()
+            .input(CellInput::new(OutPoint::new(input.to_owned(), index), 0))
+            .output(
+                CellOutput::new_builder()
+                    .capacity(Capacity::bytes(1).unwrap().pack())
+                    .build(),
+            )
+            .output_data(Bytes::new().pack())
+            .build()
+    }
+
+    fn build_entry(tx: &TransactionView, timestamp: u64) -> TxEntry {
+        let mut entry = TxEntry::new(
+            tx.clone(),
+            100,
+            Capacity::shannons(1000),
+            100,
+            get_related_dep_out_points(tx, |_| None).unwrap(),
+        );
+        entry.timestamp = timestamp;
+        entry
+    }
+
+    fn limits(size: usize) -> SelectionLimits {
+        SelectionLimits {
+            size,
+            cycles: 1_000,
+            min_fee_rate: FeeRate::zero(),
+        }
+    }
+
+    fn ids(entries: &[TxEntry]) -> Vec<ProposalShortId> {
+        entries
+            .iter()
+            .map(|entry| entry.transaction.proposal_short_id())
+            .collect()
+    }
+
+    #[test]
+    fn test_fifo_strategy() {
+        let parent = build_tx(&Byte32::zero(), 0);
+        let child = build_tx(&parent.hash(), 0);
+        let other = build_tx(&Byte32::zero(), 1);
+
+        let mut pool = ProposedPool::new(PackageLimits::new(25));
+        // the child entered earlier than its parent, e.g. after a reorg
+        pool.add_entry(build_entry(&parent, 3)).unwrap();
+        pool.add_entry(build_entry(&child, 1)).unwrap();
+        pool.add_entry(build_entry(&other, 2)).unwrap();
+        let candidates = Candidates::new(&pool);
+
+        let selected = FifoStrategy.select(&candidates, &limits(1_000));
+        assert_eq!(
+            ids(&selected),
+            vec![
+                parent.proposal_short_id(),
+                child.proposal_short_id(),
+                other.proposal_short_id(),
+            ]
+        );
+
+        // the package of the child doesn't fit, the later tx still does
+        let selected = FifoStrategy.select(&candidates, &limits(150));
+        assert_eq!(ids(&selected), vec![other.proposal_short_id()]);
+    }
+
+    #[test]
+    fn test_sanitize_selection() {
+        let parent = build_tx(&Byte32::zero(), 0);
+        let child = build_tx(&parent.hash(), 0);
+        let unknown = build_tx(&Byte32::zero(), 1);
+
+        let mut pool = ProposedPool::new(PackageLimits::new(25));
+        pool.add_entry(build_entry(&parent, 0)).unwrap();
+        pool.add_entry(build_entry(&child, 0)).unwrap();
+        let candidates = Candidates::new(&pool);
+
+        let selected = vec![
+            build_entry(&child, 0),
+            build_entry(&parent, 0),
+            build_entry(&parent, 0),
+            build_entry(&unknown, 0),
+        ];
+        let (entries, size, cycles) = sanitize_selection(&candidates, &limits(1_000), selected);
+        assert_eq!(ids(&entries), vec![parent.proposal_short_id()]);
+        assert_eq!((size, cycles), (100, 100));
+    }
+}
@@ -2,6 +2,7 @@
 //! and its top-level members.
 
 use crate::{component::entry::TxEntry, error::SubmitTxError};
+use ckb_app_config::TxPoolConfig;
 use ckb_types::{core::Capacity, packed::ProposalShortId};
 use std::cmp::Ordering;
 use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
@@ -126,22 +127,59 @@ impl TxLink {
     }
 }
 
+/// Limits of the package of an entry, the counts and sizes include the entry itself. The size
+/// limits are in bytes, 0 means unlimited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PackageLimits {
+    pub max_ancestors_count: usize,
+    pub max_ancestors_size: usize,
+    pub max_descendants_count: usize,
+    pub max_descendants_size: usize,
+}
+
+impl PackageLimits {
+    /// Limits the ancestors count only.
+    pub fn new(max_ancestors_count: usize) -> Self {
+        PackageLimits {
+            max_ancestors_count,
+            max_ancestors_size: 0,
+            max_descendants_count: 0,
+            max_descendants_size: 0,
+        }
+    }
+}
+
+impl From<&TxPoolConfig> for PackageLimits {
+    fn from(config: &TxPoolConfig) -> Self {
+        PackageLimits {
+            max_ancestors_count: config.max_ancestors_count,
+            max_ancestors_size: config.max_ancestors_size,
+            max_descendants_count: config.max_descendants_count,
+            max_descendants_size: config.max_descendants_size,
+        }
+    }
+}
+
+fn exceeds(value: usize, limit: usize) -> bool {
+    limit != 0 && value > limit
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct SortedTxMap {
     entries: HashMap<ProposalShortId, TxEntry>,
     sorted_index: BTreeSet<AncestorsScoreSortKey>,
     /// A map track transaction ancestors and descendants
     links: HashMap<ProposalShortId, TxLink>,
-    max_ancestors_count: usize,
+    limits: PackageLimits,
 }
 
 impl SortedTxMap {
-    pub fn new(max_ancestors_count: usize) -> Self {
+    pub fn new(limits: PackageLimits) -> Self {
         SortedTxMap {
             entries: Default::default(),
             sorted_index: Default::default(),
             links: Default::default(),
-            max_ancestors_count,
+            limits,
         }
     }
 
@@ -184,9 +222,31 @@ impl SortedTxMap {
         // update ancestor_fields
         self.update_ancestors_stat_for_entry(&mut entry, &parents);
 
-        if entry.ancestors_count > self.max_ancestors_count {
+        if entry.ancestors_count > self.limits.max_ancestors_count {
             return Err(SubmitTxError::ExceededMaximumAncestorsCount);
         }
+        if exceeds(entry.ancestors_size, self.limits.max_ancestors_size) {
+            return Err(SubmitTxError::ExceededMaximumAncestorsSize);
+        }
+        let mut ancestors = parents.clone();
+        for parent_id in &parents {
+            ancestors.extend(self.get_ancestors(parent_id));
+        }
+        for ancestor_id in &ancestors {
+            let ancestor = self.entries.get(ancestor_id).expect("pool consistent");
+            if exceeds(
+                ancestor.descendants_count + 1,
+                self.limits.max_descendants_count,
+            ) {
+                return Err(SubmitTxError::ExceededMaximumDescendantsCount);
+            }
+            if exceeds(
+                ancestor.descendants_size + entry.size,
+                self.limits.max_descendants_size,
+            ) {
+                return Err(SubmitTxError::ExceededMaximumDescendantsSize);
+            }
+        }
 
         // check duplicate tx
         let removed_entry = if self.contains_key(&short_id) {
@@ -195,6 +255,12 @@ impl SortedTxMap {
             None
         };
 
+        // update descendant fields of ancestors
+        for ancestor_id in &ancestors {
+            if let Some(ancestor) = self.entries.get_mut(ancestor_id) {
+                ancestor.add_descendant_weight(&entry);
+            }
+        }
         // update parents references
         for parent_id in &parents {
             self.links
@@ -226,6 +292,7 @@ impl SortedTxMap {
     }
 
     pub fn remove_entry_and_descendants(&mut self, id: &ProposalShortId) -> Vec<TxEntry> {
+        self.sub_descendants_weight_of_ancestors(id);
         let mut queue = VecDeque::new();
         let mut removed = Vec::new();
         let tx_link = self.links.get(&id).map(ToOwned::to_owned);
@@ -253,7 +320,36 @@ impl SortedTxMap {
         removed
     }
 
+    /// Subtracts the entry and its descendants, which are about to be removed, from the
+    /// descendant fields of the remaining ancestors.
+    fn sub_descendants_weight_of_ancestors(&mut self, id: &ProposalShortId) {
+        let mut removing = self.get_descendants(id);
+        removing.insert(id.clone());
+        for removing_id in &removing {
+            let removing_entry = match self.entries.get(removing_id) {
+                Some(entry) => entry.clone(),
+                None => continue,
+            };
+            for ancestor_id in self.get_ancestors(removing_id) {
+                if removing.contains(&ancestor_id) {
+                    continue;
+                }
+                if let Some(ancestor) = self.entries.get_mut(&ancestor_id) {
+                    ancestor.sub_descendant_weight(&removing_entry);
+                }
+            }
+        }
+    }
+
     pub fn remove_entry(&mut self, id: &ProposalShortId) -> Option<TxEntry> {
+        if let Some(entry) = self.entries.get(id).cloned() {
+            // update ancestors entries
+            for ancestor_id in self.get_ancestors(id) {
+                if let Some(ancestor) = self.entries.get_mut(&ancestor_id) {
+                    ancestor.sub_descendant_weight(&entry);
+                }
+            }
+        }
         self.entries.remove(&id).map(|entry| {
             let deleted = self
                 .sorted_index
@@ -312,6 +408,11 @@ impl SortedTxMap {
         self.sorted_index.iter().rev()
     }
 
+    /// return entries in no particular order
+    pub fn entries(&self) -> impl Iterator<Item = &TxEntry> {
+        self.entries.values()
+    }
+
     /// return keys sorted by tx fee rate and transaction relation
     pub fn keys_sorted_by_fee_and_relation(&self) -> Vec<&AncestorsScoreSortKey> {
         let mut keys: Vec<_> = self.keys_sorted_by_fee().collect();
@@ -423,7 +524,7 @@ mod tests {
 
     #[test]
     fn test_sorted_tx_map_with_conflict_tx_hash() {
-        let mut map = SortedTxMap::new(DEFAULT_MAX_ANCESTORS_SIZE);
+        let mut map = SortedTxMap::new(PackageLimits::new(DEFAULT_MAX_ANCESTORS_SIZE));
         let tx1 = TxEntry::new(
             TransactionBuilder::default().build(),
             100,
@@ -469,7 +570,7 @@ mod tests {
 
     #[test]
     fn test_remove_entry_and_descendants() {
-        let mut map = SortedTxMap::new(DEFAULT_MAX_ANCESTORS_SIZE);
+        let mut map = SortedTxMap::new(PackageLimits::new(DEFAULT_MAX_ANCESTORS_SIZE));
         let tx1 = TxEntry::new(
             TransactionBuilder::default().build(),
             100,
@@ -531,4 +632,67 @@ mod tests {
         assert!(!descendants_map.contains(&tx2_id));
         assert!(!descendants_map.contains(&tx3_id));
     }
+
+    #[test]
+    fn test_package_stats_and_limits() {
+        let child_of = |parent: &TxEntry, size: usize| {
+            TxEntry::new(
+                TransactionBuilder::default()
+                    .input(CellInput::new(
+                        OutPoint::new(parent.transaction.hash(), 0),
+                        0,
+                    ))
+                    .build(),
+                100,
+                Capacity::shannons(100),
+                size,
+                Default::default(),
+            )
+        };
+        let limits = PackageLimits {
+            max_descendants_count: 3,
+            max_descendants_size: 350,
+            ..PackageLimits::new(DEFAULT_MAX_ANCESTORS_SIZE)
+        };
+        let mut map = SortedTxMap::new(limits);
+        let tx1 = TxEntry::new(
+            TransactionBuilder::default().build(),
+            100,
+            Capacity::shannons(100),
+            100,
+            Default::default(),
+        );
+        let tx2 = child_of(&tx1, 100);
+        let tx3 = child_of(&tx2, 100);
+        let tx1_id = tx1.transaction.proposal_short_id();
+        let tx2_id = tx2.transaction.proposal_short_id();
+        map.add_entry(tx1.clone()).unwrap();
+        map.add_entry(tx2.clone()).unwrap();
+        map.add_entry(tx3.clone()).unwrap();
+
+        let info = map.get(&tx1_id).unwrap().package_info();
+        assert_eq!(info.descendants_count, 3);
+        assert_eq!(info.descendants_size, 300);
+        assert_eq!(info.descendants_fee, Capacity::shannons(300));
+        assert_eq!(map.get(&tx2_id).unwrap().ancestors_count, 2);
+
+        // tx1 would have 4 descendants
+        assert_eq!(
+            map.add_entry(child_of(&tx3, 10)),
+            Err(SubmitTxError::ExceededMaximumDescendantsCount)
+        );
+        map.remove_entry_and_descendants(&tx3.transaction.proposal_short_id());
+        assert_eq!(map.get(&tx1_id).unwrap().descendants_count, 2);
+        // tx1 would have 400 bytes of descendants
+        assert_eq!(
+            map.add_entry(child_of(&tx2, 200)),
+            Err(SubmitTxError::ExceededMaximumDescendantsSize)
+        );
+        map.add_entry(child_of(&tx2, 150)).unwrap();
+
+        map.remove_entry(&tx2_id);
+        let info = map.get(&tx1_id).unwrap().package_info();
+        assert_eq!(info.descendants_count, 2);
+        assert_eq!(info.descendants_size, 250);
+    }
 }
@@ -1,5 +1,6 @@
 use crate::component::container::AncestorsScoreSortKey;
 use crate::component::get_transaction_virtual_bytes;
+use ckb_fee_estimator::FeeRate;
 use ckb_types::{
     core::{Capacity, Cycle, TransactionView},
     packed::{OutPoint, ProposalShortId},
@@ -20,7 +21,7 @@ pub struct DefectEntry {
     pub cache_entry: Option<CacheEntry>,
     /// tx size
     pub size: usize,
-    // timestamp
+    // unix time in seconds the entry was created
     pub timestamp: u64,
 }
 
@@ -31,13 +32,14 @@ impl DefectEntry {
         refs_count: usize,
         cache_entry: Option<CacheEntry>,
         size: usize,
+        timestamp: u64,
     ) -> DefectEntry {
         DefectEntry {
             transaction: tx,
             refs_count,
             cache_entry,
             size,
-            timestamp: faketime::unix_time().as_secs(),
+            timestamp,
         }
     }
 }
@@ -61,8 +63,19 @@ pub struct TxEntry {
     pub ancestors_cycles: Cycle,
     /// ancestors txs count
     pub ancestors_count: usize,
+    /// descendants txs size
+    pub descendants_size: usize,
+    /// descendants txs fee
+    pub descendants_fee: Capacity,
+    /// descendants txs cycles
+    pub descendants_cycles: Cycle,
+    /// descendants txs count
+    pub descendants_count: usize,
     /// related out points (cell deps includes cell group itself)
     pub related_out_points: Vec<OutPoint>,
+    /// unix time in milliseconds the entry was created, the order the first in first out
+    /// selection packages the txs in. The pool sets it by `with_timestamp`, it is 0 otherwise
+    pub timestamp: u64,
 }
 
 impl TxEntry {
@@ -83,10 +96,21 @@ impl TxEntry {
             ancestors_fee: fee,
             ancestors_cycles: cycles,
             ancestors_count: 1,
+            descendants_size: size,
+            descendants_fee: fee,
+            descendants_cycles: cycles,
+            descendants_count: 1,
             related_out_points,
+            timestamp: 0,
         }
     }
 
+    /// Sets the unix time in milliseconds the entry was created
+    pub fn with_timestamp(mut self, timestamp: u64) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
     pub fn as_sorted_key(&self) -> AncestorsScoreSortKey {
         AncestorsScoreSortKey::from(self)
     }
@@ -132,6 +156,59 @@ impl TxEntry {
                 .saturating_sub(entry.ancestors_fee.as_u64()),
         );
     }
+
+    pub fn add_descendant_weight(&mut self, entry: &TxEntry) {
+        self.descendants_count = self.descendants_count.saturating_add(1);
+        self.descendants_size = self.descendants_size.saturating_add(entry.size);
+        self.descendants_cycles = self.descendants_cycles.saturating_add(entry.cycles);
+        self.descendants_fee = Capacity::shannons(
+            self.descendants_fee
+                .as_u64()
+                .saturating_add(entry.fee.as_u64()),
+        );
+    }
+    pub fn sub_descendant_weight(&mut self, entry: &TxEntry) {
+        self.descendants_count = self.descendants_count.saturating_sub(1);
+        self.descendants_size = self.descendants_size.saturating_sub(entry.size);
+        self.descendants_cycles = self.descendants_cycles.saturating_sub(entry.cycles);
+        self.descendants_fee = Capacity::shannons(
+            self.descendants_fee
+                .as_u64()
+                .saturating_sub(entry.fee.as_u64()),
+        );
+    }
+
+    /// The fee rate of the entry with its descendants, which are evicted together.
+    pub fn descendants_fee_rate(&self) -> FeeRate {
+        let vbytes = get_transaction_virtual_bytes(self.descendants_size, self.descendants_cycles);
+        FeeRate::calculate(self.descendants_fee, vbytes as usize)
+    }
+
+    pub fn package_info(&self) -> TxPackageInfo {
+        TxPackageInfo {
+            ancestors_count: self.ancestors_count,
+            ancestors_size: self.ancestors_size,
+            ancestors_cycles: self.ancestors_cycles,
+            ancestors_fee: self.ancestors_fee,
+            descendants_count: self.descendants_count,
+            descendants_size: self.descendants_size,
+            descendants_cycles: self.descendants_cycles,
+            descendants_fee: self.descendants_fee,
+        }
+    }
+}
+
+/// The package stats of an entry, both the ancestors and the descendants include the entry itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TxPackageInfo {
+    pub ancestors_count: usize,
+    pub ancestors_size: usize,
+    pub ancestors_cycles: Cycle,
+    pub ancestors_fee: Capacity,
+    pub descendants_count: usize,
+    pub descendants_size: usize,
+    pub descendants_cycles: Cycle,
+    pub descendants_fee: Capacity,
 }
 
 impl From<&TxEntry> for AncestorsScoreSortKey {
@@ -6,7 +6,7 @@ pub(crate) mod orphan;
 pub(crate) mod pending;
 pub(crate) mod proposed;
 
-pub use self::entry::{DefectEntry, TxEntry};
+pub use self::entry::{DefectEntry, TxEntry, TxPackageInfo};
 
 const DEFAULT_BYTES_PER_CYCLES: f64 = 0.000_17f64;
 
@@ -4,6 +4,7 @@ use ckb_types::{
     packed::{OutPoint, ProposalShortId},
 };
 use ckb_verification::cache::CacheEntry;
+use std::cmp;
 use std::collections::VecDeque;
 use std::collections::{hash_map, HashMap};
 use std::iter::ExactSizeIterator;
@@ -17,16 +18,37 @@ pub(crate) struct OrphanPool {
     pub(crate) vertices: HashMap<ProposalShortId, DefectEntry>,
     pub(crate) edges: HashMap<OutPoint, Vec<ProposalShortId>>,
     pub(crate) prune_threshold: usize,
+    /// Max count of the orphans, the oldest are evicted beyond it, 0 means unlimited
+    pub(crate) max_txs: usize,
+    /// Seconds before an orphan expires
+    pub(crate) ttl: u64,
 }
 
 impl OrphanPool {
+    #[cfg(test)]
     pub(crate) fn new() -> Self {
         OrphanPool::raw_new(PRUNE_THRESHOLD)
     }
 
+    #[cfg(test)]
     pub(crate) fn raw_new(prune_threshold: usize) -> Self {
         OrphanPool {
             prune_threshold,
+            ttl: TTL,
+            ..Default::default()
+        }
+    }
+
+    /// `ttl` 0 means the default `TTL`.
+    pub(crate) fn with_limits(max_txs: usize, ttl: u64) -> Self {
+        OrphanPool {
+            prune_threshold: if max_txs == 0 {
+                PRUNE_THRESHOLD
+            } else {
+                cmp::min(max_txs, PRUNE_THRESHOLD)
+            },
+            max_txs,
+            ttl: if ttl == 0 { TTL } else { ttl },
             ..Default::default()
         }
     }
@@ -35,6 +57,10 @@ impl OrphanPool {
         self.vertices.len()
     }
 
+    pub(crate) fn total_tx_size(&self) -> usize {
+        self.vertices.values().map(|entry| entry.size).sum()
+    }
+
     pub(crate) fn get(&self, id: &ProposalShortId) -> Option<&DefectEntry> {
         self.vertices.get(id)
     }
@@ -52,17 +78,19 @@ impl OrphanPool {
         self.vertices.contains_key(id)
     }
 
-    /// add orphan transaction
+    /// add orphan transaction, `now` is the unix time in seconds
     pub(crate) fn add_tx(
         &mut self,
         cache_entry: Option<CacheEntry>,
         size: usize,
         tx: TransactionView,
         unknown: impl ExactSizeIterator<Item = OutPoint>,
+        now: u64,
     ) -> Option<DefectEntry> {
-        self.prune();
+        self.prune(now);
+        self.evict_oldest();
         let short_id = tx.proposal_short_id();
-        let entry = DefectEntry::new(tx, unknown.len(), cache_entry, size);
+        let entry = DefectEntry::new(tx, unknown.len(), cache_entry, size, now);
         for out_point in unknown {
             let edge = self.edges.entry(out_point).or_insert_with(Vec::new);
             edge.push(short_id.clone());
@@ -70,17 +98,16 @@ impl OrphanPool {
         self.vertices.insert(short_id, entry)
     }
 
-    fn prune(&mut self) {
+    fn prune(&mut self, now: u64) {
         if self.size() < self.prune_threshold {
             return;
         }
 
-        let now = faketime::unix_time().as_secs();
         let ids: Vec<ProposalShortId> = self
             .vertices
             .iter()
             .filter_map(|(id, entry)| {
-                if entry.timestamp + TTL < now {
+                if entry.timestamp + self.ttl < now {
                     Some(id)
                 } else {
                     None
@@ -94,6 +121,21 @@ impl OrphanPool {
         }
     }
 
+    // Makes room for a new orphan, the descendants of the evicted are evicted as well.
+    fn evict_oldest(&mut self) {
+        while self.max_txs != 0 && self.size() >= self.max_txs {
+            let oldest = self
+                .vertices
+                .iter()
+                .min_by_key(|(_, entry)| entry.timestamp)
+                .map(|(id, _)| id.clone());
+            match oldest {
+                Some(id) => self.recursion_remove(&id),
+                None => break,
+            }
+        }
+    }
+
     pub(crate) fn recursion_remove(&mut self, id: &ProposalShortId) {
         let mut queue: VecDeque<ProposalShortId> = VecDeque::new();
         queue.push_back(id.clone());
@@ -201,16 +243,41 @@ mod tests {
 
         let tx6 = build_tx(vec![(&tx5_hash, 0)], 1);
 
-        pool.add_tx(None, MOCK_SIZE, tx2.clone(), tx1.output_pts().into_iter());
-        pool.add_tx(None, MOCK_SIZE, tx3.clone(), tx2.output_pts().into_iter());
-        pool.add_tx(None, MOCK_SIZE, tx4.clone(), tx3.output_pts().into_iter());
+        pool.add_tx(
+            None,
+            MOCK_SIZE,
+            tx2.clone(),
+            tx1.output_pts().into_iter(),
+            0,
+        );
+        pool.add_tx(
+            None,
+            MOCK_SIZE,
+            tx3.clone(),
+            tx2.output_pts().into_iter(),
+            0,
+        );
+        pool.add_tx(
+            None,
+            MOCK_SIZE,
+            tx4.clone(),
+            tx3.output_pts().into_iter(),
+            0,
+        );
         pool.add_tx(
             None,
             MOCK_SIZE,
             tx5.clone(),
             tx1.inputs().into_iter().map(|x| x.previous_output()),
+            0,
+        );
+        pool.add_tx(
+            None,
+            MOCK_SIZE,
+            tx6.clone(),
+            tx5.output_pts().into_iter(),
+            0,
         );
-        pool.add_tx(None, MOCK_SIZE, tx6.clone(), tx5.output_pts().into_iter());
 
         assert!(pool.contains(&tx2));
         assert!(pool.contains(&tx3));
@@ -245,8 +312,20 @@ mod tests {
 
         let tx4 = build_tx(vec![(&tx3_hash, 0)], 1);
 
-        pool.add_tx(None, MOCK_SIZE, tx3.clone(), tx2.output_pts().into_iter());
-        pool.add_tx(None, MOCK_SIZE, tx4.clone(), tx3.output_pts().into_iter());
+        pool.add_tx(
+            None,
+            MOCK_SIZE,
+            tx3.clone(),
+            tx2.output_pts().into_iter(),
+            0,
+        );
+        pool.add_tx(
+            None,
+            MOCK_SIZE,
+            tx4.clone(),
+            tx3.output_pts().into_iter(),
+            0,
+        );
 
         assert!(pool.contains(&tx3));
 
@@ -284,9 +363,27 @@ mod tests {
 
         let tx4 = build_tx(vec![(&tx3_hash, 0)], 1);
 
-        pool.add_tx(None, MOCK_SIZE, tx2.clone(), tx1.output_pts().into_iter());
-        pool.add_tx(None, MOCK_SIZE, tx3.clone(), tx2.output_pts().into_iter());
-        pool.add_tx(None, MOCK_SIZE, tx4.clone(), tx3.output_pts().into_iter());
+        pool.add_tx(
+            None,
+            MOCK_SIZE,
+            tx2.clone(),
+            tx1.output_pts().into_iter(),
+            0,
+        );
+        pool.add_tx(
+            None,
+            MOCK_SIZE,
+            tx3.clone(),
+            tx2.output_pts().into_iter(),
+            0,
+        );
+        pool.add_tx(
+            None,
+            MOCK_SIZE,
+            tx4.clone(),
+            tx3.output_pts().into_iter(),
+            0,
+        );
 
         assert!(pool.contains(&tx2));
         assert!(pool.contains(&tx3));
@@ -303,9 +400,6 @@ mod tests {
 
     #[test]
     fn test_orphan_prune() {
-        let faketime_file = faketime::millis_tempfile(0).expect("create faketime file");
-        faketime::enable(&faketime_file);
-
         let mut pool = OrphanPool::raw_new(0);
 
         let tx1 = build_tx(vec![(&Byte32::zero(), 0)], 1);
@@ -316,10 +410,60 @@ mod tests {
 
         let tx3 = build_tx(vec![(&tx2_hash, 0)], 1);
 
-        pool.add_tx(None, MOCK_SIZE, tx2.clone(), tx1.output_pts().into_iter());
-        faketime::write_millis(&faketime_file, (TTL + 1) * 1000).expect("write millis");
-        pool.add_tx(None, MOCK_SIZE, tx3.clone(), tx2.output_pts().into_iter());
+        pool.add_tx(
+            None,
+            MOCK_SIZE,
+            tx2.clone(),
+            tx1.output_pts().into_iter(),
+            0,
+        );
+        pool.add_tx(
+            None,
+            MOCK_SIZE,
+            tx3.clone(),
+            tx2.output_pts().into_iter(),
+            TTL + 1,
+        );
         assert!(!pool.contains(&tx2));
         assert!(pool.contains(&tx3));
     }
+
+    #[test]
+    fn test_orphan_evict_oldest() {
+        let mut pool = OrphanPool::with_limits(2, 0);
+        assert_eq!(pool.ttl, TTL);
+
+        let tx1 = build_tx(vec![(&Byte32::zero(), 0)], 1);
+        let tx2 = build_tx(vec![(&tx1.hash(), 0)], 1);
+        let tx3 = build_tx(vec![(&tx2.hash(), 0)], 1);
+        let tx4 = build_tx(vec![(&Byte32::zero(), 1)], 1);
+        let tx5 = build_tx(vec![(&tx4.hash(), 0)], 1);
+
+        pool.add_tx(
+            None,
+            MOCK_SIZE,
+            tx2.clone(),
+            tx1.output_pts().into_iter(),
+            0,
+        );
+        pool.add_tx(
+            None,
+            MOCK_SIZE,
+            tx3.clone(),
+            tx2.output_pts().into_iter(),
+            1,
+        );
+        pool.add_tx(
+            None,
+            MOCK_SIZE,
+            tx5.clone(),
+            tx4.output_pts().into_iter(),
+            2,
+        );
+
+        // tx2 is the oldest, and tx3 is its descendant
+        assert!(!pool.contains(&tx2));
+        assert!(!pool.contains(&tx3));
+        assert!(pool.contains(&tx5));
+    }
 }
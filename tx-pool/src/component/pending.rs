@@ -1,4 +1,4 @@
-use crate::component::container::{AncestorsScoreSortKey, SortedTxMap};
+use crate::component::container::{AncestorsScoreSortKey, PackageLimits, SortedTxMap};
 use crate::component::entry::TxEntry;
 use crate::error::SubmitTxError;
 use ckb_fee_estimator::FeeRate;
@@ -18,9 +18,9 @@ pub(crate) struct PendingQueue {
 }
 
 impl PendingQueue {
-    pub(crate) fn new(max_ancestors_count: usize) -> Self {
+    pub(crate) fn new(limits: PackageLimits) -> Self {
         PendingQueue {
-            inner: SortedTxMap::new(max_ancestors_count),
+            inner: SortedTxMap::new(limits),
         }
     }
 
@@ -57,6 +57,10 @@ impl PendingQueue {
         self.inner.get_ancestors(tx_short_id)
     }
 
+    pub(crate) fn entries(&self) -> impl Iterator<Item = &TxEntry> {
+        self.inner.entries()
+    }
+
     pub(crate) fn keys_sorted_by_fee(&self) -> impl Iterator<Item = &AncestorsScoreSortKey> {
         self.inner.keys_sorted_by_fee()
     }
@@ -65,6 +69,58 @@ impl PendingQueue {
         self.inner.keys_sorted_by_fee_and_relation()
     }
 
+    /// Plans the eviction of the packages, an entry with its descendants, of the lowest fee rates
+    /// to free `size` bytes. Only the packages paying lower fee rates than `fee_rate` and not
+    /// including any of `parents` are evicted. Returns the roots of the packages and the highest
+    /// evicted fee rate, or `None` if they can't free enough.
+    pub(crate) fn plan_eviction(
+        &self,
+        size: usize,
+        fee_rate: FeeRate,
+        parents: &HashSet<ProposalShortId>,
+    ) -> Option<(Vec<ProposalShortId>, FeeRate)> {
+        let mut packages: Vec<(FeeRate, &TxEntry)> = self
+            .entries()
+            .map(|entry| (entry.descendants_fee_rate(), entry))
+            .filter(|(package_fee_rate, _)| *package_fee_rate < fee_rate)
+            .collect();
+        packages
+            .sort_by_key(|(package_fee_rate, entry)| (*package_fee_rate, entry.ancestors_count));
+
+        let mut evicted = HashSet::new();
+        let mut roots = Vec::new();
+        let mut freed = 0;
+        let mut max_fee_rate = FeeRate::zero();
+        for (package_fee_rate, entry) in packages {
+            if freed >= size {
+                break;
+            }
+            let id = entry.transaction.proposal_short_id();
+            if evicted.contains(&id) {
+                continue;
+            }
+            let mut package = self.inner.get_descendants(&id);
+            package.insert(id.clone());
+            if package.iter().any(|member| parents.contains(member)) {
+                continue;
+            }
+            for member in package {
+                if let Some(member_entry) = self.get(&member) {
+                    if evicted.insert(member) {
+                        freed += member_entry.size;
+                    }
+                }
+            }
+            roots.push(id);
+            max_fee_rate = ::std::cmp::max(max_fee_rate, package_fee_rate);
+        }
+        if freed >= size {
+            Some((roots, max_fee_rate))
+        } else {
+            None
+        }
+    }
+
     // fill proposal txs
     pub fn fill_proposals(
         &self,
@@ -157,7 +213,7 @@ mod tests {
         let tx2 = build_tx(vec![(&Byte32::zero(), 2)], 1);
         let tx3 = build_tx(vec![(&Byte32::zero(), 3)], 1);
 
-        let mut pool = PendingQueue::new(DEFAULT_MAX_ANCESTORS_SIZE);
+        let mut pool = PendingQueue::new(PackageLimits::new(DEFAULT_MAX_ANCESTORS_SIZE));
 
         pool.add_entry(TxEntry::new(
             tx1.clone(),
@@ -216,7 +272,7 @@ mod tests {
         let tx3 = build_tx(vec![(&tx1_hash, 2)], 1);
         let tx4 = build_tx(vec![(&tx2_hash, 1)], 1);
 
-        let mut pool = PendingQueue::new(DEFAULT_MAX_ANCESTORS_SIZE);
+        let mut pool = PendingQueue::new(PackageLimits::new(DEFAULT_MAX_ANCESTORS_SIZE));
 
         pool.add_entry(TxEntry::new(
             tx1.clone(),
@@ -298,7 +354,7 @@ mod tests {
         let tx2_3_hash = tx2_3.hash();
         let tx2_4 = build_tx(vec![(&tx2_3_hash, 0)], 1);
 
-        let mut pool = PendingQueue::new(DEFAULT_MAX_ANCESTORS_SIZE);
+        let mut pool = PendingQueue::new(PackageLimits::new(DEFAULT_MAX_ANCESTORS_SIZE));
 
         for &tx in &[&tx1, &tx2, &tx3, &tx2_1, &tx2_2, &tx2_3, &tx2_4] {
             pool.add_entry(TxEntry::new(
@@ -344,4 +400,58 @@ mod tests {
         ];
         assert_eq!(keys_sorted_by_fee_and_relation, expect_result);
     }
+
+    #[test]
+    fn test_plan_eviction() {
+        let tx1 = build_tx(vec![(&Byte32::zero(), 1)], 1);
+        let tx2 = build_tx(vec![(&tx1.hash(), 0)], 1);
+        let tx3 = build_tx(vec![(&Byte32::zero(), 2)], 1);
+        let tx4 = build_tx(vec![(&Byte32::zero(), 3)], 1);
+
+        let mut pool = PendingQueue::new(PackageLimits::new(DEFAULT_MAX_ANCESTORS_SIZE));
+        // the package of tx1 and tx2 pays 600 shannons, tx3 pays 200 and tx4 pays 1000
+        for (tx, fee) in &[(&tx1, 100), (&tx2, 500), (&tx3, 200), (&tx4, 1000)] {
+            pool.add_entry(TxEntry::new(
+                (*tx).clone(),
+                MOCK_CYCLES,
+                Capacity::shannons(*fee),
+                MOCK_SIZE,
+                vec![],
+            ))
+            .unwrap();
+        }
+        let fee_rate_of = |tx: &TransactionView| {
+            pool.get(&tx.proposal_short_id())
+                .unwrap()
+                .descendants_fee_rate()
+        };
+        let high_fee_rate = fee_rate_of(&tx4);
+        let no_parents = HashSet::new();
+
+        let (roots, max_fee_rate) = pool
+            .plan_eviction(MOCK_SIZE, high_fee_rate, &no_parents)
+            .unwrap();
+        assert_eq!(roots, vec![tx3.proposal_short_id()]);
+        assert_eq!(max_fee_rate, fee_rate_of(&tx3));
+
+        // the package of tx1 is evicted with its descendant tx2
+        let (roots, max_fee_rate) = pool
+            .plan_eviction(MOCK_SIZE * 3, high_fee_rate, &no_parents)
+            .unwrap();
+        assert_eq!(
+            roots,
+            vec![tx3.proposal_short_id(), tx1.proposal_short_id()]
+        );
+        assert_eq!(max_fee_rate, fee_rate_of(&tx1));
+
+        // the packages paying no less are kept
+        assert!(pool
+            .plan_eviction(MOCK_SIZE * 4, high_fee_rate, &no_parents)
+            .is_none());
+        // the ancestors of the new tx are kept
+        let parents = vec![tx2.proposal_short_id()].into_iter().collect();
+        assert!(pool
+            .plan_eviction(MOCK_SIZE * 2, high_fee_rate, &parents)
+            .is_none());
+    }
 }
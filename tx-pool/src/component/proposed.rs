@@ -1,4 +1,4 @@
-use crate::component::container::SortedTxMap;
+use crate::component::container::{PackageLimits, SortedTxMap};
 use crate::component::entry::TxEntry;
 use crate::error::SubmitTxError;
 use ckb_types::{
@@ -109,10 +109,10 @@ impl CellProvider for ProposedPool {
 }
 
 impl ProposedPool {
-    pub(crate) fn new(max_ancestors_count: usize) -> Self {
+    pub(crate) fn new(limits: PackageLimits) -> Self {
         ProposedPool {
             edges: Default::default(),
-            inner: SortedTxMap::new(max_ancestors_count),
+            inner: SortedTxMap::new(limits),
         }
     }
 
@@ -132,6 +132,10 @@ impl ProposedPool {
         self.inner.size()
     }
 
+    pub(crate) fn entries(&self) -> impl Iterator<Item = &TxEntry> {
+        self.inner.entries()
+    }
+
     pub(crate) fn get_output_with_data(&self, out_point: &OutPoint) -> Option<(CellOutput, Bytes)> {
         self.inner
             .get(&ProposalShortId::from_tx_hash(&out_point.tx_hash()))
@@ -306,7 +310,7 @@ mod tests {
         let tx1_hash = tx1.hash();
         let tx2 = build_tx(vec![(&tx1_hash, 0)], 1);
 
-        let mut pool = ProposedPool::new(DEFAULT_MAX_ANCESTORS_SIZE);
+        let mut pool = ProposedPool::new(PackageLimits::new(DEFAULT_MAX_ANCESTORS_SIZE));
 
         pool.add_entry(TxEntry::new(
             tx1.clone(),
@@ -342,7 +346,7 @@ mod tests {
             3,
         );
 
-        let mut pool = ProposedPool::new(DEFAULT_MAX_ANCESTORS_SIZE);
+        let mut pool = ProposedPool::new(PackageLimits::new(DEFAULT_MAX_ANCESTORS_SIZE));
 
         pool.add_entry(TxEntry::new(
             tx1.clone(),
@@ -384,7 +388,7 @@ mod tests {
         let tx3_hash = tx3.hash();
         let tx5 = build_tx(vec![(&tx1_hash, 2), (&tx3_hash, 0)], 2);
 
-        let mut pool = ProposedPool::new(DEFAULT_MAX_ANCESTORS_SIZE);
+        let mut pool = ProposedPool::new(PackageLimits::new(DEFAULT_MAX_ANCESTORS_SIZE));
 
         pool.add_entry(TxEntry::new(
             tx1.clone(),
@@ -442,7 +446,7 @@ mod tests {
         let tx2 = build_tx(vec![(&Byte32::zero(), 2)], 1);
         let tx3 = build_tx(vec![(&Byte32::zero(), 3)], 1);
 
-        let mut pool = ProposedPool::new(DEFAULT_MAX_ANCESTORS_SIZE);
+        let mut pool = ProposedPool::new(PackageLimits::new(DEFAULT_MAX_ANCESTORS_SIZE));
 
         let cycles = 5_000_000;
         let size = 200;
@@ -489,7 +493,7 @@ mod tests {
         let tx3 = build_tx(vec![(&tx1_hash, 2)], 1);
         let tx4 = build_tx(vec![(&tx2_hash, 1)], 1);
 
-        let mut pool = ProposedPool::new(DEFAULT_MAX_ANCESTORS_SIZE);
+        let mut pool = ProposedPool::new(PackageLimits::new(DEFAULT_MAX_ANCESTORS_SIZE));
 
         let cycles = 5_000_000;
         let size = 200;
@@ -551,7 +555,7 @@ mod tests {
         let tx2_3_hash = tx2_3.hash();
         let tx2_4 = build_tx(vec![(&tx2_3_hash, 0)], 1);
 
-        let mut pool = ProposedPool::new(DEFAULT_MAX_ANCESTORS_SIZE);
+        let mut pool = ProposedPool::new(PackageLimits::new(DEFAULT_MAX_ANCESTORS_SIZE));
 
         let cycles = 5_000_000;
         let size = 200;
@@ -585,7 +589,7 @@ mod tests {
         let tx3 = build_tx(vec![(&tx1_hash, 1)], 1);
         let tx4 = build_tx(vec![(&tx2_hash, 0)], 1);
 
-        let mut pool = ProposedPool::new(DEFAULT_MAX_ANCESTORS_SIZE);
+        let mut pool = ProposedPool::new(PackageLimits::new(DEFAULT_MAX_ANCESTORS_SIZE));
 
         let cycles = 5_000_000;
         let size = 200;
@@ -720,7 +724,7 @@ mod tests {
             }
         };
 
-        let mut pool = ProposedPool::new(DEFAULT_MAX_ANCESTORS_SIZE);
+        let mut pool = ProposedPool::new(PackageLimits::new(DEFAULT_MAX_ANCESTORS_SIZE));
         for tx in &[&tx1, &tx2, &tx3] {
             pool.add_entry(TxEntry::new(
                 (*tx).clone(),
@@ -1,14 +1,33 @@
 use ckb_error::{Error, ErrorKind};
 use failure::Fail;
-use tokio::sync::mpsc::error::TrySendError as TokioTrySendError;
+use tokio::sync::mpsc::error::{SendError as TokioSendError, TrySendError as TokioTrySendError};
 
 #[derive(Debug, PartialEq, Clone, Eq, Fail)]
 pub enum SubmitTxError {
-    /// The fee rate of transaction is lower than min fee rate
-    #[fail(display = "LowFeeRate")]
-    LowFeeRate(u64),
+    /// The fee of transaction is lower than the fee required by the min fee rate
+    #[fail(display = "LowFeeRate required: {}, got: {}", required, got)]
+    LowFeeRate { required: u64, got: u64 },
+    /// An output capacity is lower than the min output capacity
+    #[fail(
+        display = "DustOutput index: {}, required: {}, got: {}",
+        index, required, got
+    )]
+    DustOutput {
+        index: usize,
+        required: u64,
+        got: u64,
+    },
+    /// The serialized size of transaction is greater than the max tx size
+    #[fail(display = "ExceededMaximumTxSize limit: {}, got: {}", limit, got)]
+    ExceededMaximumTxSize { limit: usize, got: usize },
     #[fail(display = "ExceededMaximumAncestorsCount")]
     ExceededMaximumAncestorsCount,
+    #[fail(display = "ExceededMaximumAncestorsSize")]
+    ExceededMaximumAncestorsSize,
+    #[fail(display = "ExceededMaximumDescendantsCount")]
+    ExceededMaximumDescendantsCount,
+    #[fail(display = "ExceededMaximumDescendantsSize")]
+    ExceededMaximumDescendantsSize,
 }
 
 impl From<SubmitTxError> for Error {
@@ -28,13 +47,34 @@ pub enum BlockAssemblerError {
 }
 
 #[derive(Fail, Debug)]
-#[fail(display = "TrySendError {}.", _0)]
-pub struct TrySendError(String);
+pub enum TrySendError {
+    /// The queue of the tx pool is full, the caller may retry later
+    #[fail(display = "TrySendError the tx pool queue is full.")]
+    Full,
+    #[fail(display = "TrySendError the tx pool service is closed.")]
+    Closed,
+}
+
+impl TrySendError {
+    pub fn is_full(&self) -> bool {
+        match self {
+            TrySendError::Full => true,
+            TrySendError::Closed => false,
+        }
+    }
+}
 
 pub fn handle_try_send_error<T>(error: TokioTrySendError<T>) -> (T, TrySendError) {
-    let e = TrySendError(format!("{}", error));
-    let m = match error {
-        TokioTrySendError::Full(t) | TokioTrySendError::Closed(t) => t,
-    };
-    (m, e)
+    match error {
+        TokioTrySendError::Full(t) => (t, TrySendError::Full),
+        TokioTrySendError::Closed(t) => (t, TrySendError::Closed),
+    }
+}
+
+#[derive(Fail, Debug)]
+#[fail(display = "SendError {}.", _0)]
+pub struct SendError(String);
+
+pub fn handle_send_error<T>(error: TokioSendError<T>) -> SendError {
+    SendError(format!("{}", error))
 }
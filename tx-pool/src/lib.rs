@@ -1,13 +1,24 @@
 mod block_assembler;
 mod component;
 pub mod error;
+mod longpoll;
+mod metrics;
+mod persisted;
+mod policy;
 pub mod pool;
 mod process;
 pub mod service;
+mod tracker;
 
 pub(crate) const LOG_TARGET_TX_POOL: &str = "ckb-tx-pool";
 
-pub use component::entry::TxEntry;
+pub use block_assembler::{
+    AncestorsScoreStrategy, Candidates, FifoStrategy, SelectionLimits, SelectionStrategy,
+    DEFAULT_SELECTION_STRATEGY, FIFO_SELECTION_STRATEGY,
+};
+pub use component::entry::{TxEntry, TxPackageInfo};
+pub use longpoll::LongpollId;
 pub use process::PlugTarget;
 pub use service::{TxPoolController, TxPoolServiceBuilder};
 pub use tokio::sync::RwLock as TokioRwLock;
+pub use tracker::LocalTxStatus;
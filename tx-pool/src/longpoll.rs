@@ -0,0 +1,145 @@
+//! Longpoll of the block templates.
+//!
+//! A template is identified by the tip and the `last_txs_updated_at` it is built on. A longpoll
+//! with the id of a template waits until either of them changes, so the miners get the new
+//! template right away instead of polling `get_block_template` in a loop.
+use ckb_types::H256;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use tokio::sync::oneshot;
+
+const TIP_HASH_HEX_LEN: usize = 64;
+const TXS_UPDATED_AT_HEX_LEN: usize = 16;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LongpollId {
+    pub tip_hash: H256,
+    pub txs_updated_at: u64,
+}
+
+/// The id is formatted as the hex of the tip hash followed by the hex of `txs_updated_at`.
+impl fmt::Display for LongpollId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:x}{:016x}", self.tip_hash, self.txs_updated_at)
+    }
+}
+
+impl FromStr for LongpollId {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() != TIP_HASH_HEX_LEN + TXS_UPDATED_AT_HEX_LEN || !s.is_ascii() {
+            return Err(format!("invalid longpoll id {}", s));
+        }
+        let (tip_hash, txs_updated_at) = s.split_at(TIP_HASH_HEX_LEN);
+        let tip_hash = H256::from_str(tip_hash).map_err(|err| err.to_string())?;
+        let txs_updated_at =
+            u64::from_str_radix(txs_updated_at, 16).map_err(|err| err.to_string())?;
+        Ok(LongpollId {
+            tip_hash,
+            txs_updated_at,
+        })
+    }
+}
+
+/// The longpolls waiting for the tip or the pool to change.
+#[derive(Default)]
+pub(crate) struct LongpollWaiters {
+    next_token: u64,
+    waiters: HashMap<u64, (LongpollId, oneshot::Sender<()>)>,
+    closed: bool,
+}
+
+impl LongpollWaiters {
+    /// Registers a waiter, returns `None` if the state has already changed since `id`, or the
+    /// waiters are closed. The token removes the waiter once it times out.
+    pub(crate) fn wait(
+        &mut self,
+        id: LongpollId,
+        current: &LongpollId,
+    ) -> Option<(u64, oneshot::Receiver<()>)> {
+        if self.closed || id != *current {
+            return None;
+        }
+        let (sender, receiver) = oneshot::channel();
+        let token = self.next_token;
+        self.next_token += 1;
+        self.waiters.insert(token, (id, sender));
+        Some((token, receiver))
+    }
+
+    pub(crate) fn remove(&mut self, token: u64) {
+        self.waiters.remove(&token);
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.waiters.is_empty()
+    }
+
+    /// Wakes all the waiters and refuses the later ones, on shutdown.
+    pub(crate) fn close(&mut self) {
+        self.closed = true;
+        for (_, (_, sender)) in self.waiters.drain() {
+            let _ = sender.send(());
+        }
+    }
+
+    /// Wakes the waiters whose ids differ from the current state.
+    pub(crate) fn wake(&mut self, current: &LongpollId) {
+        let tokens: Vec<u64> = self
+            .waiters
+            .iter()
+            .filter(|(_, (id, _))| id != current)
+            .map(|(token, _)| *token)
+            .collect();
+        for token in tokens {
+            if let Some((_, sender)) = self.waiters.remove(&token) {
+                let _ = sender.send(());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_longpoll_id_roundtrip() {
+        let id = LongpollId {
+            tip_hash: H256([7u8; 32]),
+            txs_updated_at: 1_590_000_000_000,
+        };
+        let s = id.to_string();
+        assert_eq!(s.len(), TIP_HASH_HEX_LEN + TXS_UPDATED_AT_HEX_LEN);
+        assert_eq!(s.parse::<LongpollId>(), Ok(id));
+        assert!(s[1..].parse::<LongpollId>().is_err());
+        assert!(format!("0x{}", &s[2..]).parse::<LongpollId>().is_err());
+    }
+
+    #[test]
+    fn test_wake_changed() {
+        let id = |txs_updated_at| LongpollId {
+            tip_hash: H256::default(),
+            txs_updated_at,
+        };
+        let mut waiters = LongpollWaiters::default();
+        assert!(waiters.wait(id(0), &id(1)).is_none());
+
+        let (_, mut receiver) = waiters.wait(id(1), &id(1)).expect("unchanged");
+        waiters.wake(&id(1));
+        assert!(receiver.try_recv().is_err());
+        assert!(!waiters.is_empty());
+
+        waiters.wake(&id(2));
+        assert_eq!(receiver.try_recv(), Ok(()));
+        assert!(waiters.is_empty());
+
+        let (_, mut receiver) = waiters.wait(id(2), &id(2)).expect("unchanged");
+        waiters.close();
+        assert_eq!(receiver.try_recv(), Ok(()));
+        assert!(waiters.is_empty());
+        assert!(waiters.wait(id(2), &id(2)).is_none());
+    }
+}
@@ -0,0 +1,59 @@
+use ckb_metrics::{
+    register_counter, register_counter_vec, register_gauge, register_gauge_vec, register_histogram,
+    register_histogram_with_buckets, Counter, Gauge, Histogram, MetricVec, Subsystem,
+};
+use lazy_static::lazy_static;
+
+/// Upper bounds of the fee rate buckets in shannons per KB, the default min fee rate is 1000.
+const FEE_RATE_BUCKETS: &[f64] = &[
+    1_000.0,
+    2_000.0,
+    5_000.0,
+    10_000.0,
+    20_000.0,
+    50_000.0,
+    100_000.0,
+    1_000_000.0,
+];
+
+lazy_static! {
+    pub(crate) static ref PROCESS_TXS_DURATION: Histogram = register_histogram(
+        Subsystem::TxPool,
+        "ckb_tx_pool_process_txs_duration_seconds",
+        "Time to verify a batch of transactions and add them to the pool",
+    );
+    pub(crate) static ref PROCESSED_TXS: MetricVec<Counter> = register_counter_vec(
+        Subsystem::TxPool,
+        "ckb_tx_pool_processed_transactions_total",
+        "Transactions processed by the pool, by origin and result",
+        &["origin", "result"],
+    );
+    pub(crate) static ref POOL_TXS: MetricVec<Gauge> = register_gauge_vec(
+        Subsystem::TxPool,
+        "ckb_tx_pool_transactions",
+        "Transactions in the pool, by pending, gap, proposed or orphan",
+        &["pool"],
+    );
+    pub(crate) static ref POOL_SIZE: MetricVec<Gauge> = register_gauge_vec(
+        Subsystem::TxPool,
+        "ckb_tx_pool_size",
+        "Total size in bytes and total cycles of the transactions in the pool",
+        &["unit"],
+    );
+    pub(crate) static ref QUEUE_DEPTH: Gauge = register_gauge(
+        Subsystem::TxPool,
+        "ckb_tx_pool_queue_depth",
+        "Messages waiting in the tx pool queue",
+    );
+    pub(crate) static ref QUEUE_FULL: Counter = register_counter(
+        Subsystem::TxPool,
+        "ckb_tx_pool_queue_full_total",
+        "Messages refused because the tx pool queue is full",
+    );
+    pub(crate) static ref ACCEPTED_FEE_RATE: Histogram = register_histogram_with_buckets(
+        Subsystem::TxPool,
+        "ckb_tx_pool_accepted_fee_rate",
+        "Fee rates in shannons per KB of the transactions accepted by the pool",
+        FEE_RATE_BUCKETS,
+    );
+}
@@ -0,0 +1,130 @@
+//! The file which keeps the pool entries across restarts.
+//!
+//! The entries are written on shutdown and submitted again on startup, so they are verified
+//! against the chain state at that time. The cycles and fee are kept to fill the tx verify cache,
+//! which spares the scripts of the transactions from running again.
+use crate::component::entry::TxEntry;
+use ckb_error::{Error, InternalErrorKind};
+use ckb_types::{
+    core::{Capacity, Cycle, TransactionView},
+    packed,
+    prelude::*,
+};
+use ckb_verification::cache::CacheEntry;
+use std::convert::TryInto;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+const FORMAT_VERSION: u32 = 1;
+// record layout: cycles(8, le) | fee(8, le) | tx_len(4, le) | tx
+const RECORD_HEADER_SIZE: usize = 8 + 8 + 4;
+
+pub(crate) fn encode(entries: &[TxEntry]) -> Vec<u8> {
+    let mut buf = Vec::new();
+    buf.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    for entry in entries {
+        let tx = entry.transaction.data();
+        buf.extend_from_slice(&entry.cycles.to_le_bytes());
+        buf.extend_from_slice(&entry.fee.as_u64().to_le_bytes());
+        buf.extend_from_slice(&(tx.as_slice().len() as u32).to_le_bytes());
+        buf.extend_from_slice(tx.as_slice());
+    }
+    buf
+}
+
+pub(crate) fn decode(mut buf: &[u8]) -> Result<Vec<(TransactionView, CacheEntry)>, Error> {
+    let corrupted = |reason: &'static str| InternalErrorKind::DataCorrupted.reason(reason);
+    if buf.len() < 4 {
+        return Err(corrupted("persisted tx pool is truncated").into());
+    }
+    let version = u32::from_le_bytes(buf[..4].try_into().expect("checked length"));
+    if version != FORMAT_VERSION {
+        return Err(InternalErrorKind::DataCorrupted
+            .reason(format!("unknown persisted tx pool version {}", version))
+            .into());
+    }
+    buf = &buf[4..];
+
+    let mut entries = Vec::new();
+    while !buf.is_empty() {
+        if buf.len() < RECORD_HEADER_SIZE {
+            return Err(corrupted("persisted tx pool is truncated").into());
+        }
+        let cycles = Cycle::from_le_bytes(buf[..8].try_into().expect("checked length"));
+        let fee = u64::from_le_bytes(buf[8..16].try_into().expect("checked length"));
+        let tx_len = u32::from_le_bytes(buf[16..20].try_into().expect("checked length")) as usize;
+        buf = &buf[RECORD_HEADER_SIZE..];
+        if buf.len() < tx_len {
+            return Err(corrupted("persisted tx pool is truncated").into());
+        }
+        let tx = packed::Transaction::from_slice(&buf[..tx_len])
+            .map_err(|err| InternalErrorKind::DataCorrupted.reason(format!("{}", err)))?;
+        buf = &buf[tx_len..];
+        entries.push((
+            tx.into_view(),
+            CacheEntry::new(cycles, Capacity::shannons(fee)),
+        ));
+    }
+    Ok(entries)
+}
+
+/// Writes the entries through a temporary file, so a crash never leaves a half written file.
+pub(crate) fn write_file(path: &Path, entries: &[TxEntry]) -> Result<(), Error> {
+    let tmp_path = path.with_extension("tmp");
+    let write = || -> std::io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = fs::File::create(&tmp_path)?;
+        file.write_all(&encode(entries))?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, path)
+    };
+    write().map_err(|err| InternalErrorKind::System.reason(err).into())
+}
+
+/// Returns no entries if the file does not exist.
+pub(crate) fn read_file(path: &Path) -> Result<Vec<(TransactionView, CacheEntry)>, Error> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let buf = fs::read(path).map_err(|err| InternalErrorKind::System.reason(err))?;
+    decode(&buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ckb_types::{bytes::Bytes, core::TransactionBuilder};
+
+    #[test]
+    fn test_encode_and_decode() {
+        let entries: Vec<_> = (0..3u8)
+            .map(|i| {
+                let tx = TransactionBuilder::default()
+                    .witness(Bytes::from(vec![i; i as usize]).pack())
+                    .build();
+                TxEntry::new(
+                    tx,
+                    1000 + u64::from(i),
+                    Capacity::shannons(10 * u64::from(i)),
+                    100,
+                    vec![],
+                )
+            })
+            .collect();
+        let buf = encode(&entries);
+
+        let decoded = decode(&buf).expect("decode");
+        assert_eq!(decoded.len(), 3);
+        for (entry, (tx, cache_entry)) in entries.iter().zip(decoded.iter()) {
+            assert_eq!(entry.transaction.hash(), tx.hash());
+            assert_eq!(cache_entry.cycles, entry.cycles);
+            assert_eq!(cache_entry.fee, entry.fee);
+        }
+
+        assert!(decode(&buf[..buf.len() - 1]).is_err());
+        assert_eq!(decode(&encode(&[])).expect("decode").len(), 0);
+    }
+}
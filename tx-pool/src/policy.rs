@@ -0,0 +1,157 @@
+//! Local policy rules of the tx pool.
+//!
+//! A transaction violating them is valid by the consensus, it's rejected by this node and may be
+//! accepted by the others configured differently.
+use crate::error::SubmitTxError;
+use ckb_app_config::TxPoolConfig;
+use ckb_fee_estimator::FeeRate;
+use ckb_types::{
+    core::{Capacity, TransactionView},
+    prelude::*,
+};
+
+/// Checks the size, the output capacities and the fee of the transaction, `tx_size` is the
+/// serialized size in block. `min_fee_rate` is the current min entry fee rate of the pool, no
+/// less than the configured one.
+pub(crate) fn check_tx_policy(
+    config: &TxPoolConfig,
+    min_fee_rate: FeeRate,
+    tx: &TransactionView,
+    tx_size: usize,
+    fee: Capacity,
+) -> Result<(), SubmitTxError> {
+    if config.max_tx_size != 0 && tx_size > config.max_tx_size {
+        return Err(SubmitTxError::ExceededMaximumTxSize {
+            limit: config.max_tx_size,
+            got: tx_size,
+        });
+    }
+    if config.min_output_capacity != 0 {
+        for (index, output) in tx.outputs().into_iter().enumerate() {
+            let capacity: u64 = output.capacity().unpack();
+            if capacity < config.min_output_capacity {
+                return Err(SubmitTxError::DustOutput {
+                    index,
+                    required: config.min_output_capacity,
+                    got: capacity,
+                });
+            }
+        }
+    }
+    let min_fee = min_fee_rate.fee(tx_size);
+    if fee < min_fee {
+        return Err(SubmitTxError::LowFeeRate {
+            required: min_fee.as_u64(),
+            got: fee.as_u64(),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ckb_types::{bytes::Bytes, core::TransactionBuilder, packed::CellOutput};
+
+    fn build_tx(capacities: &[u64]) -> TransactionView {
+        capacities
+            .iter()
+            .fold(TransactionBuilder::default(), |builder, capacity| {
+                builder
+                    .output(CellOutput::new_builder().capacity(capacity.pack()).build())
+                    .output_data(Bytes::new().pack())
+            })
+            .build()
+    }
+
+    #[test]
+    fn test_check_tx_policy() {
+        let config = TxPoolConfig {
+            min_fee_rate: FeeRate::from_u64(1000),
+            min_output_capacity: 100,
+            max_tx_size: 1000,
+            ..Default::default()
+        };
+        let tx = build_tx(&[100, 200]);
+        assert_eq!(
+            check_tx_policy(
+                &config,
+                config.min_fee_rate,
+                &tx,
+                500,
+                Capacity::shannons(500)
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            check_tx_policy(
+                &config,
+                config.min_fee_rate,
+                &tx,
+                500,
+                Capacity::shannons(499)
+            ),
+            Err(SubmitTxError::LowFeeRate {
+                required: 500,
+                got: 499
+            })
+        );
+        // the min entry fee rate raised by the evictions
+        assert_eq!(
+            check_tx_policy(
+                &config,
+                FeeRate::from_u64(2000),
+                &tx,
+                500,
+                Capacity::shannons(500)
+            ),
+            Err(SubmitTxError::LowFeeRate {
+                required: 1000,
+                got: 500
+            })
+        );
+        assert_eq!(
+            check_tx_policy(
+                &config,
+                config.min_fee_rate,
+                &tx,
+                1001,
+                Capacity::shannons(2000)
+            ),
+            Err(SubmitTxError::ExceededMaximumTxSize {
+                limit: 1000,
+                got: 1001
+            })
+        );
+        assert_eq!(
+            check_tx_policy(
+                &config,
+                config.min_fee_rate,
+                &build_tx(&[100, 99]),
+                500,
+                Capacity::shannons(500)
+            ),
+            Err(SubmitTxError::DustOutput {
+                index: 1,
+                required: 100,
+                got: 99
+            })
+        );
+
+        // the zero limits are disabled
+        let config = TxPoolConfig {
+            min_fee_rate: FeeRate::zero(),
+            ..Default::default()
+        };
+        assert_eq!(
+            check_tx_policy(
+                &config,
+                config.min_fee_rate,
+                &build_tx(&[0]),
+                100_000,
+                Capacity::zero()
+            ),
+            Ok(())
+        );
+    }
+}
@@ -1,13 +1,17 @@
 //! Top-level Pool type, methods, and tests
-use super::component::{DefectEntry, TxEntry};
+use super::component::{DefectEntry, TxEntry, TxPackageInfo};
+use crate::component::container::PackageLimits;
+use crate::component::get_transaction_virtual_bytes;
 use crate::component::orphan::OrphanPool;
 use crate::component::pending::PendingQueue;
 use crate::component::proposed::ProposedPool;
 use crate::error::SubmitTxError;
+use crate::metrics::{POOL_SIZE, POOL_TXS};
 use ckb_app_config::TxPoolConfig;
+use ckb_clock::Clock;
 use ckb_dao::DaoCalculator;
 use ckb_error::{Error, ErrorKind, InternalErrorKind};
-use ckb_fee_estimator::Estimator as FeeEstimator;
+use ckb_fee_estimator::{EstimateMode, Estimator as FeeEstimator, FeeRate, MempoolCurve};
 use ckb_logger::{debug_target, error_target, trace_target};
 use ckb_snapshot::Snapshot;
 use ckb_store::ChainStore;
@@ -20,8 +24,7 @@ use ckb_types::{
     packed::{Byte32, OutPoint, ProposalShortId},
 };
 use ckb_verification::cache::CacheEntry;
-use ckb_verification::{ContextualTransactionVerifier, TransactionVerifier};
-use faketime::unix_time_as_millis;
+use ckb_verification::{ContextualTransactionVerifier, TransactionVerifier, VerifyOrigin};
 use lru_cache::LruCache;
 use std::collections::HashMap;
 use std::collections::HashSet;
@@ -30,6 +33,9 @@ use std::sync::{
     Arc,
 };
 
+// The min entry fee rate raised by the evictions halves every 12 hours
+const EVICTED_FEE_RATE_HALFLIFE_MS: u64 = 12 * 3600 * 1000;
+
 #[derive(Clone)]
 pub struct TxPool {
     pub(crate) config: TxPoolConfig,
@@ -53,7 +59,11 @@ pub struct TxPool {
     pub(crate) total_tx_cycles: Cycle,
     // tx fee estimator
     pub(crate) fee_estimator: FeeEstimator,
+    // the min entry fee rate raised by the last eviction, and when in unix millis
+    pub(crate) evicted_fee_rate: (FeeRate, u64),
     pub snapshot: Arc<Snapshot>,
+    // the time source of the timestamps and the decays
+    clock: Arc<dyn Clock>,
 }
 
 #[derive(Clone, Debug)]
@@ -61,26 +71,99 @@ pub struct TxPoolInfo {
     pub pending_size: usize,
     pub proposed_size: usize,
     pub orphan_size: usize,
+    pub orphan_tx_size: usize,
     pub total_tx_size: usize,
     pub total_tx_cycles: Cycle,
+    /// The fee rate the entering transactions must pay, raised above `min_fee_rate` by the
+    /// evictions of a full pool
+    pub min_entry_fee_rate: FeeRate,
     pub last_txs_updated_at: u64,
 }
 
+// The lower bound of the first fee rate bin above zero, in shannons per kilobyte
+const FEE_HISTOGRAM_BASE_FEE_RATE: u64 = 1000;
+
+/// The virtual bytes and cycles of the pending, gap and proposed entries binned by their fee
+/// rates, and the counts of the entries in each state.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FeeHistogram {
+    /// In descending order of the fee rate
+    pub bins: Vec<FeeHistogramBin>,
+    pub pending_count: usize,
+    pub gap_count: usize,
+    pub proposed_count: usize,
+    pub orphan_count: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FeeHistogramBin {
+    /// The lower bound of the bin, the upper bound is the one of the previous bin
+    pub fee_rate: FeeRate,
+    /// The cumulative virtual bytes of the entries paying at least `fee_rate`
+    pub vbytes: u64,
+    /// The cumulative cycles of the entries paying at least `fee_rate`
+    pub cycles: Cycle,
+}
+
+impl FeeHistogram {
+    /// Bins the (fee rate, virtual bytes, cycles) of the entries. The bins grow by a quarter
+    /// from 1000 shannons per kilobyte, and the fee rates below fall in the bin of zero.
+    pub fn bin(entries: impl IntoIterator<Item = (FeeRate, u64, Cycle)>) -> Vec<FeeHistogramBin> {
+        let mut bins: Vec<FeeHistogramBin> = Vec::new();
+        for (fee_rate, vbytes, cycles) in entries {
+            let lower = fee_rate_bin(fee_rate);
+            match bins.iter_mut().find(|bin| bin.fee_rate == lower) {
+                Some(bin) => {
+                    bin.vbytes = bin.vbytes.saturating_add(vbytes);
+                    bin.cycles = bin.cycles.saturating_add(cycles);
+                }
+                None => bins.push(FeeHistogramBin {
+                    fee_rate: lower,
+                    vbytes,
+                    cycles,
+                }),
+            }
+        }
+        bins.sort_by(|a, b| b.fee_rate.cmp(&a.fee_rate));
+        let (mut vbytes, mut cycles): (u64, Cycle) = (0, 0);
+        for bin in &mut bins {
+            vbytes = vbytes.saturating_add(bin.vbytes);
+            cycles = cycles.saturating_add(bin.cycles);
+            bin.vbytes = vbytes;
+            bin.cycles = cycles;
+        }
+        bins
+    }
+}
+
+fn fee_rate_bin(fee_rate: FeeRate) -> FeeRate {
+    let fee_rate = fee_rate.as_u64();
+    if fee_rate < FEE_HISTOGRAM_BASE_FEE_RATE {
+        return FeeRate::zero();
+    }
+    let mut lower = FEE_HISTOGRAM_BASE_FEE_RATE;
+    while lower + lower / 4 <= fee_rate {
+        lower += lower / 4;
+    }
+    FeeRate::from_u64(lower)
+}
+
 impl TxPool {
     pub fn new(
         config: TxPoolConfig,
         snapshot: Arc<Snapshot>,
         last_txs_updated_at: Arc<AtomicU64>,
+        clock: Arc<dyn Clock>,
     ) -> TxPool {
         let conflict_cache_size = config.max_conflict_cache_size;
         let committed_txs_hash_cache_size = config.max_committed_txs_hash_cache_size;
 
         TxPool {
             config,
-            pending: PendingQueue::new(config.max_ancestors_count),
-            gap: PendingQueue::new(config.max_ancestors_count),
-            proposed: ProposedPool::new(config.max_ancestors_count),
-            orphan: OrphanPool::new(),
+            pending: PendingQueue::new(PackageLimits::from(&config)),
+            gap: PendingQueue::new(PackageLimits::from(&config)),
+            proposed: ProposedPool::new(PackageLimits::from(&config)),
+            orphan: OrphanPool::with_limits(config.max_orphan_txs, config.orphan_expiry_secs),
             conflict: LruCache::new(conflict_cache_size),
             committed_txs_hash_cache: LruCache::new(committed_txs_hash_cache_size),
             last_txs_updated_at,
@@ -88,9 +171,16 @@ impl TxPool {
             total_tx_cycles: 0,
             snapshot,
             fee_estimator: FeeEstimator::default(),
+            evicted_fee_rate: (FeeRate::zero(), 0),
+            clock,
         }
     }
 
+    /// The unix time in milliseconds of the clock of the node
+    pub fn unix_time_as_millis(&self) -> u64 {
+        self.clock.unix_time_as_millis()
+    }
+
     pub fn snapshot(&self) -> &Snapshot {
         &self.snapshot
     }
@@ -104,16 +194,214 @@ impl TxPool {
             pending_size: self.pending.size() + self.gap.size(),
             proposed_size: self.proposed.size(),
             orphan_size: self.orphan.size(),
+            orphan_tx_size: self.orphan.total_tx_size(),
             total_tx_size: self.total_tx_size,
             total_tx_cycles: self.total_tx_cycles,
+            min_entry_fee_rate: self.min_entry_fee_rate(),
             last_txs_updated_at: self.get_last_txs_updated_at(),
         }
     }
 
+    pub(crate) fn fee_histogram(&self) -> FeeHistogram {
+        let bins = FeeHistogram::bin(
+            self.proposed
+                .entries()
+                .chain(self.gap.entries())
+                .chain(self.pending.entries())
+                .map(|entry| {
+                    let vbytes = get_transaction_virtual_bytes(entry.size, entry.cycles);
+                    (
+                        FeeRate::calculate(entry.fee, vbytes as usize),
+                        vbytes,
+                        entry.cycles,
+                    )
+                }),
+        );
+        FeeHistogram {
+            bins,
+            pending_count: self.pending.size(),
+            gap_count: self.gap.size(),
+            proposed_count: self.proposed.size(),
+            orphan_count: self.orphan.size(),
+        }
+    }
+
+    pub(crate) fn update_metrics(&self) {
+        for (pool, size) in &[
+            ("pending", self.pending.size()),
+            ("gap", self.gap.size()),
+            ("proposed", self.proposed.size()),
+            ("orphan", self.orphan.size()),
+        ] {
+            POOL_TXS.with_label_values(&[pool]).set(*size as i64);
+        }
+        POOL_SIZE
+            .with_label_values(&["bytes"])
+            .set(self.total_tx_size as i64);
+        POOL_SIZE
+            .with_label_values(&["cycles"])
+            .set(self.total_tx_cycles as i64);
+    }
+
+    /// Estimates the fee rate to get a transaction committed within `expect_confirm_blocks`.
+    pub(crate) fn estimate_fee_rate(
+        &self,
+        expect_confirm_blocks: usize,
+        mode: EstimateMode,
+    ) -> FeeRate {
+        match mode {
+            EstimateMode::Historical => self.fee_estimator.estimate(expect_confirm_blocks),
+            EstimateMode::Mempool => {
+                let curve = MempoolCurve::new(
+                    self.proposed
+                        .entries()
+                        .chain(self.gap.entries())
+                        .chain(self.pending.entries())
+                        .map(|entry| {
+                            let vbytes = get_transaction_virtual_bytes(entry.size, entry.cycles);
+                            (FeeRate::calculate(entry.fee, vbytes as usize), vbytes)
+                        }),
+                );
+                let block_vbytes = self.snapshot.consensus().max_block_bytes();
+                let fee_rate = self.fee_estimator.estimate_with_mempool(
+                    expect_confirm_blocks,
+                    &curve,
+                    block_vbytes,
+                );
+                // the pool takes no transaction below the min fee rate anyway
+                ::std::cmp::max(fee_rate, self.config.min_fee_rate)
+            }
+        }
+    }
+
+    /// Returns the proposed, gap and pending entries, a transaction always comes after its
+    /// parents in the pool.
+    pub(crate) fn persisted_entries(&self) -> Vec<TxEntry> {
+        self.sorted_entries().into_iter().cloned().collect()
+    }
+
+    /// Returns the hashes of at most `limit` transactions in the order of `persisted_entries`,
+    /// the ones closer to be committed come first.
+    pub(crate) fn inventory(&self, limit: usize) -> Vec<Byte32> {
+        self.sorted_entries()
+            .into_iter()
+            .take(limit)
+            .map(|entry| entry.transaction.hash())
+            .collect()
+    }
+
+    fn sorted_entries(&self) -> Vec<&TxEntry> {
+        let pools: Vec<Vec<&TxEntry>> = vec![
+            self.proposed.entries().collect(),
+            self.gap.entries().collect(),
+            self.pending.entries().collect(),
+        ];
+        let mut entries = Vec::new();
+        for mut pool in pools {
+            pool.sort_by_key(|entry| entry.ancestors_count);
+            entries.extend(pool);
+        }
+        entries
+    }
+
     pub fn reach_size_limit(&self, tx_size: usize) -> bool {
         (self.total_tx_size + tx_size) > self.config.max_mem_size
     }
 
+    /// The fee rate the entering transactions must pay, the configured `min_fee_rate`, or the one
+    /// raised by the evictions while it decays.
+    pub fn min_entry_fee_rate(&self) -> FeeRate {
+        let (fee_rate, evicted_at) = self.evicted_fee_rate;
+        let elapsed = self.unix_time_as_millis().saturating_sub(evicted_at);
+        ::std::cmp::max(self.config.min_fee_rate, decay_fee_rate(fee_rate, elapsed))
+    }
+
+    /// Plans the eviction of the pending packages, an entry with its descendants, of the lowest
+    /// fee rates to make room for the transaction if the pool would exceed `max_mem_size`. The
+    /// packages paying no less than the transaction, or including its parents, are kept, and the
+    /// pool is full if they can't make enough room. Returns the roots of the packages and the
+    /// highest evicted fee rate, or `None` if there is room already. Nothing is evicted until
+    /// `evict` is called, so the transaction can be added first.
+    pub(crate) fn plan_eviction_for_tx(
+        &self,
+        tx: &TransactionView,
+        related_dep_out_points: &[OutPoint],
+        tx_size: usize,
+        cycles: Cycle,
+        fee: Capacity,
+    ) -> Result<Option<(Vec<ProposalShortId>, FeeRate)>, Error> {
+        if !self.reach_size_limit(tx_size) {
+            return Ok(None);
+        }
+        let needed = self.total_tx_size + tx_size - self.config.max_mem_size;
+        let vbytes = get_transaction_virtual_bytes(tx_size, cycles);
+        let fee_rate = FeeRate::calculate(fee, vbytes as usize);
+        let parents: HashSet<ProposalShortId> = tx
+            .input_pts_iter()
+            .chain(related_dep_out_points.iter().cloned())
+            .map(|out_point| ProposalShortId::from_tx_hash(&out_point.tx_hash()))
+            .collect();
+        self.pending
+            .plan_eviction(needed, fee_rate, &parents)
+            .map(Some)
+            .ok_or_else(|| InternalErrorKind::TransactionPoolFull.into())
+    }
+
+    /// A cheap check before running the scripts of the transaction, it must pay the min entry
+    /// fee rate and be able to evict enough if the pool is full. Its cycles are unknown yet,
+    /// they only add to the virtual bytes, so the fee rate is at most `fee / tx_size`.
+    pub(crate) fn check_room_for_tx(
+        &self,
+        tx: &TransactionView,
+        related_dep_out_points: &[OutPoint],
+        tx_size: usize,
+        fee: Capacity,
+    ) -> Result<(), Error> {
+        if !self.reach_size_limit(tx_size) {
+            return Ok(());
+        }
+        if fee < self.min_entry_fee_rate().fee(tx_size) {
+            return Err(InternalErrorKind::TransactionPoolFull.into());
+        }
+        self.plan_eviction_for_tx(tx, related_dep_out_points, tx_size, 0, fee)
+            .map(|_| ())
+    }
+
+    /// Evicts the packages planned by `plan_eviction_for_tx` for the transaction. The min entry
+    /// fee rate is raised above the evicted packages.
+    pub(crate) fn evict(
+        &mut self,
+        tx_hash: &Byte32,
+        roots: Vec<ProposalShortId>,
+        evicted_fee_rate: FeeRate,
+    ) -> Vec<TxEntry> {
+        let mut evicted = Vec::new();
+        for root in roots {
+            for entry in self.pending.remove_entry_and_descendants(&root) {
+                self.update_statics_for_remove_tx(entry.size, entry.cycles);
+                self.fee_estimator.drop_tx(&entry.transaction.hash());
+                evicted.push(entry);
+            }
+        }
+        // the increment is the configured min fee rate, so the evicted ones can't reenter
+        let raised = FeeRate::from_u64(
+            evicted_fee_rate
+                .as_u64()
+                .saturating_add(self.config.min_fee_rate.as_u64()),
+        );
+        if raised > self.min_entry_fee_rate() {
+            self.evicted_fee_rate = (raised, self.unix_time_as_millis());
+        }
+        debug_target!(
+            crate::LOG_TARGET_TX_POOL,
+            "evict {} txs for {}, min entry fee rate {}",
+            evicted.len(),
+            tx_hash,
+            self.min_entry_fee_rate()
+        );
+        evicted
+    }
+
     pub fn reach_cycles_limit(&self, cycles: Cycle) -> bool {
         (self.total_tx_cycles + cycles) > self.config.max_cycles
     }
@@ -191,13 +479,14 @@ impl TxPool {
         unknowns: Vec<OutPoint>,
     ) -> Option<DefectEntry> {
         trace_target!(crate::LOG_TARGET_TX_POOL, "add_orphan {}", &tx.hash());
+        let now = self.unix_time_as_millis() / 1000;
         self.orphan
-            .add_tx(cache_entry, size, tx, unknowns.into_iter())
+            .add_tx(cache_entry, size, tx, unknowns.into_iter(), now)
     }
 
     pub(crate) fn touch_last_txs_updated_at(&self) {
         self.last_txs_updated_at
-            .store(unix_time_as_millis(), Ordering::SeqCst);
+            .store(self.unix_time_as_millis(), Ordering::SeqCst);
     }
 
     pub fn get_last_txs_updated_at(&self) -> u64 {
@@ -219,6 +508,16 @@ impl TxPool {
             || self.conflict.contains_key(id)
     }
 
+    /// Returns the package stats of the entry in the pending, gap or proposed pool. The packages
+    /// never cross the pools, e.g. a pending entry doesn't count its proposed parents.
+    pub fn get_package_info(&self, id: &ProposalShortId) -> Option<TxPackageInfo> {
+        self.pending
+            .get(id)
+            .or_else(|| self.gap.get(id))
+            .or_else(|| self.proposed.get(id))
+            .map(TxEntry::package_info)
+    }
+
     pub fn get_tx_with_cycles(
         &self,
         id: &ProposalShortId,
@@ -377,6 +676,7 @@ impl TxPool {
                     tip_header.hash(),
                     consensus,
                 )
+                .origin(VerifyOrigin::Reorg)
                 .verify()?;
                 Ok(cache_entry)
             }
@@ -391,45 +691,41 @@ impl TxPool {
                     consensus,
                     snapshot,
                 )
+                .origin(VerifyOrigin::Reorg)
                 .verify(max_cycles)?;
                 Ok(cache_entry)
             }
         }
     }
 
-    // remove resolved tx from orphan pool
+    // remove resolved tx from orphan pool, the orphans are not counted in the statics until they
+    // are added to the pool
     pub(crate) fn try_proposed_orphan_by_ancestor(&mut self, tx: &TransactionView) {
         let entries = self.orphan.remove_by_ancestor(tx);
         for entry in entries {
             let tx_hash = entry.transaction.hash();
-            if self.contains_proposed(&entry.transaction.proposal_short_id()) {
-                let ret = self.proposed_tx(entry.cache_entry, entry.size, entry.transaction);
-                if ret.is_err() {
-                    self.update_statics_for_remove_tx(
-                        entry.size,
-                        entry.cache_entry.map(|c| c.cycles).unwrap_or(0),
-                    );
-                    trace_target!(
-                        crate::LOG_TARGET_TX_POOL,
-                        "proposed tx {} failed {:?}",
-                        tx_hash,
-                        ret
-                    );
-                }
+            let size = entry.size;
+            let (pool_name, ret) = if self.contains_proposed(&entry.transaction.proposal_short_id())
+            {
+                (
+                    "proposed",
+                    self.proposed_tx(entry.cache_entry, entry.size, entry.transaction),
+                )
             } else {
-                let ret = self.pending_tx(entry.cache_entry, entry.size, entry.transaction);
-                if ret.is_err() {
-                    self.update_statics_for_remove_tx(
-                        entry.size,
-                        entry.cache_entry.map(|c| c.cycles).unwrap_or(0),
-                    );
-                    trace_target!(
-                        crate::LOG_TARGET_TX_POOL,
-                        "pending tx {} failed {:?}",
-                        tx_hash,
-                        ret
-                    );
-                }
+                (
+                    "pending",
+                    self.pending_tx(entry.cache_entry, entry.size, entry.transaction),
+                )
+            };
+            match ret {
+                Ok(cache_entry) => self.update_statics_for_add_tx(size, cache_entry.cycles),
+                Err(err) => trace_target!(
+                    crate::LOG_TARGET_TX_POOL,
+                    "{} tx {} failed {:?}",
+                    pool_name,
+                    tx_hash,
+                    err
+                ),
             }
         }
     }
@@ -507,11 +803,9 @@ impl TxPool {
                             .expect("error kind checked")
                         {
                             OutPointError::Dead(_) => {
-                                if self
-                                    .conflict
-                                    .insert(short_id, DefectEntry::new(tx, 0, cache_entry, size))
-                                    .is_some()
-                                {
+                                let now = self.unix_time_as_millis() / 1000;
+                                let entry = DefectEntry::new(tx, 0, cache_entry, size, now);
+                                if self.conflict.insert(short_id, entry).is_some() {
                                     self.update_statics_for_remove_tx(
                                         size,
                                         cache_entry.map(|c| c.cycles).unwrap_or(0),
@@ -589,7 +883,8 @@ impl TxPool {
             tx,
             tx_result,
             |tx_pool, cycles, fee, size, related_dep_out_points, tx| {
-                let entry = TxEntry::new(tx, cycles, fee, size, related_dep_out_points);
+                let entry = TxEntry::new(tx, cycles, fee, size, related_dep_out_points)
+                    .with_timestamp(tx_pool.unix_time_as_millis());
                 let tx_hash = entry.transaction.hash();
                 if tx_pool.add_gap(entry)? {
                     Ok(())
@@ -621,7 +916,8 @@ impl TxPool {
             tx,
             tx_result,
             |tx_pool, cycles, fee, size, related_dep_out_points, tx| {
-                let entry = TxEntry::new(tx, cycles, fee, size, related_dep_out_points);
+                let entry = TxEntry::new(tx, cycles, fee, size, related_dep_out_points)
+                    .with_timestamp(tx_pool.unix_time_as_millis());
                 tx_pool.add_proposed(entry)?;
                 Ok(())
             },
@@ -649,7 +945,8 @@ impl TxPool {
             tx,
             tx_result,
             |tx_pool, cycles, fee, size, related_dep_out_points, tx| {
-                let entry = TxEntry::new(tx, cycles, fee, size, related_dep_out_points);
+                let entry = TxEntry::new(tx, cycles, fee, size, related_dep_out_points)
+                    .with_timestamp(tx_pool.unix_time_as_millis());
                 let tx_hash = entry.transaction.hash();
                 if tx_pool.add_pending(entry)? {
                     Ok(())
@@ -744,3 +1041,39 @@ impl TxPool {
             })
     }
 }
+
+/// The fee rate decayed by half every `EVICTED_FEE_RATE_HALFLIFE_MS` after `elapsed_ms`.
+fn decay_fee_rate(fee_rate: FeeRate, elapsed_ms: u64) -> FeeRate {
+    let halves = elapsed_ms as f64 / EVICTED_FEE_RATE_HALFLIFE_MS as f64;
+    FeeRate::from_u64((fee_rate.as_u64() as f64 * 0.5f64.powf(halves)) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fee_histogram_bin() {
+        let bins = FeeHistogram::bin(vec![
+            (FeeRate::from_u64(1000), 100, 10),
+            (FeeRate::from_u64(1200), 200, 20),
+            (FeeRate::from_u64(1300), 300, 30),
+            (FeeRate::from_u64(500), 400, 40),
+            (FeeRate::from_u64(2000), 500, 50),
+        ]);
+        let bins: Vec<(u64, u64, Cycle)> = bins
+            .into_iter()
+            .map(|bin| (bin.fee_rate.as_u64(), bin.vbytes, bin.cycles))
+            .collect();
+        // the bins of 1000, 1250, 1562 and 1952
+        assert_eq!(
+            bins,
+            vec![
+                (1952, 500, 50),
+                (1250, 800, 80),
+                (1000, 1100, 110),
+                (0, 1500, 150)
+            ]
+        );
+    }
+}
@@ -1,15 +1,23 @@
-use crate::block_assembler::{BlockAssembler, BlockTemplateCacheKey, TemplateCache};
-use crate::component::commit_txs_scanner::CommitTxsScanner;
+use crate::block_assembler::{
+    sanitize_selection, BlockAssembler, BlockTemplateCacheKey, Candidates, SelectionLimits,
+    SelectionStrategy, TemplateBase, TemplateCache, DEFAULT_SELECTION_STRATEGY,
+};
 use crate::component::entry::TxEntry;
-use crate::error::{BlockAssemblerError, SubmitTxError};
+use crate::error::BlockAssemblerError;
+use crate::metrics::{ACCEPTED_FEE_RATE, PROCESSED_TXS, PROCESS_TXS_DURATION};
+use crate::persisted;
+use crate::policy::check_tx_policy;
 use crate::pool::TxPool;
-use crate::service::TxPoolService;
+use crate::service::{DryRunTxsResult, TxPoolService};
+use crate::tracker::LocalTxStatus;
 use ckb_app_config::BlockAssemblerConfig;
 use ckb_dao::DaoCalculator;
-use ckb_error::{Error, InternalErrorKind};
+use ckb_error::{Error, ErrorKind, InternalErrorKind};
 use ckb_fee_estimator::FeeRate;
 use ckb_jsonrpc_types::BlockTemplate;
 use ckb_logger::{debug_target, info};
+use ckb_notify::RejectedTransaction;
+use ckb_script::TracedTransactions;
 use ckb_snapshot::Snapshot;
 use ckb_store::ChainStore;
 use ckb_types::{
@@ -18,6 +26,7 @@ use ckb_types::{
             get_related_dep_out_points, resolve_transaction, OverlayCellProvider,
             ResolvedTransaction, TransactionsProvider,
         },
+        error::OutPointError,
         BlockView, Capacity, Cycle, EpochExt, ScriptHashType, TransactionView, UncleBlockView,
         Version,
     },
@@ -25,13 +34,18 @@ use ckb_types::{
     prelude::*,
 };
 use ckb_util::LinkedHashSet;
-use ckb_verification::{cache::CacheEntry, ContextualTransactionVerifier, TransactionVerifier};
+use ckb_verification::{
+    cache::CacheEntry, measure_verify_phase, ContextualTransactionVerifier,
+    NonContextualTransactionVerifier, TransactionVerifier, VerifyOrigin, VerifyPhase, VerifyPool,
+};
 use failure::Error as FailureError;
-use faketime::unix_time_as_millis;
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use std::collections::HashSet;
 use std::collections::{HashMap, VecDeque};
+use std::path::Path;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use std::{cmp, iter};
 use tokio::task::block_in_place;
 
@@ -49,26 +63,18 @@ pub enum TxStatus {
 impl TxPoolService {
     async fn get_block_template_cache(
         &self,
-        bytes_limit: u64,
-        proposals_limit: u64,
-        version: Version,
+        key: &BlockTemplateCacheKey,
         snapshot: &Snapshot,
         block_assembler: &BlockAssembler,
     ) -> Option<BlockTemplate> {
         let tip_header = snapshot.tip_header();
-        let tip_hash = tip_header.hash();
-        let current_time = cmp::max(unix_time_as_millis(), tip_header.timestamp() + 1);
+        let current_time = cmp::max(self.clock.unix_time_as_millis(), tip_header.timestamp() + 1);
 
         let last_uncles_updated_at = block_assembler
             .last_uncles_updated_at
             .load(Ordering::SeqCst);
         let last_txs_updated_at = self.last_txs_updated_at.load(Ordering::SeqCst);
-        if let Some(template_cache) = block_assembler.template_caches.lock().await.get(&(
-            tip_hash,
-            bytes_limit,
-            proposals_limit,
-            version,
-        )) {
+        if let Some(template_cache) = block_assembler.template_caches.lock().await.get(key) {
             // check template cache outdate time
             if !template_cache.is_outdate(current_time) {
                 let mut template = template_cache.template.clone();
@@ -86,6 +92,26 @@ impl TxPoolService {
         None
     }
 
+    // Returns the base of the cached template if the uncles have not changed since, so the
+    // template is rebuilt incrementally when only new transactions arrived.
+    async fn get_block_template_base(
+        &self,
+        key: &BlockTemplateCacheKey,
+        block_assembler: &BlockAssembler,
+    ) -> Option<TemplateBase> {
+        let last_uncles_updated_at = block_assembler
+            .last_uncles_updated_at
+            .load(Ordering::SeqCst);
+        block_assembler
+            .template_caches
+            .lock()
+            .await
+            .get_mut(key)
+            .map(|template_cache| &template_cache.base)
+            .filter(|base| base.uncles_updated_at == last_uncles_updated_at)
+            .cloned()
+    }
+
     fn build_block_template_cellbase(
         &self,
         snapshot: &Snapshot,
@@ -133,6 +159,7 @@ impl TxPoolService {
         max_block_cycles: Cycle,
         cellbase: &TransactionView,
         uncles: &[UncleBlockView],
+        strategy: &dyn SelectionStrategy,
     ) -> Result<(HashSet<ProposalShortId>, Vec<TxEntry>, u64), FailureError> {
         let guard = self.tx_pool.read().await;
         let uncle_proposals = uncles
@@ -148,11 +175,14 @@ impl TxPoolService {
             &proposals,
         )?;
 
-        let (entries, size, cycles) = CommitTxsScanner::new(guard.proposed()).txs_to_commit(
-            txs_size_limit,
-            max_block_cycles,
-            guard.config.min_fee_rate,
-        );
+        let candidates = Candidates::new(guard.proposed());
+        let limits = SelectionLimits {
+            size: txs_size_limit,
+            cycles: max_block_cycles,
+            min_fee_rate: guard.config.min_fee_rate,
+        };
+        let selected = strategy.select(&candidates, &limits);
+        let (entries, size, cycles) = sanitize_selection(&candidates, &limits, selected);
         if !entries.is_empty() {
             info!(
                 "[get_block_template] candidate txs count: {}, size: {}/{}, cycles:{}/{}",
@@ -210,8 +240,11 @@ impl TxPoolService {
         let cycles_limit = consensus.max_block_cycles();
         let uncles_count_limit = consensus.max_uncles_num() as u32;
 
+        let transactions_cycles: Cycle = entries.iter().map(|entry| entry.cycles).sum();
+        let transactions_fee: u64 = entries.iter().map(|entry| entry.fee.as_u64()).sum();
+
         // Should recalculate current time after create cellbase (create cellbase may spend a lot of time)
-        let current_time = cmp::max(unix_time_as_millis(), tip_header.timestamp() + 1);
+        let current_time = cmp::max(self.clock.unix_time_as_millis(), tip_header.timestamp() + 1);
 
         Ok(BlockTemplate {
             version: version.into(),
@@ -224,14 +257,15 @@ impl TxPoolService {
             bytes_limit: bytes_limit.into(),
             uncles_count_limit: u64::from(uncles_count_limit).into(),
             uncles: uncles.iter().map(BlockAssembler::transform_uncle).collect(),
-            transactions: entries
-                .iter()
-                .map(|entry| BlockAssembler::transform_tx(entry, false, None))
-                .collect(),
+            transactions: BlockAssembler::transform_txs(&entries),
             proposals: proposals.iter().cloned().map(Into::into).collect(),
             cellbase: BlockAssembler::transform_cellbase(&cellbase, None),
             work_id: work_id.into(),
             dao: dao.into(),
+            nonce_range: None,
+            longpoll_id: None,
+            transactions_cycles: transactions_cycles.into(),
+            transactions_fee: transactions_fee.into(),
         })
     }
 
@@ -239,17 +273,17 @@ impl TxPoolService {
         &self,
         block_assembler: &BlockAssembler,
         key: BlockTemplateCacheKey,
-        uncles_updated_at: u64,
         txs_updated_at: u64,
         template: BlockTemplate,
+        base: TemplateBase,
     ) {
         block_assembler.template_caches.lock().await.insert(
             key,
             TemplateCache {
                 time: template.current_time.into(),
-                uncles_updated_at,
                 txs_updated_at,
                 template,
+                base,
             },
         );
     }
@@ -259,6 +293,7 @@ impl TxPoolService {
         bytes_limit: Option<u64>,
         proposals_limit: Option<u64>,
         max_version: Option<Version>,
+        strategy: Option<String>,
     ) -> Result<BlockTemplate, FailureError> {
         if self.block_assembler.is_none() {
             Err(InternalErrorKind::System
@@ -276,34 +311,57 @@ impl TxPoolService {
                 max_version,
             );
 
+            let strategy_name = strategy.unwrap_or_else(|| DEFAULT_SELECTION_STRATEGY.to_owned());
+            let strategy = self
+                .selection_strategies
+                .get(&strategy_name)
+                .ok_or_else(|| {
+                    BlockAssemblerError::InvalidParams(format!(
+                        "unknown selection strategy {}",
+                        strategy_name
+                    ))
+                })?;
+            let key = (
+                snapshot.tip_hash(),
+                bytes_limit,
+                proposals_limit,
+                version,
+                strategy_name,
+            );
+
             if let Some(cache) = self
-                .get_block_template_cache(
-                    bytes_limit,
-                    proposals_limit,
-                    version,
-                    &snapshot,
-                    &block_assembler,
-                )
+                .get_block_template_cache(&key, &snapshot, &block_assembler)
                 .await
             {
                 return Ok(cache);
             }
 
-            let cellbase = block_in_place(|| {
-                self.build_block_template_cellbase(&snapshot, &block_assembler.config)
-            })?;
-
-            let (uncles, current_epoch, uncles_updated_at) = self
-                .prepare_block_template_uncles(&snapshot, &block_assembler)
-                .await;
+            let base = match self.get_block_template_base(&key, &block_assembler).await {
+                Some(base) => base,
+                None => {
+                    let cellbase = block_in_place(|| {
+                        self.build_block_template_cellbase(&snapshot, &block_assembler.config)
+                    })?;
+                    let (uncles, current_epoch, uncles_updated_at) = self
+                        .prepare_block_template_uncles(&snapshot, &block_assembler)
+                        .await;
+                    TemplateBase {
+                        cellbase,
+                        uncles,
+                        current_epoch,
+                        uncles_updated_at,
+                    }
+                }
+            };
 
             let (proposals, entries, txs_updated_at) = self
                 .package_txs_for_block_template(
                     bytes_limit,
                     proposals_limit,
                     cycles_limit,
-                    &cellbase,
-                    &uncles,
+                    &base.cellbase,
+                    &base.uncles,
+                    strategy.as_ref(),
                 )
                 .await?;
 
@@ -314,10 +372,10 @@ impl TxPoolService {
                     &snapshot,
                     entries,
                     proposals,
-                    cellbase,
+                    base.cellbase.clone(),
                     work_id,
-                    current_epoch,
-                    uncles,
+                    base.current_epoch.clone(),
+                    base.uncles.clone(),
                     bytes_limit,
                     version,
                 )
@@ -325,10 +383,10 @@ impl TxPoolService {
 
             self.update_block_template_cache(
                 &block_assembler,
-                (snapshot.tip_hash(), bytes_limit, proposals_limit, version),
-                uncles_updated_at,
+                key,
                 txs_updated_at,
                 block_template.clone(),
+                base,
             )
             .await;
 
@@ -336,24 +394,35 @@ impl TxPoolService {
         }
     }
 
-    async fn pre_resolve_txs(&self, txs: &[TransactionView]) -> Result<PreResolvedTxs, Error> {
+    async fn pre_resolve_txs(
+        &self,
+        txs: &[TransactionView],
+        origin: VerifyOrigin,
+    ) -> Result<PreResolvedTxs, Error> {
         let tx_pool = self.tx_pool.read().await;
 
         debug_assert!(!txs.is_empty(), "txs should not be empty!");
         let snapshot = tx_pool.cloned_snapshot();
         let tip_hash = snapshot.tip_hash();
 
+        // reject obviously malformed txs before touching any cell
+        measure_verify_phase(origin, VerifyPhase::NonContextual, txs.len(), || {
+            txs.iter().try_for_each(|tx| {
+                NonContextualTransactionVerifier::new(tx, snapshot.consensus()).verify()
+            })
+        })?;
         check_transaction_hash_collision(&tx_pool, txs)?;
 
         let mut txs_provider = TransactionsProvider::default();
-        let resolved = txs
-            .iter()
-            .map(|tx| {
-                let ret = resolve_tx(&tx_pool, &snapshot, &txs_provider, tx.clone());
-                txs_provider.insert(tx);
-                ret
-            })
-            .collect::<Result<Vec<(ResolvedTransaction, usize, Capacity, TxStatus)>, _>>()?;
+        let resolved = measure_verify_phase(origin, VerifyPhase::Resolve, txs.len(), || {
+            txs.iter()
+                .map(|tx| {
+                    let ret = resolve_tx(&tx_pool, &snapshot, &txs_provider, tx.clone());
+                    txs_provider.insert(tx);
+                    ret
+                })
+                .collect::<Result<Vec<(ResolvedTransaction, usize, Capacity, TxStatus)>, _>>()
+        })?;
 
         let (rtxs, status) = resolved
             .into_iter()
@@ -367,12 +436,9 @@ impl TxPoolService {
         &self,
         txs: impl Iterator<Item = &TransactionView>,
     ) -> HashMap<Byte32, CacheEntry> {
-        let guard = self.txs_verify_cache.read().await;
-        txs.filter_map(|tx| {
-            let hash = tx.hash();
-            guard.get(&hash).cloned().map(|value| (hash, value))
-        })
-        .collect()
+        self.txs_verify_cache
+            .fetch(txs.map(TransactionView::hash))
+            .await
     }
 
     async fn submit_txs(
@@ -399,20 +465,33 @@ impl TxPoolService {
                 return Err(InternalErrorKind::TransactionPoolFull.into());
             }
 
-            let min_fee = tx_pool.config.min_fee_rate.fee(tx_size);
-            // reject txs which fee lower than min fee rate
-            if fee < min_fee {
-                return Err(SubmitTxError::LowFeeRate(min_fee.as_u64()).into());
-            }
+            let min_fee_rate = tx_pool.min_entry_fee_rate();
+            check_tx_policy(
+                &tx_pool.config,
+                min_fee_rate,
+                &rtx.transaction,
+                tx_size,
+                fee,
+            )?;
 
             let related_dep_out_points = rtx.related_dep_out_points();
+            // the planned packages are evicted only once the tx is added, so they stay if it fails
+            let eviction = tx_pool.plan_eviction_for_tx(
+                &rtx.transaction,
+                &related_dep_out_points,
+                tx_size,
+                cache_entry.cycles,
+                fee,
+            )?;
             let entry = TxEntry::new(
                 rtx.transaction,
                 cache_entry.cycles,
                 fee,
                 tx_size,
                 related_dep_out_points,
-            );
+            )
+            .with_timestamp(tx_pool.unix_time_as_millis());
+            let tx = entry.transaction.clone();
             let inserted = match status {
                 TxStatus::Fresh => {
                     let tx_hash = entry.transaction.hash();
@@ -428,22 +507,156 @@ impl TxPoolService {
                 TxStatus::Proposed => tx_pool.add_proposed(entry)?,
             };
             if inserted {
+                if let Some((roots, evicted_fee_rate)) = eviction {
+                    let evicted = tx_pool.evict(&tx.hash(), roots, evicted_fee_rate);
+                    self.notify_evicted(&tx, evicted, tx_pool.min_entry_fee_rate());
+                }
                 tx_pool.update_statics_for_add_tx(tx_size, cache_entry.cycles);
+                ACCEPTED_FEE_RATE.observe(FeeRate::calculate(fee, tx_size).as_u64() as f64);
+                self.notify_controller.notify_new_transaction(tx);
             }
         }
+        tx_pool.update_metrics();
         Ok(())
     }
 
+    fn notify_evicted(&self, tx: &TransactionView, evicted: Vec<TxEntry>, min_fee_rate: FeeRate) {
+        if evicted.is_empty() {
+            return;
+        }
+        let reason = format!(
+            "evicted by {} for the full pool, the min entry fee rate is {}",
+            tx.hash(),
+            min_fee_rate
+        );
+        let mut local_txs = self.local_txs.lock();
+        for entry in evicted {
+            local_txs.reject(&entry.transaction.hash(), reason.clone());
+            self.notify_controller
+                .notify_rejected_transaction(RejectedTransaction {
+                    transaction: entry.transaction,
+                    reason: reason.clone(),
+                });
+        }
+    }
+
+    /// Verifies the txs and adds them to the pool, then submits again the orphans whose missing
+    /// parents are among the accepted txs.
     pub(crate) async fn process_txs(
         &self,
         txs: Vec<TransactionView>,
+        origin: VerifyOrigin,
+    ) -> Result<Vec<CacheEntry>, Error> {
+        let result = self.process_txs_once(txs.clone(), origin).await;
+        if result.is_ok() {
+            self.process_orphans(txs).await;
+        }
+        result
+    }
+
+    // The orphans of a parent are returned with their own orphan descendants, parents first, so
+    // the parents of the nested orphans are submitted before them.
+    async fn process_orphans(&self, parents: Vec<TransactionView>) {
+        for parent in parents {
+            let orphans = self
+                .tx_pool
+                .write()
+                .await
+                .orphan
+                .remove_by_ancestor(&parent);
+            for orphan in orphans {
+                let tx_hash = orphan.transaction.hash();
+                if let Err(err) = self
+                    .process_txs_once(vec![orphan.transaction], VerifyOrigin::Orphan)
+                    .await
+                {
+                    debug_target!(
+                        crate::LOG_TARGET_TX_POOL,
+                        "evict orphan tx {:#x}: {}",
+                        tx_hash,
+                        err
+                    );
+                }
+            }
+        }
+    }
+
+    async fn process_txs_once(
+        &self,
+        txs: Vec<TransactionView>,
+        origin: VerifyOrigin,
     ) -> Result<Vec<CacheEntry>, Error> {
-        let max_tx_verify_cycles = self.tx_pool_config.max_tx_verify_cycles;
-        let (tip_hash, snapshot, rtxs, status) = self.pre_resolve_txs(&txs).await?;
+        let started_at = Instant::now();
+        let count = txs.len() as u64;
+        let result = self.verify_and_submit_txs(&txs, origin).await;
+        PROCESS_TXS_DURATION.observe_duration(started_at.elapsed());
+        let label = match result {
+            Ok(_) => "accepted",
+            Err(ref err) if self.add_orphan(&txs, err).await => "orphan",
+            Err(ref err) => {
+                let reason = err.to_string();
+                {
+                    let mut local_txs = self.local_txs.lock();
+                    for tx in &txs {
+                        local_txs.reject(&tx.hash(), reason.clone());
+                    }
+                }
+                for tx in txs {
+                    self.notify_controller
+                        .notify_rejected_transaction(RejectedTransaction {
+                            transaction: tx,
+                            reason: reason.clone(),
+                        });
+                }
+                "rejected"
+            }
+        };
+        PROCESSED_TXS
+            .with_label_values(&[origin.as_str(), label])
+            .inc_by(count);
+        result
+    }
+
+    // Keeps a single tx whose inputs are unknown in the orphan pool, returns whether it is kept.
+    // The tx is still reported with the error, since it is not in the pool yet.
+    async fn add_orphan(&self, txs: &[TransactionView], err: &Error) -> bool {
+        let max_orphan_txs = self.tx_pool_config.read().max_orphan_txs;
+        if max_orphan_txs == 0 || txs.len() != 1 || *err.kind() != ErrorKind::OutPoint {
+            return false;
+        }
+        let unknowns = match err.downcast_ref::<OutPointError>() {
+            Some(OutPointError::Unknown(out_points)) => out_points.to_owned(),
+            _ => return false,
+        };
+        let tx = txs[0].clone();
+        let size = tx.data().serialized_size_in_block();
+        let mut tx_pool = self.tx_pool.write().await;
+        tx_pool.add_orphan(None, size, tx, unknowns);
+        tx_pool.update_metrics();
+        true
+    }
+
+    async fn verify_and_submit_txs(
+        &self,
+        txs: &[TransactionView],
+        origin: VerifyOrigin,
+    ) -> Result<Vec<CacheEntry>, Error> {
+        let tx_pool_config = *self.tx_pool_config.read();
+        let (tip_hash, snapshot, rtxs, status) = self.pre_resolve_txs(txs, origin).await?;
         let fetched_cache = self.fetch_txs_verify_cache(txs.iter()).await;
 
-        let verified =
-            block_in_place(|| verify_rtxs(&snapshot, rtxs, &fetched_cache, max_tx_verify_cycles))?;
+        let verified = block_in_place(|| {
+            verify_rtxs(
+                &snapshot,
+                rtxs,
+                &fetched_cache,
+                tx_pool_config.max_tx_verify_cycles,
+                tx_pool_config.max_tx_verify_time(),
+                &self.verify_pool,
+                origin,
+                &self.traced_transactions,
+            )
+        })?;
 
         let updated_cache = verified
             .iter()
@@ -455,14 +668,167 @@ impl TxPoolService {
 
         let txs_verify_cache = Arc::clone(&self.txs_verify_cache);
         tokio::spawn(async move {
-            let mut guard = txs_verify_cache.write().await;
-            for (k, v) in updated_cache {
-                guard.insert(k, v);
-            }
+            txs_verify_cache.insert_batch(updated_cache).await;
         });
         Ok(cycles_vec)
     }
 
+    /// Verifies the txs as `process_txs` does, scripts included, but leaves the pool untouched.
+    /// A tx may spend the outputs of the txs before it, and is rejected if any of them is.
+    /// Returns the cycles and fee of every tx, or the reason it would be rejected.
+    pub(crate) async fn dry_run_txs(&self, txs: Vec<TransactionView>) -> DryRunTxsResult {
+        let tx_pool_config = *self.tx_pool_config.read();
+        let fetched_cache = self.fetch_txs_verify_cache(txs.iter()).await;
+
+        // resolves the txs under the lock, and verifies them after releasing it
+        let (snapshot, resolved) = {
+            let tx_pool = self.tx_pool.read().await;
+            let snapshot = tx_pool.cloned_snapshot();
+            let mut txs_provider = TransactionsProvider::default();
+            let resolved: Vec<_> = txs
+                .iter()
+                .map(|tx| {
+                    let ret = NonContextualTransactionVerifier::new(tx, snapshot.consensus())
+                        .verify()
+                        .and_then(|_| check_transaction_hash_collision(&tx_pool, &[tx.clone()]))
+                        .and_then(|_| resolve_tx(&tx_pool, &snapshot, &txs_provider, tx.clone()))
+                        .and_then(|(rtx, tx_size, fee, _)| {
+                            check_tx_policy(
+                                &tx_pool.config,
+                                tx_pool.min_entry_fee_rate(),
+                                &rtx.transaction,
+                                tx_size,
+                                fee,
+                            )?;
+                            Ok(rtx)
+                        });
+                    if ret.is_ok() {
+                        txs_provider.insert(tx);
+                    }
+                    ret
+                })
+                .collect();
+            (snapshot, resolved)
+        };
+
+        let mut rejected = HashSet::new();
+        let mut results = Vec::with_capacity(resolved.len());
+        for (tx, resolved) in txs.iter().zip(resolved.into_iter()) {
+            let result = resolved.and_then(|rtx| {
+                let rejected_inputs: Vec<OutPoint> = tx
+                    .input_pts_iter()
+                    .filter(|out_point| rejected.contains(&out_point.tx_hash()))
+                    .collect();
+                if !rejected_inputs.is_empty() {
+                    return Err(OutPointError::Unknown(rejected_inputs).into());
+                }
+                let verified = block_in_place(|| {
+                    verify_rtxs(
+                        &snapshot,
+                        vec![rtx],
+                        &fetched_cache,
+                        tx_pool_config.max_tx_verify_cycles,
+                        tx_pool_config.max_tx_verify_time(),
+                        &self.verify_pool,
+                        VerifyOrigin::DryRun,
+                        &self.traced_transactions,
+                    )
+                })?;
+                Ok(verified
+                    .into_iter()
+                    .next()
+                    .map(|(_, cache_entry)| cache_entry)
+                    .expect("verified one tx"))
+            });
+            if result.is_err() {
+                rejected.insert(tx.hash());
+            }
+            results.push(result);
+        }
+        results
+    }
+
+    /// Writes the pool entries to `path`, returns the number of the written entries.
+    pub(crate) async fn save_pool(&self, path: &Path) -> Result<usize, Error> {
+        let entries = self.tx_pool.read().await.persisted_entries();
+        block_in_place(|| persisted::write_file(path, &entries))?;
+        Ok(entries.len())
+    }
+
+    /// Submits the entries in `path` again, returns the numbers of the accepted and the
+    /// rejected entries. The entries are verified one by one, so an entry invalidated by the
+    /// blocks committed in the meantime does not reject the others.
+    pub(crate) async fn load_pool(&self, path: &Path) -> Result<(usize, usize), Error> {
+        let entries = block_in_place(|| persisted::read_file(path))?;
+        self.txs_verify_cache
+            .insert_batch(
+                entries
+                    .iter()
+                    .map(|(tx, cache_entry)| (tx.hash(), *cache_entry)),
+            )
+            .await;
+        let (mut accepted, mut rejected) = (0, 0);
+        for (tx, _) in entries {
+            let tx_hash = tx.hash();
+            match self.process_txs(vec![tx], VerifyOrigin::Persisted).await {
+                Ok(_) => accepted += 1,
+                Err(err) => {
+                    debug_target!(
+                        crate::LOG_TARGET_TX_POOL,
+                        "reject persisted tx {:#x}: {}",
+                        tx_hash,
+                        err
+                    );
+                    rejected += 1;
+                }
+            }
+        }
+        Ok((accepted, rejected))
+    }
+
+    // The chain and the pool are read under the pool lock, so a transaction being committed is
+    // either in the pool or in the chain.
+    pub(crate) async fn local_tx_status(&self, tx_hash: &Byte32) -> Option<LocalTxStatus> {
+        let tx_pool = self.tx_pool.read().await;
+        let committed = tx_pool
+            .snapshot()
+            .get_transaction_info(tx_hash)
+            .map(|info| info.block_hash);
+        let id = ProposalShortId::from_tx_hash(tx_hash);
+        let in_pool = tx_pool
+            .proposed()
+            .get(&id)
+            .map(|entry| (true, entry.transaction.hash()))
+            .or_else(|| {
+                tx_pool
+                    .get_tx_without_conflict(&id)
+                    .map(|tx| (false, tx.hash()))
+            })
+            .filter(|(_, hash)| hash == tx_hash)
+            .map(|(proposed, _)| proposed);
+        self.local_txs.lock().status(tx_hash, committed, in_pool)
+    }
+
+    pub(crate) async fn local_txs_to_rebroadcast(&self) -> Vec<Byte32> {
+        let (after_blocks, max_rebroadcasts) = {
+            let config = self.tx_pool_config.read();
+            (config.rebroadcast_after_blocks, config.max_rebroadcasts)
+        };
+        let tx_pool = self.tx_pool.read().await;
+        let tip_number = tx_pool.snapshot().tip_number();
+        self.local_txs.lock().take_rebroadcasts(
+            tip_number,
+            after_blocks,
+            max_rebroadcasts,
+            |tx_hash| {
+                tx_pool
+                    .get_tx_without_conflict(&ProposalShortId::from_tx_hash(tx_hash))
+                    .map(|tx| &tx.hash() == tx_hash)
+                    .unwrap_or(false)
+            },
+        )
+    }
+
     pub(crate) async fn update_tx_pool_for_reorg(
         &self,
         detached_blocks: VecDeque<BlockView>,
@@ -492,13 +858,11 @@ impl TxPoolService {
                 snapshot,
             )
         });
+        tx_pool.update_metrics();
 
         let txs_verify_cache = Arc::clone(&self.txs_verify_cache);
         tokio::spawn(async move {
-            let mut guard = txs_verify_cache.write().await;
-            for (k, v) in updated_cache {
-                guard.insert(k, v);
-            }
+            txs_verify_cache.insert_batch(updated_cache).await;
         });
     }
 }
@@ -532,27 +896,29 @@ fn resolve_tx<'a>(
     tx: TransactionView,
 ) -> ResolveResult {
     let tx_size = tx.data().serialized_size_in_block();
-    if tx_pool.reach_size_limit(tx_size) {
-        return Err(InternalErrorKind::TransactionPoolFull.into());
-    }
 
     let short_id = tx.proposal_short_id();
-    if snapshot.proposals().contains_proposed(&short_id) {
-        resolve_tx_from_proposed(tx_pool, snapshot, txs_provider, tx).and_then(|rtx| {
-            let fee = tx_pool.calculate_transaction_fee(snapshot, &rtx);
-            fee.map(|fee| (rtx, tx_size, fee, TxStatus::Proposed))
-        })
+    let (rtx, status) = if snapshot.proposals().contains_proposed(&short_id) {
+        let rtx = resolve_tx_from_proposed(tx_pool, snapshot, txs_provider, tx)?;
+        (rtx, TxStatus::Proposed)
     } else {
-        resolve_tx_from_pending_and_proposed(tx_pool, snapshot, txs_provider, tx).and_then(|rtx| {
-            let status = if snapshot.proposals().contains_gap(&short_id) {
-                TxStatus::Gap
-            } else {
-                TxStatus::Fresh
-            };
-            let fee = tx_pool.calculate_transaction_fee(snapshot, &rtx);
-            fee.map(|fee| (rtx, tx_size, fee, status))
-        })
-    }
+        let rtx = resolve_tx_from_pending_and_proposed(tx_pool, snapshot, txs_provider, tx)?;
+        let status = if snapshot.proposals().contains_gap(&short_id) {
+            TxStatus::Gap
+        } else {
+            TxStatus::Fresh
+        };
+        (rtx, status)
+    };
+    let fee = tx_pool.calculate_transaction_fee(snapshot, &rtx)?;
+    // a full pool rejects the tx before its scripts run if it can't make room anyway
+    tx_pool.check_room_for_tx(
+        &rtx.transaction,
+        &rtx.related_dep_out_points(),
+        tx_size,
+        fee,
+    )?;
+    Ok((rtx, tx_size, fee, status))
 }
 
 fn resolve_tx_from_proposed<'a>(
@@ -580,46 +946,56 @@ fn resolve_tx_from_pending_and_proposed<'a>(
     resolve_transaction(tx, &mut HashSet::new(), &provider, snapshot)
 }
 
+#[allow(clippy::too_many_arguments)]
 fn verify_rtxs(
     snapshot: &Snapshot,
     txs: Vec<ResolvedTransaction>,
     txs_verify_cache: &HashMap<Byte32, CacheEntry>,
     max_tx_verify_cycles: Cycle,
+    max_tx_verify_time: Option<Duration>,
+    verify_pool: &VerifyPool,
+    origin: VerifyOrigin,
+    traced_transactions: &TracedTransactions,
 ) -> Result<Vec<(ResolvedTransaction, CacheEntry)>, Error> {
     let tip_header = snapshot.tip_header();
     let tip_number = tip_header.number();
     let epoch = tip_header.epoch();
     let consensus = snapshot.consensus();
 
-    txs.into_iter()
-        .map(|tx| {
-            let tx_hash = tx.transaction.hash();
-            if let Some(cache_entry) = txs_verify_cache.get(&tx_hash) {
-                ContextualTransactionVerifier::new(
-                    &tx,
-                    snapshot,
-                    tip_number + 1,
-                    epoch,
-                    tip_header.hash(),
-                    consensus,
-                )
-                .verify()
-                .map(|_| (tx, *cache_entry))
-            } else {
-                TransactionVerifier::new(
-                    &tx,
-                    snapshot,
-                    tip_number + 1,
-                    epoch,
-                    tip_header.hash(),
-                    consensus,
-                    snapshot,
-                )
-                .verify(max_tx_verify_cycles)
-                .map(|cycles| (tx, cycles))
-            }
-        })
-        .collect::<Result<Vec<_>, _>>()
+    verify_pool.install(|| {
+        txs.into_par_iter()
+            .map(|tx| {
+                let tx_hash = tx.transaction.hash();
+                if let Some(cache_entry) = txs_verify_cache.get(&tx_hash) {
+                    ContextualTransactionVerifier::new(
+                        &tx,
+                        snapshot,
+                        tip_number + 1,
+                        epoch,
+                        tip_header.hash(),
+                        consensus,
+                    )
+                    .origin(origin)
+                    .verify()
+                    .map(|_| (tx, *cache_entry))
+                } else {
+                    TransactionVerifier::new(
+                        &tx,
+                        snapshot,
+                        tip_number + 1,
+                        epoch,
+                        tip_header.hash(),
+                        consensus,
+                        snapshot,
+                    )
+                    .origin(origin)
+                    .trace_syscalls(traced_transactions.contains(&tx_hash))
+                    .verify_with_time_limit(max_tx_verify_cycles, max_tx_verify_time)
+                    .map(|cycles| (tx, cycles))
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()
+    })
 }
 
 fn _update_tx_pool_for_reorg(
@@ -1,39 +1,75 @@
-use crate::block_assembler::BlockAssembler;
-use crate::component::entry::TxEntry;
-use crate::error::handle_try_send_error;
-use crate::pool::{TxPool, TxPoolInfo};
+use crate::block_assembler::{BlockAssembler, SelectionStrategies, SelectionStrategy};
+use crate::component::entry::{TxEntry, TxPackageInfo};
+use crate::error::{handle_try_send_error, TrySendError};
+use crate::longpoll::{LongpollId, LongpollWaiters};
+use crate::metrics::{QUEUE_DEPTH, QUEUE_FULL};
+use crate::pool::{FeeHistogram, TxPool, TxPoolInfo};
 use crate::process::PlugTarget;
+use crate::tracker::{LocalTxStatus, LocalTxTracker};
 use ckb_app_config::{BlockAssemblerConfig, TxPoolConfig};
 use ckb_async_runtime::{new_runtime, Handle};
+use ckb_clock::Clock;
 use ckb_error::Error;
-use ckb_fee_estimator::FeeRate;
+use ckb_fee_estimator::{EstimateMode, FeeRate};
 use ckb_jsonrpc_types::BlockTemplate;
-use ckb_logger::error;
+use ckb_logger::{error, info};
+use ckb_notify::NotifyController;
+use ckb_script::TracedTransactions;
 use ckb_snapshot::{Snapshot, SnapshotMgr};
 use ckb_stop_handler::{SignalSender, StopHandler};
 use ckb_types::{
     core::{BlockView, Cycle, TransactionView, UncleBlockView, Version},
-    packed::ProposalShortId,
+    packed::{Byte32, ProposalShortId},
+    prelude::*,
+};
+use ckb_verification::{
+    cache::{CacheEntry, TxVerifyCache},
+    VerifyOrigin, VerifyPool,
 };
-use ckb_verification::cache::{CacheEntry, TxVerifyCache};
 use failure::Error as FailureError;
-use faketime::unix_time_as_millis;
 use std::collections::{HashMap, HashSet, VecDeque};
+use std::future::Future;
+use std::path::PathBuf;
 use std::sync::atomic::Ordering;
 use std::sync::{atomic::AtomicU64, Arc};
+use std::time::Duration;
+use tokio::sync::mpsc::error::TrySendError as TokioTrySendError;
 use tokio::sync::{mpsc, oneshot, RwLock};
 
 pub const DEFAULT_CHANNEL_SIZE: usize = 512;
 
+/// Delivers the response to a blocking caller, or to the future of an async caller.
+pub enum Responder<R> {
+    Blocking(crossbeam_channel::Sender<R>),
+    Async(oneshot::Sender<R>),
+}
+
+impl<R> Responder<R> {
+    /// Fails if the caller has gone, the response is handed back in the error.
+    pub fn send(self, response: R) -> Result<(), crossbeam_channel::SendError<R>> {
+        match self {
+            Responder::Blocking(sender) => sender.send(response),
+            Responder::Async(sender) => sender.send(response).map_err(crossbeam_channel::SendError),
+        }
+    }
+}
+
 pub struct Request<A, R> {
-    pub responder: crossbeam_channel::Sender<R>,
+    pub responder: Responder<R>,
     pub arguments: A,
 }
 
 impl<A, R> Request<A, R> {
     pub fn call(arguments: A, responder: crossbeam_channel::Sender<R>) -> Request<A, R> {
         Request {
-            responder,
+            responder: Responder::Blocking(responder),
+            arguments,
+        }
+    }
+
+    pub fn call_async(arguments: A, responder: oneshot::Sender<R>) -> Request<A, R> {
+        Request {
+            responder: Responder::Async(responder),
             arguments,
         }
     }
@@ -50,9 +86,12 @@ impl<A> Notify<A> {
 }
 
 pub type BlockTemplateResult = Result<BlockTemplate, FailureError>;
-type BlockTemplateArgs = (Option<u64>, Option<u64>, Option<Version>);
+/// The bytes limit, the proposals limit, the max version and the name of the selection strategy
+type BlockTemplateArgs = (Option<u64>, Option<u64>, Option<Version>, Option<String>);
 
 pub type SubmitTxsResult = Result<Vec<CacheEntry>, Error>;
+/// The cycles and fee of every tx, or the reason it would be rejected
+pub type DryRunTxsResult = Vec<Result<CacheEntry, Error>>;
 type NotifyTxsCallback = Option<Box<dyn FnOnce(SubmitTxsResult) + Send + Sync + 'static>>;
 
 type FetchTxRPCResult = Option<(bool, TransactionView)>;
@@ -69,23 +108,38 @@ pub type ChainReorgArgs = (
 pub enum Message {
     BlockTemplate(Request<BlockTemplateArgs, BlockTemplateResult>),
     SubmitTxs(Request<Vec<TransactionView>, SubmitTxsResult>),
+    DryRunTxs(Request<Vec<TransactionView>, DryRunTxsResult>),
     NotifyTxs(Notify<(Vec<TransactionView>, NotifyTxsCallback)>),
     ChainReorg(Notify<ChainReorgArgs>),
     FreshProposalsFilter(Request<Vec<ProposalShortId>, Vec<ProposalShortId>>),
     FetchTxs(Request<Vec<ProposalShortId>, HashMap<ProposalShortId, TransactionView>>),
     FetchTxsWithCycles(Request<Vec<ProposalShortId>, FetchTxsWithCyclesResult>),
+    FetchTxPackageInfo(Request<Vec<ProposalShortId>, HashMap<ProposalShortId, TxPackageInfo>>),
     GetTxPoolInfo(Request<(), TxPoolInfo>),
     FetchTxRPC(Request<ProposalShortId, Option<(bool, TransactionView)>>),
     NewUncle(Notify<UncleBlockView>),
     PlugEntry(Request<(Vec<TxEntry>, PlugTarget), ()>),
-    EstimateFeeRate(Request<usize, FeeRate>),
+    EstimateFeeRate(Request<(usize, EstimateMode), FeeRate>),
+    UpdateConfig(Notify<TxPoolConfig>),
+    SavePool(Request<PathBuf, Result<usize, Error>>),
+    LoadPool(Request<PathBuf, Result<(usize, usize), Error>>),
+    Longpoll(Request<(LongpollId, Duration), ()>),
+    LocalTxStatus(Request<Byte32, Option<LocalTxStatus>>),
+    Inventory(Request<usize, Vec<Byte32>>),
+    FeeHistogram(Request<(), FeeHistogram>),
+    LocalTxsToRebroadcast(Request<(), Vec<Byte32>>),
 }
 
 #[derive(Clone)]
 pub struct TxPoolController {
     sender: mpsc::Sender<Message>,
+    // the messages of the chain service, see `send_chain_message`
+    chain_sender: mpsc::UnboundedSender<Message>,
     handle: Handle,
     stop: StopHandler<()>,
+    config: Arc<ckb_util::RwLock<TxPoolConfig>>,
+    persisted_path: Arc<ckb_util::Mutex<Option<PathBuf>>>,
+    selection_strategies: SelectionStrategies,
 }
 
 impl Drop for TxPoolController {
@@ -99,31 +153,126 @@ impl TxPoolController {
         &self.handle
     }
 
+    /// Returns the handler which stops the tx-pool service on shutdown.
+    pub fn stop_handler(&self) -> StopHandler<()> {
+        self.stop.clone()
+    }
+
     pub fn get_block_template(
         &self,
         bytes_limit: Option<u64>,
         proposals_limit: Option<u64>,
         max_version: Option<Version>,
     ) -> Result<BlockTemplateResult, FailureError> {
-        let mut sender = self.sender.clone();
+        self.get_block_template_with_strategy(bytes_limit, proposals_limit, max_version, None)
+    }
+
+    /// Same as `get_block_template`, but commits the transactions selected by the strategy of
+    /// the name, see `register_selection_strategy`. `None` is the default strategy.
+    pub fn get_block_template_with_strategy(
+        &self,
+        bytes_limit: Option<u64>,
+        proposals_limit: Option<u64>,
+        max_version: Option<Version>,
+        strategy: Option<String>,
+    ) -> Result<BlockTemplateResult, FailureError> {
         let (responder, response) = crossbeam_channel::bounded(1);
-        let request = Request::call((bytes_limit, proposals_limit, max_version), responder);
-        sender
-            .try_send(Message::BlockTemplate(request))
-            .map_err(|e| {
-                let (_m, e) = handle_try_send_error(e);
-                e
-            })?;
+        let request = Request::call(
+            (bytes_limit, proposals_limit, max_version, strategy),
+            responder,
+        );
+        self.send_message(Message::BlockTemplate(request))?;
         response.recv().map_err(Into::into)
     }
 
+    /// The async variant of `get_block_template_with_strategy`, waits for the room in the channel
+    /// instead of failing when the pool is busy.
+    pub fn get_block_template_async(
+        &self,
+        bytes_limit: Option<u64>,
+        proposals_limit: Option<u64>,
+        max_version: Option<Version>,
+        strategy: Option<String>,
+    ) -> impl Future<Output = Result<BlockTemplateResult, FailureError>> + Send + 'static {
+        let (responder, response) = oneshot::channel();
+        let request = Request::call_async(
+            (bytes_limit, proposals_limit, max_version, strategy),
+            responder,
+        );
+        self.call_async(Message::BlockTemplate(request), response)
+    }
+
+    /// Returns the block template once the tip or the pool has changed since the template of
+    /// `longpoll_id`, or once `timeout` expires. Returns the template immediately without an id.
+    pub fn get_block_template_longpoll(
+        &self,
+        longpoll_id: Option<LongpollId>,
+        timeout: Duration,
+        bytes_limit: Option<u64>,
+        proposals_limit: Option<u64>,
+        max_version: Option<Version>,
+        strategy: Option<String>,
+    ) -> impl Future<Output = Result<BlockTemplateResult, FailureError>> + Send + 'static {
+        let longpoll = longpoll_id.map(|longpoll_id| {
+            let (responder, response) = oneshot::channel();
+            let request = Request::call_async((longpoll_id, timeout), responder);
+            self.call_async(Message::Longpoll(request), response)
+        });
+        let get_block_template =
+            self.get_block_template_async(bytes_limit, proposals_limit, max_version, strategy);
+        async move {
+            if let Some(longpoll) = longpoll {
+                longpoll.await?;
+            }
+            get_block_template.await
+        }
+    }
+
+    /// Registers the strategy selecting the transactions of the block templates under the name,
+    /// replaces the one of the same name, including the built-in `ancestors_score` and `fifo`.
+    pub fn register_selection_strategy(&self, name: String, strategy: Arc<dyn SelectionStrategy>) {
+        self.selection_strategies.register(name, strategy);
+    }
+
+    pub fn has_selection_strategy(&self, name: &str) -> bool {
+        self.selection_strategies.contains(name)
+    }
+
+    /// Saves the pool to the path once the service is stopped, after the queued and the
+    /// in-flight messages are processed, so the transactions being submitted are not lost.
+    pub fn persist_on_stop(&self, path: PathBuf) {
+        *self.persisted_path.lock() = Some(path);
+    }
+
+    /// The config in effect, including the updates by `update_config`.
+    pub fn config(&self) -> TxPoolConfig {
+        *self.config.read()
+    }
+
+    /// Applies the reload-safe options of the config: the min fee rate, the size, cycles and
+    /// tx verify cycles limits, the queue timeout, and the min output capacity and max tx size.
+    /// The others only take effect after restart.
+    pub fn update_config(&self, config: TxPoolConfig) -> Result<(), FailureError> {
+        let updated = {
+            let mut current = self.config.write();
+            current.min_fee_rate = config.min_fee_rate;
+            current.max_mem_size = config.max_mem_size;
+            current.max_cycles = config.max_cycles;
+            current.max_tx_verify_cycles = config.max_tx_verify_cycles;
+            current.queue_timeout_millis = config.queue_timeout_millis;
+            current.min_output_capacity = config.min_output_capacity;
+            current.max_tx_size = config.max_tx_size;
+            *current
+        };
+        let notify = Notify::notify(updated);
+        self.send_message(Message::UpdateConfig(notify))
+            .map_err(Into::into)
+    }
+
     pub fn notify_new_uncle(&self, uncle: UncleBlockView) -> Result<(), FailureError> {
-        let mut sender = self.sender.clone();
         let notify = Notify::notify(uncle);
-        sender.try_send(Message::NewUncle(notify)).map_err(|e| {
-            let (_m, e) = handle_try_send_error(e);
-            e.into()
-        })
+        self.send_chain_message(Message::NewUncle(notify))
+            .map_err(Into::into)
     }
 
     pub fn update_tx_pool_for_reorg(
@@ -133,42 +282,61 @@ impl TxPoolController {
         detached_proposal_id: HashSet<ProposalShortId>,
         snapshot: Arc<Snapshot>,
     ) -> Result<(), FailureError> {
-        let mut sender = self.sender.clone();
         let notify = Notify::notify((
             detached_blocks,
             attached_blocks,
             detached_proposal_id,
             snapshot,
         ));
-        sender.try_send(Message::ChainReorg(notify)).map_err(|e| {
-            let (_m, e) = handle_try_send_error(e);
-            e.into()
-        })
+        self.send_chain_message(Message::ChainReorg(notify))
+            .map_err(Into::into)
     }
 
     pub fn submit_txs(&self, txs: Vec<TransactionView>) -> Result<SubmitTxsResult, FailureError> {
-        let mut sender = self.sender.clone();
         let (responder, response) = crossbeam_channel::bounded(1);
         let request = Request::call(txs, responder);
-        sender.try_send(Message::SubmitTxs(request)).map_err(|e| {
-            let (_m, e) = handle_try_send_error(e);
-            e
-        })?;
+        self.send_message(Message::SubmitTxs(request))?;
         response.recv().map_err(Into::into)
     }
 
+    /// The async variant of `submit_txs`, waits for the room in the channel instead of failing
+    /// when the pool is busy.
+    pub fn submit_txs_async(
+        &self,
+        txs: Vec<TransactionView>,
+    ) -> impl Future<Output = Result<SubmitTxsResult, FailureError>> + Send + 'static {
+        let (responder, response) = oneshot::channel();
+        let request = Request::call_async(txs, responder);
+        self.call_async(Message::SubmitTxs(request), response)
+    }
+
+    /// Verifies the txs, scripts included, without adding them to the pool. A tx may spend the
+    /// outputs of the txs before it.
+    pub fn dry_run_txs(&self, txs: Vec<TransactionView>) -> Result<DryRunTxsResult, FailureError> {
+        let (responder, response) = crossbeam_channel::bounded(1);
+        let request = Request::call(txs, responder);
+        self.send_message(Message::DryRunTxs(request))?;
+        response.recv().map_err(Into::into)
+    }
+
+    /// The async variant of `dry_run_txs`.
+    pub fn dry_run_txs_async(
+        &self,
+        txs: Vec<TransactionView>,
+    ) -> impl Future<Output = Result<DryRunTxsResult, FailureError>> + Send + 'static {
+        let (responder, response) = oneshot::channel();
+        let request = Request::call_async(txs, responder);
+        self.call_async(Message::DryRunTxs(request), response)
+    }
+
     pub fn plug_entry(
         &self,
         entries: Vec<TxEntry>,
         target: PlugTarget,
     ) -> Result<(), FailureError> {
-        let mut sender = self.sender.clone();
         let (responder, response) = crossbeam_channel::bounded(1);
         let request = Request::call((entries, target), responder);
-        sender.try_send(Message::PlugEntry(request)).map_err(|e| {
-            let (_m, e) = handle_try_send_error(e);
-            e
-        })?;
+        self.send_message(Message::PlugEntry(request))?;
         response.recv().map_err(Into::into)
     }
 
@@ -177,24 +345,41 @@ impl TxPoolController {
         txs: Vec<TransactionView>,
         callback: NotifyTxsCallback,
     ) -> Result<(), FailureError> {
-        let mut sender = self.sender.clone();
         let notify = Notify::notify((txs, callback));
-        sender.try_send(Message::NotifyTxs(notify)).map_err(|e| {
-            let (_m, e) = handle_try_send_error(e);
-            e.into()
-        })
+        self.send_message(Message::NotifyTxs(notify))
+            .map_err(Into::into)
+    }
+
+    /// Writes the pending, gap and proposed entries to `path`, returns the number of them.
+    pub fn save_pool(&self, path: PathBuf) -> Result<Result<usize, Error>, FailureError> {
+        let (responder, response) = crossbeam_channel::bounded(1);
+        let request = Request::call(path, responder);
+        self.send_message(Message::SavePool(request))?;
+        response.recv().map_err(Into::into)
+    }
+
+    /// Verifies and adds the entries written by `save_pool`, returns the numbers of the accepted
+    /// and the rejected entries.
+    pub fn load_pool(&self, path: PathBuf) -> Result<Result<(usize, usize), Error>, FailureError> {
+        let (responder, response) = crossbeam_channel::bounded(1);
+        let request = Request::call(path, responder);
+        self.send_message(Message::LoadPool(request))?;
+        response.recv().map_err(Into::into)
     }
 
     pub fn get_tx_pool_info(&self) -> Result<TxPoolInfo, FailureError> {
-        let mut sender = self.sender.clone();
         let (responder, response) = crossbeam_channel::bounded(1);
         let request = Request::call((), responder);
-        sender
-            .try_send(Message::GetTxPoolInfo(request))
-            .map_err(|e| {
-                let (_m, e) = handle_try_send_error(e);
-                e
-            })?;
+        self.send_message(Message::GetTxPoolInfo(request))?;
+        response.recv().map_err(Into::into)
+    }
+
+    /// The fee rate bins of the pending and proposed transactions, and the counts of the
+    /// transactions in each state.
+    pub fn get_fee_histogram(&self) -> Result<FeeHistogram, FailureError> {
+        let (responder, response) = crossbeam_channel::bounded(1);
+        let request = Request::call((), responder);
+        self.send_message(Message::FeeHistogram(request))?;
         response.recv().map_err(Into::into)
     }
 
@@ -202,26 +387,46 @@ impl TxPoolController {
         &self,
         proposals: Vec<ProposalShortId>,
     ) -> Result<Vec<ProposalShortId>, FailureError> {
-        let mut sender = self.sender.clone();
         let (responder, response) = crossbeam_channel::bounded(1);
         let request = Request::call(proposals, responder);
-        sender
-            .try_send(Message::FreshProposalsFilter(request))
-            .map_err(|e| {
-                let (_m, e) = handle_try_send_error(e);
-                e
-            })?;
+        self.send_message(Message::FreshProposalsFilter(request))?;
         response.recv().map_err(Into::into)
     }
 
     pub fn fetch_tx_for_rpc(&self, id: ProposalShortId) -> Result<FetchTxRPCResult, FailureError> {
-        let mut sender = self.sender.clone();
         let (responder, response) = crossbeam_channel::bounded(1);
         let request = Request::call(id, responder);
-        sender.try_send(Message::FetchTxRPC(request)).map_err(|e| {
-            let (_m, e) = handle_try_send_error(e);
-            e
-        })?;
+        self.send_message(Message::FetchTxRPC(request))?;
+        response.recv().map_err(Into::into)
+    }
+
+    /// The status of a transaction submitted by `submit_txs`, `None` if it is not submitted
+    /// locally or has been forgotten.
+    pub fn get_local_tx_status(
+        &self,
+        tx_hash: Byte32,
+    ) -> Result<Option<LocalTxStatus>, FailureError> {
+        let (responder, response) = crossbeam_channel::bounded(1);
+        let request = Request::call(tx_hash, responder);
+        self.send_message(Message::LocalTxStatus(request))?;
+        response.recv().map_err(Into::into)
+    }
+
+    /// The hashes of the local transactions in the pool which are due to rebroadcast, see
+    /// `rebroadcast_after_blocks` and `max_rebroadcasts` of the config.
+    pub fn get_local_txs_to_rebroadcast(&self) -> Result<Vec<Byte32>, FailureError> {
+        let (responder, response) = crossbeam_channel::bounded(1);
+        let request = Request::call((), responder);
+        self.send_message(Message::LocalTxsToRebroadcast(request))?;
+        response.recv().map_err(Into::into)
+    }
+
+    /// The hashes of at most `limit` transactions in the pool, the proposed ones come first, and
+    /// a transaction always comes after its parents.
+    pub fn inventory_snapshot(&self, limit: usize) -> Result<Vec<Byte32>, FailureError> {
+        let (responder, response) = crossbeam_channel::bounded(1);
+        let request = Request::call(limit, responder);
+        self.send_message(Message::Inventory(request))?;
         response.recv().map_err(Into::into)
     }
 
@@ -229,13 +434,9 @@ impl TxPoolController {
         &self,
         short_ids: Vec<ProposalShortId>,
     ) -> Result<HashMap<ProposalShortId, TransactionView>, FailureError> {
-        let mut sender = self.sender.clone();
         let (responder, response) = crossbeam_channel::bounded(1);
         let request = Request::call(short_ids, responder);
-        sender.try_send(Message::FetchTxs(request)).map_err(|e| {
-            let (_m, e) = handle_try_send_error(e);
-            e
-        })?;
+        self.send_message(Message::FetchTxs(request))?;
         response.recv().map_err(Into::into)
     }
 
@@ -243,30 +444,121 @@ impl TxPoolController {
         &self,
         short_ids: Vec<ProposalShortId>,
     ) -> Result<FetchTxsWithCyclesResult, FailureError> {
-        let mut sender = self.sender.clone();
         let (responder, response) = crossbeam_channel::bounded(1);
         let request = Request::call(short_ids, responder);
-        sender
-            .try_send(Message::FetchTxsWithCycles(request))
-            .map_err(|e| {
-                let (_m, e) = handle_try_send_error(e);
-                e
-            })?;
+        self.send_message(Message::FetchTxsWithCycles(request))?;
         response.recv().map_err(Into::into)
     }
 
-    pub fn estimate_fee_rate(&self, expect_confirm_blocks: usize) -> Result<FeeRate, FailureError> {
-        let mut sender = self.sender.clone();
+    /// The async variant of `fetch_txs_with_cycles`.
+    pub fn fetch_txs_with_cycles_async(
+        &self,
+        short_ids: Vec<ProposalShortId>,
+    ) -> impl Future<Output = Result<FetchTxsWithCyclesResult, FailureError>> + Send + 'static {
+        let (responder, response) = oneshot::channel();
+        let request = Request::call_async(short_ids, responder);
+        self.call_async(Message::FetchTxsWithCycles(request), response)
+    }
+
+    /// Returns the ancestors and descendants stats of the entries in the pool, the missing ones
+    /// are skipped.
+    pub fn fetch_tx_package_info(
+        &self,
+        short_ids: Vec<ProposalShortId>,
+    ) -> Result<HashMap<ProposalShortId, TxPackageInfo>, FailureError> {
         let (responder, response) = crossbeam_channel::bounded(1);
-        let request = Request::call(expect_confirm_blocks, responder);
-        sender
-            .try_send(Message::EstimateFeeRate(request))
-            .map_err(|e| {
-                let (_m, e) = handle_try_send_error(e);
-                e
-            })?;
+        let request = Request::call(short_ids, responder);
+        self.send_message(Message::FetchTxPackageInfo(request))?;
         response.recv().map_err(Into::into)
     }
+
+    pub fn estimate_fee_rate(
+        &self,
+        expect_confirm_blocks: usize,
+        mode: EstimateMode,
+    ) -> Result<FeeRate, FailureError> {
+        let (responder, response) = crossbeam_channel::bounded(1);
+        let request = Request::call((expect_confirm_blocks, mode), responder);
+        self.send_message(Message::EstimateFeeRate(request))?;
+        response.recv().map_err(Into::into)
+    }
+
+    fn queue_timeout(&self) -> Duration {
+        Duration::from_millis(self.config.read().queue_timeout_millis)
+    }
+
+    // Sends the message to the service. When the queue is full, it waits for the room until
+    // `queue_timeout_millis` of the config elapses, so a burst of messages is absorbed instead of
+    // failing at once, or fails at once if it is 0. The waiting is a task on the runtime, the
+    // caller is blocked on its result rather than polling the queue.
+    fn send_message(&self, message: Message) -> Result<(), TrySendError> {
+        let mut sender = self.sender.clone();
+        let timeout = self.queue_timeout();
+        let message = match sender.try_send(message) {
+            Ok(()) => {
+                QUEUE_DEPTH.inc();
+                return Ok(());
+            }
+            Err(TokioTrySendError::Full(message)) if timeout > Duration::from_secs(0) => message,
+            Err(e) => {
+                let (_m, e) = handle_try_send_error(e);
+                if e.is_full() {
+                    QUEUE_FULL.inc();
+                }
+                return Err(e);
+            }
+        };
+        let (responder, response) = crossbeam_channel::bounded(1);
+        self.handle.spawn(async move {
+            let _ = responder.send(send_timeout(sender, message, timeout).await);
+        });
+        response.recv().unwrap_or(Err(TrySendError::Closed))
+    }
+
+    // The returned future owns a clone of the sender, so it does not borrow the controller. It
+    // waits for the room in the full queue as long as `send_message` does.
+    fn call_async<R>(
+        &self,
+        message: Message,
+        response: oneshot::Receiver<R>,
+    ) -> impl Future<Output = Result<R, FailureError>> + Send + 'static
+    where
+        R: Send + 'static,
+    {
+        let sender = self.sender.clone();
+        let timeout = self.queue_timeout();
+        async move {
+            send_timeout(sender, message, timeout).await?;
+            response.await.map_err(Into::into)
+        }
+    }
+
+    // The chain service must not wait for a busy tx pool, nor lose or reorder the reorgs, so its
+    // messages go through an unbounded queue apart, which only fails once the service stops.
+    fn send_chain_message(&self, message: Message) -> Result<(), TrySendError> {
+        self.chain_sender
+            .send(message)
+            .map_err(|_| TrySendError::Closed)
+    }
+}
+
+// The message is sent at once if there is room in the queue, even if the timeout is 0.
+async fn send_timeout(
+    mut sender: mpsc::Sender<Message>,
+    message: Message,
+    timeout: Duration,
+) -> Result<(), TrySendError> {
+    match tokio::time::timeout(timeout, sender.send(message)).await {
+        Ok(Ok(())) => {
+            QUEUE_DEPTH.inc();
+            Ok(())
+        }
+        Ok(Err(_)) => Err(TrySendError::Closed),
+        Err(_elapsed) => {
+            QUEUE_FULL.inc();
+            Err(TrySendError::Full)
+        }
+    }
 }
 
 pub struct TxPoolServiceBuilder {
@@ -274,15 +566,25 @@ pub struct TxPoolServiceBuilder {
 }
 
 impl TxPoolServiceBuilder {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         tx_pool_config: TxPoolConfig,
         snapshot: Arc<Snapshot>,
         block_assembler_config: Option<BlockAssemblerConfig>,
-        txs_verify_cache: Arc<RwLock<TxVerifyCache>>,
+        txs_verify_cache: Arc<TxVerifyCache>,
+        verify_pool: VerifyPool,
         snapshot_mgr: Arc<SnapshotMgr>,
+        notify_controller: NotifyController,
+        traced_transactions: TracedTransactions,
+        clock: Arc<dyn Clock>,
     ) -> TxPoolServiceBuilder {
         let last_txs_updated_at = Arc::new(AtomicU64::new(0));
-        let tx_pool = TxPool::new(tx_pool_config, snapshot, Arc::clone(&last_txs_updated_at));
+        let tx_pool = TxPool::new(
+            tx_pool_config,
+            snapshot,
+            Arc::clone(&last_txs_updated_at),
+            Arc::clone(&clock),
+        );
         let block_assembler = block_assembler_config.map(BlockAssembler::new);
 
         TxPoolServiceBuilder {
@@ -290,35 +592,82 @@ impl TxPoolServiceBuilder {
                 tx_pool,
                 block_assembler,
                 txs_verify_cache,
+                verify_pool,
                 last_txs_updated_at,
                 snapshot_mgr,
+                notify_controller,
+                traced_transactions,
+                clock,
             )),
         }
     }
 
     pub fn start(mut self) -> TxPoolController {
         let (sender, mut receiver) = mpsc::channel(DEFAULT_CHANNEL_SIZE);
+        let (chain_sender, mut chain_receiver) = mpsc::unbounded_channel();
         let (signal_sender, mut signal_receiver) = oneshot::channel();
 
         let service = self.service.take().expect("tx pool service start once");
+        let config = Arc::clone(&service.tx_pool_config);
+        let selection_strategies = service.selection_strategies.clone();
+        let persisted_path: Arc<ckb_util::Mutex<Option<PathBuf>>> = Default::default();
+        let persisted_path_on_stop = Arc::clone(&persisted_path);
         let server = move |handle: Handle| async move {
+            // Every spawned process holds a clone of `in_flight`, `finished` yields `None` once
+            // all of them are done.
+            let (in_flight, mut finished) = mpsc::channel::<()>(1);
+            let spawn_process = |message: Message| {
+                let service_clone = service.clone();
+                let in_flight = in_flight.clone();
+                handle.spawn(async move {
+                    process(service_clone, message).await;
+                    drop(in_flight);
+                });
+            };
             loop {
                 tokio::select! {
                     Some(message) = receiver.recv() => {
-                        let service_clone = service.clone();
-                        handle.spawn(process(service_clone, message));
+                        QUEUE_DEPTH.dec();
+                        spawn_process(message)
                     },
+                    Some(message) = chain_receiver.recv() => spawn_process(message),
                     _ = &mut signal_receiver => break,
                     else => break,
                 }
             }
+
+            // Refuses the new messages, and processes the queued ones before exit
+            receiver.close();
+            chain_receiver.close();
+            while let Some(message) = chain_receiver.recv().await {
+                spawn_process(message);
+            }
+            while let Some(message) = receiver.recv().await {
+                QUEUE_DEPTH.dec();
+                spawn_process(message);
+            }
+            service.longpoll_waiters.lock().close();
+            drop(in_flight);
+            let _ = finished.recv().await;
+
+            let path = persisted_path_on_stop.lock().take();
+            if let Some(path) = path {
+                match service.save_pool(&path).await {
+                    Ok(count) => info!("{} txs of tx pool saved to {}", count, path.display()),
+                    Err(err) => error!("save tx pool to {} error {}", path.display(), err),
+                }
+            }
         };
         let (handle, thread) = new_runtime(server);
         let stop = StopHandler::new(SignalSender::Tokio(signal_sender), thread);
         TxPoolController {
             sender,
+            chain_sender,
             handle,
             stop,
+            config,
+            persisted_path,
+            selection_strategies,
         }
     }
 }
@@ -326,35 +675,82 @@ impl TxPoolServiceBuilder {
 #[derive(Clone)]
 pub struct TxPoolService {
     pub(crate) tx_pool: Arc<RwLock<TxPool>>,
-    pub(crate) tx_pool_config: Arc<TxPoolConfig>,
+    pub(crate) tx_pool_config: Arc<ckb_util::RwLock<TxPoolConfig>>,
     pub(crate) block_assembler: Option<BlockAssembler>,
-    pub(crate) txs_verify_cache: Arc<RwLock<TxVerifyCache>>,
+    pub(crate) txs_verify_cache: Arc<TxVerifyCache>,
+    pub(crate) verify_pool: VerifyPool,
     pub(crate) last_txs_updated_at: Arc<AtomicU64>,
     snapshot_mgr: Arc<SnapshotMgr>,
+    pub(crate) notify_controller: NotifyController,
+    pub(crate) traced_transactions: TracedTransactions,
+    longpoll_waiters: Arc<ckb_util::Mutex<LongpollWaiters>>,
+    pub(crate) local_txs: Arc<ckb_util::Mutex<LocalTxTracker>>,
+    pub(crate) selection_strategies: SelectionStrategies,
+    pub(crate) clock: Arc<dyn Clock>,
 }
 
 impl TxPoolService {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         tx_pool: TxPool,
         block_assembler: Option<BlockAssembler>,
-        txs_verify_cache: Arc<RwLock<TxVerifyCache>>,
+        txs_verify_cache: Arc<TxVerifyCache>,
+        verify_pool: VerifyPool,
         last_txs_updated_at: Arc<AtomicU64>,
         snapshot_mgr: Arc<SnapshotMgr>,
+        notify_controller: NotifyController,
+        traced_transactions: TracedTransactions,
+        clock: Arc<dyn Clock>,
     ) -> Self {
-        let tx_pool_config = Arc::new(tx_pool.config);
+        let tx_pool_config = Arc::new(ckb_util::RwLock::new(tx_pool.config));
         Self {
             tx_pool: Arc::new(RwLock::new(tx_pool)),
             tx_pool_config,
             block_assembler,
             txs_verify_cache,
+            verify_pool,
             last_txs_updated_at,
             snapshot_mgr,
+            notify_controller,
+            traced_transactions,
+            longpoll_waiters: Default::default(),
+            local_txs: Default::default(),
+            selection_strategies: Default::default(),
+            clock,
         }
     }
 
     pub(crate) fn snapshot(&self) -> Arc<Snapshot> {
         Arc::clone(&self.snapshot_mgr.load())
     }
+
+    pub(crate) fn longpoll_id(&self) -> LongpollId {
+        LongpollId {
+            tip_hash: self.snapshot().tip_hash().unpack(),
+            txs_updated_at: self.last_txs_updated_at.load(Ordering::SeqCst),
+        }
+    }
+
+    // Waits until the tip or the pool has changed since `longpoll_id`, or the timeout expires.
+    async fn longpoll(&self, longpoll_id: LongpollId, timeout: Duration) {
+        let waiter = {
+            // The state is read under the lock, so a change never slips in between
+            let mut waiters = self.longpoll_waiters.lock();
+            waiters.wait(longpoll_id, &self.longpoll_id())
+        };
+        if let Some((token, receiver)) = waiter {
+            if tokio::time::timeout(timeout, receiver).await.is_err() {
+                self.longpoll_waiters.lock().remove(token);
+            }
+        }
+    }
+
+    fn wake_longpoll_waiters(&self) {
+        let mut waiters = self.longpoll_waiters.lock();
+        if !waiters.is_empty() {
+            waiters.wake(&self.longpoll_id());
+        }
+    }
 }
 
 #[allow(clippy::cognitive_complexity)]
@@ -366,13 +762,28 @@ async fn process(service: TxPoolService, message: Message) {
                 error!("responder send get_tx_pool_info failed {:?}", e);
             };
         }
+        Message::FeeHistogram(Request { responder, .. }) => {
+            let histogram = service.tx_pool.read().await.fee_histogram();
+            if let Err(e) = responder.send(histogram) {
+                error!("responder send fee_histogram failed {:?}", e);
+            };
+        }
         Message::BlockTemplate(Request {
             responder,
-            arguments: (bytes_limit, proposals_limit, max_version),
+            arguments: (bytes_limit, proposals_limit, max_version, strategy),
         }) => {
+            let txs_updated_at = service.last_txs_updated_at.load(Ordering::SeqCst);
             let block_template_result = service
-                .get_block_template(bytes_limit, proposals_limit, max_version)
-                .await;
+                .get_block_template(bytes_limit, proposals_limit, max_version, strategy)
+                .await
+                .map(|mut template| {
+                    let longpoll_id = LongpollId {
+                        tip_hash: template.parent_hash.clone(),
+                        txs_updated_at,
+                    };
+                    template.longpoll_id = Some(longpoll_id.to_string());
+                    template
+                });
             if let Err(e) = responder.send(block_template_result) {
                 error!("responder send block_template_result failed {:?}", e);
             };
@@ -381,15 +792,31 @@ async fn process(service: TxPoolService, message: Message) {
             responder,
             arguments: txs,
         }) => {
-            let submit_txs_result = service.process_txs(txs).await;
+            {
+                let tip_number = service.snapshot().tip_number();
+                let mut local_txs = service.local_txs.lock();
+                for tx in &txs {
+                    local_txs.track(tx.hash(), tip_number);
+                }
+            }
+            let submit_txs_result = service.process_txs(txs, VerifyOrigin::RpcSubmit).await;
             if let Err(e) = responder.send(submit_txs_result) {
                 error!("responder send submit_txs_result failed {:?}", e);
             };
         }
+        Message::DryRunTxs(Request {
+            responder,
+            arguments: txs,
+        }) => {
+            let dry_run_txs_result = service.dry_run_txs(txs).await;
+            if let Err(e) = responder.send(dry_run_txs_result) {
+                error!("responder send dry_run_txs_result failed {:?}", e);
+            };
+        }
         Message::NotifyTxs(Notify {
             arguments: (txs, callback),
         }) => {
-            let submit_txs_result = service.process_txs(txs).await;
+            let submit_txs_result = service.process_txs(txs, VerifyOrigin::Relay).await;
             if let Some(call) = callback {
                 call(submit_txs_result)
             };
@@ -418,6 +845,30 @@ async fn process(service: TxPoolService, message: Message) {
                 error!("responder send fetch_tx_for_rpc failed {:?}", e)
             };
         }
+        Message::LocalTxStatus(Request {
+            responder,
+            arguments: tx_hash,
+        }) => {
+            let status = service.local_tx_status(&tx_hash).await;
+            if let Err(e) = responder.send(status) {
+                error!("responder send local_tx_status failed {:?}", e)
+            };
+        }
+        Message::LocalTxsToRebroadcast(Request { responder, .. }) => {
+            let tx_hashes = service.local_txs_to_rebroadcast().await;
+            if let Err(e) = responder.send(tx_hashes) {
+                error!("responder send local_txs_to_rebroadcast failed {:?}", e)
+            };
+        }
+        Message::Inventory(Request {
+            responder,
+            arguments: limit,
+        }) => {
+            let inventory = service.tx_pool.read().await.inventory(limit);
+            if let Err(e) = responder.send(inventory) {
+                error!("responder send inventory failed {:?}", e)
+            };
+        }
         Message::FetchTxs(Request {
             responder,
             arguments: short_ids,
@@ -454,6 +905,23 @@ async fn process(service: TxPoolService, message: Message) {
                 error!("responder send fetch_txs_with_cycles failed {:?}", e);
             };
         }
+        Message::FetchTxPackageInfo(Request {
+            responder,
+            arguments: short_ids,
+        }) => {
+            let tx_pool = service.tx_pool.read().await;
+            let infos = short_ids
+                .into_iter()
+                .filter_map(|short_id| {
+                    tx_pool
+                        .get_package_info(&short_id)
+                        .map(|info| (short_id, info))
+                })
+                .collect();
+            if let Err(e) = responder.send(infos) {
+                error!("responder send fetch_tx_package_info failed {:?}", e);
+            };
+        }
         Message::ChainReorg(Notify {
             arguments: (detached_blocks, attached_blocks, detached_proposal_id, snapshot),
         }) => {
@@ -472,7 +940,7 @@ async fn process(service: TxPoolService, message: Message) {
                 block_assembler.candidate_uncles.lock().await.insert(uncle);
                 block_assembler
                     .last_uncles_updated_at
-                    .store(unix_time_as_millis(), Ordering::SeqCst);
+                    .store(service.clock.unix_time_as_millis(), Ordering::SeqCst);
             }
         }
         Message::PlugEntry(Request {
@@ -496,19 +964,216 @@ async fn process(service: TxPoolService, message: Message) {
                     }
                 }
             };
+            tx_pool.update_metrics();
             if let Err(e) = responder.send(()) {
                 error!("responder send plug_entry failed {:?}", e);
             };
         }
         Message::EstimateFeeRate(Request {
             responder,
-            arguments: expect_confirm_blocks,
+            arguments: (expect_confirm_blocks, mode),
         }) => {
             let tx_pool = service.tx_pool.read().await;
-            let fee_rate = tx_pool.fee_estimator.estimate(expect_confirm_blocks);
+            let fee_rate = tx_pool.estimate_fee_rate(expect_confirm_blocks, mode);
             if let Err(e) = responder.send(fee_rate) {
                 error!("responder send estimate_fee_rate failed {:?}", e)
             };
         }
+        Message::SavePool(Request {
+            responder,
+            arguments: path,
+        }) => {
+            let result = service.save_pool(&path).await;
+            if let Err(e) = responder.send(result) {
+                error!("responder send save_pool failed {:?}", e);
+            };
+        }
+        Message::LoadPool(Request {
+            responder,
+            arguments: path,
+        }) => {
+            let result = service.load_pool(&path).await;
+            if let Err(e) = responder.send(result) {
+                error!("responder send load_pool failed {:?}", e);
+            };
+        }
+        Message::UpdateConfig(Notify { arguments: config }) => {
+            service.tx_pool.write().await.config = config;
+        }
+        Message::Longpoll(Request {
+            responder,
+            arguments: (longpoll_id, timeout),
+        }) => {
+            service.longpoll(longpoll_id, timeout).await;
+            if let Err(e) = responder.send(()) {
+                error!("responder send longpoll failed {:?}", e);
+            };
+        }
+    }
+    // Any message may change the tip or the pool
+    service.wake_longpoll_waiters();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ckb_types::core::{BlockBuilder, TransactionBuilder};
+    use std::time::Instant;
+
+    // A controller whose queue holds a single message, the queues are drained by the test only
+    fn stalled_controller(
+        queue_timeout_millis: u64,
+    ) -> (
+        TxPoolController,
+        mpsc::Receiver<Message>,
+        mpsc::UnboundedReceiver<Message>,
+    ) {
+        let (sender, receiver) = mpsc::channel(1);
+        let (chain_sender, chain_receiver) = mpsc::unbounded_channel();
+        let (signal_sender, signal_receiver) = oneshot::channel::<()>();
+        let (handle, thread) = new_runtime(move |_| async move {
+            let _ = signal_receiver.await;
+        });
+        let mut config = TxPoolConfig::default();
+        config.queue_timeout_millis = queue_timeout_millis;
+        let controller = TxPoolController {
+            sender,
+            chain_sender,
+            handle,
+            stop: StopHandler::new(SignalSender::Tokio(signal_sender), thread),
+            config: Arc::new(ckb_util::RwLock::new(config)),
+            persisted_path: Default::default(),
+            selection_strategies: Default::default(),
+        };
+        (controller, receiver, chain_receiver)
+    }
+
+    fn send_new_uncle(controller: &TxPoolController) -> Result<(), TrySendError> {
+        let uncle = BlockBuilder::default().build().as_uncle();
+        controller.send_message(Message::NewUncle(Notify::notify(uncle)))
+    }
+
+    fn submit_txs_async(controller: &TxPoolController) -> Result<SubmitTxsResult, FailureError> {
+        let (responder, response) = crossbeam_channel::bounded(1);
+        let submit_txs = controller.submit_txs_async(Vec::new());
+        controller.handle().spawn(async move {
+            let _ = responder.send(submit_txs.await);
+        });
+        response.recv().unwrap()
+    }
+
+    #[test]
+    fn fail_at_once_when_queue_full() {
+        let (controller, _receiver, _chain_receiver) = stalled_controller(0);
+        send_new_uncle(&controller).unwrap();
+        assert!(send_new_uncle(&controller).unwrap_err().is_full());
+
+        // the async callers do not wait either
+        let err = submit_txs_async(&controller).unwrap_err();
+        assert!(err.downcast::<TrySendError>().unwrap().is_full());
+    }
+
+    #[test]
+    fn wait_for_room_in_full_queue() {
+        let (controller, mut receiver, _chain_receiver) = stalled_controller(10_000);
+        send_new_uncle(&controller).unwrap();
+        controller.handle().spawn(async move {
+            tokio::time::delay_for(Duration::from_millis(100)).await;
+            let _ = receiver.recv().await;
+            // keep the queue open
+            receiver
+        });
+        send_new_uncle(&controller).unwrap();
+    }
+
+    #[test]
+    fn report_full_queue_after_timeout() {
+        let (controller, _receiver, _chain_receiver) = stalled_controller(100);
+        send_new_uncle(&controller).unwrap();
+        let now = Instant::now();
+        assert!(send_new_uncle(&controller).unwrap_err().is_full());
+        assert!(now.elapsed() >= Duration::from_millis(100));
+
+        // the async callers wait as long
+        let now = Instant::now();
+        let err = submit_txs_async(&controller).unwrap_err();
+        assert!(err.downcast::<TrySendError>().unwrap().is_full());
+        assert!(now.elapsed() >= Duration::from_millis(100));
+    }
+
+    #[test]
+    fn never_block_chain_service() {
+        let (controller, _receiver, mut chain_receiver) = stalled_controller(10_000);
+        send_new_uncle(&controller).unwrap();
+
+        // the chain messages neither wait for the full queue nor get dropped
+        let now = Instant::now();
+        for _ in 0..3 {
+            let uncle = BlockBuilder::default().build().as_uncle();
+            controller.notify_new_uncle(uncle).unwrap();
+        }
+        assert!(now.elapsed() < Duration::from_secs(5));
+        let (responder, response) = crossbeam_channel::bounded(1);
+        controller.handle().spawn(async move {
+            let mut count = 0;
+            while let Some(Message::NewUncle(_)) = chain_receiver.recv().await {
+                count += 1;
+                if count == 3 {
+                    break;
+                }
+            }
+            let _ = responder.send(count);
+        });
+        assert_eq!(response.recv().unwrap(), 3);
+    }
+
+    #[test]
+    fn respond_to_async_callers() {
+        let (controller, mut receiver, _chain_receiver) = stalled_controller(100);
+        controller.handle().spawn(async move {
+            if let Some(Message::FetchTxsWithCycles(Request {
+                responder,
+                arguments,
+            })) = receiver.recv().await
+            {
+                let _ = responder.send(
+                    arguments
+                        .into_iter()
+                        .map(|short_id| (short_id, (TransactionBuilder::default().build(), 100)))
+                        .collect(),
+                );
+            }
+        });
+
+        let (responder, response) = crossbeam_channel::bounded(1);
+        let short_id = ProposalShortId::new([1u8; 10]);
+        let fetch_txs = controller.fetch_txs_with_cycles_async(vec![short_id.clone()]);
+        controller.handle().spawn(async move {
+            let _ = responder.send(fetch_txs.await);
+        });
+        let fetched = response.recv().unwrap().unwrap();
+        assert_eq!(fetched.len(), 1);
+        assert_eq!(fetched[0].0, short_id);
+        assert_eq!((fetched[0].1).1, 100);
+    }
+
+    #[test]
+    fn hand_back_response_to_gone_callers() {
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+        drop(receiver);
+        assert_eq!(Responder::Blocking(sender).send(1).unwrap_err().0, 1);
+        let (sender, receiver) = oneshot::channel();
+        drop(receiver);
+        assert_eq!(Responder::Async(sender).send(2).unwrap_err().0, 2);
+    }
+
+    #[test]
+    fn report_closed_queue() {
+        let (controller, receiver, chain_receiver) = stalled_controller(100);
+        drop(receiver);
+        assert!(!send_new_uncle(&controller).unwrap_err().is_full());
+        drop(chain_receiver);
+        let uncle = BlockBuilder::default().build().as_uncle();
+        assert!(controller.notify_new_uncle(uncle).is_err());
     }
 }
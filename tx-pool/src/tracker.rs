@@ -0,0 +1,223 @@
+//! Tracks the transactions submitted locally.
+//!
+//! A transaction dropped from the pool disappears from it without a trace, so the tracker
+//! remembers the hashes of the local transactions and the reasons they are rejected for. The
+//! status is resolved against the chain and the pool when it is queried, so the commits, the
+//! reorgs and the evictions need no bookkeeping here.
+//!
+//! The local transactions staying in the pool are rebroadcast with an exponential backoff, in
+//! case the peers have dropped them from their pools.
+use ckb_types::{core::BlockNumber, packed::Byte32};
+use lru_cache::LruCache;
+
+/// The oldest local transactions are forgotten beyond it
+const MAX_LOCAL_TXS: usize = 10_000;
+
+/// The reason of a local transaction which is neither in the pool nor committed, and which is not
+/// rejected on submission.
+const DROPPED_REASON: &str = "dropped from the tx pool";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LocalTxStatus {
+    /// In the pool, not proposed yet
+    Pending,
+    /// In the pool, proposed
+    Proposed,
+    /// Committed in the main chain block of the hash
+    Committed(Byte32),
+    /// Refused by or dropped from the pool
+    Rejected(String),
+}
+
+struct LocalTx {
+    /// The reason the transaction is rejected for
+    rejected: Option<String>,
+    /// The tip number when the transaction is submitted or rebroadcast last time
+    broadcast_at: BlockNumber,
+    rebroadcasts: u32,
+}
+
+pub(crate) struct LocalTxTracker {
+    /// The local transactions by the hashes
+    txs: LruCache<Byte32, LocalTx>,
+}
+
+impl Default for LocalTxTracker {
+    fn default() -> Self {
+        LocalTxTracker::new(MAX_LOCAL_TXS)
+    }
+}
+
+impl LocalTxTracker {
+    pub(crate) fn new(capacity: usize) -> Self {
+        LocalTxTracker {
+            txs: LruCache::new(capacity),
+        }
+    }
+
+    /// Starts tracking the transaction submitted at the tip `tip_number`, a resubmitted
+    /// transaction forgets its last rejection and rebroadcasts.
+    pub(crate) fn track(&mut self, tx_hash: Byte32, tip_number: BlockNumber) {
+        self.txs.insert(
+            tx_hash,
+            LocalTx {
+                rejected: None,
+                broadcast_at: tip_number,
+                rebroadcasts: 0,
+            },
+        );
+    }
+
+    /// Records the reason if the transaction is tracked.
+    pub(crate) fn reject(&mut self, tx_hash: &Byte32, reason: String) {
+        if let Some(tx) = self.txs.get_mut(tx_hash) {
+            tx.rejected = Some(reason);
+        }
+    }
+
+    /// Takes the transactions in the pool which are due to rebroadcast at the tip `tip_number`.
+    /// The first rebroadcast is `after_blocks` blocks after the submission, and the interval
+    /// doubles each time until a transaction is rebroadcast `max_rebroadcasts` times.
+    pub(crate) fn take_rebroadcasts<F>(
+        &mut self,
+        tip_number: BlockNumber,
+        after_blocks: BlockNumber,
+        max_rebroadcasts: u32,
+        in_pool: F,
+    ) -> Vec<Byte32>
+    where
+        F: Fn(&Byte32) -> bool,
+    {
+        if after_blocks == 0 {
+            return Vec::new();
+        }
+        self.txs
+            .iter_mut()
+            .filter_map(|(tx_hash, tx)| {
+                if tx.rebroadcasts >= max_rebroadcasts || !in_pool(tx_hash) {
+                    return None;
+                }
+                let interval = after_blocks
+                    .checked_shl(tx.rebroadcasts)
+                    .filter(|interval| interval >> tx.rebroadcasts == after_blocks)
+                    .unwrap_or(BlockNumber::max_value());
+                if tip_number < tx.broadcast_at.saturating_add(interval) {
+                    return None;
+                }
+                tx.broadcast_at = tip_number;
+                tx.rebroadcasts += 1;
+                Some(tx_hash.clone())
+            })
+            .collect()
+    }
+
+    /// Resolves the status of a tracked transaction from the block committing it, and whether it
+    /// is proposed if it is in the pool. Returns `None` if the transaction is not tracked.
+    pub(crate) fn status(
+        &mut self,
+        tx_hash: &Byte32,
+        committed: Option<Byte32>,
+        in_pool: Option<bool>,
+    ) -> Option<LocalTxStatus> {
+        let rejected = &self.txs.get_mut(tx_hash)?.rejected;
+        let status = match (committed, in_pool) {
+            (Some(block_hash), _) => LocalTxStatus::Committed(block_hash),
+            (None, Some(true)) => LocalTxStatus::Proposed,
+            (None, Some(false)) => LocalTxStatus::Pending,
+            (None, None) => LocalTxStatus::Rejected(
+                rejected
+                    .clone()
+                    .unwrap_or_else(|| DROPPED_REASON.to_owned()),
+            ),
+        };
+        Some(status)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ckb_types::{h256, prelude::*};
+
+    #[test]
+    fn test_local_tx_status() {
+        let tx_hash = h256!("0x1").pack();
+        let block_hash = h256!("0x2").pack();
+        let mut tracker = LocalTxTracker::new(1);
+        assert_eq!(tracker.status(&tx_hash, None, Some(false)), None);
+
+        tracker.track(tx_hash.clone(), 0);
+        assert_eq!(
+            tracker.status(&tx_hash, None, Some(false)),
+            Some(LocalTxStatus::Pending)
+        );
+        assert_eq!(
+            tracker.status(&tx_hash, None, Some(true)),
+            Some(LocalTxStatus::Proposed)
+        );
+        assert_eq!(
+            tracker.status(&tx_hash, Some(block_hash.clone()), None),
+            Some(LocalTxStatus::Committed(block_hash))
+        );
+        assert_eq!(
+            tracker.status(&tx_hash, None, None),
+            Some(LocalTxStatus::Rejected(DROPPED_REASON.to_owned()))
+        );
+
+        tracker.reject(&tx_hash, "conflict".to_owned());
+        assert_eq!(
+            tracker.status(&tx_hash, None, None),
+            Some(LocalTxStatus::Rejected("conflict".to_owned()))
+        );
+        tracker.track(tx_hash.clone(), 0);
+        assert_eq!(
+            tracker.status(&tx_hash, None, None),
+            Some(LocalTxStatus::Rejected(DROPPED_REASON.to_owned()))
+        );
+
+        // the oldest is forgotten
+        let other_hash = h256!("0x3").pack();
+        tracker.track(other_hash, 0);
+        assert_eq!(tracker.status(&tx_hash, None, None), None);
+    }
+
+    #[test]
+    fn test_take_rebroadcasts() {
+        let tx_hash = h256!("0x1").pack();
+        let dropped_hash = h256!("0x2").pack();
+        let mut tracker = LocalTxTracker::new(10);
+        tracker.track(tx_hash.clone(), 10);
+        tracker.track(dropped_hash.clone(), 10);
+        let in_pool = |hash: &Byte32| hash != &dropped_hash;
+
+        assert!(tracker.take_rebroadcasts(12, 3, 3, in_pool).is_empty());
+        // rebroadcast at 13, 19 and 31
+        assert_eq!(
+            tracker.take_rebroadcasts(13, 3, 3, in_pool),
+            vec![tx_hash.clone()]
+        );
+        assert!(tracker.take_rebroadcasts(13, 3, 3, in_pool).is_empty());
+        assert!(tracker.take_rebroadcasts(18, 3, 3, in_pool).is_empty());
+        assert_eq!(
+            tracker.take_rebroadcasts(19, 3, 3, in_pool),
+            vec![tx_hash.clone()]
+        );
+        assert!(tracker.take_rebroadcasts(30, 3, 3, in_pool).is_empty());
+        assert_eq!(
+            tracker.take_rebroadcasts(31, 3, 3, in_pool),
+            vec![tx_hash.clone()]
+        );
+        // the cap is reached
+        assert!(tracker.take_rebroadcasts(1000, 3, 3, in_pool).is_empty());
+
+        // a resubmission restarts the backoff
+        tracker.track(tx_hash.clone(), 1000);
+        assert_eq!(
+            tracker.take_rebroadcasts(1003, 3, 3, in_pool),
+            vec![tx_hash]
+        );
+        // disabled
+        tracker.track(h256!("0x3").pack(), 0);
+        assert!(tracker.take_rebroadcasts(2000, 0, 3, in_pool).is_empty());
+    }
+}
@@ -5,14 +5,17 @@
 //! details https://docs.rs/toml/0.5.0/toml/ser/index.html
 
 use path_clean::PathClean;
+use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
 
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use ckb_chain_spec::ChainSpec;
 use ckb_logger::Config as LogConfig;
+use ckb_metrics::Config as MetricsConfig;
 use ckb_resource::Resource;
+use ckb_types::H256;
 
 use super::configs::*;
 use super::sentry_config::SentryConfig;
@@ -46,6 +49,58 @@ pub struct CKBAppConfig {
     pub alert_signature: Option<NetworkAlertConfig>,
     #[serde(default)]
     pub notify: NotifyConfig,
+    #[serde(default)]
+    pub metrics: MetricsConfig,
+    #[serde(default)]
+    pub health: HealthConfig,
+    pub stratum: Option<StratumConfig>,
+}
+
+/// Prefix of the environment variables which override the config options.
+const ENV_PREFIX: &str = "CKB_";
+/// Separator of the option path in the environment variable names, e.g. `CKB_NETWORK__MAX_PEERS`
+/// overrides `network.max_peers`.
+const ENV_SEPARATOR: &str = "__";
+
+/// Options which can be applied to the running node on config reload, in the dotted path form.
+pub const RELOADABLE_OPTIONS: &[&str] = &[
+    "logger.filter",
+    "logger.color",
+    "logger.log_to_stdout",
+    "network.whitelist_peers",
+    "network.whitelist",
+    "tx_pool.min_fee_rate",
+    "tx_pool.max_mem_size",
+    "tx_pool.max_cycles",
+    "tx_pool.max_tx_verify_cycles",
+    "tx_pool.queue_timeout_millis",
+    "tx_pool.min_output_capacity",
+    "tx_pool.max_tx_size",
+];
+
+/// Options hidden in the diagnostic reports, in the dotted path form, where `[]` stands for the
+/// items of an array. The options whose names contain `secret`, `password` or `token` are hidden
+/// as well.
+pub const REDACTED_OPTIONS: &[&str] = &[
+    "sentry.dsn",
+    "sentry.org_contact",
+    "network.public_addresses",
+    "network.whitelist_peers",
+    "network.whitelist.peers",
+    "notify.watched_transaction_webhook",
+    "notify.hooks[].command",
+    "notify.hooks[].webhook",
+];
+
+const REDACTED: &str = "<redacted>";
+
+/// Options changed in the reloaded config, in the dotted path form like `tx_pool.min_fee_rate`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConfigChanges {
+    /// Changes which can be applied at runtime, see `RELOADABLE_OPTIONS`
+    pub reloadable: Vec<String>,
+    /// Changes which only take effect after restart
+    pub immutable: Vec<String>,
 }
 
 // change the order of fields will break integration test, see module doc.
@@ -64,6 +119,9 @@ pub struct MinerAppConfig {
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ChainConfig {
     pub spec: Resource,
+    // The blocks which are ancestors of this block skip the script verification during IBD
+    #[serde(default)]
+    pub assume_valid_target: Option<H256>,
 }
 
 impl AppConfig {
@@ -74,7 +132,7 @@ impl AppConfig {
         match subcommand_name {
             cli::CMD_MINER => {
                 let resource = ensure_ckb_dir(Resource::miner_config(root_dir.as_ref()))?;
-                let config: MinerAppConfig = toml::from_slice(&resource.get()?)?;
+                let config: MinerAppConfig = parse_config(&resource.get()?)?;
 
                 Ok(AppConfig::with_miner(
                     config.derive_options(root_dir.as_ref())?,
@@ -82,7 +140,7 @@ impl AppConfig {
             }
             _ => {
                 let resource = ensure_ckb_dir(Resource::ckb_config(root_dir.as_ref()))?;
-                let config: CKBAppConfig = toml::from_slice(&resource.get()?)?;
+                let config: CKBAppConfig = parse_config(&resource.get()?)?;
                 Ok(AppConfig::with_ckb(
                     config.derive_options(root_dir.as_ref(), subcommand_name)?,
                 ))
@@ -182,6 +240,100 @@ impl CKBAppConfig {
 
         Ok(self)
     }
+
+    /// The file which keeps the tx pool across restarts, see `tx_pool.persist_on_shutdown`.
+    pub fn tx_pool_persisted_path(&self) -> PathBuf {
+        self.data_dir.join("tx_pool").join("persisted")
+    }
+
+    /// Compares the running config with the one reloaded from the config file.
+    pub fn diff(&self, reloaded: &CKBAppConfig) -> ConfigChanges {
+        let mut changed = Vec::new();
+        match (toml::Value::try_from(self), toml::Value::try_from(reloaded)) {
+            (Ok(running), Ok(reloaded)) => diff_value("", &running, &reloaded, &mut changed),
+            _ => changed.push("*".to_string()),
+        }
+        let (reloadable, immutable) = changed.into_iter().partition(|path| {
+            RELOADABLE_OPTIONS
+                .iter()
+                .any(|option| path == option || path.starts_with(&format!("{}.", option)))
+        });
+        ConfigChanges {
+            reloadable,
+            immutable,
+        }
+    }
+
+    /// Dumps the config as TOML with the secrets hidden, see `REDACTED_OPTIONS`.
+    pub fn redacted(&self) -> Result<String, ExitCode> {
+        let mut value = toml::Value::try_from(self)?;
+        redact_value("", &mut value);
+        toml::to_string(&value).map_err(Into::into)
+    }
+}
+
+fn redact_value(path: &str, value: &mut toml::Value) {
+    match value {
+        toml::Value::Table(table) => {
+            for (key, value) in table.iter_mut() {
+                let path = if path.is_empty() {
+                    key.to_string()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                let lowercase = key.to_lowercase();
+                if REDACTED_OPTIONS.contains(&path.as_str())
+                    || ["secret", "password", "token"]
+                        .iter()
+                        .any(|word| lowercase.contains(word))
+                {
+                    *value = toml::Value::String(REDACTED.to_string());
+                } else {
+                    redact_value(&path, value);
+                }
+            }
+        }
+        toml::Value::Array(array) => {
+            let path = format!("{}[]", path);
+            for value in array.iter_mut() {
+                redact_value(&path, value);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn diff_value(
+    path: &str,
+    running: &toml::Value,
+    reloaded: &toml::Value,
+    changed: &mut Vec<String>,
+) {
+    let join = |key: &str| {
+        if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", path, key)
+        }
+    };
+    match (running, reloaded) {
+        (toml::Value::Table(running), toml::Value::Table(reloaded)) => {
+            for (key, value) in running {
+                match reloaded.get(key) {
+                    Some(reloaded_value) => diff_value(&join(key), value, reloaded_value, changed),
+                    None => changed.push(join(key)),
+                }
+            }
+            for key in reloaded.keys().filter(|key| !running.contains_key(*key)) {
+                changed.push(join(key));
+            }
+        }
+        _ => {
+            if running != reloaded {
+                changed.push(path.to_string());
+            }
+        }
+    }
 }
 
 impl MinerAppConfig {
@@ -196,6 +348,61 @@ impl MinerAppConfig {
     }
 }
 
+// Parses the config file, then applies the overrides in the environment variables.
+fn parse_config<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, ExitCode> {
+    let mut value: toml::Value = toml::from_slice(bytes)?;
+    apply_env_overrides(&mut value, env::vars())?;
+    value.try_into().map_err(Into::into)
+}
+
+// Only the variables with the separator are overrides, so `CKB_LOG` and the like are left out.
+// The values are parsed as TOML values, e.g. `125` or `["a", "b"]`, and fall back to strings.
+fn apply_env_overrides<I>(config: &mut toml::Value, vars: I) -> Result<(), ExitCode>
+where
+    I: IntoIterator<Item = (String, String)>,
+{
+    for (name, raw) in vars {
+        if !name.starts_with(ENV_PREFIX) || !name.contains(ENV_SEPARATOR) {
+            continue;
+        }
+        let path: Vec<String> = name[ENV_PREFIX.len()..]
+            .split(ENV_SEPARATOR)
+            .map(str::to_lowercase)
+            .collect();
+        if path.iter().any(String::is_empty) {
+            eprintln!(
+                "Config Error: invalid override environment variable {}",
+                name
+            );
+            return Err(ExitCode::Config);
+        }
+        let value = toml::from_str::<toml::value::Table>(&format!("value = {}", raw))
+            .ok()
+            .and_then(|mut table| table.remove("value"))
+            .unwrap_or_else(|| toml::Value::String(raw));
+
+        let mut table = &mut *config;
+        for segment in &path {
+            let inner = match table {
+                toml::Value::Table(inner) => inner,
+                _ => {
+                    eprintln!(
+                        "Config Error: {} overrides {}, which is not in a table",
+                        name,
+                        path.join(".")
+                    );
+                    return Err(ExitCode::Config);
+                }
+            };
+            table = inner
+                .entry(segment.clone())
+                .or_insert_with(|| toml::Value::Table(Default::default()));
+        }
+        *table = value;
+    }
+    Ok(())
+}
+
 fn canonicalize_data_dir(data_dir: PathBuf, root_dir: &Path) -> Result<PathBuf, ExitCode> {
     let path = if data_dir.is_absolute() {
         data_dir
@@ -233,6 +440,7 @@ fn ensure_ckb_dir(r: Resource) -> Result<Resource, ExitCode> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ckb_fee_estimator::FeeRate;
     use ckb_resource::TemplateContext;
 
     fn mkdir() -> tempfile::TempDir {
@@ -253,11 +461,144 @@ mod tests {
             .expect("deserialize config");
     }
 
+    #[test]
+    fn test_env_overrides() {
+        let mut config: toml::Value = toml::from_str(
+            r#"
+            data_dir = "data"
+            [network]
+            max_peers = 125
+            bootnodes = []
+            "#,
+        )
+        .unwrap();
+        let vars = vec![
+            ("CKB_LOG", "debug"),
+            ("PATH", "/usr/bin"),
+            ("CKB_NETWORK__MAX_PEERS", "8"),
+            ("CKB_NETWORK__BOOTNODES", r#"["/ip4/127.0.0.1/tcp/8115"]"#),
+            ("CKB_RPC__LISTEN_ADDRESS", "0.0.0.0:8114"),
+        ];
+        apply_env_overrides(
+            &mut config,
+            vars.into_iter()
+                .map(|(name, value)| (name.to_string(), value.to_string())),
+        )
+        .unwrap();
+        let expected: toml::Value = toml::from_str(
+            r#"
+            data_dir = "data"
+            [network]
+            max_peers = 8
+            bootnodes = ["/ip4/127.0.0.1/tcp/8115"]
+            [rpc]
+            listen_address = "0.0.0.0:8114"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config, expected);
+
+        let vars = vec![("CKB_DATA_DIR__PATH".to_string(), "db".to_string())];
+        assert_eq!(
+            apply_env_overrides(&mut config, vars),
+            Err(ExitCode::Config)
+        );
+    }
+
+    #[test]
+    fn test_diff_reloaded_config() {
+        let resource = Resource::bundled_ckb_config();
+        let running: CKBAppConfig =
+            toml::from_slice(&resource.get().expect("read bundled file")).expect("deserialize");
+        assert_eq!(running.diff(&running), ConfigChanges::default());
+
+        let mut reloaded = running.clone();
+        reloaded.logger.filter = Some("info,ckb-sync=debug".to_string());
+        reloaded.tx_pool.min_fee_rate = FeeRate::from_u64(2000);
+        reloaded.tx_pool.max_ancestors_count += 1;
+        reloaded.rpc.listen_address = "127.0.0.1:18114".to_string();
+        reloaded.network.whitelist_peers = vec!["/ip4/10.0.0.1/tcp/8115".parse().unwrap()];
+        reloaded.network.whitelist_only = !running.network.whitelist_only;
+        let changes = running.diff(&reloaded);
+        assert_eq!(
+            changes.reloadable,
+            vec![
+                "logger.filter",
+                "network.whitelist_peers",
+                "tx_pool.min_fee_rate"
+            ]
+        );
+        assert_eq!(
+            changes.immutable,
+            vec![
+                "network.whitelist_only",
+                "rpc.listen_address",
+                "tx_pool.max_ancestors_count"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_redacted_config() {
+        let resource = Resource::bundled_ckb_config();
+        let mut config: CKBAppConfig =
+            toml::from_slice(&resource.get().expect("read bundled file")).expect("deserialize");
+        config.sentry.dsn = "https://secret@sentry.io/1".to_string();
+        config.network.whitelist_peers = vec!["/ip4/10.0.0.1/tcp/8115".parse().unwrap()];
+        config.network.whitelist.peers = vec!["/ip4/10.0.0.2/tcp/8115".parse().unwrap()];
+
+        let redacted: toml::Value =
+            toml::from_str(&config.redacted().expect("redact")).expect("parse redacted");
+        assert_eq!(redacted["sentry"]["dsn"].as_str(), Some(REDACTED));
+        assert_eq!(
+            redacted["network"]["whitelist_peers"].as_str(),
+            Some(REDACTED)
+        );
+        assert_eq!(
+            redacted["network"]["whitelist"]["peers"].as_str(),
+            Some(REDACTED)
+        );
+        assert_eq!(
+            redacted["rpc"]["listen_address"].as_str(),
+            Some(config.rpc.listen_address.as_str())
+        );
+    }
+
+    #[test]
+    fn test_redacted_notify_hooks() {
+        let resource = Resource::bundled_ckb_config();
+        let mut config: CKBAppConfig =
+            toml::from_slice(&resource.get().expect("read bundled file")).expect("deserialize");
+        config.notify.watched_transaction_webhook =
+            Some("https://example.com/watched?key=abc".to_string());
+        config.notify.hooks = vec![HookConfig {
+            event: HookEvent::Reorg,
+            command: Some("curl -H 'Authorization: abc' https://example.com".to_string()),
+            webhook: Some("https://example.com/hook?token=abc".to_string()),
+            payload: Some("{tip_number}".to_string()),
+            min_reorg_depth: None,
+        }];
+
+        let redacted: toml::Value =
+            toml::from_str(&config.redacted().expect("redact")).expect("parse redacted");
+        let notify = &redacted["notify"];
+        assert_eq!(
+            notify["watched_transaction_webhook"].as_str(),
+            Some(REDACTED)
+        );
+        let hook = &notify["hooks"][0];
+        assert_eq!(hook["webhook"].as_str(), Some(REDACTED));
+        assert_eq!(hook["command"].as_str(), Some(REDACTED));
+        assert_eq!(hook["event"].as_str(), Some("reorg"));
+        assert_eq!(hook["payload"].as_str(), Some("{tip_number}"));
+    }
+
     #[test]
     fn test_export_dev_config_files() {
         let dir = mkdir();
         let context = TemplateContext {
             spec: "dev",
+            profile: None,
             rpc_port: "7000",
             p2p_port: "8000",
             log_to_file: true,
@@ -305,6 +646,7 @@ mod tests {
         let dir = mkdir();
         let context = TemplateContext {
             spec: "dev",
+            profile: None,
             rpc_port: "7000",
             p2p_port: "8000",
             log_to_file: false,
@@ -341,6 +683,7 @@ mod tests {
         let dir = mkdir();
         let context = TemplateContext {
             spec: "testnet",
+            profile: None,
             rpc_port: "7000",
             p2p_port: "8000",
             log_to_file: true,
@@ -388,6 +731,7 @@ mod tests {
         let dir = mkdir();
         let context = TemplateContext {
             spec: "integration",
+            profile: None,
             rpc_port: "7000",
             p2p_port: "8000",
             log_to_file: true,
@@ -433,6 +777,7 @@ mod tests {
         let dir = mkdir();
         let context = TemplateContext {
             spec: "dev",
+            profile: None,
             rpc_port: "7000",
             p2p_port: "8000",
             log_to_file: true,
@@ -474,4 +819,30 @@ mod tests {
             assert_eq!(miner_config.miner.client.rpc_url, "http://127.0.0.1:7000/");
         }
     }
+
+    #[test]
+    fn test_export_rpc_public_profile() {
+        let dir = mkdir();
+        let context = TemplateContext {
+            spec: "mainnet",
+            profile: Some("rpc-public"),
+            rpc_port: "7000",
+            p2p_port: "8000",
+            log_to_file: true,
+            log_to_stdout: true,
+            block_assembler: "",
+            spec_source: "bundled",
+        };
+        Resource::bundled_ckb_config()
+            .export(&context, dir.path())
+            .expect("export config files");
+        let app_config = AppConfig::load_for_subcommand(dir.path(), cli::CMD_RUN)
+            .unwrap_or_else(|err| panic!(err));
+        let ckb_config = app_config.into_ckb().unwrap_or_else(|err| panic!(err));
+        assert_eq!(ckb_config.rpc.listen_address, "0.0.0.0:7000");
+        assert!(ckb_config.rpc.chain_enable());
+        assert!(!ckb_config.rpc.net_enable());
+        assert!(!ckb_config.rpc.miner_enable());
+        assert_eq!(ckb_config.network.max_peers, 64);
+    }
 }
@@ -2,6 +2,7 @@ use crate::{CKBAppConfig, MemoryTrackerConfig, MinerConfig};
 use ckb_chain_spec::consensus::Consensus;
 use ckb_jsonrpc_types::ScriptHashType;
 use ckb_pow::PowEngine;
+use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::sync::Arc;
 
@@ -9,20 +10,38 @@ pub struct ExportArgs {
     pub config: Box<CKBAppConfig>,
     pub consensus: Consensus,
     pub target: PathBuf,
+    /// The possible values of `--format`
+    pub format: String,
+    /// Exports the cell set as well
+    pub with_cells: bool,
 }
 
 pub struct ImportArgs {
     pub config: Box<CKBAppConfig>,
     pub consensus: Consensus,
     pub source: PathBuf,
+    /// Skips the verification of the imported blocks
+    pub trusted: bool,
 }
 
 pub struct RunArgs {
+    pub root_dir: PathBuf,
     pub config: Box<CKBAppConfig>,
     pub consensus: Consensus,
     pub block_assembler_advanced: bool,
 }
 
+pub struct IndexerArgs {
+    pub config: Box<CKBAppConfig>,
+    pub remote: String,
+}
+
+pub struct DnsSeedArgs {
+    pub config: Box<CKBAppConfig>,
+    pub domain: String,
+    pub listen: SocketAddr,
+}
+
 pub struct ProfArgs {
     pub config: Box<CKBAppConfig>,
     pub consensus: Consensus,
@@ -31,6 +50,16 @@ pub struct ProfArgs {
     pub to: u64,
 }
 
+pub struct ReplayArgs {
+    pub config: Box<CKBAppConfig>,
+    pub consensus: Consensus,
+    pub tmp_target: PathBuf,
+    pub from: Option<u64>,
+    pub to: Option<u64>,
+    /// Verifications skipped in range, the possible values of `--skip`
+    pub skip: Vec<String>,
+}
+
 pub struct MinerArgs {
     pub config: MinerConfig,
     pub pow_engine: Arc<dyn PowEngine>,
@@ -49,6 +78,7 @@ pub struct InitArgs {
     pub interactive: bool,
     pub root_dir: PathBuf,
     pub chain: String,
+    pub profile: Option<String>,
     pub rpc_port: String,
     pub p2p_port: String,
     pub log_to_file: bool,
@@ -80,6 +110,37 @@ pub struct ResetDataArgs {
     pub logs_dir: Option<PathBuf>,
 }
 
+pub struct ReportArgs {
+    pub config: Box<CKBAppConfig>,
+    /// The log file of `ckb run`
+    pub log_file: Option<PathBuf>,
+    pub rpc_url: String,
+    pub target: Option<PathBuf>,
+    pub log_lines: usize,
+}
+
 pub struct PeerIDArgs {
     pub peer_id: p2p::secio::PeerId,
 }
+
+pub struct PeerArgs {
+    pub rpc_url: String,
+    pub command: PeerCommand,
+}
+
+pub enum PeerCommand {
+    List {
+        banned: bool,
+    },
+    Ban {
+        address: String,
+        ban_time: Option<u64>,
+        reason: Option<String>,
+    },
+    Unban {
+        address: String,
+    },
+    Dial {
+        address: String,
+    },
+}
@@ -1,6 +1,8 @@
 use ckb_build_info::Version;
-use ckb_resource::{DEFAULT_P2P_PORT, DEFAULT_RPC_PORT, DEFAULT_SPEC};
+use ckb_resource::{AVAILABLE_PROFILES, DEFAULT_P2P_PORT, DEFAULT_RPC_PORT, DEFAULT_SPEC};
+use ckb_types::H256;
 use clap::{App, AppSettings, Arg, ArgGroup, ArgMatches, SubCommand};
+use std::str::FromStr;
 
 pub const CMD_RUN: &str = "run";
 pub const CMD_MINER: &str = "miner";
@@ -8,21 +10,33 @@ pub const CMD_EXPORT: &str = "export";
 pub const CMD_IMPORT: &str = "import";
 pub const CMD_INIT: &str = "init";
 pub const CMD_PROF: &str = "prof";
+pub const CMD_REPLAY: &str = "replay";
 pub const CMD_STATS: &str = "stats";
 pub const CMD_LIST_HASHES: &str = "list-hashes";
 pub const CMD_RESET_DATA: &str = "reset-data";
+pub const CMD_INDEXER: &str = "indexer";
 pub const CMD_PEERID: &str = "peer-id";
 pub const CMD_GEN_SECRET: &str = "gen";
 pub const CMD_FROM_SECRET: &str = "from-secret";
+pub const CMD_PEER: &str = "peer";
+pub const CMD_PEER_LIST: &str = "list";
+pub const CMD_PEER_BAN: &str = "ban";
+pub const CMD_PEER_UNBAN: &str = "unban";
+pub const CMD_PEER_DIAL: &str = "dial";
+pub const CMD_REPORT: &str = "report";
+pub const CMD_DNSSEED: &str = "dnsseed";
 
 pub const ARG_CONFIG_DIR: &str = "config-dir";
 pub const ARG_FORMAT: &str = "format";
 pub const ARG_TARGET: &str = "target";
+pub const ARG_WITH_CELLS: &str = "with-cells";
+pub const ARG_TRUSTED: &str = "trusted";
 pub const ARG_SOURCE: &str = "source";
 pub const ARG_DATA: &str = "data";
 pub const ARG_LIST_CHAINS: &str = "list-chains";
 pub const ARG_INTERACTIVE: &str = "interactive";
 pub const ARG_CHAIN: &str = "chain";
+pub const ARG_PROFILE: &str = "profile";
 pub const ARG_IMPORT_SPEC: &str = "import-spec";
 pub const ARG_P2P_PORT: &str = "p2p-port";
 pub const ARG_RPC_PORT: &str = "rpc-port";
@@ -46,6 +60,18 @@ pub const ARG_NETWORK_SECRET_KEY: &str = "network-secret-key";
 pub const ARG_LOGS: &str = "logs";
 pub const ARG_TMP_TARGET: &str = "tmp-target";
 pub const ARG_SECRET_PATH: &str = "secret-path";
+pub const ARG_REMOTE: &str = "remote";
+pub const ARG_URL: &str = "url";
+pub const ARG_ADDRESS: &str = "address";
+pub const ARG_BANNED: &str = "banned";
+pub const ARG_BAN_TIME: &str = "ban-time";
+pub const ARG_REASON: &str = "reason";
+pub const ARG_SKIP: &str = "skip";
+pub const ARG_LOG_LINES: &str = "log-lines";
+pub const ARG_VERIFICATION_THREADS: &str = "verification-threads";
+pub const ARG_DOMAIN: &str = "domain";
+pub const ARG_LISTEN: &str = "listen";
+pub const ARG_ASSUME_VALID_TARGET: &str = "assume-valid-target";
 
 const GROUP_BA: &str = "ba";
 
@@ -64,6 +90,18 @@ fn basic_app<'b>() -> App<'static, 'b> {
                     "Runs as if ckb was started in <path> instead of the current working directory.",
                 ),
         )
+        .arg(
+            Arg::with_name(ARG_PROFILE)
+                .long(ARG_PROFILE)
+                .value_name("name")
+                .takes_value(true)
+                .validator(is_profile_name)
+                .help(
+                    "Runs in the isolated directory <path>/profiles/<name>, which has its own \
+                     config files, data and ports. E.g. `ckb --profile dev init --chain dev` \
+                     sets up a dev chain beside the mainnet node in <path>",
+                ),
+        )
         .subcommand(run())
         .subcommand(miner())
         .subcommand(export())
@@ -71,9 +109,14 @@ fn basic_app<'b>() -> App<'static, 'b> {
         .subcommand(list_hashes())
         .subcommand(init())
         .subcommand(prof())
+        .subcommand(replay())
         .subcommand(stats())
         .subcommand(reset_data())
+        .subcommand(indexer())
         .subcommand(peer_id())
+        .subcommand(peer())
+        .subcommand(report())
+        .subcommand(dnsseed())
 }
 
 pub fn get_matches(version: &Version) -> ArgMatches<'static> {
@@ -84,11 +127,35 @@ pub fn get_matches(version: &Version) -> ArgMatches<'static> {
 }
 
 fn run() -> App<'static, 'static> {
-    SubCommand::with_name(CMD_RUN).about("Runs ckb node").arg(
-        Arg::with_name(ARG_BA_ADVANCED)
-            .long(ARG_BA_ADVANCED)
-            .help("Allows any block assembler code hash and args"),
-    )
+    SubCommand::with_name(CMD_RUN)
+        .about("Runs ckb node")
+        .arg(
+            Arg::with_name(ARG_BA_ADVANCED)
+                .long(ARG_BA_ADVANCED)
+                .help("Allows any block assembler code hash and args"),
+        )
+        .arg(
+            Arg::with_name(ARG_VERIFICATION_THREADS)
+                .long(ARG_VERIFICATION_THREADS)
+                .takes_value(true)
+                .validator(is_usize)
+                .help(
+                    "Threads verifying the scripts of transactions and blocks, \
+                     0 means the number of logical CPUs. \
+                     Overrides `tx_pool.verify_workers` in ckb.toml",
+                ),
+        )
+        .arg(
+            Arg::with_name(ARG_ASSUME_VALID_TARGET)
+                .long(ARG_ASSUME_VALID_TARGET)
+                .takes_value(true)
+                .validator(is_h256)
+                .help(
+                    "The hash of a trusted block, its ancestors skip the script verification \
+                     during the initial block download. \
+                     Overrides `chain.assume_valid_target` in ckb.toml",
+                ),
+        )
 }
 
 fn miner() -> App<'static, 'static> {
@@ -156,10 +223,28 @@ fn reset_data() -> App<'static, 'static> {
         )
 }
 
+fn indexer() -> App<'static, 'static> {
+    SubCommand::with_name(CMD_INDEXER)
+        .about(
+            "Runs the script indexer following a remote node\n\
+             Example:\n\
+             ckb -C <dir> indexer --remote http://127.0.0.1:8114",
+        )
+        .arg(
+            Arg::with_name(ARG_REMOTE)
+                .long(ARG_REMOTE)
+                .required(true)
+                .takes_value(true)
+                .value_name("url")
+                .help("Specifies the JSON-RPC url of the followed node"),
+        )
+}
+
 pub(crate) fn stats() -> App<'static, 'static> {
     SubCommand::with_name(CMD_STATS)
         .about(
-            "Statics chain information\n\
+            "Statics chain information in the local store: uncle rate, transactions, \
+             average fee, tps and epoch lengths. The node doesn't need to be running.\n\
              Example:\n\
              ckb -C <dir> stats --from 1 --to 500",
         )
@@ -201,6 +286,48 @@ fn prof() -> App<'static, 'static> {
         )
 }
 
+pub(crate) fn replay() -> App<'static, 'static> {
+    SubCommand::with_name(CMD_REPLAY)
+        .about(
+            "Replays the blocks in the local store into a temporary store, and reports the time \
+             spent in each phase of processing blocks. The node doesn't need to be running.\n\
+             Example:\n\
+             ckb -C <dir> replay <TMP> --from 1000 --to 2000 --skip epoch --skip reward",
+        )
+        .arg(Arg::with_name(ARG_TMP_TARGET).required(true).index(1).help(
+            "Specifies a target path, replay command make a temporary directory inside of target and the directory will be automatically deleted when finished",
+        ))
+        .arg(
+            Arg::with_name(ARG_FROM)
+                .long(ARG_FROM)
+                .takes_value(true)
+                .help("Specifies from block number, the blocks before it are replayed without verification [default: 1]"),
+        )
+        .arg(
+            Arg::with_name(ARG_TO)
+                .long(ARG_TO)
+                .takes_value(true)
+                .help("Specifies to block number [default: the tip]"),
+        )
+        .arg(
+            Arg::with_name(ARG_SKIP)
+                .long(ARG_SKIP)
+                .value_name("verification")
+                .multiple(true)
+                .number_of_values(1)
+                .possible_values(&[
+                    "non-contextual",
+                    "epoch",
+                    "uncles",
+                    "two-phase-commit",
+                    "dao-header",
+                    "reward",
+                    "all",
+                ])
+                .help("Skips the verification when replaying the blocks in range"),
+        )
+}
+
 fn export() -> App<'static, 'static> {
     SubCommand::with_name(CMD_EXPORT)
         .about("Exports ckb data")
@@ -213,6 +340,21 @@ fn export() -> App<'static, 'static> {
                 .index(1)
                 .help("Specifies the export target path."),
         )
+        .arg(
+            Arg::with_name(ARG_FORMAT)
+                .long(ARG_FORMAT)
+                .takes_value(true)
+                .possible_values(&["json", "flat", "car"])
+                .default_value("json")
+                .help(
+                    "Specifies the format of the exported blocks. The flat and the car formats \
+                     checksum the blocks, `ckb import` detects the format",
+                ),
+        )
+        .arg(Arg::with_name(ARG_WITH_CELLS).long(ARG_WITH_CELLS).help(
+            "Exports the cell set after the blocks in the flat format, the import checks \
+                     the imported chain against it",
+        ))
 }
 
 fn import() -> App<'static, 'static> {
@@ -227,6 +369,10 @@ fn import() -> App<'static, 'static> {
                 .index(1)
                 .help("Specifies the exported data path."),
         )
+        .arg(Arg::with_name(ARG_TRUSTED).long(ARG_TRUSTED).help(
+            "Skips the verification of the imported blocks, only for the data from a \
+                     trusted source",
+        ))
 }
 
 fn list_hashes() -> App<'static, 'static> {
@@ -264,6 +410,16 @@ fn init() -> App<'static, 'static> {
                 .default_value(DEFAULT_SPEC)
                 .help("Initializes CKB direcotry for <chain>"),
         )
+        .arg(
+            Arg::with_name(ARG_PROFILE)
+                .long(ARG_PROFILE)
+                .value_name("profile")
+                .possible_values(AVAILABLE_PROFILES)
+                .help(
+                    "Tunes the created config files for a kind of deployment. \
+                     It also selects the chain if --chain is not given",
+                ),
+        )
         .arg(
             Arg::with_name(ARG_IMPORT_SPEC)
                 .long(ARG_IMPORT_SPEC)
@@ -382,6 +538,169 @@ fn peer_id() -> App<'static, 'static> {
         )
 }
 
+pub(crate) fn peer() -> App<'static, 'static> {
+    SubCommand::with_name(CMD_PEER)
+        .about(
+            "Manages the peers of a running node via its RPC\n\
+             Example:\n\
+             ckb -C <dir> peer ban 192.168.0.2 --reason spam",
+        )
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .arg(
+            Arg::with_name(ARG_URL)
+                .long(ARG_URL)
+                .global(true)
+                .takes_value(true)
+                .value_name("url")
+                .help("Specifies the JSON-RPC url of the node [default: rpc.listen_address in ckb.toml]"),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_PEER_LIST)
+                .about("Lists the connected peers")
+                .arg(
+                    Arg::with_name(ARG_BANNED)
+                        .long(ARG_BANNED)
+                        .help("Lists the banned addresses instead"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_PEER_BAN)
+                .about("Bans an IP or a subnet, e.g. 192.168.0.0/24")
+                .arg(
+                    Arg::with_name(ARG_ADDRESS)
+                        .required(true)
+                        .index(1)
+                        .help("Specifies the IP/Subnet"),
+                )
+                .arg(
+                    Arg::with_name(ARG_BAN_TIME)
+                        .long(ARG_BAN_TIME)
+                        .takes_value(true)
+                        .value_name("milliseconds")
+                        .help("Specifies how long the address is banned [default: 1 day]"),
+                )
+                .arg(
+                    Arg::with_name(ARG_REASON)
+                        .long(ARG_REASON)
+                        .takes_value(true)
+                        .help("Specifies the reason of the ban"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_PEER_UNBAN)
+                .about("Removes an IP or a subnet from the banned list")
+                .arg(
+                    Arg::with_name(ARG_ADDRESS)
+                        .required(true)
+                        .index(1)
+                        .help("Specifies the IP/Subnet"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CMD_PEER_DIAL)
+                .about("Connects to a node")
+                .arg(
+                    Arg::with_name(ARG_ADDRESS)
+                        .required(true)
+                        .index(1)
+                        .help("Specifies the multiaddr of the node, ending with /p2p/<peer_id>"),
+                ),
+        )
+}
+
+fn report() -> App<'static, 'static> {
+    SubCommand::with_name(CMD_REPORT)
+        .about(
+            "Collects the version, the config with the secrets hidden, the recent logs and the \
+             status of a running node into an archive to attach to bug reports",
+        )
+        .arg(
+            Arg::with_name(ARG_TARGET)
+                .short("t")
+                .long(ARG_TARGET)
+                .value_name("path")
+                .takes_value(true)
+                .help("Specifies the archive path [default: <data_dir>/reports/report-<time>.tar.gz]"),
+        )
+        .arg(
+            Arg::with_name(ARG_URL)
+                .long(ARG_URL)
+                .takes_value(true)
+                .value_name("url")
+                .help("Specifies the JSON-RPC url of the node [default: rpc.listen_address in ckb.toml]"),
+        )
+        .arg(
+            Arg::with_name(ARG_LOG_LINES)
+                .long(ARG_LOG_LINES)
+                .takes_value(true)
+                .value_name("n")
+                .default_value("1000")
+                .help("Specifies how many lines at the end of the log file are collected"),
+        )
+}
+
+fn dnsseed() -> App<'static, 'static> {
+    SubCommand::with_name(CMD_DNSSEED)
+        .about(
+            "Serves the signed TXT records of the good addresses in the peer store over DNS, for \
+             the nodes using the domain as `network.dns_seeds`. Runs beside the node in the same \
+             directory, which keeps the peer store updated\n\
+             Example:\n\
+             ckb -C <dir> dnsseed --domain seed.example.com --listen 0.0.0.0:53",
+        )
+        .arg(
+            Arg::with_name(ARG_DOMAIN)
+                .long(ARG_DOMAIN)
+                .required(true)
+                .takes_value(true)
+                .value_name("domain")
+                .help("Specifies the seed domain delegated to this server"),
+        )
+        .arg(
+            Arg::with_name(ARG_LISTEN)
+                .long(ARG_LISTEN)
+                .takes_value(true)
+                .value_name("address")
+                .default_value("0.0.0.0:53")
+                .validator(is_socket_addr)
+                .help("Specifies the UDP address to listen on"),
+        )
+}
+
+fn is_profile_name(name: String) -> Result<(), String> {
+    if !name.is_empty()
+        && name
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_')
+    {
+        Ok(())
+    } else {
+        Err("Profile name must only contain letters, digits, '-' and '_'".to_string())
+    }
+}
+
+fn is_usize(number: String) -> Result<(), String> {
+    number
+        .parse::<usize>()
+        .map(|_| ())
+        .map_err(|err| format!("Must be a non-negative integer: {}", err))
+}
+
+fn is_h256(hash: String) -> Result<(), String> {
+    parse_h256(&hash).map(|_| ())
+}
+
+pub(crate) fn parse_h256(hash: &str) -> Result<H256, String> {
+    H256::from_str(hash.trim_start_matches("0x"))
+        .map_err(|err| format!("Must be a 32-byte hexadecimal hash: {}", err))
+}
+
+fn is_socket_addr(addr: String) -> Result<(), String> {
+    addr.parse::<std::net::SocketAddr>()
+        .map(|_| ())
+        .map_err(|err| format!("Must be an IP address with a port: {}", err))
+}
+
 fn is_hex(hex: String) -> Result<(), String> {
     let tmp = hex.as_bytes();
     if tmp.len() < 2 {
@@ -408,6 +727,19 @@ fn is_hex(hex: String) -> Result<(), String> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn profile_namespace_is_not_the_init_profile() {
+        let matches = basic_app()
+            .get_matches_from_safe(&["ckb", "--profile", "dev_1", "init", "--profile", "dev-fast"])
+            .unwrap();
+        assert_eq!(matches.value_of(ARG_PROFILE), Some("dev_1"));
+        let init_matches = matches.subcommand_matches(CMD_INIT).unwrap();
+        assert_eq!(init_matches.value_of(ARG_PROFILE), Some("dev-fast"));
+
+        let err = basic_app().get_matches_from_safe(&["ckb", "--profile", "../dev", "run"]);
+        assert_eq!(clap::ErrorKind::ValueValidation, err.err().unwrap().kind);
+    }
+
     #[test]
     fn ba_message_requires_ba_arg_or_ba_code_hash() {
         let ok_ba_arg = basic_app().get_matches_from_safe(&[
@@ -478,4 +810,37 @@ mod tests {
 
         assert_eq!(1, sub_matches.occurrences_of(ARG_BA_ADVANCED));
     }
+
+    #[test]
+    fn verification_threads() {
+        let matches = basic_app()
+            .get_matches_from_safe(&["ckb", "run", "--verification-threads", "4"])
+            .unwrap();
+        let sub_matches = matches.subcommand().1.unwrap();
+        assert_eq!(Some("4"), sub_matches.value_of(ARG_VERIFICATION_THREADS));
+
+        let err = basic_app()
+            .get_matches_from_safe(&["ckb", "run", "--verification-threads", "all"])
+            .unwrap_err();
+        assert_eq!(clap::ErrorKind::ValueValidation, err.kind);
+    }
+
+    #[test]
+    fn dnsseed() {
+        let matches = basic_app()
+            .get_matches_from_safe(&["ckb", "dnsseed", "--domain", "seed.example.com"])
+            .unwrap();
+        let sub_matches = matches.subcommand_matches(CMD_DNSSEED).unwrap();
+        assert_eq!(Some("seed.example.com"), sub_matches.value_of(ARG_DOMAIN));
+        assert_eq!(Some("0.0.0.0:53"), sub_matches.value_of(ARG_LISTEN));
+
+        let err = basic_app()
+            .get_matches_from_safe(&["ckb", "dnsseed", "--domain", "a", "--listen", "53"])
+            .unwrap_err();
+        assert_eq!(clap::ErrorKind::ValueValidation, err.kind);
+        let err = basic_app()
+            .get_matches_from_safe(&["ckb", "dnsseed"])
+            .unwrap_err();
+        assert_eq!(clap::ErrorKind::MissingRequiredArgument, err.kind);
+    }
 }
@@ -7,4 +7,44 @@ pub struct Config {
     #[serde(default)]
     pub path: PathBuf,
     pub options: Option<HashMap<String, String>>,
+    // Max open files of rocksdb, -1 keeps all the files open
+    pub max_open_files: Option<i32>,
+    // Pipelines the WAL and the memtable writes, which speeds up the block insertion
+    #[serde(default)]
+    pub enable_pipelined_write: bool,
+    // Fsyncs the WAL on each commit. The WAL is written asynchronously by default, an OS crash may
+    // lose the latest blocks, which are synchronized again
+    #[serde(default)]
+    pub sync_wal: bool,
+    /// The options of the column families by the column names, e.g. `"2"` for the block bodies,
+    /// and `"default"` for the columns not listed
+    #[serde(default)]
+    pub column_families: HashMap<String, ColumnFamilyConfig>,
+}
+
+impl Config {
+    /// The options of the column, falls back to the `default` ones.
+    pub fn column_family(&self, column: &str) -> Option<&ColumnFamilyConfig> {
+        self.column_families
+            .get(column)
+            .or_else(|| self.column_families.get("default"))
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ColumnFamilyConfig {
+    // LRU block cache size in bytes, the rocksdb default is 8MiB
+    pub block_cache_size: Option<usize>,
+    pub compaction_style: Option<CompactionStyle>,
+    // Bits per key of the bloom filters, 10 yields about 1% false positives
+    pub bloom_filter_bits_per_key: Option<i32>,
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum CompactionStyle {
+    Level,
+    Universal,
+    Fifo,
 }
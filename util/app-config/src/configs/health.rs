@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Config {
+    /// Seconds between the self-diagnostic log lines, 0 is disable.
+    pub interval: u64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self { interval: 600 }
+    }
+}
@@ -8,6 +8,14 @@ pub struct Config {
     pub batch_interval: u64,
     /// The maximum number of blocks in a single indexing execution batch, default is 200
     pub batch_size: usize,
+    /// Index all cells and transactions by lock and type script hashes, following new tip
+    /// blocks, default is false
+    #[serde(default)]
+    pub index_scripts: bool,
+    /// Track the main chain transactions of the watched lock scripts and push them through the
+    /// notify service, default is false
+    #[serde(default)]
+    pub watch_list: bool,
     pub db: DBConfig,
 }
 
@@ -16,6 +24,8 @@ impl Default for Config {
         Config {
             batch_interval: 500,
             batch_size: 200,
+            index_scripts: false,
+            watch_list: false,
             db: Default::default(),
         }
     }
@@ -1,4 +1,5 @@
 mod db;
+mod health;
 mod indexer;
 mod memory_tracker;
 mod miner;
@@ -7,20 +8,26 @@ mod network_alert;
 mod notify;
 mod rpc;
 mod store;
+mod stratum;
 mod tx_pool;
 
-pub use db::Config as DBConfig;
+pub use db::{ColumnFamilyConfig, CompactionStyle, Config as DBConfig};
+pub use health::Config as HealthConfig;
 pub use indexer::Config as IndexerConfig;
 pub use memory_tracker::Config as MemoryTrackerConfig;
 pub use miner::{
     ClientConfig as MinerClientConfig, Config as MinerConfig, DummyConfig, EaglesongSimpleConfig,
     ExtraHashFunction, WorkerConfig as MinerWorkerConfig,
 };
-pub use network::Config as NetworkConfig;
+pub use network::{
+    Config as NetworkConfig, DandelionConfig, IdentifyConfig, MisbehaviorAction, PeerScoringConfig,
+    RateLimitConfig, WhitelistConfig,
+};
 pub use network_alert::Config as NetworkAlertConfig;
-pub use notify::Config as NotifyConfig;
+pub use notify::{Config as NotifyConfig, HookConfig, HookEvent, PublisherConfig, PublisherTopic};
 pub use rpc::{Config as RpcConfig, Module as RpcModule};
 pub use store::Config as StoreConfig;
+pub use stratum::Config as StratumConfig;
 pub use tx_pool::{BlockAssemblerConfig, TxPoolConfig};
 
 pub(crate) use network::{generate_random_key, read_secret_key, write_secret_to_file};
@@ -4,6 +4,7 @@ use p2p::{
 };
 use rand::Rng;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::io::{Error, ErrorKind, Read, Write};
 use std::path::PathBuf;
@@ -21,6 +22,10 @@ pub struct Config {
     pub path: PathBuf,
     #[serde(default)]
     pub dns_seeds: Vec<String>,
+    // The hex of the public key signing the records of the DNS seeds, any signer is accepted if
+    // not set
+    #[serde(default)]
+    pub dns_seeds_pubkey: Option<String>,
     // Set if discovery add local address to peer store
     #[serde(default)]
     pub discovery_local_address: bool,
@@ -34,11 +39,172 @@ pub struct Config {
     #[serde(default)]
     pub whitelist_peers: Vec<Multiaddr>,
     #[serde(default)]
+    pub whitelist: WhitelistConfig,
+    #[serde(default)]
     pub upnp: bool,
     #[serde(default)]
     pub bootnode_mode: bool,
+    // Serve light clients and advertise it in identify
+    #[serde(default)]
+    pub light_client_server: bool,
+    // Reconcile the relayed transactions with the peers which support it instead of flooding
+    #[serde(default)]
+    pub tx_reconciliation: bool,
+    // Request the pool inventory of the outbound peers once connected and fetch the missing
+    // transactions
+    #[serde(default)]
+    pub mempool_sync: bool,
     // Max send buffer size
     pub max_send_buffer: Option<usize>,
+    #[serde(default)]
+    pub peer_scoring: PeerScoringConfig,
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig,
+    #[serde(default)]
+    pub dandelion: DandelionConfig,
+    #[serde(default)]
+    pub identify: IdentifyConfig,
+}
+
+/// The trusted peers, such as the operator's own infrastructure nodes. They are never evicted,
+/// banned, disconnected for misbehaviors or rate limited, and all the protocols are opened to
+/// them whatever flags they advertise.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct WhitelistConfig {
+    // The multiaddrs with the peer ids, e.g. `/ip4/192.168.1.2/tcp/8115/p2p/QmSsYx...`
+    #[serde(default)]
+    pub peers: Vec<Multiaddr>,
+}
+
+/// Dandelion relay of the transactions submitted locally, which hides their origin. A local
+/// transaction is announced to a single stem peer of the epoch instead of all the peers, and is
+/// diffused by the node itself only if it is not heard from other peers before its embargo expires.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DandelionConfig {
+    pub enable: bool,
+    // The stem peer is reselected from the outbound peers every epoch
+    pub epoch_secs: u64,
+    pub embargo_secs: u64,
+}
+
+impl Default for DandelionConfig {
+    fn default() -> Self {
+        DandelionConfig {
+            enable: false,
+            epoch_secs: 10 * 60,
+            embargo_secs: 30,
+        }
+    }
+}
+
+/// The identify protocol, which exchanges the chain name, the listen addresses and the observed
+/// address once a peer is connected.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IdentifyConfig {
+    // The peer misbehaves `identify.timeout` if it doesn't send the identify message in time
+    pub timeout_secs: u64,
+    // Max listen addresses in an identify message
+    pub max_addrs: usize,
+    // The peers on the other chains are banned for this long
+    pub ban_on_not_same_net_secs: u64,
+    // Overrides the actions of the misbehavior kinds, e.g. `identify.timeout`, the misbehaviors
+    // are scored by default
+    pub misbehavior_actions: BTreeMap<String, MisbehaviorAction>,
+}
+
+impl Default for IdentifyConfig {
+    fn default() -> Self {
+        IdentifyConfig {
+            timeout_secs: 8,
+            max_addrs: 10,
+            ban_on_not_same_net_secs: 5 * 60,
+            misbehavior_actions: BTreeMap::new(),
+        }
+    }
+}
+
+impl IdentifyConfig {
+    pub fn misbehavior_action(&self, kind: &str) -> MisbehaviorAction {
+        self.misbehavior_actions
+            .get(kind)
+            .cloned()
+            .unwrap_or(MisbehaviorAction::Score)
+    }
+}
+
+/// What to do with a misbehaving peer.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MisbehaviorAction {
+    /// Only logs the misbehavior
+    Warn,
+    /// Adds the penalty to the peer score, see `PeerScoringConfig`
+    Score,
+    /// Disconnects the peer
+    Disconnect,
+    /// Bans the peer for `PeerScoringConfig::ban_time_secs`
+    Ban,
+}
+
+/// Penalties of the peer misbehaviors. The penalties add up to the score of a peer, which
+/// decays over time, and the peer is disconnected or banned once the score reaches the thresholds.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PeerScoringConfig {
+    pub disconnect_threshold: u32,
+    pub ban_threshold: u32,
+    pub ban_time_secs: u64,
+    // The score halves every `decay_half_life_secs` seconds, 0 disables decaying
+    pub decay_half_life_secs: u64,
+    // Overrides the default penalties by the misbehavior kinds, e.g.
+    // `identify.duplicate_listen_addrs`
+    pub penalties: BTreeMap<String, u32>,
+}
+
+impl Default for PeerScoringConfig {
+    fn default() -> Self {
+        PeerScoringConfig {
+            disconnect_threshold: 100,
+            ban_threshold: 200,
+            ban_time_secs: 60 * 60,
+            decay_half_life_secs: 10 * 60,
+            penalties: BTreeMap::new(),
+        }
+    }
+}
+
+/// Limits of the inbound connections and the messages the peers send, 0 disables a limit. The
+/// peers in the whitelist are not limited.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    // Max inbound connections from an IP, the loopback addresses are not limited
+    pub max_inbound_peers_per_ip: u32,
+    // Max inbound connections from a subnet, which is /16 for IPv4 and /32 for IPv6
+    pub max_inbound_peers_per_subnet: u32,
+    // The messages a session sends per second on each protocol
+    pub messages_per_sec: u32,
+    // The messages a session can send in a burst on each protocol
+    pub message_burst: u32,
+    // The bytes a session sends per second on all protocols
+    pub bytes_per_sec: u64,
+    // The bytes a session can send in a burst on all protocols
+    pub byte_burst: u64,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        RateLimitConfig {
+            max_inbound_peers_per_ip: 8,
+            max_inbound_peers_per_subnet: 32,
+            messages_per_sec: 100,
+            message_burst: 1000,
+            bytes_per_sec: 0,
+            byte_burst: 0,
+        }
+    }
 }
 
 pub(crate) fn generate_random_key() -> [u8; 32] {
@@ -126,9 +292,14 @@ impl Config {
         }
     }
 
+    /// Returns the peers in both `whitelist_peers` and `[network.whitelist]`.
     pub fn whitelist_peers(&self) -> Result<Vec<(PeerId, Multiaddr)>, Error> {
-        let mut peers = Vec::with_capacity(self.whitelist_peers.len());
-        for addr_str in &self.whitelist_peers {
+        let mut peers = Vec::with_capacity(self.whitelist_peers.len() + self.whitelist.peers.len());
+        for addr_str in self
+            .whitelist_peers
+            .iter()
+            .chain(self.whitelist.peers.iter())
+        {
             let mut addr = addr_str.to_owned();
             let peer_id = match addr.pop() {
                 Some(Protocol::P2P(key)) => PeerId::from_bytes(key.to_vec()).map_err(|_| {
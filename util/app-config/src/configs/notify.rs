@@ -3,4 +3,52 @@ use serde::{Deserialize, Serialize};
 pub struct Config {
     pub new_block_notify_script: Option<String>,
     pub network_alert_notify_script: Option<String>,
+    /// URL which receives a POST with the JSON of every watched transaction
+    pub watched_transaction_webhook: Option<String>,
+    /// Commands and webhooks triggered by chain events
+    #[serde(default)]
+    pub hooks: Vec<HookConfig>,
+    /// Publishes the chain events to the external subscribers
+    pub publisher: Option<PublisherConfig>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookEvent {
+    NewBlock,
+    Reorg,
+    NetworkAlert,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HookConfig {
+    pub event: HookEvent,
+    /// Shell command run with `sh -c`, the payload is written to its stdin
+    pub command: Option<String>,
+    /// URL which receives a POST with the payload
+    pub webhook: Option<String>,
+    /// Payload template, `{name}` is replaced by the event variable, the event JSON by default
+    pub payload: Option<String>,
+    /// Only reorgs detaching at least this number of blocks trigger the hook, default is 1
+    pub min_reorg_depth: Option<u64>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PublisherTopic {
+    NewBlock,
+    Reorg,
+    ConfirmedTransaction,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PublisherConfig {
+    /// TCP address the subscribers connect to, the events only go to the sinks plugged in by
+    /// `NotifyService::add_sink` if it is absent
+    pub listen_address: Option<String>,
+    /// The published topics, all topics by default
+    #[serde(default)]
+    pub topics: Vec<PublisherTopic>,
+    /// A transaction is published once its block gets this number of confirmations, default is 1
+    pub confirmations: Option<u64>,
 }
@@ -8,6 +8,9 @@ pub struct Config {
     pub block_tx_hashes_cache_size: usize,
     pub block_uncles_cache_size: usize,
     pub cellbase_cache_size: usize,
+    /// Keep the bodies of this many latest blocks and prune the older ones, 0 is disable.
+    #[serde(default)]
+    pub prune_keep_blocks: u64,
 }
 
 impl Default for Config {
@@ -19,6 +22,7 @@ impl Default for Config {
             block_tx_hashes_cache_size: 30,
             block_uncles_cache_size: 30,
             cellbase_cache_size: 30,
+            prune_keep_blocks: 0,
         }
     }
 }
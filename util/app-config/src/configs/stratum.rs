@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Config {
+    /// The TCP address which the miners connect to
+    pub listen_address: String,
+    /// The difficulty of the shares, the share target is the max target divided by it. 0 is
+    /// disable, the shares must meet the block target then.
+    #[serde(default)]
+    pub share_difficulty: u64,
+    /// Milliseconds between checking the new tip and the new transactions
+    #[serde(default = "default_poll_interval")]
+    pub poll_interval: u64,
+    /// Seconds a job lasts before it is refreshed for the new transactions, the new tip always
+    /// refreshes the job.
+    #[serde(default = "default_job_refresh_interval")]
+    pub job_refresh_interval: u64,
+}
+
+fn default_poll_interval() -> u64 {
+    500
+}
+
+fn default_job_refresh_interval() -> u64 {
+    10
+}
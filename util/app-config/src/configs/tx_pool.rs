@@ -4,6 +4,7 @@ use ckb_jsonrpc_types::{JsonBytes, ScriptHashType};
 use ckb_types::core::Cycle;
 use ckb_types::H256;
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 // default min fee rate, 1000 shannons per kilobyte
 const DEFAULT_MIN_FEE_RATE: FeeRate = FeeRate::from_u64(1000);
@@ -11,6 +12,8 @@ const DEFAULT_MIN_FEE_RATE: FeeRate = FeeRate::from_u64(1000);
 const DEFAULT_MAX_TX_VERIFY_CYCLES: Cycle = TWO_IN_TWO_OUT_CYCLES * 20;
 // default max ancestors count
 const DEFAULT_MAX_ANCESTORS_COUNT: usize = 25;
+// default milliseconds a caller waits for the room in the full tx pool queue
+const DEFAULT_QUEUE_TIMEOUT_MILLIS: u64 = 3_000;
 
 /// Transaction pool configuration
 #[derive(Copy, Clone, Debug, Serialize, Deserialize)]
@@ -31,6 +34,50 @@ pub struct TxPoolConfig {
     pub max_tx_verify_cycles: Cycle,
     // max ancestors size limit for a single tx
     pub max_ancestors_count: usize,
+    // max total bytes of a tx and its in-pool ancestors, 0 means unlimited
+    #[serde(default)]
+    pub max_ancestors_size: usize,
+    // max count of a tx and its in-pool descendants, 0 means unlimited
+    #[serde(default)]
+    pub max_descendants_count: usize,
+    // max total bytes of a tx and its in-pool descendants, 0 means unlimited
+    #[serde(default)]
+    pub max_descendants_size: usize,
+    // threads used by script verification, 0 means the number of logical CPUs
+    #[serde(default)]
+    pub verify_workers: usize,
+    // seconds between persisting tx verify cache to store, 0 means disabled
+    #[serde(default)]
+    pub verify_cache_persist_interval: u64,
+    // wall time budget in milliseconds for the scripts of a single tx, 0 means unlimited
+    #[serde(default)]
+    pub max_tx_verify_millis: u64,
+    // save the pool to <data_dir>/tx_pool/persisted on shutdown and reload it on startup
+    #[serde(default)]
+    pub persist_on_shutdown: bool,
+    // max count of the txs kept until their missing parents arrive, 0 means orphans are rejected
+    #[serde(default)]
+    pub max_orphan_txs: usize,
+    // seconds before an orphan tx is evicted, 0 means 4 hours
+    #[serde(default)]
+    pub orphan_expiry_secs: u64,
+    // milliseconds a caller waits for the room in the full tx pool queue, 0 means the callers
+    // fail at once
+    #[serde(default = "default_queue_timeout_millis")]
+    pub queue_timeout_millis: u64,
+    // txs with an output below <min_output_capacity> shannons are rejected, 0 means disabled
+    #[serde(default)]
+    pub min_output_capacity: u64,
+    // txs whose serialized size is greater than <max_tx_size> bytes are rejected, 0 means unlimited
+    #[serde(default)]
+    pub max_tx_size: usize,
+    // local txs still in the pool are rebroadcast <rebroadcast_after_blocks> blocks after the
+    // submission, and the interval doubles each time, 0 means disabled
+    #[serde(default)]
+    pub rebroadcast_after_blocks: u64,
+    // a local tx is rebroadcast at most <max_rebroadcasts> times
+    #[serde(default)]
+    pub max_rebroadcasts: u32,
 }
 
 impl Default for TxPoolConfig {
@@ -44,6 +91,34 @@ impl Default for TxPoolConfig {
             min_fee_rate: DEFAULT_MIN_FEE_RATE,
             max_tx_verify_cycles: DEFAULT_MAX_TX_VERIFY_CYCLES,
             max_ancestors_count: DEFAULT_MAX_ANCESTORS_COUNT,
+            max_ancestors_size: 0,
+            max_descendants_count: 0,
+            max_descendants_size: 0,
+            verify_workers: 0,
+            verify_cache_persist_interval: 0,
+            max_tx_verify_millis: 0,
+            persist_on_shutdown: false,
+            max_orphan_txs: 0,
+            orphan_expiry_secs: 0,
+            queue_timeout_millis: DEFAULT_QUEUE_TIMEOUT_MILLIS,
+            min_output_capacity: 0,
+            max_tx_size: 0,
+            rebroadcast_after_blocks: 0,
+            max_rebroadcasts: 0,
+        }
+    }
+}
+
+fn default_queue_timeout_millis() -> u64 {
+    DEFAULT_QUEUE_TIMEOUT_MILLIS
+}
+
+impl TxPoolConfig {
+    pub fn max_tx_verify_time(&self) -> Option<Duration> {
+        if self.max_tx_verify_millis == 0 {
+            None
+        } else {
+            Some(Duration::from_millis(self.max_tx_verify_millis))
         }
     }
 }
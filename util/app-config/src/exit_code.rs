@@ -30,6 +30,13 @@ impl From<toml::de::Error> for ExitCode {
     }
 }
 
+impl From<toml::ser::Error> for ExitCode {
+    fn from(err: toml::ser::Error) -> ExitCode {
+        eprintln!("Config Error: {:?}", err);
+        ExitCode::Config
+    }
+}
+
 impl From<ckb_logger::SetLoggerError> for ExitCode {
     fn from(err: ckb_logger::SetLoggerError) -> ExitCode {
         eprintln!("Config Error: {:?}", err);
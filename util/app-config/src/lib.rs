@@ -5,10 +5,12 @@ mod configs;
 mod exit_code;
 mod sentry_config;
 
-pub use app_config::{AppConfig, CKBAppConfig, MinerAppConfig};
+pub use app_config::{
+    AppConfig, CKBAppConfig, ConfigChanges, MinerAppConfig, REDACTED_OPTIONS, RELOADABLE_OPTIONS,
+};
 pub use args::{
-    ExportArgs, ImportArgs, InitArgs, MinerArgs, PeerIDArgs, ProfArgs, ResetDataArgs, RunArgs,
-    StatsArgs,
+    DnsSeedArgs, ExportArgs, ImportArgs, IndexerArgs, InitArgs, MinerArgs, PeerArgs, PeerCommand,
+    PeerIDArgs, ProfArgs, ReplayArgs, ReportArgs, ResetDataArgs, RunArgs, StatsArgs,
 };
 pub use configs::*;
 pub use exit_code::ExitCode;
@@ -17,13 +19,18 @@ use ckb_build_info::Version;
 use ckb_chain_spec::{consensus::Consensus, ChainSpec};
 use ckb_jsonrpc_types::ScriptHashType;
 use ckb_logger::{info_target, LoggerInitGuard};
+use ckb_resource::profile_default_spec;
 use clap::{value_t, ArgMatches, ErrorKind};
+use std::net::SocketAddr;
 use std::path::PathBuf;
 
 pub(crate) const LOG_TARGET_SENTRY: &str = "sentry";
+/// Parent directory of the isolated directories of `ckb --profile <name>`.
+const PROFILES_DIR: &str = "profiles";
 
 pub struct Setup {
     pub subcommand_name: String,
+    pub root_dir: PathBuf,
     pub config: AppConfig,
     pub is_sentry_enabled: bool,
 }
@@ -45,12 +52,16 @@ impl Setup {
             }
         };
 
-        let root_dir = Self::root_dir_from_matches(matches)?;
+        let root_dir = Self::profile_dir(
+            Self::root_dir_from_matches(matches)?,
+            matches.value_of(cli::ARG_PROFILE),
+        )?;
         let config = AppConfig::load_for_subcommand(&root_dir, subcommand_name)?;
         let is_sentry_enabled = is_daemon(&subcommand_name) && config.sentry().is_enabled();
 
         Ok(Setup {
             subcommand_name: subcommand_name.to_string(),
+            root_dir,
             config,
             is_sentry_enabled,
         })
@@ -98,9 +109,26 @@ impl Setup {
 
     pub fn run<'m>(self, matches: &ArgMatches<'m>) -> Result<RunArgs, ExitCode> {
         let consensus = self.consensus()?;
-        let config = self.config.into_ckb()?;
+        let mut config = self.config.into_ckb()?;
+        match value_t!(matches, cli::ARG_VERIFICATION_THREADS, usize) {
+            Ok(threads) => config.tx_pool.verify_workers = threads,
+            Err(ref e) if e.kind == ErrorKind::ArgumentNotFound => {}
+            Err(e) => {
+                return Err(e.into());
+            }
+        }
+        if let Some(hash) = matches.value_of(cli::ARG_ASSUME_VALID_TARGET) {
+            // the validator has checked it
+            config.chain.assume_valid_target = cli::parse_h256(hash).ok();
+        }
+        // the scripts of the blocks assumed valid are verified from their bodies later
+        if config.chain.assume_valid_target.is_some() && config.store.prune_keep_blocks != 0 {
+            eprintln!("`chain.assume_valid_target` can't be set with `store.prune_keep_blocks`");
+            return Err(ExitCode::Config);
+        }
 
         Ok(RunArgs {
+            root_dir: self.root_dir,
             config,
             consensus,
             block_assembler_advanced: matches.is_present(cli::ARG_BA_ADVANCED),
@@ -144,6 +172,39 @@ impl Setup {
         })
     }
 
+    pub fn replay<'m>(self, matches: &ArgMatches<'m>) -> Result<ReplayArgs, ExitCode> {
+        let consensus = self.consensus()?;
+        let config = self.config.into_ckb()?;
+        let tmp_target = value_t!(matches, cli::ARG_TMP_TARGET, PathBuf)?;
+        let from = match value_t!(matches, cli::ARG_FROM, u64) {
+            Ok(from) => Some(from),
+            Err(ref e) if e.kind == ErrorKind::ArgumentNotFound => None,
+            Err(e) => {
+                return Err(e.into());
+            }
+        };
+        let to = match value_t!(matches, cli::ARG_TO, u64) {
+            Ok(to) => Some(to),
+            Err(ref e) if e.kind == ErrorKind::ArgumentNotFound => None,
+            Err(e) => {
+                return Err(e.into());
+            }
+        };
+        let skip = matches
+            .values_of(cli::ARG_SKIP)
+            .map(|values| values.map(ToString::to_string).collect())
+            .unwrap_or_default();
+
+        Ok(ReplayArgs {
+            config,
+            consensus,
+            tmp_target,
+            from,
+            to,
+            skip,
+        })
+    }
+
     pub fn stats<'m>(self, matches: &ArgMatches<'m>) -> Result<StatsArgs, ExitCode> {
         let consensus = self.consensus()?;
         let config = self.config.into_ckb()?;
@@ -172,15 +233,42 @@ impl Setup {
         })
     }
 
+    pub fn indexer<'m>(self, matches: &ArgMatches<'m>) -> Result<IndexerArgs, ExitCode> {
+        let config = self.config.into_ckb()?;
+        let remote = matches
+            .value_of(cli::ARG_REMOTE)
+            .expect("required arg")
+            .to_string();
+
+        Ok(IndexerArgs { config, remote })
+    }
+
+    pub fn dnsseed<'m>(self, matches: &ArgMatches<'m>) -> Result<DnsSeedArgs, ExitCode> {
+        let config = self.config.into_ckb()?;
+        let domain = matches
+            .value_of(cli::ARG_DOMAIN)
+            .expect("required arg")
+            .to_string();
+        let listen = value_t!(matches, cli::ARG_LISTEN, SocketAddr)?;
+
+        Ok(DnsSeedArgs {
+            config,
+            domain,
+            listen,
+        })
+    }
+
     pub fn import<'m>(self, matches: &ArgMatches<'m>) -> Result<ImportArgs, ExitCode> {
         let consensus = self.consensus()?;
         let config = self.config.into_ckb()?;
         let source = value_t!(matches.value_of(cli::ARG_SOURCE), PathBuf)?;
+        let trusted = matches.is_present(cli::ARG_TRUSTED);
 
         Ok(ImportArgs {
             config,
             consensus,
             source,
+            trusted,
         })
     }
 
@@ -188,15 +276,26 @@ impl Setup {
         let consensus = self.consensus()?;
         let config = self.config.into_ckb()?;
         let target = value_t!(matches.value_of(cli::ARG_TARGET), PathBuf)?;
+        let format = matches
+            .value_of(cli::ARG_FORMAT)
+            .unwrap_or("json")
+            .to_string();
+        let with_cells = matches.is_present(cli::ARG_WITH_CELLS);
 
         Ok(ExportArgs {
             config,
             consensus,
             target,
+            format,
+            with_cells,
         })
     }
 
-    pub fn init<'m>(matches: &ArgMatches<'m>) -> Result<InitArgs, ExitCode> {
+    /// `namespace` is the top level `--profile`, which isolates the created directory.
+    pub fn init<'m>(
+        matches: &ArgMatches<'m>,
+        namespace: Option<&str>,
+    ) -> Result<InitArgs, ExitCode> {
         if matches.is_present("list-specs") {
             eprintln!(
                 "Deprecated: Option `--list-specs` is deprecated, use `--list-chains` instead"
@@ -209,18 +308,39 @@ impl Setup {
             eprintln!("Deprecated: Option `--export-specs` is deprecated");
         }
 
-        let root_dir = Self::root_dir_from_matches(matches)?;
+        let root_dir = Self::profile_dir(Self::root_dir_from_matches(matches)?, namespace)?;
         let list_chains =
             matches.is_present(cli::ARG_LIST_CHAINS) || matches.is_present("list-specs");
         let interactive = matches.is_present(cli::ARG_INTERACTIVE);
         let force = matches.is_present(cli::ARG_FORCE);
-        let chain = if matches.occurrences_of(cli::ARG_CHAIN) > 0 || !matches.is_present("spec") {
+        let profile = matches.value_of(cli::ARG_PROFILE).map(str::to_string);
+        let chain = if matches.occurrences_of(cli::ARG_CHAIN) > 0 {
             matches.value_of(cli::ARG_CHAIN).unwrap().to_string()
-        } else {
+        } else if matches.is_present("spec") {
             matches.value_of("spec").unwrap().to_string()
+        } else if let Some(profile) = &profile {
+            profile_default_spec(profile).to_string()
+        } else {
+            matches.value_of(cli::ARG_CHAIN).unwrap().to_string()
         };
-        let rpc_port = matches.value_of(cli::ARG_RPC_PORT).unwrap().to_string();
-        let p2p_port = matches.value_of(cli::ARG_P2P_PORT).unwrap().to_string();
+        // moves the default ports, so the nodes of different profiles can run side by side
+        let port_offset = namespace.map(profile_port_offset).unwrap_or(0);
+        let port = |arg: &str| -> Result<String, ExitCode> {
+            let port = matches.value_of(arg).unwrap();
+            if matches.occurrences_of(arg) > 0 || port_offset == 0 {
+                return Ok(port.to_string());
+            }
+            port.parse::<u16>()
+                .ok()
+                .and_then(|port| port.checked_add(port_offset))
+                .map(|port| port.to_string())
+                .ok_or_else(|| {
+                    eprintln!("Invalid port {}", port);
+                    ExitCode::Cli
+                })
+        };
+        let rpc_port = port(cli::ARG_RPC_PORT)?;
+        let p2p_port = port(cli::ARG_P2P_PORT)?;
         let (log_to_file, log_to_stdout) = match matches.value_of(cli::ARG_LOG_TO) {
             Some("file") => (true, false),
             Some("stdout") => (false, true),
@@ -246,6 +366,7 @@ impl Setup {
             interactive,
             root_dir,
             chain,
+            profile,
             rpc_port,
             p2p_port,
             list_chains,
@@ -311,6 +432,18 @@ impl Setup {
         Ok(config_dir)
     }
 
+    /// Returns the isolated directory of the profile, or `root_dir` without a profile.
+    pub fn profile_dir(root_dir: PathBuf, profile: Option<&str>) -> Result<PathBuf, ExitCode> {
+        match profile {
+            Some(profile) => {
+                let profile_dir = root_dir.join(PROFILES_DIR).join(profile);
+                std::fs::create_dir_all(&profile_dir)?;
+                Ok(profile_dir)
+            }
+            None => Ok(root_dir),
+        }
+    }
+
     fn chain_spec(&self) -> Result<ChainSpec, ExitCode> {
         let result = self.config.chain_spec();
         if let Ok(spec) = &result {
@@ -339,6 +472,73 @@ impl Setup {
         result
     }
 
+    pub fn peer<'m>(self, matches: &ArgMatches<'m>) -> Result<PeerArgs, ExitCode> {
+        let config = self.config.into_ckb()?;
+        let rpc_url = match matches.value_of(cli::ARG_URL) {
+            Some(url) => url.to_string(),
+            None => format!("http://{}", config.rpc.listen_address),
+        };
+        let address = |matches: &ArgMatches<'m>| {
+            matches
+                .value_of(cli::ARG_ADDRESS)
+                .expect("required")
+                .to_string()
+        };
+        let command = match matches.subcommand() {
+            (cli::CMD_PEER_LIST, Some(matches)) => PeerCommand::List {
+                banned: matches.is_present(cli::ARG_BANNED),
+            },
+            (cli::CMD_PEER_BAN, Some(matches)) => {
+                let ban_time = match value_t!(matches, cli::ARG_BAN_TIME, u64) {
+                    Ok(ban_time) => Some(ban_time),
+                    Err(ref e) if e.kind == ErrorKind::ArgumentNotFound => None,
+                    Err(e) => {
+                        return Err(e.into());
+                    }
+                };
+                PeerCommand::Ban {
+                    address: address(matches),
+                    ban_time,
+                    reason: matches.value_of(cli::ARG_REASON).map(ToString::to_string),
+                }
+            }
+            (cli::CMD_PEER_UNBAN, Some(matches)) => PeerCommand::Unban {
+                address: address(matches),
+            },
+            (cli::CMD_PEER_DIAL, Some(matches)) => PeerCommand::Dial {
+                address: address(matches),
+            },
+            _ => unreachable!(),
+        };
+
+        Ok(PeerArgs { rpc_url, command })
+    }
+
+    pub fn report<'m>(self, matches: &ArgMatches<'m>) -> Result<ReportArgs, ExitCode> {
+        let config = self.config.into_ckb()?;
+        let rpc_url = match matches.value_of(cli::ARG_URL) {
+            Some(url) => url.to_string(),
+            None => format!("http://{}", config.rpc.listen_address),
+        };
+        // the logs of this subcommand are useless, collects the ones of the node
+        let log_file = config
+            .logger
+            .file
+            .as_ref()
+            .and_then(|path| path.parent())
+            .map(|dir| dir.join(format!("{}.log", cli::CMD_RUN)));
+        let target = matches.value_of(cli::ARG_TARGET).map(PathBuf::from);
+        let log_lines = value_t!(matches, cli::ARG_LOG_LINES, usize)?;
+
+        Ok(ReportArgs {
+            config,
+            log_file,
+            rpc_url,
+            target,
+            log_lines,
+        })
+    }
+
     pub fn peer_id<'m>(matches: &ArgMatches<'m>) -> Result<PeerIDArgs, ExitCode> {
         let path = matches.value_of(cli::ARG_SECRET_PATH).unwrap();
         match read_secret_key(path.into()) {
@@ -375,7 +575,7 @@ fn consensus_from_spec(spec: &ChainSpec) -> Result<Consensus, ExitCode> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::cli::CMD_STATS;
+    use crate::cli::{CMD_PEER, CMD_REPLAY, CMD_STATS};
     use clap::{App, AppSettings};
 
     #[test]
@@ -402,4 +602,91 @@ mod tests {
             .get_matches_from_safe(vec!["", CMD_STATS, "--from", "10", "--to", "100"]);
         assert!(stats.is_ok());
     }
+    #[test]
+    fn replay_args() {
+        let app = App::new("replay_args_test")
+            .setting(AppSettings::SubcommandRequiredElseHelp)
+            .subcommand(cli::replay());
+
+        let replay = app.clone().get_matches_from_safe(vec!["", CMD_REPLAY]);
+        assert!(replay.is_err());
+
+        let replay = app
+            .clone()
+            .get_matches_from_safe(vec!["", CMD_REPLAY, "/tmp", "--to", "100"]);
+        assert!(replay.is_ok());
+
+        let replay = app.clone().get_matches_from_safe(vec![
+            "", CMD_REPLAY, "/tmp", "--skip", "epoch", "--skip", "reward",
+        ]);
+        let matches = replay.unwrap();
+        let skip: Vec<_> = matches
+            .subcommand_matches(CMD_REPLAY)
+            .unwrap()
+            .values_of(cli::ARG_SKIP)
+            .unwrap()
+            .collect();
+        assert_eq!(skip, vec!["epoch", "reward"]);
+
+        let replay = app
+            .clone()
+            .get_matches_from_safe(vec!["", CMD_REPLAY, "/tmp", "--skip", "scripts"]);
+        assert!(replay.is_err());
+    }
+
+    #[test]
+    fn peer_args() {
+        let app = App::new("peer_args_test")
+            .setting(AppSettings::SubcommandRequiredElseHelp)
+            .subcommand(cli::peer());
+
+        let peer = app.clone().get_matches_from_safe(vec!["", CMD_PEER]);
+        assert!(peer.is_err());
+
+        let peer = app
+            .clone()
+            .get_matches_from_safe(vec!["", CMD_PEER, "list", "--banned"]);
+        assert!(peer.is_ok());
+
+        let peer = app.clone().get_matches_from_safe(vec![
+            "",
+            CMD_PEER,
+            "ban",
+            "192.168.0.0/24",
+            "--ban-time",
+            "60000",
+            "--url",
+            "http://127.0.0.1:8114",
+        ]);
+        assert!(peer.is_ok());
+
+        let peer = app
+            .clone()
+            .get_matches_from_safe(vec!["", CMD_PEER, "unban"]);
+        assert!(peer.is_err());
+    }
+}
+
+// The offset is derived from the name, so it is stable across `ckb init --force`. It is even, to
+// keep the RPC port and the P2P port next to each other from clashing with other profiles.
+fn profile_port_offset(profile: &str) -> u16 {
+    let hash = profile.bytes().fold(0u32, |hash, byte| {
+        hash.wrapping_mul(31).wrapping_add(u32::from(byte))
+    });
+    2 * (1 + (hash % 1000) as u16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_port_offset() {
+        assert_eq!(profile_port_offset("dev"), profile_port_offset("dev"));
+        assert_ne!(profile_port_offset("dev"), profile_port_offset("testnet"));
+        for profile in &["dev", "testnet", "a", "zzzzzzzzzzzzzzzzzzzzzzzz"] {
+            let offset = profile_port_offset(profile);
+            assert!(offset >= 2 && offset <= 2000 && offset % 2 == 0);
+        }
+    }
 }
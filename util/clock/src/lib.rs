@@ -0,0 +1,108 @@
+//! Time source of CKB.
+//!
+//! The services read the wall clock through the `Clock` passed into them, mostly via `Shared`.
+//! It is the system clock by default, which still honors the `FAKETIME` file of `faketime`. Tests
+//! pass a `MockClock` instead to advance the time deterministically, e.g. to expire the orphan
+//! transactions or to trigger the sync timeouts.
+use ckb_util::RwLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+pub trait Clock: Send + Sync {
+    /// Milliseconds since the unix epoch.
+    fn unix_time_as_millis(&self) -> u64;
+}
+
+/// The system clock, or the faketime when it is enabled.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn unix_time_as_millis(&self) -> u64 {
+        faketime::unix_time_as_millis()
+    }
+}
+
+/// A clock which only moves when it is told to, the clones share the same time.
+#[derive(Clone, Debug, Default)]
+pub struct MockClock {
+    millis: Arc<AtomicU64>,
+}
+
+impl MockClock {
+    pub fn new(millis: u64) -> Self {
+        MockClock {
+            millis: Arc::new(AtomicU64::new(millis)),
+        }
+    }
+
+    pub fn set(&self, millis: u64) {
+        self.millis.store(millis, Ordering::SeqCst);
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        self.millis
+            .fetch_add(duration.as_millis() as u64, Ordering::SeqCst);
+    }
+}
+
+impl Clock for MockClock {
+    fn unix_time_as_millis(&self) -> u64 {
+        self.millis.load(Ordering::SeqCst)
+    }
+}
+
+/// The system clock until a mock time is set, the clones share the same mock time.
+///
+/// The nodes of the integration tests run on it, the tests set the time over the RPC.
+#[derive(Clone, Default)]
+pub struct MockableClock {
+    mock_millis: Arc<RwLock<Option<u64>>>,
+}
+
+impl MockableClock {
+    /// Sets the mock time, or restores the system clock by `None`.
+    pub fn set_mock_time(&self, millis: Option<u64>) {
+        *self.mock_millis.write() = millis;
+    }
+}
+
+impl Clock for MockableClock {
+    fn unix_time_as_millis(&self) -> u64 {
+        self.mock_millis
+            .read()
+            .unwrap_or_else(faketime::unix_time_as_millis)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mock_clock() {
+        let clock = MockClock::new(1_000);
+        let shared: Arc<dyn Clock> = Arc::new(clock.clone());
+        assert_eq!(shared.unix_time_as_millis(), 1_000);
+
+        clock.advance(Duration::from_secs(2));
+        assert_eq!(shared.unix_time_as_millis(), 3_000);
+
+        clock.set(500);
+        assert_eq!(shared.unix_time_as_millis(), 500);
+    }
+
+    #[test]
+    fn test_mockable_clock() {
+        let clock = MockableClock::default();
+        let shared: Arc<dyn Clock> = Arc::new(clock.clone());
+        assert!(shared.unix_time_as_millis() > 500);
+
+        clock.set_mock_time(Some(500));
+        assert_eq!(shared.unix_time_as_millis(), 500);
+
+        clock.set_mock_time(None);
+        assert!(shared.unix_time_as_millis() > 500);
+    }
+}
@@ -1,5 +1,5 @@
 use crate::tx_confirm_stat::TxConfirmStat;
-use crate::FeeRate;
+use crate::{FeeRate, MempoolCurve};
 use ckb_logger::debug;
 use ckb_types::packed::Byte32;
 use std::collections::HashMap;
@@ -141,4 +141,26 @@ impl Estimator {
             MIN_ESTIMATE_CONFIRM_RATE,
         )
     }
+
+    /// estimate a fee rate for confirm target by both the history and the current tx pool
+    ///
+    /// The history lags behind a sudden burst of transactions, so the estimate is raised to
+    /// outbid the pool entries which fill the blocks within the confirm target. The pool decides
+    /// alone if the samples are not enough.
+    pub fn estimate_with_mempool(
+        &self,
+        expect_confirm_blocks: usize,
+        mempool: &MempoolCurve,
+        block_vbytes: u64,
+    ) -> FeeRate {
+        // A tx need 1 block to propose, then 2 block to get confirmed
+        let commit_blocks = expect_confirm_blocks.saturating_sub(2).max(1) as u64;
+        let mempool_fee_rate = mempool.fee_rate_within(commit_blocks.saturating_mul(block_vbytes));
+        let historical_fee_rate = self.estimate(expect_confirm_blocks);
+        if historical_fee_rate == FeeRate::zero() {
+            mempool_fee_rate
+        } else {
+            ::std::cmp::max(historical_fee_rate, mempool_fee_rate)
+        }
+    }
 }
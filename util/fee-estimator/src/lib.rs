@@ -1,6 +1,8 @@
 mod estimator;
 mod fee_rate;
+mod mempool;
 mod tx_confirm_stat;
 
 pub use estimator::{Estimator, MAX_CONFIRM_BLOCKS};
 pub use fee_rate::FeeRate;
+pub use mempool::{EstimateMode, MempoolCurve};
@@ -0,0 +1,87 @@
+use crate::FeeRate;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EstimateMode {
+    /// Estimate by the confirmation history only
+    Historical,
+    /// Blend the history with the transactions currently in the tx pool
+    Mempool,
+}
+
+impl Default for EstimateMode {
+    fn default() -> Self {
+        EstimateMode::Historical
+    }
+}
+
+/// The cumulative virtual bytes of the tx pool entries against their fee rates.
+///
+/// The miners pack the entries of higher fee rates first, so a new transaction has to outbid the
+/// entries which would otherwise fill the blocks before it gets committed.
+#[derive(Clone, Debug, Default)]
+pub struct MempoolCurve {
+    /// (fee rate, cumulative vbytes of the entries paying at least the fee rate), in descending
+    /// order of the fee rate
+    points: Vec<(FeeRate, u64)>,
+}
+
+impl MempoolCurve {
+    /// Builds the curve from the fee rates and virtual bytes of the entries.
+    pub fn new(entries: impl IntoIterator<Item = (FeeRate, u64)>) -> Self {
+        let mut entries: Vec<(FeeRate, u64)> = entries.into_iter().collect();
+        entries.sort_by(|a, b| b.0.cmp(&a.0));
+        let mut points: Vec<(FeeRate, u64)> = Vec::with_capacity(entries.len());
+        let mut total = 0u64;
+        for (fee_rate, vbytes) in entries {
+            total = total.saturating_add(vbytes);
+            match points.last_mut() {
+                Some(last) if last.0 == fee_rate => last.1 = total,
+                _ => points.push((fee_rate, total)),
+            }
+        }
+        MempoolCurve { points }
+    }
+
+    pub fn total_vbytes(&self) -> u64 {
+        self.points.last().map(|(_, total)| *total).unwrap_or(0)
+    }
+
+    /// Returns the fee rate which gets a transaction into the first `vbytes` of the pool, or zero
+    /// if the whole pool fits in.
+    pub fn fee_rate_within(&self, vbytes: u64) -> FeeRate {
+        if self.total_vbytes() <= vbytes {
+            return FeeRate::zero();
+        }
+        // the first fee rate whose entries overflow the space, a transaction has to pay more
+        let overflow = self
+            .points
+            .iter()
+            .find(|(_, total)| *total > vbytes)
+            .map(|(fee_rate, _)| *fee_rate)
+            .unwrap_or_else(FeeRate::zero);
+        FeeRate::from_u64(overflow.as_u64().saturating_add(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fee_rate_within() {
+        let curve = MempoolCurve::new(vec![
+            (FeeRate::from_u64(1000), 400),
+            (FeeRate::from_u64(3000), 200),
+            (FeeRate::from_u64(2000), 300),
+            (FeeRate::from_u64(3000), 100),
+        ]);
+        assert_eq!(curve.total_vbytes(), 1000);
+        assert_eq!(curve.fee_rate_within(1000), FeeRate::zero());
+        assert_eq!(curve.fee_rate_within(600), FeeRate::from_u64(1001));
+        assert_eq!(curve.fee_rate_within(300), FeeRate::from_u64(2001));
+        assert_eq!(curve.fee_rate_within(299), FeeRate::from_u64(3001));
+        assert_eq!(MempoolCurve::default().fee_rate_within(0), FeeRate::zero());
+    }
+}
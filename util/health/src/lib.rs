@@ -0,0 +1,197 @@
+//! Health of the CKB services.
+//!
+//! Each service registers a check into the `HealthRegistry`, which reports whether the service
+//! is alive and a few key states of it. The checks are aggregated by the RPC `get_health` and a
+//! periodic self-diagnostic log line.
+use ckb_logger::{info, warn};
+use ckb_util::RwLock;
+use std::fmt;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// The status of a service, the worst one is the status of the node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HealthStatus {
+    Healthy,
+    /// The service works, but something should be noticed, e.g. no connected peers.
+    Degraded,
+    /// The service does not work.
+    Unhealthy,
+}
+
+impl fmt::Display for HealthStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HealthStatus::Healthy => write!(f, "healthy"),
+            HealthStatus::Degraded => write!(f, "degraded"),
+            HealthStatus::Unhealthy => write!(f, "unhealthy"),
+        }
+    }
+}
+
+/// The result of a health check.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ServiceHealth {
+    pub name: String,
+    pub status: HealthStatus,
+    /// Why the service is not healthy.
+    pub reason: Option<String>,
+    /// The key states, in the order they are added.
+    pub details: Vec<(String, String)>,
+}
+
+impl ServiceHealth {
+    pub fn healthy() -> Self {
+        ServiceHealth {
+            name: String::new(),
+            status: HealthStatus::Healthy,
+            reason: None,
+            details: Vec::new(),
+        }
+    }
+
+    pub fn degraded<S: ToString>(reason: S) -> Self {
+        ServiceHealth {
+            status: HealthStatus::Degraded,
+            reason: Some(reason.to_string()),
+            ..ServiceHealth::healthy()
+        }
+    }
+
+    pub fn unhealthy<S: ToString>(reason: S) -> Self {
+        ServiceHealth {
+            status: HealthStatus::Unhealthy,
+            reason: Some(reason.to_string()),
+            ..ServiceHealth::healthy()
+        }
+    }
+
+    pub fn detail<K: ToString, V: ToString>(mut self, key: K, value: V) -> Self {
+        self.details.push((key.to_string(), value.to_string()));
+        self
+    }
+}
+
+impl fmt::Display for ServiceHealth {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.name, self.status)?;
+        if let Some(reason) = &self.reason {
+            write!(f, " ({})", reason)?;
+        }
+        for (key, value) in &self.details {
+            write!(f, " {}={}", key, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// The aggregated health of the node.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HealthReport {
+    pub status: HealthStatus,
+    pub services: Vec<ServiceHealth>,
+}
+
+type HealthCheck = Arc<dyn Fn() -> ServiceHealth + Send + Sync>;
+
+/// The registered health checks, the clones share the same checks.
+#[derive(Clone, Default)]
+pub struct HealthRegistry {
+    checks: Arc<RwLock<Vec<(String, HealthCheck)>>>,
+}
+
+impl HealthRegistry {
+    pub fn new() -> Self {
+        HealthRegistry::default()
+    }
+
+    /// Registers the check of a service, which replaces the previous check of the same name.
+    pub fn register<F>(&self, name: &str, check: F)
+    where
+        F: Fn() -> ServiceHealth + Send + Sync + 'static,
+    {
+        let mut checks = self.checks.write();
+        checks.retain(|(registered, _)| registered != name);
+        checks.push((name.to_string(), Arc::new(check)));
+    }
+
+    /// Runs all the checks in the registration order.
+    pub fn report(&self) -> HealthReport {
+        // the checks may block, so they are run without holding the lock
+        let checks = self.checks.read().clone();
+        let services: Vec<_> = checks
+            .into_iter()
+            .map(|(name, check)| ServiceHealth { name, ..check() })
+            .collect();
+        let status = services
+            .iter()
+            .map(|service| service.status)
+            .max()
+            .unwrap_or(HealthStatus::Healthy);
+        HealthReport { status, services }
+    }
+
+    /// Logs the report every `interval` seconds, 0 is disable.
+    pub fn start_self_diagnostic(&self, interval: u64) {
+        if interval == 0 {
+            info!("health self-diagnostic: disable");
+            return;
+        }
+        let registry = self.clone();
+        let wait = Duration::from_secs(interval);
+        if let Err(err) = thread::Builder::new()
+            .name("HealthDiagnostic".to_string())
+            .spawn(move || loop {
+                thread::sleep(wait);
+                let report = registry.report();
+                let services = report
+                    .services
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join("; ");
+                if report.status == HealthStatus::Healthy {
+                    info!("health {}: {}", report.status, services);
+                } else {
+                    warn!("health {}: {}", report.status, services);
+                }
+            })
+        {
+            warn!("health self-diagnostic: spawn thread error {}", err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aggregate_health() {
+        let registry = HealthRegistry::new();
+        assert_eq!(registry.report().status, HealthStatus::Healthy);
+
+        registry.register("chain", || ServiceHealth::healthy().detail("tip", 100));
+        registry.register("network", || {
+            ServiceHealth::degraded("no connected peers").detail("peers", 0)
+        });
+        let report = registry.report();
+        assert_eq!(report.status, HealthStatus::Degraded);
+        assert_eq!(
+            report.services[0].to_string(),
+            "chain: healthy tip=100".to_string()
+        );
+        assert_eq!(
+            report.services[1].to_string(),
+            "network: degraded (no connected peers) peers=0".to_string()
+        );
+
+        registry.register("tx-pool", || ServiceHealth::unhealthy("service stopped"));
+        registry.register("network", ServiceHealth::healthy);
+        let report = registry.report();
+        assert_eq!(report.status, HealthStatus::Unhealthy);
+        let names: Vec<_> = report.services.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["chain", "tx-pool", "network"]);
+    }
+}
@@ -0,0 +1,265 @@
+//! The CAR (Content Addressable aRchive) v1 snapshot format.
+//!
+//! A file is the varint length prefixed DAG-CBOR header followed by the sections. A section is
+//! the varint length prefixed CID and the molecule bytes of a block, in the order of block
+//! numbers. The CIDs are CIDv1 of the raw codec with the blake2b-256 multihash, unpersonalized
+//! as the multihash table defines, so the other CAR tools can verify the blocks. The root is the
+//! genesis block, which identifies the chain.
+use ckb_hash::Blake2bBuilder;
+use ckb_types::{packed, prelude::*};
+use std::io::{self, Read, Write};
+
+const CID_VERSION: u64 = 1;
+const RAW_CODEC: u64 = 0x55;
+const BLAKE2B_256_CODE: u64 = 0xb220;
+const DIGEST_LEN: usize = 32;
+// The bytes limit of a section, far above the block size limit
+const MAX_SECTION_BYTES: u64 = 64 * 1024 * 1024;
+
+fn invalid_data<E: ToString>(error: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error.to_string())
+}
+
+fn write_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+// Reads an unsigned varint, returns `None` at the end of the stream before the first byte.
+fn read_varint<R: Read>(reader: &mut R) -> io::Result<Option<u64>> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let mut byte = [0u8; 1];
+        if reader.read(&mut byte)? == 0 {
+            if shift == 0 {
+                return Ok(None);
+            }
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        value |= u64::from(byte[0] & 0x7f) << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(Some(value));
+        }
+    }
+    Err(invalid_data("varint overflow"))
+}
+
+fn digest(data: &[u8]) -> [u8; DIGEST_LEN] {
+    let mut ret = [0u8; DIGEST_LEN];
+    let mut blake2b = Blake2bBuilder::new(DIGEST_LEN).build();
+    blake2b.update(data);
+    blake2b.finalize(&mut ret);
+    ret
+}
+
+fn cid(data: &[u8]) -> Vec<u8> {
+    let mut cid = Vec::with_capacity(4 + 1 + DIGEST_LEN);
+    write_varint(&mut cid, CID_VERSION);
+    write_varint(&mut cid, RAW_CODEC);
+    write_varint(&mut cid, BLAKE2B_256_CODE);
+    write_varint(&mut cid, DIGEST_LEN as u64);
+    cid.extend_from_slice(&digest(data));
+    cid
+}
+
+// The DAG-CBOR header `{"roots": [root], "version": 1}`, the keys are sorted as DAG-CBOR requires
+fn header(root: &[u8]) -> Vec<u8> {
+    let mut header = vec![0xa2];
+    header.push(0x65);
+    header.extend_from_slice(b"roots");
+    header.push(0x81);
+    // tag 42, the CID link, of the bytes with the multibase identity prefix
+    header.extend_from_slice(&[0xd8, 0x2a, 0x58, (root.len() + 1) as u8, 0x00]);
+    header.extend_from_slice(root);
+    header.push(0x67);
+    header.extend_from_slice(b"version");
+    header.push(0x01);
+    header
+}
+
+pub(crate) struct CarWriter<W: Write> {
+    inner: W,
+}
+
+impl<W: Write> CarWriter<W> {
+    pub(crate) fn new(mut inner: W, genesis: &packed::Block) -> io::Result<Self> {
+        let header = header(&cid(genesis.as_slice()));
+        let mut buf = Vec::with_capacity(header.len() + 1);
+        write_varint(&mut buf, header.len() as u64);
+        buf.extend_from_slice(&header);
+        inner.write_all(&buf)?;
+        Ok(CarWriter { inner })
+    }
+
+    pub(crate) fn write_block(&mut self, block: &packed::Block) -> io::Result<()> {
+        let data = block.as_slice();
+        let cid = cid(data);
+        let mut prefix = Vec::with_capacity(10 + cid.len());
+        write_varint(&mut prefix, (cid.len() + data.len()) as u64);
+        prefix.extend_from_slice(&cid);
+        self.inner.write_all(&prefix)?;
+        self.inner.write_all(data)
+    }
+
+    pub(crate) fn finish(mut self) -> io::Result<W> {
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+pub(crate) struct CarReader<R: Read> {
+    inner: R,
+    root: Vec<u8>,
+    finished: bool,
+}
+
+impl<R: Read> CarReader<R> {
+    pub(crate) fn new(mut inner: R) -> io::Result<Self> {
+        let len = read_varint(&mut inner)?.ok_or(io::ErrorKind::UnexpectedEof)?;
+        if len > 1024 {
+            return Err(invalid_data("CAR header is too large"));
+        }
+        let mut header_bytes = vec![0u8; len as usize];
+        inner.read_exact(&mut header_bytes)?;
+        // Only the headers of the single raw blake2b-256 root, as written by `CarWriter`, are
+        // supported
+        let root_len = header_bytes
+            .get(11)
+            .map(|len| (*len as usize).saturating_sub(1))
+            .ok_or_else(|| invalid_data("unsupported CAR header"))?;
+        let root = header_bytes
+            .get(13..13 + root_len)
+            .ok_or_else(|| invalid_data("unsupported CAR header"))?
+            .to_vec();
+        if header(&root) != header_bytes {
+            return Err(invalid_data("unsupported CAR header"));
+        }
+        Ok(CarReader {
+            inner,
+            root,
+            finished: false,
+        })
+    }
+
+    /// Whether the root is the CID of the block.
+    pub(crate) fn is_root(&self, block: &packed::Block) -> bool {
+        self.root == cid(block.as_slice())
+    }
+
+    fn read_section(&mut self) -> io::Result<Option<packed::Block>> {
+        let len = match read_varint(&mut self.inner)? {
+            Some(len) => len,
+            None => return Ok(None),
+        };
+        if len > MAX_SECTION_BYTES {
+            return Err(invalid_data(format!(
+                "section of {} bytes is too large",
+                len
+            )));
+        }
+        let mut section = vec![0u8; len as usize];
+        self.inner.read_exact(&mut section)?;
+        let mut rest = &section[..];
+        let mut prefix = [0u64; 4];
+        for value in prefix.iter_mut() {
+            *value = read_varint(&mut rest)?.ok_or_else(|| invalid_data("truncated CID"))?;
+        }
+        if prefix != [CID_VERSION, RAW_CODEC, BLAKE2B_256_CODE, DIGEST_LEN as u64]
+            || rest.len() < DIGEST_LEN
+        {
+            return Err(invalid_data("unsupported CID"));
+        }
+        let (expected, data) = rest.split_at(DIGEST_LEN);
+        if expected != digest(data) {
+            return Err(invalid_data("section digest mismatch"));
+        }
+        packed::Block::from_slice(data)
+            .map(Some)
+            .map_err(invalid_data)
+    }
+}
+
+impl<R: Read> Iterator for CarReader<R> {
+    type Item = io::Result<packed::Block>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        let ret = self.read_section().transpose();
+        match ret {
+            Some(Ok(_)) => {}
+            // a truncated or corrupted file stops the iteration
+            _ => self.finished = true,
+        }
+        ret
+    }
+}
+
+/// Whether the bytes start a CAR file written by `CarWriter`.
+pub(crate) fn is_car(prefix: &[u8]) -> bool {
+    // the header is shorter than 128 bytes, so its length is a single byte varint
+    prefix.len() >= 8 && prefix[1] == 0xa2 && &prefix[2..8] == b"\x65roots"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ckb_types::core::{BlockBuilder, HeaderBuilder};
+
+    fn build_blocks() -> Vec<packed::Block> {
+        (0..10u64)
+            .map(|number| {
+                let header = HeaderBuilder::default().number(number.pack()).build();
+                BlockBuilder::default().header(header).build().data()
+            })
+            .collect()
+    }
+
+    fn write_blocks(blocks: &[packed::Block]) -> Vec<u8> {
+        let mut writer = CarWriter::new(Vec::new(), &blocks[0]).unwrap();
+        for block in blocks {
+            writer.write_block(block).unwrap();
+        }
+        writer.finish().unwrap()
+    }
+
+    #[test]
+    fn test_car_roundtrip() {
+        let blocks = build_blocks();
+        let bytes = write_blocks(&blocks);
+        assert!(is_car(&bytes));
+        let reader = CarReader::new(&bytes[..]).unwrap();
+        assert!(reader.is_root(&blocks[0]));
+        assert!(!reader.is_root(&blocks[1]));
+        let read: Vec<packed::Block> = reader.map(Result::unwrap).collect();
+        assert_eq!(read, blocks);
+    }
+
+    #[test]
+    fn test_car_corruption() {
+        let bytes = write_blocks(&build_blocks());
+        let mut corrupted = bytes.clone();
+        let last = corrupted.len() - 1;
+        corrupted[last] ^= 1;
+        let reader = CarReader::new(&corrupted[..]).unwrap();
+        assert!(reader.last().unwrap().is_err());
+
+        let truncated = &bytes[..bytes.len() - 1];
+        let reader = CarReader::new(truncated).unwrap();
+        assert!(reader.last().unwrap().is_err());
+    }
+
+    #[test]
+    fn test_varint() {
+        for value in &[0u64, 1, 0x7f, 0x80, 0xb220, std::u64::MAX] {
+            let mut buf = Vec::new();
+            write_varint(&mut buf, *value);
+            assert_eq!(read_varint(&mut &buf[..]).unwrap(), Some(*value));
+        }
+        assert_eq!(read_varint(&mut &[][..]).unwrap(), None);
+    }
+}
@@ -1,24 +1,94 @@
+use crate::car::CarWriter;
+use crate::flat::FlatWriter;
 use crate::iter::ChainIterator;
+use crate::progress::Progress;
+use ckb_db::iter::IteratorMode;
 use ckb_jsonrpc_types::BlockView as JsonBlock;
 use ckb_shared::shared::Shared;
+use ckb_store::{ChainStore, COLUMN_CELL_SET};
+use ckb_types::{packed, prelude::*};
 #[cfg(feature = "progress_bar")]
 use indicatif::{ProgressBar, ProgressStyle};
 use std::error::Error;
+use std::fmt;
 use std::fs;
 use std::io;
 use std::io::Write;
 use std::path::PathBuf;
+use std::str::FromStr;
+
+/// The file formats of the exported blocks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// A block in JSON per line
+    Json,
+    /// The chunks of the molecule encoded blocks and the cell set, checksummed by blake2b
+    Flat,
+    /// The CAR archive of the molecule encoded blocks, addressed by their blake2b CIDs
+    Car,
+}
+
+impl Format {
+    fn extension(self) -> &'static str {
+        match self {
+            Format::Json => "json",
+            Format::Flat => "ckbsnap",
+            Format::Car => "car",
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Format::Json),
+            "flat" => Ok(Format::Flat),
+            "car" => Ok(Format::Car),
+            _ => Err(format!("unknown export format {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Format::Json => write!(f, "json"),
+            Format::Flat => write!(f, "flat"),
+            Format::Car => write!(f, "car"),
+        }
+    }
+}
 
 /// Export block from datbase to specify file.
 pub struct Export {
     /// export target path
     pub target: PathBuf,
     pub shared: Shared,
+    pub format: Format,
+    /// Exports the cell set after the blocks, only the flat format supports it
+    pub with_cells: bool,
 }
 
 impl Export {
     pub fn new(shared: Shared, target: PathBuf) -> Self {
-        Export { shared, target }
+        Export {
+            shared,
+            target,
+            format: Format::Json,
+            with_cells: false,
+        }
+    }
+
+    pub fn format(mut self, format: Format) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn with_cells(mut self, with_cells: bool) -> Self {
+        self.with_cells = with_cells;
+        self
     }
 
     /// Returning ChainIterator dealing with blocks iterate.
@@ -28,12 +98,32 @@ impl Export {
 
     /// export file name
     fn file_name(&self) -> String {
-        format!("{}.{}", self.shared.consensus().id, "json")
+        format!("{}.{}", self.shared.consensus().id, self.format.extension())
+    }
+
+    fn create_file(&self) -> io::Result<io::BufWriter<fs::File>> {
+        let f = fs::OpenOptions::new()
+            .create_new(true)
+            .read(true)
+            .write(true)
+            .open(&self.target.join(self.file_name()))?;
+        Ok(io::BufWriter::new(f))
     }
 
     pub fn execute(self) -> Result<(), Box<dyn Error>> {
+        if self.with_cells && self.format != Format::Flat {
+            return Err(format!(
+                "the cell set can't be exported in the {} format",
+                self.format
+            )
+            .into());
+        }
         fs::create_dir_all(&self.target)?;
-        self.write_to_json()
+        match self.format {
+            Format::Json => self.write_to_json(),
+            Format::Flat => self.write_to_flat(),
+            Format::Car => self.write_to_car(),
+        }
     }
 
     #[cfg(not(feature = "progress_bar"))]
@@ -80,4 +170,41 @@ impl Export {
         progress_bar.finish_with_message("done!");
         Ok(())
     }
+
+    pub fn write_to_flat(self) -> Result<(), Box<dyn Error>> {
+        let mut writer = FlatWriter::new(self.create_file()?, &self.shared.genesis_hash())?;
+        // the cell set of the same snapshot as the blocks
+        let snapshot = self.shared.snapshot();
+        let blocks_iter = self.iter();
+        let progress = Progress::blocks(blocks_iter.len());
+        for block in blocks_iter {
+            writer.write_block(&block.data())?;
+            progress.inc(1);
+        }
+        if self.with_cells {
+            for (key, value) in snapshot.get_iter(COLUMN_CELL_SET, IteratorMode::Start) {
+                let tx_hash = packed::Byte32Reader::from_slice_should_be_ok(&key).to_entity();
+                let meta =
+                    packed::TransactionMetaReader::from_slice_should_be_ok(&value).to_entity();
+                writer.write_tx_meta(&tx_hash, &meta)?;
+            }
+        }
+        writer.finish()?;
+        progress.finish();
+        Ok(())
+    }
+
+    pub fn write_to_car(self) -> Result<(), Box<dyn Error>> {
+        let genesis = self.shared.consensus().genesis_block().data();
+        let mut writer = CarWriter::new(self.create_file()?, &genesis)?;
+        let blocks_iter = self.iter();
+        let progress = Progress::blocks(blocks_iter.len());
+        for block in blocks_iter {
+            writer.write_block(&block.data())?;
+            progress.inc(1);
+        }
+        writer.finish()?;
+        progress.finish();
+        Ok(())
+    }
 }
@@ -0,0 +1,312 @@
+//! The flat snapshot format.
+//!
+//! A file starts with the magic, the format version and the genesis hash, followed by the
+//! chunks. A chunk is the kind, the item count, the payload length, the payload and the blake2b
+//! hash of the payload, and an item in the payload is its length and its molecule bytes. The
+//! blocks chunks come first in the order of block numbers, then the cell set chunks if
+//! exported, and the end chunk marks the complete file.
+use ckb_hash::blake2b_256;
+use ckb_types::{packed, prelude::*};
+use std::convert::TryInto;
+use std::io::{self, Read, Write};
+
+pub(crate) const MAGIC: &[u8; 8] = b"CKBSNAP\0";
+const VERSION: u32 = 1;
+// The items buffered into a chunk, and the bytes limit of a chunk payload
+const MAX_CHUNK_ITEMS: usize = 1000;
+const MAX_CHUNK_BYTES: usize = 64 * 1024 * 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ChunkKind {
+    Blocks = 0,
+    /// The items are the transaction hashes followed by their metas
+    CellSet = 1,
+    End = 0xff,
+}
+
+impl ChunkKind {
+    fn from_u8(kind: u8) -> Option<Self> {
+        match kind {
+            0 => Some(ChunkKind::Blocks),
+            1 => Some(ChunkKind::CellSet),
+            0xff => Some(ChunkKind::End),
+            _ => None,
+        }
+    }
+}
+
+fn invalid_data<E: ToString>(error: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error.to_string())
+}
+
+pub(crate) struct FlatWriter<W: Write> {
+    inner: W,
+    kind: ChunkKind,
+    items: usize,
+    payload: Vec<u8>,
+}
+
+impl<W: Write> FlatWriter<W> {
+    pub(crate) fn new(mut inner: W, genesis_hash: &packed::Byte32) -> io::Result<Self> {
+        inner.write_all(MAGIC)?;
+        inner.write_all(&VERSION.to_le_bytes())?;
+        inner.write_all(genesis_hash.as_slice())?;
+        Ok(FlatWriter {
+            inner,
+            kind: ChunkKind::Blocks,
+            items: 0,
+            payload: Vec::new(),
+        })
+    }
+
+    pub(crate) fn write_block(&mut self, block: &packed::Block) -> io::Result<()> {
+        self.write_item(ChunkKind::Blocks, &[block.as_slice()])
+    }
+
+    pub(crate) fn write_tx_meta(
+        &mut self,
+        tx_hash: &packed::Byte32,
+        meta: &packed::TransactionMeta,
+    ) -> io::Result<()> {
+        self.write_item(ChunkKind::CellSet, &[tx_hash.as_slice(), meta.as_slice()])
+    }
+
+    fn write_item(&mut self, kind: ChunkKind, parts: &[&[u8]]) -> io::Result<()> {
+        let len: usize = parts.iter().map(|part| part.len()).sum();
+        if self.kind != kind
+            || self.items >= MAX_CHUNK_ITEMS
+            || self.payload.len() + 4 + len > MAX_CHUNK_BYTES
+        {
+            self.flush_chunk()?;
+            self.kind = kind;
+        }
+        self.payload.extend_from_slice(&(len as u32).to_le_bytes());
+        for part in parts {
+            self.payload.extend_from_slice(part);
+        }
+        self.items += 1;
+        Ok(())
+    }
+
+    fn flush_chunk(&mut self) -> io::Result<()> {
+        if self.items == 0 {
+            return Ok(());
+        }
+        write_chunk(&mut self.inner, self.kind, self.items, &self.payload)?;
+        self.items = 0;
+        self.payload.clear();
+        Ok(())
+    }
+
+    /// Writes the pending items and the end chunk.
+    pub(crate) fn finish(mut self) -> io::Result<W> {
+        self.flush_chunk()?;
+        write_chunk(&mut self.inner, ChunkKind::End, 0, &[])?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+fn write_chunk<W: Write>(
+    writer: &mut W,
+    kind: ChunkKind,
+    items: usize,
+    payload: &[u8],
+) -> io::Result<()> {
+    writer.write_all(&[kind as u8])?;
+    writer.write_all(&(items as u32).to_le_bytes())?;
+    writer.write_all(&(payload.len() as u32).to_le_bytes())?;
+    writer.write_all(payload)?;
+    writer.write_all(&blake2b_256(payload))
+}
+
+/// An item read from a flat snapshot.
+#[derive(Debug)]
+pub(crate) enum FlatItem {
+    Block(packed::Block),
+    TxMeta(packed::Byte32, packed::TransactionMeta),
+}
+
+pub(crate) struct FlatReader<R: Read> {
+    inner: R,
+    genesis_hash: packed::Byte32,
+    items: std::vec::IntoIter<FlatItem>,
+    finished: bool,
+}
+
+impl<R: Read> FlatReader<R> {
+    pub(crate) fn new(mut inner: R) -> io::Result<Self> {
+        let mut header = [0u8; 8 + 4 + 32];
+        inner.read_exact(&mut header)?;
+        if &header[..8] != MAGIC {
+            return Err(invalid_data("not a flat snapshot"));
+        }
+        let version = u32::from_le_bytes(header[8..12].try_into().expect("checked len"));
+        if version != VERSION {
+            return Err(invalid_data(format!(
+                "unsupported flat snapshot version {}",
+                version
+            )));
+        }
+        let genesis_hash = packed::Byte32::from_slice(&header[12..]).expect("checked len");
+        Ok(FlatReader {
+            inner,
+            genesis_hash,
+            items: Vec::new().into_iter(),
+            finished: false,
+        })
+    }
+
+    pub(crate) fn genesis_hash(&self) -> &packed::Byte32 {
+        &self.genesis_hash
+    }
+
+    // Reads and verifies the next chunk, returns false at the end chunk.
+    fn read_chunk(&mut self) -> io::Result<bool> {
+        let mut head = [0u8; 1 + 4 + 4];
+        self.inner.read_exact(&mut head)?;
+        let kind = ChunkKind::from_u8(head[0])
+            .ok_or_else(|| invalid_data(format!("unknown chunk kind {}", head[0])))?;
+        let count = u32::from_le_bytes(head[1..5].try_into().expect("checked len")) as usize;
+        let len = u32::from_le_bytes(head[5..9].try_into().expect("checked len")) as usize;
+        if len > MAX_CHUNK_BYTES {
+            return Err(invalid_data(format!("chunk of {} bytes is too large", len)));
+        }
+        let mut payload = vec![0u8; len];
+        self.inner.read_exact(&mut payload)?;
+        let mut checksum = [0u8; 32];
+        self.inner.read_exact(&mut checksum)?;
+        if checksum != blake2b_256(&payload) {
+            return Err(invalid_data("chunk checksum mismatch"));
+        }
+        if kind == ChunkKind::End {
+            return Ok(false);
+        }
+
+        let mut items = Vec::with_capacity(count.min(MAX_CHUNK_ITEMS));
+        let mut rest = &payload[..];
+        for _ in 0..count {
+            if rest.len() < 4 {
+                return Err(invalid_data("truncated chunk item"));
+            }
+            let item_len = u32::from_le_bytes(rest[..4].try_into().expect("checked len")) as usize;
+            if rest.len() < 4 + item_len {
+                return Err(invalid_data("truncated chunk item"));
+            }
+            let item = &rest[4..4 + item_len];
+            rest = &rest[4 + item_len..];
+            items.push(match kind {
+                ChunkKind::Blocks => {
+                    FlatItem::Block(packed::Block::from_slice(item).map_err(invalid_data)?)
+                }
+                ChunkKind::CellSet => {
+                    if item.len() < 32 {
+                        return Err(invalid_data("truncated cell set item"));
+                    }
+                    FlatItem::TxMeta(
+                        packed::Byte32::from_slice(&item[..32]).expect("checked len"),
+                        packed::TransactionMeta::from_slice(&item[32..]).map_err(invalid_data)?,
+                    )
+                }
+                ChunkKind::End => unreachable!(),
+            });
+        }
+        if !rest.is_empty() {
+            return Err(invalid_data("trailing bytes in chunk"));
+        }
+        self.items = items.into_iter();
+        Ok(true)
+    }
+}
+
+impl<R: Read> Iterator for FlatReader<R> {
+    type Item = io::Result<FlatItem>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.items.next() {
+                return Some(Ok(item));
+            }
+            if self.finished {
+                return None;
+            }
+            match self.read_chunk() {
+                Ok(true) => {}
+                Ok(false) => {
+                    self.finished = true;
+                    return None;
+                }
+                Err(err) => {
+                    // a truncated or corrupted file stops the iteration
+                    self.finished = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ckb_types::core::{BlockBuilder, HeaderBuilder, TransactionMeta};
+
+    fn build_items() -> Vec<FlatItem> {
+        let mut items: Vec<FlatItem> = (0..2500u64)
+            .map(|number| {
+                let header = HeaderBuilder::default().number(number.pack()).build();
+                FlatItem::Block(BlockBuilder::default().header(header).build().data())
+            })
+            .collect();
+        items.push(FlatItem::TxMeta(
+            packed::Byte32::zero(),
+            TransactionMeta::new(1, 0, packed::Byte32::zero(), 3, false).pack(),
+        ));
+        items
+    }
+
+    fn item_bytes(item: &FlatItem) -> Vec<u8> {
+        match item {
+            FlatItem::Block(block) => block.as_slice().to_vec(),
+            FlatItem::TxMeta(tx_hash, meta) => [tx_hash.as_slice(), meta.as_slice()].concat(),
+        }
+    }
+
+    fn write_items(items: &[FlatItem]) -> Vec<u8> {
+        let mut writer = FlatWriter::new(Vec::new(), &packed::Byte32::zero()).unwrap();
+        for item in items {
+            match item {
+                FlatItem::Block(block) => writer.write_block(block).unwrap(),
+                FlatItem::TxMeta(tx_hash, meta) => writer.write_tx_meta(tx_hash, meta).unwrap(),
+            }
+        }
+        writer.finish().unwrap()
+    }
+
+    #[test]
+    fn test_flat_roundtrip() {
+        let items = build_items();
+        let bytes = write_items(&items);
+        let reader = FlatReader::new(&bytes[..]).unwrap();
+        assert_eq!(reader.genesis_hash(), &packed::Byte32::zero());
+        let read: Vec<Vec<u8>> = reader.map(|item| item_bytes(&item.unwrap())).collect();
+        assert_eq!(read, items.iter().map(item_bytes).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_flat_corruption() {
+        let bytes = write_items(&build_items());
+
+        let mut corrupted = bytes.clone();
+        corrupted[100] ^= 1;
+        let reader = FlatReader::new(&corrupted[..]).unwrap();
+        assert!(reader.last().unwrap().is_err());
+
+        // the file without the end chunk is incomplete
+        let truncated = &bytes[..bytes.len() - 1];
+        let reader = FlatReader::new(truncated).unwrap();
+        assert!(reader.last().unwrap().is_err());
+
+        assert!(FlatReader::new(&bytes[1..]).is_err());
+    }
+}
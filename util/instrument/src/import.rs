@@ -1,12 +1,18 @@
-use ckb_chain::chain::ChainController;
+use crate::car::{is_car, CarReader};
+use crate::flat::{FlatItem, FlatReader, MAGIC};
+use crate::progress::Progress;
+use ckb_chain::{chain::ChainController, switch::Switch};
+use ckb_db::iter::IteratorMode;
 use ckb_jsonrpc_types::BlockView as JsonBlock;
-use ckb_types::core;
+use ckb_shared::shared::Shared;
+use ckb_store::{ChainStore, COLUMN_CELL_SET};
+use ckb_types::{core, packed, prelude::*};
 #[cfg(feature = "progress_bar")]
 use indicatif::{ProgressBar, ProgressStyle};
 use std::error::Error;
 use std::fs;
 use std::io;
-use std::io::BufRead;
+use std::io::{BufRead, Read};
 use std::path::PathBuf;
 use std::sync::Arc;
 
@@ -15,15 +21,62 @@ pub struct Import {
     /// source file contains block data
     source: PathBuf,
     chain: ChainController,
+    shared: Shared,
+    /// Skips the verification of the blocks, for the files from a trusted source
+    trusted: bool,
 }
 
 impl Import {
-    pub fn new(chain: ChainController, source: PathBuf) -> Self {
-        Import { chain, source }
+    pub fn new(chain: ChainController, shared: Shared, source: PathBuf) -> Self {
+        Import {
+            chain,
+            shared,
+            source,
+            trusted: false,
+        }
+    }
+
+    pub fn trusted(mut self, trusted: bool) -> Self {
+        self.trusted = trusted;
+        self
     }
 
+    /// Imports the file in the format detected from its head.
     pub fn execute(self) -> Result<(), Box<dyn Error>> {
-        self.read_from_json()
+        let mut reader = io::BufReader::new(fs::File::open(&self.source)?);
+        let head = reader.fill_buf()?.to_vec();
+        if head.starts_with(MAGIC) {
+            self.read_from_flat(reader)
+        } else if is_car(&head) {
+            self.read_from_car(reader)
+        } else {
+            self.read_from_json()
+        }
+    }
+
+    fn switch(&self) -> Switch {
+        if self.trusted {
+            Switch::DISABLE_ALL
+        } else {
+            Switch::NONE
+        }
+    }
+
+    fn process_block(&self, block: packed::Block) -> Result<(), Box<dyn Error>> {
+        let block: Arc<core::BlockView> = Arc::new(block.into_view());
+        if !block.is_genesis() {
+            self.chain
+                .internal_process_block(Arc::clone(&block), self.switch())
+                .map_err(|err| {
+                    format!(
+                        "failed to import block {} {:#x}: {}",
+                        block.number(),
+                        block.hash(),
+                        err
+                    )
+                })?;
+        }
+        Ok(())
     }
 
     #[cfg(not(feature = "progress_bar"))]
@@ -37,7 +90,7 @@ impl Import {
             let block: Arc<core::BlockView> = Arc::new(block.into());
             if !block.is_genesis() {
                 self.chain
-                    .process_block(block)
+                    .internal_process_block(block, self.switch())
                     .expect("import occur malformation data");
             }
         }
@@ -61,7 +114,7 @@ impl Import {
             let block: Arc<core::BlockView> = Arc::new(block.into());
             if !block.is_genesis() {
                 self.chain
-                    .process_block(block)
+                    .internal_process_block(block, self.switch())
                     .expect("import occur malformation data");
             }
             progress_bar.inc(s.as_bytes().len() as u64);
@@ -69,4 +122,72 @@ impl Import {
         progress_bar.finish_with_message("done!");
         Ok(())
     }
+
+    /// Imports the blocks of a flat snapshot, and checks the cell set after them against the
+    /// imported chain if exported.
+    pub fn read_from_flat<R: Read>(&self, reader: R) -> Result<(), Box<dyn Error>> {
+        let progress = Progress::bytes(fs::metadata(&self.source)?.len());
+        let reader = FlatReader::new(reader)?;
+        if reader.genesis_hash() != &self.shared.genesis_hash() {
+            return Err("the snapshot is of another chain".into());
+        }
+        let mut cells = 0usize;
+        for item in reader {
+            match item? {
+                FlatItem::Block(block) => {
+                    progress.inc(block.as_slice().len() as u64);
+                    self.process_block(block)?;
+                }
+                FlatItem::TxMeta(tx_hash, meta) => {
+                    progress.inc((tx_hash.as_slice().len() + meta.as_slice().len()) as u64);
+                    let matched = self
+                        .shared
+                        .snapshot()
+                        .get(COLUMN_CELL_SET, tx_hash.as_slice())
+                        .map(|stored| AsRef::<[u8]>::as_ref(&stored) == meta.as_slice())
+                        .unwrap_or(false);
+                    if !matched {
+                        return Err(format!(
+                            "the cell set mismatches the imported chain at tx {:#x}",
+                            tx_hash
+                        )
+                        .into());
+                    }
+                    cells += 1;
+                }
+            }
+        }
+        if cells > 0 {
+            let stored_cells = self
+                .shared
+                .snapshot()
+                .get_iter(COLUMN_CELL_SET, IteratorMode::Start)
+                .count();
+            if stored_cells != cells {
+                return Err(format!(
+                    "the cell set has {} txs, the imported chain has {}",
+                    cells, stored_cells
+                )
+                .into());
+            }
+        }
+        progress.finish();
+        Ok(())
+    }
+
+    /// Imports the blocks of a CAR archive.
+    pub fn read_from_car<R: Read>(&self, reader: R) -> Result<(), Box<dyn Error>> {
+        let progress = Progress::bytes(fs::metadata(&self.source)?.len());
+        let reader = CarReader::new(reader)?;
+        if !reader.is_root(&self.shared.consensus().genesis_block().data()) {
+            return Err("the archive is of another chain".into());
+        }
+        for block in reader {
+            let block = block?;
+            progress.inc(block.as_slice().len() as u64);
+            self.process_block(block)?;
+        }
+        progress.finish();
+        Ok(())
+    }
 }
@@ -12,11 +12,21 @@ pub struct ChainIterator {
 
 impl ChainIterator {
     pub fn new(shared: Shared) -> Self {
+        let tip = shared.snapshot().tip_number();
+        Self::range(shared, 0, tip)
+    }
+
+    // Iterates the main chain blocks in `[from, to]`, `to` is capped at the tip.
+    pub fn range(shared: Shared, from: BlockNumber, to: BlockNumber) -> Self {
         let snapshot = Arc::clone(&shared.snapshot());
-        let current = snapshot
-            .get_block_hash(0)
-            .and_then(|h| snapshot.get_block(&h));
-        let tip = snapshot.tip_number();
+        let tip = to.min(snapshot.tip_number());
+        let current = if from <= tip {
+            snapshot
+                .get_block_hash(from)
+                .and_then(|h| snapshot.get_block(&h))
+        } else {
+            None
+        };
         ChainIterator {
             snapshot,
             current,
@@ -36,14 +46,14 @@ impl Iterator for ChainIterator {
         let current = self.current.take();
 
         self.current = match current {
-            Some(ref b) => {
+            Some(ref b) if b.header().number() < self.tip => {
                 if let Some(block_hash) = self.snapshot.get_block_hash(b.header().number() + 1) {
                     self.snapshot.get_block(&block_hash)
                 } else {
                     None
                 }
             }
-            None => None,
+            _ => None,
         };
         current
     }
@@ -55,3 +65,53 @@ impl Iterator for ChainIterator {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ckb_chain::{chain::ChainService, switch::Switch};
+    use ckb_shared::shared::SharedBuilder;
+    use ckb_types::{core::BlockBuilder, prelude::*};
+
+    fn build_chain(tip: BlockNumber) -> Shared {
+        let (shared, table) = SharedBuilder::default().build().unwrap();
+        let chain_controller = ChainService::new(shared.clone(), table).start::<&str>(None);
+        for _ in 0..tip {
+            let parent = shared.snapshot().tip_header().to_owned();
+            let block = BlockBuilder::default()
+                .parent_hash(parent.hash())
+                .number((parent.number() + 1).pack())
+                .timestamp((parent.timestamp() + 1).pack())
+                .epoch(parent.epoch().pack())
+                .compact_target(parent.compact_target().pack())
+                .build();
+            chain_controller
+                .internal_process_block(Arc::new(block), Switch::DISABLE_ALL)
+                .unwrap();
+        }
+        shared
+    }
+
+    fn numbers(iter: ChainIterator) -> Vec<BlockNumber> {
+        iter.map(|block| block.header().number()).collect()
+    }
+
+    #[test]
+    fn test_chain_iterator_range() {
+        let shared = build_chain(5);
+        assert_eq!(
+            numbers(ChainIterator::new(shared.clone())),
+            vec![0, 1, 2, 3, 4, 5]
+        );
+        assert_eq!(
+            numbers(ChainIterator::range(shared.clone(), 2, 4)),
+            vec![2, 3, 4]
+        );
+        // the end is capped at the tip
+        assert_eq!(
+            numbers(ChainIterator::range(shared.clone(), 4, 10)),
+            vec![4, 5]
+        );
+        assert!(numbers(ChainIterator::range(shared, 6, 10)).is_empty());
+    }
+}
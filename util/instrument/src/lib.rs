@@ -6,10 +6,17 @@
 //!   export function.
 //! - [Import](instrument::import::Import) import block data which
 //!   export from `Export`.
+//!
+//! The blocks are exported in JSON lines, or in the flat and the CAR snapshot formats, which
+//! checksum the blocks so a node can be bootstrapped from a trusted snapshot.
 
+mod car;
 mod export;
+mod flat;
 mod import;
 mod iter;
+mod progress;
 
-pub use crate::export::Export;
+pub use crate::export::{Export, Format};
 pub use crate::import::Import;
+pub use crate::iter::ChainIterator;
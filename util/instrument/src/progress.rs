@@ -0,0 +1,59 @@
+#[cfg(feature = "progress_bar")]
+use indicatif::{ProgressBar, ProgressStyle};
+
+// The progress of the exported or imported snapshots, does nothing without the `progress_bar`
+// feature.
+pub(crate) struct Progress {
+    #[cfg(feature = "progress_bar")]
+    bar: ProgressBar,
+}
+
+impl Progress {
+    #[cfg(feature = "progress_bar")]
+    fn new(len: u64, template: &str) -> Self {
+        let bar = ProgressBar::new(len);
+        bar.set_style(
+            ProgressStyle::default_bar()
+                .template(template)
+                .progress_chars("##-"),
+        );
+        Progress { bar }
+    }
+
+    #[cfg(not(feature = "progress_bar"))]
+    fn new(_len: u64, _template: &str) -> Self {
+        Progress {}
+    }
+
+    /// Counts the blocks.
+    pub(crate) fn blocks(len: u64) -> Self {
+        Self::new(
+            len,
+            "[{elapsed_precise}] {bar:50.cyan/blue} {pos:>6}/{len:6} {msg}",
+        )
+    }
+
+    /// Counts the bytes.
+    pub(crate) fn bytes(len: u64) -> Self {
+        Self::new(
+            len,
+            "[{elapsed_precise}] {bar:50.cyan/blue} {bytes:>6}/{total_bytes:6} {msg}",
+        )
+    }
+
+    #[cfg(feature = "progress_bar")]
+    pub(crate) fn inc(&self, delta: u64) {
+        self.bar.inc(delta);
+    }
+
+    #[cfg(not(feature = "progress_bar"))]
+    pub(crate) fn inc(&self, _delta: u64) {}
+
+    #[cfg(feature = "progress_bar")]
+    pub(crate) fn finish(&self) {
+        self.bar.finish_with_message("done!");
+    }
+
+    #[cfg(not(feature = "progress_bar"))]
+    pub(crate) fn finish(&self) {}
+}
@@ -1,6 +1,6 @@
 use crate::{
-    BlockNumber, Byte32, Cycle, EpochNumberWithFraction, Header, ProposalShortId, Timestamp,
-    Transaction, Uint32, Uint64, Version,
+    BlockNumber, Byte32, Capacity, Cycle, EpochNumberWithFraction, Header, ProposalShortId,
+    Timestamp, Transaction, Uint128, Uint32, Uint64, Version,
 };
 use ckb_types::{packed, prelude::*, H256};
 use serde::{Deserialize, Serialize};
@@ -23,6 +23,25 @@ pub struct BlockTemplate {
     pub cellbase: CellbaseTemplate,
     pub work_id: Uint64,
     pub dao: Byte32,
+    // Nonces reserved for the caller, so that miners sharing a node search disjoint spaces
+    #[serde(default)]
+    pub nonce_range: Option<NonceRange>,
+    // Identifies the tip and the pool state the template is built on, pass it to
+    // `get_block_template_longpoll` to wait for a newer template
+    #[serde(default)]
+    pub longpoll_id: Option<String>,
+    // The sums of the cycles and the fees of `transactions`, the cellbase excluded
+    #[serde(default)]
+    pub transactions_cycles: Cycle,
+    #[serde(default)]
+    pub transactions_fee: Capacity,
+}
+
+/// Nonce range `[start, end)` leased to a single `get_block_template` caller.
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct NonceRange {
+    pub start: Uint128,
+    pub end: Uint128,
 }
 
 impl From<BlockTemplate> for packed::Block {
@@ -116,7 +135,11 @@ pub struct TransactionTemplate {
     pub hash: H256,
     pub required: bool,
     pub cycles: Option<Cycle>,
+    // The indices in `transactions` of the ancestors of this transaction in the template, which
+    // must be kept if this one is
     pub depends: Option<Vec<Uint64>>,
+    #[serde(default)]
+    pub fee: Option<Capacity>,
     pub data: Transaction, // temporary
 }
 
@@ -126,3 +149,15 @@ impl From<TransactionTemplate> for packed::Transaction {
         data.into()
     }
 }
+
+/// A getwork-style job derived from a block template.
+///
+/// Miners search a `nonce` such that the PoW output of (`pow_hash`, `nonce`) is not greater
+/// than `target`, then hand the solution back via `submit_work`.
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct Work {
+    pub work_id: Uint64,
+    pub pow_hash: H256,
+    pub target: H256,
+    pub number: BlockNumber,
+}
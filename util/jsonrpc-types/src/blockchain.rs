@@ -3,7 +3,7 @@ use crate::{
     BlockNumber, Byte32, Capacity, EpochNumber, EpochNumberWithFraction, ProposalShortId,
     Timestamp, Uint128, Uint32, Uint64, Version,
 };
-use ckb_types::{core, packed, prelude::*, H256};
+use ckb_types::{core, packed, prelude::*, utilities, H256};
 use serde::{Deserialize, Serialize};
 use std::convert::TryFrom;
 use std::fmt;
@@ -360,6 +360,8 @@ pub enum Status {
     Proposed,
     /// Transaction commit on block
     Committed,
+    /// Transaction refused by or dropped from the pool, only reported for the local transactions
+    Rejected,
 }
 
 #[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
@@ -782,6 +784,60 @@ impl From<BlockEconomicState> for core::BlockEconomicState {
     }
 }
 
+/// A main chain reorganization pushed to the `reorg` subscribers, the headers are ordered from
+/// the lowest to the highest.
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct ReorgEvent {
+    /// The last block shared by the detached and the attached blocks
+    pub common_ancestor: HeaderView,
+    pub detached_blocks: Vec<HeaderView>,
+    pub attached_blocks: Vec<HeaderView>,
+}
+
+/// The proof that the transactions are in the block, it verifies against the `transactions_root`
+/// of the block header.
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct TransactionProof {
+    pub block_hash: H256,
+    /// The root of the witness hashes, which makes the `transactions_root` with the root of the
+    /// transaction hashes
+    pub witnesses_root: H256,
+    pub proof: MerkleProof,
+}
+
+/// The proof of the leaves of a complete binary merkle tree, the leaf `i` of a tree with `n`
+/// leaves is indexed by `n - 1 + i`.
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct MerkleProof {
+    pub indices: Vec<Uint32>,
+    pub lemmas: Vec<H256>,
+}
+
+impl From<utilities::MerkleProof> for MerkleProof {
+    fn from(proof: utilities::MerkleProof) -> Self {
+        Self {
+            indices: proof
+                .indices()
+                .iter()
+                .map(|index| (*index).into())
+                .collect(),
+            lemmas: proof.lemmas().iter().map(|lemma| lemma.unpack()).collect(),
+        }
+    }
+}
+
+impl From<MerkleProof> for utilities::MerkleProof {
+    fn from(json: MerkleProof) -> Self {
+        utilities::MerkleProof::new(
+            json.indices
+                .into_iter()
+                .map(|index| index.value())
+                .collect(),
+            json.lemmas.into_iter().map(|lemma| lemma.pack()).collect(),
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
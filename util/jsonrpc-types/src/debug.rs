@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+/// The options of the main logger changed by `update_main_logger`, the absent ones are left
+/// unchanged.
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct MainLoggerConfig {
+    // The filter in the `CKB_LOG` syntax, e.g. `info,ckb-sync=debug`
+    pub filter: Option<String>,
+    pub to_stdout: Option<bool>,
+    // Ignored unless `log_to_file` is enabled in ckb.toml, since the log file is not created
+    pub to_file: Option<bool>,
+    pub color: Option<bool>,
+}
@@ -1,9 +1,48 @@
-use crate::{Cycle, FeeRate};
+use crate::{CellOutput, Cycle, FeeRate, JsonBytes, OutPoint, Uint32, Uint64};
+use ckb_types::H256;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
 pub struct DryRunResult {
     pub cycles: Cycle,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<Vec<ScriptGroupProfile>>,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ScriptGroupType {
+    Lock,
+    Type,
+}
+
+/// Cycles and wall time consumed by a single script group during dry run.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct ScriptGroupProfile {
+    pub group_type: ScriptGroupType,
+    pub index: Uint32,
+    pub script_hash: H256,
+    pub cycles: Cycle,
+    pub elapsed_micros: Uint64,
+    pub vm_version: Uint32,
+}
+
+/// A cell which dry run treats as live, whether or not it exists on chain.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct MockCell {
+    pub out_point: OutPoint,
+    pub output: CellOutput,
+    pub data: JsonBytes,
+}
+
+/// How `estimate_fee_rate` estimates the fee rate.
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum EstimateMode {
+    /// By the confirmation history only
+    Historical,
+    /// By the confirmation history and the transactions currently in the tx pool
+    Mempool,
 }
 
 #[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
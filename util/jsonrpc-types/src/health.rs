@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    Healthy,
+    Degraded,
+    Unhealthy,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub struct ServiceHealth {
+    pub name: String,
+    pub status: HealthStatus,
+    // why the service is not healthy
+    pub reason: Option<String>,
+    // key states of the service, e.g. the tip number of the chain
+    pub details: Vec<HealthDetail>,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub struct HealthDetail {
+    pub key: String,
+    pub value: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub struct HealthInfo {
+    // the worst status of the services
+    pub status: HealthStatus,
+    pub services: Vec<ServiceHealth>,
+}
@@ -1,4 +1,4 @@
-use crate::{BlockNumber, Capacity, CellOutput, Uint64};
+use crate::{BlockNumber, Capacity, CellOutput, JsonBytes, Script, TransactionView, Uint64};
 use ckb_types::H256;
 use serde::{Deserialize, Serialize};
 
@@ -38,3 +38,68 @@ pub struct LockHashCapacity {
     pub cells_count: Uint64,
     pub block_number: BlockNumber,
 }
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexerScriptType {
+    Lock,
+    Type,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexerScriptSearchMode {
+    Prefix,
+    Exact,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexerOrder {
+    Desc,
+    Asc,
+}
+
+// This is used as return value of get_grouped_cells_capacity RPC
+#[derive(Serialize, Deserialize, Debug)]
+pub struct IndexerCellsCapacity {
+    pub lock: Script,
+    #[serde(rename = "type")]
+    pub type_: Option<Script>,
+    pub capacity: Capacity,
+    pub cells_count: Uint64,
+}
+
+// This is used as params of get_cells and get_transactions RPC
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct IndexerSearchKey {
+    pub script: Script,
+    pub script_type: IndexerScriptType,
+    pub script_search_mode: Option<IndexerScriptSearchMode>,
+    pub filter: Option<IndexerSearchKeyFilter>,
+}
+
+// Ranges are [start, end)
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+pub struct IndexerSearchKeyFilter {
+    pub output_data_prefix: Option<JsonBytes>,
+    pub output_capacity_range: Option<[Capacity; 2]>,
+    pub block_range: Option<[BlockNumber; 2]>,
+}
+
+// This is used as return value of get_cells and get_transactions RPC
+#[derive(Serialize, Deserialize, Debug)]
+pub struct IndexerPagination<T> {
+    pub objects: Vec<T>,
+    pub last_cursor: JsonBytes,
+}
+
+// This is pushed to the `watched_transaction` subscribers and the webhook
+#[derive(Serialize, Deserialize, Debug)]
+pub struct WatchedTransaction {
+    pub lock_hashes: Vec<H256>,
+    pub block_number: BlockNumber,
+    pub block_hash: H256,
+    pub transaction: TransactionView,
+    pub detached: bool,
+}
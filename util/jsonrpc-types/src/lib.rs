@@ -4,9 +4,12 @@ mod blockchain;
 mod bytes;
 mod cell;
 mod chain_info;
+mod debug;
 mod experiment;
 mod fixed_bytes;
+mod health;
 mod indexer;
+mod memory;
 mod net;
 mod pool;
 mod primitive;
@@ -16,24 +19,34 @@ mod uints;
 
 pub use self::alert::{Alert, AlertMessage};
 pub use self::block_template::{
-    BlockTemplate, CellbaseTemplate, TransactionTemplate, UncleTemplate,
+    BlockTemplate, CellbaseTemplate, NonceRange, TransactionTemplate, UncleTemplate, Work,
 };
 pub use self::blockchain::{
     Block, BlockEconomicState, BlockIssuance, BlockReward, BlockView, CellDep, CellInput,
-    CellOutput, DepType, EpochView, Header, HeaderView, MinerReward, OutPoint, Script,
-    ScriptHashType, Status, Transaction, TransactionView, TransactionWithStatus, TxStatus,
-    UncleBlock, UncleBlockView,
+    CellOutput, DepType, EpochView, Header, HeaderView, MerkleProof, MinerReward, OutPoint,
+    ReorgEvent, Script, ScriptHashType, Status, Transaction, TransactionProof, TransactionView,
+    TransactionWithStatus, TxStatus, UncleBlock, UncleBlockView,
 };
 pub use self::bytes::JsonBytes;
 pub use self::cell::{CellOutputWithOutPoint, CellWithStatus};
 pub use self::chain_info::ChainInfo;
-pub use self::experiment::{DryRunResult, EstimateResult};
+pub use self::debug::MainLoggerConfig;
+pub use self::experiment::{
+    DryRunResult, EstimateMode, EstimateResult, MockCell, ScriptGroupProfile, ScriptGroupType,
+};
 pub use self::fixed_bytes::Byte32;
+pub use self::health::{HealthDetail, HealthInfo, HealthStatus, ServiceHealth};
 pub use self::indexer::{
-    CellTransaction, LiveCell, LockHashCapacity, LockHashIndexState, TransactionPoint,
+    CellTransaction, IndexerCellsCapacity, IndexerOrder, IndexerPagination,
+    IndexerScriptSearchMode, IndexerScriptType, IndexerSearchKey, IndexerSearchKeyFilter, LiveCell,
+    LockHashCapacity, LockHashIndexState, TransactionPoint, WatchedTransaction,
+};
+pub use self::memory::{MemoryUsage, StructureMemoryUsage};
+pub use self::net::{BannedAddr, CompactBlockStats, MessageStats, Node, NodeAddress, PeerScore};
+pub use self::pool::{
+    FeeHistogramBin, LocalTxStatus, OutputsValidator, RejectedTransaction, TxPoolAcceptResult,
+    TxPoolFeeHistogram, TxPoolInfo, TxPoolLimits,
 };
-pub use self::net::{BannedAddr, Node, NodeAddress};
-pub use self::pool::{OutputsValidator, TxPoolInfo};
 pub use self::proposal_short_id::ProposalShortId;
 pub use self::sync::PeerState;
 pub use self::uints::{Uint128, Uint32, Uint64};
@@ -0,0 +1,19 @@
+use crate::Uint64;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub struct MemoryUsage {
+    // bytes allocated by the application, null if the allocator stats are unsupported
+    pub allocated: Option<Uint64>,
+    // bytes in physically resident data pages mapped by the allocator
+    pub resident: Option<Uint64>,
+    // estimates of the structures which may grow on a long-running node
+    pub structures: Vec<StructureMemoryUsage>,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub struct StructureMemoryUsage {
+    pub name: String,
+    pub entries: Uint64,
+    pub bytes: Uint64,
+}
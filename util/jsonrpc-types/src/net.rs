@@ -8,6 +8,35 @@ pub struct Node {
     pub node_id: String,
     pub addresses: Vec<NodeAddress>,
     pub is_outbound: Option<bool>,
+    // Only the connected peers have it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compact_block_stats: Option<CompactBlockStats>,
+    // Only the connected peers have it
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub message_stats: Vec<MessageStats>,
+}
+
+/// The reconstruction statistics of the compact blocks received from a peer.
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct CompactBlockStats {
+    pub compact_blocks: Uint64,
+    pub short_ids: Uint64,
+    pub short_id_hits: Uint64,
+    pub fallbacks: Uint64,
+    pub collisions: Uint64,
+    /// The new blocks are announced to the peer with all the transactions prefilled
+    pub full_block_announcement: bool,
+}
+
+/// The statistics of the messages received from a peer on a protocol.
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct MessageStats {
+    pub protocol_id: Uint64,
+    pub messages: Uint64,
+    pub bytes: Uint64,
+    pub last_received_at: Timestamp,
+    /// The average time in microseconds to process a message
+    pub average_latency: Uint64,
 }
 
 #[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
@@ -23,3 +52,13 @@ pub struct BannedAddr {
     pub ban_reason: String,
     pub created_at: Timestamp,
 }
+
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct PeerScore {
+    pub node_id: String,
+    /// The accumulated penalties after decaying, rounded down
+    pub score: Uint64,
+    pub misbehaviors: Uint64,
+    pub last_misbehavior: String,
+    pub updated_at: Timestamp,
+}
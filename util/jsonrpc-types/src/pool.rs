@@ -1,4 +1,5 @@
-use crate::{Timestamp, Uint64};
+use crate::{Capacity, Cycle, Status, Timestamp, TransactionView, Uint64};
+use ckb_types::H256;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
@@ -9,12 +10,80 @@ pub struct TxPoolInfo {
     pub total_tx_size: Uint64,
     pub total_tx_cycles: Uint64,
     pub min_fee_rate: Uint64,
+    /// Shannons per kilobyte, the configured `min_fee_rate`, or the one raised by the evictions
+    /// of the full pool
+    pub min_entry_fee_rate: Uint64,
     pub last_txs_updated_at: Timestamp,
 }
 
+/// The tx pool limits changed by `set_tx_pool_config`, the absent ones are left unchanged.
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct TxPoolLimits {
+    /// Keep the total size of the transactions in the pool below it
+    pub max_mem_size: Option<Uint64>,
+    /// Keep the total cycles of the transactions in the pool below it
+    pub max_cycles: Option<Cycle>,
+    /// Shannons per kilobyte, the transactions paying less are rejected
+    pub min_fee_rate: Option<Uint64>,
+}
+
 #[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
 #[serde(rename_all = "snake_case")]
 pub enum OutputsValidator {
     Default,
     Passthrough,
 }
+
+/// A transaction pushed to the `rejected_transaction` subscribers.
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct RejectedTransaction {
+    pub transaction: TransactionView,
+    /// Why the tx pool refuses the transaction
+    pub reason: String,
+}
+
+/// The verdict of a transaction checked by `test_tx_pool_accept`.
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct TxPoolAcceptResult {
+    pub tx_hash: H256,
+    pub allowed: bool,
+    /// The cycles consumed by the scripts, null if the transaction is rejected
+    pub cycles: Option<Cycle>,
+    /// null if the transaction is rejected
+    pub fee: Option<Capacity>,
+    /// Why the tx pool would reject the transaction
+    pub reject_reason: Option<String>,
+}
+
+/// The status of a transaction submitted via `send_transaction`.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct LocalTxStatus {
+    pub status: Status,
+    /// The block committing the transaction, null unless committed
+    pub block_hash: Option<H256>,
+    /// Why the transaction is refused by or dropped from the pool, null unless rejected
+    pub reason: Option<String>,
+}
+
+/// The virtual bytes and cycles of the pending and proposed transactions binned by their fee
+/// rates, and the counts of the transactions in each state.
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct TxPoolFeeHistogram {
+    /// In descending order of the fee rate
+    pub bins: Vec<FeeHistogramBin>,
+    pub pending: Uint64,
+    /// The transactions proposed but not yet committable
+    pub gap: Uint64,
+    pub proposed: Uint64,
+    pub orphan: Uint64,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub struct FeeHistogramBin {
+    /// Shannons per kilobyte, the lower bound of the bin
+    pub fee_rate: Uint64,
+    /// The cumulative virtual bytes of the transactions paying at least `fee_rate`
+    pub vbytes: Uint64,
+    /// The cumulative cycles of the transactions paying at least `fee_rate`
+    pub cycles: Cycle,
+}
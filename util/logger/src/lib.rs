@@ -18,6 +18,7 @@ pub use serde_json::{json, Map, Value};
 lazy_static! {
     static ref CONTROL_HANDLE: sync::Arc<RwLock<Option<crossbeam_channel::Sender<Message>>>> =
         sync::Arc::new(RwLock::new(None));
+    static ref FILTER_SPEC: RwLock<String> = RwLock::new(String::new());
 }
 
 #[doc(hidden)]
@@ -143,6 +144,12 @@ macro_rules! log_enabled_target {
 enum Message {
     Record(String),
     Filter(Filter),
+    ReopenFile,
+    UpdateMainLogger {
+        to_stdout: Option<bool>,
+        to_file: Option<bool>,
+        color: Option<bool>,
+    },
     Terminate,
 }
 
@@ -151,6 +158,7 @@ pub struct Logger {
     sender: crossbeam_channel::Sender<Message>,
     handle: Mutex<Option<thread::JoinHandle<()>>>,
     filter: sync::Arc<RwLock<Filter>>,
+    format: Format,
     emit_sentry_breadcrumbs: bool,
 }
 
@@ -180,15 +188,73 @@ fn test_convert_compatible_crate_name() {
     assert_eq!(&result, &expected);
 }
 
+// Merge the directives into the filter spec, a directive replaces the one of the same module,
+// a bare level replaces the default level. The regex of the spec is kept.
+fn merge_filter_directives(spec: &str, directives: &str) -> String {
+    let mut parts = spec.splitn(2, '/');
+    let mut merged: Vec<String> = parts
+        .next()
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|directive| !directive.is_empty())
+        .map(ToString::to_string)
+        .collect();
+    let regex = parts.next();
+
+    let module = |directive: &str| directive.splitn(2, '=').next().map(ToString::to_string);
+    for directive in convert_compatible_crate_name(directives)
+        .split(',')
+        .map(str::trim)
+        .filter(|directive| !directive.is_empty())
+    {
+        if directive.contains('=') {
+            let name = module(directive);
+            match merged.iter_mut().find(|existing| module(existing) == name) {
+                Some(existing) => *existing = directive.to_string(),
+                None => merged.push(directive.to_string()),
+            }
+        } else {
+            merged.retain(|existing| existing.contains('='));
+            merged.insert(0, directive.to_string());
+        }
+    }
+
+    let merged = merged.join(",");
+    match regex {
+        Some(regex) => format!("{}/{}", merged, regex),
+        None => merged,
+    }
+}
+
+#[test]
+fn test_merge_filter_directives() {
+    let spec = "info,ckb_sync=debug,ckb_network=warn/ckb";
+    assert_eq!(
+        merge_filter_directives(spec, "ckb-sync=trace,ckb-identify=debug"),
+        "info,ckb_sync=trace,ckb_network=warn,ckb_identify=debug/ckb"
+    );
+    assert_eq!(
+        merge_filter_directives(spec, "warn"),
+        "warn,ckb_sync=debug,ckb_network=warn/ckb"
+    );
+    assert_eq!(
+        merge_filter_directives("", "ckb-sync=debug"),
+        "ckb_sync=debug"
+    );
+}
+
 impl Logger {
     fn new(config: Config) -> Logger {
         let mut builder = Builder::new();
 
-        if let Ok(ref env_filter) = std::env::var("CKB_LOG") {
-            builder.parse(&convert_compatible_crate_name(env_filter));
-        } else if let Some(ref config_filter) = config.filter {
-            builder.parse(&convert_compatible_crate_name(config_filter));
-        }
+        let spec = std::env::var("CKB_LOG")
+            .ok()
+            .or_else(|| config.filter.clone())
+            .map(|spec| convert_compatible_crate_name(&spec))
+            .unwrap_or_default();
+        builder.parse(&spec);
+        *FILTER_SPEC.write() = spec;
 
         let (sender, receiver) = unbounded();
         CONTROL_HANDLE.write().replace(sender.clone());
@@ -197,9 +263,14 @@ impl Logger {
             file,
             log_to_file,
             log_to_stdout,
+            format,
             ..
         } = config;
-        let file = if log_to_file { file } else { None };
+        let mut color = color && format == Format::Text;
+        let mut log_to_stdout = log_to_stdout;
+        let mut log_to_file = log_to_file;
+        // the file is only given when logging to file is enabled in the config
+        let file_path = if log_to_file { file } else { None };
         let filter = sync::Arc::new(RwLock::new(builder.build()));
         let filter_for_update = sync::Arc::clone(&filter);
 
@@ -208,14 +279,12 @@ impl Logger {
             .spawn(move || {
                 enable_ansi_support();
 
-                let file = file.map(|file| {
-                    fs::OpenOptions::new()
-                        .append(true)
-                        .create(true)
-                        .open(&file)
-                        .unwrap_or_else(|_| {
-                            panic!("Cannot write to log file given: {:?}", file.as_os_str())
-                        })
+                let open =
+                    |path: &PathBuf| fs::OpenOptions::new().append(true).create(true).open(path);
+                let mut file = file_path.as_ref().map(|path| {
+                    open(path).unwrap_or_else(|_| {
+                        panic!("Cannot write to log file given: {:?}", path.as_os_str())
+                    })
                 });
 
                 loop {
@@ -223,7 +292,7 @@ impl Logger {
                         Ok(Message::Record(record)) => {
                             let removed_color = sanitize_color(record.as_ref());
                             let output = if color { record } else { removed_color.clone() };
-                            if let Some(mut file) = file.as_ref() {
+                            if let Some(mut file) = file.as_ref().filter(|_| log_to_file) {
                                 let _ = file.write_all(removed_color.as_bytes());
                                 let _ = file.write_all(b"\n");
                             };
@@ -235,6 +304,31 @@ impl Logger {
                             *filter_for_update.write() = filter;
                             log::set_max_level(filter_for_update.read().filter());
                         }
+                        Ok(Message::ReopenFile) => {
+                            // the file may have been moved by logrotate, keep the opened one on
+                            // error
+                            if let Some(path) = file_path.as_ref() {
+                                match open(path) {
+                                    Ok(reopened) => file = Some(reopened),
+                                    Err(err) => eprintln!(
+                                        "Cannot reopen log file {:?}: {}",
+                                        path.as_os_str(),
+                                        err
+                                    ),
+                                }
+                            }
+                        }
+                        Ok(Message::UpdateMainLogger {
+                            to_stdout,
+                            to_file,
+                            color: new_color,
+                        }) => {
+                            log_to_stdout = to_stdout.unwrap_or(log_to_stdout);
+                            log_to_file = to_file.unwrap_or(log_to_file);
+                            color = new_color
+                                .map(|c| c && format == Format::Text)
+                                .unwrap_or(color);
+                        }
                         Ok(Message::Terminate) | Err(_) => {
                             break;
                         }
@@ -247,6 +341,7 @@ impl Logger {
             sender,
             handle: Mutex::new(Some(tb)),
             filter,
+            format,
             emit_sentry_breadcrumbs: config.emit_sentry_breadcrumbs.unwrap_or_default(),
         }
     }
@@ -256,6 +351,22 @@ impl Logger {
     }
 }
 
+/// The format of the log lines.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Format {
+    /// Human readable text, colored if `color` is enabled
+    Text,
+    /// One JSON object per line with timestamp, level, target, thread and message
+    Json,
+}
+
+impl Default for Format {
+    fn default() -> Self {
+        Format::Text
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct Config {
     pub filter: Option<String>,
@@ -264,6 +375,8 @@ pub struct Config {
     pub log_to_file: bool,
     pub log_to_stdout: bool,
     pub emit_sentry_breadcrumbs: Option<bool>,
+    #[serde(default)]
+    pub format: Format,
 }
 
 impl Default for Config {
@@ -275,6 +388,7 @@ impl Default for Config {
             log_to_file: false,
             log_to_stdout: true,
             emit_sentry_breadcrumbs: None,
+            format: Format::Text,
         }
     }
 }
@@ -295,6 +409,19 @@ impl Log for Logger {
             let thread = thread::current();
             let thread_name = thread.name().unwrap_or_default();
 
+            if self.format == Format::Json {
+                let dt: DateTime<Local> = Local::now();
+                let line = json!({
+                    "timestamp": dt.to_rfc3339(),
+                    "level": record.level().to_string(),
+                    "target": record.target(),
+                    "thread": thread_name,
+                    "message": record.args().to_string(),
+                });
+                let _ = self.sender.send(Message::Record(line.to_string()));
+                return;
+            }
+
             let with_color = {
                 let thread_name = format!("{}", Colour::Blue.bold().paint(thread_name));
                 let dt: DateTime<Local> = Local::now();
@@ -399,11 +526,43 @@ pub fn __log_metric(metric: &mut Value, default_target: &str) {
 }
 
 pub fn configure_logger_filter(filter_str: &str) {
-    let filter = Builder::new()
-        .parse(&convert_compatible_crate_name(filter_str))
-        .build();
+    let spec = convert_compatible_crate_name(filter_str);
+    let filter = Builder::new().parse(&spec).build();
+    *FILTER_SPEC.write() = spec;
     let _ = CONTROL_HANDLE
         .read()
         .as_ref()
         .map(|sender| sender.send(Message::Filter(filter)));
 }
+
+/// Reopens the log file, e.g. after it is moved by logrotate.
+pub fn reopen_log_file() {
+    let _ = CONTROL_HANDLE
+        .read()
+        .as_ref()
+        .map(|sender| sender.send(Message::ReopenFile));
+}
+
+/// Changes the outputs of the main logger, the absent options are left unchanged. Logging to
+/// file can't be turned on if the log file is not configured at startup.
+pub fn update_main_logger(to_stdout: Option<bool>, to_file: Option<bool>, color: Option<bool>) {
+    let _ = CONTROL_HANDLE.read().as_ref().map(|sender| {
+        sender.send(Message::UpdateMainLogger {
+            to_stdout,
+            to_file,
+            color,
+        })
+    });
+}
+
+/// Changes the levels of the modules in the directives, e.g. `ckb-sync=debug`, and keeps the
+/// levels of the other modules.
+pub fn update_logger_filter(directives: &str) {
+    let spec = merge_filter_directives(&FILTER_SPEC.read(), directives);
+    configure_logger_filter(&spec);
+}
+
+/// The filter in effect, in the `CKB_LOG` syntax.
+pub fn logger_filter() -> String {
+    FILTER_SPEC.read().clone()
+}
@@ -0,0 +1,132 @@
+use std::sync::Arc;
+use std::{thread, time};
+
+use ckb_logger::{debug, error, info};
+use ckb_util::RwLock;
+
+use crate::metrics::{ESTIMATED_BYTES, ESTIMATED_ENTRIES};
+use crate::utils::HumanReadableSize;
+
+/// The estimated memory of a structure, such as the pending pool of the tx-pool.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MemoryEstimate {
+    pub name: String,
+    pub entries: usize,
+    pub bytes: usize,
+}
+
+type Estimator = Arc<dyn Fn() -> (usize, usize) + Send + Sync>;
+
+/// The structures which may grow on a long-running node, the clones share the same estimators.
+///
+/// An estimator returns the count of entries and the estimated bytes of a structure. The bytes
+/// are not precise, they are used to find out which structure grows.
+#[derive(Clone, Default)]
+pub struct MemoryEstimators {
+    estimators: Arc<RwLock<Vec<(String, Estimator)>>>,
+}
+
+impl MemoryEstimators {
+    pub fn new() -> Self {
+        MemoryEstimators::default()
+    }
+
+    pub fn register<F>(&self, name: &str, estimator: F)
+    where
+        F: Fn() -> (usize, usize) + Send + Sync + 'static,
+    {
+        let mut estimators = self.estimators.write();
+        estimators.retain(|(registered, _)| registered != name);
+        estimators.push((name.to_string(), Arc::new(estimator)));
+    }
+
+    pub fn estimate(&self) -> Vec<MemoryEstimate> {
+        // the estimators may acquire other locks, so they are run without holding the lock
+        let estimators = self.estimators.read().clone();
+        estimators
+            .into_iter()
+            .map(|(name, estimator)| {
+                let (entries, bytes) = estimator();
+                MemoryEstimate {
+                    name,
+                    entries,
+                    bytes,
+                }
+            })
+            .collect()
+    }
+
+    /// Samples the estimators every `interval` seconds into the metrics and the debug log,
+    /// 0 is disable.
+    pub fn track(&self, interval: u64) {
+        if interval == 0 {
+            info!("track memory estimators: disable");
+            return;
+        }
+        info!("track memory estimators: enable");
+        let estimators = self.clone();
+        let wait_secs = time::Duration::from_secs(interval);
+        if let Err(err) = thread::Builder::new()
+            .name("MemoryEstimator".to_string())
+            .spawn(move || loop {
+                let estimates = estimators.estimate();
+                for estimate in &estimates {
+                    ESTIMATED_ENTRIES
+                        .with_label_values(&[&estimate.name])
+                        .set(estimate.entries as i64);
+                    ESTIMATED_BYTES
+                        .with_label_values(&[&estimate.name])
+                        .set(estimate.bytes as i64);
+                }
+                debug!(
+                    "MemoryEstimates {{ {} }}",
+                    estimates
+                        .iter()
+                        .map(|estimate| format!(
+                            "{}: {} entries, {}",
+                            estimate.name,
+                            estimate.entries,
+                            HumanReadableSize::from(estimate.bytes as u64)
+                        ))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
+                thread::sleep(wait_secs);
+            })
+        {
+            error!(
+                "failed to spawn the thread to track memory estimators: {}",
+                err
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_estimators() {
+        let estimators = MemoryEstimators::new();
+        estimators.register("tx_pool", || (2, 1024));
+        estimators.register("peer_store", || (10, 2000));
+        estimators.register("tx_pool", || (3, 2048));
+
+        assert_eq!(
+            estimators.estimate(),
+            vec![
+                MemoryEstimate {
+                    name: "peer_store".to_string(),
+                    entries: 10,
+                    bytes: 2000,
+                },
+                MemoryEstimate {
+                    name: "tx_pool".to_string(),
+                    entries: 3,
+                    bytes: 2048,
+                },
+            ]
+        );
+    }
+}
@@ -32,12 +32,28 @@ mod process {
     ) {
         info!("track current process: unsupported");
     }
+
+    pub fn allocator_stats() -> Option<crate::AllocatorStats> {
+        None
+    }
 }
+mod estimator;
+mod metrics;
 pub mod rocksdb;
 pub mod utils;
 
+pub use estimator::{MemoryEstimate, MemoryEstimators};
 pub use jemalloc::jemalloc_profiling_dump;
-pub use process::track_current_process;
+pub use process::{allocator_stats, track_current_process};
+
+/// The jemalloc stats in bytes.
+#[derive(Clone, Copy, Debug)]
+pub struct AllocatorStats {
+    /// Bytes allocated by the application.
+    pub allocated: u64,
+    /// Bytes in physically resident data pages mapped by the allocator.
+    pub resident: u64,
+}
 
 pub fn track_current_process_simple(interval: u64) {
     track_current_process::<rocksdb::DummyRocksDB>(interval, None);
@@ -0,0 +1,32 @@
+use ckb_metrics::{register_gauge, register_gauge_vec, Gauge, MetricVec, Subsystem};
+use lazy_static::lazy_static;
+
+lazy_static! {
+    pub(crate) static ref ESTIMATED_ENTRIES: MetricVec<Gauge> = register_gauge_vec(
+        Subsystem::Memory,
+        "ckb_memory_estimated_entries",
+        "Entries of the structures which may grow, by structure",
+        &["structure"],
+    );
+    pub(crate) static ref ESTIMATED_BYTES: MetricVec<Gauge> = register_gauge_vec(
+        Subsystem::Memory,
+        "ckb_memory_estimated_bytes",
+        "Estimated bytes of the structures which may grow, by structure",
+        &["structure"],
+    );
+    pub(crate) static ref JEMALLOC_ALLOCATED_BYTES: Gauge = register_gauge(
+        Subsystem::Memory,
+        "ckb_memory_jemalloc_allocated_bytes",
+        "Bytes allocated by the application",
+    );
+    pub(crate) static ref JEMALLOC_RESIDENT_BYTES: Gauge = register_gauge(
+        Subsystem::Memory,
+        "ckb_memory_jemalloc_resident_bytes",
+        "Bytes in physically resident data pages mapped by the allocator",
+    );
+    pub(crate) static ref PROCESS_RSS_BYTES: Gauge = register_gauge(
+        Subsystem::Memory,
+        "ckb_memory_process_rss_bytes",
+        "Resident set size of the process",
+    );
+}
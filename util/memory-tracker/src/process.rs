@@ -5,7 +5,8 @@ use futures::executor::block_on;
 use heim::units::information::byte;
 use jemalloc_ctl::{epoch, stats};
 
-use crate::{rocksdb::TrackRocksDBMemory, utils::HumanReadableSize};
+use crate::metrics::{JEMALLOC_ALLOCATED_BYTES, JEMALLOC_RESIDENT_BYTES, PROCESS_RSS_BYTES};
+use crate::{rocksdb::TrackRocksDBMemory, utils::HumanReadableSize, AllocatorStats};
 
 macro_rules! je_mib {
     ($key:ty) => {
@@ -65,6 +66,14 @@ pub fn track_current_process<Tracker: 'static + TrackRocksDBMemory + Sync + Send
                             return;
                         }
                         if let Ok(memory) = block_on(process.memory()) {
+                            PROCESS_RSS_BYTES.set(memory.rss().get::<byte>() as i64);
+                            if let (Ok(allocated), Ok(resident)) =
+                                (allocated.read(), resident.read())
+                            {
+                                JEMALLOC_ALLOCATED_BYTES.set(allocated as i64);
+                                JEMALLOC_RESIDENT_BYTES.set(resident as i64);
+                            }
+
                             // Resident set size, amount of non-swapped physical memory.
                             let rss: HumanReadableSize = memory.rss().get::<byte>().into();
                             // Virtual memory size, total amount of memory.
@@ -145,3 +154,12 @@ pub fn track_current_process<Tracker: 'static + TrackRocksDBMemory + Sync + Send
         }
     }
 }
+
+/// Reads the jemalloc stats, which are refreshed first.
+pub fn allocator_stats() -> Option<AllocatorStats> {
+    epoch::advance().ok()?;
+    Some(AllocatorStats {
+        allocated: stats::allocated::read().ok()? as u64,
+        resident: stats::resident::read().ok()? as u64,
+    })
+}
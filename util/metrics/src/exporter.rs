@@ -0,0 +1,60 @@
+use crate::registry;
+use ckb_logger::{debug, info};
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+const MAX_REQUEST_SIZE: usize = 8 * 1024;
+
+/// Serves `GET /metrics` for the Prometheus scraper, connections are handled one by one as
+/// scraping is infrequent.
+pub(crate) fn start(listen_address: &str) -> io::Result<()> {
+    let listener = TcpListener::bind(listen_address)?;
+    info!("metrics exporter listens on {}", listener.local_addr()?);
+    thread::Builder::new()
+        .name("MetricsExporter".to_string())
+        .spawn(move || {
+            for stream in listener.incoming() {
+                if let Err(err) = stream.and_then(handle) {
+                    debug!("metrics exporter connection error: {}", err);
+                }
+            }
+        })?;
+    Ok(())
+}
+
+fn handle(mut stream: TcpStream) -> io::Result<()> {
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    stream.set_write_timeout(Some(REQUEST_TIMEOUT))?;
+
+    let mut request = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+        let n = stream.read(&mut buf)?;
+        if n == 0 || request.len() + n > MAX_REQUEST_SIZE {
+            break;
+        }
+        request.extend_from_slice(&buf[..n]);
+    }
+
+    let request = String::from_utf8_lossy(&request);
+    let mut request_line = request
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .split_whitespace();
+    let (status, body) = match (request_line.next(), request_line.next()) {
+        (Some("GET"), Some("/metrics")) => ("200 OK", registry::render()),
+        _ => ("404 Not Found", "Not Found\n".to_string()),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
@@ -0,0 +1,155 @@
+//! Metrics facade of CKB.
+//!
+//! Modules register their counters, gauges and histograms once, usually in a `lazy_static`, and
+//! update them on the hot path. Updates are cheap atomic operations, and no-ops until `init`
+//! enables the subsystem of the metric. The Prometheus exporter serves all the enabled metrics
+//! in the text exposition format on `GET /metrics`.
+mod exporter;
+mod metric;
+mod registry;
+
+use serde::{Deserialize, Serialize};
+use std::io;
+
+pub use metric::{Counter, Gauge, Histogram, MetricVec};
+
+/// Histogram buckets in seconds, used when the config doesn't set them.
+pub const DEFAULT_BUCKETS: &[f64] = &[
+    0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Subsystem {
+    Network,
+    TxPool,
+    Chain,
+    Store,
+    Rpc,
+    Memory,
+}
+
+impl Subsystem {
+    pub const ALL: [Subsystem; 6] = [
+        Subsystem::Network,
+        Subsystem::TxPool,
+        Subsystem::Chain,
+        Subsystem::Store,
+        Subsystem::Rpc,
+        Subsystem::Memory,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Subsystem::Network => "network",
+            Subsystem::TxPool => "tx_pool",
+            Subsystem::Chain => "chain",
+            Subsystem::Store => "store",
+            Subsystem::Rpc => "rpc",
+            Subsystem::Memory => "memory",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Config {
+    /// Address of the Prometheus exporter, metrics are disabled when absent
+    pub listen_address: Option<String>,
+    /// Upper bounds of the histogram buckets in seconds, `DEFAULT_BUCKETS` when absent
+    pub buckets: Option<Vec<f64>>,
+    /// Subsystems to collect, all when absent
+    pub subsystems: Option<Vec<Subsystem>>,
+}
+
+/// Enables the configured subsystems and starts the exporter.
+///
+/// Call it before the services start, histograms registered earlier keep the default buckets.
+pub fn init(config: &Config) -> io::Result<()> {
+    let listen_address = match config.listen_address.as_ref() {
+        Some(listen_address) => listen_address,
+        None => return Ok(()),
+    };
+    if let Some(buckets) = config.buckets.as_ref() {
+        registry::set_buckets(buckets.clone());
+    }
+    let subsystems = config
+        .subsystems
+        .clone()
+        .unwrap_or_else(|| Subsystem::ALL.to_vec());
+    for subsystem in subsystems {
+        registry::enable(subsystem);
+    }
+    exporter::start(listen_address)
+}
+
+/// Enables the subsystem without starting the exporter, for the tools which read the metrics in
+/// process, e.g. `ckb replay`.
+pub fn enable(subsystem: Subsystem) {
+    registry::enable(subsystem);
+}
+
+pub fn register_counter(subsystem: Subsystem, name: &'static str, help: &'static str) -> Counter {
+    register_counter_vec(subsystem, name, help, &[]).with_label_values(&[])
+}
+
+pub fn register_counter_vec(
+    subsystem: Subsystem,
+    name: &'static str,
+    help: &'static str,
+    label_names: &[&'static str],
+) -> MetricVec<Counter> {
+    registry::register(subsystem, name, help, label_names, Counter::new)
+}
+
+pub fn register_gauge(subsystem: Subsystem, name: &'static str, help: &'static str) -> Gauge {
+    register_gauge_vec(subsystem, name, help, &[]).with_label_values(&[])
+}
+
+pub fn register_gauge_vec(
+    subsystem: Subsystem,
+    name: &'static str,
+    help: &'static str,
+    label_names: &[&'static str],
+) -> MetricVec<Gauge> {
+    registry::register(subsystem, name, help, label_names, Gauge::new)
+}
+
+pub fn register_histogram(
+    subsystem: Subsystem,
+    name: &'static str,
+    help: &'static str,
+) -> Histogram {
+    register_histogram_vec(subsystem, name, help, &[]).with_label_values(&[])
+}
+
+pub fn register_histogram_vec(
+    subsystem: Subsystem,
+    name: &'static str,
+    help: &'static str,
+    label_names: &[&'static str],
+) -> MetricVec<Histogram> {
+    let buckets = registry::buckets();
+    registry::register(subsystem, name, help, label_names, move |enabled| {
+        Histogram::new(enabled, buckets.clone())
+    })
+}
+
+/// Registers a histogram of the values other than seconds, e.g. fee rates, whose buckets don't
+/// follow the config.
+pub fn register_histogram_with_buckets(
+    subsystem: Subsystem,
+    name: &'static str,
+    help: &'static str,
+    buckets: &[f64],
+) -> Histogram {
+    let buckets = buckets.to_vec();
+    registry::register(subsystem, name, help, &[], move |enabled| {
+        Histogram::new(enabled, buckets.clone())
+    })
+    .with_label_values(&[])
+}
+
+/// Renders the enabled metrics in the Prometheus text exposition format.
+pub fn gather() -> String {
+    registry::render()
+}
@@ -0,0 +1,294 @@
+use crate::Subsystem;
+use ckb_util::Mutex;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+pub trait Metric: Clone + Send + Sync + 'static {
+    const KIND: &'static str;
+
+    /// Appends the samples of the series, `labels` are the rendered label pairs.
+    fn render(&self, name: &str, labels: &str, out: &mut String);
+}
+
+fn braced(labels: &str) -> String {
+    if labels.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", labels)
+    }
+}
+
+/// A value which only goes up.
+#[derive(Clone)]
+pub struct Counter {
+    enabled: &'static AtomicBool,
+    value: Arc<AtomicU64>,
+}
+
+impl Counter {
+    pub(crate) fn new(enabled: &'static AtomicBool) -> Self {
+        Counter {
+            enabled,
+            value: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    pub fn inc(&self) {
+        self.inc_by(1);
+    }
+
+    pub fn inc_by(&self, value: u64) {
+        if self.enabled.load(Ordering::Relaxed) {
+            self.value.fetch_add(value, Ordering::Relaxed);
+        }
+    }
+
+    pub fn get(&self) -> u64 {
+        self.value.load(Ordering::Relaxed)
+    }
+}
+
+impl Metric for Counter {
+    const KIND: &'static str = "counter";
+
+    fn render(&self, name: &str, labels: &str, out: &mut String) {
+        let _ = writeln!(out, "{}{} {}", name, braced(labels), self.get());
+    }
+}
+
+/// A value which goes up and down.
+#[derive(Clone)]
+pub struct Gauge {
+    enabled: &'static AtomicBool,
+    value: Arc<AtomicI64>,
+}
+
+impl Gauge {
+    pub(crate) fn new(enabled: &'static AtomicBool) -> Self {
+        Gauge {
+            enabled,
+            value: Arc::new(AtomicI64::new(0)),
+        }
+    }
+
+    pub fn set(&self, value: i64) {
+        if self.enabled.load(Ordering::Relaxed) {
+            self.value.store(value, Ordering::Relaxed);
+        }
+    }
+
+    pub fn add(&self, value: i64) {
+        if self.enabled.load(Ordering::Relaxed) {
+            self.value.fetch_add(value, Ordering::Relaxed);
+        }
+    }
+
+    pub fn inc(&self) {
+        self.add(1);
+    }
+
+    pub fn dec(&self) {
+        self.add(-1);
+    }
+
+    pub fn get(&self) -> i64 {
+        self.value.load(Ordering::Relaxed)
+    }
+}
+
+impl Metric for Gauge {
+    const KIND: &'static str = "gauge";
+
+    fn render(&self, name: &str, labels: &str, out: &mut String) {
+        let _ = writeln!(out, "{}{} {}", name, braced(labels), self.get());
+    }
+}
+
+struct HistogramInner {
+    bounds: Vec<f64>,
+    // the last one counts the observations beyond all the bounds
+    buckets: Vec<AtomicU64>,
+    count: AtomicU64,
+    sum: AtomicU64,
+}
+
+/// Counts the observations in buckets, e.g. how long a block verification takes.
+#[derive(Clone)]
+pub struct Histogram {
+    enabled: &'static AtomicBool,
+    inner: Arc<HistogramInner>,
+}
+
+impl Histogram {
+    pub(crate) fn new(enabled: &'static AtomicBool, bounds: Vec<f64>) -> Self {
+        let buckets = (0..=bounds.len()).map(|_| AtomicU64::new(0)).collect();
+        Histogram {
+            enabled,
+            inner: Arc::new(HistogramInner {
+                bounds,
+                buckets,
+                count: AtomicU64::new(0),
+                sum: AtomicU64::new(0f64.to_bits()),
+            }),
+        }
+    }
+
+    pub fn observe(&self, value: f64) {
+        if !self.enabled.load(Ordering::Relaxed) {
+            return;
+        }
+        let inner = &self.inner;
+        let index = inner
+            .bounds
+            .iter()
+            .position(|bound| value <= *bound)
+            .unwrap_or_else(|| inner.bounds.len());
+        inner.buckets[index].fetch_add(1, Ordering::Relaxed);
+        inner.count.fetch_add(1, Ordering::Relaxed);
+        let mut sum = inner.sum.load(Ordering::Relaxed);
+        loop {
+            let new_sum = (f64::from_bits(sum) + value).to_bits();
+            let current = inner.sum.compare_and_swap(sum, new_sum, Ordering::Relaxed);
+            if current == sum {
+                break;
+            }
+            sum = current;
+        }
+    }
+
+    pub fn observe_duration(&self, duration: Duration) {
+        self.observe(duration.as_secs_f64());
+    }
+
+    pub fn count(&self) -> u64 {
+        self.inner.count.load(Ordering::Relaxed)
+    }
+
+    pub fn sum(&self) -> f64 {
+        f64::from_bits(self.inner.sum.load(Ordering::Relaxed))
+    }
+}
+
+impl Metric for Histogram {
+    const KIND: &'static str = "histogram";
+
+    fn render(&self, name: &str, labels: &str, out: &mut String) {
+        let separator = if labels.is_empty() { "" } else { "," };
+        let mut cumulative = 0;
+        for (bound, bucket) in self.inner.bounds.iter().zip(self.inner.buckets.iter()) {
+            cumulative += bucket.load(Ordering::Relaxed);
+            let _ = writeln!(
+                out,
+                "{}_bucket{{{}{}le=\"{}\"}} {}",
+                name, labels, separator, bound, cumulative
+            );
+        }
+        let _ = writeln!(
+            out,
+            "{}_bucket{{{}{}le=\"+Inf\"}} {}",
+            name,
+            labels,
+            separator,
+            self.count()
+        );
+        let _ = writeln!(out, "{}_sum{} {}", name, braced(labels), self.sum());
+        let _ = writeln!(out, "{}_count{} {}", name, braced(labels), self.count());
+    }
+}
+
+struct MetricVecInner<T> {
+    subsystem: Subsystem,
+    name: &'static str,
+    help: &'static str,
+    label_names: Vec<&'static str>,
+    enabled: &'static AtomicBool,
+    new_metric: Box<dyn Fn(&'static AtomicBool) -> T + Send + Sync>,
+    series: Mutex<BTreeMap<Vec<String>, T>>,
+}
+
+/// A metric family partitioned by labels, e.g. RPC calls by method.
+#[derive(Clone)]
+pub struct MetricVec<T> {
+    inner: Arc<MetricVecInner<T>>,
+}
+
+impl<T: Metric> MetricVec<T> {
+    pub(crate) fn new<F>(
+        subsystem: Subsystem,
+        name: &'static str,
+        help: &'static str,
+        label_names: &[&'static str],
+        enabled: &'static AtomicBool,
+        new_metric: F,
+    ) -> Self
+    where
+        F: Fn(&'static AtomicBool) -> T + Send + Sync + 'static,
+    {
+        MetricVec {
+            inner: Arc::new(MetricVecInner {
+                subsystem,
+                name,
+                help,
+                label_names: label_names.to_vec(),
+                enabled,
+                new_metric: Box::new(new_metric),
+                series: Mutex::new(BTreeMap::new()),
+            }),
+        }
+    }
+
+    /// Returns the series of the label values, which are in the order of the label names.
+    pub fn with_label_values(&self, values: &[&str]) -> T {
+        assert_eq!(
+            values.len(),
+            self.inner.label_names.len(),
+            "metric {} label values mismatch",
+            self.inner.name
+        );
+        let key = values.iter().map(|value| (*value).to_string()).collect();
+        self.inner
+            .series
+            .lock()
+            .entry(key)
+            .or_insert_with(|| (self.inner.new_metric)(self.inner.enabled))
+            .clone()
+    }
+}
+
+pub(crate) trait Family: Send + Sync {
+    fn subsystem(&self) -> Subsystem;
+
+    fn render(&self, out: &mut String);
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+impl<T: Metric> Family for MetricVec<T> {
+    fn subsystem(&self) -> Subsystem {
+        self.inner.subsystem
+    }
+
+    fn render(&self, out: &mut String) {
+        let inner = &self.inner;
+        let _ = writeln!(out, "# HELP {} {}", inner.name, inner.help);
+        let _ = writeln!(out, "# TYPE {} {}", inner.name, T::KIND);
+        for (values, metric) in inner.series.lock().iter() {
+            let labels = inner
+                .label_names
+                .iter()
+                .zip(values.iter())
+                .map(|(name, value)| format!("{}=\"{}\"", name, escape_label_value(value)))
+                .collect::<Vec<_>>()
+                .join(",");
+            metric.render(inner.name, &labels, out);
+        }
+    }
+}
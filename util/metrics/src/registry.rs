@@ -0,0 +1,135 @@
+use crate::metric::{Family, Metric, MetricVec};
+use crate::{Subsystem, DEFAULT_BUCKETS};
+use ckb_util::{Mutex, RwLock};
+use lazy_static::lazy_static;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// indexed by `Subsystem`, one for each of `Subsystem::ALL`
+static ENABLED: [AtomicBool; 6] = [
+    AtomicBool::new(false),
+    AtomicBool::new(false),
+    AtomicBool::new(false),
+    AtomicBool::new(false),
+    AtomicBool::new(false),
+    AtomicBool::new(false),
+];
+
+lazy_static! {
+    static ref BUCKETS: RwLock<Vec<f64>> = RwLock::new(DEFAULT_BUCKETS.to_vec());
+    static ref FAMILIES: Mutex<BTreeMap<&'static str, Box<dyn Family>>> =
+        Mutex::new(BTreeMap::new());
+}
+
+fn flag(subsystem: Subsystem) -> &'static AtomicBool {
+    &ENABLED[subsystem as usize]
+}
+
+pub(crate) fn enable(subsystem: Subsystem) {
+    flag(subsystem).store(true, Ordering::Relaxed);
+}
+
+pub(crate) fn set_buckets(mut buckets: Vec<f64>) {
+    buckets.retain(|bound| bound.is_finite());
+    buckets.sort_by(|a, b| a.partial_cmp(b).expect("finite bounds"));
+    buckets.dedup();
+    *BUCKETS.write() = buckets;
+}
+
+pub(crate) fn buckets() -> Vec<f64> {
+    BUCKETS.read().clone()
+}
+
+pub(crate) fn register<T, F>(
+    subsystem: Subsystem,
+    name: &'static str,
+    help: &'static str,
+    label_names: &[&'static str],
+    new_metric: F,
+) -> MetricVec<T>
+where
+    T: Metric,
+    F: Fn(&'static AtomicBool) -> T + Send + Sync + 'static,
+{
+    let metric_vec = MetricVec::new(
+        subsystem,
+        name,
+        help,
+        label_names,
+        flag(subsystem),
+        new_metric,
+    );
+    let mut families = FAMILIES.lock();
+    assert!(
+        !families.contains_key(name),
+        "metric {} is registered twice",
+        name
+    );
+    families.insert(name, Box::new(metric_vec.clone()));
+    metric_vec
+}
+
+pub(crate) fn render() -> String {
+    let mut out = String::new();
+    for family in FAMILIES.lock().values() {
+        if flag(family.subsystem()).load(Ordering::Relaxed) {
+            family.render(&mut out);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        register_counter_vec, register_gauge, register_histogram, register_histogram_with_buckets,
+    };
+
+    #[test]
+    fn render_enabled_metrics() {
+        let disabled = register_gauge(
+            Subsystem::Store,
+            "test_disabled_gauge",
+            "gauge of a disabled subsystem",
+        );
+        disabled.set(1);
+        assert_eq!(disabled.get(), 0);
+
+        enable(Subsystem::Rpc);
+        let calls = register_counter_vec(
+            Subsystem::Rpc,
+            "test_calls_total",
+            "calls by method",
+            &["method"],
+        );
+        calls.with_label_values(&["get_tip"]).inc();
+        calls.with_label_values(&["get_tip"]).inc_by(2);
+        calls.with_label_values(&["say \"hi\""]).inc();
+        let duration = register_histogram(Subsystem::Rpc, "test_duration_seconds", "durations");
+        duration.observe(0.003);
+        duration.observe(20.0);
+
+        let text = render();
+        assert!(!text.contains("test_disabled_gauge"));
+        assert!(text.contains(
+            "# HELP test_calls_total calls by method\n\
+             # TYPE test_calls_total counter\n\
+             test_calls_total{method=\"get_tip\"} 3\n\
+             test_calls_total{method=\"say \\\"hi\\\"\"} 1\n"
+        ));
+        assert!(text.contains("test_duration_seconds_bucket{le=\"0.001\"} 0\n"));
+        assert!(text.contains("test_duration_seconds_bucket{le=\"0.005\"} 1\n"));
+        assert!(text.contains("test_duration_seconds_bucket{le=\"10\"} 1\n"));
+        assert!(text.contains("test_duration_seconds_bucket{le=\"+Inf\"} 2\n"));
+        assert!(text.contains("test_duration_seconds_sum 20.003\n"));
+        assert!(text.contains("test_duration_seconds_count 2\n"));
+
+        let depth =
+            register_histogram_with_buckets(Subsystem::Rpc, "test_depth", "depths", &[1.0, 10.0]);
+        depth.observe(5.0);
+        let text = render();
+        assert!(text.contains("test_depth_bucket{le=\"1\"} 0\n"));
+        assert!(text.contains("test_depth_bucket{le=\"10\"} 1\n"));
+    }
+}
@@ -13,6 +13,7 @@ where
         .spawn(move || {
             let mut runtime = runtime::Builder::new()
                 .threaded_scheduler()
+                .enable_time()
                 .thread_name("GlobalRuntime-")
                 .build()
                 .expect("Global tokio runtime init");
@@ -6,6 +6,10 @@ use std::sync::Arc;
 use std::thread::JoinHandle;
 use tokio::sync::oneshot as tokio_oneshot;
 
+mod shutdown;
+
+pub use shutdown::{ShutdownCoordinator, ShutdownStage};
+
 #[derive(Debug)]
 pub enum SignalSender {
     Future(oneshot::Sender<()>),
@@ -56,18 +60,35 @@ impl<T> StopHandler<T> {
         }
     }
 
+    /// Stops the thread when this is the last handler, does nothing if it has been stopped by
+    /// `stop`.
     pub fn try_send(&mut self) {
         let inner = self
             .inner
             .take()
             .expect("Stop signal can only be sent once");
         if let Ok(lock) = Arc::try_unwrap(inner) {
-            let handler = lock.lock().take().expect("Handler can only be taken once");
-            let Handler { signal, thread } = handler;
-            signal.send();
-            if let Err(e) = thread.join() {
-                error!("handler thread join error {:?}", e);
-            };
+            if let Some(handler) = lock.lock().take() {
+                handler.stop();
+            }
+        };
+    }
+
+    /// Stops the thread regardless of the other handlers, only the first call takes effect.
+    pub fn stop(&self) {
+        let handler = self.inner.as_ref().and_then(|inner| inner.lock().take());
+        if let Some(handler) = handler {
+            handler.stop();
+        }
+    }
+}
+
+impl<T> Handler<T> {
+    fn stop(self) {
+        let Handler { signal, thread } = self;
+        signal.send();
+        if let Err(e) = thread.join() {
+            error!("handler thread join error {:?}", e);
         };
     }
 }
@@ -0,0 +1,198 @@
+use crate::StopHandler;
+use ckb_logger::{error, info, warn};
+use crossbeam_channel::{bounded, RecvTimeoutError};
+use std::collections::BTreeMap;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The stages of the shutdown, in the order they are stopped. A service only depends on the
+/// services of the later stages, e.g. the RPC submits blocks to the chain, so it stops first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ShutdownStage {
+    Rpc,
+    Sync,
+    TxPool,
+    Chain,
+    Network,
+    Store,
+}
+
+impl ShutdownStage {
+    /// The earlier stages whose services use the services of this stage, this stage is skipped
+    /// if any of them is not stopped, rather than pulling the services away under them.
+    fn users(self) -> &'static [ShutdownStage] {
+        match self {
+            ShutdownStage::Rpc => &[],
+            ShutdownStage::Sync => &[ShutdownStage::Rpc],
+            ShutdownStage::TxPool => &[ShutdownStage::Rpc, ShutdownStage::Sync],
+            ShutdownStage::Chain => &[ShutdownStage::Rpc, ShutdownStage::Sync],
+            ShutdownStage::Network => &[ShutdownStage::Rpc, ShutdownStage::Sync],
+            ShutdownStage::Store => &[ShutdownStage::Chain],
+        }
+    }
+}
+
+type StopFn = Box<dyn FnOnce() + Send>;
+
+/// Stops the registered services stage by stage.
+///
+/// The services of a stage are stopped one by one in the registration order. A stage which
+/// does not finish in time is logged and left behind, so a stuck service can not block the
+/// later stages, such as flushing the store. The stages used by a stage left behind are
+/// skipped, see `ShutdownStage::users`.
+pub struct ShutdownCoordinator {
+    stage_timeout: Duration,
+    stages: BTreeMap<ShutdownStage, Vec<(String, StopFn)>>,
+}
+
+impl ShutdownCoordinator {
+    pub fn new(stage_timeout: Duration) -> Self {
+        ShutdownCoordinator {
+            stage_timeout,
+            stages: BTreeMap::new(),
+        }
+    }
+
+    pub fn register<F>(&mut self, stage: ShutdownStage, name: &str, stop: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.stages
+            .entry(stage)
+            .or_default()
+            .push((name.to_string(), Box::new(stop)));
+    }
+
+    /// Registers a service thread, which is stopped even if its controller is still held by
+    /// others.
+    pub fn register_handler<T>(&mut self, stage: ShutdownStage, name: &str, handler: StopHandler<T>)
+    where
+        T: Send + 'static,
+    {
+        self.register(stage, name, move || handler.stop());
+    }
+
+    /// Runs the stages in order, returns false if any stage timed out or panicked.
+    pub fn shutdown(self) -> bool {
+        let stage_timeout = self.stage_timeout;
+        let mut clean = true;
+        let mut failed = Vec::new();
+        for (stage, services) in self.stages {
+            if let Some(user) = stage.users().iter().find(|user| failed.contains(*user)) {
+                warn!(
+                    "shutdown stage {:?}: skip it since stage {:?} is not stopped",
+                    stage, user
+                );
+                failed.push(stage);
+                continue;
+            }
+            let names = services
+                .iter()
+                .map(|(name, _)| name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ");
+            info!("shutdown stage {:?}: stopping {}", stage, names);
+
+            let now = Instant::now();
+            let (sender, receiver) = bounded(1);
+            let spawned = thread::Builder::new()
+                .name(format!("Shutdown{:?}", stage))
+                .spawn(move || {
+                    for (name, stop) in services {
+                        stop();
+                        info!("shutdown stage {:?}: {} stopped", stage, name);
+                    }
+                    let _ = sender.send(());
+                });
+            if let Err(err) = spawned {
+                error!("shutdown stage {:?}: spawn thread error {}", stage, err);
+                clean = false;
+                failed.push(stage);
+                continue;
+            }
+
+            match receiver.recv_timeout(stage_timeout) {
+                Ok(()) => info!(
+                    "shutdown stage {:?}: finished in {:?}",
+                    stage,
+                    now.elapsed()
+                ),
+                Err(RecvTimeoutError::Timeout) => {
+                    warn!(
+                        "shutdown stage {:?}: not finished in {:?}, skip it",
+                        stage, stage_timeout
+                    );
+                    clean = false;
+                    failed.push(stage);
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    error!("shutdown stage {:?}: panicked", stage);
+                    clean = false;
+                    failed.push(stage);
+                }
+            }
+        }
+        clean
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_shutdown_in_stage_order() {
+        let stopped = Arc::new(Mutex::new(Vec::new()));
+        let mut coordinator = ShutdownCoordinator::new(Duration::from_secs(5));
+        for (stage, name) in &[
+            (ShutdownStage::Store, "store"),
+            (ShutdownStage::Chain, "chain"),
+            (ShutdownStage::Rpc, "rpc"),
+            (ShutdownStage::Chain, "notify"),
+        ] {
+            let stopped = Arc::clone(&stopped);
+            coordinator.register(*stage, name, move || stopped.lock().unwrap().push(*name));
+        }
+        assert!(coordinator.shutdown());
+        assert_eq!(
+            *stopped.lock().unwrap(),
+            vec!["rpc", "chain", "notify", "store"]
+        );
+    }
+
+    #[test]
+    fn test_shutdown_skips_stuck_stage() {
+        let stopped = Arc::new(Mutex::new(Vec::new()));
+        let mut coordinator = ShutdownCoordinator::new(Duration::from_millis(100));
+        coordinator.register(ShutdownStage::TxPool, "tx-pool", || {
+            thread::sleep(Duration::from_secs(2))
+        });
+        let store_stopped = Arc::clone(&stopped);
+        coordinator.register(ShutdownStage::Store, "store", move || {
+            store_stopped.lock().unwrap().push("store")
+        });
+        coordinator.register(ShutdownStage::Network, "network", || panic!("stop network"));
+        assert!(!coordinator.shutdown());
+        assert_eq!(*stopped.lock().unwrap(), vec!["store"]);
+    }
+
+    #[test]
+    fn test_shutdown_skips_dependent_stages() {
+        let stopped = Arc::new(Mutex::new(Vec::new()));
+        let mut coordinator = ShutdownCoordinator::new(Duration::from_millis(100));
+        coordinator.register(ShutdownStage::Chain, "chain", || {
+            thread::sleep(Duration::from_secs(2))
+        });
+        for (stage, name) in &[
+            (ShutdownStage::Network, "network"),
+            (ShutdownStage::Store, "store"),
+        ] {
+            let stopped = Arc::clone(&stopped);
+            coordinator.register(*stage, name, move || stopped.lock().unwrap().push(*name));
+        }
+        assert!(!coordinator.shutdown());
+        // the chain may still write the store
+        assert_eq!(*stopped.lock().unwrap(), vec!["network"]);
+    }
+}
@@ -0,0 +1,330 @@
+//! Golomb-coded set filters of blocks, in the spirit of BIP158.
+//!
+//! Every filter commits to a set of 32-byte items (the lock and type script hashes touched by
+//! the block). Items are hashed with SipHash-2-4, keyed by the first 16 bytes of the block hash,
+//! into the range `[0, N * M)`, sorted, and the differences between neighbours are written as
+//! Golomb-Rice codes with parameter `P`. The serialized filter starts with `N` as a compact size.
+//!
+//! Like the BIP157 filter headers, the filter hashes chain the filters of the main chain, so a
+//! light client checks the filters from a peer against the filter hashes it trusts.
+use ckb_hash::{blake2b_256, new_blake2b};
+use siphasher::sip::SipHasher24;
+use std::collections::BTreeSet;
+use std::convert::TryInto;
+use std::hash::Hasher;
+
+use crate::{bytes::Bytes, packed::Byte32, prelude::*};
+
+/// Golomb-Rice coding parameter, the remainder of every delta takes `P` bits.
+pub const BLOCK_FILTER_P: u8 = 19;
+/// Inverse false positive rate of a single item query.
+pub const BLOCK_FILTER_M: u64 = 784_931;
+
+// Upper bound of items in a filter, keeps `N * M` in u64
+const MAX_FILTER_ITEMS: u64 = u32::max_value() as u64;
+
+/// Builds the filter of a block which commits to `items`.
+///
+/// Duplicated items are only encoded once.
+pub fn build_block_filter(block_hash: &Byte32, items: &[Byte32]) -> Bytes {
+    let items: BTreeSet<&[u8]> = items.iter().map(|item| item.as_slice()).collect();
+    let n = items.len() as u64;
+    let mut filter = Vec::new();
+    write_compact_size(n, &mut filter);
+    if n == 0 {
+        return filter.into();
+    }
+
+    let hasher = FilterHasher::new(block_hash, n);
+    let mut values: Vec<u64> = items.into_iter().map(|item| hasher.hash(item)).collect();
+    values.sort();
+
+    let mut writer = BitWriter::new(filter);
+    let mut last = 0;
+    for value in values {
+        writer.write_golomb(value - last);
+        last = value;
+    }
+    writer.finish().into()
+}
+
+/// Chains the filter onto the filter hash of the parent block, which is zero for the genesis.
+///
+/// `blake2b(parent_block_filter_hash || blake2b(filter))`
+pub fn calc_block_filter_hash(parent_block_filter_hash: &Byte32, filter: &[u8]) -> Byte32 {
+    let mut ret = [0u8; 32];
+    let mut blake2b = new_blake2b();
+    blake2b.update(parent_block_filter_hash.as_slice());
+    blake2b.update(&blake2b_256(filter));
+    blake2b.finalize(&mut ret);
+    ret.pack()
+}
+
+/// Returns true when any of `items` may be in the filter.
+///
+/// False positives happen with probability `1 / M` per item, false negatives never happen.
+/// Malformed filters match nothing.
+pub fn block_filter_match_any(block_hash: &Byte32, filter: &[u8], items: &[Byte32]) -> bool {
+    let (n, header_size) = match read_compact_size(filter) {
+        Some(result) => result,
+        None => return false,
+    };
+    if n == 0 || n > MAX_FILTER_ITEMS || items.is_empty() {
+        return false;
+    }
+
+    let hasher = FilterHasher::new(block_hash, n);
+    let mut queries: Vec<u64> = items
+        .iter()
+        .map(|item| hasher.hash(item.as_slice()))
+        .collect();
+    queries.sort();
+
+    let mut reader = BitReader::new(&filter[header_size..]);
+    let mut queries = queries.into_iter().peekable();
+    let mut value = 0u64;
+    for _ in 0..n {
+        value = match reader
+            .read_golomb()
+            .and_then(|delta| value.checked_add(delta))
+        {
+            Some(value) => value,
+            None => return false,
+        };
+        while let Some(query) = queries.peek() {
+            if *query < value {
+                queries.next();
+            } else {
+                break;
+            }
+        }
+        match queries.peek() {
+            Some(query) if *query == value => return true,
+            Some(_) => {}
+            None => return false,
+        }
+    }
+    false
+}
+
+struct FilterHasher {
+    k0: u64,
+    k1: u64,
+    range: u64,
+}
+
+impl FilterHasher {
+    fn new(block_hash: &Byte32, n: u64) -> Self {
+        let key = block_hash.as_slice();
+        FilterHasher {
+            k0: u64::from_le_bytes(key[0..8].try_into().expect("checked length")),
+            k1: u64::from_le_bytes(key[8..16].try_into().expect("checked length")),
+            range: n * BLOCK_FILTER_M,
+        }
+    }
+
+    // Maps the SipHash of item uniformly into [0, N * M)
+    fn hash(&self, item: &[u8]) -> u64 {
+        let mut hasher = SipHasher24::new_with_keys(self.k0, self.k1);
+        hasher.write(item);
+        ((u128::from(hasher.finish()) * u128::from(self.range)) >> 64) as u64
+    }
+}
+
+struct BitWriter {
+    bytes: Vec<u8>,
+    used_bits: u8,
+}
+
+impl BitWriter {
+    fn new(bytes: Vec<u8>) -> Self {
+        BitWriter {
+            bytes,
+            used_bits: 0,
+        }
+    }
+
+    fn write_bit(&mut self, bit: bool) {
+        if self.used_bits == 0 {
+            self.bytes.push(0);
+        }
+        if bit {
+            let last = self.bytes.last_mut().expect("pushed above");
+            *last |= 0x80 >> self.used_bits;
+        }
+        self.used_bits = (self.used_bits + 1) % 8;
+    }
+
+    fn write_bits(&mut self, value: u64, count: u8) {
+        for i in (0..count).rev() {
+            self.write_bit((value >> i) & 1 == 1);
+        }
+    }
+
+    fn write_golomb(&mut self, value: u64) {
+        for _ in 0..(value >> BLOCK_FILTER_P) {
+            self.write_bit(true);
+        }
+        self.write_bit(false);
+        self.write_bits(value, BLOCK_FILTER_P);
+    }
+
+    fn finish(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        BitReader { bytes, position: 0 }
+    }
+
+    fn read_bit(&mut self) -> Option<bool> {
+        let byte = self.bytes.get(self.position / 8)?;
+        let bit = byte & (0x80 >> (self.position % 8)) != 0;
+        self.position += 1;
+        Some(bit)
+    }
+
+    fn read_bits(&mut self, count: u8) -> Option<u64> {
+        let mut value = 0;
+        for _ in 0..count {
+            value = (value << 1) | u64::from(self.read_bit()?);
+        }
+        Some(value)
+    }
+
+    fn read_golomb(&mut self) -> Option<u64> {
+        let mut quotient = 0u64;
+        while self.read_bit()? {
+            quotient += 1;
+        }
+        let remainder = self.read_bits(BLOCK_FILTER_P)?;
+        quotient
+            .checked_mul(1 << BLOCK_FILTER_P)
+            .map(|value| value | remainder)
+    }
+}
+
+fn write_compact_size(n: u64, out: &mut Vec<u8>) {
+    if n < 0xfd {
+        out.push(n as u8);
+    } else if n <= 0xffff {
+        out.push(0xfd);
+        out.extend_from_slice(&(n as u16).to_le_bytes());
+    } else if n <= 0xffff_ffff {
+        out.push(0xfe);
+        out.extend_from_slice(&(n as u32).to_le_bytes());
+    } else {
+        out.push(0xff);
+        out.extend_from_slice(&n.to_le_bytes());
+    }
+}
+
+fn read_compact_size(data: &[u8]) -> Option<(u64, usize)> {
+    match *data.first()? {
+        0xfd => data
+            .get(1..3)
+            .map(|bytes| (u64::from(u16::from_le_bytes([bytes[0], bytes[1]])), 3)),
+        0xfe => data.get(1..5).map(|bytes| {
+            let n = u32::from_le_bytes(bytes.try_into().expect("checked length"));
+            (u64::from(n), 5)
+        }),
+        0xff => data.get(1..9).map(|bytes| {
+            let n = u64::from_le_bytes(bytes.try_into().expect("checked length"));
+            (n, 9)
+        }),
+        n => Some((u64::from(n), 1)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn items(range: std::ops::Range<u8>) -> Vec<Byte32> {
+        range.map(|i| [i; 32].pack()).collect()
+    }
+
+    #[test]
+    fn test_empty_filter() {
+        let block_hash = [1u8; 32].pack();
+        let filter = build_block_filter(&block_hash, &[]);
+        assert_eq!(&filter[..], &[0u8][..]);
+        assert!(!block_filter_match_any(&block_hash, &filter, &items(0..10)));
+    }
+
+    #[test]
+    fn test_filter_matches_inserted_items() {
+        let block_hash = [2u8; 32].pack();
+        let mut inserted = items(0..100);
+        inserted.extend(items(0..10));
+        let filter = build_block_filter(&block_hash, &inserted);
+        assert_eq!(read_compact_size(&filter), Some((100, 1)));
+
+        for item in &inserted {
+            assert!(block_filter_match_any(
+                &block_hash,
+                &filter,
+                &[item.clone()]
+            ));
+        }
+        assert!(!block_filter_match_any(
+            &block_hash,
+            &filter,
+            &items(100..200)
+        ));
+
+        let mut mixed = items(150..200);
+        mixed.push(inserted[42].clone());
+        assert!(block_filter_match_any(&block_hash, &filter, &mixed));
+    }
+
+    #[test]
+    fn test_filter_is_keyed_by_block_hash() {
+        let filter = build_block_filter(&[3u8; 32].pack(), &items(0..50));
+        let other = build_block_filter(&[4u8; 32].pack(), &items(0..50));
+        assert_ne!(filter, other);
+    }
+
+    #[test]
+    fn test_malformed_filter() {
+        let block_hash = [5u8; 32].pack();
+        let filter = build_block_filter(&block_hash, &items(0..10));
+        assert!(!block_filter_match_any(
+            &block_hash,
+            &filter[..1],
+            &items(0..10)
+        ));
+        assert!(!block_filter_match_any(&block_hash, &[], &items(0..10)));
+    }
+
+    #[test]
+    fn test_compact_size() {
+        for n in &[0, 0xfc, 0xfd, 0xffff, 0x10000, 0xffff_ffff, 0x1_0000_0000] {
+            let mut out = Vec::new();
+            write_compact_size(*n, &mut out);
+            assert_eq!(read_compact_size(&out), Some((*n, out.len())));
+        }
+    }
+
+    #[test]
+    fn test_block_filter_hash_chains_parent() {
+        let filter = build_block_filter(&[3u8; 32].pack(), &items(0..10));
+        let genesis_filter_hash = calc_block_filter_hash(&Byte32::zero(), &filter);
+        assert_ne!(genesis_filter_hash, Byte32::zero());
+        assert_ne!(
+            calc_block_filter_hash(&genesis_filter_hash, &filter),
+            genesis_filter_hash
+        );
+        assert_ne!(
+            calc_block_filter_hash(&Byte32::zero(), &[0u8][..]),
+            genesis_filter_hash
+        );
+    }
+}
@@ -0,0 +1,331 @@
+//! Merkle Mountain Range, an append-only accumulator.
+//!
+//! Nodes are numbered by their positions in post-order, so the nodes of any prefix of the leaves
+//! never change when more leaves are appended. The root of every historical size can be
+//! recomputed from the same nodes, which makes it possible to prove that a leaf is included in
+//! any prefix of the range.
+//!
+//! The root is the peaks bagged from right to left, `merge(merge(p2, p1), p0)` for three peaks.
+use merkle_cbt::merkle_tree::Merge;
+
+use crate::{packed::Byte32, utilities::MergeByte32};
+
+/// Returns the number of nodes in a range of `leaves` leaves.
+pub fn mmr_size_from_leaves(leaves: u64) -> u64 {
+    2 * leaves - u64::from(leaves.count_ones())
+}
+
+/// Returns the position of the leaf at `index`.
+pub fn leaf_index_to_pos(index: u64) -> u64 {
+    mmr_size_from_leaves(index + 1) - u64::from((index + 1).trailing_zeros()) - 1
+}
+
+/// Merkle Mountain Range backed by a node getter.
+///
+/// New nodes are kept in memory, take them with `into_batch` and persist them at the returned
+/// start position. When any method returns `None` because a node is missing, the range is
+/// inconsistent and should be discarded.
+pub struct MMR<F> {
+    size: u64,
+    get_elem: F,
+    batch_start: u64,
+    batch: Vec<Byte32>,
+}
+
+impl<F> MMR<F>
+where
+    F: Fn(u64) -> Option<Byte32>,
+{
+    /// Opens a range which already has `size` nodes.
+    pub fn new(size: u64, get_elem: F) -> Self {
+        MMR {
+            size,
+            get_elem,
+            batch_start: size,
+            batch: Vec::new(),
+        }
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    fn find_elem(&self, pos: u64) -> Option<Byte32> {
+        if pos >= self.batch_start {
+            self.batch.get((pos - self.batch_start) as usize).cloned()
+        } else {
+            (self.get_elem)(pos)
+        }
+    }
+
+    /// Appends a leaf and returns its position.
+    pub fn push(&mut self, leaf: Byte32) -> Option<u64> {
+        let leaf_pos = self.size;
+        let mut pos = self.size;
+        let mut height = 0;
+        self.batch.push(leaf);
+        // Merge with the left siblings while the leaf completes subtrees
+        while pos_height_in_tree(pos + 1) > height {
+            pos += 1;
+            let left_pos = pos - parent_offset(height);
+            let right_pos = left_pos + sibling_offset(height);
+            let left = self.find_elem(left_pos)?;
+            let right = self.find_elem(right_pos)?;
+            self.batch.push(MergeByte32::merge(&left, &right));
+            height += 1;
+        }
+        self.size = pos + 1;
+        Some(leaf_pos)
+    }
+
+    /// Returns the root, `None` for an empty range.
+    pub fn get_root(&self) -> Option<Byte32> {
+        let peaks = get_peaks(self.size)
+            .into_iter()
+            .map(|pos| self.find_elem(pos))
+            .collect::<Option<Vec<_>>>()?;
+        bag_peaks(peaks)
+    }
+
+    /// Generates the proof that the leaf at `leaf_index` is included in the range.
+    pub fn gen_proof(&self, leaf_index: u64) -> Option<MMRProof> {
+        let mut pos = leaf_index_to_pos(leaf_index);
+        if pos >= self.size {
+            return None;
+        }
+        let peaks = get_peaks(self.size);
+        let mut items = Vec::new();
+        let mut height = 0;
+        while !peaks.contains(&pos) {
+            if pos_height_in_tree(pos + 1) > height {
+                items.push(self.find_elem(pos - sibling_offset(height))?);
+                pos += 1;
+            } else {
+                items.push(self.find_elem(pos + sibling_offset(height))?);
+                pos += parent_offset(height);
+            }
+            height += 1;
+        }
+        for peak_pos in peaks.iter().take_while(|peak_pos| **peak_pos != pos) {
+            items.push(self.find_elem(*peak_pos)?);
+        }
+        let right_peaks = peaks
+            .iter()
+            .skip_while(|peak_pos| **peak_pos != pos)
+            .skip(1)
+            .map(|peak_pos| self.find_elem(*peak_pos))
+            .collect::<Option<Vec<_>>>()?;
+        if let Some(right_peaks_root) = bag_peaks(right_peaks) {
+            items.push(right_peaks_root);
+        }
+        Some(MMRProof {
+            mmr_size: self.size,
+            items,
+        })
+    }
+
+    /// Takes the position of the first new node and the new nodes.
+    pub fn into_batch(self) -> (u64, Vec<Byte32>) {
+        (self.batch_start, self.batch)
+    }
+}
+
+/// Proof of a leaf in a range of `mmr_size` nodes.
+///
+/// Items are the siblings on the path from the leaf to its peak, then the peaks left to it,
+/// then the bagged peaks right to it if there are any.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MMRProof {
+    mmr_size: u64,
+    items: Vec<Byte32>,
+}
+
+impl MMRProof {
+    pub fn new(mmr_size: u64, items: Vec<Byte32>) -> Self {
+        MMRProof { mmr_size, items }
+    }
+
+    pub fn mmr_size(&self) -> u64 {
+        self.mmr_size
+    }
+
+    pub fn items(&self) -> &[Byte32] {
+        &self.items
+    }
+
+    /// Computes the root from the leaf at `leaf_index`, `None` if the proof is malformed.
+    pub fn calculate_root(&self, leaf_index: u64, leaf: &Byte32) -> Option<Byte32> {
+        let mut pos = leaf_index_to_pos(leaf_index);
+        if pos >= self.mmr_size {
+            return None;
+        }
+        let peaks = get_peaks(self.mmr_size);
+        let mut items = self.items.iter();
+        let mut hash = leaf.clone();
+        let mut height = 0;
+        while !peaks.contains(&pos) {
+            let sibling = items.next()?;
+            if pos_height_in_tree(pos + 1) > height {
+                hash = MergeByte32::merge(sibling, &hash);
+                pos += 1;
+            } else {
+                hash = MergeByte32::merge(&hash, sibling);
+                pos += parent_offset(height);
+            }
+            height += 1;
+        }
+        let mut peak_hashes = peaks
+            .iter()
+            .take_while(|peak_pos| **peak_pos != pos)
+            .map(|_| items.next().cloned())
+            .collect::<Option<Vec<_>>>()?;
+        peak_hashes.push(hash);
+        if peaks.last() != Some(&pos) {
+            peak_hashes.push(items.next()?.clone());
+        }
+        if items.next().is_some() {
+            return None;
+        }
+        bag_peaks(peak_hashes)
+    }
+
+    /// Returns true if the leaf at `leaf_index` is included in the range with `root`.
+    pub fn verify(&self, root: &Byte32, leaf_index: u64, leaf: &Byte32) -> bool {
+        self.calculate_root(leaf_index, leaf).as_ref() == Some(root)
+    }
+}
+
+fn bag_peaks(mut peaks: Vec<Byte32>) -> Option<Byte32> {
+    let mut root = peaks.pop()?;
+    while let Some(left) = peaks.pop() {
+        root = MergeByte32::merge(&root, &left);
+    }
+    Some(root)
+}
+
+fn parent_offset(height: u32) -> u64 {
+    2 << height
+}
+
+fn sibling_offset(height: u32) -> u64 {
+    (2 << height) - 1
+}
+
+// Height of the node at `pos`, leaves are at height 0
+fn pos_height_in_tree(pos: u64) -> u32 {
+    // In 1-based positions, the leftmost node of every height is all ones in binary, and
+    // jumping to the left subtree removes the most significant bit and adds back one.
+    let mut pos = pos + 1;
+    while pos.count_zeros() != pos.leading_zeros() {
+        let most_significant_bit = 1 << (63 - pos.leading_zeros());
+        pos -= most_significant_bit - 1;
+    }
+    63 - pos.leading_zeros()
+}
+
+// Positions of the peaks from left to right
+fn get_peaks(mmr_size: u64) -> Vec<u64> {
+    let mut peaks = Vec::new();
+    if mmr_size == 0 {
+        return peaks;
+    }
+    // The leftmost peak is the highest perfect tree which fits in the range
+    let mut height = 0;
+    while height < 62 && (1u64 << (height + 2)) - 2 < mmr_size {
+        height += 1;
+    }
+    let mut pos = (1u64 << (height + 1)) - 2;
+    peaks.push(pos);
+    // Move to the right sibling, then descend to its left child until it fits in the range
+    while height > 0 {
+        pos += sibling_offset(height);
+        while pos >= mmr_size {
+            if height == 0 {
+                return peaks;
+            }
+            height -= 1;
+            pos -= parent_offset(height);
+        }
+        peaks.push(pos);
+    }
+    peaks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use std::collections::HashMap;
+
+    fn leaf(index: u64) -> Byte32 {
+        let mut bytes = [0u8; 32];
+        bytes[..8].copy_from_slice(&index.to_le_bytes());
+        bytes.pack()
+    }
+
+    fn build(leaves: u64) -> (HashMap<u64, Byte32>, u64) {
+        let mut store = HashMap::new();
+        let mut size = 0;
+        for index in 0..leaves {
+            let (start, batch) = {
+                let mut mmr = MMR::new(size, |pos| store.get(&pos).cloned());
+                assert_eq!(mmr.push(leaf(index)), Some(leaf_index_to_pos(index)));
+                size = mmr.size();
+                mmr.into_batch()
+            };
+            for (offset, elem) in batch.into_iter().enumerate() {
+                store.insert(start + offset as u64, elem);
+            }
+        }
+        (store, size)
+    }
+
+    #[test]
+    fn test_mmr_size() {
+        let sizes: Vec<u64> = (0..8).map(mmr_size_from_leaves).collect();
+        assert_eq!(sizes, vec![0, 1, 3, 4, 7, 8, 10, 11]);
+        let positions: Vec<u64> = (0..8).map(leaf_index_to_pos).collect();
+        assert_eq!(positions, vec![0, 1, 3, 4, 7, 8, 10, 11]);
+        assert_eq!(get_peaks(11), vec![6, 9, 10]);
+        assert_eq!(get_peaks(15), vec![14]);
+        assert!(get_peaks(0).is_empty());
+    }
+
+    #[test]
+    fn test_mmr_root() {
+        let (store, size) = build(3);
+        assert_eq!(size, 4);
+        let mmr = MMR::new(size, |pos| store.get(&pos).cloned());
+        let left = MergeByte32::merge(&leaf(0), &leaf(1));
+        assert_eq!(mmr.get_root(), Some(MergeByte32::merge(&leaf(2), &left)));
+    }
+
+    #[test]
+    fn test_mmr_proof() {
+        let (store, _) = build(20);
+        for leaves in 1..=20u64 {
+            let size = mmr_size_from_leaves(leaves);
+            let mmr = MMR::new(size, |pos| store.get(&pos).cloned());
+            let root = mmr.get_root().unwrap();
+            for index in 0..leaves {
+                let proof = mmr.gen_proof(index).unwrap();
+                assert!(proof.verify(&root, index, &leaf(index)));
+                assert!(!proof.verify(&root, index, &leaf(index + 1)));
+            }
+            assert!(mmr.gen_proof(leaves).is_none());
+        }
+    }
+
+    #[test]
+    fn test_mmr_malformed_proof() {
+        let (store, size) = build(11);
+        let mmr = MMR::new(size, |pos| store.get(&pos).cloned());
+        let root = mmr.get_root().unwrap();
+        let proof = mmr.gen_proof(4).unwrap();
+        let mut items = proof.items().to_vec();
+        items.push(leaf(0));
+        assert!(!MMRProof::new(size, items).verify(&root, 4, &leaf(4)));
+        let items = proof.items()[1..].to_vec();
+        assert!(!MMRProof::new(size, items).verify(&root, 4, &leaf(4)));
+    }
+}
@@ -1,7 +1,14 @@
+mod block_filter;
 mod difficulty;
 mod merkle_tree;
+mod mmr;
 
+pub use block_filter::{
+    block_filter_match_any, build_block_filter, calc_block_filter_hash, BLOCK_FILTER_M,
+    BLOCK_FILTER_P,
+};
 pub use difficulty::{
     compact_to_difficulty, compact_to_target, difficulty_to_compact, target_to_compact, DIFF_TWO,
 };
-pub use merkle_tree::{merkle_root, MergeByte32, CBMT};
+pub use merkle_tree::{merkle_root, MergeByte32, MerkleProof, CBMT};
+pub use mmr::{leaf_index_to_pos, mmr_size_from_leaves, MMRProof, MMR};
@@ -1,9 +1,106 @@
+use crate::metrics::VERIFY_CACHE_LOOKUPS;
 use ckb_types::{
     core::{Capacity, Cycle},
     packed::Byte32,
+    prelude::*,
 };
+use lru_cache::LruCache;
+use std::collections::HashMap;
+use tokio::sync::RwLock;
 
-pub type TxVerifyCache = lru_cache::LruCache<Byte32, CacheEntry>;
+// Keys are spread over shards by the first byte of tx hash, so submission and
+// block verification rarely wait on the same lock.
+const SHARDS: usize = 16;
+
+type Shard = LruCache<Byte32, CacheEntry>;
+
+pub struct TxVerifyCache {
+    shards: Vec<RwLock<Shard>>,
+}
+
+impl TxVerifyCache {
+    pub fn new(capacity: usize) -> Self {
+        Self::from_entries(capacity, Vec::new())
+    }
+
+    /// Build the cache with entries ordered from least to most recently used
+    pub fn from_entries(capacity: usize, entries: Vec<(Byte32, CacheEntry)>) -> Self {
+        let shard_capacity = ((capacity + SHARDS - 1) / SHARDS).max(1);
+        let mut shards = (0..SHARDS)
+            .map(|_| Shard::new(shard_capacity))
+            .collect::<Vec<_>>();
+        for (tx_hash, entry) in entries {
+            shards[shard_index(&tx_hash)].insert(tx_hash, entry);
+        }
+        TxVerifyCache {
+            shards: shards.into_iter().map(RwLock::new).collect(),
+        }
+    }
+
+    fn shard(&self, tx_hash: &Byte32) -> &RwLock<Shard> {
+        &self.shards[shard_index(tx_hash)]
+    }
+
+    pub async fn fetch<I: IntoIterator<Item = Byte32>>(
+        &self,
+        keys: I,
+    ) -> HashMap<Byte32, CacheEntry> {
+        let mut ret = HashMap::new();
+        let mut misses = 0;
+        for tx_hash in keys {
+            let entry = self.shard(&tx_hash).read().await.get(&tx_hash).cloned();
+            match entry {
+                Some(entry) => {
+                    ret.insert(tx_hash, entry);
+                }
+                None => misses += 1,
+            }
+        }
+        // the hit rate is hit / (hit + miss) of the counters
+        VERIFY_CACHE_LOOKUPS
+            .with_label_values(&["hit"])
+            .inc_by(ret.len() as u64);
+        VERIFY_CACHE_LOOKUPS
+            .with_label_values(&["miss"])
+            .inc_by(misses);
+        ret
+    }
+
+    pub async fn insert_batch<I: IntoIterator<Item = (Byte32, CacheEntry)>>(&self, entries: I) {
+        for (tx_hash, entry) in entries {
+            self.shard(&tx_hash).write().await.insert(tx_hash, entry);
+        }
+    }
+
+    /// Inserts the entries which are not cached yet, e.g. the persisted entries loaded after the
+    /// cache is in use, which must not override the entries cached since.
+    pub async fn warm_up<I: IntoIterator<Item = (Byte32, CacheEntry)>>(&self, entries: I) {
+        for (tx_hash, entry) in entries {
+            let mut shard = self.shard(&tx_hash).write().await;
+            if !shard.contains_key(&tx_hash) {
+                shard.insert(tx_hash, entry);
+            }
+        }
+    }
+
+    /// All entries, ordered from least to most recently used within each shard
+    pub async fn entries(&self) -> Vec<(Byte32, CacheEntry)> {
+        let mut ret = Vec::new();
+        for shard in &self.shards {
+            let guard = shard.read().await;
+            ret.extend(
+                guard
+                    .iter()
+                    .map(|(tx_hash, entry)| (tx_hash.clone(), *entry)),
+            );
+        }
+        ret
+    }
+}
+
+fn shard_index(tx_hash: &Byte32) -> usize {
+    tx_hash.as_slice()[0] as usize % SHARDS
+}
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct CacheEntry {
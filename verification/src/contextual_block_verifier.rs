@@ -1,6 +1,10 @@
 use crate::cache::{CacheEntry, TxVerifyCache};
 use crate::error::{BlockTransactionsError, EpochError};
+use crate::metrics::{measure_verify_phase, VerifyOrigin, VerifyPhase};
+use crate::script_executor::{verify_scripts, ScriptsFailure};
+use crate::transaction_verifier::FeeCalculator;
 use crate::uncles_verifier::{UncleProvider, UnclesVerifier};
+use crate::verify_pool::VerifyPool;
 use crate::{
     BlockErrorKind, CellbaseError, CommitError, ContextualTransactionVerifier, TransactionVerifier,
     UnknownParentError,
@@ -11,7 +15,8 @@ use ckb_dao::DaoCalculator;
 use ckb_error::Error;
 use ckb_logger::error_target;
 use ckb_reward_calculator::RewardCalculator;
-use ckb_store::ChainStore;
+use ckb_script::{TracedTransactions, TransactionScriptsVerifier};
+use ckb_store::{data_loader_wrapper::DataLoaderWrapper, ChainStore};
 use ckb_traits::BlockMedianTimeContext;
 use ckb_types::{
     core::error::OutPointError,
@@ -23,14 +28,14 @@ use ckb_types::{
     packed::{Byte32, CellOutput, Script},
     prelude::*,
 };
-use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use tokio::sync::RwLock;
 
 pub struct VerifyContext<'a, CS> {
     pub(crate) store: &'a CS,
     pub(crate) consensus: &'a Consensus,
+    pub(crate) traced_transactions: TracedTransactions,
 }
 
 pub trait Switch {
@@ -39,11 +44,24 @@ pub trait Switch {
     fn disable_two_phase_commit(&self) -> bool;
     fn disable_daoheader(&self) -> bool;
     fn disable_reward(&self) -> bool;
+    /// Skips the scripts of the transactions, which are verified later, e.g. the blocks assumed
+    /// valid by the sync
+    fn disable_script(&self) -> bool;
 }
 
 impl<'a, CS: ChainStore<'a>> VerifyContext<'a, CS> {
     pub fn new(store: &'a CS, consensus: &'a Consensus) -> Self {
-        VerifyContext { store, consensus }
+        VerifyContext {
+            store,
+            consensus,
+            traced_transactions: TracedTransactions::default(),
+        }
+    }
+
+    // Log the syscalls of these transactions while verifying their scripts
+    pub fn traced_transactions(mut self, traced_transactions: TracedTransactions) -> Self {
+        self.traced_transactions = traced_transactions;
+        self
     }
 
     fn finalize_block_reward(&self, parent: &HeaderView) -> Result<(Script, BlockReward), Error> {
@@ -359,17 +377,13 @@ impl<'a, CS: ChainStore<'a>> BlockTxsVerifier<'a, CS> {
 
     fn fetched_cache<K: IntoIterator<Item = Byte32> + Send + 'static>(
         &self,
-        txs_verify_cache: Arc<RwLock<TxVerifyCache>>,
+        txs_verify_cache: Arc<TxVerifyCache>,
         keys: K,
         handle: &Handle,
     ) -> HashMap<Byte32, CacheEntry> {
         let (sender, receiver) = crossbeam_channel::bounded(1);
         handle.spawn(async move {
-            let guard = txs_verify_cache.read().await;
-            let ret = keys
-                .into_iter()
-                .filter_map(|hash| guard.get(&hash).cloned().map(|value| (hash, value)))
-                .collect();
+            let ret = txs_verify_cache.fetch(keys).await;
 
             if let Err(e) = sender.send(ret) {
                 error_target!(crate::LOG_TARGET, "TxsVerifier fetched_cache error {:?}", e);
@@ -380,8 +394,10 @@ impl<'a, CS: ChainStore<'a>> BlockTxsVerifier<'a, CS> {
 
     pub fn verify(
         &self,
-        txs_verify_cache: Arc<RwLock<TxVerifyCache>>,
+        txs_verify_cache: Arc<TxVerifyCache>,
+        verify_pool: &VerifyPool,
         handle: &Handle,
+        skip_script: bool,
     ) -> Result<(Cycle, Vec<CacheEntry>), Error> {
         let keys: Vec<Byte32> = self
             .resolved
@@ -390,53 +406,151 @@ impl<'a, CS: ChainStore<'a>> BlockTxsVerifier<'a, CS> {
             .collect();
         let fetched_cache = self.fetched_cache(Arc::clone(&txs_verify_cache), keys, handle);
 
-        // make verifiers orthogonal
-        let ret = self
-            .resolved
-            .par_iter()
+        // make verifiers orthogonal, the checks before the scripts come first
+        let checked: Vec<Result<Option<CacheEntry>, Error>> = verify_pool.install(|| {
+            self.resolved
+                .par_iter()
+                .map(|tx| {
+                    if let Some(cache_entry) = fetched_cache.get(&tx.transaction.hash()) {
+                        ContextualTransactionVerifier::new(
+                            &tx,
+                            self.context,
+                            self.block_number,
+                            self.epoch_number_with_fraction,
+                            self.parent_hash.clone(),
+                            self.context.consensus,
+                        )
+                        .verify()
+                        .map(|_| Some(*cache_entry))
+                    } else {
+                        TransactionVerifier::new(
+                            &tx,
+                            self.context,
+                            self.block_number,
+                            self.epoch_number_with_fraction,
+                            self.parent_hash.clone(),
+                            self.context.consensus,
+                            self.context.store,
+                        )
+                        .verify_without_scripts()
+                        .map(|_| None)
+                    }
+                })
+                .collect()
+        });
+        // only the transactions before the first failed one matter
+        let checked_len = checked
+            .iter()
+            .position(Result::is_err)
+            .unwrap_or_else(|| checked.len());
+
+        // then the scripts of the uncached transactions, the script groups of all of them are
+        // scheduled together
+        let uncached: Vec<usize> = checked[..checked_len]
+            .iter()
             .enumerate()
-            .map(|(index, tx)| {
-                let tx_hash = tx.transaction.hash();
-                if let Some(cache_entry) = fetched_cache.get(&tx_hash) {
-                    ContextualTransactionVerifier::new(
-                        &tx,
-                        self.context,
-                        self.block_number,
-                        self.epoch_number_with_fraction,
-                        self.parent_hash.clone(),
-                        self.context.consensus,
-                    )
-                    .verify()
-                    .map_err(|error| {
-                        BlockTransactionsError {
-                            index: index as u32,
-                            error,
-                        }
-                        .into()
+            .filter_map(|(index, ret)| match ret {
+                Ok(None) => Some(index),
+                _ => None,
+            })
+            .collect();
+        // the cached transactions take their cycles out of the block first
+        let cached_cycles: Cycle = checked[..checked_len]
+            .iter()
+            .filter_map(|ret| match ret {
+                Ok(Some(cache_entry)) => Some(cache_entry.cycles),
+                _ => None,
+            })
+            .sum();
+        let scripts_ret = if skip_script {
+            // the cycles are unknown until the scripts are verified
+            Ok(vec![0; uncached.len()])
+        } else {
+            let data_loader = DataLoaderWrapper::new(self.context.store);
+            let vm_version = self
+                .context
+                .consensus
+                .vm_version(self.epoch_number_with_fraction.number());
+            let scripts_verifiers: Vec<_> = verify_pool.install(|| {
+                uncached
+                    .par_iter()
+                    .map(|index| {
+                        let mut verifier =
+                            TransactionScriptsVerifier::new(&self.resolved[*index], &data_loader);
+                        verifier.set_vm_version(vm_version);
+                        verifier.set_trace_syscalls(
+                            self.context
+                                .traced_transactions
+                                .contains(&self.resolved[*index].transaction.hash()),
+                        );
+                        verifier
                     })
-                    .map(|_| (tx_hash, *cache_entry))
-                } else {
-                    TransactionVerifier::new(
-                        &tx,
-                        self.context,
-                        self.block_number,
-                        self.epoch_number_with_fraction,
-                        self.parent_hash.clone(),
+                    .collect()
+            });
+            measure_verify_phase(
+                VerifyOrigin::Block,
+                VerifyPhase::Script,
+                scripts_verifiers.len(),
+                || {
+                    verify_scripts(
+                        verify_pool,
+                        &scripts_verifiers,
+                        self.context.consensus.max_block_cycles(),
+                        self.context
+                            .consensus
+                            .max_block_cycles()
+                            .saturating_sub(cached_cycles),
+                    )
+                },
+            )
+        };
+        let (cycles, scripts_error) = match scripts_ret {
+            Ok(cycles) => (cycles, None),
+            // the transactions before the failed one are still checked for their fees
+            Err(ScriptsFailure::Transaction(position, error)) => (vec![0; position], Some(error)),
+            Err(ScriptsFailure::ExceededBlockCycles) => {
+                return Err(BlockErrorKind::ExceededMaximumCycles.into());
+            }
+        };
+
+        // and the fees last
+        let fees: Vec<Result<Capacity, Error>> = verify_pool.install(|| {
+            uncached[..cycles.len()]
+                .par_iter()
+                .map(|index| {
+                    FeeCalculator::new(
+                        &self.resolved[*index],
                         self.context.consensus,
                         self.context.store,
                     )
-                    .verify(self.context.consensus.max_block_cycles())
-                    .map_err(|error| {
-                        BlockTransactionsError {
-                            index: index as u32,
-                            error,
-                        }
-                        .into()
-                    })
-                    .map(|cache_entry| (tx_hash, cache_entry))
-                }
-            })
-            .collect::<Result<Vec<(Byte32, CacheEntry)>, Error>>()?;
+                    .transaction_fee()
+                })
+                .collect()
+        });
+        let mut uncached_entries = cycles.into_iter().zip(fees);
+
+        // the error of the first failed transaction, the same as verifying them one by one
+        let mut ret = Vec::with_capacity(checked_len);
+        for (index, checked) in checked.into_iter().enumerate() {
+            let to_block_error = |error| {
+                Error::from(BlockTransactionsError {
+                    index: index as u32,
+                    error,
+                })
+            };
+            let cache_entry = match checked.map_err(to_block_error)? {
+                Some(cache_entry) => cache_entry,
+                None => match uncached_entries.next() {
+                    Some((cycles, fee)) => CacheEntry::new(cycles, fee.map_err(to_block_error)?),
+                    None => {
+                        return Err(to_block_error(
+                            scripts_error.expect("the scripts failed at this transaction"),
+                        ));
+                    }
+                },
+            };
+            ret.push((self.resolved[index].transaction.hash(), cache_entry));
+        }
 
         let sum: Cycle = ret.iter().map(|(_, cache_entry)| cache_entry.cycles).sum();
         let cache_entires = ret
@@ -444,12 +558,12 @@ impl<'a, CS: ChainStore<'a>> BlockTxsVerifier<'a, CS> {
             .map(|(_, cache_entry)| cache_entry)
             .cloned()
             .collect();
-        handle.spawn(async move {
-            let mut guard = txs_verify_cache.write().await;
-            for (k, v) in ret {
-                guard.insert(k, v);
-            }
-        });
+        // the entries without the verified scripts must not be taken by the tx pool
+        if !skip_script {
+            handle.spawn(async move {
+                txs_verify_cache.insert_batch(ret).await;
+            });
+        }
 
         if sum > self.context.consensus.max_block_cycles() {
             Err(BlockErrorKind::ExceededMaximumCycles.into())
@@ -521,7 +635,8 @@ impl<'a, CS: ChainStore<'a>> ContextualBlockVerifier<'a, CS> {
         &'a self,
         resolved: &'a [ResolvedTransaction],
         block: &'a BlockView,
-        txs_verify_cache: Arc<RwLock<TxVerifyCache>>,
+        txs_verify_cache: Arc<TxVerifyCache>,
+        verify_pool: &VerifyPool,
         handle: &Handle,
         switch: SW,
     ) -> Result<(Cycle, Vec<CacheEntry>), Error> {
@@ -568,6 +683,11 @@ impl<'a, CS: ChainStore<'a>> ContextualBlockVerifier<'a, CS> {
             parent_hash,
             resolved,
         )
-        .verify(txs_verify_cache, handle)
+        .verify(
+            txs_verify_cache,
+            verify_pool,
+            handle,
+            switch.disable_script(),
+        )
     }
 }
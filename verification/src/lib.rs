@@ -8,8 +8,11 @@ mod convert;
 mod error;
 mod genesis_verifier;
 mod header_verifier;
+mod metrics;
+mod script_executor;
 mod transaction_verifier;
 mod uncles_verifier;
+mod verify_pool;
 
 #[cfg(test)]
 mod tests;
@@ -23,9 +26,12 @@ pub use crate::error::{
 };
 pub use crate::genesis_verifier::GenesisVerifier;
 pub use crate::header_verifier::{HeaderResolver, HeaderVerifier};
+pub use crate::metrics::{measure_verify_phase, VerifyOrigin, VerifyPhase};
 pub use crate::transaction_verifier::{
-    ContextualTransactionVerifier, ScriptVerifier, Since, SinceMetric, TransactionVerifier,
+    ContextualTransactionVerifier, NonContextualTransactionVerifier, ScriptVerifier, Since,
+    SinceMetric, TransactionVerifier,
 };
+pub use crate::verify_pool::{new_verify_pool, VerifyPool};
 
 pub const ALLOWED_FUTURE_BLOCKTIME: u64 = 15 * 1000; // 15 Second
 
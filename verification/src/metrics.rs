@@ -0,0 +1,103 @@
+use ckb_metrics::{
+    register_counter_vec, register_histogram_vec, Counter, Histogram, MetricVec, Subsystem,
+};
+use lazy_static::lazy_static;
+use std::time::Instant;
+
+lazy_static! {
+    pub(crate) static ref VERIFY_CACHE_LOOKUPS: MetricVec<Counter> = register_counter_vec(
+        Subsystem::TxPool,
+        "ckb_tx_verify_cache_lookups_total",
+        "Transactions looked up in the tx verify cache, by hit or miss",
+        &["result"],
+    );
+    static ref VERIFY_PHASE_DURATION: MetricVec<Histogram> = register_histogram_vec(
+        Subsystem::TxPool,
+        "ckb_verify_phase_duration_seconds",
+        "Time of a verification phase of a batch of transactions, by origin and phase",
+        &["origin", "phase"],
+    );
+    static ref VERIFY_PHASE_TXS: MetricVec<Counter> = register_counter_vec(
+        Subsystem::TxPool,
+        "ckb_verify_phase_transactions_total",
+        "Transactions passed through a verification phase, by origin and phase",
+        &["origin", "phase"],
+    );
+}
+
+/// Where a transaction being verified comes from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VerifyOrigin {
+    /// Submitted through the `send_transaction` RPC
+    RpcSubmit,
+    /// Relayed by a peer
+    Relay,
+    /// Committed in a block
+    Block,
+    /// Re-added to the tx-pool after a chain reorganization
+    Reorg,
+    /// Reloaded into the tx-pool from the file persisted on the last shutdown
+    Persisted,
+    /// Submitted again once the missing parents of an orphan arrived
+    Orphan,
+    /// Verified by `dry_run_txs` without entering the tx-pool
+    DryRun,
+}
+
+impl VerifyOrigin {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            VerifyOrigin::RpcSubmit => "rpc_submit",
+            VerifyOrigin::Relay => "relay",
+            VerifyOrigin::Block => "block",
+            VerifyOrigin::Reorg => "reorg",
+            VerifyOrigin::Persisted => "persisted",
+            VerifyOrigin::Orphan => "orphan",
+            VerifyOrigin::DryRun => "dry_run",
+        }
+    }
+}
+
+/// Phases of the transaction verification pipeline.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VerifyPhase {
+    /// Resolving inputs and cell deps
+    Resolve,
+    /// Checks on the transaction itself
+    NonContextual,
+    /// Checks against the chain state, e.g. maturity, capacity and since
+    Contextual,
+    /// Executing lock and type scripts
+    Script,
+}
+
+impl VerifyPhase {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            VerifyPhase::Resolve => "resolve",
+            VerifyPhase::NonContextual => "non_contextual",
+            VerifyPhase::Contextual => "contextual",
+            VerifyPhase::Script => "script",
+        }
+    }
+}
+
+/// Run `f` and observe how long it took in `ckb_verify_phase_duration_seconds`, and count the
+/// transactions it verified in `ckb_verify_phase_transactions_total`, by origin and phase.
+pub fn measure_verify_phase<T, F: FnOnce() -> T>(
+    origin: VerifyOrigin,
+    phase: VerifyPhase,
+    count: usize,
+    f: F,
+) -> T {
+    let started_at = Instant::now();
+    let ret = f();
+    let labels = [origin.as_str(), phase.as_str()];
+    VERIFY_PHASE_DURATION
+        .with_label_values(&labels)
+        .observe_duration(started_at.elapsed());
+    VERIFY_PHASE_TXS
+        .with_label_values(&labels)
+        .inc_by(count as u64);
+    ret
+}
@@ -0,0 +1,129 @@
+//! Runs the script groups of many transactions on the verify pool.
+//!
+//! The script groups of all the transactions are flattened into one list of tasks, which the
+//! work stealing rayon pool spreads across the workers, so a transaction with many groups no
+//! longer keeps a single worker busy while the others are idle. Once a group fails, the groups
+//! of the later transactions are skipped, while the groups of the earlier ones still run, so
+//! the reported error doesn't depend on the scheduling: it's always the error of the first
+//! failing transaction, and of its first failing group in execution order, the same one as
+//! verifying the transactions one by one. The cycles of the finished groups add up to a shared
+//! total, and all the remaining groups are skipped once it exceeds the cycles of the block.
+use crate::verify_pool::VerifyPool;
+use ckb_error::Error;
+use ckb_script::{DataLoader, ScriptError, TransactionScriptsVerifier};
+use ckb_types::core::Cycle;
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+
+/// Why the scripts of the transactions are rejected
+#[derive(Debug)]
+pub(crate) enum ScriptsFailure {
+    /// The index and the error of the first failing transaction
+    Transaction(usize, Error),
+    /// The groups run so far exceed the cycles left in the block
+    ExceededBlockCycles,
+}
+
+/// Runs the scripts of the transactions, returns the cycles of every transaction.
+/// `max_cycles` limits every transaction, and `max_total_cycles` all of them together.
+pub(crate) fn verify_scripts<DL: DataLoader + Sync>(
+    verify_pool: &VerifyPool,
+    verifiers: &[TransactionScriptsVerifier<DL>],
+    max_cycles: Cycle,
+    max_total_cycles: Cycle,
+) -> Result<Vec<Cycle>, ScriptsFailure> {
+    let tasks: Vec<_> = verifiers
+        .iter()
+        .enumerate()
+        .flat_map(|(tx_index, verifier)| {
+            verifier
+                .script_groups()
+                .into_iter()
+                .map(move |(group_type, index, group)| (tx_index, group_type, index, group))
+        })
+        .collect();
+
+    let first_failed = AtomicUsize::new(usize::max_value());
+    let total_cycles = AtomicU64::new(0);
+    let exceeded = AtomicBool::new(false);
+    let results: Vec<Option<Result<Cycle, Error>>> = verify_pool.install(|| {
+        tasks
+            .par_iter()
+            .map(|(tx_index, group_type, index, group)| {
+                if exceeded.load(Ordering::Acquire)
+                    || *tx_index > first_failed.load(Ordering::Acquire)
+                {
+                    return None;
+                }
+                let ret = verifiers[*tx_index]
+                    .verify_indexed_script_group(group_type, *index, group, max_cycles);
+                if let Ok(cycle) = ret.as_ref() {
+                    let total = total_cycles
+                        .fetch_add(*cycle, Ordering::AcqRel)
+                        .saturating_add(*cycle);
+                    if total > max_total_cycles {
+                        exceeded.store(true, Ordering::Release);
+                    }
+                } else {
+                    let mut current = first_failed.load(Ordering::Acquire);
+                    while *tx_index < current {
+                        match first_failed.compare_exchange_weak(
+                            current,
+                            *tx_index,
+                            Ordering::AcqRel,
+                            Ordering::Acquire,
+                        ) {
+                            Ok(_) => break,
+                            Err(actual) => current = actual,
+                        }
+                    }
+                }
+                Some(ret)
+            })
+            .collect()
+    });
+
+    aggregate(
+        tasks.iter().map(|(tx_index, ..)| *tx_index).zip(results),
+        verifiers.len(),
+        max_cycles,
+        max_total_cycles,
+    )
+}
+
+/// Sums up the results of the groups in execution order, tagged by their transaction indices,
+/// into the cycles of the `txs` transactions, stops at the first failing group, the first
+/// transaction exceeding `max_cycles` or once the total exceeds `max_total_cycles`. The skipped
+/// groups, `None`, follow a failure, or the total exceeding the limit.
+pub(crate) fn aggregate<I>(
+    groups: I,
+    txs: usize,
+    max_cycles: Cycle,
+    max_total_cycles: Cycle,
+) -> Result<Vec<Cycle>, ScriptsFailure>
+where
+    I: IntoIterator<Item = (usize, Option<Result<Cycle, Error>>)>,
+{
+    let mut cycles = vec![0; txs];
+    let mut total_cycles: Cycle = 0;
+    for (tx_index, ret) in groups {
+        let cycle = match ret {
+            Some(Ok(cycle)) => cycle,
+            Some(Err(error)) => return Err(ScriptsFailure::Transaction(tx_index, error)),
+            // the failed group comes first in execution order
+            None => return Err(ScriptsFailure::ExceededBlockCycles),
+        };
+        let current_cycles = cycles[tx_index]
+            .checked_add(cycle)
+            .filter(|current_cycles| *current_cycles <= max_cycles)
+            .ok_or_else(|| {
+                ScriptsFailure::Transaction(tx_index, ScriptError::ExceededMaximumCycles.into())
+            })?;
+        cycles[tx_index] = current_cycles;
+        total_cycles = total_cycles
+            .checked_add(cycle)
+            .filter(|total_cycles| *total_cycles <= max_total_cycles)
+            .ok_or(ScriptsFailure::ExceededBlockCycles)?;
+    }
+    Ok(cycles)
+}
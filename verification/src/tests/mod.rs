@@ -2,6 +2,7 @@ mod block_verifier;
 mod contextual_block_verifier;
 mod genesis_verifier;
 mod header_verifier;
+mod script_executor;
 mod transaction_verifier;
 mod two_phase_commit_verifier;
 #[cfg(not(disable_faketime))]
@@ -0,0 +1,183 @@
+use crate::new_verify_pool;
+use crate::script_executor::{aggregate, verify_scripts, ScriptsFailure};
+use ckb_error::{assert_error_eq, Error};
+use ckb_script::{DataLoader, ScriptError, TransactionScriptsVerifier};
+use ckb_test_chain_utils::always_success_cell;
+use ckb_types::{
+    bytes::Bytes,
+    core::{
+        capacity_bytes,
+        cell::{CellMeta, CellMetaBuilder, ResolvedTransaction},
+        BlockExt, Capacity, EpochExt, HeaderView, TransactionBuilder,
+    },
+    h256,
+    packed::{Byte32, CellDep, CellInput, CellOutput, OutPoint, Script},
+    prelude::*,
+    H256,
+};
+
+fn failed_transaction(failure: ScriptsFailure) -> (usize, Error) {
+    match failure {
+        ScriptsFailure::Transaction(index, error) => (index, error),
+        ScriptsFailure::ExceededBlockCycles => panic!("expect a failed transaction"),
+    }
+}
+
+#[test]
+fn test_aggregate_cycles() {
+    let groups = vec![(0, Some(Ok(1))), (0, Some(Ok(2))), (2, Some(Ok(3)))];
+    assert_eq!(aggregate(groups, 3, 10, 10).unwrap(), vec![3, 0, 3]);
+}
+
+#[test]
+fn test_aggregate_first_error() {
+    let groups = vec![
+        (0, Some(Ok(1))),
+        (1, Some(Err(ScriptError::ValidationFailure(1).into()))),
+        (1, Some(Err(ScriptError::ValidationFailure(2).into()))),
+        (2, Some(Err(ScriptError::ValidationFailure(3).into()))),
+        (3, None),
+    ];
+    let (index, error) = failed_transaction(aggregate(groups, 4, 10, 10).unwrap_err());
+    assert_eq!(index, 1);
+    assert_error_eq!(error, ScriptError::ValidationFailure(1));
+}
+
+#[test]
+fn test_aggregate_exceeded_maximum_cycles() {
+    // every transaction is limited on its own
+    let groups = vec![(0, Some(Ok(6))), (1, Some(Ok(6))), (1, Some(Ok(6)))];
+    let (index, error) = failed_transaction(aggregate(groups, 2, 10, 20).unwrap_err());
+    assert_eq!(index, 1);
+    assert_error_eq!(error, ScriptError::ExceededMaximumCycles);
+
+    let groups = vec![(0, Some(Ok(std::u64::MAX))), (0, Some(Ok(1)))];
+    let (index, error) =
+        failed_transaction(aggregate(groups, 1, std::u64::MAX, std::u64::MAX).unwrap_err());
+    assert_eq!(index, 0);
+    assert_error_eq!(error, ScriptError::ExceededMaximumCycles);
+}
+
+#[test]
+fn test_aggregate_exceeded_block_cycles() {
+    // and all of them together
+    let groups = vec![(0, Some(Ok(6))), (1, Some(Ok(6))), (2, Some(Ok(6)))];
+    match aggregate(groups, 3, 10, 15).unwrap_err() {
+        ScriptsFailure::ExceededBlockCycles => {}
+        failure => panic!("unexpected failure {:?}", failure),
+    }
+
+    // the groups skipped after the total is exceeded
+    let groups = vec![(0, Some(Ok(6))), (1, None), (2, Some(Ok(6)))];
+    match aggregate(groups, 3, 10, 15).unwrap_err() {
+        ScriptsFailure::ExceededBlockCycles => {}
+        failure => panic!("unexpected failure {:?}", failure),
+    }
+}
+
+struct MockDataLoader;
+
+impl DataLoader for MockDataLoader {
+    fn load_cell_data(&self, cell: &CellMeta) -> Option<(Bytes, Byte32)> {
+        cell.mem_cell_data.clone()
+    }
+    fn get_block_ext(&self, _block_hash: &Byte32) -> Option<BlockExt> {
+        None
+    }
+    fn get_block_epoch(&self, _block_hash: &Byte32) -> Option<EpochExt> {
+        None
+    }
+    fn get_header(&self, _block_hash: &Byte32) -> Option<HeaderView> {
+        None
+    }
+}
+
+// A transaction spending a cell locked by `lock`, the always success script is its only dep
+fn resolve_transaction(lock: Script, nonce: u8) -> ResolvedTransaction {
+    let (always_success_cell, always_success_data, _) = always_success_cell();
+    let always_success_out_point = OutPoint::new(h256!("0x11").pack(), 0);
+    let input = CellInput::new(OutPoint::new([nonce; 32].pack(), 0), 0);
+    let transaction = TransactionBuilder::default()
+        .input(input.clone())
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(always_success_out_point.clone())
+                .build(),
+        )
+        .build();
+    let input_cell = CellOutput::new_builder()
+        .capacity(capacity_bytes!(100).pack())
+        .lock(lock)
+        .build();
+    ResolvedTransaction {
+        transaction,
+        resolved_cell_deps: vec![CellMetaBuilder::from_cell_output(
+            always_success_cell.clone(),
+            always_success_data.clone(),
+        )
+        .out_point(always_success_out_point)
+        .build()],
+        resolved_inputs: vec![CellMetaBuilder::from_cell_output(input_cell, Bytes::new())
+            .out_point(input.previous_output())
+            .build()],
+        resolved_dep_groups: vec![],
+    }
+}
+
+#[test]
+fn test_verify_scripts_failing_middle_group() {
+    let (_, _, always_success_script) = always_success_cell();
+    // no dep provides the code
+    let missing_script = Script::new_builder()
+        .code_hash(h256!("0x22").pack())
+        .build();
+    let rtxs = vec![
+        resolve_transaction(always_success_script.clone(), 1),
+        resolve_transaction(missing_script, 2),
+        resolve_transaction(always_success_script.clone(), 3),
+    ];
+    let data_loader = MockDataLoader;
+    let verifiers: Vec<_> = rtxs
+        .iter()
+        .map(|rtx| TransactionScriptsVerifier::new(rtx, &data_loader))
+        .collect();
+    let verify_pool = new_verify_pool(2);
+
+    let (index, error) = failed_transaction(
+        verify_scripts(&verify_pool, &verifiers, 100_000_000, 100_000_000).unwrap_err(),
+    );
+    assert_eq!(index, 1);
+    assert_error_eq!(error, ScriptError::InvalidCodeHash);
+
+    // the same as the transactions verified one by one
+    assert!(verifiers[0].verify(100_000_000).is_ok());
+    assert_error_eq!(
+        verifiers[1].verify(100_000_000).unwrap_err(),
+        ScriptError::InvalidCodeHash
+    );
+}
+
+#[test]
+fn test_verify_scripts_exceeded_block_cycles() {
+    let (_, _, always_success_script) = always_success_cell();
+    let rtxs: Vec<_> = (1..=3)
+        .map(|nonce| resolve_transaction(always_success_script.clone(), nonce))
+        .collect();
+    let data_loader = MockDataLoader;
+    let verifiers: Vec<_> = rtxs
+        .iter()
+        .map(|rtx| TransactionScriptsVerifier::new(rtx, &data_loader))
+        .collect();
+    let verify_pool = new_verify_pool(2);
+    let cycles = verifiers[0].verify(100_000_000).unwrap();
+
+    assert_eq!(
+        verify_scripts(&verify_pool, &verifiers, cycles, cycles * 3).unwrap(),
+        vec![cycles; 3]
+    );
+    // every transaction fits in its own limit, but not all of them in the block
+    match verify_scripts(&verify_pool, &verifiers, cycles, cycles * 3 - 1).unwrap_err() {
+        ScriptsFailure::ExceededBlockCycles => {}
+        failure => panic!("unexpected failure {:?}", failure),
+    }
+}
@@ -1,9 +1,10 @@
 use super::super::transaction_verifier::{
-    CapacityVerifier, DuplicateDepsVerifier, EmptyVerifier, MaturityVerifier, OutputsDataVerifier,
-    Since, SinceVerifier, SizeVerifier, VersionVerifier,
+    CapacityVerifier, DuplicateDepsVerifier, EmptyVerifier, MaturityVerifier,
+    NonContextualTransactionVerifier, OutputsDataVerifier, Since, SinceFormatVerifier,
+    SinceVerifier, SizeVerifier, VersionVerifier,
 };
 use crate::TransactionError;
-use ckb_chain_spec::{build_genesis_type_id_script, OUTPUT_INDEX_DAO};
+use ckb_chain_spec::{build_genesis_type_id_script, consensus::ConsensusBuilder, OUTPUT_INDEX_DAO};
 use ckb_error::{assert_error_eq, Error};
 use ckb_test_chain_utils::MockMedianTime;
 use ckb_traits::BlockMedianTimeContext;
@@ -434,6 +435,26 @@ fn test_invalid_since_verify() {
     );
 }
 
+#[test]
+fn test_invalid_since_format() {
+    // use remain flags
+    let tx = create_tx_with_lock(0x0100_0000_0000_0001);
+    assert_error_eq!(
+        SinceFormatVerifier::new(&tx).verify().unwrap_err(),
+        TransactionError::InvalidSince,
+    );
+
+    // metric type flag 0b11 is reserved
+    let tx = create_tx_with_lock(0x6000_0000_0000_0001);
+    assert_error_eq!(
+        SinceFormatVerifier::new(&tx).verify().unwrap_err(),
+        TransactionError::InvalidSince,
+    );
+
+    let tx = create_tx_with_lock(0x2000_0000_0000_0001);
+    assert!(SinceFormatVerifier::new(&tx).verify().is_ok());
+}
+
 #[test]
 fn test_valid_zero_length_since() {
     // use remain flags
@@ -634,3 +655,39 @@ pub fn test_outputs_data_length_mismatch() {
 
     assert!(verifier.verify().is_ok());
 }
+
+#[test]
+pub fn test_non_contextual_verifier_rejects_before_resolving() {
+    let consensus = ConsensusBuilder::default().build();
+    let out_point = OutPoint::new(h256!("0x1").pack(), 0);
+    let cell_dep = CellDep::new_builder().out_point(out_point.clone()).build();
+    let output = CellOutput::new_builder()
+        .capacity(capacity_bytes!(50).pack())
+        .build();
+
+    let transaction = TransactionBuilder::default()
+        .input(CellInput::new(out_point.clone(), 0))
+        .output(output.clone())
+        .output_data(Bytes::new().pack())
+        .cell_deps(vec![cell_dep.clone(), cell_dep.clone()])
+        .build();
+    assert_error_eq!(
+        NonContextualTransactionVerifier::new(&transaction, &consensus)
+            .verify()
+            .unwrap_err(),
+        TransactionError::DuplicateDeps,
+    );
+
+    let transaction = TransactionBuilder::default()
+        .input(CellInput::new(out_point, 0x0100_0000_0000_0001))
+        .output(output)
+        .output_data(Bytes::new().pack())
+        .cell_dep(cell_dep)
+        .build();
+    assert_error_eq!(
+        NonContextualTransactionVerifier::new(&transaction, &consensus)
+            .verify()
+            .unwrap_err(),
+        TransactionError::InvalidSince,
+    );
+}
@@ -1,9 +1,10 @@
 use crate::cache::CacheEntry;
+use crate::metrics::{measure_verify_phase, VerifyOrigin, VerifyPhase};
 use crate::TransactionError;
-use ckb_chain_spec::consensus::Consensus;
+use ckb_chain_spec::consensus::{Consensus, VmVersion};
 use ckb_dao::DaoCalculator;
 use ckb_error::Error;
-use ckb_script::TransactionScriptsVerifier;
+use ckb_script::{ScriptError, ScriptGroupProfile, TransactionScriptsVerifier};
 use ckb_store::{data_loader_wrapper::DataLoaderWrapper, ChainStore};
 use ckb_traits::BlockMedianTimeContext;
 use ckb_types::{
@@ -18,10 +19,16 @@ use ckb_types::{
 use lru_cache::LruCache;
 use std::cell::RefCell;
 use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+// Cycles before the first clock check when the wall time budget is set, the checks are further
+// apart each time, see `TransactionScriptsVerifier::verify_with_checks`
+const WATCHDOG_CHUNK_CYCLES: Cycle = 1_000_000;
 
 pub struct ContextualTransactionVerifier<'a, M> {
     pub maturity: MaturityVerifier<'a>,
     pub since: SinceVerifier<'a, M>,
+    origin: VerifyOrigin,
 }
 
 impl<'a, M> ContextualTransactionVerifier<'a, M>
@@ -49,27 +56,72 @@ where
                 epoch_number_with_fraction,
                 parent_hash,
             ),
+            origin: VerifyOrigin::Block,
         }
     }
 
+    // Tag the verification metrics with where the transaction comes from
+    pub fn origin(mut self, origin: VerifyOrigin) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    // Log every syscall of the scripts, see `ckb_script::TracedTransactions`
+    pub fn trace_syscalls(mut self, trace_syscalls: bool) -> Self {
+        self.script = self.script.trace_syscalls(trace_syscalls);
+        self
+    }
+
     pub fn verify(&self) -> Result<(), Error> {
-        self.maturity.verify()?;
-        self.since.verify()?;
-        Ok(())
+        measure_verify_phase(self.origin, VerifyPhase::Contextual, 1, || {
+            self.maturity.verify()?;
+            self.since.verify()
+        })
     }
 }
 
-pub struct TransactionVerifier<'a, M, CS> {
+/// Checks which only need the transaction itself, cheap enough to run on every
+/// relayed transaction before resolving its cells or executing any script.
+pub struct NonContextualTransactionVerifier<'a> {
     pub version: VersionVerifier<'a>,
     pub size: SizeVerifier<'a>,
     pub empty: EmptyVerifier<'a>,
-    pub maturity: MaturityVerifier<'a>,
-    pub capacity: CapacityVerifier<'a>,
     pub duplicate_deps: DuplicateDepsVerifier<'a>,
     pub outputs_data_verifier: OutputsDataVerifier<'a>,
+    pub since_format: SinceFormatVerifier<'a>,
+}
+
+impl<'a> NonContextualTransactionVerifier<'a> {
+    pub fn new(tx: &'a TransactionView, consensus: &'a Consensus) -> Self {
+        NonContextualTransactionVerifier {
+            version: VersionVerifier::new(tx, consensus.tx_version()),
+            size: SizeVerifier::new(tx, consensus.max_block_bytes()),
+            empty: EmptyVerifier::new(tx),
+            duplicate_deps: DuplicateDepsVerifier::new(tx),
+            outputs_data_verifier: OutputsDataVerifier::new(tx),
+            since_format: SinceFormatVerifier::new(tx),
+        }
+    }
+
+    pub fn verify(&self) -> Result<(), Error> {
+        self.version.verify()?;
+        self.size.verify()?;
+        self.empty.verify()?;
+        self.duplicate_deps.verify()?;
+        self.outputs_data_verifier.verify()?;
+        self.since_format.verify()?;
+        Ok(())
+    }
+}
+
+pub struct TransactionVerifier<'a, M, CS> {
+    pub non_contextual: NonContextualTransactionVerifier<'a>,
+    pub maturity: MaturityVerifier<'a>,
+    pub capacity: CapacityVerifier<'a>,
     pub script: ScriptVerifier<'a, CS>,
     pub since: SinceVerifier<'a, M>,
     pub fee_calculator: FeeCalculator<'a, CS>,
+    origin: VerifyOrigin,
 }
 
 impl<'a, M, CS> TransactionVerifier<'a, M, CS>
@@ -88,17 +140,14 @@ where
         chain_store: &'a CS,
     ) -> Self {
         TransactionVerifier {
-            version: VersionVerifier::new(&rtx.transaction, consensus.tx_version()),
-            size: SizeVerifier::new(&rtx.transaction, consensus.max_block_bytes()),
-            empty: EmptyVerifier::new(&rtx.transaction),
+            non_contextual: NonContextualTransactionVerifier::new(&rtx.transaction, consensus),
             maturity: MaturityVerifier::new(
                 &rtx,
                 epoch_number_with_fraction,
                 consensus.cellbase_maturity(),
             ),
-            duplicate_deps: DuplicateDepsVerifier::new(&rtx.transaction),
-            outputs_data_verifier: OutputsDataVerifier::new(&rtx.transaction),
-            script: ScriptVerifier::new(rtx, chain_store),
+            script: ScriptVerifier::new(rtx, chain_store)
+                .vm_version(consensus.vm_version(epoch_number_with_fraction.number())),
             capacity: CapacityVerifier::new(rtx, consensus.dao_type_hash()),
             since: SinceVerifier::new(
                 rtx,
@@ -108,22 +157,47 @@ where
                 parent_hash,
             ),
             fee_calculator: FeeCalculator::new(rtx, &consensus, &chain_store),
+            origin: VerifyOrigin::Block,
         }
     }
 
+    // Tag the verification metrics with where the transaction comes from
+    pub fn origin(mut self, origin: VerifyOrigin) -> Self {
+        self.origin = origin;
+        self
+    }
+
     pub fn verify(&self, max_cycles: Cycle) -> Result<CacheEntry, Error> {
-        self.version.verify()?;
-        self.size.verify()?;
-        self.empty.verify()?;
-        self.maturity.verify()?;
-        self.capacity.verify()?;
-        self.duplicate_deps.verify()?;
-        self.outputs_data_verifier.verify()?;
-        self.since.verify()?;
-        let cycles = self.script.verify(max_cycles)?;
+        self.verify_with_time_limit(max_cycles, None)
+    }
+
+    // Same as verify, but scripts running longer than `time_limit` are
+    // aborted, which must never be used to verify blocks.
+    pub fn verify_with_time_limit(
+        &self,
+        max_cycles: Cycle,
+        time_limit: Option<Duration>,
+    ) -> Result<CacheEntry, Error> {
+        self.verify_without_scripts()?;
+        let cycles = measure_verify_phase(self.origin, VerifyPhase::Script, 1, || {
+            self.script.verify_with_time_limit(max_cycles, time_limit)
+        })?;
         let fee = self.fee_calculator.transaction_fee()?;
         Ok(CacheEntry::new(cycles, fee))
     }
+
+    // The checks of `verify` before running the scripts, block verification
+    // runs the scripts of all the transactions together afterwards.
+    pub(crate) fn verify_without_scripts(&self) -> Result<(), Error> {
+        measure_verify_phase(self.origin, VerifyPhase::NonContextual, 1, || {
+            self.non_contextual.verify()
+        })?;
+        measure_verify_phase(self.origin, VerifyPhase::Contextual, 1, || {
+            self.maturity.verify()?;
+            self.capacity.verify()?;
+            self.since.verify()
+        })
+    }
 }
 
 pub struct FeeCalculator<'a, CS> {
@@ -133,7 +207,7 @@ pub struct FeeCalculator<'a, CS> {
 }
 
 impl<'a, CS: ChainStore<'a>> FeeCalculator<'a, CS> {
-    fn new(
+    pub(crate) fn new(
         transaction: &'a ResolvedTransaction,
         consensus: &'a Consensus,
         chain_store: &'a CS,
@@ -145,7 +219,7 @@ impl<'a, CS: ChainStore<'a>> FeeCalculator<'a, CS> {
         }
     }
 
-    fn transaction_fee(&self) -> Result<Capacity, Error> {
+    pub(crate) fn transaction_fee(&self) -> Result<Capacity, Error> {
         // skip tx fee calculation for cellbase
         if self.transaction.is_cellbase() {
             Ok(Capacity::zero())
@@ -202,6 +276,8 @@ impl<'a> SizeVerifier<'a> {
 pub struct ScriptVerifier<'a, CS> {
     chain_store: &'a CS,
     resolved_transaction: &'a ResolvedTransaction,
+    vm_version: VmVersion,
+    trace_syscalls: bool,
 }
 
 impl<'a, CS: ChainStore<'a>> ScriptVerifier<'a, CS> {
@@ -209,12 +285,63 @@ impl<'a, CS: ChainStore<'a>> ScriptVerifier<'a, CS> {
         ScriptVerifier {
             chain_store,
             resolved_transaction,
+            vm_version: VmVersion::default(),
+            trace_syscalls: false,
         }
     }
 
+    pub fn vm_version(mut self, vm_version: VmVersion) -> Self {
+        self.vm_version = vm_version;
+        self
+    }
+
+    pub fn trace_syscalls(mut self, trace_syscalls: bool) -> Self {
+        self.trace_syscalls = trace_syscalls;
+        self
+    }
+
     pub fn verify(&self, max_cycles: Cycle) -> Result<Cycle, Error> {
         let data_loader = DataLoaderWrapper::new(self.chain_store);
-        TransactionScriptsVerifier::new(&self.resolved_transaction, &data_loader).verify(max_cycles)
+        self.scripts_verifier(&data_loader).verify(max_cycles)
+    }
+
+    pub fn verify_with_time_limit(
+        &self,
+        max_cycles: Cycle,
+        time_limit: Option<Duration>,
+    ) -> Result<Cycle, Error> {
+        let data_loader = DataLoaderWrapper::new(self.chain_store);
+        let verifier = self.scripts_verifier(&data_loader);
+        match time_limit {
+            None => verifier.verify(max_cycles),
+            // the default machine can't be suspended, so the watchdog restarts the script
+            // groups with growing cycle budgets to check the clock in between
+            Some(time_limit) => {
+                let started_at = Instant::now();
+                verifier.verify_with_checks(max_cycles, WATCHDOG_CHUNK_CYCLES, || {
+                    if started_at.elapsed() > time_limit {
+                        Err(ScriptError::ExceededMaximumTime.into())
+                    } else {
+                        Ok(())
+                    }
+                })
+            }
+        }
+    }
+
+    pub fn profile(&self, max_cycles: Cycle) -> Result<(Cycle, Vec<ScriptGroupProfile>), Error> {
+        let data_loader = DataLoaderWrapper::new(self.chain_store);
+        self.scripts_verifier(&data_loader).profile(max_cycles)
+    }
+
+    fn scripts_verifier<'b>(
+        &'b self,
+        data_loader: &'b DataLoaderWrapper<'a, CS>,
+    ) -> TransactionScriptsVerifier<'b, DataLoaderWrapper<'a, CS>> {
+        let mut verifier = TransactionScriptsVerifier::new(&self.resolved_transaction, data_loader);
+        verifier.set_vm_version(self.vm_version);
+        verifier.set_trace_syscalls(self.trace_syscalls);
+        verifier
     }
 }
 
@@ -573,6 +700,30 @@ where
     }
 }
 
+/// Rejects malformed `since` values without resolving the inputs, the lock
+/// itself is checked later by `SinceVerifier`.
+pub struct SinceFormatVerifier<'a> {
+    transaction: &'a TransactionView,
+}
+
+impl<'a> SinceFormatVerifier<'a> {
+    pub fn new(transaction: &'a TransactionView) -> Self {
+        SinceFormatVerifier { transaction }
+    }
+
+    pub fn verify(&self) -> Result<(), Error> {
+        let invalid = self.transaction.inputs().into_iter().any(|input| {
+            let since: u64 = input.since().unpack();
+            since != 0 && !Since(since).flags_is_valid()
+        });
+        if invalid {
+            Err(TransactionError::InvalidSince.into())
+        } else {
+            Ok(())
+        }
+    }
+}
+
 pub struct OutputsDataVerifier<'a> {
     transaction: &'a TransactionView,
 }
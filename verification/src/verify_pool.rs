@@ -0,0 +1,16 @@
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use std::sync::Arc;
+
+/// Dedicated thread pool used by script verification, shared between tx-pool and chain.
+pub type VerifyPool = Arc<ThreadPool>;
+
+/// Build the script verification pool with `workers` threads,
+/// `0` means one thread per logical CPU.
+pub fn new_verify_pool(workers: usize) -> VerifyPool {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(workers)
+        .thread_name(|index| format!("VerifyWorker-{}", index))
+        .build()
+        .expect("build verify thread pool");
+    Arc::new(pool)
+}